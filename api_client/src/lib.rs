@@ -2,9 +2,57 @@
 #![warn(rust_2018_idioms)]
 //! API client module for Google Photos.
 
-use reqwest::header::{AUTHORIZATION, CONTENT_TYPE};
+use reqwest::header::{HeaderMap, AUTHORIZATION, CONTENT_TYPE};
+use reqwest::StatusCode;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::path::Path;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+/// Files larger than this should use `upload_media_item_resumable`.
+pub const RESUMABLE_UPLOAD_THRESHOLD: u64 = 16 * 1024 * 1024;
+/// Chunk size for resumable uploads; Google requires a multiple of 256 KiB.
+const UPLOAD_CHUNK_SIZE: usize = 8 * 1024 * 1024;
+/// Maximum number of items accepted by a single `batchAddMediaItems` call.
+pub const BATCH_ADD_LIMIT: usize = 50;
+/// Maximum number of ids the API accepts in one `mediaItems:batchGet` call.
+pub const BATCH_GET_LIMIT: usize = 50;
+/// Where requests go unless `ApiClient::with_base_url` says otherwise.
+pub const API_BASE_URL: &str = "https://photoslibrary.googleapis.com";
+/// Requests per second this process sends to Google Photos, see `rate_limit`.
+pub const RATE_LIMIT_PER_SECOND: u32 = 10;
+
+/// MIME type for a file Google Photos accepts, based on its extension.
+pub fn mime_type_for_path(path: &Path) -> Option<&'static str> {
+    let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+    let mime = match ext.as_str() {
+        "jpg" | "jpeg" => "image/jpeg",
+        "png" => "image/png",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "heic" => "image/heic",
+        "heif" => "image/heif",
+        "avif" => "image/avif",
+        "bmp" => "image/bmp",
+        "tif" | "tiff" => "image/tiff",
+        "ico" => "image/x-icon",
+        "mp4" | "m4v" => "video/mp4",
+        "mov" | "qt" => "video/quicktime",
+        "avi" => "video/x-msvideo",
+        "mkv" => "video/x-matroska",
+        "webm" => "video/webm",
+        "3gp" => "video/3gpp",
+        "3g2" => "video/3gpp2",
+        "mpg" | "mpeg" => "video/mpeg",
+        "mts" | "m2ts" => "video/mp2t",
+        "wmv" => "video/x-ms-wmv",
+        _ => return None,
+    };
+    Some(mime)
+}
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -16,6 +64,9 @@ pub struct MediaItem {
     pub mime_type: String,
     pub media_metadata: MediaMetadata,
     pub filename: String,
+    /// Local favorite mark from the cache; the Library API does not expose it.
+    #[serde(skip)]
+    pub is_favorite: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -56,6 +107,19 @@ struct ListMediaItemsResponse {
     next_page_token: Option<String>,
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BatchGetMediaItemsResponse {
+    media_item_results: Option<Vec<MediaItemResult>>,
+}
+
+/// One entry of a batchGet response; `media_item` is missing when the id failed.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MediaItemResult {
+    media_item: Option<MediaItem>,
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct ListAlbumsResponse {
@@ -109,9 +173,96 @@ pub enum ApiClientError {
     Other(String),
 }
 
+/// Network usage of API requests.
+///
+/// Byte counts are request and response bodies as sent and read, so
+/// compressed responses count with their decompressed size.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransferStats {
+    pub bytes_down: u64,
+    pub bytes_up: u64,
+    pub requests: u64,
+    /// Requests that failed to send or got a non-success status
+    pub errors: u64,
+    /// Time spent waiting for requests, in milliseconds
+    pub duration_ms: u64,
+}
+
+impl TransferStats {
+    fn record(&mut self, up: u64, down: u64, ok: bool, duration_ms: u64) {
+        self.requests += 1;
+        self.bytes_up += up;
+        self.bytes_down += down;
+        self.errors += u64::from(!ok);
+        self.duration_ms += duration_ms;
+    }
+
+    /// Usage between an `earlier` snapshot and this one.
+    pub fn since(&self, earlier: &TransferStats) -> TransferStats {
+        TransferStats {
+            bytes_down: self.bytes_down.saturating_sub(earlier.bytes_down),
+            bytes_up: self.bytes_up.saturating_sub(earlier.bytes_up),
+            requests: self.requests.saturating_sub(earlier.requests),
+            errors: self.errors.saturating_sub(earlier.errors),
+            duration_ms: self.duration_ms.saturating_sub(earlier.duration_ms),
+        }
+    }
+}
+
+fn session() -> &'static Mutex<TransferStats> {
+    static SESSION: OnceLock<Mutex<TransferStats>> = OnceLock::new();
+    SESSION.get_or_init(Mutex::default)
+}
+
+/// Network usage of every `ApiClient` in this process so far.
+pub fn session_stats() -> TransferStats {
+    *session().lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn next_request_slot() -> &'static Mutex<Option<Instant>> {
+    static NEXT: OnceLock<Mutex<Option<Instant>>> = OnceLock::new();
+    NEXT.get_or_init(Mutex::default)
+}
+
+/// Wait for the next slot of the rate limit every `ApiClient` in this
+/// process shares. Downloads from base URLs outside a client should take a
+/// slot too, so bulk jobs do not trip Google's per-user limits.
+pub async fn rate_limit() {
+    let interval = Duration::from_secs(1) / RATE_LIMIT_PER_SECOND;
+    let wait = {
+        let mut next = next_request_slot().lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        let slot = next.map_or(now, |next| next.max(now));
+        *next = Some(slot + interval);
+        slot - now
+    };
+    if !wait.is_zero() {
+        tokio::time::sleep(wait).await;
+    }
+}
+
+/// A response with its body already read.
+struct ApiResponse {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: Vec<u8>,
+}
+
+impl ApiResponse {
+    fn json<T: DeserializeOwned>(&self) -> Result<T, ApiClientError> {
+        serde_json::from_slice(&self.body).map_err(|e| ApiClientError::RequestError(e.to_string()))
+    }
+
+    fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+}
+
 pub struct ApiClient {
     client: reqwest::Client,
     access_token: String,
+    base_url: String,
+    stats: Arc<Mutex<TransferStats>>,
 }
 
 impl ApiClient {
@@ -129,20 +280,86 @@ impl ApiClient {
                 video: None,
             },
             filename: format!("{}.jpg", id),
+            is_favorite: false,
         }
     }
 
     pub fn new(access_token: String) -> Self {
+        // Search and list responses are large JSON documents that compress well
+        let client = reqwest::Client::builder()
+            .gzip(true)
+            .brotli(true)
+            .deflate(true)
+            .build()
+            .unwrap_or_default();
         ApiClient {
-            client: reqwest::Client::new(),
+            client,
             access_token,
+            base_url: API_BASE_URL.to_string(),
+            stats: Arc::default(),
         }
     }
 
+    /// Send requests to `base_url` instead of the Photos Library API, e.g. a
+    /// local test server.
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
     pub fn set_access_token(&mut self, token: String) {
         self.access_token = token;
     }
 
+    /// Network usage of this client; see `session_stats` for all clients.
+    pub fn stats(&self) -> TransferStats {
+        *self.stats.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn record(&self, up: u64, down: u64, ok: bool, started: Instant) {
+        let duration_ms = started.elapsed().as_millis() as u64;
+        self.stats.lock().unwrap_or_else(|e| e.into_inner()).record(up, down, ok, duration_ms);
+        session().lock().unwrap_or_else(|e| e.into_inner()).record(up, down, ok, duration_ms);
+    }
+
+    /// Send `request` and read the whole response, counting it in the stats.
+    ///
+    /// Only failures to send or read are errors; any status is returned.
+    async fn send_raw(&self, request: reqwest::RequestBuilder) -> Result<ApiResponse, ApiClientError> {
+        let request = request.build().map_err(|e| ApiClientError::RequestError(e.to_string()))?;
+        rate_limit().await;
+        let up = request.body().and_then(|b| b.as_bytes()).map_or(0, |b| b.len() as u64);
+        let started = Instant::now();
+        let response = match self.client.execute(request).await {
+            Ok(response) => response,
+            Err(e) => {
+                self.record(up, 0, false, started);
+                return Err(ApiClientError::RequestError(e.to_string()));
+            }
+        };
+        let status = response.status();
+        let headers = response.headers().clone();
+        match response.bytes().await {
+            Ok(body) => {
+                self.record(up, body.len() as u64, status.is_success(), started);
+                Ok(ApiResponse { status, headers, body: body.to_vec() })
+            }
+            Err(e) => {
+                self.record(up, 0, false, started);
+                Err(ApiClientError::RequestError(e.to_string()))
+            }
+        }
+    }
+
+    /// Like `send_raw`, with a non-success status turned into `GoogleApiError`.
+    async fn send(&self, request: reqwest::RequestBuilder) -> Result<ApiResponse, ApiClientError> {
+        let response = self.send_raw(request).await?;
+        if !response.status.is_success() {
+            return Err(ApiClientError::GoogleApiError(response.text()));
+        }
+        Ok(response)
+    }
+
     #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self, page_token)))]
     pub async fn list_media_items(
         &self,
@@ -154,33 +371,21 @@ impl ApiClient {
             return Ok((items, None));
         }
         let mut url = format!(
-            "https://photoslibrary.googleapis.com/v1/mediaItems?pageSize={}",
+            "{}/v1/mediaItems?pageSize={}",
+            self.base_url,
             page_size
         );
         if let Some(token) = page_token {
             url.push_str(&format!("&pageToken={}", token));
         }
 
-        let response = self
+        let request = self
             .client
             .get(&url)
-            .header(AUTHORIZATION, format!("Bearer {}", self.access_token))
-            .send()
-            .await
-            .map_err(|e| ApiClientError::RequestError(e.to_string()))?;
+            .header(AUTHORIZATION, format!("Bearer {}", self.access_token));
+        let response = self.send(request).await?;
 
-        if !response.status().is_success() {
-            let error_text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-            return Err(ApiClientError::GoogleApiError(error_text));
-        }
-
-        let list_response = response
-            .json::<ListMediaItemsResponse>()
-            .await
-            .map_err(|e| ApiClientError::RequestError(e.to_string()))?;
+        let list_response = response.json::<ListMediaItemsResponse>()?;
 
         Ok((
             list_response.media_items.unwrap_or_default(),
@@ -207,33 +412,21 @@ impl ApiClient {
             return Ok((vec![album], None));
         }
         let mut url = format!(
-            "https://photoslibrary.googleapis.com/v1/albums?pageSize={}",
+            "{}/v1/albums?pageSize={}",
+            self.base_url,
             page_size
         );
         if let Some(token) = page_token {
             url.push_str(&format!("&pageToken={}", token));
         }
 
-        let response = self
+        let request = self
             .client
             .get(&url)
-            .header(AUTHORIZATION, format!("Bearer {}", self.access_token))
-            .send()
-            .await
-            .map_err(|e| ApiClientError::RequestError(e.to_string()))?;
+            .header(AUTHORIZATION, format!("Bearer {}", self.access_token));
+        let response = self.send(request).await?;
 
-        if !response.status().is_success() {
-            let error_text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-            return Err(ApiClientError::GoogleApiError(error_text));
-        }
-
-        let list_response = response
-            .json::<ListAlbumsResponse>()
-            .await
-            .map_err(|e| ApiClientError::RequestError(e.to_string()))?;
+        let list_response = response.json::<ListAlbumsResponse>()?;
 
         Ok((
             list_response.albums.unwrap_or_default(),
@@ -250,10 +443,13 @@ impl ApiClient {
         filters: Option<Value>,
     ) -> Result<(Vec<MediaItem>, Option<String>), ApiClientError> {
         if std::env::var("MOCK_API_CLIENT").is_ok() {
+            if std::env::var("MOCK_API_FAIL").is_ok() {
+                return Err(ApiClientError::RequestError("Mock request failed".into()));
+            }
             let items = vec![Self::mock_media_item("3")];
             return Ok((items, None));
         }
-        let url = "https://photoslibrary.googleapis.com/v1/mediaItems:search";
+        let url = format!("{}/v1/mediaItems:search", self.base_url);
 
         let request_body = SearchMediaItemsRequest {
             album_id,
@@ -262,28 +458,15 @@ impl ApiClient {
             filters,
         };
 
-        let response = self
+        let request = self
             .client
             .post(url)
             .header(AUTHORIZATION, format!("Bearer {}", self.access_token))
             .header(CONTENT_TYPE, "application/json")
-            .json(&request_body)
-            .send()
-            .await
-            .map_err(|e| ApiClientError::RequestError(e.to_string()))?;
+            .json(&request_body);
+        let response = self.send(request).await?;
 
-        if !response.status().is_success() {
-            let error_text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-            return Err(ApiClientError::GoogleApiError(error_text));
-        }
-
-        let search_response = response
-            .json::<ListMediaItemsResponse>()
-            .await
-            .map_err(|e| ApiClientError::RequestError(e.to_string()))?;
+        let search_response = response.json::<ListMediaItemsResponse>()?;
 
         Ok((
             search_response.media_items.unwrap_or_default(),
@@ -305,35 +488,22 @@ impl ApiClient {
                 cover_photo_media_item_id: None,
             });
         }
-        let url = "https://photoslibrary.googleapis.com/v1/albums";
+        let url = format!("{}/v1/albums", self.base_url);
         let body = CreateAlbumRequest {
             album: NewAlbum {
                 title: title.to_string(),
             },
         };
 
-        let response = self
+        let request = self
             .client
             .post(url)
             .header(AUTHORIZATION, format!("Bearer {}", self.access_token))
             .header(CONTENT_TYPE, "application/json")
-            .json(&body)
-            .send()
-            .await
-            .map_err(|e| ApiClientError::RequestError(e.to_string()))?;
-
-        if !response.status().is_success() {
-            let error_text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-            return Err(ApiClientError::GoogleApiError(error_text));
-        }
+            .json(&body);
+        let response = self.send(request).await?;
 
-        let album = response
-            .json::<Album>()
-            .await
-            .map_err(|e| ApiClientError::RequestError(e.to_string()))?;
+        let album = response.json::<Album>()?;
         Ok(album)
     }
 
@@ -353,33 +523,21 @@ impl ApiClient {
         }
 
         let url = format!(
-            "https://photoslibrary.googleapis.com/v1/albums/{}?updateMask=title",
+            "{}/v1/albums/{}?updateMask=title",
+            self.base_url,
             album_id
         );
         let body = serde_json::json!({ "title": title });
 
-        let response = self
+        let request = self
             .client
             .patch(&url)
             .header(AUTHORIZATION, format!("Bearer {}", self.access_token))
             .header(CONTENT_TYPE, "application/json")
-            .json(&body)
-            .send()
-            .await
-            .map_err(|e| ApiClientError::RequestError(e.to_string()))?;
-
-        if !response.status().is_success() {
-            let error_text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-            return Err(ApiClientError::GoogleApiError(error_text));
-        }
+            .json(&body);
+        let response = self.send(request).await?;
 
-        let album = response
-            .json::<Album>()
-            .await
-            .map_err(|e| ApiClientError::RequestError(e.to_string()))?;
+        let album = response.json::<Album>()?;
         Ok(album)
     }
 
@@ -390,22 +548,12 @@ impl ApiClient {
             return Ok(());
         }
 
-        let url = format!("https://photoslibrary.googleapis.com/v1/albums/{}", album_id);
-        let response = self
+        let url = format!("{}/v1/albums/{}", self.base_url, album_id);
+        let request = self
             .client
             .delete(&url)
-            .header(AUTHORIZATION, format!("Bearer {}", self.access_token))
-            .send()
-            .await
-            .map_err(|e| ApiClientError::RequestError(e.to_string()))?;
-
-        if !response.status().is_success() {
-            let error_text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-            return Err(ApiClientError::GoogleApiError(error_text));
-        }
+            .header(AUTHORIZATION, format!("Bearer {}", self.access_token));
+        self.send(request).await?;
         Ok(())
     }
 
@@ -431,27 +579,14 @@ impl ApiClient {
             return Ok(Some("2023-01-02T00:00:00Z".into()));
         }
 
-        let url = format!("https://photoslibrary.googleapis.com/v1/albums/{}", album_id);
-        let response = self
+        let url = format!("{}/v1/albums/{}", self.base_url, album_id);
+        let request = self
             .client
             .get(&url)
-            .header(AUTHORIZATION, format!("Bearer {}", self.access_token))
-            .send()
-            .await
-            .map_err(|e| ApiClientError::RequestError(e.to_string()))?;
-
-        if !response.status().is_success() {
-            let error_text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-            return Err(ApiClientError::GoogleApiError(error_text));
-        }
+            .header(AUTHORIZATION, format!("Bearer {}", self.access_token));
+        let response = self.send(request).await?;
 
-        let value: Value = response
-            .json()
-            .await
-            .map_err(|e| ApiClientError::RequestError(e.to_string()))?;
+        let value: Value = response.json()?;
 
         Ok(value
             .get("updateTime")
@@ -471,28 +606,19 @@ impl ApiClient {
         }
 
         let url = format!(
-            "https://photoslibrary.googleapis.com/v1/albums/{}:batchRemoveMediaItems",
+            "{}/v1/albums/{}:batchRemoveMediaItems",
+            self.base_url,
             album_id
         );
         let body = serde_json::json!({ "mediaItemIds": [media_item_id] });
 
-        let response = self
+        let request = self
             .client
             .post(&url)
             .header(AUTHORIZATION, format!("Bearer {}", self.access_token))
             .header(CONTENT_TYPE, "application/json")
-            .json(&body)
-            .send()
-            .await
-            .map_err(|e| ApiClientError::RequestError(e.to_string()))?;
-
-        if !response.status().is_success() {
-            let error_text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-            return Err(ApiClientError::GoogleApiError(error_text));
-        }
+            .json(&body);
+        self.send(request).await?;
 
         Ok(())
     }
@@ -511,33 +637,21 @@ impl ApiClient {
         }
 
         let url = format!(
-            "https://photoslibrary.googleapis.com/v1/mediaItems/{}?updateMask=description",
+            "{}/v1/mediaItems/{}?updateMask=description",
+            self.base_url,
             media_item_id
         );
         let body = serde_json::json!({ "description": description });
 
-        let response = self
+        let request = self
             .client
             .patch(&url)
             .header(AUTHORIZATION, format!("Bearer {}", self.access_token))
             .header(CONTENT_TYPE, "application/json")
-            .json(&body)
-            .send()
-            .await
-            .map_err(|e| ApiClientError::RequestError(e.to_string()))?;
-
-        if !response.status().is_success() {
-            let error_text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-            return Err(ApiClientError::GoogleApiError(error_text));
-        }
+            .json(&body);
+        let response = self.send(request).await?;
 
-        let item = response
-            .json::<MediaItem>()
-            .await
-            .map_err(|e| ApiClientError::RequestError(e.to_string()))?;
+        let item = response.json::<MediaItem>()?;
         Ok(item)
     }
 
@@ -554,21 +668,133 @@ impl ApiClient {
         }
 
         // Step 1: upload bytes and obtain upload token
-        let upload_token = self
+        let request = self
             .client
-            .post("https://photoslibrary.googleapis.com/v1/uploads")
+            .post(format!("{}/v1/uploads", self.base_url))
             .header(AUTHORIZATION, format!("Bearer {}", self.access_token))
             .header("X-Goog-Upload-File-Name", file_name)
             .header("X-Goog-Upload-Protocol", "raw")
-            .body(data.to_vec())
-            .send()
+            .body(data.to_vec());
+        let upload_token = self.send(request).await?.text();
+
+        self.create_media_item(&upload_token, file_name, description).await
+    }
+
+    /// Upload a file in chunks using Google's resumable upload protocol.
+    ///
+    /// Interrupted chunks are retried from the offset the server reports.
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
+    pub async fn upload_media_item_resumable(
+        &self,
+        path: &Path,
+        file_name: &str,
+        description: &str,
+        mime_type: &str,
+    ) -> Result<MediaItem, ApiClientError> {
+        if std::env::var("MOCK_API_CLIENT").is_ok() {
+            return Ok(Self::mock_media_item("uploaded"));
+        }
+
+        let mut file = tokio::fs::File::open(path)
             .await
-            .map_err(|e| ApiClientError::RequestError(e.to_string()))?
-            .text()
+            .map_err(|e| ApiClientError::Other(e.to_string()))?;
+        let size = file
+            .metadata()
             .await
-            .map_err(|e| ApiClientError::RequestError(e.to_string()))?;
+            .map_err(|e| ApiClientError::Other(e.to_string()))?
+            .len();
 
-        // Step 2: create the media item from the upload token
+        let request = self
+            .client
+            .post(format!("{}/v1/uploads", self.base_url))
+            .header(AUTHORIZATION, format!("Bearer {}", self.access_token))
+            .header("X-Goog-Upload-Command", "start")
+            .header("X-Goog-Upload-Content-Type", mime_type)
+            .header("X-Goog-Upload-File-Name", file_name)
+            .header("X-Goog-Upload-Protocol", "resumable")
+            .header("X-Goog-Upload-Raw-Size", size.to_string());
+        let start = self.send(request).await?;
+        let upload_url = start
+            .headers
+            .get("X-Goog-Upload-URL")
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.to_string())
+            .ok_or_else(|| ApiClientError::Other("No upload URL returned".into()))?;
+
+        let mut offset: u64 = 0;
+        let mut retries = 0;
+        let mut buf = vec![0u8; UPLOAD_CHUNK_SIZE];
+        let upload_token = loop {
+            file.seek(std::io::SeekFrom::Start(offset))
+                .await
+                .map_err(|e| ApiClientError::Other(e.to_string()))?;
+            let mut len = 0;
+            while len < buf.len() {
+                let n = file
+                    .read(&mut buf[len..])
+                    .await
+                    .map_err(|e| ApiClientError::Other(e.to_string()))?;
+                if n == 0 {
+                    break;
+                }
+                len += n;
+            }
+            let last = offset + len as u64 >= size;
+            let command = if last { "upload, finalize" } else { "upload" };
+            let request = self
+                .client
+                .post(&upload_url)
+                .header(AUTHORIZATION, format!("Bearer {}", self.access_token))
+                .header("X-Goog-Upload-Command", command)
+                .header("X-Goog-Upload-Offset", offset.to_string())
+                .body(buf[..len].to_vec());
+            match self.send_raw(request).await {
+                Ok(resp) if resp.status.is_success() => {
+                    retries = 0;
+                    if last {
+                        break resp.text();
+                    }
+                    offset += len as u64;
+                }
+                Ok(resp) if !resp.status.is_server_error() => {
+                    return Err(ApiClientError::GoogleApiError(resp.text()));
+                }
+                failed => {
+                    retries += 1;
+                    if retries > 3 {
+                        return Err(match failed {
+                            Ok(resp) => ApiClientError::GoogleApiError(resp.status.to_string()),
+                            Err(e) => e,
+                        });
+                    }
+                    offset = self.query_upload_offset(&upload_url).await?;
+                }
+            }
+        };
+
+        self.create_media_item(&upload_token, file_name, description).await
+    }
+
+    async fn query_upload_offset(&self, upload_url: &str) -> Result<u64, ApiClientError> {
+        let request = self
+            .client
+            .post(upload_url)
+            .header(AUTHORIZATION, format!("Bearer {}", self.access_token))
+            .header("X-Goog-Upload-Command", "query");
+        let resp = self.send_raw(request).await?;
+        resp.headers
+            .get("X-Goog-Upload-Size-Received")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse().ok())
+            .ok_or_else(|| ApiClientError::Other("Upload status unavailable".into()))
+    }
+
+    async fn create_media_item(
+        &self,
+        upload_token: &str,
+        file_name: &str,
+        description: &str,
+    ) -> Result<MediaItem, ApiClientError> {
         let body = serde_json::json!({
             "newMediaItems": [{
                 "description": description,
@@ -579,28 +805,15 @@ impl ApiClient {
             }]
         });
 
-        let response = self
+        let request = self
             .client
-            .post("https://photoslibrary.googleapis.com/v1/mediaItems:batchCreate")
+            .post(format!("{}/v1/mediaItems:batchCreate", self.base_url))
             .header(AUTHORIZATION, format!("Bearer {}", self.access_token))
             .header(CONTENT_TYPE, "application/json")
-            .json(&body)
-            .send()
-            .await
-            .map_err(|e| ApiClientError::RequestError(e.to_string()))?;
-
-        if !response.status().is_success() {
-            let error_text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-            return Err(ApiClientError::GoogleApiError(error_text));
-        }
+            .json(&body);
+        let response = self.send(request).await?;
 
-        let result = response
-            .json::<BatchCreateMediaItemsResponse>()
-            .await
-            .map_err(|e| ApiClientError::RequestError(e.to_string()))?;
+        let result = response.json::<BatchCreateMediaItemsResponse>()?;
 
         let media_item = result
             .new_media_item_results
@@ -611,6 +824,90 @@ impl ApiClient {
 
         Ok(media_item)
     }
+
+    /// Fetch media items by id, e.g. to renew expired base URLs.
+    ///
+    /// Requests at most `BATCH_GET_LIMIT` ids per call; ids the API cannot
+    /// resolve are left out of the result.
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self, media_item_ids)))]
+    pub async fn batch_get_media_items(&self, media_item_ids: &[String]) -> Result<Vec<MediaItem>, ApiClientError> {
+        if std::env::var("MOCK_API_CLIENT").is_ok() {
+            return Ok(media_item_ids.iter().map(|id| Self::mock_media_item(id)).collect());
+        }
+
+        let mut items = Vec::with_capacity(media_item_ids.len());
+        for chunk in media_item_ids.chunks(BATCH_GET_LIMIT) {
+            let query: Vec<(&str, &str)> = chunk.iter().map(|id| ("mediaItemIds", id.as_str())).collect();
+            let request = self
+                .client
+                .get(format!("{}/v1/mediaItems:batchGet", self.base_url))
+                .header(AUTHORIZATION, format!("Bearer {}", self.access_token))
+                .query(&query);
+            let response = self.send(request).await?;
+
+            let batch = response.json::<BatchGetMediaItemsResponse>()?;
+            items.extend(
+                batch
+                    .media_item_results
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter_map(|r| r.media_item),
+            );
+        }
+        Ok(items)
+    }
+
+    /// Add existing media items to an album, at most `BATCH_ADD_LIMIT` per call.
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self, media_item_ids)))]
+    pub async fn batch_add_media_items(
+        &self,
+        album_id: &str,
+        media_item_ids: &[String],
+    ) -> Result<(), ApiClientError> {
+        if std::env::var("MOCK_API_CLIENT").is_ok() {
+            return Ok(());
+        }
+
+        let url = format!(
+            "{}/v1/albums/{}:batchAddMediaItems",
+            self.base_url,
+            album_id
+        );
+        self.post_media_item_batches(&url, media_item_ids).await
+    }
+
+    /// Remove several media items from an album, at most `BATCH_ADD_LIMIT` per call.
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self, media_item_ids)))]
+    pub async fn batch_remove_media_items(
+        &self,
+        album_id: &str,
+        media_item_ids: &[String],
+    ) -> Result<(), ApiClientError> {
+        if std::env::var("MOCK_API_CLIENT").is_ok() {
+            return Ok(());
+        }
+
+        let url = format!(
+            "{}/v1/albums/{}:batchRemoveMediaItems",
+            self.base_url,
+            album_id
+        );
+        self.post_media_item_batches(&url, media_item_ids).await
+    }
+
+    async fn post_media_item_batches(&self, url: &str, media_item_ids: &[String]) -> Result<(), ApiClientError> {
+        for chunk in media_item_ids.chunks(BATCH_ADD_LIMIT) {
+            let body = serde_json::json!({ "mediaItemIds": chunk });
+            let request = self
+                .client
+                .post(url)
+                .header(AUTHORIZATION, format!("Bearer {}", self.access_token))
+                .header(CONTENT_TYPE, "application/json")
+                .json(&body);
+            self.send(request).await?;
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -643,6 +940,17 @@ mod tests {
         assert_eq!(parsed.next_page_token, Some("token123".to_string()));
     }
 
+    #[tokio::test]
+    #[serial]
+    async fn test_rate_limit_spaces_requests() {
+        let interval = Duration::from_secs(1) / RATE_LIMIT_PER_SECOND;
+        rate_limit().await;
+        let start = Instant::now();
+        rate_limit().await;
+        rate_limit().await;
+        assert!(start.elapsed() >= interval + interval / 2, "{:?}", start.elapsed());
+    }
+
     #[tokio::test]
     #[serial]
     async fn test_create_album_mock() {
@@ -663,6 +971,20 @@ mod tests {
         std::env::remove_var("MOCK_API_CLIENT");
     }
 
+    #[tokio::test]
+    #[serial]
+    async fn test_album_media_items_mock_failure() {
+        std::env::set_var("MOCK_API_CLIENT", "1");
+        std::env::set_var("MOCK_API_FAIL", "1");
+        let client = ApiClient::new("token".into());
+        let result = client.get_album_media_items("1", 100, None).await;
+        assert!(matches!(result, Err(ApiClientError::RequestError(_))));
+        std::env::remove_var("MOCK_API_FAIL");
+        let (items, _) = client.get_album_media_items("1", 100, None).await.unwrap();
+        assert_eq!(items[0].id, "3");
+        std::env::remove_var("MOCK_API_CLIENT");
+    }
+
     #[tokio::test]
     #[serial]
     async fn test_delete_album_mock() {
@@ -671,4 +993,57 @@ mod tests {
         client.delete_album("1").await.unwrap();
         std::env::remove_var("MOCK_API_CLIENT");
     }
+
+    #[test]
+    fn test_mime_type_for_path() {
+        assert_eq!(mime_type_for_path(Path::new("a/IMG_1.JPG")), Some("image/jpeg"));
+        assert_eq!(mime_type_for_path(Path::new("clip.mov")), Some("video/quicktime"));
+        assert_eq!(mime_type_for_path(Path::new("notes.txt")), None);
+        assert_eq!(mime_type_for_path(Path::new("noext")), None);
+    }
+
+    #[test]
+    fn test_parse_batch_get_response_skips_failed_ids() {
+        let json = r#"{
+            "mediaItemResults": [
+                {
+                    "mediaItem": {
+                        "id": "a",
+                        "productUrl": "http://example.com/a",
+                        "baseUrl": "http://example.com/a/base",
+                        "mimeType": "image/jpeg",
+                        "mediaMetadata": {
+                            "creationTime": "2023-01-01T00:00:00Z",
+                            "width": "1",
+                            "height": "1"
+                        },
+                        "filename": "a.jpg"
+                    }
+                },
+                { "status": { "code": 5, "message": "not found" } }
+            ]
+        }"#;
+
+        let parsed: BatchGetMediaItemsResponse = serde_json::from_str(json).unwrap();
+        let items: Vec<MediaItem> = parsed
+            .media_item_results
+            .unwrap()
+            .into_iter()
+            .filter_map(|r| r.media_item)
+            .collect();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].base_url, "http://example.com/a/base");
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_batch_add_media_items_mock() {
+        std::env::set_var("MOCK_API_CLIENT", "1");
+        let client = ApiClient::new("token".into());
+        client
+            .batch_add_media_items("1", &["a".to_string(), "b".to_string()])
+            .await
+            .unwrap();
+        std::env::remove_var("MOCK_API_CLIENT");
+    }
 }
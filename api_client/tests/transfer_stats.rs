@@ -0,0 +1,65 @@
+use api_client::{ApiClient, ApiClientError};
+use serial_test::serial;
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::sync::mpsc;
+
+/// Answer one request per entry of `responses` and pass each request's head on.
+fn serve(responses: Vec<(u16, &'static str)>) -> (String, mpsc::Receiver<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = format!("http://{}", listener.local_addr().unwrap());
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        for (status, body) in responses {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 4096];
+            while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                let n = stream.read(&mut buf).unwrap();
+                if n == 0 {
+                    break;
+                }
+                request.extend_from_slice(&buf[..n]);
+            }
+            let _ = tx.send(String::from_utf8_lossy(&request).into_owned());
+            let reply = format!(
+                "HTTP/1.1 {} X\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            );
+            stream.write_all(reply.as_bytes()).unwrap();
+        }
+    });
+    (addr, rx)
+}
+
+#[tokio::test]
+#[serial]
+async fn test_requests_accept_compression_and_count_transfers() {
+    std::env::remove_var("MOCK_API_CLIENT");
+    let albums = r#"{"albums":[{"id":"a1","title":"Beach"}]}"#;
+    let (addr, requests) = serve(vec![(200, albums), (500, "boom")]);
+    let client = ApiClient::new("token".into()).with_base_url(addr);
+    let before = api_client::session_stats();
+
+    let (list, _) = client.list_albums(50, None).await.unwrap();
+    assert_eq!(list[0].id, "a1");
+    let head = requests.recv().unwrap().to_ascii_lowercase();
+    let encoding = head.lines().find(|l| l.starts_with("accept-encoding:")).unwrap();
+    assert!(encoding.contains("gzip") && encoding.contains("br"), "{}", encoding);
+
+    let stats = client.stats();
+    assert_eq!(stats.requests, 1);
+    assert_eq!(stats.bytes_down, albums.len() as u64);
+    assert_eq!(stats.errors, 0);
+
+    let err = client.create_album("Trip").await.unwrap_err();
+    assert!(matches!(err, ApiClientError::GoogleApiError(ref text) if text == "boom"));
+    let stats = client.stats();
+    assert_eq!(stats.requests, 2);
+    assert_eq!(stats.errors, 1);
+    assert!(stats.bytes_up > 0);
+    assert_eq!(stats.bytes_down, (albums.len() + "boom".len()) as u64);
+    assert_eq!(api_client::session_stats().since(&before), stats);
+}
@@ -0,0 +1,31 @@
+use std::path::PathBuf;
+use std::process::Command;
+
+fn git(args: &[&str]) -> Option<String> {
+    Command::new("git")
+        .args(args)
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .and_then(|o| String::from_utf8(o.stdout).ok())
+        .map(|s| s.trim().to_string())
+}
+
+fn main() {
+    let git_hash = git(&["rev-parse", "--short", "HEAD"]).unwrap_or_else(|| "unknown".into());
+    println!("cargo:rustc-env=GIT_HASH={}", git_hash);
+
+    let git_dir = git(&["rev-parse", "--absolute-git-dir"])
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("../.git"));
+    // HEAD only changes on checkout; a commit moves the branch it points to,
+    // which lives in its own ref file or, once packed, in packed-refs
+    let mut watched = vec![git_dir.join("HEAD"), git_dir.join("packed-refs")];
+    if let Some(branch) = git(&["symbolic-ref", "-q", "HEAD"]) {
+        watched.push(git_dir.join(branch));
+    }
+    // A path that does not exist would rerun this script on every build
+    for path in watched.iter().filter(|p| p.exists()) {
+        println!("cargo:rerun-if-changed={}", path.display());
+    }
+}
@@ -1,11 +1,12 @@
 #![warn(clippy::all)]
 #![warn(rust_2018_idioms)]
-use cache::CacheManager;
-use clap::{Parser, Subcommand};
+use cache::{CacheManager, ImportMode, MediaQuery, MediaSort};
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
 use api_client::ApiClient;
-use auth::ensure_access_token_valid;
-use std::path::PathBuf;
-use sync::{SyncProgress, Syncer};
+use auth::{ensure_access_token_valid, AuthError, TokenStatus};
+use std::path::{Path, PathBuf};
+use sync::{SyncProgress, SyncTaskError, Syncer};
+use ui::{ImageLoader, PrefetchProgress, Variant};
 use tokio::sync::mpsc;
 use tracing_appender::rolling;
 use tracing_subscriber::fmt::writer::MakeWriterExt;
@@ -13,17 +14,27 @@ use tracing_subscriber::EnvFilter;
 use chrono::{DateTime, Utc, NaiveDate, TimeZone};
 
 #[path = "../config.rs"]
+#[allow(dead_code)]
 mod config;
+#[path = "../shutdown.rs"]
+mod shutdown;
 
 #[derive(Parser)]
 #[command(
     name = "sync_cli",
     author,
     version,
+    disable_version_flag = true,
     about = "GooglePicz synchronization CLI",
-    after_help = "EXAMPLES:\n  sync_cli export-faces --file faces.json\n  sync_cli import-faces --file faces.json\n  sync_cli set-favorite <ID> true\n  sync_cli upload-item path/to/file.jpg --description \"My photo\"\n  sync_cli update-description <ID> \"New description\""
+    after_help = "EXAMPLES:\n  sync_cli export-faces --file faces.json\n  sync_cli import-faces --file faces.json\n  sync_cli set-favorite <ID> true\n  sync_cli upload-item path/to/file.jpg --description \"My photo\"\n  sync_cli update-description <ID> \"New description\"\n  sync_cli auth status --json\n\nEXIT CODES:\n  3  no stored tokens\n  4  access token expired and no refresh token\n  5  keyring unavailable"
 )]
 struct Cli {
+    /// Print version information
+    #[arg(short = 'V', long)]
+    version: bool,
+    /// With --version, print build details as JSON
+    #[arg(long = "json", requires = "version")]
+    version_json: bool,
     /// Override log level (e.g. info, debug)
     #[arg(long)]
     log_level: Option<String>,
@@ -39,6 +50,9 @@ struct Cli {
     /// Path to config file
     #[arg(long)]
     config: Option<PathBuf>,
+    /// Use the settings, cache and tokens of this profile (defaults to GOOGLEPICZ_PROFILE)
+    #[arg(long)]
+    profile: Option<String>,
     /// Enable tokio console for debugging
     #[arg(long)]
     debug_console: bool,
@@ -52,10 +66,13 @@ struct Cli {
     #[arg(long)]
     use_file_store: bool,
     #[command(subcommand)]
-    command: Commands,
+    command: Option<Commands>,
 }
 
 fn parse_date(val: &str, end: bool) -> Option<DateTime<Utc>> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(val) {
+        return Some(dt.with_timezone(&Utc));
+    }
     NaiveDate::parse_from_str(val, "%Y-%m-%d")
         .ok()
         .and_then(|d| {
@@ -68,12 +85,809 @@ fn parse_date(val: &str, end: bool) -> Option<DateTime<Utc>> {
         })
 }
 
+#[derive(Clone, Copy, ValueEnum)]
+enum OutputFormat {
+    Json,
+    Csv,
+    Table,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum SortOrder {
+    Newest,
+    Oldest,
+    Filename,
+}
+
+impl From<SortOrder> for MediaSort {
+    fn from(s: SortOrder) -> Self {
+        match s {
+            SortOrder::Newest => MediaSort::NewestFirst,
+            SortOrder::Oldest => MediaSort::OldestFirst,
+            SortOrder::Filename => MediaSort::Filename,
+        }
+    }
+}
+
+fn csv_field(val: &str) -> String {
+    if val.contains([',', '"', '\n']) {
+        format!("\"{}\"", val.replace('"', "\"\""))
+    } else {
+        val.to_string()
+    }
+}
+
+/// `transfer` is the network usage of the last sync, if one completed.
+fn print_stats(
+    stats: &cache::LibraryStats,
+    cache_size: u64,
+    transfer: Option<api_client::TransferStats>,
+    output: OutputFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let fmt_date = |d: Option<chrono::DateTime<chrono::Utc>>| d.map(|d| d.to_rfc3339()).unwrap_or_default();
+    match output {
+        OutputFormat::Json => {
+            let mut value = serde_json::to_value(stats)?;
+            value["cache_size_bytes"] = cache_size.into();
+            value["last_sync_transfer"] = serde_json::to_value(transfer)?;
+            println!("{}", serde_json::to_string_pretty(&value)?);
+        }
+        OutputFormat::Csv => {
+            println!("metric,value");
+            println!("items,{}", stats.item_count);
+            println!("videos,{}", stats.video_count);
+            println!("favorites,{}", stats.favorite_count);
+            println!("earliest,{}", fmt_date(stats.earliest));
+            println!("latest,{}", fmt_date(stats.latest));
+            println!("albums,{}", stats.album_count);
+            println!("people,{}", stats.people_count);
+            println!("cache_size_bytes,{}", cache_size);
+            if let Some(t) = transfer {
+                println!("last_sync_bytes_down,{}", t.bytes_down);
+                println!("last_sync_bytes_up,{}", t.bytes_up);
+                println!("last_sync_requests,{}", t.requests);
+                println!("last_sync_errors,{}", t.errors);
+            }
+            for (model, count) in &stats.top_camera_models {
+                println!("camera:{},{}", csv_field(model), count);
+            }
+            for (mime, count) in &stats.top_mime_types {
+                println!("mime:{},{}", csv_field(mime), count);
+            }
+        }
+        OutputFormat::Table => {
+            println!("Items:      {}", stats.item_count);
+            println!("Videos:     {}", stats.video_count);
+            println!("Favorites:  {}", stats.favorite_count);
+            println!("Date range: {} - {}", fmt_date(stats.earliest), fmt_date(stats.latest));
+            println!("Albums:     {}", stats.album_count);
+            println!("People:     {}", stats.people_count);
+            println!("Cache size: {} bytes", cache_size);
+            if let Some(t) = transfer {
+                println!(
+                    "Last sync:  {} bytes down, {} bytes up, {} requests ({} failed)",
+                    t.bytes_down, t.bytes_up, t.requests, t.errors
+                );
+            }
+            if !stats.top_camera_models.is_empty() {
+                println!("Top camera models:");
+                for (model, count) in &stats.top_camera_models {
+                    println!("  {:<24} {}", model, count);
+                }
+            }
+            if !stats.top_mime_types.is_empty() {
+                println!("Top MIME types:");
+                for (mime, count) in &stats.top_mime_types {
+                    println!("  {:<24} {}", mime, count);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn print_year_histogram(years: &[(String, u64)], output: OutputFormat) -> Result<(), Box<dyn std::error::Error>> {
+    match output {
+        OutputFormat::Json => {
+            let map: serde_json::Map<String, serde_json::Value> =
+                years.iter().map(|(y, n)| (y.clone(), (*n).into())).collect();
+            println!("{}", serde_json::to_string_pretty(&map)?);
+        }
+        OutputFormat::Csv => {
+            println!("year,count");
+            for (year, count) in years {
+                println!("{},{}", year, count);
+            }
+        }
+        OutputFormat::Table => {
+            let max = years.iter().map(|(_, n)| *n).max().unwrap_or(0);
+            for (year, count) in years {
+                let width = (count * 40).checked_div(max).unwrap_or(0) as usize;
+                println!("{}  {:>6}  {}", year, count, "#".repeat(width.max(1)));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn print_items(items: &[api_client::MediaItem], output: OutputFormat) -> Result<(), Box<dyn std::error::Error>> {
+    match output {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(items)?),
+        OutputFormat::Csv => {
+            println!("id,filename,mime_type,creation_time,description");
+            for item in items {
+                println!(
+                    "{},{},{},{},{}",
+                    csv_field(&item.id),
+                    csv_field(&item.filename),
+                    csv_field(&item.mime_type),
+                    csv_field(&item.media_metadata.creation_time),
+                    csv_field(item.description.as_deref().unwrap_or(""))
+                );
+            }
+        }
+        OutputFormat::Table => {
+            let id_w = items.iter().map(|i| i.id.len()).max().unwrap_or(0).max(2);
+            let name_w = items.iter().map(|i| i.filename.len()).max().unwrap_or(0).max(8);
+            println!("{:<id_w$}  {:<name_w$}  {:<12}  CREATED", "ID", "FILENAME", "MIME");
+            for item in items {
+                println!(
+                    "{:<id_w$}  {:<name_w$}  {:<12}  {}",
+                    item.id, item.filename, item.mime_type, item.media_metadata.creation_time
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Exit code when no tokens are stored.
+const EXIT_NO_TOKENS: i32 = 3;
+/// Exit code when the access token expired and no refresh token is stored.
+const EXIT_NO_REFRESH_TOKEN: i32 = 4;
+/// Exit code when the keyring cannot be accessed.
+const EXIT_KEYRING_UNAVAILABLE: i32 = 5;
+
+#[derive(Subcommand)]
+enum AuthCommands {
+    /// Sign in and store tokens
+    Login {
+        /// Use the device flow instead of opening a browser
+        #[arg(long)]
+        device: bool,
+        /// Local port for the OAuth redirect
+        #[arg(long)]
+        port: Option<u16>,
+    },
+    /// Remove all stored tokens
+    Logout,
+    /// Show account, token expiry and storage backend
+    Status {
+        /// Print machine-readable JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+fn auth_error_exit(err: AuthError) -> ! {
+    eprintln!("Error: {}", err);
+    match err {
+        AuthError::Keyring(_) => std::process::exit(EXIT_KEYRING_UNAVAILABLE),
+        _ => std::process::exit(1),
+    }
+}
+
+fn print_token_status(status: &TokenStatus, json: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let expiry = status
+        .access_token_expiry
+        .and_then(|secs| DateTime::<Utc>::from_timestamp(secs as i64, 0))
+        .map(|dt| dt.to_rfc3339());
+    if json {
+        let value = serde_json::json!({
+            "account": status.account,
+            "has_access_token": status.has_access_token,
+            "has_refresh_token": status.has_refresh_token,
+            "access_token_expiry": expiry,
+            "access_token_expired": status.access_token_expired(),
+            "backend": status.backend.as_str(),
+        });
+        println!("{}", serde_json::to_string_pretty(&value)?);
+    } else {
+        println!("Account: {}", status.account.as_deref().unwrap_or("unknown"));
+        println!("Access token expiry: {}", expiry.as_deref().unwrap_or("none"));
+        println!("Refresh token: {}", if status.has_refresh_token { "present" } else { "missing" });
+        println!("Storage backend: {}", status.backend.as_str());
+    }
+    Ok(())
+}
+
+/// File name of the sync state inside an export.
+const SYNC_STATE_EXPORT: &str = "sync_state.json";
+
+/// Private directory for an archive's contents, removed when dropped.
+fn scratch_dir(kind: &str) -> std::io::Result<tempfile::TempDir> {
+    tempfile::Builder::new().prefix(&format!("googlepicz-{}-", kind)).tempdir()
+}
+
+fn export_state(cache: &CacheManager, db_path: &Path, dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    cache.export_state(dir, |name, count| println!("Exported {} ({} records)", name, count))?;
+    let state = db_path.with_extension("state.json");
+    if state.exists() {
+        std::fs::copy(&state, dir.join(SYNC_STATE_EXPORT))?;
+        println!("Exported {}", SYNC_STATE_EXPORT);
+    }
+    Ok(())
+}
+
+fn import_state(
+    cache: &CacheManager,
+    db_path: &Path,
+    dir: &Path,
+    mode: ImportMode,
+) -> Result<(), Box<dyn std::error::Error>> {
+    cache.import_state(dir, mode, |name, count| println!("Imported {} ({} records)", name, count))?;
+    let exported = dir.join(SYNC_STATE_EXPORT);
+    let state = db_path.with_extension("state.json");
+    if exported.exists() && (mode == ImportMode::Replace || !state.exists()) {
+        std::fs::copy(&exported, &state)?;
+        println!("Imported {}", SYNC_STATE_EXPORT);
+    }
+    Ok(())
+}
+
+/// Exit code when watch mode stops after repeated sync failures.
+const EXIT_SYNC_ABORTED: i32 = 6;
+/// Exit code when a setting in the config file cannot be used.
+const EXIT_INVALID_CONFIG: i32 = 7;
+
+fn print_progress(p: SyncProgress) {
+    match p {
+        SyncProgress::Started => println!("Sync started"),
+        SyncProgress::Retrying(wait) => println!("Retrying in {}s", wait),
+        SyncProgress::ItemSynced(n) => println!("Synced {} items...", n),
+        SyncProgress::Progress { stage, processed, page, .. } => {
+            println!("{:?}: page {}, {} items processed", stage, page, processed)
+        }
+        SyncProgress::Report(report) => println!(
+            "Fetched {} items ({} new, {} updated, {} unchanged)",
+            report.fetched, report.inserted, report.updated, report.skipped
+        ),
+        SyncProgress::FacesScanned { processed, total } => {
+            println!("Scanning faces: {}/{}", processed, total)
+        }
+        SyncProgress::Finished(total) => println!("Finished sync: {} items", total),
+    }
+}
+
+fn write_status_file(path: &Path, last_synced: DateTime<Utc>, message: &str) -> std::io::Result<()> {
+    let value = serde_json::json!({
+        "last_synced": last_synced.to_rfc3339(),
+        "message": message,
+        "updated_at": Utc::now().to_rfc3339(),
+    });
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, value.to_string())?;
+    std::fs::rename(tmp, path)
+}
+
+/// Run periodic sync until a shutdown signal or an abort; returns the exit code.
+async fn run_watch(
+    syncer: Syncer,
+    interval: std::time::Duration,
+    quiet: bool,
+    status_file: Option<PathBuf>,
+) -> i32 {
+    let local = tokio::task::LocalSet::new();
+    local
+        .run_until(async move {
+            let (tx, mut rx) = mpsc::unbounded_channel();
+            let (err_tx, mut err_rx) = mpsc::unbounded_channel();
+            let (handle, shutdown) = syncer.start_periodic_sync(interval, tx, err_tx, None, None, None);
+            tokio::task::spawn_local(async move {
+                while let Some(p) = rx.recv().await {
+                    if !quiet {
+                        print_progress(p);
+                    }
+                }
+            });
+            let signal = shutdown::shutdown_signal();
+            tokio::pin!(signal);
+            let code = loop {
+                tokio::select! {
+                    _ = &mut signal => {
+                        tracing::info!("Shutdown signal received");
+                        break 0;
+                    }
+                    err = err_rx.recv() => match err {
+                        Some(SyncTaskError::Status { last_synced, message }) => {
+                            if !quiet {
+                                println!("Status ({}): {}", last_synced.to_rfc3339(), message);
+                            }
+                            if let Some(path) = &status_file {
+                                if let Err(e) = write_status_file(path, last_synced, &message) {
+                                    tracing::error!("Failed to write status file: {}", e);
+                                }
+                            }
+                        }
+                        Some(SyncTaskError::Aborted(msg)) => {
+                            eprintln!("Error: {}", msg);
+                            break EXIT_SYNC_ABORTED;
+                        }
+                        Some(e) => eprintln!("Error: {}", e),
+                        None => break 1,
+                    }
+                }
+            };
+            let _ = shutdown.send(());
+            let _ = handle.await;
+            if !quiet {
+                println!("Watch stopped");
+            }
+            code
+        })
+        .await
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+}
+
+impl CheckStatus {
+    fn label(self) -> &'static str {
+        match self {
+            CheckStatus::Pass => "PASS",
+            CheckStatus::Warn => "WARN",
+            CheckStatus::Fail => "FAIL",
+        }
+    }
+}
+
+#[derive(serde::Serialize)]
+struct Check {
+    name: &'static str,
+    status: CheckStatus,
+    message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    hint: Option<&'static str>,
+}
+
+impl Check {
+    fn new(name: &'static str, status: CheckStatus, message: impl Into<String>, hint: Option<&'static str>) -> Self {
+        Check { name, status, message: message.into(), hint }
+    }
+}
+
+/// Minimum free space in the cache directory before `doctor` fails.
+const MIN_FREE_BYTES: u64 = 100 * 1024 * 1024;
+/// Free space below which `doctor` warns.
+const LOW_FREE_BYTES: u64 = 1024 * 1024 * 1024;
+
+fn free_space(dir: &Path) -> Option<u64> {
+    use sysinfo::{DiskExt, System, SystemExt};
+    let dir = dir.canonicalize().ok()?;
+    let mut sys = System::new();
+    sys.refresh_disks_list();
+    sys.disks()
+        .iter()
+        .filter(|d| dir.starts_with(d.mount_point()))
+        .max_by_key(|d| d.mount_point().as_os_str().len())
+        .map(|d| d.available_space())
+}
+
+async fn run_doctor(db_path: &Path, base_dir: &Path) -> Vec<Check> {
+    let mut checks = Vec::new();
+
+    match auth::get_token_status() {
+        Ok(status) => {
+            checks.push(if !status.has_access_token && !status.has_refresh_token {
+                Check::new("tokens", CheckStatus::Fail, "no stored tokens", Some("run `sync_cli auth login`"))
+            } else if status.access_token_expired() && !status.has_refresh_token {
+                Check::new(
+                    "tokens",
+                    CheckStatus::Fail,
+                    "access token expired and no refresh token",
+                    Some("run `sync_cli auth login` again"),
+                )
+            } else if status.access_token_expired() {
+                Check::new("tokens", CheckStatus::Warn, "access token expired; it will be refreshed", None)
+            } else {
+                Check::new("tokens", CheckStatus::Pass, "access token valid", None)
+            });
+            checks.push(match status.backend {
+                auth::TokenBackend::File => Check::new(
+                    "keyring",
+                    CheckStatus::Warn,
+                    "tokens stored in ~/.googlepicz/tokens.json (file-store fallback)",
+                    Some("check that the system keyring service is running"),
+                ),
+                backend => Check::new("keyring", CheckStatus::Pass, format!("using {}", backend.as_str()), None),
+            });
+        }
+        Err(e) => {
+            checks.push(Check::new("tokens", CheckStatus::Fail, e.to_string(), Some("run `sync_cli auth login`")));
+            checks.push(Check::new(
+                "keyring",
+                CheckStatus::Fail,
+                "keyring not accessible",
+                Some("start the keyring service or pass --use-file-store"),
+            ));
+        }
+    }
+
+    if db_path.exists() {
+        match CacheManager::new(db_path) {
+            Ok(cache) => {
+                checks.push(match cache.check_integrity() {
+                    Ok(report) if report.is_ok() => Check::new("cache", CheckStatus::Pass, "integrity check passed", None),
+                    Ok(report) => Check::new(
+                        "cache",
+                        CheckStatus::Fail,
+                        format!("{:?}", report),
+                        Some("run `sync_cli clear-cache` and sync again"),
+                    ),
+                    Err(e) => Check::new("cache", CheckStatus::Fail, e.to_string(), Some("delete the cache file and sync again")),
+                });
+                checks.push(match cache.schema_version() {
+                    Ok(v) if v == cache::SCHEMA_VERSION => {
+                        Check::new("schema", CheckStatus::Pass, format!("version {}", v), None)
+                    }
+                    Ok(v) => Check::new(
+                        "schema",
+                        CheckStatus::Fail,
+                        format!("version {} (expected {})", v, cache::SCHEMA_VERSION),
+                        Some("upgrade sync_cli to a release matching this cache"),
+                    ),
+                    Err(e) => Check::new("schema", CheckStatus::Fail, e.to_string(), None),
+                });
+            }
+            Err(e) => {
+                checks.push(Check::new("cache", CheckStatus::Fail, e.to_string(), Some("delete the cache file and sync again")));
+            }
+        }
+    } else {
+        checks.push(Check::new("cache", CheckStatus::Warn, "no cache found", Some("run `sync_cli sync`")));
+    }
+
+    let state_path = db_path.with_extension("state.json");
+    checks.push(match std::fs::read_to_string(&state_path) {
+        Ok(data) => match serde_json::from_str::<serde_json::Value>(&data) {
+            Ok(_) => Check::new("state", CheckStatus::Pass, "state file parses", None),
+            Err(e) => Check::new(
+                "state",
+                CheckStatus::Fail,
+                format!("invalid state file: {}", e),
+                Some("delete the .state.json file next to the cache"),
+            ),
+        },
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            Check::new("state", CheckStatus::Pass, "no state file yet", None)
+        }
+        Err(e) => Check::new("state", CheckStatus::Fail, e.to_string(), Some("check file permissions")),
+    });
+
+    checks.push(match free_space(base_dir) {
+        Some(free) if free < MIN_FREE_BYTES => Check::new(
+            "disk",
+            CheckStatus::Fail,
+            format!("{} MB free", free / 1024 / 1024),
+            Some("free up space in the cache directory"),
+        ),
+        Some(free) if free < LOW_FREE_BYTES => Check::new(
+            "disk",
+            CheckStatus::Warn,
+            format!("{} MB free", free / 1024 / 1024),
+            Some("free up space in the cache directory"),
+        ),
+        Some(free) => Check::new("disk", CheckStatus::Pass, format!("{} MB free", free / 1024 / 1024), None),
+        None => Check::new("disk", CheckStatus::Warn, "could not determine free space", None),
+    });
+
+    checks.push(match ensure_access_token_valid().await {
+        Ok(token) => match ApiClient::new(token).list_albums(1, None).await {
+            Ok(_) => Check::new("api", CheckStatus::Pass, "Google Photos API reachable", None),
+            Err(e) => Check::new("api", CheckStatus::Fail, e.to_string(), Some("check network connectivity")),
+        },
+        Err(e) => Check::new("api", CheckStatus::Fail, e.to_string(), Some("run `sync_cli auth login`")),
+    });
+
+    checks
+}
+
+fn collect_upload_files(paths: &[PathBuf], recursive: bool, out: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    for path in paths {
+        if path.is_dir() {
+            let mut entries: Vec<PathBuf> = std::fs::read_dir(path)?
+                .filter_map(|e| e.ok().map(|e| e.path()))
+                .collect();
+            entries.sort();
+            for entry in entries {
+                if entry.is_dir() {
+                    if recursive {
+                        collect_upload_files(&[entry], recursive, out)?;
+                    }
+                } else if api_client::mime_type_for_path(&entry).is_some() {
+                    out.push(entry);
+                }
+            }
+        } else if api_client::mime_type_for_path(path).is_some() {
+            out.push(path.clone());
+        } else {
+            println!("Skipping unsupported file {:?}", path);
+        }
+    }
+    Ok(())
+}
+
+fn progress_bar(done: usize, total: usize) -> String {
+    const WIDTH: usize = 20;
+    let filled = (done * WIDTH).checked_div(total).unwrap_or(WIDTH);
+    format!("[{}{}] {}/{}", "#".repeat(filled), " ".repeat(WIDTH - filled), done, total)
+}
+
+/// Thumbnail size the grid requests for its smallest tiles.
+const THUMBNAIL_SIZE: u32 = 150;
+
+/// A valid access token, pointing at `auth login` when the stored sign-in was
+/// revoked or does not grant the upload and album permissions yet.
+async fn access_token() -> Result<String, Box<dyn std::error::Error>> {
+    ensure_access_token_valid().await.map_err(|e| match e {
+        AuthError::ReauthRequired(reason) => {
+            format!("Sign-in required: {} (run `sync_cli auth login`)", reason).into()
+        }
+        e => e.into(),
+    })
+}
+
+async fn upload_file(
+    client: &ApiClient,
+    cache: &CacheManager,
+    path: &Path,
+    description: &str,
+) -> Result<Option<api_client::MediaItem>, Box<dyn std::error::Error>> {
+    let hash = cache::hash_file(path)?;
+    if cache.get_uploaded_file(&hash)?.is_some() {
+        return Ok(None);
+    }
+    let file_name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "upload".to_string());
+    let mime = api_client::mime_type_for_path(path).unwrap_or("application/octet-stream");
+    let item = if std::fs::metadata(path)?.len() > api_client::RESUMABLE_UPLOAD_THRESHOLD {
+        client
+            .upload_media_item_resumable(path, &file_name, description, mime)
+            .await?
+    } else {
+        let data = tokio::fs::read(path).await?;
+        client.upload_media_item(&data, &file_name, description).await?
+    };
+    cache.insert_media_item(&item)?;
+    cache.record_uploaded_file(&hash, &item.id, &file_name)?;
+    Ok(Some(item))
+}
+
+#[derive(Subcommand)]
+enum PeopleCommands {
+    /// List named people with photo counts
+    List {
+        /// Print machine-readable JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Rename a person on every face
+    Rename {
+        /// Current name
+        old: String,
+        /// New name
+        new: String,
+        /// Print machine-readable JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Merge one person into another
+    Merge {
+        /// Name to keep
+        keep: String,
+        /// Name to fold into `keep`
+        remove: String,
+        /// Print machine-readable JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigCommands {
+    /// Print every setting after flags, environment and file are applied
+    Show {
+        /// Also print where each value came from
+        #[arg(long)]
+        sources: bool,
+    },
+}
+
+/// Print `cfg` as `key = value`, followed by any problems `validate` finds.
+fn print_config(cfg: &config::AppConfig, sources: bool) {
+    if let Some(profile) = &cfg.profile {
+        println!("# profile: {}", profile);
+    }
+    for (key, value, source) in cfg.entries() {
+        if sources {
+            println!("{} = {} ({})", key, value, source.as_str());
+        } else {
+            println!("{} = {}", key, value);
+        }
+    }
+    if let Err(issues) = cfg.validate() {
+        println!();
+        println!("{}", config::describe_issues(&issues));
+    }
+}
+
+#[derive(Subcommand)]
+enum ProfileCommands {
+    /// List the profiles under ~/.googlepicz/profiles
+    List,
+    /// Create a profile with the default settings
+    Create {
+        /// Letters, digits, - and _
+        name: String,
+    },
+}
+
+/// List or create profiles; `active` is marked in the list.
+fn run_profile_command(command: ProfileCommands, active: Option<&str>) {
+    match command {
+        ProfileCommands::List => {
+            for name in config::list_profiles() {
+                let marker = if Some(name.as_str()) == active { "*" } else { " " };
+                println!("{} {}", marker, name);
+            }
+        }
+        ProfileCommands::Create { name } => match config::create_profile(&name) {
+            Ok(path) => println!("Created profile {} ({})", name, path.display()),
+            Err(e) => {
+                eprintln!("Cannot create profile: {}", e);
+                std::process::exit(1);
+            }
+        },
+    }
+}
+
+#[derive(Subcommand)]
+enum FacesCommands {
+    /// Show bounding boxes and names for one media item
+    Show {
+        /// ID of the media item
+        media_id: String,
+        /// Print machine-readable JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Group unnamed faces that look alike into clusters
+    Cluster {
+        /// Cosine similarity (-1 to 1) at which two faces count as the same person
+        #[arg(long, default_value_t = cache::DEFAULT_CLUSTER_THRESHOLD)]
+        threshold: f32,
+        /// Print machine-readable JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Detect faces in cached photos that have not been scanned yet,
+    /// resuming an interrupted scan
+    #[cfg(feature = "face-recognition")]
+    Scan {
+        /// Number of photos downloaded and scanned at the same time
+        #[arg(long, default_value_t = sync::DEFAULT_SCAN_CONCURRENCY)]
+        concurrency: usize,
+    },
+    /// Ignore unnamed faces detected with a lower score, without detecting again
+    ApplyThreshold {
+        /// Confidence from 0 to 1; defaults to `face_detector.confidence_threshold`
+        #[arg(long)]
+        threshold: Option<f32>,
+    },
+    /// Save each face as a JPEG in a folder per person
+    ExportCrops {
+        /// Only export faces with this name
+        #[arg(long)]
+        person: Option<String>,
+        /// Directory to write the crops to
+        #[arg(long)]
+        out: PathBuf,
+    },
+}
+
+fn enabled_features() -> Vec<&'static str> {
+    let mut features = Vec::new();
+    if cfg!(feature = "file-store") {
+        features.push("file-store");
+    }
+    if cfg!(feature = "trace-spans") {
+        features.push("trace-spans");
+    }
+    if cfg!(feature = "tokio-console") {
+        features.push("tokio-console");
+    }
+    if cfg!(feature = "face-recognition") {
+        features.push("face-recognition");
+    }
+    if cfg!(feature = "face-detector-onnx") {
+        features.push("face-detector-onnx");
+    }
+    features
+}
+
+fn print_version(json: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let version = env!("CARGO_PKG_VERSION");
+    let git_hash = option_env!("GIT_HASH").unwrap_or("unknown");
+    if json {
+        let value = serde_json::json!({
+            "version": version,
+            "git_hash": git_hash,
+            "features": enabled_features(),
+            "schema_version": cache::SCHEMA_VERSION,
+        });
+        println!("{}", serde_json::to_string_pretty(&value)?);
+    } else {
+        println!("sync_cli {} ({})", version, git_hash);
+    }
+    Ok(())
+}
+
 #[derive(Subcommand)]
 enum Commands {
+    /// Print shell completions to stdout
+    Completions {
+        /// Target shell
+        shell: clap_complete::Shell,
+    },
+    /// Manage stored credentials
+    Auth {
+        #[command(subcommand)]
+        command: AuthCommands,
+    },
+    /// Inspect the effective configuration
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommands,
+    },
+    /// Manage named profiles with their own settings, cache and account
+    Profile {
+        #[command(subcommand)]
+        command: ProfileCommands,
+    },
     /// Perform a full synchronization
-    Sync,
+    Sync {
+        /// Keep running and sync periodically until interrupted
+        #[arg(long)]
+        watch: bool,
+        /// Minutes between syncs in watch mode (defaults to the config value)
+        #[arg(long, requires = "watch")]
+        interval: Option<u64>,
+        /// Suppress progress output
+        #[arg(long)]
+        quiet: bool,
+        /// Write the latest sync status as JSON to this file
+        #[arg(long, requires = "watch")]
+        status_file: Option<PathBuf>,
+        /// Stop after this many pages in a row with nothing new; 0 syncs every page
+        /// (defaults to the config value)
+        #[arg(long, value_name = "N")]
+        unchanged_page_limit: Option<u32>,
+    },
     /// Show last sync time and cached item count
     Status,
+    /// Diagnose tokens, keyring, cache, disk space and API access
+    Doctor {
+        /// Print machine-readable JSON
+        #[arg(long)]
+        json: bool,
+    },
     /// Delete all cached media items
     ClearCache,
     /// Display all cached albums
@@ -90,6 +904,27 @@ enum Commands {
     },
     /// Show statistics about cached data
     CacheStats,
+    /// Download thumbnails for cached items ahead of the first UI launch, within the API rate limit
+    PrefetchThumbnails {
+        /// Maximum number of items to prefetch
+        #[arg(long)]
+        limit: Option<usize>,
+        /// Number of parallel downloads
+        #[arg(long, default_value_t = 4, value_parser = clap::value_parser!(u16).range(1..))]
+        concurrency: u16,
+        /// Only prefetch items from this album
+        #[arg(long)]
+        album: Option<String>,
+    },
+    /// Summarize the cached library without contacting the API
+    Stats {
+        /// Output format
+        #[arg(long, value_enum, default_value = "table")]
+        output: OutputFormat,
+        /// Print a per-year histogram instead of the summary
+        #[arg(long)]
+        by_year: bool,
+    },
     /// List cached media items
     ListItems {
         /// Maximum number of items to display
@@ -125,6 +960,42 @@ enum Commands {
         #[arg(long)]
         file: PathBuf,
     },
+    /// Export the full local state (items, albums, associations, favorites, faces, sync state)
+    #[command(group(clap::ArgGroup::new("target").required(true).args(["dir", "archive"])))]
+    Export {
+        /// Directory to write the export into
+        #[arg(long)]
+        dir: Option<PathBuf>,
+        /// Write a single tar archive instead of a directory
+        #[arg(long)]
+        archive: Option<PathBuf>,
+    },
+    /// Import a full state export
+    #[command(group(clap::ArgGroup::new("source").required(true).args(["dir", "archive"])))]
+    Import {
+        /// Directory containing the export
+        #[arg(long)]
+        dir: Option<PathBuf>,
+        /// Tar archive created with `export --archive`
+        #[arg(long)]
+        archive: Option<PathBuf>,
+        /// Keep existing data and overwrite matching entries (default)
+        #[arg(long, conflicts_with = "replace")]
+        merge: bool,
+        /// Clear the cache before importing
+        #[arg(long)]
+        replace: bool,
+    },
+    /// Inspect and fix names assigned to faces
+    People {
+        #[command(subcommand)]
+        command: PeopleCommands,
+    },
+    /// Inspect detected faces
+    Faces {
+        #[command(subcommand)]
+        command: FacesCommands,
+    },
     /// Import media items from a JSON file
     ImportItems {
         /// Path to the JSON file
@@ -142,6 +1013,7 @@ enum Commands {
         /// ID of the media item
         id: String,
         /// Set favorite (true/false)
+        #[arg(action = clap::ArgAction::Set)]
         fav: bool,
     },
     /// Search cached media items
@@ -173,6 +1045,45 @@ enum Commands {
         #[arg(long)]
         faces: bool,
     },
+    /// Query cached media items with filters
+    Query {
+        /// Filename contains this text
+        #[arg(long)]
+        filename: Option<String>,
+        /// Description contains this text
+        #[arg(long)]
+        description: Option<String>,
+        /// Filter by MIME type
+        #[arg(long)]
+        mime: Option<String>,
+        /// Filter by camera model
+        #[arg(long)]
+        camera_model: Option<String>,
+        /// Filter by camera make
+        #[arg(long)]
+        camera_make: Option<String>,
+        /// Only show favorites
+        #[arg(long)]
+        favorite: bool,
+        /// Start date (YYYY-MM-DD or RFC3339)
+        #[arg(long)]
+        from: Option<String>,
+        /// End date (YYYY-MM-DD or RFC3339)
+        #[arg(long)]
+        to: Option<String>,
+        /// Only items in this album
+        #[arg(long)]
+        album: Option<String>,
+        /// Maximum number of items to return
+        #[arg(long)]
+        limit: Option<usize>,
+        /// Sort order
+        #[arg(long, value_enum, default_value = "newest")]
+        sort: SortOrder,
+        /// Output format
+        #[arg(long, value_enum, default_value = "table")]
+        output: OutputFormat,
+    },
     /// Search cached albums by title
     SearchAlbums {
         /// Query string to match album title
@@ -203,6 +1114,21 @@ enum Commands {
         #[arg(long)]
         description: String,
     },
+    /// Upload files or folders of photos and videos
+    Upload {
+        /// Files or directories to upload
+        #[arg(required = true)]
+        paths: Vec<PathBuf>,
+        /// Add the uploaded items to this album
+        #[arg(long)]
+        album: Option<String>,
+        /// Description for every uploaded item
+        #[arg(long)]
+        description: Option<String>,
+        /// Descend into subdirectories
+        #[arg(long)]
+        recursive: bool,
+    },
     /// List items of an album
     ListAlbumItems {
         /// Album ID
@@ -225,6 +1151,18 @@ enum Commands {
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let cli = Cli::parse();
 
+    if cli.version {
+        return print_version(cli.version_json);
+    }
+    let Some(command) = cli.command else {
+        Cli::command().print_help()?;
+        std::process::exit(2);
+    };
+    if let Commands::Completions { shell } = command {
+        clap_complete::generate(shell, &mut Cli::command(), "sync_cli", &mut std::io::stdout());
+        return Ok(());
+    }
+
     if cli.use_file_store {
         std::env::set_var("USE_FILE_STORE", "1");
     }
@@ -233,12 +1171,34 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         log_level: cli.log_level.clone(),
         oauth_redirect_port: cli.oauth_redirect_port,
         thumbnails_preload: cli.thumbnails_preload,
+        preload_threads: None,
         sync_interval_minutes: cli.sync_interval_minutes,
         debug_console: cli.debug_console,
         trace_spans: cli.trace_spans,
         detect_faces: cli.detect_faces,
+        headless: false,
     };
-    let cfg = config::AppConfig::load_from(cli.config.clone()).apply_overrides(&overrides);
+    let profile = config::active_profile(cli.profile.as_deref());
+    // Token storage looks the profile up in the environment
+    match &profile {
+        Some(name) => std::env::set_var(config::PROFILE_ENV, name),
+        None => std::env::remove_var(config::PROFILE_ENV),
+    }
+    if let Commands::Profile { command } = command {
+        run_profile_command(command, profile.as_deref());
+        return Ok(());
+    }
+    let mut cfg = config::AppConfig::load_from(cli.config.clone(), profile.as_deref()).apply_overrides(&overrides);
+    // Show the config as loaded, even when it would not start
+    if let Commands::Config { command: ConfigCommands::Show { sources } } = command {
+        print_config(&cfg, sources);
+        return Ok(());
+    }
+    let clamped = cfg.clamp_recoverable();
+    if let Err(issues) = cfg.validate() {
+        eprintln!("{}", config::describe_issues(&issues));
+        std::process::exit(EXIT_INVALID_CONFIG);
+    }
     let base_dir = cfg.cache_path.clone();
     std::fs::create_dir_all(&base_dir)?;
     let file_appender = rolling::daily(&base_dir, "googlepicz.log");
@@ -246,24 +1206,80 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     tracing_subscriber::fmt()
         .with_env_filter(EnvFilter::new(cfg.log_level.clone()))
-        .with_writer(std::io::stdout.and(file_writer))
+        .with_writer(std::io::stderr.and(file_writer))
         .init();
+    for issue in clamped {
+        tracing::warn!("Setting out of range, clamped: {}", issue);
+    }
 
     let db_path = base_dir.join("cache.sqlite");
 
-    match cli.command {
-        Commands::Sync => {
+    match command {
+        // Handled before setup, as these work without a valid config
+        Commands::Completions { shell } => {
+            clap_complete::generate(shell, &mut Cli::command(), "sync_cli", &mut std::io::stdout());
+        }
+        Commands::Config { command: ConfigCommands::Show { sources } } => print_config(&cfg, sources),
+        Commands::Profile { command } => run_profile_command(command, cfg.profile.as_deref()),
+        Commands::Auth { command } => match command {
+            AuthCommands::Login { device, port } => {
+                let result = if device {
+                    auth::authenticate_device(|url, code| {
+                        println!("Visit {} and enter code {}", url, code);
+                    })
+                    .await
+                } else {
+                    auth::authenticate(port.unwrap_or(cfg.oauth_redirect_port)).await
+                };
+                if let Err(e) = result {
+                    auth_error_exit(e);
+                }
+                println!("Login successful");
+            }
+            AuthCommands::Logout => {
+                if let Err(e) = auth::logout() {
+                    auth_error_exit(e);
+                }
+                println!("Logged out");
+            }
+            AuthCommands::Status { json } => {
+                let status = match auth::get_token_status() {
+                    Ok(s) => s,
+                    Err(e) => auth_error_exit(e),
+                };
+                print_token_status(&status, json)?;
+                if !status.has_access_token && !status.has_refresh_token {
+                    std::process::exit(EXIT_NO_TOKENS);
+                }
+                if status.access_token_expired() && !status.has_refresh_token {
+                    std::process::exit(EXIT_NO_REFRESH_TOKEN);
+                }
+            }
+        },
+        Commands::Sync { watch, interval, quiet, status_file, unchanged_page_limit } => {
             let mut syncer = Syncer::new(&db_path).await?;
             syncer.set_face_detection(cfg.detect_faces);
+            syncer.set_min_face_size(cfg.min_face_size);
+            syncer.set_favorites_album_sync(cfg.favorites_album_sync);
+            syncer.set_unchanged_page_limit(unchanged_page_limit.unwrap_or(cfg.unchanged_page_limit));
+            #[cfg(feature = "face-recognition")]
+            syncer.set_detector_config(cfg.face_detector.clone());
+            if watch {
+                let minutes = interval.unwrap_or(cfg.sync_interval_minutes).max(1);
+                let interval = std::time::Duration::from_secs(minutes * 60);
+                let code = run_watch(syncer, interval, quiet, status_file).await;
+                if code != 0 {
+                    drop(_guard);
+                    std::process::exit(code);
+                }
+                return Ok(());
+            }
             let (tx, mut rx) = mpsc::unbounded_channel();
             let (err_tx, mut err_rx) = mpsc::unbounded_channel();
             tokio::spawn(async move {
                 while let Some(p) = rx.recv().await {
-                    match p {
-                        SyncProgress::Started => println!("Sync started"),
-                        SyncProgress::Retrying(wait) => println!("Retrying in {}s", wait),
-                        SyncProgress::ItemSynced(n) => println!("Synced {} items...", n),
-                        SyncProgress::Finished(total) => println!("Finished sync: {} items", total),
+                    if !quiet {
+                        print_progress(p);
                     }
                 }
             });
@@ -273,9 +1289,20 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     eprintln!("Error: {}", e);
                 }
             });
-            syncer
+            let report = syncer
                 .sync_media_items(Some(tx), Some(err_tx), None, None)
                 .await?;
+            println!(
+                "New: {}, updated: {}, unchanged: {}{}",
+                report.inserted,
+                report.updated,
+                report.skipped,
+                if report.stopped_early { " (stopped early)" } else { "" }
+            );
+            println!(
+                "Network: {} bytes down, {} bytes up, {} requests ({} failed)",
+                report.transfer.bytes_down, report.transfer.bytes_up, report.transfer.requests, report.transfer.errors
+            );
         }
         Commands::Status => {
             if !db_path.exists() {
@@ -288,6 +1315,26 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             println!("Last sync: {}", last.to_rfc3339());
             println!("Cached items: {}", count);
         }
+        Commands::Doctor { json } => {
+            let checks = run_doctor(&db_path, &base_dir).await;
+            let worst = checks.iter().map(|c| c.status).max().unwrap_or(CheckStatus::Pass);
+            if json {
+                let value = serde_json::json!({ "overall": worst, "checks": checks });
+                println!("{}", serde_json::to_string_pretty(&value)?);
+            } else {
+                for check in &checks {
+                    println!("[{}] {}: {}", check.status.label(), check.name, check.message);
+                    if let Some(hint) = check.hint {
+                        println!("       hint: {}", hint);
+                    }
+                }
+            }
+            match worst {
+                CheckStatus::Pass => {}
+                CheckStatus::Warn => std::process::exit(1),
+                CheckStatus::Fail => std::process::exit(2),
+            }
+        }
         Commands::ClearCache => {
             if !db_path.exists() {
                 println!("No cache found at {:?}", db_path);
@@ -317,7 +1364,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 println!("No cache found at {:?}", db_path);
                 return Ok(());
             }
-            let token = ensure_access_token_valid().await?;
+            let token = access_token().await?;
             let client = ApiClient::new(token);
             let album = client.create_album(&title).await?;
             let cache = CacheManager::new(&db_path)?;
@@ -330,7 +1377,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 println!("No cache found at {:?}", db_path);
                 return Ok(());
             }
-            let token = ensure_access_token_valid().await?;
+            let token = access_token().await?;
             let client = ApiClient::new(token);
             client.delete_album(&id).await?;
             let cache = CacheManager::new(&db_path)?;
@@ -348,6 +1395,68 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             println!("Albums: {}", albums);
             println!("Media items: {}", items);
         }
+        Commands::PrefetchThumbnails { limit, concurrency, album } => {
+            if !db_path.exists() {
+                println!("No cache found at {:?}", db_path);
+                return Ok(());
+            }
+            let cache = CacheManager::new(&db_path)?;
+            let mut items = match &album {
+                Some(id) => cache.get_media_items_by_album(id)?,
+                None => cache.get_all_media_items()?,
+            };
+            if let Some(limit) = limit {
+                items.truncate(limit);
+            }
+            let thumbs = base_dir.join("thumbnails");
+            let total = items.len();
+            items.retain(|i| !thumbs.join(format!("{}.jpg", i.id)).exists());
+            let cached = total - items.len();
+            if items.is_empty() {
+                println!("All {} thumbnails already cached", total);
+                return Ok(());
+            }
+            // Base URLs are only served to authorized sessions, so refuse to start without a token.
+            access_token().await?;
+            let loader = ImageLoader::new(base_dir.clone(), concurrency as usize).with_rate_limit();
+            let start = std::time::Instant::now();
+            let items = items.into_iter().map(|i| (i.id, i.base_url)).collect();
+            let (tx, mut rx) = mpsc::channel(64);
+            let printer = tokio::spawn(async move {
+                while let Some(PrefetchProgress { done, total }) = rx.recv().await {
+                    let rate = done as f64 / start.elapsed().as_secs_f64().max(0.001);
+                    println!("{} {:.1} items/s", progress_bar(done, total), rate);
+                }
+            });
+            let summary = loader
+                .prefetch(items, Variant::Thumbnail(THUMBNAIL_SIZE), concurrency as usize, Some(tx))
+                .await;
+            let _ = printer.await;
+            println!(
+                "Downloaded: {}, cached: {}, failed: {}",
+                summary.downloaded,
+                cached + summary.skipped,
+                summary.failed
+            );
+            if summary.failed > 0 {
+                std::process::exit(1);
+            }
+        }
+        Commands::Stats { output, by_year } => {
+            if !db_path.exists() {
+                println!("No cache found at {:?}", db_path);
+                return Ok(());
+            }
+            let cache = CacheManager::new(&db_path)?;
+            if by_year {
+                let years = cache.count_media_items_by_period(cache::TimeBucket::Year, cache::TimeMode::Utc)?;
+                print_year_histogram(&years, output)?;
+            } else {
+                let stats = cache.get_library_stats()?;
+                let size = std::fs::metadata(&db_path)?.len();
+                print_stats(&stats, size, sync::last_transfer_stats(&db_path), output)?;
+            }
+        }
         Commands::ListItems { limit } => {
             if !db_path.exists() {
                 println!("No cache found at {:?}", db_path);
@@ -419,6 +1528,187 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             cache.import_media_items(&file)?;
             println!("Imported from {:?}", file);
         }
+        Commands::Export { dir, archive } => {
+            if !db_path.exists() {
+                println!("No cache found at {:?}", db_path);
+                return Ok(());
+            }
+            let cache = CacheManager::new(&db_path)?;
+            if let Some(archive) = archive {
+                let tmp = scratch_dir("export")?;
+                export_state(&cache, &db_path, tmp.path())?;
+                let mut builder = tar::Builder::new(std::fs::File::create(&archive)?);
+                builder.append_dir_all(".", tmp.path())?;
+                builder.finish()?;
+                println!("Exported state to {:?}", archive);
+            } else if let Some(dir) = dir {
+                export_state(&cache, &db_path, &dir)?;
+                println!("Exported state to {:?}", dir);
+            }
+        }
+        Commands::Import { dir, archive, merge: _, replace } => {
+            let mode = if replace { ImportMode::Replace } else { ImportMode::Merge };
+            let cache = CacheManager::new(&db_path)?;
+            if let Some(archive) = archive {
+                let tmp = scratch_dir("import")?;
+                tar::Archive::new(std::fs::File::open(&archive)?).unpack(tmp.path())?;
+                import_state(&cache, &db_path, tmp.path(), mode)?;
+                println!("Imported state from {:?}", archive);
+            } else if let Some(dir) = dir {
+                import_state(&cache, &db_path, &dir, mode)?;
+                println!("Imported state from {:?}", dir);
+            }
+        }
+        Commands::People { command } => {
+            if !db_path.exists() {
+                println!("No cache found at {:?}", db_path);
+                return Ok(());
+            }
+            let cache = CacheManager::new(&db_path)?;
+            match command {
+                PeopleCommands::List { json } => {
+                    let people = cache.list_people()?;
+                    if json {
+                        println!("{}", serde_json::to_string_pretty(&people)?);
+                    } else {
+                        for person in people {
+                            println!("{} ({} photos)", person.name, person.photo_count);
+                        }
+                    }
+                }
+                PeopleCommands::Rename { old, new, json } => {
+                    let changed = cache.rename_person(&old, &new)?;
+                    if json {
+                        println!("{}", serde_json::json!({ "changed": changed }));
+                    } else {
+                        println!("Renamed {} faces from {} to {}", changed, old, new);
+                    }
+                }
+                PeopleCommands::Merge { keep, remove, json } => {
+                    let changed = cache.merge_people(&keep, &remove)?;
+                    if json {
+                        println!("{}", serde_json::json!({ "changed": changed }));
+                    } else {
+                        println!("Merged {} faces from {} into {}", changed, remove, keep);
+                    }
+                }
+            }
+        }
+        Commands::Faces { command } => match command {
+            FacesCommands::Show { media_id, json } => {
+                if !db_path.exists() {
+                    println!("No cache found at {:?}", db_path);
+                    return Ok(());
+                }
+                let cache = CacheManager::new(&db_path)?;
+                let faces = cache.get_faces(&media_id)?.unwrap_or_default();
+                if json {
+                    println!("{}", serde_json::to_string_pretty(&faces)?);
+                } else if faces.is_empty() {
+                    println!("No faces for {}", media_id);
+                } else {
+                    for (i, face) in faces.iter().enumerate() {
+                        let [x, y, w, h] = face.bbox;
+                        println!(
+                            "{}: x={} y={} w={} h={} name={}",
+                            i,
+                            x,
+                            y,
+                            w,
+                            h,
+                            face.name.as_deref().unwrap_or("-")
+                        );
+                    }
+                }
+            }
+            FacesCommands::Cluster { threshold, json } => {
+                if !db_path.exists() {
+                    println!("No cache found at {:?}", db_path);
+                    return Ok(());
+                }
+                let cache = CacheManager::new(&db_path)?;
+                let count = cache.cluster_unnamed_faces(threshold)?;
+                let clusters: Vec<_> = cache
+                    .get_people_with_counts()?
+                    .into_iter()
+                    .filter(|p| p.cluster.is_some())
+                    .collect();
+                if json {
+                    println!("{}", serde_json::to_string_pretty(&clusters)?);
+                } else {
+                    println!("Found {} clusters of unnamed faces", count);
+                    for person in clusters {
+                        println!(
+                            "Unnamed person #{} ({} photos)",
+                            person.cluster.unwrap_or_default(),
+                            person.photo_count
+                        );
+                    }
+                }
+            }
+            #[cfg(feature = "face-recognition")]
+            FacesCommands::Scan { concurrency } => {
+                if !db_path.exists() {
+                    println!("No cache found at {:?}", db_path);
+                    return Ok(());
+                }
+                let cache = CacheManager::new(&db_path)?;
+                let recognizer = face_recognition::FaceRecognizer::new(cfg.face_detector.clone())
+                    .with_min_face_size(cfg.min_face_size);
+                let mut scanner = sync::FaceScanner::new(cache, recognizer).with_concurrency(concurrency);
+                // Cached base URLs expire, so refresh them when signed in
+                match ensure_access_token_valid().await {
+                    Ok(token) => scanner = scanner.with_api_client(ApiClient::new(token)),
+                    Err(e) => println!("Not signed in, using cached URLs: {}", e),
+                }
+                let (tx, mut rx) = mpsc::unbounded_channel();
+                let printer = tokio::spawn(async move {
+                    while let Some(p) = rx.recv().await {
+                        print_progress(p);
+                    }
+                });
+                let report = scanner.run(Some(tx)).await?;
+                let _ = printer.await;
+                println!(
+                    "Scanned {} photos, found {} faces ({} failed)",
+                    report.scanned, report.faces, report.failed
+                );
+            }
+            FacesCommands::ApplyThreshold { threshold } => {
+                if !db_path.exists() {
+                    println!("No cache found at {:?}", db_path);
+                    return Ok(());
+                }
+                let cache = CacheManager::new(&db_path)?;
+                let threshold = threshold.unwrap_or(cfg.face_detector.confidence_threshold);
+                let count = cache.ignore_faces_below_confidence(threshold)?;
+                println!("Ignored {} faces scoring below {}", count, threshold);
+            }
+            FacesCommands::ExportCrops { person, out } => {
+                if !db_path.exists() {
+                    println!("No cache found at {:?}", db_path);
+                    return Ok(());
+                }
+                let cache = CacheManager::new(&db_path)?;
+                // Originals that are not cached yet need an authorized session
+                access_token().await?;
+                let loader = ImageLoader::new(base_dir.clone(), 4);
+                let (tx, mut rx) = mpsc::channel(64);
+                let printer = tokio::spawn(async move {
+                    while let Some(PrefetchProgress { done, total }) = rx.recv().await {
+                        println!("{}", progress_bar(done, total));
+                    }
+                });
+                let summary = loader
+                    .export_face_crops(&cache, &out, person.as_deref(), Some(tx))
+                    .await?;
+                let _ = printer.await;
+                println!(
+                    "Exported {} face crops to {:?} ({} skipped)",
+                    summary.exported, out, summary.skipped
+                );
+            }
+        },
         Commands::ImportFaces { file } => {
             if !db_path.exists() {
                 std::fs::create_dir_all(&base_dir)?;
@@ -434,6 +1724,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             }
             let cache = CacheManager::new(&db_path)?;
             cache.set_favorite(&id, fav)?;
+            if cfg.favorites_album_sync {
+                cache.enqueue_mutation(&cache::Mutation::favorites(vec![id.clone()], fav))?;
+            }
             println!("Favorite for {} set to {}", id, fav);
         }
         Commands::Search {
@@ -466,6 +1759,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 if favorite { Some(true) } else { None },
                 mime_type.as_deref(),
                 Some(&query),
+                None,
             )?;
             if faces {
                 items.retain(|it| cache.get_faces(&it.id).ok().flatten().is_some());
@@ -475,6 +1769,45 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 println!("{} - {}", item.id, item.filename);
             }
         }
+        Commands::Query {
+            filename,
+            description,
+            mime,
+            camera_model,
+            camera_make,
+            favorite,
+            from,
+            to,
+            album,
+            limit,
+            sort,
+            output,
+        } => {
+            if !db_path.exists() {
+                println!("No cache found at {:?}", db_path);
+                return Ok(());
+            }
+            let mut query = MediaQuery::new().sort(sort.into());
+            query.filename = filename;
+            query.description = description;
+            query.mime_type = mime;
+            query.camera_model = camera_model;
+            query.camera_make = camera_make;
+            query.album_id = album;
+            query.limit = limit;
+            if favorite {
+                query = query.favorite(true);
+            }
+            if let Some(val) = from {
+                query.start = Some(parse_date(&val, false).ok_or(format!("Invalid date: {}", val))?);
+            }
+            if let Some(val) = to {
+                query.end = Some(parse_date(&val, true).ok_or(format!("Invalid date: {}", val))?);
+            }
+            let cache = CacheManager::new(&db_path)?;
+            let items = cache.query(&query)?;
+            print_items(&items, output)?;
+        }
         Commands::SearchAlbums { query, limit } => {
             if !db_path.exists() {
                 println!("No cache found at {:?}", db_path);
@@ -506,7 +1839,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 println!("No cache found at {:?}", db_path);
                 return Ok(());
             }
-            let token = ensure_access_token_valid().await?;
+            let token = access_token().await?;
             let client = ApiClient::new(token);
             let album = client.rename_album(&id, &title).await?;
             let cache = CacheManager::new(&db_path)?;
@@ -527,7 +1860,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             if !db_path.exists() {
                 std::fs::create_dir_all(&base_dir)?;
             }
-            let token = ensure_access_token_valid().await?;
+            let token = access_token().await?;
             let client = ApiClient::new(token);
             let data = tokio::fs::read(&path).await?;
             let file_name = path
@@ -541,6 +1874,58 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             cache.insert_media_item(&item)?;
             println!("Uploaded {} (id: {})", file_name, item.id);
         }
+        Commands::Upload { paths, album, description, recursive } => {
+            let mut files = Vec::new();
+            collect_upload_files(&paths, recursive, &mut files)?;
+            if files.is_empty() {
+                println!("No supported files found");
+                return Ok(());
+            }
+            let token = access_token().await?;
+            let client = ApiClient::new(token);
+            let cache = CacheManager::new(&db_path)?;
+            let description = description.unwrap_or_default();
+            let (mut uploaded, mut skipped, mut failed) = (Vec::new(), 0, 0);
+            for (i, path) in files.iter().enumerate() {
+                let outcome = match upload_file(&client, &cache, path, &description).await {
+                    Ok(Some(item)) => {
+                        uploaded.push(item.id);
+                        "uploaded".to_string()
+                    }
+                    Ok(None) => {
+                        skipped += 1;
+                        "already uploaded".to_string()
+                    }
+                    Err(e) => {
+                        failed += 1;
+                        tracing::error!("Upload of {:?} failed: {}", path, e);
+                        format!("failed: {}", e)
+                    }
+                };
+                println!("{} {} ({})", progress_bar(i + 1, files.len()), path.display(), outcome);
+            }
+            if let Some(album_id) = album {
+                if !uploaded.is_empty() {
+                    client.batch_add_media_items(&album_id, &uploaded).await?;
+                    for id in &uploaded {
+                        if let Err(e) = cache.associate_media_item_with_album(id, &album_id) {
+                            tracing::warn!("Album {} not cached, skipping local association: {}", album_id, e);
+                            break;
+                        }
+                    }
+                    println!("Added {} items to album {}", uploaded.len(), album_id);
+                }
+            }
+            println!(
+                "Uploaded: {}, skipped: {}, failed: {}",
+                uploaded.len(),
+                skipped,
+                failed
+            );
+            if failed > 0 {
+                std::process::exit(1);
+            }
+        }
         Commands::ListAlbumItems { album_id, limit } => {
             if !db_path.exists() {
                 println!("No cache found at {:?}", db_path);
@@ -558,7 +1943,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 println!("No cache found at {:?}", db_path);
                 return Ok(());
             }
-            let token = ensure_access_token_valid().await?;
+            let token = access_token().await?;
             let client = ApiClient::new(token);
             let item = client
                 .update_media_item_description(&id, &description)
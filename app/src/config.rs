@@ -1,7 +1,33 @@
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
 use serde::{Serialize, Deserialize};
 
-#[derive(Serialize, Deserialize, Clone)]
+/// More thumbnails than this ahead of scrolling only costs memory.
+pub const MAX_THUMBS_PRELOAD: usize = 1000;
+pub const MAX_PRELOAD_THREADS: usize = 64;
+pub const THEMES: [&str; 3] = ["system", "light", "dark"];
+pub const LANGUAGES: [&str; 2] = ["en", "de"];
+/// Environment variable selecting the profile when `--profile` is not given.
+pub const PROFILE_ENV: &str = "GOOGLEPICZ_PROFILE";
+/// How often the app and the UI look for edits to the config file.
+pub const CONFIG_POLL_INTERVAL: Duration = Duration::from_secs(2);
+/// Settings only read at startup; the others are applied while running.
+pub const RESTART_KEYS: [&str; 11] = [
+    "oauth_redirect_port",
+    "cache_path",
+    "debug_console",
+    "trace_spans",
+    "detect_faces",
+    "min_face_size",
+    "face_detector",
+    "headless",
+    "favorites_album_sync",
+    "metrics_port",
+    "unchanged_page_limit",
+];
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct AppConfig {
     pub log_level: String,
     pub oauth_redirect_port: u16,
@@ -11,7 +37,306 @@ pub struct AppConfig {
     pub debug_console: bool,
     pub trace_spans: bool,
     pub detect_faces: bool,
+    /// Only sync in the background, without opening a window
+    pub headless: bool,
     pub cache_path: PathBuf,
+    pub slideshow_interval_secs: u64,
+    pub slideshow_play_videos: bool,
+    /// `system`, `light` or `dark`
+    pub theme: String,
+    /// `en` or `de`
+    pub language: String,
+    /// Cap for images cached on disk in megabytes; 0 keeps everything
+    pub image_cache_max_mb: u64,
+    /// Cap for videos cached on disk in megabytes; 0 keeps everything
+    pub video_cache_max_mb: u64,
+    /// Download thumbnails of newly synced items in the background
+    pub prefetch_after_sync: bool,
+    /// Mirror favorites into an app-created "Favorites (GooglePicz)" album
+    pub favorites_album_sync: bool,
+    /// Serve Prometheus metrics on this localhost port; 0 turns it off
+    pub metrics_port: u16,
+    /// Stop a sync after this many pages in a row with nothing new; 0 syncs every page
+    pub unchanged_page_limit: u32,
+    /// Faces covering less of the image area than this fraction are ignored
+    pub min_face_size: f32,
+    /// `[face_detector]` table: backend, model and confidence threshold
+    pub face_detector: face_recognition::DetectorConfig,
+    /// Profile the settings were loaded for, `None` for the default one
+    #[serde(skip)]
+    pub profile: Option<String>,
+    /// Where each value came from; not written to the file
+    #[serde(skip)]
+    pub sources: ConfigSources,
+}
+
+/// Where an effective setting came from. Later layers win: flags over
+/// environment variables over the file over the defaults.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigSource {
+    Default,
+    File,
+    /// A `GOOGLEPICZ_<FIELD>` environment variable
+    Env,
+    /// A command line flag
+    Flag,
+}
+
+impl ConfigSource {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ConfigSource::Default => "default",
+            ConfigSource::File => "file",
+            ConfigSource::Env => "env",
+            ConfigSource::Flag => "flag",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ConfigSources {
+    /// Keys not left at their default, see `AppConfig::source`
+    pub keys: BTreeMap<&'static str, ConfigSource>,
+    /// Values that could not be parsed, reported by `validate`
+    pub unparsable: Vec<ConfigIssue>,
+}
+
+/// A setting outside its allowed range, see `AppConfig::validate`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigIssue {
+    /// Key in the config file
+    pub field: &'static str,
+    /// Offending value as written
+    pub value: String,
+    /// Allowed values, e.g. `1-65535`
+    pub allowed: String,
+    /// The app cannot run with it; other issues are clamped by `clamp_recoverable`
+    pub fatal: bool,
+}
+
+impl std::fmt::Display for ConfigIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} = {:?} (allowed: {})", self.field, self.value, self.allowed)
+    }
+}
+
+/// An existing folder, or one whose nearest existing ancestor is a folder
+/// so it can be created.
+pub fn usable_dir(path: &Path) -> bool {
+    path.ancestors()
+        .find(|p| !p.as_os_str().is_empty() && p.exists())
+        .map(|p| p.is_dir())
+        .unwrap_or(false)
+}
+
+/// `~/.googlepicz`, which holds the default profile.
+pub fn base_dir() -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(".googlepicz")
+}
+
+/// Folder with the config, cache and tokens of `profile`:
+/// `~/.googlepicz/profiles/<name>`, or `~/.googlepicz` for the default profile.
+pub fn profile_dir(profile: Option<&str>) -> PathBuf {
+    match profile {
+        Some(name) => base_dir().join("profiles").join(name),
+        None => base_dir(),
+    }
+}
+
+pub fn config_path(profile: Option<&str>) -> PathBuf {
+    profile_dir(profile).join("config")
+}
+
+/// The profile named by `flag`, else by `GOOGLEPICZ_PROFILE`; blank names
+/// select the default profile.
+pub fn active_profile(flag: Option<&str>) -> Option<String> {
+    let name = match flag {
+        Some(name) => name.to_string(),
+        None => std::env::var(PROFILE_ENV).ok()?,
+    };
+    let name = name.trim();
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+/// Letters, digits, `-` and `_`, so the name is safe as a folder name.
+pub fn valid_profile_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Names of the profiles under `~/.googlepicz/profiles`, sorted.
+pub fn list_profiles() -> Vec<String> {
+    let mut names: Vec<String> = std::fs::read_dir(base_dir().join("profiles"))
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .filter(|e| e.path().is_dir())
+                .filter_map(|e| e.file_name().into_string().ok())
+                .filter(|name| valid_profile_name(name))
+                .collect()
+        })
+        .unwrap_or_default();
+    names.sort();
+    names
+}
+
+/// Create the folder of profile `name` with a config file holding the
+/// defaults, and return the config path. Fails if the profile exists.
+pub fn create_profile(name: &str) -> std::io::Result<PathBuf> {
+    if !valid_profile_name(name) {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("invalid profile name {:?}: use letters, digits, - and _", name),
+        ));
+    }
+    let path = config_path(Some(name));
+    if path.exists() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::AlreadyExists,
+            format!("profile {:?} already exists", name),
+        ));
+    }
+    AppConfig::defaults(Some(name)).save_to(Some(path.clone()))?;
+    Ok(path)
+}
+
+/// `value` clamped to 0-1, or `default` when it is not a number.
+fn clamp_unit(value: f32, default: f32) -> f32 {
+    if value.is_nan() {
+        default
+    } else {
+        value.clamp(0.0, 1.0)
+    }
+}
+
+/// Environment variable overriding `key`; nested keys use a double
+/// underscore, e.g. `GOOGLEPICZ_FACE_DETECTOR__MAX_FACES`.
+pub fn env_var(key: &str) -> String {
+    format!("GOOGLEPICZ_{}", key.replace('.', "__").to_uppercase())
+}
+
+fn parse_bool(value: &str) -> Option<bool> {
+    match value.to_ascii_lowercase().as_str() {
+        "true" | "1" | "yes" | "on" => Some(true),
+        "false" | "0" | "no" | "off" => Some(false),
+        _ => None,
+    }
+}
+
+/// The config file and the environment, read key by key so each value
+/// remembers where it came from.
+struct Layers {
+    file: config::Config,
+    /// Read `GOOGLEPICZ_<FIELD>` variables
+    env: bool,
+    sources: ConfigSources,
+}
+
+impl Layers {
+    /// `key` parsed by `parse`, from the environment before the file. Values
+    /// that do not parse are recorded as fatal issues and `default` is used.
+    fn get<T>(&mut self, key: &'static str, allowed: &str, default: T, parse: impl Fn(&str) -> Option<T>) -> T {
+        let env = if self.env { std::env::var(env_var(key)).ok() } else { None };
+        let raw = match env {
+            Some(value) => (value, ConfigSource::Env),
+            None => match self.file.get_string(key) {
+                Ok(value) => (value, ConfigSource::File),
+                Err(_) => return default,
+            },
+        };
+        match parse(raw.0.trim()) {
+            Some(value) => {
+                self.sources.keys.insert(key, raw.1);
+                value
+            }
+            None => {
+                self.sources.unparsable.push(ConfigIssue {
+                    field: key,
+                    value: raw.0,
+                    allowed: allowed.to_string(),
+                    fatal: true,
+                });
+                default
+            }
+        }
+    }
+
+    fn string(&mut self, key: &'static str, default: &str) -> String {
+        self.get(key, "text", default.to_string(), |v| Some(v.to_string()))
+    }
+
+    /// A whole number that has to fit `T`. One that does not, such as a
+    /// negative count or a port above 65535, is recorded as written with the
+    /// `allowed` range and `default` is used.
+    fn number<T: TryFrom<i64> + Copy>(&mut self, key: &'static str, default: T, allowed: &str, fatal: bool) -> T {
+        let value = self.get(key, "a whole number", Ok(default), |v| {
+            let n: i64 = v.parse().ok()?;
+            Some(T::try_from(n).map_err(|_| v.to_string()))
+        });
+        value.unwrap_or_else(|raw| {
+            self.sources.unparsable.push(ConfigIssue {
+                field: key,
+                value: raw,
+                allowed: allowed.to_string(),
+                fatal,
+            });
+            default
+        })
+    }
+
+    fn flag(&mut self, key: &'static str, default: bool) -> bool {
+        self.get(key, "true or false", default, parse_bool)
+    }
+
+    fn float(&mut self, key: &'static str, default: f32) -> f32 {
+        self.get(key, "a number", default, |v| v.parse().ok())
+    }
+}
+
+/// Message listing `issues`, one per line, for when the app cannot start.
+pub fn describe_issues(issues: &[ConfigIssue]) -> String {
+    let mut msg = String::from("Invalid configuration:");
+    for issue in issues {
+        msg.push_str("\n  ");
+        msg.push_str(&issue.to_string());
+    }
+    msg
+}
+
+/// Notices edits to a config file by its size and modification time.
+pub struct ConfigWatcher {
+    path: PathBuf,
+    profile: Option<String>,
+    stamp: Option<(SystemTime, u64)>,
+}
+
+impl ConfigWatcher {
+    /// Watch `path`, taking the file as it is now as seen.
+    pub fn new(path: PathBuf, profile: Option<String>) -> Self {
+        let stamp = Self::read_stamp(&path);
+        Self { path, profile, stamp }
+    }
+
+    fn read_stamp(path: &Path) -> Option<(SystemTime, u64)> {
+        let meta = std::fs::metadata(path).ok()?;
+        Some((meta.modified().ok()?, meta.len()))
+    }
+
+    /// Take the file as it is now as seen, e.g. after writing it ourselves.
+    pub fn reset(&mut self) {
+        self.stamp = Self::read_stamp(&self.path);
+    }
+
+    /// The settings loaded again if the file changed since the last call.
+    pub fn poll(&mut self) -> Option<AppConfig> {
+        let stamp = Self::read_stamp(&self.path);
+        if stamp == self.stamp {
+            return None;
+        }
+        self.stamp = stamp;
+        Some(AppConfig::load_from(Some(self.path.clone()), self.profile.as_deref()))
+    }
 }
 
 pub struct AppConfigOverrides {
@@ -23,39 +348,72 @@ pub struct AppConfigOverrides {
     pub debug_console: bool,
     pub trace_spans: bool,
     pub detect_faces: bool,
+    pub headless: bool,
 }
 
 impl AppConfig {
-    pub fn load_from(path: Option<PathBuf>) -> Self {
-        let mut builder = config::Config::builder();
-        let path = match path {
-            Some(p) => p,
-            None => dirs::home_dir()
-                .unwrap_or_else(|| PathBuf::from("."))
-                .join(".googlepicz")
-                .join("config"),
+    /// Settings of `profile` from the file at `path` (default
+    /// `config_path(profile)`), overridden by `GOOGLEPICZ_<FIELD>`
+    /// environment variables. The cache defaults to the profile folder.
+    pub fn load_from(path: Option<PathBuf>, profile: Option<&str>) -> Self {
+        let path = path.unwrap_or_else(|| config_path(profile));
+        // Without an extension the format has to be named, or the file is skipped
+        let file = config::Config::builder()
+            .add_source(
+                config::File::from(path)
+                    .format(config::FileFormat::Toml)
+                    .required(false),
+            )
+            .build()
+            .unwrap_or_default();
+        Self::from_layers(Layers { file, env: true, sources: ConfigSources::default() }, profile)
+    }
+
+    /// Built-in settings of `profile`, ignoring files and the environment.
+    pub fn defaults(profile: Option<&str>) -> Self {
+        Self::from_layers(
+            Layers { file: config::Config::default(), env: false, sources: ConfigSources::default() },
+            profile,
+        )
+    }
+
+    fn from_layers(mut layers: Layers, profile: Option<&str>) -> Self {
+
+        let log_level = layers.string("log_level", "info");
+        let oauth_redirect_port = layers.number("oauth_redirect_port", 8080u16, "1-65535", true);
+        let thumbnails_preload = layers.number("thumbnails_preload", 20usize, "0-1000", false);
+        let preload_threads = layers.number("preload_threads", 4usize, "1-64", false);
+        let sync_interval_minutes = layers.number("sync_interval_minutes", 5u64, "1 or more", false);
+        let debug_console = layers.flag("debug_console", false);
+        let trace_spans = layers.flag("trace_spans", false);
+        let detect_faces = layers.flag("detect_faces", false);
+        let headless = layers.flag("headless", false);
+        let slideshow_interval_secs = layers.number("slideshow_interval_secs", 5u64, "1 or more", false);
+        let slideshow_play_videos = layers.flag("slideshow_play_videos", false);
+        let theme = layers.string("theme", "system");
+        let language = layers.string("language", "en");
+        let image_cache_max_mb = layers.number("image_cache_max_mb", 1024u64, "0 or more", false);
+        let video_cache_max_mb = layers.number("video_cache_max_mb", 2048u64, "0 or more", false);
+        let prefetch_after_sync = layers.flag("prefetch_after_sync", false);
+        let favorites_album_sync = layers.flag("favorites_album_sync", false);
+        let metrics_port = layers.number("metrics_port", 0u16, "0-65535", false);
+        let unchanged_page_limit =
+            layers.number("unchanged_page_limit", sync::DEFAULT_UNCHANGED_PAGE_LIMIT, "0 or more", false);
+        let min_face_size = layers.float("min_face_size", cache::DEFAULT_MIN_FACE_SIZE);
+        let defaults = face_recognition::DetectorConfig::default();
+        let face_detector = face_recognition::DetectorConfig {
+            backend: layers.get("face_detector.backend", "haar or onnx", defaults.backend, |v| match v {
+                "haar" => Some(face_recognition::DetectorBackend::Haar),
+                "onnx" => Some(face_recognition::DetectorBackend::Onnx),
+                _ => None,
+            }),
+            model_path: layers.get("face_detector.model_path", "a file path", None, |v| Some(Some(PathBuf::from(v)))),
+            confidence_threshold: layers.float("face_detector.confidence_threshold", defaults.confidence_threshold),
+            max_faces: layers.number("face_detector.max_faces", defaults.max_faces, "0 or more", false),
+            input_size: layers.number("face_detector.input_size", defaults.input_size, "0 or more", false),
         };
-        builder = builder.add_source(config::File::from(path).required(false));
-        let cfg = builder.build().unwrap_or_default();
-
-        let log_level = cfg
-            .get_string("log_level")
-            .unwrap_or_else(|_| "info".to_string());
-        let oauth_redirect_port = cfg.get_int("oauth_redirect_port").unwrap_or(8080) as u16;
-        let thumbnails_preload = cfg.get_int("thumbnails_preload").unwrap_or(20) as usize;
-        let preload_threads = cfg.get_int("preload_threads").unwrap_or(4) as usize;
-        let sync_interval_minutes = cfg.get_int("sync_interval_minutes").unwrap_or(5) as u64;
-        let debug_console = cfg.get_bool("debug_console").unwrap_or(false);
-        let trace_spans = cfg.get_bool("trace_spans").unwrap_or(false);
-        let detect_faces = cfg.get_bool("detect_faces").unwrap_or(false);
-        let cache_path = cfg
-            .get_string("cache_path")
-            .map(PathBuf::from)
-            .unwrap_or_else(|_| {
-                dirs::home_dir()
-                    .unwrap_or_else(|| PathBuf::from("."))
-                    .join(".googlepicz")
-            });
+        let default_cache = profile_dir(profile);
+        let cache_path = PathBuf::from(layers.string("cache_path", &default_cache.to_string_lossy()));
 
         Self {
             log_level,
@@ -66,46 +424,233 @@ impl AppConfig {
             debug_console,
             trace_spans,
             detect_faces,
+            headless,
             cache_path,
+            slideshow_interval_secs,
+            slideshow_play_videos,
+            theme,
+            language,
+            image_cache_max_mb,
+            video_cache_max_mb,
+            prefetch_after_sync,
+            favorites_album_sync,
+            metrics_port,
+            unchanged_page_limit,
+            min_face_size,
+            face_detector,
+            profile: profile.map(str::to_string),
+            sources: layers.sources,
         }
     }
 
+    /// Where the value of `key` came from.
+    pub fn source(&self, key: &str) -> ConfigSource {
+        self.sources.keys.get(key).copied().unwrap_or(ConfigSource::Default)
+    }
+
+    /// Every effective setting as `(key, value, source)`, nested keys dotted.
+    pub fn entries(&self) -> Vec<(String, String, ConfigSource)> {
+        fn flatten(prefix: &str, table: &toml::value::Table, out: &mut Vec<(String, String)>) {
+            for (key, value) in table {
+                let key = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+                match value {
+                    toml::Value::Table(inner) => flatten(&key, inner, out),
+                    toml::Value::String(s) => out.push((key, s.clone())),
+                    other => out.push((key, other.to_string())),
+                }
+            }
+        }
+        let mut values = Vec::new();
+        if let Ok(toml::Value::Table(table)) = toml::Value::try_from(self) {
+            flatten("", &table, &mut values);
+        }
+        values
+            .into_iter()
+            .map(|(key, value)| {
+                let source = self.source(&key);
+                (key, value, source)
+            })
+            .collect()
+    }
+
+    /// Keys of `RESTART_KEYS` whose value differs from `running`.
+    pub fn restart_required(&self, running: &AppConfig) -> Vec<&'static str> {
+        let changed = [
+            self.oauth_redirect_port != running.oauth_redirect_port,
+            self.cache_path != running.cache_path,
+            self.debug_console != running.debug_console,
+            self.trace_spans != running.trace_spans,
+            self.detect_faces != running.detect_faces,
+            self.min_face_size != running.min_face_size,
+            self.face_detector != running.face_detector,
+            self.headless != running.headless,
+            self.favorites_album_sync != running.favorites_album_sync,
+            self.metrics_port != running.metrics_port,
+            self.unchanged_page_limit != running.unchanged_page_limit,
+        ];
+        RESTART_KEYS
+            .into_iter()
+            .zip(changed)
+            .filter_map(|(key, changed)| changed.then_some(key))
+            .collect()
+    }
+
+    /// Every setting outside its allowed range.
+    pub fn validate(&self) -> Result<(), Vec<ConfigIssue>> {
+        let mut issues = self.sources.unparsable.clone();
+        let mut check = |ok: bool, field, value: String, allowed: &str, fatal| {
+            if !ok {
+                issues.push(ConfigIssue { field, value, allowed: allowed.to_string(), fatal });
+            }
+        };
+        if let Some(profile) = &self.profile {
+            check(
+                valid_profile_name(profile),
+                "profile",
+                profile.clone(),
+                "letters, digits, - and _",
+                true,
+            );
+        }
+        check(
+            self.oauth_redirect_port != 0,
+            "oauth_redirect_port",
+            self.oauth_redirect_port.to_string(),
+            "1-65535",
+            true,
+        );
+        let cache_path = self.cache_path.to_string_lossy().to_string();
+        check(
+            !cache_path.trim().is_empty() && usable_dir(&self.cache_path),
+            "cache_path",
+            cache_path,
+            "a folder that exists or can be created",
+            true,
+        );
+        check(
+            self.thumbnails_preload <= MAX_THUMBS_PRELOAD,
+            "thumbnails_preload",
+            self.thumbnails_preload.to_string(),
+            "0-1000",
+            false,
+        );
+        check(
+            (1..=MAX_PRELOAD_THREADS).contains(&self.preload_threads),
+            "preload_threads",
+            self.preload_threads.to_string(),
+            "1-64",
+            false,
+        );
+        check(
+            self.sync_interval_minutes >= 1,
+            "sync_interval_minutes",
+            self.sync_interval_minutes.to_string(),
+            "1 or more",
+            false,
+        );
+        check(
+            self.slideshow_interval_secs >= 1,
+            "slideshow_interval_secs",
+            self.slideshow_interval_secs.to_string(),
+            "1 or more",
+            false,
+        );
+        check(THEMES.contains(&self.theme.as_str()), "theme", self.theme.clone(), "system, light or dark", false);
+        check(LANGUAGES.contains(&self.language.as_str()), "language", self.language.clone(), "en or de", false);
+        check(
+            (0.0..=1.0).contains(&self.min_face_size),
+            "min_face_size",
+            self.min_face_size.to_string(),
+            "0-1",
+            false,
+        );
+        check(
+            (0.0..=1.0).contains(&self.face_detector.confidence_threshold),
+            "face_detector.confidence_threshold",
+            self.face_detector.confidence_threshold.to_string(),
+            "0-1",
+            false,
+        );
+        if issues.is_empty() {
+            Ok(())
+        } else {
+            Err(issues)
+        }
+    }
+
+    /// Bring the recoverable issues found by `validate` into range and
+    /// return them for logging; fatal issues are left for the caller.
+    pub fn clamp_recoverable(&mut self) -> Vec<ConfigIssue> {
+        let issues: Vec<ConfigIssue> = match self.validate() {
+            Ok(()) => return Vec::new(),
+            Err(issues) => issues.into_iter().filter(|i| !i.fatal).collect(),
+        };
+        self.thumbnails_preload = self.thumbnails_preload.min(MAX_THUMBS_PRELOAD);
+        self.preload_threads = self.preload_threads.clamp(1, MAX_PRELOAD_THREADS);
+        self.sync_interval_minutes = self.sync_interval_minutes.max(1);
+        self.slideshow_interval_secs = self.slideshow_interval_secs.max(1);
+        if !THEMES.contains(&self.theme.as_str()) {
+            self.theme = THEMES[0].to_string();
+        }
+        if !LANGUAGES.contains(&self.language.as_str()) {
+            self.language = LANGUAGES[0].to_string();
+        }
+        self.min_face_size = clamp_unit(self.min_face_size, cache::DEFAULT_MIN_FACE_SIZE);
+        self.face_detector.confidence_threshold = clamp_unit(
+            self.face_detector.confidence_threshold,
+            face_recognition::DEFAULT_CONFIDENCE_THRESHOLD,
+        );
+        // Numbers that did not fit their type already fell back to the default
+        self.sources.unparsable.retain(|issue| issue.fatal);
+        issues
+    }
+
     pub fn apply_overrides(mut self, ov: &AppConfigOverrides) -> Self {
+        let mut flag = |key| {
+            self.sources.keys.insert(key, ConfigSource::Flag);
+        };
         if let Some(l) = &ov.log_level {
+            flag("log_level");
             self.log_level = l.clone();
         }
         if let Some(p) = ov.oauth_redirect_port {
+            flag("oauth_redirect_port");
             self.oauth_redirect_port = p;
         }
         if let Some(t) = ov.thumbnails_preload {
+            flag("thumbnails_preload");
             self.thumbnails_preload = t;
         }
         if let Some(pt) = ov.preload_threads {
+            flag("preload_threads");
             self.preload_threads = pt;
         }
         if let Some(s) = ov.sync_interval_minutes {
+            flag("sync_interval_minutes");
             self.sync_interval_minutes = s;
         }
         if ov.debug_console {
+            flag("debug_console");
             self.debug_console = true;
         }
         if ov.trace_spans {
+            flag("trace_spans");
             self.trace_spans = true;
         }
         if ov.detect_faces {
+            flag("detect_faces");
             self.detect_faces = true;
         }
+        if ov.headless {
+            flag("headless");
+            self.headless = true;
+        }
         self
     }
 
+    /// Write the settings to `path`, by default the config of `self.profile`.
     pub fn save_to(&self, path: Option<PathBuf>) -> std::io::Result<()> {
-        let path = match path {
-            Some(p) => p,
-            None => dirs::home_dir()
-                .unwrap_or_else(|| PathBuf::from("."))
-                .join(".googlepicz")
-                .join("config"),
-        };
+        let path = path.unwrap_or_else(|| config_path(self.profile.as_deref()));
         if let Some(parent) = path.parent() {
             std::fs::create_dir_all(parent)?;
         }
@@ -0,0 +1,129 @@
+//! Keeps a second GooglePicz from fighting the first over the cache and the
+//! sync. The running instance holds `~/.googlepicz/googlepicz.lock` with its
+//! PID and the port of a socket on localhost; a second instance asks it
+//! through that socket to raise its window, then exits.
+
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use sysinfo::{Pid, PidExt, System, SystemExt};
+
+pub const LOCK_FILE: &str = "googlepicz.lock";
+const FOCUS_REQUEST: &str = "focus";
+const SOCKET_TIMEOUT: Duration = Duration::from_secs(2);
+
+pub enum Instance {
+    /// No other instance is running; the lock is held until dropped
+    Primary(InstanceLock),
+    /// The instance with this PID is running and was asked to raise its window
+    Running(u32),
+}
+
+/// Lock of the running instance, removed again on drop.
+pub struct InstanceLock {
+    path: PathBuf,
+    listener: TcpListener,
+}
+
+impl InstanceLock {
+    /// Take the lock in `dir`, or hand off to the instance holding it. A lock
+    /// left behind by a crashed process is removed and taken over.
+    pub fn acquire(dir: &Path) -> io::Result<Instance> {
+        std::fs::create_dir_all(dir)?;
+        let path = dir.join(LOCK_FILE);
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+        let port = listener.local_addr()?.port();
+        let contents = format!("{} {}\n", std::process::id(), port);
+        // Linking a complete file in place means nobody reads a half written lock
+        let tmp = dir.join(format!("{}.{}", LOCK_FILE, std::process::id()));
+        std::fs::write(&tmp, &contents)?;
+        let result = Self::link(&tmp, &path);
+        let _ = std::fs::remove_file(&tmp);
+        match result? {
+            None => Ok(Instance::Primary(InstanceLock { path, listener })),
+            Some(pid) => Ok(Instance::Running(pid)),
+        }
+    }
+
+    /// Link `tmp` to `path`; the PID of the running instance holding `path`
+    /// if there is one.
+    fn link(tmp: &Path, path: &Path) -> io::Result<Option<u32>> {
+        // One retry after removing a stale lock
+        for _ in 0..2 {
+            match std::fs::hard_link(tmp, path) {
+                Ok(()) => return Ok(None),
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+                    let held = std::fs::read_to_string(path).unwrap_or_default();
+                    if let Some(pid) = hand_off(&held) {
+                        return Ok(Some(pid));
+                    }
+                    tracing::warn!("Removing stale instance lock {:?}", path);
+                    // Another instance may have replaced the stale lock meanwhile
+                    if std::fs::read_to_string(path).unwrap_or_default() == held {
+                        match std::fs::remove_file(path) {
+                            Ok(()) => {}
+                            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+                            Err(e) => return Err(e),
+                        }
+                    }
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("cannot take the instance lock {:?}", path),
+        ))
+    }
+
+    /// Call `on_focus` each time another instance asks to raise the window.
+    pub fn listen(&self, on_focus: impl Fn() + Send + 'static) -> io::Result<()> {
+        let listener = self.listener.try_clone()?;
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let _ = stream.set_read_timeout(Some(SOCKET_TIMEOUT));
+                let mut line = String::new();
+                if BufReader::new(stream).read_line(&mut line).is_ok() && line.trim() == FOCUS_REQUEST {
+                    on_focus();
+                }
+            }
+        });
+        Ok(())
+    }
+}
+
+impl Drop for InstanceLock {
+    fn drop(&mut self) {
+        let ours = std::fs::read_to_string(&self.path)
+            .ok()
+            .and_then(|c| parse(&c))
+            .is_some_and(|(pid, _)| pid == std::process::id());
+        if ours {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// `(pid, port)` from the contents of a lock file.
+fn parse(contents: &str) -> Option<(u32, u16)> {
+    let mut parts = contents.split_whitespace();
+    let pid = parts.next()?.parse().ok()?;
+    let port = parts.next()?.parse().ok()?;
+    Some((pid, port))
+}
+
+/// Ask the instance recorded in `contents` to raise its window and return
+/// its PID; `None` when the lock is stale because it cannot be read, its
+/// process is gone or its socket does not answer.
+fn hand_off(contents: &str) -> Option<u32> {
+    let (pid, port) = parse(contents)?;
+    // After a crash in a container the new process often gets the same PID
+    if pid == std::process::id() || !System::new().refresh_process(Pid::from_u32(pid)) {
+        return None;
+    }
+    let addr = SocketAddr::from((Ipv4Addr::LOCALHOST, port));
+    let mut stream = TcpStream::connect_timeout(&addr, SOCKET_TIMEOUT).ok()?;
+    stream.write_all(format!("{}\n", FOCUS_REQUEST).as_bytes()).ok()?;
+    Some(pid)
+}
@@ -2,14 +2,15 @@
 #![warn(rust_2018_idioms)]
 //! Main application entry point for GooglePicz.
 
-use auth::{authenticate, ensure_access_token_valid};
+use auth::{authenticate, authenticate_device, ensure_access_token_valid};
 use clap::Parser;
 use std::path::PathBuf;
-use sync::{Syncer, SyncTaskError};
+use sync::{SyncControl, SyncProgress, Syncer, SyncTaskError};
 use tokio::fs;
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
 use tokio::task::LocalSet;
 use tokio::time::Duration;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 use tracing_appender::rolling;
 use tracing_subscriber::fmt::writer::MakeWriterExt;
 use tracing_subscriber::EnvFilter;
@@ -24,7 +25,18 @@ struct PrevSyncState {
 #[cfg(feature = "tokio-console")]
 use console_subscriber;
 use ui;
+#[allow(dead_code)]
 mod config;
+mod instance;
+mod shutdown;
+// Nothing checks for updates yet
+#[allow(dead_code)]
+mod updater;
+
+/// Exit code of `--headless` when no valid token can be obtained.
+const EXIT_AUTH_FAILED: i32 = 3;
+/// Exit code of `--headless` when periodic sync stops after repeated failures.
+const EXIT_SYNC_ABORTED: i32 = 6;
 
 #[derive(Parser, Debug)]
 #[command(name = "googlepicz", about = "Google Photos Desktop Client")]
@@ -47,6 +59,9 @@ struct Cli {
     /// Path to config file
     #[arg(long)]
     config: Option<PathBuf>,
+    /// Use the settings, cache and tokens of this profile (defaults to GOOGLEPICZ_PROFILE, else the account used last)
+    #[arg(long)]
+    profile: Option<String>,
     /// Enable tokio console for debugging
     #[arg(long)]
     debug_console: bool,
@@ -56,6 +71,12 @@ struct Cli {
     /// Detect faces after downloading images
     #[arg(long)]
     detect_faces: bool,
+    /// Only sync in the background, without opening a window
+    #[arg(long)]
+    headless: bool,
+    /// Start even when GooglePicz is already running, e.g. for a second profile
+    #[arg(long)]
+    allow_multiple: bool,
 }
 
 #[cfg_attr(feature = "trace-spans", tracing::instrument)]
@@ -71,17 +92,75 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         debug_console: cli.debug_console,
         trace_spans: cli.trace_spans,
         detect_faces: cli.detect_faces,
+        headless: cli.headless,
     };
-    let cfg = config::AppConfig::load_from(cli.config.clone()).apply_overrides(&overrides);
+    // Without a flag or GOOGLEPICZ_PROFILE the account used last opens again
+    let profile = if cli.profile.is_none() && std::env::var_os(config::PROFILE_ENV).is_none() {
+        ui::last_account()
+    } else {
+        config::active_profile(cli.profile.as_deref())
+    };
+    // Token storage and the UI look the profile up in the environment
+    match &profile {
+        Some(name) => std::env::set_var(config::PROFILE_ENV, name),
+        None => std::env::remove_var(config::PROFILE_ENV),
+    }
+    let mut cfg = config::AppConfig::load_from(cli.config.clone(), profile.as_deref()).apply_overrides(&overrides);
+    let clamped = cfg.clamp_recoverable();
+    if let Err(issues) = cfg.validate() {
+        eprintln!("❌ {}", config::describe_issues(&issues));
+        std::process::exit(1);
+    }
     let log_dir = cfg.cache_path.clone();
     std::fs::create_dir_all(&log_dir)?;
     let file_appender = rolling::daily(&log_dir, "googlepicz.log");
-    let (file_writer, _guard) = tracing_appender::non_blocking(file_appender);
+    let (file_writer, guard) = tracing_appender::non_blocking(file_appender);
 
-    tracing_subscriber::fmt()
+    let subscriber = tracing_subscriber::fmt()
         .with_env_filter(EnvFilter::new(cfg.log_level.clone()))
         .with_writer(std::io::stdout.and(file_writer))
-        .init();
+        .with_filter_reloading();
+    let log_filter = subscriber.reload_handle();
+    subscriber.init();
+    let set_log_level = move |level: &str| log_filter.reload(EnvFilter::new(level)).map_err(|e| e.to_string());
+    for issue in clamped {
+        warn!("⚠️ Setting out of range, clamped: {}", issue);
+    }
+
+    // A second instance raises the window of the first and exits
+    let (focus_tx, focus_rx) = tokio::sync::mpsc::unbounded_channel();
+    let instance = if cli.allow_multiple {
+        None
+    } else {
+        match instance::InstanceLock::acquire(&config::base_dir()) {
+            Ok(instance::Instance::Primary(lock)) => {
+                if let Err(e) = lock.listen(move || {
+                    let _ = focus_tx.send(());
+                }) {
+                    warn!("⚠️ Other instances cannot reach this one: {}", e);
+                }
+                Some(lock)
+            }
+            Ok(instance::Instance::Running(pid)) => {
+                info!("👋 GooglePicz is already running (PID {}), switching to it", pid);
+                return Ok(());
+            }
+            Err(e) => {
+                warn!("⚠️ Cannot check for a running instance: {}", e);
+                None
+            }
+        }
+    };
+
+    if cfg.metrics_port != 0 {
+        #[cfg(feature = "prometheus")]
+        match sync::metrics::serve_prometheus(cfg.metrics_port) {
+            Ok(addr) => info!("📊 Serving metrics on http://{}/metrics", addr),
+            Err(e) => warn!("⚠️ {}", e),
+        }
+        #[cfg(not(feature = "prometheus"))]
+        warn!("⚠️ metrics_port is set, but this build lacks the prometheus feature");
+    }
 
     if cfg.debug_console {
         #[cfg(feature = "tokio-console")]
@@ -91,13 +170,129 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     }
 
+    let config_path = cli.config.clone().unwrap_or_else(|| config::config_path(profile.as_deref()));
+    let watcher = config::ConfigWatcher::new(config_path, profile.clone());
+    // The UI applies interval changes itself; without it the watcher does
+    let (control_tx, control_rx) = tokio::sync::mpsc::unbounded_channel();
+    let sync_control = cfg.headless.then(|| control_tx.clone());
+    tokio::spawn(watch_config(watcher, overrides, cfg.clone(), set_log_level, sync_control));
+
     let local = LocalSet::new();
-    local.run_until(main_inner(cfg)).await
+    let code = local.run_until(main_inner(cfg, control_tx, control_rx, focus_rx)).await?;
+    if code != 0 {
+        // `exit` skips destructors, so release the lock and flush the log first
+        drop(instance);
+        drop(guard);
+        std::process::exit(code);
+    }
+    Ok(())
 }
 
-#[cfg_attr(feature = "trace-spans", tracing::instrument(skip(cfg)))]
-async fn main_inner(cfg: config::AppConfig) -> Result<(), Box<dyn std::error::Error>> {
+/// Apply edits to the config file that the UI does not handle itself: the
+/// log level, and the sync interval when `sync_control` is given. Changes
+/// that only take effect after a restart are logged.
+async fn watch_config(
+    mut watcher: config::ConfigWatcher,
+    overrides: config::AppConfigOverrides,
+    startup: config::AppConfig,
+    set_log_level: impl Fn(&str) -> Result<(), String>,
+    sync_control: Option<UnboundedSender<SyncControl>>,
+) {
+    let mut log_level = startup.log_level.clone();
+    let mut sync_interval = startup.sync_interval_minutes;
+    let mut restart_required = Vec::new();
+    let mut ticker = tokio::time::interval(config::CONFIG_POLL_INTERVAL);
+    loop {
+        ticker.tick().await;
+        let Some(cfg) = watcher.poll() else {
+            continue;
+        };
+        // Flags still win over the edited file
+        let mut cfg = cfg.apply_overrides(&overrides);
+        cfg.clamp_recoverable();
+        if let Err(issues) = cfg.validate() {
+            warn!("⚠️ Ignoring config change. {}", config::describe_issues(&issues));
+            continue;
+        }
+        if cfg.log_level != log_level {
+            match set_log_level(&cfg.log_level) {
+                Ok(()) => info!("📝 Log level changed to {}", cfg.log_level),
+                Err(e) => error!("❌ Failed to change log level: {}", e),
+            }
+            log_level = cfg.log_level.clone();
+        }
+        if let Some(tx) = &sync_control {
+            if cfg.sync_interval_minutes != sync_interval {
+                info!("⏱️ Sync interval changed to {} minutes", cfg.sync_interval_minutes);
+                let _ = tx.send(SyncControl::SetInterval(Duration::from_secs(cfg.sync_interval_minutes * 60)));
+                sync_interval = cfg.sync_interval_minutes;
+            }
+        }
+        let required = cfg.restart_required(&startup);
+        if !required.is_empty() && required != restart_required {
+            warn!("🔁 Restart to apply: {}", required.join(", "));
+        }
+        restart_required = required;
+    }
+}
+
+/// Log sync progress until SIGINT/SIGTERM or an aborted sync; returns the exit code.
+async fn run_headless(
+    mut rx: UnboundedReceiver<SyncProgress>,
+    mut err_rx: UnboundedReceiver<SyncTaskError>,
+) -> i32 {
+    tokio::task::spawn_local(async move {
+        while let Some(progress) = rx.recv().await {
+            match progress {
+                SyncProgress::Started => info!("📥 Sync started"),
+                SyncProgress::Report(report) => info!(
+                    "📊 Fetched {} items ({} new, {} updated, {} unchanged)",
+                    report.fetched, report.inserted, report.updated, report.skipped
+                ),
+                SyncProgress::FacesScanned { processed, total } => {
+                    info!("🙂 Scanning faces: {}/{}", processed, total)
+                }
+                SyncProgress::Finished(total) => info!("✅ Sync finished: {} items", total),
+                SyncProgress::Retrying(wait) => warn!("🔁 Sync failed, retrying in {}s", wait),
+                SyncProgress::ItemSynced(_) | SyncProgress::Progress { .. } => {}
+            }
+        }
+    });
+    let signal = shutdown::shutdown_signal();
+    tokio::pin!(signal);
+    loop {
+        tokio::select! {
+            _ = &mut signal => {
+                info!("👋 Shutdown signal received");
+                return 0;
+            }
+            err = err_rx.recv() => match err {
+                Some(SyncTaskError::Status { message, .. }) => info!("ℹ️ {}", message),
+                Some(SyncTaskError::Aborted(msg)) => {
+                    error!("❌ {}", msg);
+                    return EXIT_SYNC_ABORTED;
+                }
+                Some(e) => error!("❌ {}", e),
+                None => return 1,
+            }
+        }
+    }
+}
+
+/// Runs the app and returns the exit code. Only `--headless` reports failures
+/// through it; the window shows them instead.
+#[cfg_attr(feature = "trace-spans", tracing::instrument(skip(cfg, control_tx, control_rx, focus_rx)))]
+async fn main_inner(
+    cfg: config::AppConfig,
+    control_tx: UnboundedSender<SyncControl>,
+    control_rx: UnboundedReceiver<SyncControl>,
+    focus_rx: UnboundedReceiver<()>,
+) -> Result<i32, Box<dyn std::error::Error>> {
     info!("🚀 Starting GooglePicz - Google Photos Manager");
+    let auth_failed = if cfg.headless { EXIT_AUTH_FAILED } else { 0 };
+    if let Some(profile) = &cfg.profile {
+        info!("👤 Profile: {}", profile);
+    }
     #[cfg(feature = "trace-spans")]
     let start = std::time::Instant::now();
     #[cfg(feature = "trace-spans")]
@@ -115,7 +310,7 @@ async fn main_inner(cfg: config::AppConfig) -> Result<(), Box<dyn std::error::Er
         error!("💡 Set them using:");
         error!("   export GOOGLE_CLIENT_ID=your_client_id");
         error!("   export GOOGLE_CLIENT_SECRET=your_client_secret");
-        return Ok(());
+        return Ok(auth_failed);
     }
 
     // Setup cache directory
@@ -157,12 +352,18 @@ async fn main_inner(cfg: config::AppConfig) -> Result<(), Box<dyn std::error::Er
     // Authenticate if needed
     if needs_auth {
         info!("🔑 Starting authentication process...");
-        match authenticate(cfg.oauth_redirect_port).await {
+        // Without a window there may be no browser either
+        let result = if cfg.headless {
+            authenticate_device(|url, code| info!("🔑 Open {} and enter the code {}", url, code)).await
+        } else {
+            authenticate(cfg.oauth_redirect_port).await
+        };
+        match result {
             Ok(_) => info!("✅ Authentication successful!"),
             Err(e) => {
                 error!("❌ Authentication failed: {}", e);
                 error!("💡 Please ensure your GOOGLE_CLIENT_ID and GOOGLE_CLIENT_SECRET are correct and you have internet access.");
-                return Ok(());
+                return Ok(auth_failed);
             }
         }
     }
@@ -170,13 +371,19 @@ async fn main_inner(cfg: config::AppConfig) -> Result<(), Box<dyn std::error::Er
     // Always ensure we have a valid token before continuing
     if let Err(e) = ensure_access_token_valid().await {
         error!("❌ Failed to validate access token: {}", e);
-        return Ok(());
+        return Ok(auth_failed);
     }
 
     info!("🔄 Initializing synchronization...");
     match Syncer::new(&db_path).await {
         Ok(mut syncer) => {
             syncer.set_face_detection(cfg.detect_faces);
+            syncer.set_min_face_size(cfg.min_face_size);
+            syncer.set_favorites_album_sync(cfg.favorites_album_sync);
+            syncer.set_unchanged_page_limit(cfg.unchanged_page_limit);
+            #[cfg(feature = "face-recognition")]
+            syncer.set_detector_config(cfg.face_detector.clone());
+            syncer.set_control(control_rx);
             let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
             let (err_tx, err_rx) = tokio::sync::mpsc::unbounded_channel::<SyncTaskError>();
             let (status_tx, status_rx) = tokio::sync::mpsc::unbounded_channel::<SyncTaskError>();
@@ -185,7 +392,10 @@ async fn main_inner(cfg: config::AppConfig) -> Result<(), Box<dyn std::error::Er
             let interval = Duration::from_secs(cfg.sync_interval_minutes * 60);
 
             info!("📥 Starting synchronization...");
-            if ensure_access_token_valid().await.is_ok() {
+            if cfg.headless {
+                // Periodic sync starts with a run anyway; skipping the
+                // blocking one lets a signal stop the first sync
+            } else if ensure_access_token_valid().await.is_ok() {
                 if let Err(e) = syncer
                     .sync_media_items(
                         Some(tx.clone()),
@@ -201,14 +411,22 @@ async fn main_inner(cfg: config::AppConfig) -> Result<(), Box<dyn std::error::Er
                 error!("❌ Cannot synchronize without a valid access token");
             }
 
+            // Without a window the UI channels would only duplicate every message
+            let (ui_tx, ui_err_tx) = if cfg.headless {
+                (None, None)
+            } else {
+                (Some(tx.clone()), Some(err_tx.clone()))
+            };
+            // Token refresh follows the syncer when the UI switches accounts
+            let profile = syncer.profile_watch();
             let (sync_handle, sync_shutdown) = if ensure_access_token_valid().await.is_ok() {
                 syncer.start_periodic_sync(
                     interval,
                     tx.clone(),
                     err_tx.clone(),
                     Some(status_tx.clone()),
-                    Some(tx.clone()),
-                    Some(err_tx.clone()),
+                    ui_tx.clone(),
+                    ui_err_tx.clone(),
                 )
             } else {
                 error!("❌ Cannot start periodic sync without a valid token");
@@ -217,18 +435,51 @@ async fn main_inner(cfg: config::AppConfig) -> Result<(), Box<dyn std::error::Er
                     tx.clone(),
                     err_tx.clone(),
                     Some(status_tx.clone()),
-                    Some(tx.clone()),
-                    Some(err_tx.clone()),
+                    ui_tx.clone(),
+                    ui_err_tx.clone(),
                 )
             };
 
             let (refresh_handle, refresh_shutdown) =
-                Syncer::start_token_refresh_task(
+                Syncer::start_token_refresh_task_for(
+                    profile,
                     Duration::from_secs(60),
                     err_tx.clone(),
-                    Some(err_tx.clone()),
+                    Some(status_tx.clone()),
+                    ui_err_tx.clone(),
                 );
 
+            // A raised threshold hides faces found earlier without detecting again
+            match cache::CacheManager::new(&db_path)
+                .and_then(|cache| cache.ignore_faces_below_confidence(cfg.face_detector.confidence_threshold))
+            {
+                Ok(0) => {}
+                Ok(count) => info!("🙈 Ignored {} faces below the confidence threshold", count),
+                Err(e) => error!("❌ Failed to apply face confidence threshold: {}", e),
+            }
+
+            // Catch up on photos synced before face detection was enabled
+            #[cfg(feature = "face-recognition")]
+            if cfg.detect_faces {
+                match cache::CacheManager::new(&db_path) {
+                    Ok(cache) => {
+                        let recognizer = face_recognition::FaceRecognizer::new(cfg.face_detector.clone())
+                            .with_min_face_size(cfg.min_face_size);
+                        let mut scanner = sync::FaceScanner::new(cache, recognizer);
+                        if let Ok(token) = ensure_access_token_valid().await {
+                            scanner = scanner.with_api_client(api_client::ApiClient::new(token));
+                        }
+                        let progress = tx.clone();
+                        tokio::spawn(async move {
+                            if let Err(e) = scanner.run(Some(progress)).await {
+                                error!("❌ Face scan failed: {}", e);
+                            }
+                        });
+                    }
+                    Err(e) => error!("❌ Cannot start face scan: {}", e),
+                }
+            }
+
             #[cfg(feature = "trace-spans")]
             {
                 sys.refresh_memory();
@@ -236,16 +487,29 @@ async fn main_inner(cfg: config::AppConfig) -> Result<(), Box<dyn std::error::Er
                                "mem_before_kb" = mem_before, "mem_after_kb" = sys.used_memory());
             }
 
+            if cfg.headless {
+                drop(status_rx);
+                info!("🖥️ Running headless, stop with Ctrl-C or SIGTERM");
+                let code = run_headless(rx, err_rx).await;
+                let _ = sync_shutdown.send(());
+                let _ = refresh_shutdown.send(());
+                let _ = sync_handle.await;
+                let _ = refresh_handle.await;
+                info!("👋 Headless sync stopped");
+                return Ok(code);
+            }
 
             let ui_thread = std::thread::spawn(move || {
-                if let Err(e) = ui::run(
-                    Some(rx),
-                    Some(err_rx),
-                    Some(status_rx),
+                if let Err(e) = ui::run(ui::UiFlags {
+                    progress: Some(rx),
+                    errors: Some(err_rx),
+                    status: Some(status_rx),
+                    control: Some(control_tx),
+                    focus: Some(focus_rx),
                     preload,
-                    cfg.preload_threads,
+                    preload_threads: cfg.preload_threads,
                     cache_dir,
-                ) {
+                }) {
                     error!("UI error: {}", e);
                 }
             });
@@ -258,6 +522,10 @@ async fn main_inner(cfg: config::AppConfig) -> Result<(), Box<dyn std::error::Er
             let _ = sync_handle.await;
             let _ = refresh_handle.await;
         }
+        Err(e) if cfg.headless => {
+            error!("❌ Failed to initialize syncer: {}", e);
+            return Ok(1);
+        }
         Err(e) => {
             error!("❌ Failed to initialize syncer: {}", e);
             error!("💡 The UI will still start, but photos may not be available until sync is working.");
@@ -267,9 +535,14 @@ async fn main_inner(cfg: config::AppConfig) -> Result<(), Box<dyn std::error::Er
                 tracing::info!(target = "app", "startup_time_ms" = start.elapsed().as_millis(),
                                "mem_before_kb" = mem_before, "mem_after_kb" = sys.used_memory());
             }
-            ui::run(None, None, None, cfg.thumbnails_preload, cfg.preload_threads, cfg.cache_path.clone())?;
+            ui::run(ui::UiFlags {
+                focus: Some(focus_rx),
+                preload: cfg.thumbnails_preload,
+                preload_threads: cfg.preload_threads,
+                ..ui::UiFlags::new(cfg.cache_path.clone())
+            })?;
         }
     }
 
-    Ok(())
+    Ok(0)
 }
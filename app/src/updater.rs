@@ -0,0 +1,87 @@
+//! Update checks. The packager publishes `update-manifest.json` with a
+//! detached ed25519 signature in `update-manifest.json.sig`; a manifest is
+//! only trusted when it verifies against the public key embedded at build
+//! time through `GOOGLEPICZ_UPDATE_PUBLIC_KEY`. Downloading and installing
+//! the update is not handled yet.
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use ring::signature::{UnparsedPublicKey, ED25519};
+use serde::Deserialize;
+
+/// Base64 ed25519 key the release manifests are signed with.
+pub const PUBLIC_KEY: Option<&str> = option_env!("GOOGLEPICZ_UPDATE_PUBLIC_KEY");
+
+#[derive(Debug, thiserror::Error)]
+pub enum UpdateError {
+    #[error("this build has no update key embedded")]
+    NoPublicKey,
+    #[error("failed to fetch {0}: {1}")]
+    Fetch(String, String),
+    #[error("invalid update manifest: {0}")]
+    Invalid(String),
+    #[error("update manifest signature does not match")]
+    BadSignature,
+}
+
+/// A release as described by `update-manifest.json`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct UpdateManifest {
+    pub version: String,
+    /// `linux`, `macos` or `windows`
+    pub platform: String,
+    /// `x86_64`, `aarch64` or `universal`
+    pub arch: String,
+    /// File name of the installer next to the manifest
+    pub artifact: String,
+    pub size: u64,
+    /// Hex encoded SHA256 of the installer
+    pub sha256: String,
+    pub released_at: DateTime<Utc>,
+}
+
+impl UpdateManifest {
+    /// Whether this release is newer than `current`, comparing dotted numbers.
+    pub fn is_newer_than(&self, current: &str) -> bool {
+        fn parts(version: &str) -> Vec<u64> {
+            version
+                .split(['.', '-', '+'])
+                .map_while(|p| p.parse().ok())
+                .collect()
+        }
+        parts(&self.version) > parts(current)
+    }
+}
+
+/// Parse `manifest` after checking `signature` (base64, as written to the
+/// `.sig` file) against `public_key` (base64).
+pub fn verify_manifest(manifest: &[u8], signature: &str, public_key: &str) -> Result<UpdateManifest, UpdateError> {
+    let key = BASE64
+        .decode(public_key.trim())
+        .map_err(|e| UpdateError::Invalid(format!("public key: {}", e)))?;
+    let signature = BASE64.decode(signature.trim()).map_err(|_| UpdateError::BadSignature)?;
+    UnparsedPublicKey::new(&ED25519, key)
+        .verify(manifest, &signature)
+        .map_err(|_| UpdateError::BadSignature)?;
+    serde_json::from_slice(manifest).map_err(|e| UpdateError::Invalid(e.to_string()))
+}
+
+/// Download the manifest at `url` and its signature at `<url>.sig`, and
+/// verify them against the embedded `PUBLIC_KEY`.
+pub async fn fetch_manifest(url: &str) -> Result<UpdateManifest, UpdateError> {
+    let public_key = PUBLIC_KEY.ok_or(UpdateError::NoPublicKey)?;
+    let get = |url: String| async move {
+        let response = reqwest::get(&url)
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| UpdateError::Fetch(url.clone(), e.to_string()))?;
+        response
+            .bytes()
+            .await
+            .map_err(|e| UpdateError::Fetch(url, e.to_string()))
+    };
+    let manifest = get(url.to_string()).await?;
+    let signature = get(format!("{}.sig", url)).await?;
+    verify_manifest(&manifest, &String::from_utf8_lossy(&signature), public_key)
+}
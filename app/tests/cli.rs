@@ -98,3 +98,102 @@ fn sync_cli_search_albums_no_cache() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+
+#[test]
+fn sync_cli_rejects_invalid_config() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp_home = TempDir::new()?;
+    let config = tmp_home.path().join("config");
+    std::fs::write(&config, "oauth_redirect_port = 0\npreload_threads = 0\n")?;
+    let mut cmd = Command::cargo_bin("sync_cli")?;
+    cmd.args(["--config", config.to_str().unwrap(), "status"]);
+    cmd.env("MOCK_API_CLIENT", "1");
+    cmd.env("MOCK_KEYRING", "1");
+    cmd.env("HOME", tmp_home.path());
+    // Only the fatal issue is listed; the thread count is clamped
+    cmd.assert()
+        .code(7)
+        .stderr(predicate::str::contains("oauth_redirect_port = \"0\" (allowed: 1-65535)"))
+        .stderr(predicate::str::contains("preload_threads").not());
+    Ok(())
+}
+
+#[test]
+fn sync_cli_reports_out_of_range_numbers_as_written() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp_home = TempDir::new()?;
+    let config = tmp_home.path().join("config");
+    std::fs::write(&config, "oauth_redirect_port = 70000\nthumbnails_preload = -5\n")?;
+    let mut cmd = Command::cargo_bin("sync_cli")?;
+    cmd.args(["--config", config.to_str().unwrap(), "config", "show"]);
+    cmd.env("MOCK_API_CLIENT", "1");
+    cmd.env("MOCK_KEYRING", "1");
+    cmd.env("HOME", tmp_home.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("oauth_redirect_port = \"70000\" (allowed: 1-65535)"))
+        .stdout(predicate::str::contains("thumbnails_preload = \"-5\" (allowed: 0-1000)"));
+
+    // The negative count falls back to the default, the port stops the command
+    let mut cmd = Command::cargo_bin("sync_cli")?;
+    cmd.args(["--config", config.to_str().unwrap(), "status"]);
+    cmd.env("MOCK_API_CLIENT", "1");
+    cmd.env("MOCK_KEYRING", "1");
+    cmd.env("HOME", tmp_home.path());
+    cmd.assert()
+        .code(7)
+        .stderr(predicate::str::contains("oauth_redirect_port = \"70000\""))
+        .stderr(predicate::str::contains("thumbnails_preload").not());
+    Ok(())
+}
+
+#[test]
+fn sync_cli_config_show_sources() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp_home = TempDir::new()?;
+    let config = tmp_home.path().join("config");
+    std::fs::write(&config, "sync_interval_minutes = 10\ntheme = \"dark\"\n")?;
+    let mut cmd = Command::cargo_bin("sync_cli")?;
+    cmd.args(["--config", config.to_str().unwrap(), "config", "show", "--sources"]);
+    cmd.env("MOCK_API_CLIENT", "1");
+    cmd.env("MOCK_KEYRING", "1");
+    cmd.env("HOME", tmp_home.path());
+    cmd.env("GOOGLEPICZ_SYNC_INTERVAL_MINUTES", "15");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("sync_interval_minutes = 15 (env)"))
+        .stdout(predicate::str::contains("theme = dark (file)"))
+        .stdout(predicate::str::contains("language = en (default)"));
+    Ok(())
+}
+
+#[test]
+fn sync_cli_profile_create_and_list() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp_home = TempDir::new()?;
+    for name in ["work", "personal"] {
+        let mut cmd = Command::cargo_bin("sync_cli")?;
+        cmd.args(["profile", "create", name]);
+        cmd.env("HOME", tmp_home.path());
+        cmd.assert().success();
+    }
+    let config = tmp_home.path().join(".googlepicz").join("profiles").join("work").join("config");
+    assert!(config.exists());
+
+    let mut cmd = Command::cargo_bin("sync_cli")?;
+    cmd.args(["profile", "create", "work"]);
+    cmd.env("HOME", tmp_home.path());
+    cmd.assert().failure().stderr(predicate::str::contains("already exists"));
+
+    let mut cmd = Command::cargo_bin("sync_cli")?;
+    cmd.args(["profile", "list"]);
+    cmd.env("HOME", tmp_home.path());
+    cmd.env("GOOGLEPICZ_PROFILE", "work");
+    cmd.assert().success().stdout("  personal\n* work\n");
+
+    // The cache of a profile defaults to its own folder
+    let mut cmd = Command::cargo_bin("sync_cli")?;
+    cmd.args(["--profile", "personal", "config", "show"]);
+    cmd.env("HOME", tmp_home.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("# profile: personal"))
+        .stdout(predicate::str::contains(format!("profiles{}personal", std::path::MAIN_SEPARATOR)));
+    Ok(())
+}
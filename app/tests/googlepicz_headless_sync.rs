@@ -1,37 +1,53 @@
-use assert_cmd::cargo::cargo_bin;
+#![cfg(unix)]
+use assert_cmd::prelude::*;
 use cache::CacheManager;
+use std::process::Command;
+use std::time::{Duration, Instant};
 use tempfile::TempDir;
-use tokio::{process::Command, time::{timeout, Duration}};
 
-#[tokio::test]
-async fn googlepicz_headless_sync() {
-    // Setup temporary home directory
-    let dir = TempDir::new().expect("temp dir");
-
-    let bin = cargo_bin("googlepicz");
-    if which::which("xvfb-run").is_err() {
-        return; // skip if xvfb-run not available
-    }
-    let mut child = Command::new("xvfb-run");
-    child.arg("-a")
-        .arg(bin)
-        .arg("--sync-interval-minutes").arg("1")
+fn googlepicz(home: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("googlepicz").unwrap();
+    cmd.arg("--headless")
         .env("MOCK_API_CLIENT", "1")
         .env("MOCK_KEYRING", "1")
-        .env("MOCK_ACCESS_TOKEN", "token")
-        .env("MOCK_REFRESH_TOKEN", "refresh")
         .env("GOOGLE_CLIENT_ID", "id")
         .env("GOOGLE_CLIENT_SECRET", "secret")
-        .env("HOME", dir.path());
-    let mut child = child.spawn().expect("spawn googlepicz");
+        .env("HOME", home.path());
+    cmd
+}
 
-    let _ = timeout(Duration::from_secs(10), child.wait())
-        .await
-        .expect("process timed out")
-        .expect("failed to wait");
+#[test]
+fn googlepicz_headless_sync() {
+    let dir = TempDir::new().expect("temp dir");
+    let mut child = googlepicz(&dir)
+        .env("MOCK_ACCESS_TOKEN", "token")
+        .env("MOCK_REFRESH_TOKEN", "refresh")
+        .spawn()
+        .expect("spawn googlepicz");
 
     let db_path = dir.path().join(".googlepicz").join("cache.sqlite");
-    let cache = CacheManager::new(&db_path).expect("open cache");
-    let items = cache.get_all_media_items().expect("read items");
-    assert!(!items.is_empty(), "Cache should contain media items");
+    let synced = || {
+        CacheManager::new(&db_path)
+            .and_then(|cache| cache.get_all_media_items())
+            .map(|items| !items.is_empty())
+            .unwrap_or(false)
+    };
+    let start = Instant::now();
+    while !synced() && start.elapsed() < Duration::from_secs(30) {
+        std::thread::sleep(Duration::from_millis(100));
+    }
+
+    Command::new("kill")
+        .args(["-TERM", &child.id().to_string()])
+        .status()
+        .unwrap();
+    let status = child.wait().expect("failed to wait");
+    assert!(synced(), "Cache should contain media items");
+    assert!(status.success(), "SIGTERM should shut down cleanly: {status:?}");
+}
+
+#[test]
+fn googlepicz_headless_exits_when_auth_fails() {
+    let dir = TempDir::new().expect("temp dir");
+    googlepicz(&dir).env_remove("GOOGLE_CLIENT_ID").assert().code(3);
 }
@@ -0,0 +1,72 @@
+#![cfg(unix)]
+use assert_cmd::prelude::*;
+use std::path::Path;
+use std::process::{Child, Command};
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+
+fn googlepicz(home: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("googlepicz").unwrap();
+    cmd.arg("--headless")
+        .env("MOCK_API_CLIENT", "1")
+        .env("MOCK_KEYRING", "1")
+        .env("MOCK_ACCESS_TOKEN", "token")
+        .env("MOCK_REFRESH_TOKEN", "refresh")
+        .env("GOOGLE_CLIENT_ID", "id")
+        .env("GOOGLE_CLIENT_SECRET", "secret")
+        .env("HOME", home.path());
+    cmd
+}
+
+fn lock_pid(lock: &Path) -> Option<u32> {
+    std::fs::read_to_string(lock).ok()?.split_whitespace().next()?.parse().ok()
+}
+
+/// Wait until `child` holds the lock.
+fn wait_for_lock(lock: &Path, child: &Child) {
+    let start = Instant::now();
+    while lock_pid(lock) != Some(child.id()) && start.elapsed() < Duration::from_secs(30) {
+        std::thread::sleep(Duration::from_millis(100));
+    }
+    assert_eq!(lock_pid(lock), Some(child.id()));
+}
+
+fn stop(mut child: Child) {
+    Command::new("kill").args(["-TERM", &child.id().to_string()]).status().unwrap();
+    assert!(child.wait().unwrap().success());
+}
+
+#[test]
+fn second_instance_hands_off_unless_allowed() {
+    let dir = TempDir::new().unwrap();
+    let lock = dir.path().join(".googlepicz").join("googlepicz.lock");
+    let first = googlepicz(&dir).spawn().unwrap();
+    wait_for_lock(&lock, &first);
+
+    let output = googlepicz(&dir).output().unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("already running"));
+
+    // The escape hatch starts anyway and leaves the lock alone
+    let second = googlepicz(&dir).arg("--allow-multiple").spawn().unwrap();
+    std::thread::sleep(Duration::from_secs(1));
+    stop(second);
+    assert_eq!(lock_pid(&lock), Some(first.id()));
+
+    stop(first);
+    assert!(!lock.exists());
+}
+
+#[test]
+fn stale_lock_is_taken_over() {
+    let dir = TempDir::new().unwrap();
+    let lock = dir.path().join(".googlepicz").join("googlepicz.lock");
+    let mut gone = Command::new("true").spawn().unwrap();
+    gone.wait().unwrap();
+    std::fs::create_dir_all(lock.parent().unwrap()).unwrap();
+    std::fs::write(&lock, format!("{} 1\n", gone.id())).unwrap();
+
+    let child = googlepicz(&dir).spawn().unwrap();
+    wait_for_lock(&lock, &child);
+    stop(child);
+}
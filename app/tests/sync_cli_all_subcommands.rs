@@ -26,6 +26,7 @@ fn sample_item(id: &str) -> api_client::MediaItem {
             video: None,
         },
         filename: format!("{}.jpg", id),
+        is_favorite: false,
     }
 }
 
@@ -0,0 +1,81 @@
+use assert_cmd::prelude::*;
+use predicates::prelude::*;
+use std::process::Command;
+use tempfile::TempDir;
+
+#[test]
+fn auth_login_with_mock_tokens() {
+    let dir = TempDir::new().unwrap();
+    let mut cmd = Command::cargo_bin("sync_cli").unwrap();
+    cmd.args(["auth", "login"])
+        .env("MOCK_KEYRING", "1")
+        .env("MOCK_ACCESS_TOKEN", "tok")
+        .env("MOCK_REFRESH_TOKEN", "ref")
+        .env("HOME", dir.path());
+    cmd.assert().success().stdout(predicate::str::contains("Login successful"));
+}
+
+#[test]
+fn auth_device_login_prints_code() {
+    let dir = TempDir::new().unwrap();
+    let mut cmd = Command::cargo_bin("sync_cli").unwrap();
+    cmd.args(["auth", "login", "--device"])
+        .env("MOCK_KEYRING", "1")
+        .env("MOCK_ACCESS_TOKEN", "tok")
+        .env("HOME", dir.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("enter code MOCK-CODE"));
+}
+
+#[test]
+fn auth_status_json_without_tokens() {
+    let dir = TempDir::new().unwrap();
+    let mut cmd = Command::cargo_bin("sync_cli").unwrap();
+    cmd.args(["auth", "status", "--json"])
+        .env("MOCK_KEYRING", "1")
+        .env("HOME", dir.path());
+    let output = cmd.output().unwrap();
+    assert_eq!(output.status.code(), Some(3));
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["backend"], "mock");
+    assert_eq!(json["has_access_token"], false);
+}
+
+#[cfg(not(feature = "file-store"))]
+#[test]
+fn auth_status_keyring_unavailable() {
+    let dir = TempDir::new().unwrap();
+    let mut cmd = Command::cargo_bin("sync_cli").unwrap();
+    cmd.args(["auth", "status"])
+        .env("MOCK_KEYRING_FAIL", "1")
+        .env("HOME", dir.path());
+    cmd.assert().code(5).stderr(predicate::str::contains("Keyring error"));
+}
+
+#[cfg(feature = "file-store")]
+#[test]
+fn auth_login_status_logout_round_trip() {
+    let dir = TempDir::new().unwrap();
+    let run = |args: &[&str]| {
+        let mut cmd = Command::cargo_bin("sync_cli").unwrap();
+        cmd.arg("--use-file-store")
+            .args(args)
+            .env("MOCK_ACCESS_TOKEN", "tok")
+            .env("MOCK_REFRESH_TOKEN", "ref")
+            .env("MOCK_ACCOUNT", "user@example.com")
+            .env("HOME", dir.path());
+        cmd.output().unwrap()
+    };
+    assert!(run(&["auth", "login"]).status.success());
+
+    let output = run(&["auth", "status", "--json"]);
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["account"], "user@example.com");
+    assert_eq!(json["backend"], "file");
+    assert_eq!(json["access_token_expired"], false);
+
+    assert!(run(&["auth", "logout"]).status.success());
+    assert_eq!(run(&["auth", "status"]).status.code(), Some(3));
+}
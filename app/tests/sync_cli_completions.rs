@@ -0,0 +1,41 @@
+use assert_cmd::prelude::*;
+use std::process::Command;
+
+#[test]
+fn completions_for_each_shell() {
+    for shell in ["bash", "zsh", "fish"] {
+        let output = Command::cargo_bin("sync_cli")
+            .unwrap()
+            .args(["completions", shell])
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "completions failed for {}", shell);
+        let script = String::from_utf8(output.stdout).unwrap();
+        assert!(!script.trim().is_empty(), "empty completions for {}", shell);
+        assert!(script.contains("sync_cli"));
+    }
+}
+
+#[test]
+fn version_json_parses() {
+    let output = Command::cargo_bin("sync_cli")
+        .unwrap()
+        .args(["--version", "--json"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["version"], env!("CARGO_PKG_VERSION"));
+    assert!(json["git_hash"].is_string());
+    assert!(json["features"].is_array());
+    assert_eq!(json["schema_version"], cache::SCHEMA_VERSION);
+}
+
+#[test]
+fn json_requires_version() {
+    Command::cargo_bin("sync_cli")
+        .unwrap()
+        .arg("--json")
+        .assert()
+        .failure();
+}
@@ -0,0 +1,57 @@
+use assert_cmd::prelude::*;
+use cache::CacheManager;
+use predicates::str::contains;
+use std::process::Command;
+use tempfile::tempdir;
+
+fn build_cmd(home: &std::path::Path) -> Command {
+    let mut cmd = Command::cargo_bin("sync_cli").unwrap();
+    cmd.env("MOCK_API_CLIENT", "1");
+    cmd.env("MOCK_KEYRING", "1");
+    cmd.env("MOCK_REFRESH_TOKEN", "t");
+    cmd.env("HOME", home);
+    cmd
+}
+
+fn check<'a>(json: &'a serde_json::Value, name: &str) -> &'a serde_json::Value {
+    json["checks"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|c| c["name"] == name)
+        .unwrap()
+}
+
+#[test]
+fn doctor_json_reports_each_check() {
+    let dir = tempdir().unwrap();
+    let base = dir.path().join(".googlepicz");
+    std::fs::create_dir_all(&base).unwrap();
+    CacheManager::new(&base.join("cache.sqlite")).unwrap();
+
+    let output = build_cmd(dir.path()).args(["doctor", "--json"]).output().unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    for name in ["tokens", "keyring", "cache", "schema", "state", "disk", "api"] {
+        assert!(check(&json, name)["status"].is_string(), "missing check {}", name);
+    }
+    assert_eq!(check(&json, "tokens")["status"], "fail");
+    assert_eq!(check(&json, "cache")["status"], "pass");
+    assert_eq!(check(&json, "api")["status"], "pass");
+    assert_eq!(json["overall"], "fail");
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn doctor_flags_corrupt_state_file() {
+    let dir = tempdir().unwrap();
+    let base = dir.path().join(".googlepicz");
+    std::fs::create_dir_all(&base).unwrap();
+    std::fs::write(base.join("cache.state.json"), "{not json").unwrap();
+
+    build_cmd(dir.path())
+        .arg("doctor")
+        .assert()
+        .code(2)
+        .stdout(contains("[FAIL] state"))
+        .stdout(contains("hint:"));
+}
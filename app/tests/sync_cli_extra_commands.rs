@@ -1,4 +1,5 @@
 use assert_cmd::prelude::*;
+use predicates::prelude::*;
 use predicates::str::contains;
 use std::process::Command;
 use tempfile::tempdir;
@@ -28,6 +29,7 @@ fn sample_item(id: &str) -> api_client::MediaItem {
             video: None,
         },
         filename: format!("{}.jpg", id),
+        is_favorite: false,
     }
 }
 
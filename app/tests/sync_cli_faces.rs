@@ -1,4 +1,5 @@
 use assert_cmd::prelude::*;
+use predicates::prelude::*;
 use predicates::str::contains;
 use std::process::Command;
 use tempfile::tempdir;
@@ -27,6 +28,7 @@ fn sample_item(id: &str) -> api_client::MediaItem {
             video: None,
         },
         filename: format!("{}.jpg", id),
+        is_favorite: false,
     }
 }
 
@@ -39,7 +41,7 @@ fn export_faces_writes_file() {
     let cache = CacheManager::new(&db).unwrap();
     let item = sample_item("1");
     cache.insert_media_item(&item).unwrap();
-    let faces = vec![FaceData { bbox: [0,0,10,10], name: Some("a".into()) }];
+    let faces = vec![FaceData { bbox: [0,0,10,10], name: Some("a".into()), ignored: false, confidence: None }];
     let json = serde_json::to_string(&faces).unwrap();
     cache.insert_faces(&item.id, &json).unwrap();
 
@@ -63,7 +65,7 @@ fn import_faces_populates_cache() {
     let item = sample_item("1");
     cache.insert_media_item(&item).unwrap();
 
-    let faces = vec![cache::FaceExport { media_item_id: item.id.clone(), faces: vec![FaceData { bbox: [1,1,5,5], name: None }] }];
+    let faces = vec![cache::FaceExport { media_item_id: item.id.clone(), faces: vec![FaceData { bbox: [1,1,5,5], name: None, ignored: false, confidence: None }] }];
     let file_path = dir.path().join("faces.json");
     std::fs::write(&file_path, serde_json::to_vec(&faces).unwrap()).unwrap();
 
@@ -93,7 +95,7 @@ fn set_favorite_updates_item() {
         .success()
         .stdout(contains("Favorite for 1 set to true"));
 
-    let conn = cache.conn.lock().unwrap();
+    let conn = cache.lock_conn().unwrap();
     let fav: i64 = conn.query_row("SELECT is_favorite FROM media_items WHERE id = '1'", [], |r| r.get(0)).unwrap();
     assert_eq!(fav, 1);
 }
@@ -109,7 +111,7 @@ fn search_with_faces_only_lists_matches() {
     let item2 = sample_item("2");
     cache.insert_media_item(&item1).unwrap();
     cache.insert_media_item(&item2).unwrap();
-    let faces = vec![FaceData { bbox: [0,0,5,5], name: None }];
+    let faces = vec![FaceData { bbox: [0,0,5,5], name: None, ignored: false, confidence: None }];
     let json = serde_json::to_string(&faces).unwrap();
     cache.insert_faces(&item1.id, &json).unwrap();
 
@@ -0,0 +1,107 @@
+use assert_cmd::prelude::*;
+use cache::CacheManager;
+use predicates::str::contains;
+use std::process::Command;
+use tempfile::tempdir;
+
+fn build_cmd(home: &std::path::Path) -> Command {
+    let mut cmd = Command::cargo_bin("sync_cli").unwrap();
+    cmd.env("MOCK_API_CLIENT", "1");
+    cmd.env("MOCK_KEYRING", "1");
+    cmd.env("MOCK_REFRESH_TOKEN", "test");
+    cmd.env("HOME", home);
+    cmd
+}
+
+fn sample_item(id: &str) -> api_client::MediaItem {
+    api_client::MediaItem {
+        id: id.to_string(),
+        description: None,
+        product_url: "http://example.com".into(),
+        base_url: "http://example.com/base".into(),
+        mime_type: "image/jpeg".into(),
+        media_metadata: api_client::MediaMetadata {
+            creation_time: "2023-01-01T00:00:00Z".into(),
+            width: "1".into(),
+            height: "1".into(),
+            video: None,
+        },
+        filename: format!("{}.jpg", id),
+        is_favorite: false,
+    }
+}
+
+fn seed(home: &std::path::Path) -> CacheManager {
+    let base = home.join(".googlepicz");
+    std::fs::create_dir_all(&base).unwrap();
+    let cache = CacheManager::new(&base.join("cache.sqlite")).unwrap();
+    cache.insert_media_item(&sample_item("1")).unwrap();
+    cache.insert_media_item(&sample_item("2")).unwrap();
+    cache
+        .insert_faces("1", r#"[{"bbox":[1,2,3,4],"name":"Ann"},{"bbox":[5,6,7,8],"name":null}]"#)
+        .unwrap();
+    cache.insert_faces("2", r#"[{"bbox":[0,0,9,9],"name":"Annie"}]"#).unwrap();
+    cache
+}
+
+fn people_json(home: &std::path::Path) -> serde_json::Value {
+    let output = build_cmd(home).args(["people", "list", "--json"]).output().unwrap();
+    assert!(output.status.success());
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+#[test]
+fn people_list_rename_merge() {
+    let dir = tempdir().unwrap();
+    let cache = seed(dir.path());
+
+    let people = people_json(dir.path());
+    assert_eq!(people.as_array().unwrap().len(), 2);
+
+    build_cmd(dir.path())
+        .args(["people", "rename", "Annie", "Anne"])
+        .assert()
+        .success()
+        .stdout(contains("Renamed 1 faces from Annie to Anne"));
+
+    let output = build_cmd(dir.path())
+        .args(["people", "merge", "Ann", "Anne", "--json"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["changed"], 1);
+
+    let people = people_json(dir.path());
+    assert_eq!(people, serde_json::json!([{ "name": "Ann", "photo_count": 2 }]));
+    assert_eq!(cache.get_faces("2").unwrap().unwrap()[0].name.as_deref(), Some("Ann"));
+}
+
+#[test]
+fn faces_show_prints_boxes() {
+    let dir = tempdir().unwrap();
+    seed(dir.path());
+    build_cmd(dir.path())
+        .args(["faces", "show", "1"])
+        .assert()
+        .success()
+        .stdout(contains("0: x=1 y=2 w=3 h=4 name=Ann"))
+        .stdout(contains("1: x=5 y=6 w=7 h=8 name=-"));
+
+    let output = build_cmd(dir.path())
+        .args(["faces", "show", "1", "--json"])
+        .output()
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json[0]["bbox"], serde_json::json!([1, 2, 3, 4]));
+}
+
+#[test]
+fn people_merge_unknown_fails() {
+    let dir = tempdir().unwrap();
+    seed(dir.path());
+    build_cmd(dir.path())
+        .args(["people", "merge", "Ann", "Nobody"])
+        .assert()
+        .failure();
+}
@@ -0,0 +1,69 @@
+use assert_cmd::prelude::*;
+use cache::CacheManager;
+use predicates::str::contains;
+use std::process::Command;
+use tempfile::tempdir;
+
+fn build_cmd(home: &std::path::Path) -> Command {
+    let mut cmd = Command::cargo_bin("sync_cli").unwrap();
+    cmd.env("MOCK_API_CLIENT", "1");
+    cmd.env("MOCK_KEYRING", "1");
+    cmd.env("MOCK_REFRESH_TOKEN", "test");
+    cmd.env("HOME", home);
+    cmd
+}
+
+fn sample_item(id: &str) -> api_client::MediaItem {
+    api_client::MediaItem {
+        id: id.to_string(),
+        description: None,
+        product_url: "http://example.com".into(),
+        // Nothing listens here, so every download fails with a network error.
+        base_url: "http://127.0.0.1:9/base".into(),
+        mime_type: "image/jpeg".into(),
+        media_metadata: api_client::MediaMetadata {
+            creation_time: "2023-01-01T00:00:00Z".into(),
+            width: "1".into(),
+            height: "1".into(),
+            video: None,
+        },
+        filename: format!("{}.jpg", id),
+        is_favorite: false,
+    }
+}
+
+fn seed(home: &std::path::Path) -> std::path::PathBuf {
+    let base = home.join(".googlepicz");
+    std::fs::create_dir_all(&base).unwrap();
+    let cache = CacheManager::new(&base.join("cache.sqlite")).unwrap();
+    cache.insert_media_item(&sample_item("1")).unwrap();
+    cache.insert_media_item(&sample_item("2")).unwrap();
+    base
+}
+
+#[test]
+fn prefetch_skips_cached_thumbnails() {
+    let dir = tempdir().unwrap();
+    let base = seed(dir.path());
+    let thumbs = base.join("thumbnails");
+    std::fs::create_dir_all(&thumbs).unwrap();
+    std::fs::write(thumbs.join("1.jpg"), b"img").unwrap();
+    std::fs::write(thumbs.join("2.jpg"), b"img").unwrap();
+
+    build_cmd(dir.path())
+        .arg("prefetch-thumbnails")
+        .assert()
+        .success()
+        .stdout(contains("All 2 thumbnails already cached"));
+}
+
+#[test]
+fn prefetch_reports_permanent_failures() {
+    let dir = tempdir().unwrap();
+    seed(dir.path());
+    build_cmd(dir.path())
+        .args(["prefetch-thumbnails", "--limit", "1", "--concurrency", "1"])
+        .assert()
+        .code(1)
+        .stdout(contains("Downloaded: 0, cached: 0, failed: 1"));
+}
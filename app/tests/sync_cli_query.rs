@@ -0,0 +1,109 @@
+use assert_cmd::prelude::*;
+use cache::CacheManager;
+use predicates::str::contains;
+use std::process::Command;
+use tempfile::tempdir;
+
+fn build_cmd(home: &std::path::Path) -> Command {
+    let mut cmd = Command::cargo_bin("sync_cli").unwrap();
+    cmd.env("MOCK_API_CLIENT", "1");
+    cmd.env("MOCK_KEYRING", "1");
+    cmd.env("MOCK_REFRESH_TOKEN", "test");
+    cmd.env("HOME", home);
+    cmd
+}
+
+fn sample_item(id: &str, day: u32, mime: &str) -> api_client::MediaItem {
+    api_client::MediaItem {
+        id: id.to_string(),
+        description: Some(format!("desc {}", id)),
+        product_url: "http://example.com".into(),
+        base_url: "http://example.com/base".into(),
+        mime_type: mime.into(),
+        media_metadata: api_client::MediaMetadata {
+            creation_time: format!("2023-01-{:02}T00:00:00Z", day),
+            width: "1".into(),
+            height: "1".into(),
+            video: None,
+        },
+        filename: format!("{}.jpg", id),
+        is_favorite: false,
+    }
+}
+
+fn seed(home: &std::path::Path) {
+    let base = home.join(".googlepicz");
+    std::fs::create_dir_all(&base).unwrap();
+    let cache = CacheManager::new(&base.join("cache.sqlite")).unwrap();
+    cache.insert_media_item(&sample_item("1", 1, "image/jpeg")).unwrap();
+    cache.insert_media_item(&sample_item("2", 2, "image/png")).unwrap();
+    cache.insert_media_item(&sample_item("3", 3, "image/jpeg")).unwrap();
+}
+
+#[test]
+fn query_no_cache() {
+    let dir = tempdir().unwrap();
+    build_cmd(dir.path())
+        .arg("query")
+        .assert()
+        .success()
+        .stdout(contains("No cache found"));
+}
+
+#[test]
+fn query_json_filters_and_sorts() {
+    let dir = tempdir().unwrap();
+    seed(dir.path());
+    let output = build_cmd(dir.path())
+        .args([
+            "query",
+            "--mime",
+            "image/jpeg",
+            "--from",
+            "2023-01-01",
+            "--to",
+            "2023-01-03T12:00:00Z",
+            "--sort",
+            "oldest",
+            "--output",
+            "json",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let items: Vec<api_client::MediaItem> = serde_json::from_slice(&output.stdout).unwrap();
+    let ids: Vec<_> = items.iter().map(|i| i.id.as_str()).collect();
+    assert_eq!(ids, vec!["1", "3"]);
+    assert_eq!(items[0].filename, "1.jpg");
+    assert_eq!(items[0].media_metadata.creation_time, "2023-01-01T00:00:00+00:00");
+}
+
+#[test]
+fn query_json_limit_and_csv() {
+    let dir = tempdir().unwrap();
+    seed(dir.path());
+    let output = build_cmd(dir.path())
+        .args(["query", "--limit", "1", "--output", "json"])
+        .output()
+        .unwrap();
+    let items: Vec<api_client::MediaItem> = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].id, "3");
+
+    build_cmd(dir.path())
+        .args(["query", "--filename", "2.jpg", "--output", "csv"])
+        .assert()
+        .success()
+        .stdout(contains("id,filename,mime_type,creation_time,description"))
+        .stdout(contains("2,2.jpg,image/png"));
+}
+
+#[test]
+fn query_rejects_invalid_date() {
+    let dir = tempdir().unwrap();
+    seed(dir.path());
+    build_cmd(dir.path())
+        .args(["query", "--from", "yesterday"])
+        .assert()
+        .failure();
+}
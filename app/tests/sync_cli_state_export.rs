@@ -0,0 +1,149 @@
+use assert_cmd::prelude::*;
+use cache::CacheManager;
+use chrono::{TimeZone, Utc};
+use predicates::str::contains;
+use std::path::Path;
+use std::process::Command;
+use tempfile::tempdir;
+
+fn build_cmd(home: &Path) -> Command {
+    let mut cmd = Command::cargo_bin("sync_cli").unwrap();
+    cmd.env("MOCK_API_CLIENT", "1");
+    cmd.env("MOCK_KEYRING", "1");
+    cmd.env("MOCK_REFRESH_TOKEN", "t");
+    cmd.env("HOME", home);
+    cmd
+}
+
+fn sample_item(id: &str, day: u32) -> api_client::MediaItem {
+    api_client::MediaItem {
+        id: id.to_string(),
+        description: Some(format!("desc {}", id)),
+        product_url: "http://example.com".into(),
+        base_url: "http://example.com/base".into(),
+        mime_type: "image/jpeg".into(),
+        media_metadata: api_client::MediaMetadata {
+            creation_time: format!("2023-01-{:02}T00:00:00Z", day),
+            width: "1".into(),
+            height: "1".into(),
+            video: None,
+        },
+        filename: format!("{}.jpg", id),
+        is_favorite: false,
+    }
+}
+
+fn sample_album(id: &str) -> api_client::Album {
+    api_client::Album {
+        id: id.into(),
+        title: Some(format!("Album {}", id)),
+        product_url: None,
+        is_writeable: None,
+        media_items_count: None,
+        cover_photo_base_url: None,
+        cover_photo_media_item_id: None,
+    }
+}
+
+fn open_cache(home: &Path) -> CacheManager {
+    let base = home.join(".googlepicz");
+    std::fs::create_dir_all(&base).unwrap();
+    CacheManager::new(&base.join("cache.sqlite")).unwrap()
+}
+
+fn seed(home: &Path) {
+    let cache = open_cache(home);
+    for (id, day) in [("1", 1), ("2", 2), ("3", 3)] {
+        cache.insert_media_item(&sample_item(id, day)).unwrap();
+    }
+    cache.insert_album(&sample_album("a1")).unwrap();
+    cache.associate_media_item_with_album("1", "a1").unwrap();
+    cache.associate_media_item_with_album("3", "a1").unwrap();
+    cache.set_favorite("2", true).unwrap();
+    cache
+        .insert_faces("1", r#"[{"bbox":[1,2,3,4],"name":"Alice"}]"#)
+        .unwrap();
+    cache
+        .update_last_sync(Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap())
+        .unwrap();
+}
+
+fn query_json(home: &Path, args: &[&str]) -> serde_json::Value {
+    let output = build_cmd(home)
+        .args(["query", "--output", "json"])
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+fn assert_same_state(src: &Path, dst: &Path) {
+    for args in [&[][..], &["--favorite"][..], &["--album", "a1"][..], &["--sort", "filename"][..]] {
+        assert_eq!(query_json(src, args), query_json(dst, args));
+    }
+    let (a, b) = (open_cache(src), open_cache(dst));
+    assert_eq!(a.get_album_associations().unwrap(), b.get_album_associations().unwrap());
+    assert_eq!(
+        serde_json::to_value(a.get_all_albums().unwrap()).unwrap(),
+        serde_json::to_value(b.get_all_albums().unwrap()).unwrap()
+    );
+    assert_eq!(
+        serde_json::to_value(a.get_faces("1").unwrap()).unwrap(),
+        serde_json::to_value(b.get_faces("1").unwrap()).unwrap()
+    );
+    assert_eq!(a.get_last_sync().unwrap(), b.get_last_sync().unwrap());
+}
+
+#[test]
+fn export_import_dir_round_trip() {
+    let src = tempdir().unwrap();
+    let dst = tempdir().unwrap();
+    seed(src.path());
+    let export = src.path().join("export");
+
+    build_cmd(src.path())
+        .args(["export", "--dir", export.to_str().unwrap()])
+        .assert()
+        .success()
+        .stdout(contains("Exported album_media_items.json (2 records)"));
+
+    build_cmd(dst.path())
+        .args(["import", "--dir", export.to_str().unwrap()])
+        .assert()
+        .success()
+        .stdout(contains("Imported faces.json (1 records)"));
+
+    assert_same_state(src.path(), dst.path());
+}
+
+#[test]
+fn export_import_archive_replace() {
+    let src = tempdir().unwrap();
+    let dst = tempdir().unwrap();
+    seed(src.path());
+    open_cache(dst.path())
+        .insert_media_item(&sample_item("stale", 9))
+        .unwrap();
+    let archive = src.path().join("state.tar");
+
+    build_cmd(src.path())
+        .args(["export", "--archive", archive.to_str().unwrap()])
+        .assert()
+        .success();
+    assert!(archive.exists());
+
+    build_cmd(dst.path())
+        .args(["import", "--archive", archive.to_str().unwrap(), "--replace"])
+        .assert()
+        .success();
+
+    assert!(open_cache(dst.path()).get_media_item("stale").unwrap().is_none());
+    assert_same_state(src.path(), dst.path());
+}
+
+#[test]
+fn export_requires_target() {
+    let dir = tempdir().unwrap();
+    build_cmd(dir.path()).arg("export").assert().failure();
+}
@@ -0,0 +1,77 @@
+use assert_cmd::prelude::*;
+use cache::CacheManager;
+use predicates::str::contains;
+use std::process::Command;
+use tempfile::tempdir;
+
+fn build_cmd(home: &std::path::Path) -> Command {
+    // No token variables: stats must work offline against the cache alone.
+    let mut cmd = Command::cargo_bin("sync_cli").unwrap();
+    cmd.env("MOCK_KEYRING", "1");
+    cmd.env("HOME", home);
+    cmd
+}
+
+fn sample_item(id: &str, created: &str) -> api_client::MediaItem {
+    api_client::MediaItem {
+        id: id.to_string(),
+        description: None,
+        product_url: "http://example.com".into(),
+        base_url: "http://example.com/base".into(),
+        mime_type: "image/jpeg".into(),
+        media_metadata: api_client::MediaMetadata {
+            creation_time: created.into(),
+            width: "1".into(),
+            height: "1".into(),
+            video: None,
+        },
+        filename: format!("{}.jpg", id),
+        is_favorite: false,
+    }
+}
+
+fn seed(home: &std::path::Path) {
+    let base = home.join(".googlepicz");
+    std::fs::create_dir_all(&base).unwrap();
+    let cache = CacheManager::new(&base.join("cache.sqlite")).unwrap();
+    cache.insert_media_item(&sample_item("1", "2020-03-01T00:00:00Z")).unwrap();
+    cache.insert_media_item(&sample_item("2", "2023-01-01T00:00:00Z")).unwrap();
+    let mut video = sample_item("3", "2023-05-01T00:00:00Z");
+    video.mime_type = "video/mp4".into();
+    cache.insert_media_item(&video).unwrap();
+    cache.set_favorite("1", true).unwrap();
+}
+
+#[test]
+fn stats_json_summary() {
+    let dir = tempdir().unwrap();
+    seed(dir.path());
+    let output = build_cmd(dir.path()).args(["stats", "--output", "json"]).output().unwrap();
+    assert!(output.status.success());
+    let stats: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(stats["item_count"], 3);
+    assert_eq!(stats["video_count"], 1);
+    assert_eq!(stats["favorite_count"], 1);
+    assert!(stats["cache_size_bytes"].as_u64().unwrap() > 0);
+}
+
+#[test]
+fn stats_table_and_by_year() {
+    let dir = tempdir().unwrap();
+    seed(dir.path());
+    build_cmd(dir.path())
+        .arg("stats")
+        .assert()
+        .success()
+        .stdout(contains("Items:      3"))
+        .stdout(contains("image/jpeg"));
+
+    let output = build_cmd(dir.path())
+        .args(["stats", "--by-year", "--output", "json"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let years: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(years["2020"], 1);
+    assert_eq!(years["2023"], 2);
+}
@@ -0,0 +1,59 @@
+use assert_cmd::prelude::*;
+use cache::CacheManager;
+use predicates::str::contains;
+use std::process::Command;
+use tempfile::tempdir;
+
+fn build_cmd(home: &std::path::Path) -> Command {
+    let mut cmd = Command::cargo_bin("sync_cli").unwrap();
+    cmd.env("MOCK_API_CLIENT", "1");
+    cmd.env("MOCK_KEYRING", "1");
+    cmd.env("MOCK_REFRESH_TOKEN", "t");
+    cmd.env("HOME", home);
+    cmd
+}
+
+#[test]
+fn upload_folder_filters_and_resumes() {
+    let dir = tempdir().unwrap();
+    let photos = dir.path().join("photos");
+    std::fs::create_dir_all(photos.join("nested")).unwrap();
+    std::fs::write(photos.join("a.jpg"), b"jpeg data").unwrap();
+    std::fs::write(photos.join("notes.txt"), b"not a photo").unwrap();
+    std::fs::write(photos.join("nested").join("b.mp4"), b"video data").unwrap();
+
+    build_cmd(dir.path())
+        .args(["upload", "--recursive", "--description", "holiday"])
+        .arg(&photos)
+        .assert()
+        .success()
+        .stdout(contains("[####################] 2/2"))
+        .stdout(contains("Uploaded: 2, skipped: 0, failed: 0"));
+
+    let cache = CacheManager::new(&dir.path().join(".googlepicz").join("cache.sqlite")).unwrap();
+    assert!(cache.get_media_item("uploaded").unwrap().is_some());
+
+    build_cmd(dir.path())
+        .args(["upload", "--recursive"])
+        .arg(&photos)
+        .assert()
+        .success()
+        .stdout(contains("Uploaded: 0, skipped: 2, failed: 0"));
+}
+
+#[test]
+fn upload_without_recursive_skips_subdirectories() {
+    let dir = tempdir().unwrap();
+    let photos = dir.path().join("photos");
+    std::fs::create_dir_all(photos.join("nested")).unwrap();
+    std::fs::write(photos.join("a.png"), b"png").unwrap();
+    std::fs::write(photos.join("nested").join("b.jpg"), b"jpg").unwrap();
+
+    build_cmd(dir.path())
+        .args(["upload", "--album", "1"])
+        .arg(&photos)
+        .assert()
+        .success()
+        .stdout(contains("Added 1 items to album 1"))
+        .stdout(contains("Uploaded: 1, skipped: 0, failed: 0"));
+}
@@ -0,0 +1,61 @@
+#![cfg(unix)]
+use assert_cmd::prelude::*;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+use tempfile::tempdir;
+
+#[test]
+fn watch_writes_status_file_and_stops_on_sigterm() {
+    let dir = tempdir().unwrap();
+    let status = dir.path().join("status.json");
+    let mut child = Command::cargo_bin("sync_cli")
+        .unwrap()
+        .args(["sync", "--watch", "--interval", "60", "--status-file"])
+        .arg(&status)
+        .env("MOCK_API_CLIENT", "1")
+        .env("MOCK_KEYRING", "1")
+        .env("MOCK_REFRESH_TOKEN", "t")
+        .env("HOME", dir.path())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    // Earlier statuses such as "Sync started" may be written first.
+    let read_message = || {
+        std::fs::read_to_string(&status)
+            .ok()
+            .and_then(|s| serde_json::from_str::<serde_json::Value>(&s).ok())
+            .and_then(|json| json["message"].as_str().map(str::to_string))
+    };
+    let deadline = Instant::now() + Duration::from_secs(30);
+    let mut message = read_message();
+    while message.as_deref() != Some("Sync completed") && Instant::now() < deadline {
+        // No point waiting out the deadline once the watcher has died
+        if let Some(exit) = child.try_wait().unwrap() {
+            panic!("sync_cli exited with {} before the sync completed", exit);
+        }
+        std::thread::sleep(Duration::from_millis(100));
+        message = read_message();
+    }
+
+    Command::new("kill")
+        .args(["-TERM", &child.id().to_string()])
+        .status()
+        .unwrap();
+    assert_eq!(message.as_deref(), Some("Sync completed"), "status file did not reach completion in time");
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Watch stopped"));
+}
+
+#[test]
+fn interval_requires_watch() {
+    let dir = tempdir().unwrap();
+    Command::cargo_bin("sync_cli")
+        .unwrap()
+        .args(["sync", "--interval", "5"])
+        .env("HOME", dir.path())
+        .assert()
+        .failure();
+}
@@ -0,0 +1,57 @@
+#[path = "../src/updater.rs"]
+#[allow(dead_code)]
+mod updater;
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use ring::signature::{Ed25519KeyPair, KeyPair};
+use updater::{verify_manifest, UpdateError};
+
+const MANIFEST: &str = r#"{
+  "version": "1.2.0",
+  "platform": "linux",
+  "arch": "aarch64",
+  "artifact": "GooglePicz-1.2.0-linux-aarch64.deb",
+  "size": 4,
+  "sha256": "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08",
+  "released_at": "2026-10-01T12:00:00+00:00"
+}"#;
+
+fn key(seed: u8) -> Ed25519KeyPair {
+    Ed25519KeyPair::from_seed_unchecked(&[seed; 32]).unwrap()
+}
+
+#[test]
+fn signed_manifest_is_accepted() {
+    let key = key(7);
+    let signature = BASE64.encode(key.sign(MANIFEST.as_bytes()).as_ref());
+    let public_key = BASE64.encode(key.public_key().as_ref());
+
+    let manifest = verify_manifest(MANIFEST.as_bytes(), &signature, &public_key).unwrap();
+    assert_eq!(manifest.version, "1.2.0");
+    assert_eq!(manifest.arch, "aarch64");
+    assert_eq!(manifest.artifact, "GooglePicz-1.2.0-linux-aarch64.deb");
+    assert_eq!(manifest.size, 4);
+    assert!(manifest.is_newer_than("1.1.9"));
+    assert!(manifest.is_newer_than("1.2"));
+    assert!(!manifest.is_newer_than("1.2.0"));
+    assert!(!manifest.is_newer_than("1.10.0"));
+}
+
+#[test]
+fn tampered_or_foreign_manifest_is_rejected() {
+    let key = key(7);
+    let signature = BASE64.encode(key.sign(MANIFEST.as_bytes()).as_ref());
+    let public_key = BASE64.encode(key.public_key().as_ref());
+
+    let tampered = MANIFEST.replace("\"size\": 4", "\"size\": 5");
+    assert!(matches!(
+        verify_manifest(tampered.as_bytes(), &signature, &public_key),
+        Err(UpdateError::BadSignature)
+    ));
+    let other = BASE64.encode(self::key(8).public_key().as_ref());
+    assert!(matches!(
+        verify_manifest(MANIFEST.as_bytes(), &signature, &other),
+        Err(UpdateError::BadSignature)
+    ));
+}
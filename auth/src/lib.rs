@@ -3,20 +3,22 @@
 //! Authentication module for Google Photos API.
 
 use keyring::Entry;
-use oauth2::basic::BasicClient;
+use oauth2::basic::{BasicClient, BasicErrorResponseType};
 use oauth2::reqwest::async_http_client;
+use oauth2::devicecode::StandardDeviceAuthorizationResponse;
 use oauth2::{
-    AuthUrl, AuthorizationCode, ClientId, ClientSecret, CsrfToken, PkceCodeChallenge, RedirectUrl,
-    Scope, TokenResponse, TokenUrl,
+    AuthUrl, AuthorizationCode, ClientId, ClientSecret, CsrfToken, DeviceAuthorizationUrl,
+    PkceCodeChallenge, RedirectUrl, RequestTokenError, Scope, TokenResponse, TokenUrl,
 };
 use once_cell::sync::Lazy;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Mutex;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tokio::task::JoinHandle;
 use tokio::time::sleep;
 use tokio::io::{AsyncBufReadExt, BufReader};
 use tokio::net::TcpListener;
+use tokio::sync::broadcast;
 use url::Url;
 use thiserror::Error;
 #[cfg(feature = "file-store")]
@@ -25,36 +27,87 @@ use std::path::PathBuf;
 use std::fs;
 #[cfg(feature = "file-store")]
 use std::collections::HashMap as FileMap;
+use serde::Deserialize;
 #[cfg(feature = "file-store")]
-use serde::{Deserialize, Serialize};
-#[cfg(feature = "file-store")]
-use serde_json;
+use serde::Serialize;
 
 
 const KEYRING_SERVICE_NAME: &str = "GooglePicz";
 const ACCESS_TOKEN_EXPIRY_KEY: &str = "access_token_expiry";
+const ACCOUNT_KEY: &str = "account";
+/// Scopes the token endpoint reported for the stored tokens, space separated.
+const SCOPES_KEY: &str = "scopes";
+const TOKEN_KEYS: [&str; 5] = ["access_token", "refresh_token", ACCESS_TOKEN_EXPIRY_KEY, ACCOUNT_KEY, SCOPES_KEY];
+/// Reading the library, uploading and creating albums, and changing the
+/// albums the app created.
+const PHOTOS_SCOPES: [&str; 3] = [
+    "https://www.googleapis.com/auth/photoslibrary.readonly",
+    "https://www.googleapis.com/auth/photoslibrary.appendonly",
+    "https://www.googleapis.com/auth/photoslibrary.edit.appcreateddata",
+];
+/// Scopes for the email and avatar shown in the account menu.
+const PROFILE_SCOPES: [&str; 2] = ["email", "profile"];
+const USERINFO_URL: &str = "https://www.googleapis.com/oauth2/v3/userinfo";
 /// Seconds before expiry when we proactively refresh the token.
 pub const REFRESH_MARGIN_SECS: u64 = 300;
 /// Environment variable to opt into storing tokens in a file instead of the keyring.
 pub const USE_FILE_STORE_ENV: &str = "USE_FILE_STORE";
 /// Environment variable to simulate keyring failures in tests.
 const MOCK_KEYRING_FAIL_ENV: &str = "MOCK_KEYRING_FAIL";
+/// Environment variable to simulate a revoked refresh token in tests.
+const MOCK_REFRESH_REVOKED_ENV: &str = "MOCK_REFRESH_REVOKED";
+/// Environment variable naming the active config profile; each profile keeps
+/// its own tokens.
+pub const PROFILE_ENV: &str = "GOOGLEPICZ_PROFILE";
+
+/// Pending scheduled refresh per keyring service, see `keyring_service`.
+static SCHEDULED_REFRESH: Lazy<Mutex<HashMap<String, JoinHandle<()>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
 
-static SCHEDULED_REFRESH: Lazy<Mutex<Option<JoinHandle<()>>>> =
-    Lazy::new(|| Mutex::new(None));
+/// Keyring services whose refresh token the token endpoint rejected, until
+/// the next sign-in of that profile.
+static REAUTH_REQUIRED: Lazy<Mutex<HashSet<String>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+
+static AUTH_EVENTS: Lazy<broadcast::Sender<AuthEvent>> = Lazy::new(|| broadcast::channel(16).0);
 
 static MOCK_STORE: Lazy<Mutex<HashMap<String, String>>> = Lazy::new(|| Mutex::new(HashMap::new()));
 
+/// The profile named by `PROFILE_ENV`, `None` for the default one. Only the
+/// functions without a `_for` suffix use it; code that switches accounts at
+/// runtime passes the profile explicitly instead.
+pub fn active_profile() -> Option<String> {
+    std::env::var(PROFILE_ENV)
+        .ok()
+        .map(|p| p.trim().to_string())
+        .filter(|p| !p.is_empty())
+}
+
+/// Keyring service holding the tokens of `profile`.
+fn keyring_service(profile: Option<&str>) -> String {
+    match profile {
+        Some(profile) => format!("{}-{}", KEYRING_SERVICE_NAME, profile),
+        None => KEYRING_SERVICE_NAME.to_string(),
+    }
+}
+
+/// Key in the mock store, kept apart per profile like the keyring entries.
+fn mock_key(profile: Option<&str>, key: &str) -> String {
+    format!("{}/{}", keyring_service(profile), key)
+}
+
 #[cfg(feature = "file-store")]
 #[derive(Serialize, Deserialize, Default)]
 struct FileTokens(FileMap<String, String>);
 
 #[cfg(feature = "file-store")]
-fn token_file_path() -> PathBuf {
-    dirs::home_dir()
+fn token_file_path(profile: Option<&str>) -> PathBuf {
+    let base = dirs::home_dir()
         .unwrap_or_else(|| PathBuf::from("."))
-        .join(".googlepicz")
-        .join("tokens.json")
+        .join(".googlepicz");
+    match profile {
+        Some(profile) => base.join("profiles").join(profile).join("tokens.json"),
+        None => base.join("tokens.json"),
+    }
 }
 
 #[cfg(feature = "file-store")]
@@ -63,8 +116,8 @@ fn enable_file_store() {
 }
 
 #[cfg(feature = "file-store")]
-fn store_value_file(key: &str, value: &str) -> Result<(), AuthError> {
-    let path = token_file_path();
+fn store_value_file(profile: Option<&str>, key: &str, value: &str) -> Result<(), AuthError> {
+    let path = token_file_path(profile);
     let mut map = if path.exists() {
         let data = fs::read_to_string(&path).map_err(|e| AuthError::Other(e.to_string()))?;
         serde_json::from_str::<FileTokens>(&data).unwrap_or_default().0
@@ -80,8 +133,8 @@ fn store_value_file(key: &str, value: &str) -> Result<(), AuthError> {
 }
 
 #[cfg(feature = "file-store")]
-fn get_value_file(key: &str) -> Result<Option<String>, AuthError> {
-    let path = token_file_path();
+fn get_value_file(profile: Option<&str>, key: &str) -> Result<Option<String>, AuthError> {
+    let path = token_file_path(profile);
     if !path.exists() {
         return Ok(None);
     }
@@ -90,6 +143,19 @@ fn get_value_file(key: &str) -> Result<Option<String>, AuthError> {
     Ok(map.0.get(key).cloned())
 }
 
+#[cfg(feature = "file-store")]
+fn delete_value_file(profile: Option<&str>, key: &str) -> Result<(), AuthError> {
+    let path = token_file_path(profile);
+    if !path.exists() {
+        return Ok(());
+    }
+    let data = fs::read_to_string(&path).map_err(|e| AuthError::Other(e.to_string()))?;
+    let mut map = serde_json::from_str::<FileTokens>(&data).unwrap_or_default().0;
+    map.remove(key);
+    let data = serde_json::to_string(&FileTokens(map)).map_err(|e| AuthError::Other(e.to_string()))?;
+    fs::write(path, data).map_err(|e| AuthError::Other(e.to_string()))
+}
+
 #[derive(Debug, Error)]
 pub enum AuthError {
     #[error("Keyring error: {0}")]
@@ -98,39 +164,140 @@ pub enum AuthError {
     OAuth(String),
     #[error("Other error: {0}")]
     Other(String),
+    #[error("Sign-in required: {0}")]
+    ReauthRequired(String),
+}
+
+/// Changes of the sign-in state, see `subscribe_events`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthEvent {
+    /// The refresh token was revoked, has expired or lacks permissions the app
+    /// needs; the user has to sign in again
+    ReauthRequired,
+    /// A sign-in stored fresh tokens
+    SignedIn,
+}
+
+/// Receive every `AuthEvent` emitted from now on.
+pub fn subscribe_events() -> broadcast::Receiver<AuthEvent> {
+    AUTH_EVENTS.subscribe()
 }
 
-fn store_value(key: &str, value: &str) -> Result<(), AuthError> {
+/// Whether refreshing is on hold until the user signs in again.
+pub fn reauth_required() -> bool {
+    reauth_required_for(active_profile().as_deref())
+}
+
+/// Like `reauth_required`, for `profile` instead of the active profile.
+pub fn reauth_required_for(profile: Option<&str>) -> bool {
+    REAUTH_REQUIRED.lock().unwrap().contains(&keyring_service(profile))
+}
+
+/// Wait until the user has signed in again. Returns at once when no
+/// re-authentication is pending.
+pub async fn wait_for_reauth() {
+    wait_for_reauth_for(active_profile().as_deref()).await
+}
+
+/// Like `wait_for_reauth`, for `profile` instead of the active profile.
+pub async fn wait_for_reauth_for(profile: Option<&str>) {
+    let mut events = subscribe_events();
+    while reauth_required_for(profile) {
+        if let Err(broadcast::error::RecvError::Closed) = events.recv().await {
+            return;
+        }
+    }
+}
+
+/// The token endpoint rejected the refresh token of `profile`: forget it,
+/// stop its scheduled refresh and ask for a new sign-in.
+fn refresh_token_revoked(profile: Option<&str>, reason: &str) -> AuthError {
+    cancel_scheduled_refresh(profile);
+    if let Err(e) = delete_value(profile, "refresh_token") {
+        tracing::warn!("Failed to remove revoked refresh token: {}", e);
+    }
+    REAUTH_REQUIRED.lock().unwrap().insert(keyring_service(profile));
+    let _ = AUTH_EVENTS.send(AuthEvent::ReauthRequired);
+    tracing::warn!("Refresh token rejected, sign-in required: {}", reason);
+    AuthError::ReauthRequired(reason.to_string())
+}
+
+fn signed_in(profile: Option<&str>) {
+    REAUTH_REQUIRED.lock().unwrap().remove(&keyring_service(profile));
+    let _ = AUTH_EVENTS.send(AuthEvent::SignedIn);
+}
+
+/// Replace the scopes recorded for `profile` with `granted`; `None` forgets
+/// them when the token endpoint did not say.
+fn record_scopes(profile: Option<&str>, granted: Option<&Vec<Scope>>) -> Result<(), AuthError> {
+    match granted {
+        Some(scopes) => {
+            let joined = scopes.iter().map(|s| s.as_str()).collect::<Vec<_>>().join(" ");
+            store_value(profile, SCOPES_KEY, &joined)
+        }
+        None => delete_value(profile, SCOPES_KEY),
+    }
+}
+
+/// Fails with `AuthError::ReauthRequired` when the tokens of `profile` were
+/// granted without some of `PHOTOS_SCOPES`, e.g. by a sign-in from before
+/// uploads and album edits needed them, or when the user unticked one on the
+/// consent screen. Tokens whose scopes are unknown pass until a refresh
+/// reports them.
+fn check_scopes(profile: Option<&str>) -> Result<(), AuthError> {
+    let Some(granted) = get_value(profile, SCOPES_KEY)? else {
+        return Ok(());
+    };
+    let missing: Vec<&str> = PHOTOS_SCOPES
+        .iter()
+        .copied()
+        .filter(|scope| !granted.split_whitespace().any(|g| g == *scope))
+        .collect();
+    if missing.is_empty() {
+        return Ok(());
+    }
+    REAUTH_REQUIRED.lock().unwrap().insert(keyring_service(profile));
+    let _ = AUTH_EVENTS.send(AuthEvent::ReauthRequired);
+    tracing::warn!(?missing, "Stored tokens lack permissions, sign-in required");
+    Err(AuthError::ReauthRequired(format!("permissions not granted: {}", missing.join(", "))))
+}
+
+/// All of `PHOTOS_SCOPES`, as granted to the mock sign-ins.
+fn mock_scopes() -> Vec<Scope> {
+    PHOTOS_SCOPES.iter().map(|s| Scope::new(s.to_string())).collect()
+}
+
+fn store_value(profile: Option<&str>, key: &str, value: &str) -> Result<(), AuthError> {
+    #[cfg(feature = "file-store")]
+    if std::env::var(USE_FILE_STORE_ENV).is_ok() {
+        return store_value_file(profile, key, value);
+    }
     if std::env::var("MOCK_KEYRING").is_ok() {
         let mut store = MOCK_STORE
             .lock()
             .map_err(|_| AuthError::Other("Poisoned mock store lock".into()))?;
-        store.insert(key.to_string(), value.to_string());
+        store.insert(mock_key(profile, key), value.to_string());
         return Ok(());
     }
     if std::env::var(MOCK_KEYRING_FAIL_ENV).is_ok() {
         #[cfg(feature = "file-store")]
         {
             enable_file_store();
-            return store_value_file(key, value);
+            return store_value_file(profile, key, value);
         }
         #[cfg(not(feature = "file-store"))]
         {
             return Err(AuthError::Keyring("mock failure".into()));
         }
     }
-    #[cfg(feature = "file-store")]
-    if std::env::var(USE_FILE_STORE_ENV).is_ok() {
-        return store_value_file(key, value);
-    }
     {
-        let entry = match Entry::new(KEYRING_SERVICE_NAME, key) {
+        let entry = match Entry::new(&keyring_service(profile), key) {
             Ok(e) => e,
             Err(e) => {
                 #[cfg(feature = "file-store")]
                 {
                     enable_file_store();
-                    store_value_file(key, value)?;
+                    store_value_file(profile, key, value)?;
                     return Ok(());
                 }
                 #[cfg(not(feature = "file-store"))]
@@ -145,7 +312,7 @@ fn store_value(key: &str, value: &str) -> Result<(), AuthError> {
                 #[cfg(feature = "file-store")]
                 {
                     enable_file_store();
-                    store_value_file(key, value)?;
+                    store_value_file(profile, key, value)?;
                     Ok(())
                 }
                 #[cfg(not(feature = "file-store"))]
@@ -157,36 +324,36 @@ fn store_value(key: &str, value: &str) -> Result<(), AuthError> {
     }
 }
 
-fn get_value(key: &str) -> Result<Option<String>, AuthError> {
+fn get_value(profile: Option<&str>, key: &str) -> Result<Option<String>, AuthError> {
+    #[cfg(feature = "file-store")]
+    if std::env::var(USE_FILE_STORE_ENV).is_ok() {
+        return get_value_file(profile, key);
+    }
     if std::env::var("MOCK_KEYRING").is_ok() {
         let store = MOCK_STORE
             .lock()
             .map_err(|_| AuthError::Other("Poisoned mock store lock".into()))?;
-        return Ok(store.get(key).cloned());
+        return Ok(store.get(&mock_key(profile, key)).cloned());
     }
     if std::env::var(MOCK_KEYRING_FAIL_ENV).is_ok() {
         #[cfg(feature = "file-store")]
         {
             enable_file_store();
-            return get_value_file(key);
+            return get_value_file(profile, key);
         }
         #[cfg(not(feature = "file-store"))]
         {
             return Err(AuthError::Keyring("mock failure".into()));
         }
     }
-    #[cfg(feature = "file-store")]
-    if std::env::var(USE_FILE_STORE_ENV).is_ok() {
-        return get_value_file(key);
-    }
     {
-        let entry = match Entry::new(KEYRING_SERVICE_NAME, key) {
+        let entry = match Entry::new(&keyring_service(profile), key) {
             Ok(e) => e,
             Err(e) => {
                 #[cfg(feature = "file-store")]
                 {
                     enable_file_store();
-                    return get_value_file(key);
+                    return get_value_file(profile, key);
                 }
                 #[cfg(not(feature = "file-store"))]
                 {
@@ -199,7 +366,7 @@ fn get_value(key: &str) -> Result<Option<String>, AuthError> {
             Err(keyring::Error::NoEntry) => {
                 #[cfg(feature = "file-store")]
                 {
-                    Ok(get_value_file(key)?)
+                    Ok(get_value_file(profile, key)?)
                 }
                 #[cfg(not(feature = "file-store"))]
                 {
@@ -210,7 +377,7 @@ fn get_value(key: &str) -> Result<Option<String>, AuthError> {
                 #[cfg(feature = "file-store")]
                 {
                     enable_file_store();
-                    let val = get_value_file(key)?;
+                    let val = get_value_file(profile, key)?;
                     if val.is_some() {
                         Ok(val)
                     } else {
@@ -226,15 +393,72 @@ fn get_value(key: &str) -> Result<Option<String>, AuthError> {
     }
 }
 
-#[cfg_attr(feature = "trace-spans", tracing::instrument)]
+fn delete_value(profile: Option<&str>, key: &str) -> Result<(), AuthError> {
+    #[cfg(feature = "file-store")]
+    if std::env::var(USE_FILE_STORE_ENV).is_ok() {
+        return delete_value_file(profile, key);
+    }
+    if std::env::var("MOCK_KEYRING").is_ok() {
+        let mut store = MOCK_STORE
+            .lock()
+            .map_err(|_| AuthError::Other("Poisoned mock store lock".into()))?;
+        store.remove(&mock_key(profile, key));
+        return Ok(());
+    }
+    if std::env::var(MOCK_KEYRING_FAIL_ENV).is_ok() {
+        #[cfg(feature = "file-store")]
+        {
+            return delete_value_file(profile, key);
+        }
+        #[cfg(not(feature = "file-store"))]
+        {
+            return Err(AuthError::Keyring("mock failure".into()));
+        }
+    }
+    let entry = Entry::new(&keyring_service(profile), key).map_err(|e| AuthError::Keyring(e.to_string()))?;
+    match entry.delete_password() {
+        Ok(_) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(AuthError::Keyring(e.to_string())),
+    }
+}
+
+fn store_tokens(
+    profile: Option<&str>,
+    access_token: &str,
+    refresh_token: Option<&str>,
+    expires_in: Duration,
+) -> Result<(), AuthError> {
+    let expiry_secs = (SystemTime::now() + expires_in)
+        .duration_since(UNIX_EPOCH)
+        .map_err(|e| AuthError::Other(e.to_string()))?
+        .as_secs();
+    store_value(profile, "access_token", access_token)?;
+    store_value(profile, ACCESS_TOKEN_EXPIRY_KEY, &expiry_secs.to_string())?;
+    if let Some(refresh_token) = refresh_token {
+        store_value(profile, "refresh_token", refresh_token)?;
+    }
+    if let Ok(account) = std::env::var("MOCK_ACCOUNT") {
+        store_value(profile, ACCOUNT_KEY, &account)?;
+    }
+    signed_in(profile);
+    Ok(())
+}
+
 pub async fn authenticate(redirect_port: u16) -> Result<(), AuthError> {
+    authenticate_for(active_profile().as_deref(), redirect_port).await
+}
+
+/// Like `authenticate`, storing the tokens for `profile` instead of the
+/// active profile.
+#[cfg_attr(feature = "trace-spans", tracing::instrument)]
+pub async fn authenticate_for(profile: Option<&str>, redirect_port: u16) -> Result<(), AuthError> {
     if let Ok(mock_token) = std::env::var("MOCK_ACCESS_TOKEN") {
-        store_value("access_token", &mock_token)?;
+        store_value(profile, "access_token", &mock_token)?;
         if let Ok(refresh) = std::env::var("MOCK_REFRESH_TOKEN") {
-            store_value("refresh_token", &refresh)?;
+            store_value(profile, "refresh_token", &refresh)?;
         }
         let expiry = SystemTime::now() + Duration::from_secs(3600);
-        store_value(
+        store_value(profile, 
             ACCESS_TOKEN_EXPIRY_KEY,
             &expiry
                 .duration_since(UNIX_EPOCH)
@@ -242,6 +466,11 @@ pub async fn authenticate(redirect_port: u16) -> Result<(), AuthError> {
                 .as_secs()
                 .to_string(),
         )?;
+        if let Ok(account) = std::env::var("MOCK_ACCOUNT") {
+            store_value(profile, ACCOUNT_KEY, &account)?;
+        }
+        record_scopes(profile, Some(&mock_scopes()))?;
+        signed_in(profile);
         return Ok(());
     }
     let client_id = ClientId::new(std::env::var("GOOGLE_CLIENT_ID").map_err(|e| AuthError::Other(e.to_string()))?);
@@ -262,9 +491,7 @@ pub async fn authenticate(redirect_port: u16) -> Result<(), AuthError> {
 
     let (authorize_url, _csrf_state) = client
         .authorize_url(CsrfToken::new_random)
-        .add_scope(Scope::new(
-            "https://www.googleapis.com/auth/photoslibrary.readonly".to_string(),
-        ))
+        .add_scopes(PHOTOS_SCOPES.iter().chain(&PROFILE_SCOPES).map(|s| Scope::new(s.to_string())))
         .set_pkce_challenge(pkce_challenge)
         .url();
 
@@ -315,19 +542,243 @@ pub async fn authenticate(redirect_port: u16) -> Result<(), AuthError> {
         .as_secs();
 
     // Store tokens securely
-    store_value("access_token", access_token)?;
-    store_value(ACCESS_TOKEN_EXPIRY_KEY, &expiry_secs.to_string())?;
+    store_value(profile, "access_token", access_token)?;
+    store_value(profile, ACCESS_TOKEN_EXPIRY_KEY, &expiry_secs.to_string())?;
 
     if let Some(refresh_token) = refresh_token {
-        store_value("refresh_token", &refresh_token)?;
+        store_value(profile, "refresh_token", &refresh_token)?;
     }
+    remember_account(profile, access_token).await;
+    record_scopes(profile, token_response.scopes())?;
 
+    signed_in(profile);
+    check_scopes(profile)?;
     tracing::info!("Authentication successful!");
     Ok(())
 }
 
+/// Authenticate via the OAuth device flow, for machines without a browser.
+///
+/// `on_code` receives the verification URL and the user code to display.
+pub async fn authenticate_device<F>(on_code: F) -> Result<(), AuthError>
+where
+    F: FnOnce(&str, &str),
+{
+    authenticate_device_for(active_profile().as_deref(), on_code).await
+}
+
+/// Like `authenticate_device`, storing the tokens for `profile` instead of
+/// the active profile.
+#[cfg_attr(feature = "trace-spans", tracing::instrument(skip(on_code)))]
+pub async fn authenticate_device_for<F>(profile: Option<&str>, on_code: F) -> Result<(), AuthError>
+where
+    F: FnOnce(&str, &str),
+{
+    if let Ok(mock_token) = std::env::var("MOCK_ACCESS_TOKEN") {
+        on_code("https://www.google.com/device", "MOCK-CODE");
+        let refresh = std::env::var("MOCK_REFRESH_TOKEN").ok();
+        record_scopes(profile, Some(&mock_scopes()))?;
+        return store_tokens(profile, &mock_token, refresh.as_deref(), Duration::from_secs(3600));
+    }
+    let client_id = ClientId::new(std::env::var("GOOGLE_CLIENT_ID").map_err(|e| AuthError::Other(e.to_string()))?);
+    let client_secret = ClientSecret::new(std::env::var("GOOGLE_CLIENT_SECRET").map_err(|e| AuthError::Other(e.to_string()))?);
+    let auth_url = AuthUrl::new("https://accounts.google.com/o/oauth2/v2/auth".to_string()).map_err(|e| AuthError::OAuth(e.to_string()))?;
+    let token_url = TokenUrl::new("https://oauth2.googleapis.com/token".to_string()).map_err(|e| AuthError::OAuth(e.to_string()))?;
+    let device_url = DeviceAuthorizationUrl::new("https://oauth2.googleapis.com/device/code".to_string())
+        .map_err(|e| AuthError::OAuth(e.to_string()))?;
+
+    let client = BasicClient::new(client_id, Some(client_secret), auth_url, Some(token_url))
+        .set_device_authorization_url(device_url);
+
+    let details: StandardDeviceAuthorizationResponse = client
+        .exchange_device_code()
+        .map_err(|e| AuthError::OAuth(e.to_string()))?
+        .add_scopes(PHOTOS_SCOPES.iter().chain(&PROFILE_SCOPES).map(|s| Scope::new(s.to_string())))
+        .request_async(async_http_client)
+        .await
+        .map_err(|e| AuthError::OAuth(e.to_string()))?;
+
+    on_code(details.verification_uri().as_str(), details.user_code().secret());
+
+    let token_response = client
+        .exchange_device_access_token(&details)
+        .request_async(async_http_client, sleep, None)
+        .await
+        .map_err(|e| AuthError::OAuth(e.to_string()))?;
+
+    remember_account(profile, token_response.access_token().secret()).await;
+    record_scopes(profile, token_response.scopes())?;
+    store_tokens(
+        profile,
+        token_response.access_token().secret(),
+        token_response.refresh_token().map(|t| t.secret().as_str()),
+        token_response.expires_in().unwrap_or_else(|| Duration::from_secs(3600)),
+    )?;
+    check_scopes(profile)?;
+    tracing::info!("Device authentication successful!");
+    Ok(())
+}
+
+/// Remove all stored tokens and cancel any scheduled refresh.
+pub fn logout() -> Result<(), AuthError> {
+    logout_for(active_profile().as_deref())
+}
+
+/// Like `logout`, for `profile` instead of the active profile.
+#[cfg_attr(feature = "trace-spans", tracing::instrument)]
+pub fn logout_for(profile: Option<&str>) -> Result<(), AuthError> {
+    cancel_scheduled_refresh(profile);
+    for key in TOKEN_KEYS {
+        delete_value(profile, key)?;
+    }
+    tracing::info!("Stored tokens removed");
+    Ok(())
+}
+
+/// Where tokens are currently stored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenBackend {
+    Keyring,
+    File,
+    Mock,
+}
+
+impl TokenBackend {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TokenBackend::Keyring => "keyring",
+            TokenBackend::File => "file",
+            TokenBackend::Mock => "mock",
+        }
+    }
+}
+
+/// Snapshot of the stored credentials.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenStatus {
+    pub account: Option<String>,
+    pub has_access_token: bool,
+    pub has_refresh_token: bool,
+    /// Access token expiry as seconds since the Unix epoch.
+    pub access_token_expiry: Option<u64>,
+    pub backend: TokenBackend,
+}
+
+impl TokenStatus {
+    /// Whether the access token is missing or past its expiry.
+    pub fn access_token_expired(&self) -> bool {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_else(|_| Duration::from_secs(0))
+            .as_secs();
+        !self.has_access_token || self.access_token_expiry.unwrap_or(0) <= now
+    }
+}
+
+/// Inspect the stored tokens without contacting the OAuth server.
+pub fn get_token_status() -> Result<TokenStatus, AuthError> {
+    get_token_status_for(active_profile().as_deref())
+}
+
+/// Like `get_token_status`, for `profile` instead of the active profile.
+pub fn get_token_status_for(profile: Option<&str>) -> Result<TokenStatus, AuthError> {
+    let has_access_token = get_value(profile, "access_token")?.is_some();
+    let has_refresh_token = get_refresh_token_for(profile)?.is_some();
+    let access_token_expiry = get_access_token_expiry(profile)?;
+    let account = get_value(profile, ACCOUNT_KEY)?;
+    let backend = if cfg!(feature = "file-store") && std::env::var(USE_FILE_STORE_ENV).is_ok() {
+        TokenBackend::File
+    } else if std::env::var("MOCK_KEYRING").is_ok() {
+        TokenBackend::Mock
+    } else {
+        TokenBackend::Keyring
+    };
+    Ok(TokenStatus {
+        account,
+        has_access_token,
+        has_refresh_token,
+        access_token_expiry,
+        backend,
+    })
+}
+
+/// The signed in Google account as returned by the userinfo endpoint.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Deserialize)]
+pub struct UserInfo {
+    pub email: Option<String>,
+    pub name: Option<String>,
+    /// URL of the avatar
+    pub picture: Option<String>,
+}
+
+/// Fetch the email, name and avatar of the active account and remember the
+/// email so `get_token_status` can report it offline.
+pub async fn get_user_info() -> Result<UserInfo, AuthError> {
+    get_user_info_for(active_profile().as_deref()).await
+}
+
+/// Like `get_user_info`, for `profile` instead of the active profile.
+#[cfg_attr(feature = "trace-spans", tracing::instrument)]
+pub async fn get_user_info_for(profile: Option<&str>) -> Result<UserInfo, AuthError> {
+    if std::env::var("MOCK_ACCESS_TOKEN").is_ok() {
+        let email = get_value(profile, ACCOUNT_KEY)?.or_else(|| std::env::var("MOCK_ACCOUNT").ok());
+        return Ok(UserInfo { email, ..UserInfo::default() });
+    }
+    let token = ensure_access_token_valid_for(profile).await?;
+    let info = fetch_user_info(&token).await?;
+    if let Some(email) = &info.email {
+        store_value(profile, ACCOUNT_KEY, email)?;
+    }
+    Ok(info)
+}
+
+/// Store the email of the account `access_token` was issued for, replacing
+/// the one of a previous sign-in. Signing in still succeeds when the lookup
+/// fails; `get_user_info` fills the email in later.
+async fn remember_account(profile: Option<&str>, access_token: &str) {
+    if let Err(e) = delete_value(profile, ACCOUNT_KEY) {
+        tracing::warn!(error = %e, "Failed to forget the previous account");
+    }
+    match fetch_user_info(access_token).await {
+        Ok(UserInfo { email: Some(email), .. }) => {
+            if let Err(e) = store_value(profile, ACCOUNT_KEY, &email) {
+                tracing::warn!(error = %e, "Failed to store the account email");
+            }
+        }
+        Ok(_) => tracing::warn!("userinfo response has no email"),
+        Err(e) => tracing::warn!(error = %e, "Failed to look up the signed in account"),
+    }
+}
+
+async fn fetch_user_info(token: &str) -> Result<UserInfo, AuthError> {
+    let mut headers = oauth2::http::HeaderMap::new();
+    headers.insert(
+        oauth2::http::header::AUTHORIZATION,
+        format!("Bearer {}", token)
+            .parse()
+            .map_err(|e: oauth2::http::header::InvalidHeaderValue| AuthError::Other(e.to_string()))?,
+    );
+    let response = async_http_client(oauth2::HttpRequest {
+        url: Url::parse(USERINFO_URL).map_err(|e| AuthError::Other(e.to_string()))?,
+        method: oauth2::http::Method::GET,
+        headers,
+        body: Vec::new(),
+    })
+    .await
+    .map_err(|e| AuthError::Other(e.to_string()))?;
+    if !response.status_code.is_success() {
+        return Err(AuthError::Other(format!("userinfo request failed: {}", response.status_code)));
+    }
+    serde_json::from_slice(&response.body).map_err(|e| AuthError::Other(e.to_string()))
+}
+
 pub fn get_access_token() -> Result<String, AuthError> {
-    if let Some(val) = get_value("access_token")? {
+    get_access_token_for(active_profile().as_deref())
+}
+
+/// Like `get_access_token`, for `profile` instead of the active profile.
+pub fn get_access_token_for(profile: Option<&str>) -> Result<String, AuthError> {
+    if let Some(val) = get_value(profile, "access_token")? {
         Ok(val)
     } else {
         Err(AuthError::Other("No access token".into()))
@@ -335,40 +786,57 @@ pub fn get_access_token() -> Result<String, AuthError> {
 }
 
 pub fn get_refresh_token() -> Result<Option<String>, AuthError> {
-    Ok(get_value("refresh_token")?)
+    get_refresh_token_for(active_profile().as_deref())
+}
+
+/// Like `get_refresh_token`, for `profile` instead of the active profile.
+pub fn get_refresh_token_for(profile: Option<&str>) -> Result<Option<String>, AuthError> {
+    get_value(profile, "refresh_token")
 }
 
-fn get_access_token_expiry() -> Result<Option<u64>, AuthError> {
-    Ok(get_value(ACCESS_TOKEN_EXPIRY_KEY)?.map(|v| v.parse().unwrap_or(0)))
+fn get_access_token_expiry(profile: Option<&str>) -> Result<Option<u64>, AuthError> {
+    Ok(get_value(profile, ACCESS_TOKEN_EXPIRY_KEY)?.map(|v| v.parse().unwrap_or(0)))
 }
 
-fn cancel_scheduled_refresh() {
-    if let Some(handle) = SCHEDULED_REFRESH.lock().unwrap().take() {
+fn cancel_scheduled_refresh(profile: Option<&str>) {
+    if let Some(handle) = SCHEDULED_REFRESH.lock().unwrap().remove(&keyring_service(profile)) {
         handle.abort();
     }
 }
 
-fn schedule_token_refresh(expiry: u64) {
-    cancel_scheduled_refresh();
+fn schedule_token_refresh(profile: Option<&str>, expiry: u64) {
+    cancel_scheduled_refresh(profile);
     let when_secs = expiry.saturating_sub(REFRESH_MARGIN_SECS);
     let now_secs = SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .unwrap_or_else(|_| Duration::from_secs(0))
         .as_secs();
     let delay = when_secs.saturating_sub(now_secs);
+    let owned = profile.map(str::to_string);
     let handle = tokio::spawn(async move {
         if delay > 0 {
             sleep(Duration::from_secs(delay)).await;
         }
-        if let Err(e) = refresh_access_token().await {
+        if let Err(e) = refresh_access_token_for(owned.as_deref()).await {
             tracing::error!("Scheduled token refresh failed: {}", e);
         }
     });
-    *SCHEDULED_REFRESH.lock().unwrap() = Some(handle);
+    SCHEDULED_REFRESH.lock().unwrap().insert(keyring_service(profile), handle);
 }
 
-#[cfg_attr(feature = "trace-spans", tracing::instrument)]
 pub async fn refresh_access_token() -> Result<String, AuthError> {
+    refresh_access_token_for(active_profile().as_deref()).await
+}
+
+/// Like `refresh_access_token`, for `profile` instead of the active profile.
+#[cfg_attr(feature = "trace-spans", tracing::instrument)]
+pub async fn refresh_access_token_for(profile: Option<&str>) -> Result<String, AuthError> {
+    if reauth_required_for(profile) {
+        return Err(AuthError::ReauthRequired("waiting for the user to sign in again".into()));
+    }
+    if std::env::var(MOCK_REFRESH_REVOKED_ENV).is_ok() {
+        return Err(refresh_token_revoked(profile, "Token has been expired or revoked."));
+    }
     if let Ok(mock_token) = std::env::var("MOCK_REFRESH_TOKEN") {
         let new_token = mock_token;
         let expiry = SystemTime::now() + Duration::from_secs(3600);
@@ -376,8 +844,8 @@ pub async fn refresh_access_token() -> Result<String, AuthError> {
             .duration_since(UNIX_EPOCH)
             .map_err(|e| AuthError::Other(e.to_string()))?
             .as_secs();
-        store_value("access_token", &new_token)?;
-        store_value(ACCESS_TOKEN_EXPIRY_KEY, &expiry_secs.to_string())?;
+        store_value(profile, "access_token", &new_token)?;
+        store_value(profile, ACCESS_TOKEN_EXPIRY_KEY, &expiry_secs.to_string())?;
         return Ok(new_token);
     }
     let client_id = ClientId::new(std::env::var("GOOGLE_CLIENT_ID").map_err(|e| AuthError::Other(e.to_string()))?);
@@ -392,13 +860,18 @@ pub async fn refresh_access_token() -> Result<String, AuthError> {
         Some(token_url),
     );
 
-    let refresh_token = get_refresh_token()?.ok_or_else(|| AuthError::Other("No refresh token found".into()))?;
+    let refresh_token = get_refresh_token_for(profile)?.ok_or_else(|| AuthError::Other("No refresh token found".into()))?;
 
     let token_response = client
         .exchange_refresh_token(&oauth2::RefreshToken::new(refresh_token))
         .request_async(async_http_client)
         .await
-        .map_err(|e| AuthError::OAuth(e.to_string()))?;
+        .map_err(|e| match e {
+            RequestTokenError::ServerResponse(ref resp) if *resp.error() == BasicErrorResponseType::InvalidGrant => {
+                refresh_token_revoked(profile, resp.error_description().map(String::as_str).unwrap_or("invalid_grant"))
+            }
+            e => AuthError::OAuth(e.to_string()),
+        })?;
 
     let access_token = token_response.access_token().secret();
     let expires_in = token_response
@@ -409,29 +882,49 @@ pub async fn refresh_access_token() -> Result<String, AuthError> {
         .duration_since(UNIX_EPOCH)
         .map_err(|e| AuthError::Other(e.to_string()))?
         .as_secs();
-    store_value("access_token", access_token)?;
-    store_value(ACCESS_TOKEN_EXPIRY_KEY, &expiry_secs.to_string())?;
+    store_value(profile, "access_token", access_token)?;
+    store_value(profile, ACCESS_TOKEN_EXPIRY_KEY, &expiry_secs.to_string())?;
+    // Usually reported on refresh as well, which catches sign-ins from
+    // before the scopes were widened
+    if token_response.scopes().is_some() {
+        record_scopes(profile, token_response.scopes())?;
+        check_scopes(profile)?;
+    }
 
     Ok(access_token.to_string())
 }
 
 /// Ensure the stored access token is valid, refreshing it if expired.
-#[cfg_attr(feature = "trace-spans", tracing::instrument)]
+///
+/// Fails with `AuthError::ReauthRequired` once the refresh token has been
+/// revoked, without contacting the token endpoint, until the user signs in
+/// again.
 pub async fn ensure_access_token_valid() -> Result<String, AuthError> {
-    let mut expiry = get_access_token_expiry()?.unwrap_or(0);
+    ensure_access_token_valid_for(active_profile().as_deref()).await
+}
+
+/// Like `ensure_access_token_valid`, for `profile` instead of the active
+/// profile.
+#[cfg_attr(feature = "trace-spans", tracing::instrument)]
+pub async fn ensure_access_token_valid_for(profile: Option<&str>) -> Result<String, AuthError> {
+    if reauth_required_for(profile) {
+        return Err(AuthError::ReauthRequired("waiting for the user to sign in again".into()));
+    }
+    check_scopes(profile)?;
+    let mut expiry = get_access_token_expiry(profile)?.unwrap_or(0);
     let now = SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .map_err(|e| AuthError::Other(e.to_string()))?
         .as_secs();
     if expiry <= now + REFRESH_MARGIN_SECS {
         // expired or about to expire soon
-        let token = refresh_access_token().await?;
-        expiry = get_access_token_expiry()?.unwrap_or(expiry);
-        schedule_token_refresh(expiry);
+        let token = refresh_access_token_for(profile).await?;
+        expiry = get_access_token_expiry(profile)?.unwrap_or(expiry);
+        schedule_token_refresh(profile, expiry);
         Ok(token)
     } else {
-        schedule_token_refresh(expiry);
-        get_access_token()
+        schedule_token_refresh(profile, expiry);
+        get_access_token_for(profile)
     }
 }
 
@@ -439,7 +932,6 @@ pub async fn ensure_access_token_valid() -> Result<String, AuthError> {
 mod tests {
     use super::*;
     use serial_test::serial;
-    use tokio;
 
     // Note: These tests require GOOGLE_CLIENT_ID and GOOGLE_CLIENT_SECRET to be set
     // and may require manual interaction for the initial authentication flow.
@@ -482,9 +974,9 @@ mod tests {
     async fn test_ensure_access_token_valid_no_refresh_needed() {
         std::env::set_var("MOCK_KEYRING", "1");
         std::env::set_var("MOCK_REFRESH_TOKEN", "unused");
-        store_value("access_token", "valid_token").unwrap();
+        store_value(None, "access_token", "valid_token").unwrap();
         let expiry = SystemTime::now() + Duration::from_secs(3600);
-        store_value(
+        store_value(None, 
             ACCESS_TOKEN_EXPIRY_KEY,
             &expiry
                 .duration_since(UNIX_EPOCH)
@@ -505,9 +997,9 @@ mod tests {
     async fn test_ensure_access_token_valid_with_refresh() {
         std::env::set_var("MOCK_KEYRING", "1");
         std::env::set_var("MOCK_REFRESH_TOKEN", "new_token");
-        store_value("access_token", "old_token").unwrap();
+        store_value(None, "access_token", "old_token").unwrap();
         let expiry = SystemTime::now() - Duration::from_secs(10);
-        store_value(
+        store_value(None, 
             ACCESS_TOKEN_EXPIRY_KEY,
             &expiry
                 .duration_since(UNIX_EPOCH)
@@ -531,7 +1023,7 @@ mod tests {
         std::env::set_var("MOCK_KEYRING", "1");
         {
             let mut store = MOCK_STORE.lock().unwrap();
-            store.remove("access_token");
+            store.remove(&mock_key(None, "access_token"));
         }
         let result = get_access_token();
         assert!(result.is_err());
@@ -553,12 +1045,12 @@ mod tests {
     #[tokio::test]
     #[serial]
     async fn test_ensure_access_token_valid_expiring_soon() {
-        cancel_scheduled_refresh();
+        cancel_scheduled_refresh(None);
         std::env::set_var("MOCK_KEYRING", "1");
         std::env::set_var("MOCK_REFRESH_TOKEN", "soon_new");
-        store_value("access_token", "soon_old").unwrap();
+        store_value(None, "access_token", "soon_old").unwrap();
         let expiry = SystemTime::now() + Duration::from_secs(10);
-        store_value(
+        store_value(None, 
             ACCESS_TOKEN_EXPIRY_KEY,
             &expiry
                 .duration_since(UNIX_EPOCH)
@@ -569,7 +1061,7 @@ mod tests {
         .unwrap();
         let token = ensure_access_token_valid().await.unwrap();
         assert_eq!(token, "soon_new");
-        cancel_scheduled_refresh();
+        cancel_scheduled_refresh(None);
         std::env::remove_var("MOCK_REFRESH_TOKEN");
         std::env::remove_var("MOCK_KEYRING");
     }
@@ -577,12 +1069,12 @@ mod tests {
     #[tokio::test]
     #[serial]
     async fn test_scheduled_refresh_happens() {
-        cancel_scheduled_refresh();
+        cancel_scheduled_refresh(None);
         std::env::set_var("MOCK_KEYRING", "1");
         std::env::set_var("MOCK_REFRESH_TOKEN", "sched_new");
-        store_value("access_token", "sched_old").unwrap();
+        store_value(None, "access_token", "sched_old").unwrap();
         let expiry = SystemTime::now() + Duration::from_secs(REFRESH_MARGIN_SECS + 1);
-        store_value(
+        store_value(None, 
             ACCESS_TOKEN_EXPIRY_KEY,
             &expiry
                 .duration_since(UNIX_EPOCH)
@@ -596,11 +1088,175 @@ mod tests {
         tokio::time::sleep(Duration::from_millis(1200)).await;
         let stored = get_access_token().unwrap();
         assert_eq!(stored, "sched_new");
-        cancel_scheduled_refresh();
+        cancel_scheduled_refresh(None);
+        std::env::remove_var("MOCK_REFRESH_TOKEN");
+        std::env::remove_var("MOCK_KEYRING");
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_revoked_refresh_token_requires_reauth() {
+        cancel_scheduled_refresh(None);
+        std::env::set_var("MOCK_KEYRING", "1");
+        store_value(None, "access_token", "revoked_old").unwrap();
+        store_value(None, "refresh_token", "revoked_ref").unwrap();
+        store_value(None, ACCESS_TOKEN_EXPIRY_KEY, "0").unwrap();
+        let mut events = subscribe_events();
+
+        std::env::set_var(MOCK_REFRESH_REVOKED_ENV, "1");
+        let result = ensure_access_token_valid().await;
+        std::env::remove_var(MOCK_REFRESH_REVOKED_ENV);
+        assert!(matches!(result, Err(AuthError::ReauthRequired(_))));
+        assert_eq!(get_refresh_token().unwrap(), None);
+        assert!(!SCHEDULED_REFRESH.lock().unwrap().contains_key(&keyring_service(None)));
+        assert_eq!(events.recv().await.unwrap(), AuthEvent::ReauthRequired);
+        assert!(reauth_required());
+
+        // Nothing is refreshed until the user signs in again
+        std::env::set_var("MOCK_REFRESH_TOKEN", "revoked_new");
+        assert!(matches!(ensure_access_token_valid().await, Err(AuthError::ReauthRequired(_))));
+        assert_eq!(get_access_token().unwrap(), "revoked_old");
+
+        let waiting = tokio::spawn(wait_for_reauth());
+        std::env::set_var("MOCK_ACCESS_TOKEN", "signed_in_again");
+        authenticate(8080).await.unwrap();
+        tokio::time::timeout(Duration::from_secs(1), waiting).await.unwrap().unwrap();
+        assert_eq!(events.recv().await.unwrap(), AuthEvent::SignedIn);
+        assert!(!reauth_required());
+        assert_eq!(ensure_access_token_valid().await.unwrap(), "signed_in_again");
+        cancel_scheduled_refresh(None);
+        std::env::remove_var("MOCK_ACCESS_TOKEN");
+        std::env::remove_var("MOCK_REFRESH_TOKEN");
+        std::env::remove_var("MOCK_KEYRING");
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_missing_scopes_require_consent() {
+        std::env::set_var("MOCK_KEYRING", "1");
+        std::env::set_var("MOCK_ACCESS_TOKEN", "scoped_tok");
+        authenticate(8080).await.unwrap();
+        assert_eq!(ensure_access_token_valid().await.unwrap(), "scoped_tok");
+
+        // A sign-in from when only read access was requested
+        store_value(None, SCOPES_KEY, PHOTOS_SCOPES[0]).unwrap();
+        let result = ensure_access_token_valid().await;
+        assert!(matches!(result, Err(AuthError::ReauthRequired(ref m)) if m.contains("appendonly")));
+        assert!(reauth_required());
+
+        authenticate(8080).await.unwrap();
+        assert!(!reauth_required());
+        assert_eq!(ensure_access_token_valid().await.unwrap(), "scoped_tok");
+        logout().unwrap();
+        assert_eq!(get_value(None, SCOPES_KEY).unwrap(), None);
+        std::env::remove_var("MOCK_ACCESS_TOKEN");
+        std::env::remove_var("MOCK_KEYRING");
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_logout_clears_tokens() {
+        std::env::set_var("MOCK_KEYRING", "1");
+        std::env::set_var("MOCK_ACCESS_TOKEN", "logout_tok");
+        std::env::set_var("MOCK_REFRESH_TOKEN", "logout_ref");
+        std::env::set_var("MOCK_ACCOUNT", "user@example.com");
+        authenticate(8080).await.unwrap();
+        let status = get_token_status().unwrap();
+        assert!(status.has_access_token && status.has_refresh_token);
+        assert!(!status.access_token_expired());
+        assert_eq!(status.account.as_deref(), Some("user@example.com"));
+        assert_eq!(status.backend, TokenBackend::Mock);
+
+        logout().unwrap();
+        let status = get_token_status().unwrap();
+        assert!(!status.has_access_token && !status.has_refresh_token);
+        assert!(status.account.is_none());
+        assert!(get_access_token().is_err());
+        std::env::remove_var("MOCK_ACCOUNT");
+        std::env::remove_var("MOCK_ACCESS_TOKEN");
         std::env::remove_var("MOCK_REFRESH_TOKEN");
         std::env::remove_var("MOCK_KEYRING");
     }
 
+    #[tokio::test]
+    #[serial]
+    async fn test_authenticate_device_mock() {
+        std::env::set_var("MOCK_KEYRING", "1");
+        std::env::set_var("MOCK_ACCESS_TOKEN", "device_tok");
+        let mut shown = None;
+        authenticate_device(|url, code| shown = Some((url.to_string(), code.to_string())))
+            .await
+            .unwrap();
+        assert!(shown.is_some());
+        assert_eq!(get_access_token().unwrap(), "device_tok");
+        logout().unwrap();
+        std::env::remove_var("MOCK_ACCESS_TOKEN");
+        std::env::remove_var("MOCK_KEYRING");
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_tokens_are_kept_per_profile() {
+        std::env::set_var("MOCK_KEYRING", "1");
+        std::env::set_var("MOCK_ACCESS_TOKEN", "personal_tok");
+        authenticate(8080).await.unwrap();
+        assert!(get_access_token_for(Some("work")).is_err());
+        std::env::set_var("MOCK_ACCESS_TOKEN", "work_tok");
+        authenticate_for(Some("work"), 8080).await.unwrap();
+        assert_eq!(get_access_token_for(Some("work")).unwrap(), "work_tok");
+        assert_eq!(get_access_token().unwrap(), "personal_tok");
+        logout_for(Some("work")).unwrap();
+        assert_eq!(get_access_token().unwrap(), "personal_tok");
+        logout().unwrap();
+        std::env::remove_var("MOCK_ACCESS_TOKEN");
+        std::env::remove_var("MOCK_KEYRING");
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_reauth_is_required_per_profile() {
+        std::env::set_var("MOCK_KEYRING", "1");
+        std::env::set_var("MOCK_ACCESS_TOKEN", "stale_tok");
+        authenticate_for(Some("stale"), 8080).await.unwrap();
+        store_value(Some("stale"), ACCESS_TOKEN_EXPIRY_KEY, "0").unwrap();
+        std::env::set_var(MOCK_REFRESH_REVOKED_ENV, "1");
+        let result = ensure_access_token_valid_for(Some("stale")).await;
+        std::env::remove_var(MOCK_REFRESH_REVOKED_ENV);
+        assert!(matches!(result, Err(AuthError::ReauthRequired(_))));
+        assert!(reauth_required_for(Some("stale")));
+        assert!(!reauth_required());
+
+        // Signing in to another profile leaves the revoked one waiting
+        authenticate(8080).await.unwrap();
+        assert!(reauth_required_for(Some("stale")));
+        authenticate_for(Some("stale"), 8080).await.unwrap();
+        assert!(!reauth_required_for(Some("stale")));
+        logout_for(Some("stale")).unwrap();
+        logout().unwrap();
+        std::env::remove_var("MOCK_ACCESS_TOKEN");
+        std::env::remove_var("MOCK_KEYRING");
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_user_info_and_status_of_other_profile() {
+        std::env::set_var("MOCK_KEYRING", "1");
+        std::env::set_var("MOCK_ACCESS_TOKEN", "info_tok");
+        std::env::set_var("MOCK_ACCOUNT", "me@example.com");
+        authenticate(8080).await.unwrap();
+        let info = get_user_info().await.unwrap();
+        assert_eq!(info.email.as_deref(), Some("me@example.com"));
+        assert!(info.picture.is_none());
+
+        let status = get_token_status_for(None).unwrap();
+        assert_eq!(status.account.as_deref(), Some("me@example.com"));
+        assert!(!get_token_status_for(Some("other")).unwrap().has_access_token);
+        logout().unwrap();
+        std::env::remove_var("MOCK_ACCOUNT");
+        std::env::remove_var("MOCK_ACCESS_TOKEN");
+        std::env::remove_var("MOCK_KEYRING");
+    }
+
     #[cfg(feature = "file-store")]
     #[tokio::test]
     #[serial]
@@ -609,8 +1265,8 @@ mod tests {
         let dir = TempDir::new().unwrap();
         std::env::set_var(USE_FILE_STORE_ENV, "1");
         std::env::set_var("HOME", dir.path());
-        store_value("access_token", "file_token").unwrap();
-        let val = get_value("access_token").unwrap();
+        store_value(None, "access_token", "file_token").unwrap();
+        let val = get_value(None, "access_token").unwrap();
         assert_eq!(val.unwrap(), "file_token");
         let path = dir.path().join(".googlepicz").join("tokens.json");
         assert!(path.exists());
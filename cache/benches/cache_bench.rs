@@ -17,6 +17,7 @@ fn sample_media_item(id: &str) -> MediaItem {
             video: None,
         },
         filename: format!("{}.jpg", id),
+        is_favorite: false,
     }
 }
 
@@ -34,6 +35,7 @@ fn sample_media_item_with_mime(id: &str, mime: &str) -> MediaItem {
             video: None,
         },
         filename: format!("{}.jpg", id),
+        is_favorite: false,
     }
 }
 
@@ -68,6 +70,7 @@ fn sample_media_item_with_camera(id: &str, model: &str) -> MediaItem {
             }),
         },
         filename: format!("{}.jpg", id),
+        is_favorite: false,
     }
 }
 
@@ -241,7 +244,7 @@ fn bench_text_query_get_1k(c: &mut Criterion) {
     }
     c.bench_function("get_text_1k", |b| {
         b.iter(|| {
-            let _ = cache.get_media_items_by_text("foo").unwrap();
+            let _ = cache.get_media_items_by_text("foo", None).unwrap();
         })
     });
 }
@@ -258,7 +261,7 @@ fn bench_text_query_get_10k(c: &mut Criterion) {
     }
     c.bench_function("get_text_10k", |b| {
         b.iter(|| {
-            let _ = cache.get_media_items_by_text("foo").unwrap();
+            let _ = cache.get_media_items_by_text("foo", None).unwrap();
         })
     });
 }
@@ -275,7 +278,7 @@ fn bench_text_query_get_100k(c: &mut Criterion) {
     }
     c.bench_function("get_text_100k", |b| {
         b.iter(|| {
-            let _ = cache.get_media_items_by_text("foo").unwrap();
+            let _ = cache.get_media_items_by_text("foo", None).unwrap();
         })
     });
 }
@@ -301,6 +304,7 @@ fn bench_text_query_general_1k(c: &mut Criterion) {
                     None,
                     None,
                     Some("foo"),
+                    None,
                 )
                 .unwrap();
         })
@@ -328,6 +332,7 @@ fn bench_text_query_general_10k(c: &mut Criterion) {
                     None,
                     None,
                     Some("foo"),
+                    None,
                 )
                 .unwrap();
         })
@@ -355,6 +360,7 @@ fn bench_text_query_general_100k(c: &mut Criterion) {
                     None,
                     None,
                     Some("foo"),
+                    None,
                 )
                 .unwrap();
         })
@@ -382,6 +388,7 @@ fn bench_text_query_general_200k(c: &mut Criterion) {
                     None,
                     None,
                     Some("foo"),
+                    None,
                 )
                 .unwrap();
         })
@@ -23,6 +23,7 @@ fn item_with_meta(id: &str, model: &str, ts: &str) -> MediaItem {
             }),
         },
         filename: format!("{}.jpg", id),
+        is_favorite: false,
     }
 }
 
@@ -53,6 +54,7 @@ fn bench_real_query(c: &mut Criterion) {
                     None,
                     None,
                     Some("sample"),
+                    None,
                 )
                 .unwrap();
         })
@@ -2,8 +2,9 @@
 #![warn(rust_2018_idioms)]
 //! Cache module for Google Photos data.
 
-use chrono::{DateTime, Utc, TimeZone};
+use chrono::{DateTime, FixedOffset, Utc, TimeZone};
 use rusqlite::{params, Connection, OptionalExtension};
+use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, Mutex};
 use rusqlite_migration::{Migrations, M};
 use thiserror::Error;
@@ -31,6 +32,12 @@ pub struct CacheManager {
 pub struct FaceData {
     pub bbox: [i32; 4],
     pub name: Option<String>,
+    /// Hidden from people counts and clustering; missing in older exports
+    #[serde(default)]
+    pub ignored: bool,
+    /// Detection score from 0 to 1; missing for faces detected by older versions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -39,7 +46,408 @@ pub struct FaceExport {
     pub faces: Vec<FaceData>,
 }
 
-fn apply_migrations(conn: &mut Connection) -> Result<(), CacheError> {
+/// Granularity for `CacheManager::count_media_items_by_period`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeBucket {
+    Year,
+    Month,
+    Day,
+}
+
+impl TimeBucket {
+    fn format(self) -> &'static str {
+        match self {
+            TimeBucket::Year => "%Y",
+            TimeBucket::Month => "%Y-%m",
+            TimeBucket::Day => "%Y-%m-%d",
+        }
+    }
+}
+
+/// How date bounds and time buckets read a creation time.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TimeMode {
+    /// Compare the instant in UTC.
+    #[default]
+    Utc,
+    /// Compare the wall-clock time where the item was captured, by applying
+    /// its stored UTC offset. Bounds are then read as wall-clock times too.
+    Local,
+}
+
+impl TimeMode {
+    /// SQL expression for `md.creation_time` in this mode, in seconds.
+    fn creation_time(self) -> &'static str {
+        match self {
+            TimeMode::Utc => "md.creation_time",
+            TimeMode::Local => "(md.creation_time + md.creation_tz_offset_minutes * 60)",
+        }
+    }
+}
+
+/// Library totals returned by `CacheManager::get_library_stats`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LibraryStats {
+    pub item_count: u64,
+    pub video_count: u64,
+    pub favorite_count: u64,
+    pub earliest: Option<DateTime<Utc>>,
+    pub latest: Option<DateTime<Utc>>,
+    /// Up to ten camera models ordered by item count.
+    pub top_camera_models: Vec<(String, u64)>,
+    /// Up to ten MIME types ordered by item count.
+    pub top_mime_types: Vec<(String, u64)>,
+    pub album_count: u64,
+    pub people_count: u64,
+}
+
+/// Named person with the number of media items they appear in.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PersonSummary {
+    pub name: String,
+    pub photo_count: u64,
+}
+
+/// A person, or the group of unnamed faces, with one face to picture them by.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PersonEntry {
+    /// `None` collects detected faces nobody has named yet
+    pub name: Option<String>,
+    /// Set on unnamed faces that `cluster_unnamed_faces` found to look alike
+    #[serde(default)]
+    pub cluster: Option<u32>,
+    pub photo_count: u64,
+    pub sample_media_item_id: String,
+    pub sample_bbox: [i32; 4],
+}
+
+/// Cluster an unnamed face belongs to, returned by `CacheManager::face_cluster`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FaceCluster {
+    pub id: u32,
+    /// Media items with faces of the cluster that are still unnamed
+    pub photo_count: u64,
+}
+
+/// Progress of an interrupted face scan, see `CacheManager::set_face_scan_checkpoint`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FaceScanCheckpoint {
+    /// Items up to this id, in id order, have been scanned
+    pub last_media_item_id: String,
+    pub processed: u64,
+}
+
+/// Media item as written by `export_media_items`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MediaItemExport {
+    #[serde(flatten)]
+    pub item: api_client::MediaItem,
+    /// Albums containing the item; left out when it is in none
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub album_ids: Option<Vec<String>>,
+    /// `MediaItem` does not serialize the favorite mark; left out when unset
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub is_favorite: bool,
+}
+
+/// Link between an album and one of its media items.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AlbumAssociation {
+    pub album_id: String,
+    pub media_item_id: String,
+}
+
+/// Library change that could not reach the API and waits for the next sync.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Mutation {
+    UpdateDescription {
+        media_item_id: String,
+        description: String,
+    },
+    /// Items favorited locally, mirrored into the favorites album
+    AddFavorites {
+        media_item_ids: Vec<String>,
+    },
+    /// Items unfavorited locally, taken out of the favorites album
+    RemoveFavorites {
+        media_item_ids: Vec<String>,
+    },
+}
+
+impl Mutation {
+    /// Mirror a local favorite change into the favorites album.
+    pub fn favorites(media_item_ids: Vec<String>, fav: bool) -> Self {
+        if fav {
+            Mutation::AddFavorites { media_item_ids }
+        } else {
+            Mutation::RemoveFavorites { media_item_ids }
+        }
+    }
+}
+
+/// Queued `Mutation` with its row id, used to remove it once applied.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingMutation {
+    pub id: i64,
+    pub mutation: Mutation,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct LastSyncExport {
+    timestamp: DateTime<Utc>,
+}
+
+/// How `import_state` treats data already in the cache.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ImportMode {
+    /// Keep existing rows and overwrite those present in the export.
+    #[default]
+    Merge,
+    /// Clear the cache before importing.
+    Replace,
+}
+
+/// Files written by `export_state`, in the order `import_state` restores them.
+pub const STATE_FILES: [&str; 6] = [
+    "media_items.json",
+    "albums.json",
+    "album_media_items.json",
+    "favorites.json",
+    "faces.json",
+    "last_sync.json",
+];
+
+/// Tables emptied by `clear_cache`, rows before those they point to. Queued
+/// mutations and the upload history stay, since no sync brings them back.
+const LIBRARY_TABLES: [&str; 9] = [
+    "album_media_items",
+    "face_embeddings",
+    "face_scan_state",
+    "faces",
+    "video_durations",
+    "media_items_fts",
+    "media_metadata",
+    "albums",
+    "media_items",
+];
+
+/// Query behind `CacheManager::get_albums_for_media_item`, shared with the
+/// test that checks its plan.
+pub const ALBUMS_FOR_MEDIA_ITEM_SQL: &str =
+    "SELECT a.id, a.title, a.product_url, a.is_writeable, a.media_items_count, a.cover_photo_base_url, a.cover_photo_media_item_id
+     FROM albums a
+     JOIN album_media_items am ON a.id = am.album_id
+     WHERE am.media_item_id = ?1
+     ORDER BY a.title";
+
+/// Schema version written by the latest migration.
+pub const SCHEMA_VERSION: u32 = 23;
+
+/// Length of the face embeddings stored by `set_face_embeddings`.
+pub const EMBEDDING_DIM: usize = 128;
+/// Faces covering less of the image than this fraction are stored but ignored.
+pub const DEFAULT_MIN_FACE_SIZE: f32 = 0.001;
+/// Cosine similarity at which `cluster_unnamed_faces` considers two faces the same person.
+pub const DEFAULT_CLUSTER_THRESHOLD: f32 = 0.6;
+
+/// Result of `CacheManager::check_integrity`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IntegrityReport {
+    /// Problems reported by `PRAGMA integrity_check`; empty when the database is sound.
+    pub sqlite_errors: Vec<String>,
+    pub orphaned_metadata: u64,
+    pub orphaned_album_links: u64,
+    pub orphaned_faces: u64,
+    pub missing_metadata: u64,
+}
+
+impl IntegrityReport {
+    pub fn is_ok(&self) -> bool {
+        self.sqlite_errors.is_empty()
+            && self.orphaned_metadata == 0
+            && self.orphaned_album_links == 0
+            && self.orphaned_faces == 0
+            && self.missing_metadata == 0
+    }
+}
+
+/// Per-batch counts returned by `upsert_media_items_batch`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UpsertStats {
+    pub inserted: u64,
+    pub updated: u64,
+    pub unchanged: u64,
+    /// IDs of items that were inserted or updated.
+    pub changed_ids: Vec<String>,
+    /// IDs of items that were not cached before.
+    pub inserted_ids: Vec<String>,
+}
+
+/// An item in the trash with the time it was moved there.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashedMediaItem {
+    pub item: api_client::MediaItem,
+    pub deleted_at: DateTime<Utc>,
+}
+
+/// Ordering applied to `MediaQuery` results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MediaSort {
+    #[default]
+    NewestFirst,
+    OldestFirst,
+    Filename,
+}
+
+/// Filters for `CacheManager::query`; unset fields match everything.
+#[derive(Debug, Clone, Default)]
+pub struct MediaQuery {
+    pub filename: Option<String>,
+    pub description: Option<String>,
+    pub text: Option<String>,
+    pub mime_type: Option<String>,
+    pub camera_model: Option<String>,
+    pub camera_make: Option<String>,
+    pub favorite: Option<bool>,
+    pub start: Option<DateTime<Utc>>,
+    pub end: Option<DateTime<Utc>>,
+    pub album_id: Option<String>,
+    /// Name given to a face on the item
+    pub person: Option<String>,
+    /// Whether face detection found anything on the item
+    pub faces: Option<bool>,
+    pub limit: Option<usize>,
+    pub sort: MediaSort,
+    /// Whether `start` and `end` are UTC instants or wall-clock times
+    pub time_mode: TimeMode,
+}
+
+impl MediaQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Substring match on the filename.
+    pub fn filename(mut self, pattern: impl Into<String>) -> Self {
+        self.filename = Some(pattern.into());
+        self
+    }
+
+    /// Substring match on the description.
+    pub fn description(mut self, pattern: impl Into<String>) -> Self {
+        self.description = Some(pattern.into());
+        self
+    }
+
+    /// Substring match on filename or description.
+    pub fn text(mut self, pattern: impl Into<String>) -> Self {
+        self.text = Some(pattern.into());
+        self
+    }
+
+    pub fn mime_type(mut self, mime: impl Into<String>) -> Self {
+        self.mime_type = Some(mime.into());
+        self
+    }
+
+    pub fn camera_model(mut self, model: impl Into<String>) -> Self {
+        self.camera_model = Some(model.into());
+        self
+    }
+
+    pub fn camera_make(mut self, make: impl Into<String>) -> Self {
+        self.camera_make = Some(make.into());
+        self
+    }
+
+    pub fn favorite(mut self, fav: bool) -> Self {
+        self.favorite = Some(fav);
+        self
+    }
+
+    pub fn start(mut self, start: DateTime<Utc>) -> Self {
+        self.start = Some(start);
+        self
+    }
+
+    pub fn end(mut self, end: DateTime<Utc>) -> Self {
+        self.end = Some(end);
+        self
+    }
+
+    pub fn time_mode(mut self, mode: TimeMode) -> Self {
+        self.time_mode = mode;
+        self
+    }
+
+    pub fn album(mut self, album_id: impl Into<String>) -> Self {
+        self.album_id = Some(album_id.into());
+        self
+    }
+
+    /// Items with a face named `name` that is not ignored.
+    pub fn person(mut self, name: impl Into<String>) -> Self {
+        self.person = Some(name.into());
+        self
+    }
+
+    /// Items with (or without) detected faces, ignored ones included.
+    pub fn faces(mut self, faces: bool) -> Self {
+        self.faces = Some(faces);
+        self
+    }
+
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn sort(mut self, sort: MediaSort) -> Self {
+        self.sort = sort;
+        self
+    }
+}
+
+/// SHA-256 of a file's contents, the key under which uploads are recorded.
+pub fn hash_file(path: &Path) -> std::io::Result<String> {
+    use sha2::{Digest, Sha256};
+    use std::io::Read;
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buf = [0u8; 64 * 1024];
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect())
+}
+
+/// Decode a stored embedding scaled to unit length, so a dot product is the
+/// cosine similarity. An all-zero embedding stays zero and matches nothing.
+fn normalized_embedding(blob: &[u8]) -> Vec<f32> {
+    let mut values: Vec<f32> = blob
+        .chunks_exact(4)
+        .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .collect();
+    let norm = values.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        values.iter_mut().for_each(|v| *v /= norm);
+    }
+    values
+}
+
+/// Bring the schema up to date one migration at a time, calling
+/// `on_step(step, steps)` before each of the `steps` that are pending.
+fn apply_migrations(conn: &mut Connection, mut on_step: impl FnMut(usize, usize)) -> Result<(), CacheError> {
     let migrations = Migrations::new(vec![
         M::up(
             "CREATE TABLE IF NOT EXISTS schema_version (version INTEGER NOT NULL);\
@@ -196,21 +604,88 @@ fn apply_migrations(conn: &mut Connection) -> Result<(), CacheError> {
         M::up(
             "CREATE VIRTUAL TABLE IF NOT EXISTS media_items_fts USING fts5(media_item_id UNINDEXED, filename, description);\
              INSERT INTO media_items_fts (media_item_id, filename, description) SELECT id, filename, coalesce(description, '') FROM media_items;\
-             CREATE TRIGGER IF NOT EXISTS media_items_ai AFTER INSERT ON media_items BEGIN\
+             CREATE TRIGGER IF NOT EXISTS media_items_ai AFTER INSERT ON media_items BEGIN \
                  INSERT INTO media_items_fts (media_item_id, filename, description) VALUES (new.id, new.filename, coalesce(new.description, ''));\
-             END;\
-             CREATE TRIGGER IF NOT EXISTS media_items_ad AFTER DELETE ON media_items BEGIN\
+             END; \
+             CREATE TRIGGER IF NOT EXISTS media_items_ad AFTER DELETE ON media_items BEGIN \
                  DELETE FROM media_items_fts WHERE media_item_id = old.id;\
-             END;\
-             CREATE TRIGGER IF NOT EXISTS media_items_au AFTER UPDATE OF filename, description ON media_items BEGIN\
+             END; \
+             CREATE TRIGGER IF NOT EXISTS media_items_au AFTER UPDATE OF filename, description ON media_items BEGIN \
                  UPDATE media_items_fts SET filename = new.filename, description = coalesce(new.description, '') WHERE media_item_id = old.id;\
-             END;\
+             END; \
              UPDATE schema_version SET version = 16;"
         ),
+        M::up(
+            "CREATE TABLE IF NOT EXISTS uploaded_files (\
+                 hash TEXT PRIMARY KEY,\
+                 media_item_id TEXT NOT NULL,\
+                 file_name TEXT NOT NULL,\
+                 uploaded_at INTEGER NOT NULL\
+             );\
+             UPDATE schema_version SET version = 17;"
+        ),
+        M::up(
+            "CREATE TABLE IF NOT EXISTS pending_mutations (\
+                 id INTEGER PRIMARY KEY AUTOINCREMENT,\
+                 payload TEXT NOT NULL,\
+                 created_at INTEGER NOT NULL\
+             );\
+             UPDATE schema_version SET version = 18;"
+        ),
+        M::up(
+            // Separate from media_items so INSERT OR REPLACE during sync keeps the durations
+            "CREATE TABLE IF NOT EXISTS video_durations (\
+                 media_item_id TEXT PRIMARY KEY,\
+                 duration_ms INTEGER NOT NULL\
+             );\
+             CREATE TRIGGER IF NOT EXISTS video_durations_ad AFTER DELETE ON media_items BEGIN \
+                 DELETE FROM video_durations WHERE media_item_id = old.id;\
+             END; \
+             UPDATE schema_version SET version = 19;"
+        ),
+        M::up(
+            // Keyed by the position of the face in `faces_json`
+            "CREATE TABLE IF NOT EXISTS face_embeddings (\
+                 media_item_id TEXT NOT NULL,\
+                 face_index INTEGER NOT NULL,\
+                 embedding BLOB NOT NULL,\
+                 cluster_id INTEGER,\
+                 PRIMARY KEY (media_item_id, face_index)\
+             );\
+             CREATE INDEX IF NOT EXISTS idx_face_embeddings_cluster_id ON face_embeddings (cluster_id);\
+             CREATE TRIGGER IF NOT EXISTS face_embeddings_ad AFTER DELETE ON media_items BEGIN \
+                 DELETE FROM face_embeddings WHERE media_item_id = old.id;\
+             END; \
+             UPDATE schema_version SET version = 20;"
+        ),
+        M::up(
+            // Single row written by the face scan after each batch
+            "CREATE TABLE IF NOT EXISTS face_scan_state (\
+                 id INTEGER PRIMARY KEY,\
+                 last_media_item_id TEXT NOT NULL,\
+                 processed INTEGER NOT NULL\
+             );\
+             UPDATE schema_version SET version = 21;"
+        ),
+        M::up(
+            // Set when an item is moved to the trash, cleared on restore
+            "ALTER TABLE media_items ADD COLUMN deleted_at INTEGER;\
+             CREATE INDEX IF NOT EXISTS idx_media_items_deleted_at ON media_items (deleted_at);\
+             UPDATE schema_version SET version = 22;"
+        ),
+        M::up(
+            // UTC offset the creation time was reported with, in minutes
+            "ALTER TABLE media_metadata ADD COLUMN creation_tz_offset_minutes INTEGER NOT NULL DEFAULT 0;\
+             UPDATE schema_version SET version = 23;"
+        ),
     ]);
-    migrations
-        .to_latest(conn)
-        .map_err(|e| CacheError::DatabaseError(format!("Failed to apply migrations: {}", e)))?;
+    let failed = |e: rusqlite_migration::Error| CacheError::DatabaseError(format!("Failed to apply migrations: {}", e));
+    let current = usize::from(&migrations.current_version(conn).map_err(failed)?);
+    let pending = usize::try_from(migrations.pending_migrations(conn).map_err(failed)?).unwrap_or(0);
+    for step in 1..=pending {
+        on_step(step, pending);
+        migrations.to_version(conn, current + step).map_err(failed)?;
+    }
     Ok(())
 }
 
@@ -220,25 +695,101 @@ impl CacheManager {
             .lock()
             .map_err(|_| CacheError::Other("Poisoned lock".into()))
     }
-    fn ts_to_rfc3339(ts: i64) -> String {
-        DateTime::<Utc>::from_timestamp(ts, 0)
-            .unwrap_or_else(|| DateTime::<Utc>::from(std::time::UNIX_EPOCH))
-            .to_rfc3339()
+    /// Render a stored timestamp in the UTC offset it was captured with, so
+    /// `+09:00` reads back as `+09:00` rather than as the same instant in UTC.
+    fn ts_to_rfc3339(ts: i64, offset_minutes: i32) -> String {
+        let utc = DateTime::<Utc>::from_timestamp(ts, 0)
+            .unwrap_or_else(|| DateTime::<Utc>::from(std::time::UNIX_EPOCH));
+        match FixedOffset::east_opt(offset_minutes * 60) {
+            Some(offset) if offset_minutes != 0 => utc.with_timezone(&offset).to_rfc3339(),
+            _ => utc.to_rfc3339(),
+        }
+    }
+    /// Seconds since the epoch and the UTC offset in minutes of an RFC 3339
+    /// creation time.
+    fn parse_creation_time(value: &str) -> Result<(i64, i32), CacheError> {
+        let parsed =
+            DateTime::parse_from_rfc3339(value).map_err(|e| CacheError::SerializationError(e.to_string()))?;
+        Ok((parsed.timestamp(), parsed.offset().local_minus_utc() / 60))
+    }
+    /// `video` of a cached item. The API leaves it out for photos, so a photo
+    /// row without any of its columns set reads back as `None`.
+    fn video_metadata(mime_type: &str, video: api_client::VideoMetadata) -> Option<api_client::VideoMetadata> {
+        let empty = video.camera_make.is_none()
+            && video.camera_model.is_none()
+            && video.fps.is_none()
+            && video.status.is_none();
+        (!empty || mime_type.starts_with("video/")).then_some(video)
     }
     #[cfg_attr(feature = "trace-spans", tracing::instrument)]
     pub fn new(db_path: &Path) -> Result<Self, CacheError> {
+        Self::open_with_progress(db_path, |_, _| {})
+    }
+
+    /// Like `new`, calling `on_step(step, steps)` before each pending schema
+    /// migration so a caller can show how far an upgrade has come.
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(on_step)))]
+    pub fn open_with_progress(db_path: &Path, on_step: impl FnMut(usize, usize)) -> Result<Self, CacheError> {
         let mut conn = Connection::open(db_path)
             .map_err(|e| CacheError::DatabaseError(format!("Failed to open database: {}", e)))?;
-        apply_migrations(&mut conn)?;
+        apply_migrations(&mut conn, on_step)?;
 
         Ok(CacheManager { conn: Arc::new(Mutex::new(conn)) })
     }
 
+    /// Version recorded in the `schema_version` table.
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
+    pub fn schema_version(&self) -> Result<u32, CacheError> {
+        let conn = self.lock_conn()?;
+        conn.query_row("SELECT version FROM schema_version", [], |row| row.get(0))
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to query schema version: {}", e)))
+    }
+
+    /// Run SQLite's integrity check and look for rows that lost their media item.
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
+    pub fn check_integrity(&self) -> Result<IntegrityReport, CacheError> {
+        let conn = self.lock_conn()?;
+        let mut stmt = conn
+            .prepare("PRAGMA integrity_check")
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to prepare statement: {}", e)))?;
+        let sqlite_errors = stmt
+            .query_map([], |row| row.get::<_, String>(0))
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to run integrity check: {}", e)))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to run integrity check: {}", e)))?
+            .into_iter()
+            .filter(|line| line != "ok")
+            .collect();
+        let count = |sql: &str| -> Result<u64, CacheError> {
+            conn.query_row(sql, [], |row| row.get::<_, i64>(0))
+                .map(|n| n as u64)
+                .map_err(|e| CacheError::DatabaseError(format!("Failed to run integrity check: {}", e)))
+        };
+        let report = IntegrityReport {
+            sqlite_errors,
+            orphaned_metadata: count(
+                "SELECT COUNT(*) FROM media_metadata WHERE media_item_id NOT IN (SELECT id FROM media_items)",
+            )?,
+            orphaned_album_links: count(
+                "SELECT COUNT(*) FROM album_media_items WHERE media_item_id NOT IN (SELECT id FROM media_items) \
+                 OR album_id NOT IN (SELECT id FROM albums)",
+            )?,
+            orphaned_faces: count(
+                "SELECT COUNT(*) FROM faces WHERE media_item_id NOT IN (SELECT id FROM media_items)",
+            )?,
+            missing_metadata: count(
+                "SELECT COUNT(*) FROM media_items WHERE id NOT IN (SELECT media_item_id FROM media_metadata)",
+            )?,
+        };
+        if !report.is_ok() {
+            tracing::warn!(?report, "Cache integrity problems found");
+        }
+        Ok(report)
+    }
+
     #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self, item)))]
     pub fn insert_media_item(&self, item: &api_client::MediaItem) -> Result<(), CacheError> {
-        let creation_ts = DateTime::parse_from_rfc3339(&item.media_metadata.creation_time)
-            .map_err(|e| CacheError::SerializationError(e.to_string()))?
-            .timestamp();
+        let (creation_ts, tz_offset) = Self::parse_creation_time(&item.media_metadata.creation_time)?;
         let width: i64 = item
             .media_metadata
             .width
@@ -254,8 +805,9 @@ impl CacheManager {
         let mut item_stmt = conn
             .prepare_cached(
                 "INSERT OR REPLACE INTO media_items (
-                    id, description, product_url, base_url, mime_type, filename
-                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                    id, description, product_url, base_url, mime_type, filename, deleted_at, is_favorite
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, (SELECT deleted_at FROM media_items WHERE id = ?1),
+                    MAX(?7, COALESCE((SELECT is_favorite FROM media_items WHERE id = ?1), 0)))",
             )
             .map_err(|e| CacheError::DatabaseError(format!("Failed to prepare statement: {}", e)))?;
         item_stmt
@@ -265,7 +817,8 @@ impl CacheManager {
                 item.product_url,
                 item.base_url,
                 item.mime_type,
-                item.filename
+                item.filename,
+                item.is_favorite
             ])
             .map_err(|e| {
                 CacheError::DatabaseError(format!("Failed to insert media item: {}", e))
@@ -274,8 +827,9 @@ impl CacheManager {
         let mut meta_stmt = conn
             .prepare_cached(
                 "INSERT OR REPLACE INTO media_metadata (
-                    media_item_id, creation_time, width, height, camera_make, camera_model, fps, status
-                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                    media_item_id, creation_time, width, height, camera_make, camera_model, fps, status,
+                    creation_tz_offset_minutes
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
             )
             .map_err(|e| CacheError::DatabaseError(format!("Failed to prepare statement: {}", e)))?;
         meta_stmt
@@ -288,6 +842,7 @@ impl CacheManager {
                 item.media_metadata.video.as_ref().and_then(|v| v.camera_model.clone()),
                 item.media_metadata.video.as_ref().and_then(|v| v.fps),
                 item.media_metadata.video.as_ref().and_then(|v| v.status.clone()),
+                tz_offset,
             ])
             .map_err(|e| CacheError::DatabaseError(format!("Failed to insert metadata: {}", e)))?;
 
@@ -304,23 +859,23 @@ impl CacheManager {
         let mut item_stmt = tx
             .prepare_cached(
                 "INSERT OR REPLACE INTO media_items (
-                    id, description, product_url, base_url, mime_type, filename
-                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                    id, description, product_url, base_url, mime_type, filename, deleted_at, is_favorite
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, (SELECT deleted_at FROM media_items WHERE id = ?1),
+                    MAX(?7, COALESCE((SELECT is_favorite FROM media_items WHERE id = ?1), 0)))",
             )
             .map_err(|e| CacheError::DatabaseError(format!("Failed to prepare statement: {}", e)))?;
 
         let mut meta_stmt = tx
             .prepare_cached(
                 "INSERT OR REPLACE INTO media_metadata (
-                    media_item_id, creation_time, width, height, camera_make, camera_model, fps, status
-                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                    media_item_id, creation_time, width, height, camera_make, camera_model, fps, status,
+                    creation_tz_offset_minutes
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
             )
             .map_err(|e| CacheError::DatabaseError(format!("Failed to prepare statement: {}", e)))?;
 
         for item in items {
-            let creation_ts = DateTime::parse_from_rfc3339(&item.media_metadata.creation_time)
-                .map_err(|e| CacheError::SerializationError(e.to_string()))?
-                .timestamp();
+            let (creation_ts, tz_offset) = Self::parse_creation_time(&item.media_metadata.creation_time)?;
             let width: i64 = item
                 .media_metadata
                 .width
@@ -339,7 +894,8 @@ impl CacheManager {
                     item.product_url,
                     item.base_url,
                     item.mime_type,
-                    item.filename
+                    item.filename,
+                    item.is_favorite
                 ])
                 .map_err(|e| CacheError::DatabaseError(format!("Failed to insert media item: {}", e)))?;
 
@@ -353,6 +909,7 @@ impl CacheManager {
                     item.media_metadata.video.as_ref().and_then(|v| v.camera_model.clone()),
                     item.media_metadata.video.as_ref().and_then(|v| v.fps),
                     item.media_metadata.video.as_ref().and_then(|v| v.status.clone()),
+                    tz_offset,
                 ])
                 .map_err(|e| CacheError::DatabaseError(format!("Failed to insert metadata: {}", e)))?;
         }
@@ -364,12 +921,196 @@ impl CacheManager {
         Ok(())
     }
 
+    /// Insert or update media items, skipping rows whose content is unchanged.
+    /// A refreshed `base_url` alone is written but not counted as a change.
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self, items)))]
+    pub fn upsert_media_items_batch(&self, items: &[api_client::MediaItem]) -> Result<UpsertStats, CacheError> {
+        let mut conn = self.lock_conn()?;
+        let tx = conn
+            .transaction()
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to start transaction: {}", e)))?;
+        let stats = Self::upsert_media_items_in(&tx, items)?;
+        tx.commit()
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to commit transaction: {}", e)))?;
+        Ok(stats)
+    }
+
+    fn upsert_media_items_in(conn: &Connection, items: &[api_client::MediaItem]) -> Result<UpsertStats, CacheError> {
+        let mut select_stmt = conn
+            .prepare_cached(
+                "SELECT m.description, m.product_url, m.base_url, m.mime_type, m.filename,
+                        md.creation_time, md.width, md.height, md.camera_make, md.camera_model, md.fps, md.status,
+                        md.creation_tz_offset_minutes
+                 FROM media_items m
+                 LEFT JOIN media_metadata md ON m.id = md.media_item_id
+                 WHERE m.id = ?1",
+            )
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to prepare statement: {}", e)))?;
+
+        let mut insert_stmt = conn
+            .prepare_cached(
+                "INSERT INTO media_items (
+                    id, description, product_url, base_url, mime_type, filename
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            )
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to prepare statement: {}", e)))?;
+
+        let mut update_stmt = conn
+            .prepare_cached(
+                "UPDATE media_items
+                 SET description = ?2, product_url = ?3, base_url = ?4, mime_type = ?5, filename = ?6
+                 WHERE id = ?1",
+            )
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to prepare statement: {}", e)))?;
+
+        let mut base_url_stmt = conn
+            .prepare_cached("UPDATE media_items SET base_url = ?2 WHERE id = ?1")
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to prepare statement: {}", e)))?;
+
+        let mut meta_stmt = conn
+            .prepare_cached(
+                "INSERT OR REPLACE INTO media_metadata (
+                    media_item_id, creation_time, width, height, camera_make, camera_model, fps, status,
+                    creation_tz_offset_minutes
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            )
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to prepare statement: {}", e)))?;
+
+        let mut stats = UpsertStats::default();
+        for item in items {
+            let (creation_ts, tz_offset) = Self::parse_creation_time(&item.media_metadata.creation_time)?;
+            let width: i64 = item
+                .media_metadata
+                .width
+                .parse::<i64>()
+                .map_err(|e| CacheError::SerializationError(e.to_string()))?;
+            let height: i64 = item
+                .media_metadata
+                .height
+                .parse::<i64>()
+                .map_err(|e| CacheError::SerializationError(e.to_string()))?;
+            let video = item.media_metadata.video.as_ref();
+            let camera_make = video.and_then(|v| v.camera_make.clone());
+            let camera_model = video.and_then(|v| v.camera_model.clone());
+            let fps = video.and_then(|v| v.fps);
+            let status = video.and_then(|v| v.status.clone());
+
+            let existing = select_stmt
+                .query_row(params![item.id], |row| {
+                    Ok((
+                        (
+                            row.get::<_, Option<String>>(0)?,
+                            row.get::<_, String>(1)?,
+                            row.get::<_, String>(3)?,
+                            row.get::<_, String>(4)?,
+                        ),
+                        (
+                            row.get::<_, Option<i64>>(5)?,
+                            row.get::<_, Option<i64>>(6)?,
+                            row.get::<_, Option<i64>>(7)?,
+                            row.get::<_, Option<String>>(8)?,
+                            row.get::<_, Option<String>>(9)?,
+                            row.get::<_, Option<f64>>(10)?,
+                            row.get::<_, Option<String>>(11)?,
+                            row.get::<_, Option<i32>>(12)?,
+                        ),
+                        row.get::<_, String>(2)?,
+                    ))
+                })
+                .optional()
+                .map_err(|e| CacheError::DatabaseError(format!("Failed to query media item: {}", e)))?;
+
+            let unchanged_base_url = match existing {
+                None => {
+                    insert_stmt
+                        .execute(params![
+                            item.id,
+                            item.description,
+                            item.product_url,
+                            item.base_url,
+                            item.mime_type,
+                            item.filename
+                        ])
+                        .map_err(|e| CacheError::DatabaseError(format!("Failed to insert media item: {}", e)))?;
+                    stats.inserted += 1;
+                    stats.inserted_ids.push(item.id.clone());
+                    None
+                }
+                Some((content, meta, base_url)) => {
+                    let same_content = content
+                        == (
+                            item.description.clone(),
+                            item.product_url.clone(),
+                            item.mime_type.clone(),
+                            item.filename.clone(),
+                        );
+                    let same_meta = meta
+                        == (
+                            Some(creation_ts),
+                            Some(width),
+                            Some(height),
+                            camera_make.clone(),
+                            camera_model.clone(),
+                            fps.map(f64::from),
+                            status.clone(),
+                            Some(tz_offset),
+                        );
+                    if same_content && same_meta {
+                        stats.unchanged += 1;
+                        Some(base_url)
+                    } else {
+                        update_stmt
+                            .execute(params![
+                                item.id,
+                                item.description,
+                                item.product_url,
+                                item.base_url,
+                                item.mime_type,
+                                item.filename
+                            ])
+                            .map_err(|e| CacheError::DatabaseError(format!("Failed to update media item: {}", e)))?;
+                        stats.updated += 1;
+                        None
+                    }
+                }
+            };
+
+            match unchanged_base_url {
+                Some(base_url) => {
+                    if base_url != item.base_url {
+                        base_url_stmt
+                            .execute(params![item.id, item.base_url])
+                            .map_err(|e| CacheError::DatabaseError(format!("Failed to update base url: {}", e)))?;
+                    }
+                }
+                None => {
+                    meta_stmt
+                        .execute(params![
+                            item.id,
+                            creation_ts,
+                            width,
+                            height,
+                            camera_make,
+                            camera_model,
+                            fps,
+                            status,
+                            tz_offset
+                        ])
+                        .map_err(|e| CacheError::DatabaseError(format!("Failed to insert metadata: {}", e)))?;
+                    stats.changed_ids.push(item.id.clone());
+                }
+            }
+        }
+
+        Ok(stats)
+    }
+
     #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
     pub fn get_media_item(&self, id: &str) -> Result<Option<api_client::MediaItem>, CacheError> {
         let conn = self.lock_conn()?;
         let mut stmt = conn
             .prepare_cached(
-                "SELECT m.id, m.description, m.product_url, m.base_url, m.mime_type, md.creation_time, md.width, md.height, md.camera_make, md.camera_model, md.fps, md.status, m.filename
+                "SELECT m.id, m.description, m.product_url, m.base_url, m.mime_type, md.creation_time, md.width, md.height, md.camera_make, md.camera_model, md.fps, md.status, m.filename, m.is_favorite, md.creation_tz_offset_minutes
                  FROM media_items m
                  JOIN media_metadata md ON m.id = md.media_item_id
                  WHERE m.id = ?1",
@@ -393,7 +1134,8 @@ impl CacheManager {
                 media_metadata: api_client::MediaMetadata {
                     creation_time: {
                         let ts: i64 = row.get(5).map_err(|e| CacheError::DatabaseError(e.to_string()))?;
-                        Self::ts_to_rfc3339(ts)
+                        let offset: i32 = row.get(14).map_err(|e| CacheError::DatabaseError(e.to_string()))?;
+                        Self::ts_to_rfc3339(ts, offset)
                     },
                     width: {
                         let w: i64 = row.get(6).map_err(|e| CacheError::DatabaseError(e.to_string()))?;
@@ -403,14 +1145,18 @@ impl CacheManager {
                         let h: i64 = row.get(7).map_err(|e| CacheError::DatabaseError(e.to_string()))?;
                         h.to_string()
                     },
-                    video: Some(api_client::VideoMetadata {
+                    video: Self::video_metadata(
+                        &row.get::<_, String>(4).map_err(|e| CacheError::DatabaseError(e.to_string()))?,
+                        api_client::VideoMetadata {
                         camera_make: row.get(8).map_err(|e| CacheError::DatabaseError(e.to_string()))?,
                         camera_model: row.get(9).map_err(|e| CacheError::DatabaseError(e.to_string()))?,
                         fps: row.get(10).map_err(|e| CacheError::DatabaseError(e.to_string()))?,
                         status: row.get(11).map_err(|e| CacheError::DatabaseError(e.to_string()))?,
-                    }),
+                        },
+                    ),
                 },
                 filename: row.get(12).map_err(|e| CacheError::DatabaseError(e.to_string()))?,
+                is_favorite: row.get(13).map_err(|e| CacheError::DatabaseError(e.to_string()))?,
             };
             Ok(Some(item))
         } else {
@@ -424,9 +1170,10 @@ impl CacheManager {
         let conn = self.lock_conn()?;
         let mut stmt = conn
             .prepare_cached(
-                "SELECT m.id, m.description, m.product_url, m.base_url, m.mime_type, md.creation_time, md.width, md.height, md.camera_make, md.camera_model, md.fps, md.status, m.filename
+                "SELECT m.id, m.description, m.product_url, m.base_url, m.mime_type, md.creation_time, md.width, md.height, md.camera_make, md.camera_model, md.fps, md.status, m.filename, m.is_favorite, md.creation_tz_offset_minutes
                  FROM media_items m
-                 JOIN media_metadata md ON m.id = md.media_item_id",
+                 JOIN media_metadata md ON m.id = md.media_item_id
+                 WHERE m.deleted_at IS NULL",
             )
             .map_err(|e| CacheError::DatabaseError(format!("Failed to prepare statement: {}", e)))?;
 
@@ -442,10 +1189,10 @@ impl CacheManager {
                     base_url: row.get(3)?,
                     mime_type: row.get(4)?,
                     media_metadata: api_client::MediaMetadata {
-                        creation_time: Self::ts_to_rfc3339(ts),
+                        creation_time: Self::ts_to_rfc3339(ts, row.get(14)?),
                         width: w.to_string(),
                         height: h.to_string(),
-                        video: Some(api_client::VideoMetadata {
+                        video: Self::video_metadata(&row.get::<_, String>(4)?, api_client::VideoMetadata {
                             camera_make: row.get(8)?,
                             camera_model: row.get(9)?,
                             fps: row.get(10)?,
@@ -453,6 +1200,7 @@ impl CacheManager {
                         }),
                     },
                     filename: row.get(12)?,
+                    is_favorite: row.get(13)?,
                 })
             })
             .map_err(|e| {
@@ -460,7 +1208,7 @@ impl CacheManager {
             })?;
 
         let count: i64 = conn
-            .query_row("SELECT COUNT(*) FROM media_items", [], |row| row.get(0))
+            .query_row("SELECT COUNT(*) FROM media_items WHERE deleted_at IS NULL", [], |row| row.get(0))
             .map_err(|e| CacheError::DatabaseError(format!("Failed to count items: {}", e)))?;
         let mut items = Vec::with_capacity(count as usize);
         for item_result in media_item_iter {
@@ -477,10 +1225,10 @@ impl CacheManager {
         let conn = self.lock_conn()?;
         let mut stmt = conn
             .prepare_cached(
-                "SELECT m.id, m.description, m.product_url, m.base_url, m.mime_type, md.creation_time, md.width, md.height, md.camera_make, md.camera_model, md.fps, md.status, m.filename
+                "SELECT m.id, m.description, m.product_url, m.base_url, m.mime_type, md.creation_time, md.width, md.height, md.camera_make, md.camera_model, md.fps, md.status, m.filename, m.is_favorite, md.creation_tz_offset_minutes
                  FROM media_items m
                  JOIN media_metadata md ON m.id = md.media_item_id
-                 WHERE m.mime_type = ?1",
+                 WHERE m.mime_type = ?1 AND m.deleted_at IS NULL",
             )
             .map_err(|e| CacheError::DatabaseError(format!("Failed to prepare statement: {}", e)))?;
 
@@ -496,10 +1244,10 @@ impl CacheManager {
                     base_url: row.get(3)?,
                     mime_type: row.get(4)?,
                     media_metadata: api_client::MediaMetadata {
-                        creation_time: Self::ts_to_rfc3339(ts),
+                        creation_time: Self::ts_to_rfc3339(ts, row.get(14)?),
                         width: w.to_string(),
                         height: h.to_string(),
-                        video: Some(api_client::VideoMetadata {
+                        video: Self::video_metadata(&row.get::<_, String>(4)?, api_client::VideoMetadata {
                             camera_make: row.get(8)?,
                             camera_model: row.get(9)?,
                             fps: row.get(10)?,
@@ -507,6 +1255,7 @@ impl CacheManager {
                         }),
                     },
                     filename: row.get(12)?,
+                    is_favorite: row.get(13)?,
                 })
             })
             .map_err(|e| CacheError::DatabaseError(format!("Failed to query media items: {}", e)))?;
@@ -520,8 +1269,10 @@ impl CacheManager {
         Ok(items)
     }
 
-    /// Retrieve media items filtered by optional camera model, date range and favorite flag.
+    /// Retrieve media items filtered by optional camera model, date range,
+    /// favorite flag and album.
     #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
+    #[allow(clippy::too_many_arguments)]
     pub fn query_media_items(
         &self,
         camera_model: Option<&str>,
@@ -531,41 +1282,128 @@ impl CacheManager {
         favorite: Option<bool>,
         mime_type: Option<&str>,
         text: Option<&str>,
+        album_id: Option<&str>,
     ) -> Result<Vec<api_client::MediaItem>, CacheError> {
+        self.query(&MediaQuery {
+            camera_model: camera_model.map(str::to_string),
+            camera_make: camera_make.map(str::to_string),
+            start,
+            end,
+            favorite,
+            mime_type: mime_type.map(str::to_string),
+            text: text.map(str::to_string),
+            album_id: album_id.map(str::to_string),
+            ..MediaQuery::default()
+        })
+    }
+
+    /// WHERE clause and parameters for `query`. Only filters that are set
+    /// become predicates: a `?1 IS NULL OR col = ?1` term keeps SQLite from
+    /// using the column's index even when the filter is unset.
+    fn media_query_filter(query: &MediaQuery) -> (String, Vec<rusqlite::types::Value>) {
+        use rusqlite::types::Value;
+
+        // Nearly every row is out of the trash, so `+` keeps the deleted_at
+        // index from being picked over one that narrows the search
+        let mut predicates = vec!["+m.deleted_at IS NULL".to_string()];
+        let mut values: Vec<Value> = Vec::new();
+        // Every `?` in a predicate stands for the one value it is given
+        let mut filter = |predicate: &str, value: Value| {
+            values.push(value);
+            predicates.push(predicate.replace('?', &format!("?{}", values.len())));
+        };
+        let like = |pattern: &str| Value::Text(format!("%{}%", pattern));
+        let time = query.time_mode.creation_time();
+
+        if let Some(filename) = &query.filename {
+            filter("m.filename LIKE ?", like(filename));
+        }
+        if let Some(description) = &query.description {
+            filter("m.description LIKE ?", like(description));
+        }
+        if let Some(text) = &query.text {
+            filter("(m.filename LIKE ? OR m.description LIKE ?)", like(text));
+        }
+        if let Some(mime) = &query.mime_type {
+            filter("m.mime_type = ?", Value::Text(mime.clone()));
+        }
+        if let Some(model) = &query.camera_model {
+            filter("md.camera_model = ?", Value::Text(model.clone()));
+        }
+        if let Some(make) = &query.camera_make {
+            filter("md.camera_make = ?", Value::Text(make.clone()));
+        }
+        if let Some(favorite) = query.favorite {
+            filter("m.is_favorite = ?", Value::Integer(favorite as i64));
+        }
+        if let Some(start) = query.start {
+            filter(&format!("{} >= ?", time), Value::Integer(start.timestamp()));
+        }
+        if let Some(end) = query.end {
+            filter(&format!("{} <= ?", time), Value::Integer(end.timestamp()));
+        }
+        if let Some(album) = &query.album_id {
+            filter(
+                "m.id IN (SELECT media_item_id FROM album_media_items WHERE album_id = ?)",
+                Value::Text(album.clone()),
+            );
+        }
+        if let Some(person) = &query.person {
+            filter(
+                "m.id IN (SELECT f.media_item_id FROM faces f, json_each(f.faces_json) j \
+                 WHERE json_extract(j.value, '$.name') = ? AND NOT coalesce(json_extract(j.value, '$.ignored'), 0))",
+                Value::Text(person.clone()),
+            );
+        }
+        if let Some(faces) = query.faces {
+            predicates.push(format!(
+                "m.id {}IN (SELECT media_item_id FROM faces WHERE json_array_length(faces_json) > 0)",
+                if faces { "" } else { "NOT " }
+            ));
+        }
+        (predicates.join(" AND "), values)
+    }
+
+    /// SQL and parameters for `query`.
+    fn media_query_sql(query: &MediaQuery) -> (String, Vec<rusqlite::types::Value>) {
+        let (filter, mut values) = Self::media_query_filter(query);
+        let order = match query.sort {
+            MediaSort::NewestFirst => "md.creation_time DESC, m.id",
+            MediaSort::OldestFirst => "md.creation_time ASC, m.id",
+            MediaSort::Filename => "m.filename COLLATE NOCASE, m.id",
+        };
+        let mut sql = format!(
+            concat!(
+                "SELECT m.id, m.description, m.product_url, m.base_url, m.mime_type, md.creation_time, md.width, md.height, md.camera_make, md.camera_model, md.fps, md.status, m.filename, m.is_favorite, md.creation_tz_offset_minutes ",
+                "FROM media_items m ",
+                "JOIN media_metadata md ON m.id = md.media_item_id ",
+                "WHERE {} ",
+                "ORDER BY {}"
+            ),
+            filter,
+            order,
+        );
+        if let Some(limit) = query.limit {
+            values.push(rusqlite::types::Value::Integer(limit as i64));
+            sql.push_str(&format!(" LIMIT ?{}", values.len()));
+        }
+        (sql, values)
+    }
+
+    /// Retrieve media items matching all filters set on `query`.
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
+    pub fn query(&self, query: &MediaQuery) -> Result<Vec<api_client::MediaItem>, CacheError> {
         let timer = std::time::Instant::now();
+        let (sql, values) = Self::media_query_sql(query);
 
-      
         let conn = self.lock_conn()?;
-        let sql = concat!(
-            "SELECT m.id, m.description, m.product_url, m.base_url, m.mime_type, md.creation_time, md.width, md.height, md.camera_make, md.camera_model, md.fps, md.status, m.filename ",
-            "FROM media_items m ",
-            "JOIN media_metadata md ON m.id = md.media_item_id ",
-            "WHERE (?1 IS NULL OR md.camera_model = ?1) ",
-            "AND (?2 IS NULL OR md.camera_make = ?2) ",
-            "AND (?3 IS NULL OR md.creation_time >= ?3) ",
-            "AND (?4 IS NULL OR md.creation_time <= ?4) ",
-            "AND (?5 IS NULL OR m.is_favorite = ?5) ",
-            "AND (?6 IS NULL OR m.mime_type = ?6) ",
-            "AND (?7 IS NULL OR m.filename LIKE ?7 OR m.description LIKE ?7)"
-        );
         let mut stmt = conn
-            .prepare_cached(sql)
+            .prepare_cached(&sql)
             .map_err(|e| CacheError::DatabaseError(format!("Failed to prepare statement: {}", e)))?;
 
-        let fav_val: Option<i64> = favorite.map(|f| if f { 1 } else { 0 });
-        let like_pattern = text.map(|t| format!("%{}%", t));
-
         let iter = stmt
             .query_map(
-                params![
-                    camera_model,
-                    camera_make,
-                    start.map(|s| s.timestamp()),
-                    end.map(|e| e.timestamp()),
-                    fav_val,
-                    mime_type,
-                    like_pattern.as_deref()
-                ],
+                rusqlite::params_from_iter(values),
                 |row| {
                     let ts: i64 = row.get(5)?;
                     let w: i64 = row.get(6)?;
@@ -577,10 +1415,10 @@ impl CacheManager {
                         base_url: row.get(3)?,
                         mime_type: row.get(4)?,
                         media_metadata: api_client::MediaMetadata {
-                            creation_time: Self::ts_to_rfc3339(ts),
+                            creation_time: Self::ts_to_rfc3339(ts, row.get(14)?),
                             width: w.to_string(),
                             height: h.to_string(),
-                            video: Some(api_client::VideoMetadata {
+                            video: Self::video_metadata(&row.get::<_, String>(4)?, api_client::VideoMetadata {
                                 camera_make: row.get(8)?,
                                 camera_model: row.get(9)?,
                                 fps: row.get(10)?,
@@ -588,6 +1426,7 @@ impl CacheManager {
                             }),
                         },
                         filename: row.get(12)?,
+                        is_favorite: row.get(13)?,
                     })
                 },
             )
@@ -599,22 +1438,67 @@ impl CacheManager {
                 CacheError::DatabaseError(format!("Failed to retrieve media item from iterator: {}", e))
             })?);
         }
-        tracing::info!("query_time_ms" = %timer.elapsed().as_millis(), "query" = "general", "count" = items.len());
-
-      
+        tracing::info!("query_time_ms" = %timer.elapsed().as_millis(), "query" = "builder", "count" = items.len());
         Ok(items)
     }
 
+    /// Number of media items matching all filters set on `query`, ignoring its limit.
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
+    pub fn query_count(&self, query: &MediaQuery) -> Result<u64, CacheError> {
+        let (filter, values) = Self::media_query_filter(query);
+        let sql = format!(
+            "SELECT COUNT(*) FROM media_items m JOIN media_metadata md ON m.id = md.media_item_id WHERE {}",
+            filter
+        );
+        let conn = self.lock_conn()?;
+        let mut stmt = conn
+            .prepare_cached(&sql)
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to prepare statement: {}", e)))?;
+        let count: i64 = stmt
+            .query_row(rusqlite::params_from_iter(values), |row| row.get(0))
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to count media items: {}", e)))?;
+        Ok(count as u64)
+    }
+
+    /// `EXPLAIN QUERY PLAN` for `query`, one step per line and indented
+    /// below its parent, to check which indexes it uses.
+    #[cfg(debug_assertions)]
+    pub fn explain(&self, query: &MediaQuery) -> Result<String, CacheError> {
+        let (sql, values) = Self::media_query_sql(query);
+        let conn = self.lock_conn()?;
+        let mut stmt = conn
+            .prepare(&format!("EXPLAIN QUERY PLAN {}", sql))
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to prepare statement: {}", e)))?;
+        let steps = stmt
+            .query_map(rusqlite::params_from_iter(values), |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?, row.get::<_, String>(3)?))
+            })
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to explain query: {}", e)))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to explain query: {}", e)))?;
+
+        let mut depths: HashMap<i64, usize> = HashMap::new();
+        let mut plan = String::new();
+        for (id, parent, detail) in steps {
+            let depth = depths.get(&parent).map_or(0, |d| d + 1);
+            depths.insert(id, depth);
+            plan.push_str(&"  ".repeat(depth));
+            plan.push_str(&detail);
+            plan.push('\n');
+        }
+        Ok(plan)
+    }
+
     #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
     pub fn get_media_items_by_camera_model(&self, model: &str) -> Result<Vec<api_client::MediaItem>, CacheError> {
         let start_time = std::time::Instant::now();
         let conn = self.lock_conn()?;
         let mut stmt = conn
             .prepare_cached(
-                "SELECT m.id, m.description, m.product_url, m.base_url, m.mime_type, md.creation_time, md.width, md.height, md.camera_make, md.camera_model, md.fps, md.status, m.filename
+                "SELECT m.id, m.description, m.product_url, m.base_url, m.mime_type, md.creation_time, md.width, md.height, md.camera_make, md.camera_model, md.fps, md.status, m.filename, m.is_favorite, md.creation_tz_offset_minutes
                  FROM media_items m
                  JOIN media_metadata md ON m.id = md.media_item_id
-                 WHERE md.camera_model = ?1",
+                 WHERE md.camera_model = ?1 AND m.deleted_at IS NULL",
             )
             .map_err(|e| CacheError::DatabaseError(format!("Failed to prepare statement: {}", e)))?;
 
@@ -630,10 +1514,10 @@ impl CacheManager {
                     base_url: row.get(3)?,
                     mime_type: row.get(4)?,
                     media_metadata: api_client::MediaMetadata {
-                        creation_time: Self::ts_to_rfc3339(ts),
+                        creation_time: Self::ts_to_rfc3339(ts, row.get(14)?),
                         width: w.to_string(),
                         height: h.to_string(),
-                        video: Some(api_client::VideoMetadata {
+                        video: Self::video_metadata(&row.get::<_, String>(4)?, api_client::VideoMetadata {
                             camera_make: row.get(8)?,
                             camera_model: row.get(9)?,
                             fps: row.get(10)?,
@@ -641,6 +1525,7 @@ impl CacheManager {
                         }),
                     },
                     filename: row.get(12)?,
+                    is_favorite: row.get(13)?,
                 })
             })
             .map_err(|e| CacheError::DatabaseError(format!("Failed to query media items: {}", e)))?;
@@ -661,10 +1546,10 @@ impl CacheManager {
         let conn = self.lock_conn()?;
         let mut stmt = conn
             .prepare_cached(
-                "SELECT m.id, m.description, m.product_url, m.base_url, m.mime_type, md.creation_time, md.width, md.height, md.camera_make, md.camera_model, md.fps, md.status, m.filename
+                "SELECT m.id, m.description, m.product_url, m.base_url, m.mime_type, md.creation_time, md.width, md.height, md.camera_make, md.camera_model, md.fps, md.status, m.filename, m.is_favorite, md.creation_tz_offset_minutes
                  FROM media_items m
                  JOIN media_metadata md ON m.id = md.media_item_id
-                 WHERE md.camera_make = ?1",
+                 WHERE md.camera_make = ?1 AND m.deleted_at IS NULL",
             )
             .map_err(|e| CacheError::DatabaseError(format!("Failed to prepare statement: {}", e)))?;
 
@@ -680,10 +1565,10 @@ impl CacheManager {
                     base_url: row.get(3)?,
                     mime_type: row.get(4)?,
                     media_metadata: api_client::MediaMetadata {
-                        creation_time: Self::ts_to_rfc3339(ts),
+                        creation_time: Self::ts_to_rfc3339(ts, row.get(14)?),
                         width: w.to_string(),
                         height: h.to_string(),
-                        video: Some(api_client::VideoMetadata {
+                        video: Self::video_metadata(&row.get::<_, String>(4)?, api_client::VideoMetadata {
                             camera_make: row.get(8)?,
                             camera_model: row.get(9)?,
                             fps: row.get(10)?,
@@ -691,6 +1576,7 @@ impl CacheManager {
                         }),
                     },
                     filename: row.get(12)?,
+                    is_favorite: row.get(13)?,
                 })
             })
             .map_err(|e| CacheError::DatabaseError(format!("Failed to query media items: {}", e)))?;
@@ -712,10 +1598,10 @@ impl CacheManager {
         let conn = self.lock_conn()?;
         let mut stmt = conn
             .prepare_cached(
-                "SELECT m.id, m.description, m.product_url, m.base_url, m.mime_type, md.creation_time, md.width, md.height, md.camera_make, md.camera_model, md.fps, md.status, m.filename
+                "SELECT m.id, m.description, m.product_url, m.base_url, m.mime_type, md.creation_time, md.width, md.height, md.camera_make, md.camera_model, md.fps, md.status, m.filename, m.is_favorite, md.creation_tz_offset_minutes
                  FROM media_items m
                  JOIN media_metadata md ON m.id = md.media_item_id
-                 WHERE m.filename LIKE ?1",
+                 WHERE m.filename LIKE ?1 AND m.deleted_at IS NULL",
             )
             .map_err(|e| CacheError::DatabaseError(format!("Failed to prepare statement: {}", e)))?;
 
@@ -731,10 +1617,10 @@ impl CacheManager {
                     base_url: row.get(3)?,
                     mime_type: row.get(4)?,
                     media_metadata: api_client::MediaMetadata {
-                        creation_time: Self::ts_to_rfc3339(ts),
+                        creation_time: Self::ts_to_rfc3339(ts, row.get(14)?),
                         width: w.to_string(),
                         height: h.to_string(),
-                        video: Some(api_client::VideoMetadata {
+                        video: Self::video_metadata(&row.get::<_, String>(4)?, api_client::VideoMetadata {
                             camera_make: row.get(8)?,
                             camera_model: row.get(9)?,
                             fps: row.get(10)?,
@@ -742,6 +1628,7 @@ impl CacheManager {
                         }),
                     },
                     filename: row.get(12)?,
+                    is_favorite: row.get(13)?,
                 })
             })
             .map_err(|e| CacheError::DatabaseError(format!("Failed to query media items: {}", e)))?;
@@ -762,10 +1649,10 @@ impl CacheManager {
         let conn = self.lock_conn()?;
         let mut stmt = conn
             .prepare_cached(
-                "SELECT m.id, m.description, m.product_url, m.base_url, m.mime_type, md.creation_time, md.width, md.height, md.camera_make, md.camera_model, md.fps, md.status, m.filename
+                "SELECT m.id, m.description, m.product_url, m.base_url, m.mime_type, md.creation_time, md.width, md.height, md.camera_make, md.camera_model, md.fps, md.status, m.filename, m.is_favorite, md.creation_tz_offset_minutes
                  FROM media_items m
                  JOIN media_metadata md ON m.id = md.media_item_id
-                 WHERE m.description LIKE ?1",
+                 WHERE m.description LIKE ?1 AND m.deleted_at IS NULL",
             )
             .map_err(|e| CacheError::DatabaseError(format!("Failed to prepare statement: {}", e)))?;
 
@@ -781,10 +1668,10 @@ impl CacheManager {
                     base_url: row.get(3)?,
                     mime_type: row.get(4)?,
                     media_metadata: api_client::MediaMetadata {
-                        creation_time: Self::ts_to_rfc3339(ts),
+                        creation_time: Self::ts_to_rfc3339(ts, row.get(14)?),
                         width: w.to_string(),
                         height: h.to_string(),
-                        video: Some(api_client::VideoMetadata {
+                        video: Self::video_metadata(&row.get::<_, String>(4)?, api_client::VideoMetadata {
                             camera_make: row.get(8)?,
                             camera_model: row.get(9)?,
                             fps: row.get(10)?,
@@ -792,6 +1679,7 @@ impl CacheManager {
                         }),
                     },
                     filename: row.get(12)?,
+                    is_favorite: row.get(13)?,
                 })
             })
             .map_err(|e| CacheError::DatabaseError(format!("Failed to query media items: {}", e)))?;
@@ -805,21 +1693,27 @@ impl CacheManager {
         Ok(items)
     }
 
+    /// Full-text search, optionally limited to the items of one album.
     #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
-    pub fn get_media_items_by_text(&self, pattern: &str) -> Result<Vec<api_client::MediaItem>, CacheError> {
+    pub fn get_media_items_by_text(
+        &self,
+        pattern: &str,
+        album_id: Option<&str>,
+    ) -> Result<Vec<api_client::MediaItem>, CacheError> {
         let conn = self.lock_conn()?;
         let mut stmt = conn
             .prepare_cached(
-                "SELECT m.id, m.description, m.product_url, m.base_url, m.mime_type, md.creation_time, md.width, md.height, md.camera_make, md.camera_model, md.fps, md.status, m.filename
+                "SELECT m.id, m.description, m.product_url, m.base_url, m.mime_type, md.creation_time, md.width, md.height, md.camera_make, md.camera_model, md.fps, md.status, m.filename, m.is_favorite, md.creation_tz_offset_minutes
                  FROM media_items_fts f
                  JOIN media_items m ON m.id = f.media_item_id
                  JOIN media_metadata md ON m.id = md.media_item_id
-                 WHERE f MATCH ?1",
+                 WHERE media_items_fts MATCH ?1 AND m.deleted_at IS NULL
+                 AND (?2 IS NULL OR m.id IN (SELECT media_item_id FROM album_media_items WHERE album_id = ?2))",
             )
             .map_err(|e| CacheError::DatabaseError(format!("Failed to prepare statement: {}", e)))?;
 
         let iter = stmt
-            .query_map(params![pattern], |row| {
+            .query_map(params![pattern, album_id], |row| {
                 let ts: i64 = row.get(5)?;
                 let w: i64 = row.get(6)?;
                 let h: i64 = row.get(7)?;
@@ -830,10 +1724,10 @@ impl CacheManager {
                     base_url: row.get(3)?,
                     mime_type: row.get(4)?,
                     media_metadata: api_client::MediaMetadata {
-                        creation_time: Self::ts_to_rfc3339(ts),
+                        creation_time: Self::ts_to_rfc3339(ts, row.get(14)?),
                         width: w.to_string(),
                         height: h.to_string(),
-                        video: Some(api_client::VideoMetadata {
+                        video: Self::video_metadata(&row.get::<_, String>(4)?, api_client::VideoMetadata {
                             camera_make: row.get(8)?,
                             camera_model: row.get(9)?,
                             fps: row.get(10)?,
@@ -841,6 +1735,7 @@ impl CacheManager {
                         }),
                     },
                     filename: row.get(12)?,
+                    is_favorite: row.get(13)?,
                 })
             })
             .map_err(|e| CacheError::DatabaseError(format!("Failed to query media items: {}", e)))?;
@@ -859,10 +1754,10 @@ impl CacheManager {
         let conn = self.lock_conn()?;
         let mut stmt = conn
             .prepare_cached(
-                "SELECT m.id, m.description, m.product_url, m.base_url, m.mime_type, md.creation_time, md.width, md.height, md.camera_make, md.camera_model, md.fps, md.status, m.filename
+                "SELECT m.id, m.description, m.product_url, m.base_url, m.mime_type, md.creation_time, md.width, md.height, md.camera_make, md.camera_model, md.fps, md.status, m.filename, m.is_favorite, md.creation_tz_offset_minutes
                  FROM media_items m
                  JOIN media_metadata md ON m.id = md.media_item_id
-                 WHERE m.is_favorite = 1",
+                 WHERE m.is_favorite = 1 AND m.deleted_at IS NULL",
             )
             .map_err(|e| CacheError::DatabaseError(format!("Failed to prepare statement: {}", e)))?;
 
@@ -878,10 +1773,10 @@ impl CacheManager {
                     base_url: row.get(3)?,
                     mime_type: row.get(4)?,
                     media_metadata: api_client::MediaMetadata {
-                        creation_time: Self::ts_to_rfc3339(ts),
+                        creation_time: Self::ts_to_rfc3339(ts, row.get(14)?),
                         width: w.to_string(),
                         height: h.to_string(),
-                        video: Some(api_client::VideoMetadata {
+                        video: Self::video_metadata(&row.get::<_, String>(4)?, api_client::VideoMetadata {
                             camera_make: row.get(8)?,
                             camera_model: row.get(9)?,
                             fps: row.get(10)?,
@@ -889,6 +1784,7 @@ impl CacheManager {
                         }),
                     },
                     filename: row.get(12)?,
+                    is_favorite: row.get(13)?,
                 })
             })
             .map_err(|e| CacheError::DatabaseError(format!("Failed to query media items: {}", e)))?;
@@ -907,10 +1803,10 @@ impl CacheManager {
         let conn = self.lock_conn()?;
         let mut stmt = conn
             .prepare_cached(
-                "SELECT m.id, m.description, m.product_url, m.base_url, m.mime_type, md.creation_time, md.width, md.height, md.camera_make, md.camera_model, md.fps, md.status, m.filename
+                "SELECT m.id, m.description, m.product_url, m.base_url, m.mime_type, md.creation_time, md.width, md.height, md.camera_make, md.camera_model, md.fps, md.status, m.filename, m.is_favorite, md.creation_tz_offset_minutes
                  FROM media_items m
                  JOIN media_metadata md ON m.id = md.media_item_id
-                 WHERE m.is_favorite = ?1",
+                 WHERE m.is_favorite = ?1 AND m.deleted_at IS NULL",
             )
             .map_err(|e| CacheError::DatabaseError(format!("Failed to prepare statement: {}", e)))?;
 
@@ -926,10 +1822,10 @@ impl CacheManager {
                     base_url: row.get(3)?,
                     mime_type: row.get(4)?,
                     media_metadata: api_client::MediaMetadata {
-                        creation_time: Self::ts_to_rfc3339(ts),
+                        creation_time: Self::ts_to_rfc3339(ts, row.get(14)?),
                         width: w.to_string(),
                         height: h.to_string(),
-                        video: Some(api_client::VideoMetadata {
+                        video: Self::video_metadata(&row.get::<_, String>(4)?, api_client::VideoMetadata {
                             camera_make: row.get(8)?,
                             camera_model: row.get(9)?,
                             fps: row.get(10)?,
@@ -937,6 +1833,7 @@ impl CacheManager {
                         }),
                     },
                     filename: row.get(12)?,
+                    is_favorite: row.get(13)?,
                 })
             })
             .map_err(|e| CacheError::DatabaseError(format!("Failed to query media items: {}", e)))?;
@@ -953,6 +1850,10 @@ impl CacheManager {
     #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self, album)))]
     pub fn insert_album(&self, album: &api_client::Album) -> Result<(), CacheError> {
         let conn = self.lock_conn()?;
+        Self::insert_album_in(&conn, album)
+    }
+
+    fn insert_album_in(conn: &Connection, album: &api_client::Album) -> Result<(), CacheError> {
         let mut stmt = conn
             .prepare_cached(
                 "INSERT OR REPLACE INTO albums (
@@ -1028,42 +1929,192 @@ impl CacheManager {
         Ok(albums)
     }
 
+    /// Albums that contain the given media item.
     #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
-    pub fn associate_media_item_with_album(&self, media_item_id: &str, album_id: &str) -> Result<(), CacheError> {
+    pub fn get_albums_for_media_item(&self, media_item_id: &str) -> Result<Vec<api_client::Album>, CacheError> {
         let conn = self.lock_conn()?;
         let mut stmt = conn
-            .prepare_cached(
-                "INSERT OR REPLACE INTO album_media_items (album_id, media_item_id) VALUES (?1, ?2)",
-            )
+            .prepare_cached(ALBUMS_FOR_MEDIA_ITEM_SQL)
             .map_err(|e| CacheError::DatabaseError(format!("Failed to prepare statement: {}", e)))?;
-        stmt.execute(params![album_id, media_item_id])
-            .map_err(|e| CacheError::DatabaseError(format!("Failed to associate media item with album: {}", e)))?;
-        Ok(())
-    }
 
-    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
-    pub fn remove_media_item_from_album(&self, media_item_id: &str, album_id: &str) -> Result<(), CacheError> {
-        let conn = self.lock_conn()?;
-        let mut stmt = conn
-            .prepare_cached(
-                "DELETE FROM album_media_items WHERE album_id = ?1 AND media_item_id = ?2",
-            )
-            .map_err(|e| CacheError::DatabaseError(format!("Failed to prepare statement: {}", e)))?;
+        let iter = stmt
+            .query_map(params![media_item_id], |row| {
+                Ok(api_client::Album {
+                    id: row.get(0)?,
+                    title: row.get(1)?,
+                    product_url: row.get(2)?,
+                    is_writeable: row.get::<_, Option<i64>>(3)?.map(|v| v != 0),
+                    media_items_count: row.get(4)?,
+                    cover_photo_base_url: row.get(5)?,
+                    cover_photo_media_item_id: row.get(6)?,
+                })
+            })
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to query albums: {}", e)))?;
+
+        iter.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to retrieve album: {}", e)))
+    }
+
+    /// Number of cached media items per album, keyed by album id. Albums
+    /// without cached items are left out.
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
+    pub fn get_album_item_counts(&self) -> Result<HashMap<String, u64>, CacheError> {
+        let conn = self.lock_conn()?;
+        let mut stmt = conn
+            .prepare_cached(
+                "SELECT am.album_id, COUNT(*) FROM album_media_items am \
+                 JOIN media_items m ON m.id = am.media_item_id \
+                 WHERE m.deleted_at IS NULL GROUP BY am.album_id",
+            )
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to prepare statement: {}", e)))?;
+        let iter = stmt
+            .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)? as u64)))
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to query album item counts: {}", e)))?;
+        iter.collect::<Result<HashMap<_, _>, _>>()
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to retrieve album item counts: {}", e)))
+    }
+
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
+    pub fn associate_media_item_with_album(&self, media_item_id: &str, album_id: &str) -> Result<(), CacheError> {
+        let conn = self.lock_conn()?;
+        Self::associate_media_item_with_album_in(&conn, media_item_id, album_id)
+    }
+
+    fn associate_media_item_with_album_in(
+        conn: &Connection,
+        media_item_id: &str,
+        album_id: &str,
+    ) -> Result<(), CacheError> {
+        let mut stmt = conn
+            .prepare_cached(
+                "INSERT OR REPLACE INTO album_media_items (album_id, media_item_id) VALUES (?1, ?2)",
+            )
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to prepare statement: {}", e)))?;
+        stmt.execute(params![album_id, media_item_id])
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to associate media item with album: {}", e)))?;
+        Ok(())
+    }
+
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
+    pub fn remove_media_item_from_album(&self, media_item_id: &str, album_id: &str) -> Result<(), CacheError> {
+        let conn = self.lock_conn()?;
+        let mut stmt = conn
+            .prepare_cached(
+                "DELETE FROM album_media_items WHERE album_id = ?1 AND media_item_id = ?2",
+            )
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to prepare statement: {}", e)))?;
         stmt.execute(params![album_id, media_item_id])
             .map_err(|e| CacheError::DatabaseError(format!("Failed to remove media item from album: {}", e)))?;
         Ok(())
     }
 
+    /// Associate several media items with an album in one transaction.
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self, media_item_ids)))]
+    pub fn associate_media_items_with_album(&self, media_item_ids: &[String], album_id: &str) -> Result<(), CacheError> {
+        self.batch_execute(
+            "INSERT OR REPLACE INTO album_media_items (album_id, media_item_id) VALUES (?1, ?2)",
+            media_item_ids,
+            |stmt, id| stmt.execute(params![album_id, id]),
+        )
+    }
+
+    /// Remove several media items from an album in one transaction.
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self, media_item_ids)))]
+    pub fn remove_media_items_from_album(&self, media_item_ids: &[String], album_id: &str) -> Result<(), CacheError> {
+        self.batch_execute(
+            "DELETE FROM album_media_items WHERE album_id = ?1 AND media_item_id = ?2",
+            media_item_ids,
+            |stmt, id| stmt.execute(params![album_id, id]),
+        )
+    }
+
+    /// Replace the album's membership with `media_item_ids` in one
+    /// transaction, dropping items no longer in the album.
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self, media_item_ids)))]
+    pub fn set_album_media_items(&self, album_id: &str, media_item_ids: &[String]) -> Result<(), CacheError> {
+        let mut conn = self.lock_conn()?;
+        let tx = conn
+            .transaction()
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to start transaction: {}", e)))?;
+        tx.execute("DELETE FROM album_media_items WHERE album_id = ?1", params![album_id])
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to clear album items: {}", e)))?;
+        for id in media_item_ids {
+            Self::associate_media_item_with_album_in(&tx, id, album_id)?;
+        }
+        tx.commit()
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to commit transaction: {}", e)))
+    }
+
+    /// Set the favorite flag for several media items in one transaction.
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self, ids)))]
+    pub fn set_favorites(&self, ids: &[String], fav: bool) -> Result<(), CacheError> {
+        self.batch_execute(
+            "UPDATE media_items SET is_favorite = ?1 WHERE id = ?2",
+            ids,
+            |stmt, id| stmt.execute(params![if fav { 1 } else { 0 }, id]),
+        )
+    }
+
+    /// Make `ids` the favorites among the items GooglePicz uploaded, e.g.
+    /// after reading them back from the favorites album. Other items can not
+    /// go in that album, so their favorite flag stays as it is. Ids not in
+    /// the cache are skipped.
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self, ids)))]
+    pub fn replace_favorites(&self, ids: &[String]) -> Result<(), CacheError> {
+        let mut conn = self.lock_conn()?;
+        let tx = conn
+            .transaction()
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to start transaction: {}", e)))?;
+        tx.execute(
+            "UPDATE media_items SET is_favorite = 0 \
+             WHERE is_favorite = 1 AND id IN (SELECT media_item_id FROM uploaded_files)",
+            [],
+        )
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to clear favorites: {}", e)))?;
+        {
+            let mut stmt = tx
+                .prepare_cached("UPDATE media_items SET is_favorite = 1 WHERE id = ?1")
+                .map_err(|e| CacheError::DatabaseError(format!("Failed to prepare statement: {}", e)))?;
+            for id in ids {
+                stmt.execute(params![id])
+                    .map_err(|e| CacheError::DatabaseError(format!("Failed to update {}: {}", id, e)))?;
+            }
+        }
+        tx.commit()
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to commit transaction: {}", e)))
+    }
+
+    fn batch_execute<F>(&self, sql: &str, ids: &[String], mut exec: F) -> Result<(), CacheError>
+    where
+        F: FnMut(&mut rusqlite::CachedStatement<'_>, &str) -> rusqlite::Result<usize>,
+    {
+        let mut conn = self.lock_conn()?;
+        let tx = conn
+            .transaction()
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to start transaction: {}", e)))?;
+        {
+            let mut stmt = tx
+                .prepare_cached(sql)
+                .map_err(|e| CacheError::DatabaseError(format!("Failed to prepare statement: {}", e)))?;
+            for id in ids {
+                exec(&mut stmt, id)
+                    .map_err(|e| CacheError::DatabaseError(format!("Failed to update {}: {}", id, e)))?;
+            }
+        }
+        tx.commit()
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to commit transaction: {}", e)))
+    }
+
     #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
     pub fn get_media_items_by_album(&self, album_id: &str) -> Result<Vec<api_client::MediaItem>, CacheError> {
         let conn = self.lock_conn()?;
         let mut stmt = conn
             .prepare_cached(
-                "SELECT m.id, m.description, m.product_url, m.base_url, m.mime_type, md.creation_time, md.width, md.height, md.camera_make, md.camera_model, md.fps, md.status, m.filename
+                "SELECT m.id, m.description, m.product_url, m.base_url, m.mime_type, md.creation_time, md.width, md.height, md.camera_make, md.camera_model, md.fps, md.status, m.filename, m.is_favorite, md.creation_tz_offset_minutes
                  FROM media_items m
                  JOIN album_media_items ami ON m.id = ami.media_item_id
                  JOIN media_metadata md ON m.id = md.media_item_id
-                 WHERE ami.album_id = ?1",
+                 WHERE ami.album_id = ?1 AND m.deleted_at IS NULL",
             )
             .map_err(|e| CacheError::DatabaseError(format!("Failed to prepare statement: {}", e)))?;
 
@@ -1079,10 +2130,10 @@ impl CacheManager {
                     base_url: row.get(3)?,
                     mime_type: row.get(4)?,
                     media_metadata: api_client::MediaMetadata {
-                        creation_time: Self::ts_to_rfc3339(ts),
+                        creation_time: Self::ts_to_rfc3339(ts, row.get(14)?),
                         width: w.to_string(),
                         height: h.to_string(),
-                        video: Some(api_client::VideoMetadata {
+                        video: Self::video_metadata(&row.get::<_, String>(4)?, api_client::VideoMetadata {
                             camera_make: row.get(8)?,
                             camera_model: row.get(9)?,
                             fps: row.get(10)?,
@@ -1090,6 +2141,7 @@ impl CacheManager {
                         }),
                     },
                     filename: row.get(12)?,
+                    is_favorite: row.get(13)?,
                 })
             })
             .map_err(|e| CacheError::DatabaseError(format!("Failed to query media items by album: {}", e)))?;
@@ -1103,16 +2155,25 @@ impl CacheManager {
         Ok(items)
     }
 
+    /// Items created between `start` and `end`, both inclusive. With
+    /// `TimeMode::Local` the bounds are wall-clock times at the place of capture.
     #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
-    pub fn get_media_items_by_date_range(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Result<Vec<api_client::MediaItem>, CacheError> {
+    pub fn get_media_items_by_date_range(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        mode: TimeMode,
+    ) -> Result<Vec<api_client::MediaItem>, CacheError> {
         let conn = self.lock_conn()?;
+        let sql = format!(
+            "SELECT m.id, m.description, m.product_url, m.base_url, m.mime_type, md.creation_time, md.width, md.height, md.camera_make, md.camera_model, md.fps, md.status, m.filename, m.is_favorite, md.creation_tz_offset_minutes
+             FROM media_items m
+             JOIN media_metadata md ON m.id = md.media_item_id
+             WHERE {time} >= ?1 AND {time} <= ?2 AND m.deleted_at IS NULL",
+            time = mode.creation_time()
+        );
         let mut stmt = conn
-            .prepare_cached(
-                "SELECT m.id, m.description, m.product_url, m.base_url, m.mime_type, md.creation_time, md.width, md.height, md.camera_make, md.camera_model, md.fps, md.status, m.filename
-                 FROM media_items m
-                 JOIN media_metadata md ON m.id = md.media_item_id
-                 WHERE md.creation_time >= ?1 AND md.creation_time <= ?2",
-            )
+            .prepare_cached(&sql)
             .map_err(|e| CacheError::DatabaseError(format!("Failed to prepare statement: {}", e)))?;
 
         let iter = stmt
@@ -1127,10 +2188,10 @@ impl CacheManager {
                     base_url: row.get(3)?,
                     mime_type: row.get(4)?,
                     media_metadata: api_client::MediaMetadata {
-                        creation_time: Self::ts_to_rfc3339(ts),
+                        creation_time: Self::ts_to_rfc3339(ts, row.get(14)?),
                         width: w.to_string(),
                         height: h.to_string(),
-                        video: Some(api_client::VideoMetadata {
+                        video: Self::video_metadata(&row.get::<_, String>(4)?, api_client::VideoMetadata {
                             camera_make: row.get(8)?,
                             camera_model: row.get(9)?,
                             fps: row.get(10)?,
@@ -1138,6 +2199,7 @@ impl CacheManager {
                         }),
                     },
                     filename: row.get(12)?,
+                    is_favorite: row.get(13)?,
                 })
             })
             .map_err(|e| CacheError::DatabaseError(format!("Failed to query media items by date: {}", e)))?;
@@ -1162,9 +2224,135 @@ impl CacheManager {
         Ok(())
     }
 
+    /// Move items to the trash. They stay cached with their album
+    /// associations but are left out of every listing until restored.
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
+    pub fn trash_media_items(&self, ids: &[String]) -> Result<(), CacheError> {
+        let now = Utc::now().timestamp();
+        self.batch_execute(
+            "UPDATE media_items SET deleted_at = ?1 WHERE id = ?2 AND deleted_at IS NULL",
+            ids,
+            |stmt, id| stmt.execute(params![now, id]),
+        )
+    }
+
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
+    pub fn restore_media_items(&self, ids: &[String]) -> Result<(), CacheError> {
+        self.batch_execute(
+            "UPDATE media_items SET deleted_at = NULL WHERE id = ?1",
+            ids,
+            |stmt, id| stmt.execute(params![id]),
+        )
+    }
+
+    /// Items in the trash, most recently deleted first.
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
+    pub fn get_trashed_media_items(&self) -> Result<Vec<TrashedMediaItem>, CacheError> {
+        let conn = self.lock_conn()?;
+        let mut stmt = conn
+            .prepare_cached(
+                "SELECT m.id, m.description, m.product_url, m.base_url, m.mime_type, md.creation_time, md.width, md.height, md.camera_make, md.camera_model, md.fps, md.status, m.filename, m.is_favorite, md.creation_tz_offset_minutes, m.deleted_at
+                 FROM media_items m
+                 JOIN media_metadata md ON m.id = md.media_item_id
+                 WHERE m.deleted_at IS NOT NULL
+                 ORDER BY m.deleted_at DESC, m.id",
+            )
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to prepare statement: {}", e)))?;
+
+        let iter = stmt
+            .query_map([], |row| {
+                let ts: i64 = row.get(5)?;
+                let w: i64 = row.get(6)?;
+                let h: i64 = row.get(7)?;
+                let deleted: i64 = row.get(15)?;
+                Ok(TrashedMediaItem {
+                    item: api_client::MediaItem {
+                        id: row.get(0)?,
+                        description: row.get(1)?,
+                        product_url: row.get(2)?,
+                        base_url: row.get(3)?,
+                        mime_type: row.get(4)?,
+                        media_metadata: api_client::MediaMetadata {
+                            creation_time: Self::ts_to_rfc3339(ts, row.get(14)?),
+                            width: w.to_string(),
+                            height: h.to_string(),
+                            video: Self::video_metadata(&row.get::<_, String>(4)?, api_client::VideoMetadata {
+                                camera_make: row.get(8)?,
+                                camera_model: row.get(9)?,
+                                fps: row.get(10)?,
+                                status: row.get(11)?,
+                            }),
+                        },
+                        filename: row.get(12)?,
+                        is_favorite: row.get::<_, i64>(13)? != 0,
+                    },
+                    deleted_at: DateTime::<Utc>::from_timestamp(deleted, 0)
+                        .unwrap_or_else(|| DateTime::<Utc>::from(std::time::UNIX_EPOCH)),
+                })
+            })
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to query trashed items: {}", e)))?;
+
+        iter.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to read trashed item: {}", e)))
+    }
+
+    /// Remove trashed items from the cache for good, together with their
+    /// album associations, metadata and faces. Items not in the trash are
+    /// left alone. Returns how many items were removed.
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
+    pub fn purge_media_items(&self, ids: &[String]) -> Result<u64, CacheError> {
+        let mut conn = self.lock_conn()?;
+        let tx = conn
+            .transaction()
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to start transaction: {}", e)))?;
+        let mut removed = 0;
+        for id in ids {
+            let trashed = tx
+                .query_row(
+                    "SELECT 1 FROM media_items WHERE id = ?1 AND deleted_at IS NOT NULL",
+                    params![id],
+                    |_| Ok(()),
+                )
+                .optional()
+                .map_err(|e| CacheError::DatabaseError(format!("Failed to look up {}: {}", id, e)))?
+                .is_some();
+            if !trashed {
+                continue;
+            }
+            for sql in [
+                "DELETE FROM album_media_items WHERE media_item_id = ?1",
+                "DELETE FROM media_metadata WHERE media_item_id = ?1",
+                "DELETE FROM faces WHERE media_item_id = ?1",
+                "DELETE FROM media_items WHERE id = ?1",
+            ] {
+                tx.execute(sql, params![id])
+                    .map_err(|e| CacheError::DatabaseError(format!("Failed to purge {}: {}", id, e)))?;
+            }
+            removed += 1;
+        }
+        tx.commit()
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to commit transaction: {}", e)))?;
+        Ok(removed)
+    }
+
+    /// Purge everything in the trash and return how many items were removed.
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
+    pub fn empty_trash(&self) -> Result<u64, CacheError> {
+        let ids: Vec<String> = self
+            .get_trashed_media_items()?
+            .into_iter()
+            .map(|t| t.item.id)
+            .collect();
+        self.purge_media_items(&ids)
+    }
+
     #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
     pub fn set_favorite(&self, id: &str, fav: bool) -> Result<(), CacheError> {
         let conn = self.lock_conn()?;
+        Self::set_favorite_in(&conn, id, fav)
+    }
+
+    fn set_favorite_in(conn: &Connection, id: &str, fav: bool) -> Result<(), CacheError> {
         let mut stmt = conn
             .prepare_cached("UPDATE media_items SET is_favorite = ?1 WHERE id = ?2")
             .map_err(|e| CacheError::DatabaseError(format!("Failed to prepare statement: {}", e)))?;
@@ -1173,40 +2361,142 @@ impl CacheManager {
         Ok(())
     }
 
+    /// Remember the playback length of a video, measured locally since the API
+    /// does not report it.
     #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
-    pub fn clear_cache(&self) -> Result<(), CacheError> {
+    pub fn set_video_duration(&self, id: &str, duration_ms: u64) -> Result<(), CacheError> {
+        let conn = self.lock_conn()?;
+        let mut stmt = conn
+            .prepare_cached("INSERT OR REPLACE INTO video_durations (media_item_id, duration_ms) VALUES (?1, ?2)")
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to prepare statement: {}", e)))?;
+        stmt.execute(params![id, duration_ms as i64])
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to store duration: {}", e)))?;
+        Ok(())
+    }
+
+    /// All known video durations in milliseconds, keyed by media item id.
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
+    pub fn get_video_durations(&self) -> Result<HashMap<String, u64>, CacheError> {
         let conn = self.lock_conn()?;
         let mut stmt = conn
-            .prepare_cached("DELETE FROM album_media_items")
+            .prepare_cached("SELECT media_item_id, duration_ms FROM video_durations")
             .map_err(|e| CacheError::DatabaseError(format!("Failed to prepare statement: {}", e)))?;
-        stmt.execute([])
-            .map_err(|e| CacheError::DatabaseError(format!("Failed to clear album_media_items: {}", e)))?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?)))
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to query durations: {}", e)))?;
+        let mut durations = HashMap::new();
+        for row in rows {
+            let (id, ms) = row.map_err(|e| CacheError::DatabaseError(format!("Failed to read duration: {}", e)))?;
+            durations.insert(id, ms.max(0) as u64);
+        }
+        Ok(durations)
+    }
+
+    /// Set the description of a cached item; the FTS row follows via trigger.
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
+    pub fn update_media_item_description(&self, id: &str, description: &str) -> Result<(), CacheError> {
+        let conn = self.lock_conn()?;
         let mut stmt = conn
-            .prepare_cached("DELETE FROM albums")
+            .prepare_cached("UPDATE media_items SET description = ?1 WHERE id = ?2")
             .map_err(|e| CacheError::DatabaseError(format!("Failed to prepare statement: {}", e)))?;
-        stmt.execute([])
-            .map_err(|e| CacheError::DatabaseError(format!("Failed to clear albums: {}", e)))?;
+        stmt.execute(params![description, id])
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to update description: {}", e)))?;
+        Ok(())
+    }
+
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
+    pub fn enqueue_mutation(&self, mutation: &Mutation) -> Result<(), CacheError> {
+        let payload = serde_json::to_string(mutation)
+            .map_err(|e| CacheError::SerializationError(e.to_string()))?;
+        let conn = self.lock_conn()?;
         let mut stmt = conn
-            .prepare_cached("DELETE FROM media_metadata")
+            .prepare_cached("INSERT INTO pending_mutations (payload, created_at) VALUES (?1, ?2)")
             .map_err(|e| CacheError::DatabaseError(format!("Failed to prepare statement: {}", e)))?;
-        stmt.execute([])
-            .map_err(|e| CacheError::DatabaseError(format!("Failed to clear media_metadata: {}", e)))?;
+        stmt.execute(params![payload, Utc::now().timestamp()])
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to enqueue mutation: {}", e)))?;
+        Ok(())
+    }
+
+    /// Queued mutations, oldest first.
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
+    pub fn pending_mutations(&self) -> Result<Vec<PendingMutation>, CacheError> {
+        let conn = self.lock_conn()?;
         let mut stmt = conn
-            .prepare_cached("DELETE FROM media_items")
+            .prepare_cached("SELECT id, payload, created_at FROM pending_mutations ORDER BY id")
             .map_err(|e| CacheError::DatabaseError(format!("Failed to prepare statement: {}", e)))?;
-        stmt.execute([])
-            .map_err(|e| CacheError::DatabaseError(format!("Failed to clear cache: {}", e)))?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?, row.get::<_, i64>(2)?))
+            })
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to query mutations: {}", e)))?;
+        let mut mutations = Vec::new();
+        for row in rows {
+            let (id, payload, created_at) =
+                row.map_err(|e| CacheError::DatabaseError(format!("Failed to read mutation: {}", e)))?;
+            let mutation = serde_json::from_str(&payload)
+                .map_err(|e| CacheError::DeserializationError(e.to_string()))?;
+            mutations.push(PendingMutation {
+                id,
+                mutation,
+                created_at: DateTime::<Utc>::from_timestamp(created_at, 0).unwrap_or_default(),
+            });
+        }
+        Ok(mutations)
+    }
+
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
+    pub fn remove_mutation(&self, id: i64) -> Result<(), CacheError> {
+        let conn = self.lock_conn()?;
         let mut stmt = conn
-            .prepare_cached("UPDATE last_sync SET timestamp = '1970-01-01T00:00:00Z' WHERE id = 1")
+            .prepare_cached("DELETE FROM pending_mutations WHERE id = ?1")
             .map_err(|e| CacheError::DatabaseError(format!("Failed to prepare statement: {}", e)))?;
-        stmt.execute([])
+        stmt.execute(params![id])
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to remove mutation: {}", e)))?;
+        Ok(())
+    }
+
+    /// Forget all synced items, albums and faces and reset the last sync
+    /// time, so the next sync fetches everything again. Runs as one
+    /// transaction: if any step fails, nothing is removed.
+    ///
+    /// No row counter needs resetting: the only `AUTOINCREMENT` table is
+    /// `pending_mutations`, which is kept.
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
+    pub fn clear_cache(&self) -> Result<(), CacheError> {
+        let mut conn = self.lock_conn()?;
+        let tx = conn
+            .transaction()
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to start transaction: {}", e)))?;
+        Self::clear_cache_in(&tx)?;
+        tx.commit()
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to commit transaction: {}", e)))
+    }
+
+    fn clear_cache_in(conn: &Connection) -> Result<(), CacheError> {
+        for table in LIBRARY_TABLES {
+            conn.execute(&format!("DELETE FROM {}", table), [])
+                .map_err(|e| CacheError::DatabaseError(format!("Failed to clear {}: {}", table, e)))?;
+        }
+        conn.execute("UPDATE last_sync SET timestamp = '1970-01-01T00:00:00Z' WHERE id = 1", [])
             .map_err(|e| CacheError::DatabaseError(format!("Failed to reset last_sync: {}", e)))?;
+        for table in LIBRARY_TABLES {
+            let left: bool = conn
+                .query_row(&format!("SELECT EXISTS (SELECT 1 FROM {})", table), [], |row| row.get(0))
+                .map_err(|e| CacheError::DatabaseError(format!("Failed to check {}: {}", table, e)))?;
+            if left {
+                return Err(CacheError::DatabaseError(format!("{} still has rows after clearing", table)));
+            }
+        }
         Ok(())
     }
 
     #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
     pub fn get_last_sync(&self) -> Result<DateTime<Utc>, CacheError> {
         let conn = self.lock_conn()?;
+        Self::get_last_sync_in(&conn)
+    }
+
+    fn get_last_sync_in(conn: &Connection) -> Result<DateTime<Utc>, CacheError> {
         let mut stmt = conn
             .prepare_cached("SELECT timestamp FROM last_sync WHERE id = 1")
             .map_err(|e| CacheError::DatabaseError(format!("Failed to prepare statement: {}", e)))?;
@@ -1221,6 +2511,10 @@ impl CacheManager {
     #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
     pub fn update_last_sync(&self, ts: DateTime<Utc>) -> Result<(), CacheError> {
         let conn = self.lock_conn()?;
+        Self::update_last_sync_in(&conn, ts)
+    }
+
+    fn update_last_sync_in(conn: &Connection, ts: DateTime<Utc>) -> Result<(), CacheError> {
         let mut stmt = conn
             .prepare_cached("UPDATE last_sync SET timestamp = ?1 WHERE id = 1")
             .map_err(|e| CacheError::DatabaseError(format!("Failed to prepare statement: {}", e)))?;
@@ -1230,112 +2524,1121 @@ impl CacheManager {
     }
 
     #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
-    pub fn export_media_items<P: AsRef<Path>>(&self, path: P) -> Result<(), CacheError> {
-        let items = self.get_all_media_items()?;
-        let file = std::fs::File::create(path.as_ref())
-            .map_err(|e| CacheError::Other(format!("Failed to create export file: {}", e)))?;
-        serde_json::to_writer(file, &items)
-            .map_err(|e| CacheError::SerializationError(e.to_string()))
+    pub fn export_media_items<P: AsRef<Path>>(&self, path: P) -> Result<(), CacheError> {
+        let mut albums: HashMap<String, Vec<String>> = HashMap::new();
+        for link in self.get_album_associations()? {
+            albums.entry(link.media_item_id).or_default().push(link.album_id);
+        }
+        let items: Vec<MediaItemExport> = self
+            .get_all_media_items()?
+            .into_iter()
+            .map(|item| MediaItemExport { album_ids: albums.remove(&item.id), is_favorite: item.is_favorite, item })
+            .collect();
+        let file = std::fs::File::create(path.as_ref())
+            .map_err(|e| CacheError::Other(format!("Failed to create export file: {}", e)))?;
+        serde_json::to_writer(file, &items)
+            .map_err(|e| CacheError::SerializationError(e.to_string()))
+    }
+
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
+    pub fn export_albums<P: AsRef<Path>>(&self, path: P) -> Result<(), CacheError> {
+        let albums = self.get_all_albums()?;
+        let file = std::fs::File::create(path.as_ref())
+            .map_err(|e| CacheError::Other(format!("Failed to create export file: {}", e)))?;
+        serde_json::to_writer(file, &albums)
+            .map_err(|e| CacheError::SerializationError(e.to_string()))
+    }
+
+    /// Faces of every media item, ordered by media item id.
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
+    pub fn get_all_faces(&self) -> Result<Vec<FaceExport>, CacheError> {
+        let conn = self.lock_conn()?;
+        Ok(Self::load_all_faces(&conn)?
+            .into_iter()
+            .map(|(media_item_id, faces)| FaceExport { media_item_id, faces })
+            .collect())
+    }
+
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
+    pub fn export_faces<P: AsRef<Path>>(&self, path: P) -> Result<(), CacheError> {
+        let data = self.get_all_faces()?;
+        let file = std::fs::File::create(path.as_ref())
+            .map_err(|e| CacheError::Other(format!("Failed to create export file: {}", e)))?;
+        serde_json::to_writer(file, &data)
+            .map_err(|e| CacheError::SerializationError(e.to_string()))
+    }
+
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
+    pub fn import_media_items<P: AsRef<Path>>(&self, path: P) -> Result<(), CacheError> {
+        let file = std::fs::File::open(path.as_ref())
+            .map_err(|e| CacheError::Other(format!("Failed to open import file: {}", e)))?;
+        let items: Vec<MediaItemExport> = serde_json::from_reader(file)
+            .map_err(|e| CacheError::DeserializationError(e.to_string()))?;
+        let known: HashSet<String> = self.get_all_albums()?.into_iter().map(|a| a.id).collect();
+        for entry in &items {
+            let mut item = entry.item.clone();
+            item.is_favorite = entry.is_favorite;
+            self.insert_media_item(&item)?;
+            // Links to albums missing from this cache are dropped
+            for album_id in entry.album_ids.iter().flatten().filter(|id| known.contains(*id)) {
+                self.associate_media_item_with_album(&entry.item.id, album_id)?;
+            }
+        }
+        Ok(())
+    }
+
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
+    pub fn import_faces<P: AsRef<Path>>(&self, path: P) -> Result<(), CacheError> {
+        let file = std::fs::File::open(path.as_ref())
+            .map_err(|e| CacheError::Other(format!("Failed to open import file: {}", e)))?;
+        let entries: Vec<FaceExport> = serde_json::from_reader(file)
+            .map_err(|e| CacheError::DeserializationError(e.to_string()))?;
+        for entry in &entries {
+            let json = serde_json::to_string(&entry.faces)
+                .map_err(|e| CacheError::SerializationError(e.to_string()))?;
+            self.insert_faces(&entry.media_item_id, &json)?;
+        }
+        Ok(())
+    }
+
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
+    pub fn import_albums<P: AsRef<Path>>(&self, path: P) -> Result<(), CacheError> {
+        let file = std::fs::File::open(path.as_ref())
+            .map_err(|e| CacheError::Other(format!("Failed to open import file: {}", e)))?;
+        let albums: Vec<api_client::Album> = serde_json::from_reader(file)
+            .map_err(|e| CacheError::DeserializationError(e.to_string()))?;
+        for album in &albums {
+            self.insert_album(album)?;
+        }
+        Ok(())
+    }
+
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
+    pub fn get_album_associations(&self) -> Result<Vec<AlbumAssociation>, CacheError> {
+        let conn = self.lock_conn()?;
+        let mut stmt = conn
+            .prepare_cached("SELECT album_id, media_item_id FROM album_media_items ORDER BY album_id, media_item_id")
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to prepare statement: {}", e)))?;
+        let iter = stmt
+            .query_map([], |row| {
+                Ok(AlbumAssociation {
+                    album_id: row.get(0)?,
+                    media_item_id: row.get(1)?,
+                })
+            })
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to query album associations: {}", e)))?;
+        iter.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to retrieve album associations: {}", e)))
+    }
+
+    /// Write every cached table into `dir`, one JSON file per entry in `STATE_FILES`.
+    ///
+    /// `progress` is called with each file name and its record count.
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self, progress)))]
+    pub fn export_state<P, F>(&self, dir: P, mut progress: F) -> Result<(), CacheError>
+    where
+        P: AsRef<Path> + std::fmt::Debug,
+        F: FnMut(&str, usize),
+    {
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir)
+            .map_err(|e| CacheError::Other(format!("Failed to create export directory: {}", e)))?;
+        fn write<T: Serialize>(path: &Path, value: &T) -> Result<(), CacheError> {
+            let file = std::fs::File::create(path)
+                .map_err(|e| CacheError::Other(format!("Failed to create export file: {}", e)))?;
+            serde_json::to_writer(file, value).map_err(|e| CacheError::SerializationError(e.to_string()))
+        }
+
+        let items = self.get_all_media_items()?;
+        write(&dir.join(STATE_FILES[0]), &items)?;
+        progress(STATE_FILES[0], items.len());
+
+        let albums = self.get_all_albums()?;
+        write(&dir.join(STATE_FILES[1]), &albums)?;
+        progress(STATE_FILES[1], albums.len());
+
+        let links = self.get_album_associations()?;
+        write(&dir.join(STATE_FILES[2]), &links)?;
+        progress(STATE_FILES[2], links.len());
+
+        let favorites: Vec<String> = self
+            .get_favorite_media_items()?
+            .into_iter()
+            .map(|i| i.id)
+            .collect();
+        write(&dir.join(STATE_FILES[3]), &favorites)?;
+        progress(STATE_FILES[3], favorites.len());
+
+        self.export_faces(dir.join(STATE_FILES[4]))?;
+        let faces = {
+            let conn = self.lock_conn()?;
+            conn.query_row("SELECT COUNT(*) FROM faces", [], |row| row.get::<_, i64>(0))
+                .map_err(|e| CacheError::DatabaseError(format!("Failed to count faces: {}", e)))?
+        };
+        progress(STATE_FILES[4], faces as usize);
+
+        write(&dir.join(STATE_FILES[5]), &LastSyncExport { timestamp: self.get_last_sync()? })?;
+        progress(STATE_FILES[5], 1);
+        Ok(())
+    }
+
+    /// Restore a directory written by `export_state`.
+    ///
+    /// Files are applied in `STATE_FILES` order so associations and faces
+    /// always find their media items; missing files are skipped. Everything
+    /// is applied in one transaction, so a failed import leaves the cache as
+    /// it was. The cache stays locked until the import ends, so `progress`
+    /// must not use it.
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self, progress)))]
+    pub fn import_state<P, F>(&self, dir: P, mode: ImportMode, mut progress: F) -> Result<(), CacheError>
+    where
+        P: AsRef<Path> + std::fmt::Debug,
+        F: FnMut(&str, usize),
+    {
+        fn read<T: serde::de::DeserializeOwned>(path: &Path) -> Result<Option<T>, CacheError> {
+            if !path.exists() {
+                return Ok(None);
+            }
+            let file = std::fs::File::open(path)
+                .map_err(|e| CacheError::Other(format!("Failed to open import file: {}", e)))?;
+            serde_json::from_reader(file)
+                .map(Some)
+                .map_err(|e| CacheError::DeserializationError(e.to_string()))
+        }
+
+        let dir = dir.as_ref();
+        // The connection stays locked for the whole import, so no other clone
+        // of this cache can write in between
+        let mut conn = self.lock_conn()?;
+        let tx = conn
+            .savepoint()
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to start transaction: {}", e)))?;
+        let result = (|| {
+            if mode == ImportMode::Replace {
+                Self::clear_cache_in(&tx)?;
+            }
+
+            if let Some(items) = read::<Vec<api_client::MediaItem>>(&dir.join(STATE_FILES[0]))? {
+                Self::upsert_media_items_in(&tx, &items)?;
+                progress(STATE_FILES[0], items.len());
+            }
+            if let Some(albums) = read::<Vec<api_client::Album>>(&dir.join(STATE_FILES[1]))? {
+                for album in &albums {
+                    Self::insert_album_in(&tx, album)?;
+                }
+                progress(STATE_FILES[1], albums.len());
+            }
+            if let Some(links) = read::<Vec<AlbumAssociation>>(&dir.join(STATE_FILES[2]))? {
+                for link in &links {
+                    Self::associate_media_item_with_album_in(&tx, &link.media_item_id, &link.album_id)?;
+                }
+                progress(STATE_FILES[2], links.len());
+            }
+            if let Some(favorites) = read::<Vec<String>>(&dir.join(STATE_FILES[3]))? {
+                for id in &favorites {
+                    Self::set_favorite_in(&tx, id, true)?;
+                }
+                progress(STATE_FILES[3], favorites.len());
+            }
+            if let Some(faces) = read::<Vec<FaceExport>>(&dir.join(STATE_FILES[4]))? {
+                for entry in &faces {
+                    let json = serde_json::to_string(&entry.faces)
+                        .map_err(|e| CacheError::SerializationError(e.to_string()))?;
+                    Self::insert_faces_in(&tx, &entry.media_item_id, &json)?;
+                }
+                progress(STATE_FILES[4], faces.len());
+            }
+            if let Some(last) = read::<LastSyncExport>(&dir.join(STATE_FILES[5]))? {
+                if mode == ImportMode::Replace || last.timestamp > Self::get_last_sync_in(&tx)? {
+                    Self::update_last_sync_in(&tx, last.timestamp)?;
+                }
+                progress(STATE_FILES[5], 1);
+            }
+            Ok(())
+        })();
+        match result {
+            Ok(()) => tx
+                .commit()
+                .map_err(|e| CacheError::DatabaseError(format!("Failed to commit transaction: {}", e))),
+            Err(e) => {
+                // The import error is what the caller needs to see
+                if let Err(rollback) = tx.finish() {
+                    tracing::warn!(error = %rollback, "Failed to roll back import");
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// Media item ID previously uploaded from a file with this content hash.
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
+    pub fn get_uploaded_file(&self, hash: &str) -> Result<Option<String>, CacheError> {
+        let conn = self.lock_conn()?;
+        let mut stmt = conn
+            .prepare_cached("SELECT media_item_id FROM uploaded_files WHERE hash = ?1")
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to prepare statement: {}", e)))?;
+        stmt.query_row(params![hash], |row| row.get(0))
+            .optional()
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to query uploaded file: {}", e)))
+    }
+
+    /// The ids in `ids` that GooglePicz uploaded, in the same order.
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self, ids)))]
+    pub fn uploaded_media_item_ids(&self, ids: &[String]) -> Result<Vec<String>, CacheError> {
+        let conn = self.lock_conn()?;
+        let mut stmt = conn
+            .prepare_cached("SELECT EXISTS(SELECT 1 FROM uploaded_files WHERE media_item_id = ?1)")
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to prepare statement: {}", e)))?;
+        let mut uploaded = Vec::new();
+        for id in ids {
+            let exists: bool = stmt
+                .query_row(params![id], |row| row.get(0))
+                .map_err(|e| CacheError::DatabaseError(format!("Failed to query uploaded file: {}", e)))?;
+            if exists {
+                uploaded.push(id.clone());
+            }
+        }
+        Ok(uploaded)
+    }
+
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
+    pub fn record_uploaded_file(&self, hash: &str, media_item_id: &str, file_name: &str) -> Result<(), CacheError> {
+        let conn = self.lock_conn()?;
+        let mut stmt = conn
+            .prepare_cached(
+                "INSERT OR REPLACE INTO uploaded_files (hash, media_item_id, file_name, uploaded_at) VALUES (?1, ?2, ?3, ?4)",
+            )
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to prepare statement: {}", e)))?;
+        stmt.execute(params![hash, media_item_id, file_name, Utc::now().timestamp()])
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to record uploaded file: {}", e)))?;
+        Ok(())
+    }
+
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
+    pub fn insert_faces(&self, media_item_id: &str, faces_json: &str) -> Result<(), CacheError> {
+        let conn = self.lock_conn()?;
+        Self::insert_faces_in(&conn, media_item_id, faces_json)
+    }
+
+    fn insert_faces_in(conn: &Connection, media_item_id: &str, faces_json: &str) -> Result<(), CacheError> {
+        let mut stmt = conn
+            .prepare_cached("INSERT OR REPLACE INTO faces (media_item_id, faces_json) VALUES (?1, ?2)")
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to prepare statement: {}", e)))?;
+        stmt.execute(params![media_item_id, faces_json])
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to insert faces: {}", e)))?;
+        Ok(())
+    }
+
+    #[cfg(feature = "face-recognition")]
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self, faces_json)))]
+    pub async fn insert_faces_async(
+        &self,
+        media_item_id: String,
+        faces_json: String,
+    ) -> Result<(), CacheError> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.insert_faces(&media_item_id, &faces_json))
+            .await
+            .map_err(|e| CacheError::Other(e.to_string()))?
+    }
+
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
+    pub fn get_faces(&self, media_item_id: &str) -> Result<Option<Vec<FaceData>>, CacheError> {
+        let conn = self.lock_conn()?;
+        let mut stmt = conn
+            .prepare_cached("SELECT faces_json FROM faces WHERE media_item_id = ?1")
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to prepare statement: {}", e)))?;
+        let faces_json: Option<String> = stmt
+            .query_row(params![media_item_id], |row| row.get(0))
+            .optional()
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to query faces: {}", e)))?;
+        if let Some(json) = faces_json {
+            let data: Vec<FaceData> = serde_json::from_str(&json)
+                .map_err(|e| CacheError::DeserializationError(e.to_string()))?;
+            Ok(Some(data))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn group_counts(conn: &Connection, sql: &str) -> Result<Vec<(String, u64)>, CacheError> {
+        let mut stmt = conn
+            .prepare_cached(sql)
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to prepare statement: {}", e)))?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)? as u64)))
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to query stats: {}", e)))?;
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to query stats: {}", e)))
+    }
+
+    /// Number of cached media items; cheaper than loading them.
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
+    pub fn count_media_items(&self) -> Result<u64, CacheError> {
+        let conn = self.lock_conn()?;
+        conn.query_row("SELECT COUNT(*) FROM media_items WHERE deleted_at IS NULL", [], |row| row.get::<_, i64>(0))
+            .map(|n| n as u64)
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to count items: {}", e)))
+    }
+
+    /// Summary counts for the whole cached library.
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
+    pub fn get_library_stats(&self) -> Result<LibraryStats, CacheError> {
+        let people_count = self.list_people()?.len() as u64;
+        let conn = self.lock_conn()?;
+        let count = |sql: &str| -> Result<u64, CacheError> {
+            conn.query_row(sql, [], |row| row.get::<_, i64>(0))
+                .map(|n| n as u64)
+                .map_err(|e| CacheError::DatabaseError(format!("Failed to query stats: {}", e)))
+        };
+        let item_count = count("SELECT COUNT(*) FROM media_items WHERE deleted_at IS NULL")?;
+        let video_count =
+            count("SELECT COUNT(*) FROM media_items WHERE mime_type LIKE 'video/%' AND deleted_at IS NULL")?;
+        let favorite_count = count("SELECT COUNT(*) FROM media_items WHERE is_favorite = 1 AND deleted_at IS NULL")?;
+        let album_count = count("SELECT COUNT(*) FROM albums")?;
+        let (earliest, latest): (Option<i64>, Option<i64>) = conn
+            .query_row(
+                "SELECT MIN(creation_time), MAX(creation_time) FROM media_metadata",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to query stats: {}", e)))?;
+        let top_camera_models = Self::group_counts(
+            &conn,
+            "SELECT camera_model, COUNT(*) AS n FROM media_metadata WHERE camera_model IS NOT NULL \
+             GROUP BY camera_model ORDER BY n DESC, camera_model LIMIT 10",
+        )?;
+        let top_mime_types = Self::group_counts(
+            &conn,
+            "SELECT mime_type, COUNT(*) AS n FROM media_items WHERE deleted_at IS NULL \
+             GROUP BY mime_type ORDER BY n DESC, mime_type LIMIT 10",
+        )?;
+        Ok(LibraryStats {
+            item_count,
+            video_count,
+            favorite_count,
+            earliest: earliest.and_then(|ts| DateTime::<Utc>::from_timestamp(ts, 0)),
+            latest: latest.and_then(|ts| DateTime::<Utc>::from_timestamp(ts, 0)),
+            top_camera_models,
+            top_mime_types,
+            album_count,
+            people_count,
+        })
+    }
+
+    /// Number of media items per time bucket, oldest first. With
+    /// `TimeMode::Local` an item counts towards the day it was taken on where
+    /// it was taken.
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
+    pub fn count_media_items_by_period(
+        &self,
+        bucket: TimeBucket,
+        mode: TimeMode,
+    ) -> Result<Vec<(String, u64)>, CacheError> {
+        let conn = self.lock_conn()?;
+        let sql = format!(
+            "SELECT strftime('{}', {}, 'unixepoch') AS period, COUNT(*) FROM media_metadata md \
+             JOIN media_items m ON m.id = md.media_item_id WHERE m.deleted_at IS NULL \
+             GROUP BY period ORDER BY period",
+            bucket.format(),
+            mode.creation_time()
+        );
+        Self::group_counts(&conn, &sql)
+    }
+
+    fn load_all_faces(conn: &Connection) -> Result<Vec<(String, Vec<FaceData>)>, CacheError> {
+        let mut stmt = conn
+            .prepare_cached("SELECT media_item_id, faces_json FROM faces ORDER BY media_item_id")
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to prepare statement: {}", e)))?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to query faces: {}", e)))?;
+        let mut out = Vec::new();
+        for row in rows {
+            let (id, json) = row.map_err(|e| CacheError::DatabaseError(format!("Failed to retrieve faces: {}", e)))?;
+            let faces: Vec<FaceData> = serde_json::from_str(&json)
+                .map_err(|e| CacheError::DeserializationError(e.to_string()))?;
+            out.push((id, faces));
+        }
+        Ok(out)
+    }
+
+    /// Names assigned to faces, with how many media items each appears in.
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
+    pub fn list_people(&self) -> Result<Vec<PersonSummary>, CacheError> {
+        let conn = self.lock_conn()?;
+        let mut counts: std::collections::BTreeMap<String, u64> = std::collections::BTreeMap::new();
+        for (_, faces) in Self::load_all_faces(&conn)? {
+            let names: std::collections::BTreeSet<&str> =
+                faces.iter().filter(|f| !f.ignored).filter_map(|f| f.name.as_deref()).collect();
+            for name in names {
+                *counts.entry(name.to_string()).or_default() += 1;
+            }
+        }
+        let mut people: Vec<PersonSummary> = counts
+            .into_iter()
+            .map(|(name, photo_count)| PersonSummary { name, photo_count })
+            .collect();
+        people.sort_by(|a, b| b.photo_count.cmp(&a.photo_count).then_with(|| a.name.cmp(&b.name)));
+        Ok(people)
+    }
+
+    /// Rename every face labelled `old`; returns the number of faces changed.
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
+    pub fn rename_person(&self, old: &str, new: &str) -> Result<u64, CacheError> {
+        let mut conn = self.lock_conn()?;
+        let all = Self::load_all_faces(&conn)?;
+        let tx = conn
+            .transaction()
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to start transaction: {}", e)))?;
+        let mut changed = 0;
+        for (id, mut faces) in all {
+            let mut touched = false;
+            for face in faces.iter_mut().filter(|f| f.name.as_deref() == Some(old)) {
+                face.name = Some(new.to_string());
+                touched = true;
+                changed += 1;
+            }
+            if touched {
+                let json = serde_json::to_string(&faces)
+                    .map_err(|e| CacheError::SerializationError(e.to_string()))?;
+                tx.execute(
+                    "UPDATE faces SET faces_json = ?1 WHERE media_item_id = ?2",
+                    params![json, id],
+                )
+                .map_err(|e| CacheError::DatabaseError(format!("Failed to update faces: {}", e)))?;
+            }
+        }
+        tx.commit()
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to commit transaction: {}", e)))?;
+        Ok(changed)
+    }
+
+    /// Mark unnamed faces detected with a score below `threshold` as ignored,
+    /// so a stricter threshold applies without detecting again. Faces without
+    /// a score are left alone. Returns how many faces were ignored.
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
+    pub fn ignore_faces_below_confidence(&self, threshold: f32) -> Result<u64, CacheError> {
+        let mut conn = self.lock_conn()?;
+        let all = Self::load_all_faces(&conn)?;
+        let tx = conn
+            .transaction()
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to start transaction: {}", e)))?;
+        let mut changed = 0;
+        for (id, mut faces) in all {
+            let mut touched = false;
+            for (index, face) in faces.iter_mut().enumerate() {
+                let below = face.confidence.is_some_and(|c| c < threshold);
+                if !below || face.ignored || face.name.is_some() {
+                    continue;
+                }
+                face.ignored = true;
+                touched = true;
+                changed += 1;
+                tx.execute(
+                    "UPDATE face_embeddings SET cluster_id = NULL WHERE media_item_id = ?1 AND face_index = ?2",
+                    params![id, index as i64],
+                )
+                .map_err(|e| CacheError::DatabaseError(format!("Failed to reset cluster: {}", e)))?;
+            }
+            if touched {
+                let json = serde_json::to_string(&faces)
+                    .map_err(|e| CacheError::SerializationError(e.to_string()))?;
+                tx.execute(
+                    "UPDATE faces SET faces_json = ?1 WHERE media_item_id = ?2",
+                    params![json, id],
+                )
+                .map_err(|e| CacheError::DatabaseError(format!("Failed to update faces: {}", e)))?;
+            }
+        }
+        tx.commit()
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to commit transaction: {}", e)))?;
+        Ok(changed)
+    }
+
+    /// Fold the person `remove` into `keep`; both must already exist.
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
+    pub fn merge_people(&self, keep: &str, remove: &str) -> Result<u64, CacheError> {
+        let people = self.list_people()?;
+        for name in [keep, remove] {
+            if !people.iter().any(|p| p.name == name) {
+                return Err(CacheError::Other(format!("Unknown person: {}", name)));
+            }
+        }
+        self.rename_person(remove, keep)
+    }
+
+    /// People with their photo counts and largest face. Named people come
+    /// first, then clusters of unnamed faces, then the unnamed faces left over.
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
+    pub fn get_people_with_counts(&self) -> Result<Vec<PersonEntry>, CacheError> {
+        let conn = self.lock_conn()?;
+        let clusters = Self::load_face_clusters(&conn)?;
+        let mut people: std::collections::BTreeMap<(Option<String>, Option<u32>), PersonEntry> =
+            std::collections::BTreeMap::new();
+        for (id, faces) in Self::load_all_faces(&conn)? {
+            let mut counted = std::collections::BTreeSet::new();
+            for (index, face) in faces.iter().enumerate().filter(|(_, f)| !f.ignored) {
+                let cluster = match face.name {
+                    Some(_) => None,
+                    None => clusters.get(&(id.clone(), index)).copied(),
+                };
+                let key = (face.name.clone(), cluster);
+                let entry = people.entry(key.clone()).or_insert_with(|| PersonEntry {
+                    name: face.name.clone(),
+                    cluster,
+                    photo_count: 0,
+                    sample_media_item_id: id.clone(),
+                    sample_bbox: face.bbox,
+                });
+                if counted.insert(key) {
+                    entry.photo_count += 1;
+                }
+                let area = |b: [i32; 4]| b[2] as i64 * b[3] as i64;
+                if area(face.bbox) > area(entry.sample_bbox) {
+                    entry.sample_media_item_id = id.clone();
+                    entry.sample_bbox = face.bbox;
+                }
+            }
+        }
+        let rank = |p: &PersonEntry| match (&p.name, p.cluster) {
+            (Some(_), _) => 0,
+            (None, Some(_)) => 1,
+            (None, None) => 2,
+        };
+        let mut out: Vec<PersonEntry> = people.into_values().collect();
+        out.sort_by(|a, b| {
+            rank(a)
+                .cmp(&rank(b))
+                .then_with(|| b.photo_count.cmp(&a.photo_count))
+                .then_with(|| a.name.cmp(&b.name))
+                .then_with(|| a.cluster.cmp(&b.cluster))
+        });
+        Ok(out)
+    }
+
+    /// Media items showing `name`, or an unnamed face outside any cluster
+    /// when `name` is `None`.
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
+    pub fn get_media_items_by_person(&self, name: Option<&str>) -> Result<Vec<api_client::MediaItem>, CacheError> {
+        let ids: Vec<String> = {
+            let conn = self.lock_conn()?;
+            let clusters = Self::load_face_clusters(&conn)?;
+            Self::load_all_faces(&conn)?
+                .into_iter()
+                .filter(|(id, faces)| {
+                    faces.iter().enumerate().any(|(index, f)| {
+                        !f.ignored
+                            && f.name.as_deref() == name
+                            && (name.is_some() || !clusters.contains_key(&(id.clone(), index)))
+                    })
+                })
+                .map(|(id, _)| id)
+                .collect()
+        };
+        self.media_items_by_ids(ids)
+    }
+
+    /// Media items with an unnamed face in `cluster`.
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
+    pub fn get_media_items_by_cluster(&self, cluster: u32) -> Result<Vec<api_client::MediaItem>, CacheError> {
+        let ids: Vec<String> = {
+            let conn = self.lock_conn()?;
+            Self::unnamed_cluster_faces(&conn, cluster)?.into_keys().collect()
+        };
+        self.media_items_by_ids(ids)
+    }
+
+    fn media_items_by_ids(&self, ids: Vec<String>) -> Result<Vec<api_client::MediaItem>, CacheError> {
+        let mut items = Vec::new();
+        let trashed: HashSet<String> = {
+            let conn = self.lock_conn()?;
+            let mut stmt = conn
+                .prepare_cached("SELECT id FROM media_items WHERE deleted_at IS NOT NULL")
+                .map_err(|e| CacheError::DatabaseError(format!("Failed to prepare statement: {}", e)))?;
+            let rows = stmt
+                .query_map([], |row| row.get(0))
+                .map_err(|e| CacheError::DatabaseError(format!("Failed to query trashed items: {}", e)))?;
+            rows.collect::<Result<_, _>>()
+                .map_err(|e| CacheError::DatabaseError(format!("Failed to read trashed items: {}", e)))?
+        };
+        for id in ids {
+            if trashed.contains(&id) {
+                continue;
+            }
+            if let Some(item) = self.get_media_item(&id)? {
+                items.push(item);
+            }
+        }
+        Ok(items)
+    }
+
+    /// Hide or show again face `index` of a media item. Ignoring a face takes
+    /// it out of its cluster.
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
+    pub fn set_face_ignored(&self, media_item_id: &str, index: usize, ignored: bool) -> Result<(), CacheError> {
+        let mut faces = self
+            .get_faces(media_item_id)?
+            .ok_or_else(|| CacheError::Other(format!("No faces for {}", media_item_id)))?;
+        let face = faces
+            .get_mut(index)
+            .ok_or_else(|| CacheError::Other(format!("No face {} in {}", index, media_item_id)))?;
+        face.ignored = ignored;
+        let json = serde_json::to_string(&faces).map_err(|e| CacheError::SerializationError(e.to_string()))?;
+        let mut conn = self.lock_conn()?;
+        let tx = conn
+            .transaction()
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to start transaction: {}", e)))?;
+        tx.execute(
+            "UPDATE faces SET faces_json = ?1 WHERE media_item_id = ?2",
+            params![json, media_item_id],
+        )
+        .map_err(|e| CacheError::DatabaseError(format!("Failed to update faces: {}", e)))?;
+        if ignored {
+            tx.execute(
+                "UPDATE face_embeddings SET cluster_id = NULL WHERE media_item_id = ?1 AND face_index = ?2",
+                params![media_item_id, index as i64],
+            )
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to reset cluster: {}", e)))?;
+        }
+        tx.commit()
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to commit transaction: {}", e)))?;
+        Ok(())
+    }
+
+    /// Filter for photos outside the trash without a `faces` row, after
+    /// `after` when set. The id bound is only added when there is one, so
+    /// SQLite can seek the primary key instead of scanning it.
+    fn without_faces_filter(after: Option<&str>) -> (String, Vec<rusqlite::types::Value>) {
+        let mut filter = String::from(
+            "mime_type LIKE 'image/%' AND deleted_at IS NULL \
+             AND id NOT IN (SELECT media_item_id FROM faces)",
+        );
+        let mut values = Vec::new();
+        if let Some(after) = after {
+            filter.push_str(" AND id > ?");
+            values.push(rusqlite::types::Value::Text(after.to_string()));
+        }
+        (filter, values)
+    }
+
+    /// Photos without a `faces` row and an id after `after`, in id order.
+    /// Trashed photos are left out.
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
+    pub fn get_media_items_without_faces(
+        &self,
+        after: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<api_client::MediaItem>, CacheError> {
+        let (filter, mut values) = Self::without_faces_filter(after);
+        values.push(rusqlite::types::Value::Integer(limit as i64));
+        let ids: Vec<String> = {
+            let conn = self.lock_conn()?;
+            let mut stmt = conn
+                .prepare_cached(&format!("SELECT id FROM media_items WHERE {} ORDER BY id LIMIT ?", filter))
+                .map_err(|e| CacheError::DatabaseError(format!("Failed to prepare statement: {}", e)))?;
+            let rows = stmt
+                .query_map(rusqlite::params_from_iter(values), |row| row.get(0))
+                .map_err(|e| CacheError::DatabaseError(format!("Failed to query media items: {}", e)))?;
+            rows.collect::<Result<_, _>>()
+                .map_err(|e| CacheError::DatabaseError(format!("Failed to query media items: {}", e)))?
+        };
+        self.media_items_by_ids(ids)
+    }
+
+    /// Number of items `get_media_items_without_faces` would return without a limit.
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
+    pub fn count_media_items_without_faces(&self, after: Option<&str>) -> Result<u64, CacheError> {
+        let (filter, values) = Self::without_faces_filter(after);
+        let conn = self.lock_conn()?;
+        conn.query_row(
+            &format!("SELECT COUNT(*) FROM media_items WHERE {}", filter),
+            rusqlite::params_from_iter(values),
+            |row| row.get::<_, i64>(0),
+        )
+        .map(|n| n as u64)
+        .map_err(|e| CacheError::DatabaseError(format!("Failed to count items: {}", e)))
+    }
+
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
+    pub fn get_face_scan_checkpoint(&self) -> Result<Option<FaceScanCheckpoint>, CacheError> {
+        let conn = self.lock_conn()?;
+        conn.query_row(
+            "SELECT last_media_item_id, processed FROM face_scan_state WHERE id = 1",
+            [],
+            |row| {
+                Ok(FaceScanCheckpoint {
+                    last_media_item_id: row.get(0)?,
+                    processed: row.get::<_, i64>(1)? as u64,
+                })
+            },
+        )
+        .optional()
+        .map_err(|e| CacheError::DatabaseError(format!("Failed to query face scan state: {}", e)))
+    }
+
+    /// Remember how far the face scan got so a restart resumes after it.
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
+    pub fn set_face_scan_checkpoint(&self, checkpoint: &FaceScanCheckpoint) -> Result<(), CacheError> {
+        let conn = self.lock_conn()?;
+        conn.execute(
+            "INSERT OR REPLACE INTO face_scan_state (id, last_media_item_id, processed) VALUES (1, ?1, ?2)",
+            params![checkpoint.last_media_item_id, checkpoint.processed as i64],
+        )
+        .map_err(|e| CacheError::DatabaseError(format!("Failed to save face scan state: {}", e)))?;
+        Ok(())
+    }
+
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
+    pub fn clear_face_scan_checkpoint(&self) -> Result<(), CacheError> {
+        let conn = self.lock_conn()?;
+        conn.execute("DELETE FROM face_scan_state", [])
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to clear face scan state: {}", e)))?;
+        Ok(())
+    }
+
+    /// Replace the embeddings of the faces of a media item; `embeddings[i]`
+    /// belongs to face `i` of `insert_faces`. Their clusters are reset until
+    /// the next `cluster_unnamed_faces`.
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self, embeddings)))]
+    pub fn set_face_embeddings(&self, media_item_id: &str, embeddings: &[Vec<f32>]) -> Result<(), CacheError> {
+        if let Some(bad) = embeddings.iter().find(|e| e.len() != EMBEDDING_DIM) {
+            return Err(CacheError::Other(format!(
+                "Embedding has {} values, expected {}",
+                bad.len(),
+                EMBEDDING_DIM
+            )));
+        }
+        let mut conn = self.lock_conn()?;
+        let tx = conn
+            .transaction()
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to start transaction: {}", e)))?;
+        tx.execute("DELETE FROM face_embeddings WHERE media_item_id = ?1", params![media_item_id])
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to clear embeddings: {}", e)))?;
+        for (index, embedding) in embeddings.iter().enumerate() {
+            let blob: Vec<u8> = embedding.iter().flat_map(|v| v.to_le_bytes()).collect();
+            tx.execute(
+                "INSERT INTO face_embeddings (media_item_id, face_index, embedding) VALUES (?1, ?2, ?3)",
+                params![media_item_id, index as i64, blob],
+            )
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to store embedding: {}", e)))?;
+        }
+        tx.commit()
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to commit transaction: {}", e)))?;
+        Ok(())
+    }
+
+    /// Cluster ids of unnamed faces, keyed by media item id and face index.
+    /// Named faces may keep a stale id until the next clustering.
+    fn load_face_clusters(conn: &Connection) -> Result<HashMap<(String, usize), u32>, CacheError> {
+        let mut stmt = conn
+            .prepare_cached("SELECT media_item_id, face_index, cluster_id FROM face_embeddings WHERE cluster_id IS NOT NULL")
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to prepare statement: {}", e)))?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?, row.get::<_, i64>(2)?)))
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to query clusters: {}", e)))?;
+        let mut out = HashMap::new();
+        for row in rows {
+            let (id, index, cluster) =
+                row.map_err(|e| CacheError::DatabaseError(format!("Failed to retrieve cluster: {}", e)))?;
+            out.insert((id, index as usize), cluster as u32);
+        }
+        Ok(out)
+    }
+
+    /// Indices of the unnamed faces in `cluster`, keyed by media item id.
+    fn unnamed_cluster_faces(
+        conn: &Connection,
+        cluster: u32,
+    ) -> Result<std::collections::BTreeMap<String, Vec<usize>>, CacheError> {
+        let clusters = Self::load_face_clusters(conn)?;
+        let mut out: std::collections::BTreeMap<String, Vec<usize>> = std::collections::BTreeMap::new();
+        for (id, faces) in Self::load_all_faces(conn)? {
+            for (index, face) in faces.iter().enumerate() {
+                if face.name.is_none() && !face.ignored && clusters.get(&(id.clone(), index)) == Some(&cluster) {
+                    out.entry(id.clone()).or_default().push(index);
+                }
+            }
+        }
+        Ok(out)
+    }
+
+    /// Group unnamed faces whose embeddings have a cosine similarity of at
+    /// least `threshold`, directly or through other faces of the group.
+    /// Groups of two or more faces get cluster ids from 1 up, largest first;
+    /// the rest stay unclustered. Returns the number of clusters.
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
+    pub fn cluster_unnamed_faces(&self, threshold: f32) -> Result<usize, CacheError> {
+        let mut conn = self.lock_conn()?;
+        let names: HashMap<String, Vec<FaceData>> = Self::load_all_faces(&conn)?.into_iter().collect();
+        let mut faces: Vec<(String, usize, Vec<f32>)> = Vec::new();
+        {
+            let mut stmt = conn
+                .prepare_cached("SELECT media_item_id, face_index, embedding FROM face_embeddings ORDER BY media_item_id, face_index")
+                .map_err(|e| CacheError::DatabaseError(format!("Failed to prepare statement: {}", e)))?;
+            let rows = stmt
+                .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?, row.get::<_, Vec<u8>>(2)?)))
+                .map_err(|e| CacheError::DatabaseError(format!("Failed to query embeddings: {}", e)))?;
+            for row in rows {
+                let (id, index, blob) =
+                    row.map_err(|e| CacheError::DatabaseError(format!("Failed to retrieve embedding: {}", e)))?;
+                let index = index as usize;
+                let unnamed = names
+                    .get(&id)
+                    .and_then(|f| f.get(index))
+                    .is_some_and(|f| f.name.is_none() && !f.ignored);
+                if unnamed {
+                    faces.push((id, index, normalized_embedding(&blob)));
+                }
+            }
+        }
+
+        // Union-find over every pair similar enough
+        let mut parent: Vec<usize> = (0..faces.len()).collect();
+        fn root(parent: &mut [usize], mut i: usize) -> usize {
+            while parent[i] != i {
+                parent[i] = parent[parent[i]];
+                i = parent[i];
+            }
+            i
+        }
+        for a in 0..faces.len() {
+            for b in a + 1..faces.len() {
+                let similarity: f32 = faces[a].2.iter().zip(&faces[b].2).map(|(x, y)| x * y).sum();
+                if similarity >= threshold {
+                    let (ra, rb) = (root(&mut parent, a), root(&mut parent, b));
+                    // The earlier face stays the root so the result does not depend on timing
+                    parent[ra.max(rb)] = ra.min(rb);
+                }
+            }
+        }
+        let mut groups: std::collections::BTreeMap<usize, Vec<usize>> = std::collections::BTreeMap::new();
+        for i in 0..faces.len() {
+            let r = root(&mut parent, i);
+            groups.entry(r).or_default().push(i);
+        }
+        let mut groups: Vec<Vec<usize>> = groups.into_values().filter(|g| g.len() > 1).collect();
+        // Stable, so equal sizes keep the order of their first face
+        groups.sort_by_key(|g| std::cmp::Reverse(g.len()));
+
+        let tx = conn
+            .transaction()
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to start transaction: {}", e)))?;
+        tx.execute("UPDATE face_embeddings SET cluster_id = NULL", [])
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to reset clusters: {}", e)))?;
+        for (n, group) in groups.iter().enumerate() {
+            for &i in group {
+                let (id, index, _) = &faces[i];
+                tx.execute(
+                    "UPDATE face_embeddings SET cluster_id = ?1 WHERE media_item_id = ?2 AND face_index = ?3",
+                    params![n as i64 + 1, id, *index as i64],
+                )
+                .map_err(|e| CacheError::DatabaseError(format!("Failed to store cluster: {}", e)))?;
+            }
+        }
+        tx.commit()
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to commit transaction: {}", e)))?;
+        Ok(groups.len())
+    }
+
+    /// The cluster of face `index` of a media item, counted without faces
+    /// named since, so it can be offered for naming the rest.
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
+    pub fn face_cluster(&self, media_item_id: &str, index: usize) -> Result<Option<FaceCluster>, CacheError> {
+        let conn = self.lock_conn()?;
+        let cluster: Option<Option<i64>> = conn
+            .query_row(
+                "SELECT cluster_id FROM face_embeddings WHERE media_item_id = ?1 AND face_index = ?2",
+                params![media_item_id, index as i64],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to query cluster: {}", e)))?;
+        let Some(id) = cluster.flatten().map(|c| c as u32) else {
+            return Ok(None);
+        };
+        let photo_count = Self::unnamed_cluster_faces(&conn, id)?.len() as u64;
+        Ok(Some(FaceCluster { id, photo_count }))
+    }
+
+    /// Name every unnamed face of `cluster`; returns the number of faces changed.
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
+    pub fn name_cluster(&self, cluster: u32, name: &str) -> Result<u64, CacheError> {
+        let mut conn = self.lock_conn()?;
+        let members = Self::unnamed_cluster_faces(&conn, cluster)?;
+        let mut all: HashMap<String, Vec<FaceData>> = Self::load_all_faces(&conn)?.into_iter().collect();
+        let tx = conn
+            .transaction()
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to start transaction: {}", e)))?;
+        let mut changed = 0;
+        for (id, indices) in members {
+            let Some(faces) = all.get_mut(&id) else { continue };
+            for index in indices {
+                faces[index].name = Some(name.to_string());
+                changed += 1;
+            }
+            let json = serde_json::to_string(faces)
+                .map_err(|e| CacheError::SerializationError(e.to_string()))?;
+            tx.execute(
+                "UPDATE faces SET faces_json = ?1 WHERE media_item_id = ?2",
+                params![json, id],
+            )
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to update faces: {}", e)))?;
+        }
+        tx.execute("UPDATE face_embeddings SET cluster_id = NULL WHERE cluster_id = ?1", params![cluster as i64])
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to reset cluster: {}", e)))?;
+        tx.commit()
+            .map_err(|e| CacheError::DatabaseError(format!("Failed to commit transaction: {}", e)))?;
+        Ok(changed)
+    }
+
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
+    pub async fn clear_cache_async(&self) -> Result<(), CacheError> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.clear_cache())
+            .await
+            .map_err(|e| CacheError::Other(e.to_string()))?
+    }
+
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
+    pub async fn list_people_async(&self) -> Result<Vec<PersonSummary>, CacheError> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.list_people())
+            .await
+            .map_err(|e| CacheError::Other(e.to_string()))?
+    }
+
+    pub async fn get_people_with_counts_async(&self) -> Result<Vec<PersonEntry>, CacheError> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.get_people_with_counts())
+            .await
+            .map_err(|e| CacheError::Other(e.to_string()))?
+    }
+
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
+    pub async fn get_media_items_by_person_async(
+        &self,
+        name: Option<String>,
+    ) -> Result<Vec<api_client::MediaItem>, CacheError> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.get_media_items_by_person(name.as_deref()))
+            .await
+            .map_err(|e| CacheError::Other(e.to_string()))?
+    }
+
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
+    pub async fn rename_person_async(&self, old: String, new: String) -> Result<u64, CacheError> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.rename_person(&old, &new))
+            .await
+            .map_err(|e| CacheError::Other(e.to_string()))?
+    }
+
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
+    pub async fn merge_people_async(&self, keep: String, remove: String) -> Result<u64, CacheError> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.merge_people(&keep, &remove))
+            .await
+            .map_err(|e| CacheError::Other(e.to_string()))?
+    }
+
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
+    pub async fn get_media_items_by_cluster_async(
+        &self,
+        cluster: u32,
+    ) -> Result<Vec<api_client::MediaItem>, CacheError> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.get_media_items_by_cluster(cluster))
+            .await
+            .map_err(|e| CacheError::Other(e.to_string()))?
+    }
+
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self, embeddings)))]
+    pub async fn set_face_embeddings_async(
+        &self,
+        media_item_id: String,
+        embeddings: Vec<Vec<f32>>,
+    ) -> Result<(), CacheError> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.set_face_embeddings(&media_item_id, &embeddings))
+            .await
+            .map_err(|e| CacheError::Other(e.to_string()))?
+    }
+
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
+    pub async fn set_face_ignored_async(&self, media_item_id: String, index: usize, ignored: bool) -> Result<(), CacheError> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.set_face_ignored(&media_item_id, index, ignored))
+            .await
+            .map_err(|e| CacheError::Other(e.to_string()))?
     }
 
     #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
-    pub fn export_albums<P: AsRef<Path>>(&self, path: P) -> Result<(), CacheError> {
-        let albums = self.get_all_albums()?;
-        let file = std::fs::File::create(path.as_ref())
-            .map_err(|e| CacheError::Other(format!("Failed to create export file: {}", e)))?;
-        serde_json::to_writer(file, &albums)
-            .map_err(|e| CacheError::SerializationError(e.to_string()))
+    pub async fn get_media_items_without_faces_async(
+        &self,
+        after: Option<String>,
+        limit: usize,
+    ) -> Result<Vec<api_client::MediaItem>, CacheError> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.get_media_items_without_faces(after.as_deref(), limit))
+            .await
+            .map_err(|e| CacheError::Other(e.to_string()))?
     }
 
     #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
-    pub fn export_faces<P: AsRef<Path>>(&self, path: P) -> Result<(), CacheError> {
-        let conn = self.lock_conn()?;
-        let mut stmt = conn
-            .prepare_cached("SELECT media_item_id, faces_json FROM faces")
-            .map_err(|e| CacheError::DatabaseError(format!("Failed to prepare statement: {}", e)))?;
-        let iter = stmt
-            .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))
-            .map_err(|e| CacheError::DatabaseError(format!("Failed to query faces: {}", e)))?;
-        let mut data = Vec::new();
-        for entry in iter {
-            let (id, json) = entry.map_err(|e| CacheError::DatabaseError(format!("Failed to retrieve faces: {}", e)))?;
-            let faces: Vec<FaceData> = serde_json::from_str(&json)
-                .map_err(|e| CacheError::DeserializationError(e.to_string()))?;
-            data.push(FaceExport { media_item_id: id, faces });
-        }
-        let file = std::fs::File::create(path.as_ref())
-            .map_err(|e| CacheError::Other(format!("Failed to create export file: {}", e)))?;
-        serde_json::to_writer(file, &data)
-            .map_err(|e| CacheError::SerializationError(e.to_string()))
+    pub async fn count_media_items_without_faces_async(&self, after: Option<String>) -> Result<u64, CacheError> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.count_media_items_without_faces(after.as_deref()))
+            .await
+            .map_err(|e| CacheError::Other(e.to_string()))?
     }
 
     #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
-    pub fn import_media_items<P: AsRef<Path>>(&self, path: P) -> Result<(), CacheError> {
-        let file = std::fs::File::open(path.as_ref())
-            .map_err(|e| CacheError::Other(format!("Failed to open import file: {}", e)))?;
-        let items: Vec<api_client::MediaItem> = serde_json::from_reader(file)
-            .map_err(|e| CacheError::DeserializationError(e.to_string()))?;
-        for item in &items {
-            self.insert_media_item(item)?;
-        }
-        Ok(())
+    pub async fn get_face_scan_checkpoint_async(&self) -> Result<Option<FaceScanCheckpoint>, CacheError> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.get_face_scan_checkpoint())
+            .await
+            .map_err(|e| CacheError::Other(e.to_string()))?
     }
 
     #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
-    pub fn import_faces<P: AsRef<Path>>(&self, path: P) -> Result<(), CacheError> {
-        let file = std::fs::File::open(path.as_ref())
-            .map_err(|e| CacheError::Other(format!("Failed to open import file: {}", e)))?;
-        let entries: Vec<FaceExport> = serde_json::from_reader(file)
-            .map_err(|e| CacheError::DeserializationError(e.to_string()))?;
-        for entry in &entries {
-            let json = serde_json::to_string(&entry.faces)
-                .map_err(|e| CacheError::SerializationError(e.to_string()))?;
-            self.insert_faces(&entry.media_item_id, &json)?;
-        }
-        Ok(())
+    pub async fn set_face_scan_checkpoint_async(&self, checkpoint: FaceScanCheckpoint) -> Result<(), CacheError> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.set_face_scan_checkpoint(&checkpoint))
+            .await
+            .map_err(|e| CacheError::Other(e.to_string()))?
     }
 
     #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
-    pub fn insert_faces(&self, media_item_id: &str, faces_json: &str) -> Result<(), CacheError> {
-        let conn = self.lock_conn()?;
-        let mut stmt = conn
-            .prepare_cached("INSERT OR REPLACE INTO faces (media_item_id, faces_json) VALUES (?1, ?2)")
-            .map_err(|e| CacheError::DatabaseError(format!("Failed to prepare statement: {}", e)))?;
-        stmt.execute(params![media_item_id, faces_json])
-            .map_err(|e| CacheError::DatabaseError(format!("Failed to insert faces: {}", e)))?;
-        Ok(())
+    pub async fn clear_face_scan_checkpoint_async(&self) -> Result<(), CacheError> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.clear_face_scan_checkpoint())
+            .await
+            .map_err(|e| CacheError::Other(e.to_string()))?
     }
 
-    #[cfg(feature = "face-recognition")]
-    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self, faces_json)))]
-    pub async fn insert_faces_async(
-        &self,
-        media_item_id: String,
-        faces_json: String,
-    ) -> Result<(), CacheError> {
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
+    pub async fn cluster_unnamed_faces_async(&self, threshold: f32) -> Result<usize, CacheError> {
         let this = self.clone();
-        tokio::task::spawn_blocking(move || this.insert_faces(&media_item_id, &faces_json))
+        tokio::task::spawn_blocking(move || this.cluster_unnamed_faces(threshold))
             .await
             .map_err(|e| CacheError::Other(e.to_string()))?
     }
 
     #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
-    pub fn get_faces(&self, media_item_id: &str) -> Result<Option<Vec<FaceData>>, CacheError> {
-        let conn = self.lock_conn()?;
-        let mut stmt = conn
-            .prepare_cached("SELECT faces_json FROM faces WHERE media_item_id = ?1")
-            .map_err(|e| CacheError::DatabaseError(format!("Failed to prepare statement: {}", e)))?;
-        let faces_json: Option<String> = stmt
-            .query_row(params![media_item_id], |row| row.get(0))
-            .optional()
-            .map_err(|e| CacheError::DatabaseError(format!("Failed to query faces: {}", e)))?;
-        if let Some(json) = faces_json {
-            let data: Vec<FaceData> = serde_json::from_str(&json)
-                .map_err(|e| CacheError::DeserializationError(e.to_string()))?;
-            Ok(Some(data))
-        } else {
-            Ok(None)
-        }
+    pub async fn face_cluster_async(&self, media_item_id: String, index: usize) -> Result<Option<FaceCluster>, CacheError> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.face_cluster(&media_item_id, index))
+            .await
+            .map_err(|e| CacheError::Other(e.to_string()))?
+    }
+
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
+    pub async fn name_cluster_async(&self, cluster: u32, name: String) -> Result<u64, CacheError> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.name_cluster(cluster, &name))
+            .await
+            .map_err(|e| CacheError::Other(e.to_string()))?
     }
 
     #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self, item)))]
@@ -1354,6 +3657,14 @@ impl CacheManager {
             .map_err(|e| CacheError::Other(e.to_string()))?
     }
 
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self, items)))]
+    pub async fn upsert_media_items_batch_async(&self, items: Vec<api_client::MediaItem>) -> Result<UpsertStats, CacheError> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.upsert_media_items_batch(&items))
+            .await
+            .map_err(|e| CacheError::Other(e.to_string()))?
+    }
+
     #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
     pub async fn get_all_media_items_async(&self) -> Result<Vec<api_client::MediaItem>, CacheError> {
         let this = self.clone();
@@ -1460,6 +3771,42 @@ impl CacheManager {
             .map_err(|e| CacheError::Other(e.to_string()))?
     }
 
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self, media_item_ids)))]
+    pub async fn associate_media_items_with_album_async(
+        &self,
+        media_item_ids: Vec<String>,
+        album_id: String,
+    ) -> Result<(), CacheError> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.associate_media_items_with_album(&media_item_ids, &album_id))
+            .await
+            .map_err(|e| CacheError::Other(e.to_string()))?
+    }
+
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self, media_item_ids)))]
+    pub async fn set_album_media_items_async(
+        &self,
+        album_id: String,
+        media_item_ids: Vec<String>,
+    ) -> Result<(), CacheError> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.set_album_media_items(&album_id, &media_item_ids))
+            .await
+            .map_err(|e| CacheError::Other(e.to_string()))?
+    }
+
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self, media_item_ids)))]
+    pub async fn remove_media_items_from_album_async(
+        &self,
+        media_item_ids: Vec<String>,
+        album_id: String,
+    ) -> Result<(), CacheError> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.remove_media_items_from_album(&media_item_ids, &album_id))
+            .await
+            .map_err(|e| CacheError::Other(e.to_string()))?
+    }
+
     #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
     pub async fn rename_album_async(&self, album_id: String, new_title: String) -> Result<(), CacheError> {
         let this = self.clone();
@@ -1484,6 +3831,41 @@ impl CacheManager {
             .map_err(|e| CacheError::Other(e.to_string()))?
     }
 
+    pub async fn trash_media_items_async(&self, ids: Vec<String>) -> Result<(), CacheError> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.trash_media_items(&ids))
+            .await
+            .map_err(|e| CacheError::Other(e.to_string()))?
+    }
+
+    pub async fn restore_media_items_async(&self, ids: Vec<String>) -> Result<(), CacheError> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.restore_media_items(&ids))
+            .await
+            .map_err(|e| CacheError::Other(e.to_string()))?
+    }
+
+    pub async fn get_trashed_media_items_async(&self) -> Result<Vec<TrashedMediaItem>, CacheError> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.get_trashed_media_items())
+            .await
+            .map_err(|e| CacheError::Other(e.to_string()))?
+    }
+
+    pub async fn purge_media_items_async(&self, ids: Vec<String>) -> Result<u64, CacheError> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.purge_media_items(&ids))
+            .await
+            .map_err(|e| CacheError::Other(e.to_string()))?
+    }
+
+    pub async fn empty_trash_async(&self) -> Result<u64, CacheError> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.empty_trash())
+            .await
+            .map_err(|e| CacheError::Other(e.to_string()))?
+    }
+
     pub async fn set_favorite_async(&self, id: String, fav: bool) -> Result<(), CacheError> {
         let this = self.clone();
         tokio::task::spawn_blocking(move || this.set_favorite(&id, fav))
@@ -1491,6 +3873,103 @@ impl CacheManager {
             .map_err(|e| CacheError::Other(e.to_string()))?
     }
 
+    pub async fn set_favorites_async(&self, ids: Vec<String>, fav: bool) -> Result<(), CacheError> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.set_favorites(&ids, fav))
+            .await
+            .map_err(|e| CacheError::Other(e.to_string()))?
+    }
+
+    pub async fn replace_favorites_async(&self, ids: Vec<String>) -> Result<(), CacheError> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.replace_favorites(&ids))
+            .await
+            .map_err(|e| CacheError::Other(e.to_string()))?
+    }
+
+    pub async fn uploaded_media_item_ids_async(&self, ids: Vec<String>) -> Result<Vec<String>, CacheError> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.uploaded_media_item_ids(&ids))
+            .await
+            .map_err(|e| CacheError::Other(e.to_string()))?
+    }
+
+    pub async fn record_uploaded_file_async(
+        &self,
+        hash: String,
+        media_item_id: String,
+        file_name: String,
+    ) -> Result<(), CacheError> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.record_uploaded_file(&hash, &media_item_id, &file_name))
+            .await
+            .map_err(|e| CacheError::Other(e.to_string()))?
+    }
+
+    pub async fn count_media_items_async(&self) -> Result<u64, CacheError> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.count_media_items())
+            .await
+            .map_err(|e| CacheError::Other(e.to_string()))?
+    }
+
+    pub async fn count_media_items_by_period_async(
+        &self,
+        bucket: TimeBucket,
+        mode: TimeMode,
+    ) -> Result<Vec<(String, u64)>, CacheError> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.count_media_items_by_period(bucket, mode))
+            .await
+            .map_err(|e| CacheError::Other(e.to_string()))?
+    }
+
+    pub async fn set_video_duration_async(&self, id: String, duration_ms: u64) -> Result<(), CacheError> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.set_video_duration(&id, duration_ms))
+            .await
+            .map_err(|e| CacheError::Other(e.to_string()))?
+    }
+
+    pub async fn get_video_durations_async(&self) -> Result<HashMap<String, u64>, CacheError> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.get_video_durations())
+            .await
+            .map_err(|e| CacheError::Other(e.to_string()))?
+    }
+
+    pub async fn update_media_item_description_async(
+        &self,
+        id: String,
+        description: String,
+    ) -> Result<(), CacheError> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.update_media_item_description(&id, &description))
+            .await
+            .map_err(|e| CacheError::Other(e.to_string()))?
+    }
+
+    pub async fn enqueue_mutation_async(&self, mutation: Mutation) -> Result<(), CacheError> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.enqueue_mutation(&mutation))
+            .await
+            .map_err(|e| CacheError::Other(e.to_string()))?
+    }
+
+    pub async fn pending_mutations_async(&self) -> Result<Vec<PendingMutation>, CacheError> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.pending_mutations())
+            .await
+            .map_err(|e| CacheError::Other(e.to_string()))?
+    }
+
+    pub async fn remove_mutation_async(&self, id: i64) -> Result<(), CacheError> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.remove_mutation(id))
+            .await
+            .map_err(|e| CacheError::Other(e.to_string()))?
+    }
+
     #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
     pub async fn get_all_albums_async(&self) -> Result<Vec<api_client::Album>, CacheError> {
         let this = self.clone();
@@ -1499,6 +3978,22 @@ impl CacheManager {
             .map_err(|e| CacheError::Other(e.to_string()))?
     }
 
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
+    pub async fn get_albums_for_media_item_async(&self, media_item_id: String) -> Result<Vec<api_client::Album>, CacheError> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.get_albums_for_media_item(&media_item_id))
+            .await
+            .map_err(|e| CacheError::Other(e.to_string()))?
+    }
+
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
+    pub async fn get_album_item_counts_async(&self) -> Result<HashMap<String, u64>, CacheError> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.get_album_item_counts())
+            .await
+            .map_err(|e| CacheError::Other(e.to_string()))?
+    }
+
     #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
     pub async fn get_media_items_by_album_async(&self, album_id: String) -> Result<Vec<api_client::MediaItem>, CacheError> {
         let this = self.clone();
@@ -1524,9 +4019,14 @@ impl CacheManager {
     }
 
     #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
-    pub async fn get_media_items_by_date_range_async(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Result<Vec<api_client::MediaItem>, CacheError> {
+    pub async fn get_media_items_by_date_range_async(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        mode: TimeMode,
+    ) -> Result<Vec<api_client::MediaItem>, CacheError> {
         let this = self.clone();
-        tokio::task::spawn_blocking(move || this.get_media_items_by_date_range(start, end))
+        tokio::task::spawn_blocking(move || this.get_media_items_by_date_range(start, end, mode))
             .await
             .map_err(|e| CacheError::Other(e.to_string()))?
     }
@@ -1538,9 +4038,13 @@ impl CacheManager {
             .map_err(|e| CacheError::Other(e.to_string()))?
     }
 
-    pub async fn get_media_items_by_text_async(&self, pattern: String) -> Result<Vec<api_client::MediaItem>, CacheError> {
+    pub async fn get_media_items_by_text_async(
+        &self,
+        pattern: String,
+        album_id: Option<String>,
+    ) -> Result<Vec<api_client::MediaItem>, CacheError> {
         let this = self.clone();
-        tokio::task::spawn_blocking(move || this.get_media_items_by_text(&pattern))
+        tokio::task::spawn_blocking(move || this.get_media_items_by_text(&pattern, album_id.as_deref()))
             .await
             .map_err(|e| CacheError::Other(e.to_string()))?
     }
@@ -1580,6 +4084,23 @@ impl CacheManager {
   
   
     #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
+    pub async fn query_async(&self, query: MediaQuery) -> Result<Vec<api_client::MediaItem>, CacheError> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.query(&query))
+            .await
+            .map_err(|e| CacheError::Other(e.to_string()))?
+    }
+
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
+    pub async fn query_count_async(&self, query: MediaQuery) -> Result<u64, CacheError> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.query_count(&query))
+            .await
+            .map_err(|e| CacheError::Other(e.to_string()))?
+    }
+
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
+    #[allow(clippy::too_many_arguments)]
     pub async fn query_media_items_async(
         &self,
         camera_model: Option<String>,
@@ -1589,6 +4110,7 @@ impl CacheManager {
         favorite: Option<bool>,
         mime_type: Option<String>,
         text: Option<String>,
+        album_id: Option<String>,
     ) -> Result<Vec<api_client::MediaItem>, CacheError> {
         let this = self.clone();
         tokio::task::spawn_blocking(move || {
@@ -1600,6 +4122,7 @@ impl CacheManager {
                 favorite,
                 mime_type.as_deref(),
                 text.as_deref(),
+                album_id.as_deref(),
             )
         })
         .await
@@ -1617,6 +4140,8 @@ impl CacheManager {
                     .map(|f| face_recognition::Face {
                         bbox: f.bbox,
                         name: f.name,
+                        ignored: f.ignored,
+                        confidence: f.confidence,
                         rect: (
                             f.bbox[0] as u32,
                             f.bbox[1] as u32,
@@ -1680,6 +4205,7 @@ mod tests {
                 video: None,
             },
             filename: format!("{}.jpg", id),
+            is_favorite: false,
         }
     }
 
@@ -1856,6 +4382,7 @@ mod tests {
                 Some(true),
                 None,
                 None,
+                None,
             )
             .expect("query");
         assert_eq!(items.len(), 1);
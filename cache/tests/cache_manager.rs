@@ -1,4 +1,4 @@
-use cache::{CacheManager, CacheError};
+use cache::{CacheManager, CacheError, ImportMode, MediaQuery, MediaSort, Mutation, TimeBucket, TimeMode};
 use tempfile::NamedTempFile;
 use api_client::{MediaItem, MediaMetadata};
 use chrono::{Utc, TimeZone};
@@ -19,6 +19,7 @@ fn sample_item(id: &str) -> MediaItem {
             video: None,
         },
         filename: format!("{}.jpg", id),
+        is_favorite: false,
     }
 }
 
@@ -47,7 +48,7 @@ fn test_new_applies_migrations() {
     let version: i64 = conn
         .query_row("SELECT version FROM schema_version", [], |row| row.get(0))
         .unwrap();
-    assert_eq!(version, 13);
+    assert_eq!(version, 23);
 }
 
 #[test]
@@ -197,6 +198,41 @@ fn test_explain_mime_filter_uses_index() {
     assert!(plan.contains("idx_media_items_mime_type"), "plan was {}", plan);
 }
 
+#[tokio::test]
+async fn test_clear_cache_is_all_or_nothing() {
+    let file = NamedTempFile::new().unwrap();
+    let cache = CacheManager::new(file.path()).unwrap();
+    let item = sample_item("1");
+    cache.insert_media_item(&item).unwrap();
+    cache.insert_faces(&item.id, r#"[{"bbox":[0,0,1,1],"name":null}]"#).unwrap();
+    cache.set_video_duration(&item.id, 1000).unwrap();
+    cache
+        .enqueue_mutation(&Mutation::UpdateDescription { media_item_id: item.id.clone(), description: "x".into() })
+        .unwrap();
+    let count = |table: &str| -> i64 {
+        let conn = Connection::open(file.path()).unwrap();
+        conn.query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |r| r.get(0)).unwrap()
+    };
+
+    // A step failing halfway leaves everything in place
+    Connection::open(file.path())
+        .unwrap()
+        .execute_batch("CREATE TRIGGER block_clear BEFORE DELETE ON media_items BEGIN SELECT RAISE(ABORT, 'blocked'); END;")
+        .unwrap();
+    assert!(cache.clear_cache_async().await.is_err());
+    for table in ["media_items", "media_metadata", "media_items_fts", "faces", "video_durations"] {
+        assert_eq!(count(table), 1, "{} was cleared", table);
+    }
+
+    Connection::open(file.path()).unwrap().execute_batch("DROP TRIGGER block_clear;").unwrap();
+    cache.clear_cache_async().await.unwrap();
+    for table in ["media_items", "media_metadata", "media_items_fts", "faces", "video_durations"] {
+        assert_eq!(count(table), 0, "{} was not cleared", table);
+    }
+    assert_eq!(cache.pending_mutations().unwrap().len(), 1);
+    assert!(cache.check_integrity().unwrap().is_ok());
+}
+
 #[tokio::test]
 async fn test_async_wrappers() {
     let file = NamedTempFile::new().unwrap();
@@ -263,12 +299,45 @@ fn test_get_media_items_by_text() {
     item2.filename = "foo.png".into();
     cm.insert_media_item(&item2).unwrap();
 
-    let results = cm.get_media_items_by_text("foo").unwrap();
+    let results = cm.get_media_items_by_text("foo", None).unwrap();
     assert_eq!(results.len(), 2);
     let ids: HashSet<_> = results.iter().map(|i| i.id.as_str()).collect();
     assert!(ids.contains("1") && ids.contains("2"));
 }
 
+#[test]
+fn test_text_search_within_album() {
+    let file = NamedTempFile::new().unwrap();
+    let cm = CacheManager::new(file.path()).unwrap();
+    for id in ["1", "2"] {
+        let mut item = sample_item(id);
+        item.description = Some("sunset".into());
+        cm.insert_media_item(&item).unwrap();
+    }
+    cm.insert_album(&api_client::Album {
+        id: "a1".into(),
+        title: Some("Beach".into()),
+        product_url: None,
+        is_writeable: None,
+        media_items_count: None,
+        cover_photo_base_url: None,
+        cover_photo_media_item_id: None,
+    })
+    .unwrap();
+    cm.associate_media_item_with_album("2", "a1").unwrap();
+
+    assert_eq!(cm.get_media_items_by_text("sunset", None).unwrap().len(), 2);
+    let scoped = cm.get_media_items_by_text("sunset", Some("a1")).unwrap();
+    assert_eq!(scoped.len(), 1);
+    assert_eq!(scoped[0].id, "2");
+    let scoped = cm
+        .query_media_items(None, None, None, None, None, None, Some("sunset"), Some("a1"))
+        .unwrap();
+    assert_eq!(scoped.len(), 1);
+    assert_eq!(scoped[0].id, "2");
+    assert!(cm.get_media_items_by_text("sunset", Some("missing")).unwrap().is_empty());
+}
+
 #[test]
 fn test_query_media_items_combined() {
     let file = NamedTempFile::new().unwrap();
@@ -283,7 +352,7 @@ fn test_query_media_items_combined() {
     });
     cm.insert_media_item(&item1).unwrap();
     {
-        let conn = cm.conn.lock().unwrap();
+        let conn = cm.lock_conn().unwrap();
         conn.execute(
             "UPDATE media_items SET is_favorite = 1 WHERE id = ?1",
             params![item1.id],
@@ -310,6 +379,7 @@ fn test_query_media_items_combined() {
             Some(true),
             None,
             None,
+            None,
         )
         .unwrap();
     assert_eq!(results.len(), 1);
@@ -325,7 +395,7 @@ fn test_query_media_items_text_date_fav() {
     item1.media_metadata.creation_time = "2023-01-02T00:00:00Z".into();
     cm.insert_media_item(&item1).unwrap();
     {
-        let conn = cm.conn.lock().unwrap();
+        let conn = cm.lock_conn().unwrap();
         conn.execute(
             "UPDATE media_items SET is_favorite = 1 WHERE id = ?1",
             rusqlite::params![item1.id],
@@ -347,8 +417,893 @@ fn test_query_media_items_text_date_fav() {
             Some(true),
             None,
             Some("holiday"),
+            None,
         )
         .unwrap();
     assert_eq!(results.len(), 1);
     assert_eq!(results[0].id, item1.id);
 }
+
+#[test]
+fn test_upsert_media_items_batch_stats() {
+    let file = NamedTempFile::new().unwrap();
+    let cm = CacheManager::new(file.path()).unwrap();
+    let items = vec![sample_item("1"), sample_item("2")];
+    let stats = cm.upsert_media_items_batch(&items).unwrap();
+    assert_eq!((stats.inserted, stats.updated, stats.unchanged), (2, 0, 0));
+    assert_eq!(stats.inserted_ids, vec!["1".to_string(), "2".to_string()]);
+    cm.set_favorite("1", true).unwrap();
+
+    let mut changed = items.clone();
+    changed[0].base_url = "http://example.com/fresh".into();
+    changed[1].description = Some("edited".into());
+    let stats = cm.upsert_media_items_batch(&changed).unwrap();
+    assert_eq!((stats.inserted, stats.updated, stats.unchanged), (0, 1, 1));
+    assert_eq!(stats.changed_ids, vec!["2".to_string()]);
+    assert!(stats.inserted_ids.is_empty());
+
+    let first = cm.get_media_item("1").unwrap().unwrap();
+    assert_eq!(first.base_url, "http://example.com/fresh");
+    let favs = cm.get_favorite_media_items().unwrap();
+    assert_eq!(favs.len(), 1);
+    assert_eq!(cm.get_media_items_by_text("edited", None).unwrap().len(), 1);
+}
+
+#[test]
+fn test_query_builder_album_sort_limit() {
+    let file = NamedTempFile::new().unwrap();
+    let cm = CacheManager::new(file.path()).unwrap();
+    for (id, day) in [("1", "01"), ("2", "02"), ("3", "03")] {
+        let mut item = sample_item(id);
+        item.media_metadata.creation_time = format!("2023-01-{}T00:00:00Z", day);
+        cm.insert_media_item(&item).unwrap();
+    }
+    let album = api_client::Album {
+        id: "a1".into(),
+        title: Some("Album".into()),
+        product_url: None,
+        is_writeable: None,
+        media_items_count: None,
+        cover_photo_base_url: None,
+        cover_photo_media_item_id: None,
+    };
+    cm.insert_album(&album).unwrap();
+    cm.associate_media_item_with_album("1", "a1").unwrap();
+    cm.associate_media_item_with_album("3", "a1").unwrap();
+
+    let newest = cm.query(&MediaQuery::new().album("a1")).unwrap();
+    let ids: Vec<_> = newest.iter().map(|i| i.id.as_str()).collect();
+    assert_eq!(ids, vec!["3", "1"]);
+
+    let oldest = cm
+        .query(&MediaQuery::new().sort(MediaSort::OldestFirst).limit(2))
+        .unwrap();
+    let ids: Vec<_> = oldest.iter().map(|i| i.id.as_str()).collect();
+    assert_eq!(ids, vec!["1", "2"]);
+
+    let by_name = cm.query(&MediaQuery::new().filename("2.jpg")).unwrap();
+    assert_eq!(by_name.len(), 1);
+    assert_eq!(by_name[0].id, "2");
+}
+
+#[test]
+fn test_query_count_ignores_limit() {
+    let file = NamedTempFile::new().unwrap();
+    let cm = CacheManager::new(file.path()).unwrap();
+    for id in ["1", "2", "3"] {
+        cm.insert_media_item(&sample_item(id)).unwrap();
+    }
+    cm.set_favorite("2", true).unwrap();
+
+    assert_eq!(cm.query_count(&MediaQuery::new().limit(1)).unwrap(), 3);
+    assert_eq!(cm.query_count(&MediaQuery::new().favorite(true)).unwrap(), 1);
+    cm.trash_media_items(&["3".to_string()]).unwrap();
+    assert_eq!(cm.query_count(&MediaQuery::new()).unwrap(), 2);
+}
+
+#[test]
+fn test_export_import_state_merge() {
+    let src_file = NamedTempFile::new().unwrap();
+    let src = CacheManager::new(src_file.path()).unwrap();
+    src.insert_media_item(&sample_item("1")).unwrap();
+    src.set_favorite("1", true).unwrap();
+    src.insert_faces("1", r#"[{"bbox":[0,0,5,5],"name":null}]"#).unwrap();
+
+    let dir = tempfile::tempdir().unwrap();
+    let mut exported = Vec::new();
+    src.export_state(dir.path(), |name, _| exported.push(name.to_string()))
+        .unwrap();
+    assert_eq!(exported, cache::STATE_FILES);
+
+    let dst_file = NamedTempFile::new().unwrap();
+    let dst = CacheManager::new(dst_file.path()).unwrap();
+    dst.insert_media_item(&sample_item("local")).unwrap();
+    dst.import_state(dir.path(), ImportMode::Merge, |_, _| {}).unwrap();
+
+    assert_eq!(dst.get_all_media_items().unwrap().len(), 2);
+    let favs = dst.get_favorite_media_items().unwrap();
+    assert_eq!(favs.len(), 1);
+    assert_eq!(favs[0].id, "1");
+    assert_eq!(dst.get_faces("1").unwrap().unwrap().len(), 1);
+}
+
+#[test]
+fn test_failed_import_state_changes_nothing() {
+    let src_file = NamedTempFile::new().unwrap();
+    let src = CacheManager::new(src_file.path()).unwrap();
+    src.insert_media_item(&sample_item("1")).unwrap();
+    let dir = tempfile::tempdir().unwrap();
+    src.export_state(dir.path(), |_, _| {}).unwrap();
+    std::fs::write(dir.path().join("faces.json"), "not json").unwrap();
+
+    let dst_file = NamedTempFile::new().unwrap();
+    let dst = CacheManager::new(dst_file.path()).unwrap();
+    dst.insert_media_item(&sample_item("local")).unwrap();
+    assert!(dst.import_state(dir.path(), ImportMode::Replace, |_, _| {}).is_err());
+
+    let items = dst.get_all_media_items().unwrap();
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].id, "local");
+    // The connection is usable again after the rollback
+    dst.import_state(tempfile::tempdir().unwrap().path(), ImportMode::Merge, |_, _| {}).unwrap();
+}
+
+#[test]
+fn test_check_integrity_detects_orphans() {
+    let file = NamedTempFile::new().unwrap();
+    let cache = CacheManager::new(file.path()).unwrap();
+    assert_eq!(cache.schema_version().unwrap(), cache::SCHEMA_VERSION);
+    cache.insert_media_item(&sample_item("1")).unwrap();
+    assert!(cache.check_integrity().unwrap().is_ok());
+
+    {
+        let conn = Connection::open(file.path()).unwrap();
+        conn.execute_batch(
+            "PRAGMA foreign_keys=off;\
+             INSERT INTO faces (media_item_id, faces_json) VALUES ('missing', '[]');\
+             INSERT INTO album_media_items (album_id, media_item_id) VALUES ('no_album', '1');",
+        )
+        .unwrap();
+    }
+    let report = cache.check_integrity().unwrap();
+    assert!(!report.is_ok());
+    assert_eq!(report.orphaned_faces, 1);
+    assert_eq!(report.orphaned_album_links, 1);
+    assert!(report.sqlite_errors.is_empty());
+}
+
+#[test]
+fn test_record_uploaded_file() {
+    let file = NamedTempFile::new().unwrap();
+    let cache = CacheManager::new(file.path()).unwrap();
+    assert!(cache.get_uploaded_file("abc").unwrap().is_none());
+    cache.record_uploaded_file("abc", "item1", "a.jpg").unwrap();
+    assert_eq!(cache.get_uploaded_file("abc").unwrap().as_deref(), Some("item1"));
+}
+
+#[test]
+fn test_people_rename_and_merge() {
+    let file = NamedTempFile::new().unwrap();
+    let cache = CacheManager::new(file.path()).unwrap();
+    for id in ["1", "2", "3"] {
+        cache.insert_media_item(&sample_item(id)).unwrap();
+    }
+    cache
+        .insert_faces("1", r#"[{"bbox":[0,0,1,1],"name":"Ann"},{"bbox":[2,2,1,1],"name":"Bob"}]"#)
+        .unwrap();
+    cache.insert_faces("2", r#"[{"bbox":[0,0,1,1],"name":"Ann"}]"#).unwrap();
+    cache.insert_faces("3", r#"[{"bbox":[0,0,1,1],"name":"Bobby"}]"#).unwrap();
+
+    let people = cache.list_people().unwrap();
+    assert_eq!(people[0], cache::PersonSummary { name: "Ann".into(), photo_count: 2 });
+    assert_eq!(people.len(), 3);
+
+    assert_eq!(cache.rename_person("Ann", "Anna").unwrap(), 2);
+    assert_eq!(cache.merge_people("Bob", "Bobby").unwrap(), 1);
+    assert!(cache.merge_people("Bob", "Nobody").is_err());
+
+    let people = cache.list_people().unwrap();
+    let names: Vec<_> = people.iter().map(|p| (p.name.as_str(), p.photo_count)).collect();
+    assert_eq!(names, vec![("Anna", 2), ("Bob", 2)]);
+}
+
+#[test]
+fn test_people_with_counts_and_items_by_person() {
+    let file = NamedTempFile::new().unwrap();
+    let cache = CacheManager::new(file.path()).unwrap();
+    for id in ["1", "2", "3"] {
+        cache.insert_media_item(&sample_item(id)).unwrap();
+    }
+    cache
+        .insert_faces("1", r#"[{"bbox":[0,0,10,10],"name":"Ann"},{"bbox":[20,0,5,5],"name":null}]"#)
+        .unwrap();
+    cache.insert_faces("2", r#"[{"bbox":[0,0,40,40],"name":"Ann"},{"bbox":[50,0,8,8],"name":"Bob"}]"#).unwrap();
+    cache.insert_faces("3", r#"[{"bbox":[0,0,6,6],"name":null},{"bbox":[9,0,7,7],"name":null}]"#).unwrap();
+
+    let people = cache.get_people_with_counts().unwrap();
+    let summary: Vec<_> = people
+        .iter()
+        .map(|p| (p.name.as_deref(), p.photo_count, p.sample_media_item_id.as_str()))
+        .collect();
+    assert_eq!(summary, vec![(Some("Ann"), 2, "2"), (Some("Bob"), 1, "2"), (None, 2, "3")]);
+    assert_eq!(people[0].sample_bbox, [0, 0, 40, 40]);
+
+    let mut ann: Vec<_> = cache
+        .get_media_items_by_person(Some("Ann"))
+        .unwrap()
+        .into_iter()
+        .map(|i| i.id)
+        .collect();
+    ann.sort();
+    assert_eq!(ann, vec!["1", "2"]);
+    let mut unnamed: Vec<_> = cache
+        .get_media_items_by_person(None)
+        .unwrap()
+        .into_iter()
+        .map(|i| i.id)
+        .collect();
+    unnamed.sort();
+    assert_eq!(unnamed, vec!["1", "3"]);
+}
+
+#[test]
+fn test_library_stats_and_year_buckets() {
+    let file = NamedTempFile::new().unwrap();
+    let cache = CacheManager::new(file.path()).unwrap();
+    for (id, year, mime) in [("1", 2021, "image/jpeg"), ("2", 2022, "image/jpeg"), ("3", 2022, "video/mp4")] {
+        let mut item = sample_item(id);
+        item.mime_type = mime.into();
+        item.media_metadata.creation_time = format!("{}-06-01T00:00:00Z", year);
+        item.media_metadata.video = Some(api_client::VideoMetadata {
+            camera_make: None,
+            camera_model: Some("Pixel".into()),
+            fps: None,
+            status: None,
+        });
+        cache.insert_media_item(&item).unwrap();
+    }
+    cache.set_favorite("2", true).unwrap();
+    cache.insert_faces("1", r#"[{"bbox":[0,0,1,1],"name":"Ann"}]"#).unwrap();
+
+    let stats = cache.get_library_stats().unwrap();
+    assert_eq!(stats.item_count, 3);
+    assert_eq!(cache.count_media_items().unwrap(), 3);
+    assert_eq!(stats.video_count, 1);
+    assert_eq!(stats.favorite_count, 1);
+    assert_eq!(stats.people_count, 1);
+    assert_eq!(stats.top_camera_models, vec![("Pixel".to_string(), 3)]);
+    assert_eq!(stats.top_mime_types[0], ("image/jpeg".to_string(), 2));
+    assert_eq!(stats.earliest.unwrap().to_rfc3339(), "2021-06-01T00:00:00+00:00");
+
+    let years = cache.count_media_items_by_period(cache::TimeBucket::Year, cache::TimeMode::Utc).unwrap();
+    assert_eq!(years, vec![("2021".to_string(), 1), ("2022".to_string(), 2)]);
+}
+
+#[test]
+fn test_batch_album_and_favorite_updates() {
+    let file = NamedTempFile::new().unwrap();
+    let cache = CacheManager::new(file.path()).unwrap();
+    let ids: Vec<String> = ["1", "2", "3"].iter().map(|s| s.to_string()).collect();
+    for id in &ids {
+        cache.insert_media_item(&sample_item(id)).unwrap();
+    }
+    cache
+        .insert_album(&api_client::Album {
+            id: "a1".into(),
+            title: Some("Trip".into()),
+            product_url: None,
+            is_writeable: None,
+            media_items_count: None,
+            cover_photo_base_url: None,
+            cover_photo_media_item_id: None,
+        })
+        .unwrap();
+
+    assert!(cache.get_album_item_counts().unwrap().is_empty());
+    cache.associate_media_items_with_album(&ids, "a1").unwrap();
+    assert_eq!(cache.get_media_items_by_album("a1").unwrap().len(), 3);
+    assert_eq!(cache.get_album_item_counts().unwrap().get("a1"), Some(&3));
+    cache.remove_media_items_from_album(&ids[..2], "a1").unwrap();
+    assert_eq!(cache.get_media_items_by_album("a1").unwrap().len(), 1);
+    assert_eq!(cache.get_album_item_counts().unwrap().get("a1"), Some(&1));
+    cache.set_album_media_items("a1", &ids[..2]).unwrap();
+    let mut in_album: Vec<String> = cache.get_media_items_by_album("a1").unwrap().into_iter().map(|m| m.id).collect();
+    in_album.sort();
+    assert_eq!(in_album, ids[..2]);
+    cache.set_album_media_items("a1", &ids[2..]).unwrap();
+
+    cache.set_favorites(&ids[1..], true).unwrap();
+    assert_eq!(cache.get_favorite_media_items().unwrap().len(), 2);
+
+    let albums = cache.get_albums_for_media_item("3").unwrap();
+    assert_eq!(albums.len(), 1);
+    assert_eq!(albums[0].title.as_deref(), Some("Trip"));
+    assert!(cache.get_albums_for_media_item("1").unwrap().is_empty());
+}
+
+#[test]
+fn test_favorite_flag_returned_by_item_queries() {
+    let file = NamedTempFile::new().unwrap();
+    let cache = CacheManager::new(file.path()).unwrap();
+    cache.insert_media_item(&sample_item("1")).unwrap();
+    cache.insert_media_item(&sample_item("2")).unwrap();
+    cache.set_favorite("2", true).unwrap();
+
+    assert!(cache.get_media_item("2").unwrap().unwrap().is_favorite);
+    assert!(!cache.get_media_item("1").unwrap().unwrap().is_favorite);
+    let all = cache.get_all_media_items().unwrap();
+    assert!(all.iter().find(|i| i.id == "2").unwrap().is_favorite);
+
+    let flag = |items: Vec<MediaItem>| items.iter().find(|i| i.id == "2").map(|i| i.is_favorite);
+    let album = api_client::Album {
+        id: "a1".into(),
+        title: Some("Album".into()),
+        product_url: None,
+        is_writeable: None,
+        media_items_count: None,
+        cover_photo_base_url: None,
+        cover_photo_media_item_id: None,
+    };
+    cache.insert_album(&album).unwrap();
+    cache.associate_media_item_with_album("2", "a1").unwrap();
+    assert_eq!(flag(cache.get_media_items_by_album("a1").unwrap()), Some(true));
+    assert_eq!(flag(cache.query(&MediaQuery::new()).unwrap()), Some(true));
+    assert_eq!(flag(cache.get_media_items_by_mime_type("image/jpeg").unwrap()), Some(true));
+    assert_eq!(flag(cache.get_media_items_by_text("2", None).unwrap()), Some(true));
+    assert_eq!(
+        flag(cache.query_media_items(None, None, None, None, None, None, None, Some("a1")).unwrap()),
+        Some(true)
+    );
+
+    // Writing the item again, as a sync does, keeps the mark
+    cache.insert_media_item(&sample_item("2")).unwrap();
+    cache.insert_media_items_batch(&[sample_item("2")]).unwrap();
+    assert!(cache.get_media_item("2").unwrap().unwrap().is_favorite);
+
+    let export_file = NamedTempFile::new().unwrap();
+    cache.export_media_items(export_file.path()).unwrap();
+    cache.clear_cache().unwrap();
+    cache.import_media_items(export_file.path()).unwrap();
+    assert!(cache.get_media_item("2").unwrap().unwrap().is_favorite);
+    assert!(!cache.get_media_item("1").unwrap().unwrap().is_favorite);
+}
+
+#[test]
+fn test_update_description_refreshes_search() {
+    let file = NamedTempFile::new().unwrap();
+    let cache = CacheManager::new(file.path()).unwrap();
+    cache.insert_media_item(&sample_item("1")).unwrap();
+    cache.update_media_item_description("1", "sunset at the lake").unwrap();
+
+    let item = cache.get_media_item("1").unwrap().unwrap();
+    assert_eq!(item.description.as_deref(), Some("sunset at the lake"));
+    let found = cache.get_media_items_by_text("sunset", None).unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].id, "1");
+}
+
+#[test]
+fn test_mutation_queue_roundtrip() {
+    let file = NamedTempFile::new().unwrap();
+    let cache = CacheManager::new(file.path()).unwrap();
+    let first = Mutation::UpdateDescription { media_item_id: "1".into(), description: "a".into() };
+    let second = Mutation::UpdateDescription { media_item_id: "2".into(), description: "b".into() };
+    cache.enqueue_mutation(&first).unwrap();
+    cache.enqueue_mutation(&second).unwrap();
+
+    let pending = cache.pending_mutations().unwrap();
+    assert_eq!(pending.len(), 2);
+    assert_eq!(pending[0].mutation, first);
+    assert_eq!(pending[1].mutation, second);
+
+    cache.remove_mutation(pending[0].id).unwrap();
+    let pending = cache.pending_mutations().unwrap();
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].mutation, second);
+}
+
+#[test]
+fn test_favorite_mutations_and_replace_favorites() {
+    let file = NamedTempFile::new().unwrap();
+    let cache = CacheManager::new(file.path()).unwrap();
+    for id in ["1", "2", "3", "4"] {
+        cache.insert_media_item(&sample_item(id)).unwrap();
+    }
+    for id in ["1", "2", "3"] {
+        cache.record_uploaded_file(&format!("hash{}", id), id, &format!("{}.jpg", id)).unwrap();
+    }
+    let add = Mutation::favorites(vec!["1".into()], true);
+    cache.enqueue_mutation(&add).unwrap();
+    cache.enqueue_mutation(&Mutation::favorites(vec!["2".into()], false)).unwrap();
+    let pending = cache.pending_mutations().unwrap();
+    assert_eq!(pending[0].mutation, Mutation::AddFavorites { media_item_ids: vec!["1".into()] });
+    assert_eq!(pending[1].mutation, Mutation::RemoveFavorites { media_item_ids: vec!["2".into()] });
+
+    cache.set_favorites(&["1".to_string(), "2".to_string(), "4".to_string()], true).unwrap();
+    let ids: Vec<String> = ["4", "2", "1"].map(String::from).to_vec();
+    assert_eq!(cache.uploaded_media_item_ids(&ids).unwrap(), vec!["2", "1"]);
+    // "missing" is in the album but not cached yet; "4" was not uploaded
+    // here, so it can not be in the album and keeps its star
+    cache.replace_favorites(&["2".to_string(), "3".to_string(), "missing".to_string()]).unwrap();
+    let mut favorites: Vec<_> = cache.get_favorite_media_items().unwrap().into_iter().map(|i| i.id).collect();
+    favorites.sort();
+    assert_eq!(favorites, vec!["2", "3", "4"]);
+}
+
+#[test]
+fn test_video_durations_survive_resync() {
+    let file = NamedTempFile::new().unwrap();
+    let cache = CacheManager::new(file.path()).unwrap();
+    cache.insert_media_item(&sample_item("1")).unwrap();
+    cache.insert_media_item(&sample_item("2")).unwrap();
+    cache.set_video_duration("1", 83_000).unwrap();
+
+    // Sync rewrites the row; the measured duration must stay
+    cache.insert_media_item(&sample_item("1")).unwrap();
+    assert_eq!(cache.get_video_durations().unwrap().get("1"), Some(&83_000));
+
+    cache.delete_media_item("1").unwrap();
+    assert!(cache.get_video_durations().unwrap().is_empty());
+}
+
+/// Synthetic embedding pointing mostly along `axis`, tilted towards `tilt_axis`.
+fn embedding(axis: usize, tilt_axis: usize, tilt: f32) -> Vec<f32> {
+    let mut v = vec![0.0; cache::EMBEDDING_DIM];
+    v[axis] = 1.0;
+    v[tilt_axis] += tilt;
+    v
+}
+
+#[test]
+fn test_cluster_unnamed_faces() {
+    let file = NamedTempFile::new().unwrap();
+    let cache = CacheManager::new(file.path()).unwrap();
+    for id in ["1", "2", "3", "4"] {
+        cache.insert_media_item(&sample_item(id)).unwrap();
+    }
+    let unnamed = |n: usize| format!("[{}]", vec![r#"{"bbox":[0,0,10,10],"name":null}"#; n].join(","));
+    cache.insert_faces("1", &unnamed(2)).unwrap();
+    cache.insert_faces("2", &unnamed(1)).unwrap();
+    cache.insert_faces("3", &unnamed(1)).unwrap();
+    cache.insert_faces("4", r#"[{"bbox":[0,0,10,10],"name":"Ann"},{"bbox":[0,0,10,10],"name":null}]"#).unwrap();
+    // Person A in 1, 2 and 3; person B in 1 and 4; Ann looks like B but is named
+    cache.set_face_embeddings("1", &[embedding(0, 5, 0.1), embedding(1, 6, 0.2)]).unwrap();
+    cache.set_face_embeddings("2", &[embedding(0, 6, 0.2)]).unwrap();
+    cache.set_face_embeddings("3", &[embedding(0, 7, 0.3)]).unwrap();
+    cache.set_face_embeddings("4", &[embedding(1, 5, 0.1), embedding(1, 7, 0.1)]).unwrap();
+    assert!(cache.set_face_embeddings("4", &[vec![1.0; 3]]).is_err());
+
+    assert_eq!(cache.cluster_unnamed_faces(cache::DEFAULT_CLUSTER_THRESHOLD).unwrap(), 2);
+    // Same input, same ids
+    assert_eq!(cache.cluster_unnamed_faces(cache::DEFAULT_CLUSTER_THRESHOLD).unwrap(), 2);
+    let people = cache.get_people_with_counts().unwrap();
+    let summary: Vec<_> = people.iter().map(|p| (p.name.as_deref(), p.cluster, p.photo_count)).collect();
+    assert_eq!(summary, vec![(Some("Ann"), None, 1), (None, Some(1), 3), (None, Some(2), 2)]);
+
+    let mut ids: Vec<_> = cache.get_media_items_by_cluster(2).unwrap().into_iter().map(|i| i.id).collect();
+    ids.sort();
+    assert_eq!(ids, vec!["1", "4"]);
+    assert!(cache.get_media_items_by_person(None).unwrap().is_empty());
+
+    // A stricter threshold leaves every face on its own
+    assert_eq!(cache.cluster_unnamed_faces(0.999).unwrap(), 0);
+    assert_eq!(cache.get_people_with_counts().unwrap().last().unwrap().photo_count, 4);
+}
+
+#[test]
+fn test_name_cluster_after_naming_one_face() {
+    let file = NamedTempFile::new().unwrap();
+    let cache = CacheManager::new(file.path()).unwrap();
+    for id in ["1", "2", "3"] {
+        cache.insert_media_item(&sample_item(id)).unwrap();
+        cache.insert_faces(id, r#"[{"bbox":[0,0,10,10],"name":null}]"#).unwrap();
+        cache.set_face_embeddings(id, &[embedding(3, 4, 0.1)]).unwrap();
+    }
+    assert_eq!(cache.cluster_unnamed_faces(0.9).unwrap(), 1);
+
+    // Naming one face leaves the others of its cluster to offer the name to
+    cache.insert_faces("1", r#"[{"bbox":[0,0,10,10],"name":"Cleo"}]"#).unwrap();
+    let cluster = cache.face_cluster("1", 0).unwrap().unwrap();
+    assert_eq!(cluster, cache::FaceCluster { id: 1, photo_count: 2 });
+    assert_eq!(cache.face_cluster("1", 1).unwrap(), None);
+
+    assert_eq!(cache.name_cluster(cluster.id, "Cleo").unwrap(), 2);
+    let people = cache.get_people_with_counts().unwrap();
+    assert_eq!(people.len(), 1);
+    assert_eq!((people[0].name.as_deref(), people[0].cluster, people[0].photo_count), (Some("Cleo"), None, 3));
+
+    // Embeddings go with their media item
+    cache.delete_media_item("2").unwrap();
+    let conn = Connection::open(file.path()).unwrap();
+    let left: i64 = conn.query_row("SELECT COUNT(*) FROM face_embeddings", [], |row| row.get(0)).unwrap();
+    assert_eq!(left, 2);
+}
+
+#[test]
+fn test_ignored_faces_hidden_from_people_and_clusters() {
+    let file = NamedTempFile::new().unwrap();
+    let cache = CacheManager::new(file.path()).unwrap();
+    for id in ["1", "2", "3"] {
+        cache.insert_media_item(&sample_item(id)).unwrap();
+        cache
+            .insert_faces(id, r#"[{"bbox":[0,0,10,10],"name":null},{"bbox":[0,0,2,2],"name":"Ann","ignored":true}]"#)
+            .unwrap();
+        cache.set_face_embeddings(id, &[embedding(3, 4, 0.1), embedding(3, 4, 0.1)]).unwrap();
+    }
+    // The ignored faces would otherwise join the cluster
+    assert_eq!(cache.cluster_unnamed_faces(0.9).unwrap(), 1);
+    assert!(cache.list_people().unwrap().is_empty());
+    assert!(cache.get_media_items_by_person(Some("Ann")).unwrap().is_empty());
+    let people = cache.get_people_with_counts().unwrap();
+    assert_eq!(people.len(), 1);
+    assert_eq!((people[0].cluster, people[0].photo_count), (Some(1), 3));
+
+    cache.set_face_ignored("1", 0, true).unwrap();
+    assert_eq!(cache.face_cluster("1", 0).unwrap(), None);
+    assert_eq!(cache.get_media_items_by_cluster(1).unwrap().len(), 2);
+    cache.set_face_ignored("1", 1, false).unwrap();
+    assert_eq!(cache.list_people().unwrap()[0].name, "Ann");
+    assert!(cache.set_face_ignored("1", 5, true).is_err());
+
+    // The flag survives an export, and older exports without it still import
+    let export = NamedTempFile::new().unwrap();
+    cache.export_faces(export.path()).unwrap();
+    let other = NamedTempFile::new().unwrap();
+    let restored = CacheManager::new(other.path()).unwrap();
+    for id in ["1", "2", "3", "9"] {
+        restored.insert_media_item(&sample_item(id)).unwrap();
+    }
+    restored.import_faces(export.path()).unwrap();
+    let faces = restored.get_faces("1").unwrap().unwrap();
+    assert_eq!(faces.iter().map(|f| f.ignored).collect::<Vec<_>>(), vec![true, false]);
+    let old = NamedTempFile::new().unwrap();
+    std::fs::write(old.path(), r#"[{"media_item_id":"9","faces":[{"bbox":[0,0,1,1],"name":"Bo"}]}]"#).unwrap();
+    restored.import_faces(old.path()).unwrap();
+    assert!(!restored.get_faces("9").unwrap().unwrap()[0].ignored);
+}
+
+#[test]
+fn test_media_items_without_faces_and_scan_checkpoint() {
+    let file = NamedTempFile::new().unwrap();
+    let cache = CacheManager::new(file.path()).unwrap();
+    for id in ["a", "b", "c", "d"] {
+        cache.insert_media_item(&sample_item(id)).unwrap();
+    }
+    let mut video = sample_item("v");
+    video.mime_type = "video/mp4".into();
+    cache.insert_media_item(&video).unwrap();
+    // Scanned photos keep their row even when nobody was found
+    cache.insert_faces("b", "[]").unwrap();
+    cache.insert_media_item(&sample_item("e")).unwrap();
+    cache.trash_media_items(&["e".to_string()]).unwrap();
+
+    let ids = |items: Vec<MediaItem>| items.into_iter().map(|i| i.id).collect::<Vec<_>>();
+    assert_eq!(ids(cache.get_media_items_without_faces(None, 10).unwrap()), vec!["a", "c", "d"]);
+    assert_eq!(ids(cache.get_media_items_without_faces(None, 2).unwrap()), vec!["a", "c"]);
+    assert_eq!(ids(cache.get_media_items_without_faces(Some("c"), 10).unwrap()), vec!["d"]);
+    assert_eq!(cache.count_media_items_without_faces(None).unwrap(), 3);
+    assert_eq!(cache.count_media_items_without_faces(Some("a")).unwrap(), 2);
+
+    assert_eq!(cache.get_face_scan_checkpoint().unwrap(), None);
+    let checkpoint = cache::FaceScanCheckpoint { last_media_item_id: "c".into(), processed: 2 };
+    cache.set_face_scan_checkpoint(&checkpoint).unwrap();
+    let reopened = CacheManager::new(file.path()).unwrap();
+    assert_eq!(reopened.get_face_scan_checkpoint().unwrap(), Some(checkpoint));
+    reopened.clear_face_scan_checkpoint().unwrap();
+    assert_eq!(reopened.get_face_scan_checkpoint().unwrap(), None);
+}
+
+#[test]
+fn test_ignore_faces_below_confidence() {
+    let file = NamedTempFile::new().unwrap();
+    let cache = CacheManager::new(file.path()).unwrap();
+    cache.insert_media_item(&sample_item("1")).unwrap();
+    cache
+        .insert_faces(
+            "1",
+            r#"[{"bbox":[0,0,10,10],"name":null,"confidence":0.9},
+                {"bbox":[0,0,10,10],"name":null,"confidence":0.6},
+                {"bbox":[0,0,10,10],"name":"Ann","confidence":0.6},
+                {"bbox":[0,0,10,10],"name":null}]"#,
+        )
+        .unwrap();
+    cache.set_face_embeddings("1", &vec![embedding(3, 4, 0.1); 4]).unwrap();
+    cache.cluster_unnamed_faces(0.9).unwrap();
+    assert!(cache.face_cluster("1", 1).unwrap().is_some());
+
+    assert_eq!(cache.ignore_faces_below_confidence(0.7).unwrap(), 1);
+    let faces = cache.get_faces("1").unwrap().unwrap();
+    assert_eq!(faces.iter().map(|f| f.ignored).collect::<Vec<_>>(), vec![false, true, false, false]);
+    assert_eq!(faces[0].confidence, Some(0.9));
+    assert_eq!(cache.face_cluster("1", 1).unwrap(), None);
+    // Already ignored faces are not counted again
+    assert_eq!(cache.ignore_faces_below_confidence(0.7).unwrap(), 0);
+}
+
+#[test]
+fn test_trash_hides_items_and_restore_brings_them_back() {
+    let file = NamedTempFile::new().unwrap();
+    let cm = CacheManager::new(file.path()).unwrap();
+    cm.insert_media_item(&sample_item("1")).unwrap();
+    cm.insert_media_item(&sample_item("2")).unwrap();
+    let album = api_client::Album {
+        id: "a1".into(),
+        title: Some("Album".into()),
+        product_url: None,
+        is_writeable: None,
+        media_items_count: Some("1".into()),
+        cover_photo_base_url: None,
+        cover_photo_media_item_id: None,
+    };
+    cm.insert_album(&album).unwrap();
+    cm.associate_media_item_with_album("1", "a1").unwrap();
+
+    cm.trash_media_items(&["1".to_string()]).unwrap();
+    let ids: Vec<String> = cm.get_all_media_items().unwrap().into_iter().map(|i| i.id).collect();
+    assert_eq!(ids, vec!["2".to_string()]);
+    assert!(cm.get_media_items_by_album("a1").unwrap().is_empty());
+    assert!(cm.get_media_items_by_text("\"1.jpg\"", None).unwrap().is_empty());
+    assert_eq!(cm.count_media_items().unwrap(), 1);
+    let trashed = cm.get_trashed_media_items().unwrap();
+    assert_eq!(trashed.len(), 1);
+    assert_eq!(trashed[0].item.id, "1");
+
+
+    cm.restore_media_items(&["1".to_string()]).unwrap();
+    assert!(cm.get_trashed_media_items().unwrap().is_empty());
+    let in_album: Vec<String> = cm.get_media_items_by_album("a1").unwrap().into_iter().map(|i| i.id).collect();
+    assert_eq!(in_album, vec!["1".to_string()]);
+    assert_eq!(cm.get_media_items_by_text("\"1.jpg\"", None).unwrap().len(), 1);
+}
+
+#[test]
+fn test_purge_and_empty_trash() {
+    let file = NamedTempFile::new().unwrap();
+    let cm = CacheManager::new(file.path()).unwrap();
+    for id in ["1", "2", "3"] {
+        cm.insert_media_item(&sample_item(id)).unwrap();
+    }
+    cm.trash_media_items(&["1".to_string(), "2".to_string()]).unwrap();
+    // A sync re-inserting an item keeps it in the trash
+    cm.insert_media_item(&sample_item("2")).unwrap();
+    assert_eq!(cm.get_trashed_media_items().unwrap().len(), 2);
+
+    // Items outside the trash are not purged
+    assert_eq!(cm.purge_media_items(&["1".to_string(), "3".to_string()]).unwrap(), 1);
+    assert!(cm.get_media_item("1").unwrap().is_none());
+    assert!(cm.get_media_item("3").unwrap().is_some());
+
+    assert_eq!(cm.empty_trash().unwrap(), 1);
+    assert!(cm.get_trashed_media_items().unwrap().is_empty());
+    let ids: Vec<String> = cm.get_all_media_items().unwrap().into_iter().map(|i| i.id).collect();
+    assert_eq!(ids, vec!["3".to_string()]);
+    assert!(cm.check_integrity().unwrap().is_ok());
+}
+
+#[test]
+fn test_query_by_person_combines_with_album_and_favorites() {
+    let file = NamedTempFile::new().unwrap();
+    let cm = CacheManager::new(file.path()).unwrap();
+    for id in ["1", "2", "3"] {
+        cm.insert_media_item(&sample_item(id)).unwrap();
+    }
+    cm.insert_faces("1", r#"[{"bbox":[0,0,1,1],"name":"Anna"}]"#).unwrap();
+    cm.insert_faces("2", r#"[{"bbox":[0,0,1,1],"name":"Anna","ignored":true},{"bbox":[2,2,1,1],"name":"Bob"}]"#)
+        .unwrap();
+    cm.insert_faces("3", r#"[{"bbox":[0,0,1,1],"name":"Anna"}]"#).unwrap();
+    let album = api_client::Album {
+        id: "a1".into(),
+        title: Some("Vacation".into()),
+        product_url: None,
+        is_writeable: None,
+        media_items_count: None,
+        cover_photo_base_url: None,
+        cover_photo_media_item_id: None,
+    };
+    cm.insert_album(&album).unwrap();
+    cm.associate_media_item_with_album("1", "a1").unwrap();
+    cm.associate_media_item_with_album("2", "a1").unwrap();
+    cm.set_favorite("3", true).unwrap();
+
+    let ids = |q: MediaQuery| -> HashSet<String> { cm.query(&q).unwrap().into_iter().map(|i| i.id).collect() };
+    // Ignored faces do not count
+    assert_eq!(ids(MediaQuery::new().person("Anna")), HashSet::from(["1".to_string(), "3".to_string()]));
+    assert_eq!(ids(MediaQuery::new().person("Anna").album("a1")), HashSet::from(["1".to_string()]));
+    assert_eq!(ids(MediaQuery::new().person("Anna").favorite(true)), HashSet::from(["3".to_string()]));
+    assert!(ids(MediaQuery::new().person("Bob").album("a1").favorite(true)).is_empty());
+}
+
+#[test]
+fn test_query_by_detected_faces() {
+    let file = NamedTempFile::new().unwrap();
+    let cm = CacheManager::new(file.path()).unwrap();
+    for id in ["1", "2", "3"] {
+        cm.insert_media_item(&sample_item(id)).unwrap();
+    }
+    cm.insert_faces("1", r#"[{"bbox":[0,0,1,1],"name":null,"ignored":true}]"#).unwrap();
+    // Detection ran and found nothing
+    cm.insert_faces("2", "[]").unwrap();
+    cm.set_favorite("1", true).unwrap();
+
+    let ids = |q: MediaQuery| -> Vec<String> { cm.query(&q).unwrap().into_iter().map(|i| i.id).collect() };
+    assert_eq!(ids(MediaQuery::new().faces(true)), vec!["1".to_string()]);
+    let mut without = ids(MediaQuery::new().faces(false));
+    without.sort();
+    assert_eq!(without, vec!["2".to_string(), "3".to_string()]);
+    assert!(ids(MediaQuery::new().faces(true).favorite(false)).is_empty());
+}
+
+#[test]
+fn test_albums_for_media_item_and_export_album_ids() {
+    let file = NamedTempFile::new().unwrap();
+    let cm = CacheManager::new(file.path()).unwrap();
+    cm.insert_media_item(&sample_item("1")).unwrap();
+    cm.insert_media_item(&sample_item("2")).unwrap();
+    for (id, title) in [("a1", "Beach"), ("a2", "Alps")] {
+        cm.insert_album(&api_client::Album {
+            id: id.into(),
+            title: Some(title.into()),
+            product_url: None,
+            is_writeable: None,
+            media_items_count: None,
+            cover_photo_base_url: None,
+            cover_photo_media_item_id: None,
+        })
+        .unwrap();
+        cm.associate_media_item_with_album("1", id).unwrap();
+    }
+    let titles: Vec<_> = cm
+        .get_albums_for_media_item("1")
+        .unwrap()
+        .into_iter()
+        .filter_map(|a| a.title)
+        .collect();
+    assert_eq!(titles, vec!["Alps", "Beach"]);
+    assert!(cm.get_albums_for_media_item("2").unwrap().is_empty());
+
+    let export_file = NamedTempFile::new().unwrap();
+    cm.export_media_items(export_file.path()).unwrap();
+    let json: serde_json::Value = serde_json::from_reader(std::fs::File::open(export_file.path()).unwrap()).unwrap();
+    let entries = json.as_array().unwrap();
+    let first = entries.iter().find(|e| e["id"] == "1").unwrap();
+    let mut ids: Vec<_> = first["album_ids"].as_array().unwrap().iter().map(|v| v.as_str().unwrap()).collect();
+    ids.sort();
+    assert_eq!(ids, vec!["a1", "a2"]);
+    assert!(entries.iter().find(|e| e["id"] == "2").unwrap().get("album_ids").is_none());
+
+    // Importing puts the links back for albums the cache knows
+    cm.delete_media_item("1").unwrap();
+    cm.import_media_items(export_file.path()).unwrap();
+    assert_eq!(cm.get_albums_for_media_item("1").unwrap().len(), 2);
+}
+
+#[test]
+fn test_explain_albums_for_media_item_uses_index() {
+    let file = NamedTempFile::new().unwrap();
+    let _ = CacheManager::new(file.path()).unwrap();
+    let conn = Connection::open(file.path()).unwrap();
+    let mut stmt = conn
+        .prepare(&format!("EXPLAIN QUERY PLAN {}", cache::ALBUMS_FOR_MEDIA_ITEM_SQL))
+        .unwrap();
+    let plan: Vec<String> = stmt
+        .query_map(["1"], |row| row.get(3))
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert!(
+        plan.iter().any(|p| p.contains("idx_album_media_items_media_item_id")),
+        "plan was {:?}",
+        plan
+    );
+}
+
+#[test]
+fn test_photos_read_back_without_video_metadata() {
+    let file = NamedTempFile::new().unwrap();
+    let cache = CacheManager::new(file.path()).unwrap();
+    let api_json = r#"[
+        {"id": "p1", "productUrl": "http://example.com/p1", "baseUrl": "http://example.com/p1/base",
+         "mimeType": "image/jpeg", "filename": "p1.jpg",
+         "mediaMetadata": {"creationTime": "2023-01-01T00:00:00Z", "width": "4000", "height": "3000"}},
+        {"id": "v1", "productUrl": "http://example.com/v1", "baseUrl": "http://example.com/v1/base",
+         "mimeType": "video/mp4", "filename": "v1.mp4",
+         "mediaMetadata": {"creationTime": "2023-01-02T00:00:00Z", "width": "1920", "height": "1080",
+                           "video": {"cameraMake": "Pixel", "fps": 30.0, "status": "READY"}}}
+    ]"#;
+    let items: Vec<MediaItem> = serde_json::from_str(api_json).unwrap();
+    cache.insert_media_items_batch(&items).unwrap();
+    // A video cached before its metadata was known still counts as one
+    let mut bare_video = sample_item("v2");
+    bare_video.mime_type = "video/mp4".into();
+    cache.insert_media_item(&bare_video).unwrap();
+
+    assert!(cache.get_media_item("p1").unwrap().unwrap().media_metadata.video.is_none());
+    let video = cache.get_media_item("v1").unwrap().unwrap().media_metadata.video.unwrap();
+    assert_eq!(video.camera_make.as_deref(), Some("Pixel"));
+    assert_eq!(video.status.as_deref(), Some("READY"));
+    assert!(cache.get_media_item("v2").unwrap().unwrap().media_metadata.video.is_some());
+    let all = cache.get_all_media_items().unwrap();
+    assert!(all.iter().find(|i| i.id == "p1").unwrap().media_metadata.video.is_none());
+
+    let export_file = NamedTempFile::new().unwrap();
+    cache.export_media_items(export_file.path()).unwrap();
+    let exported: Vec<serde_json::Value> =
+        serde_json::from_str(&std::fs::read_to_string(export_file.path()).unwrap()).unwrap();
+    let original: Vec<serde_json::Value> = serde_json::from_str(api_json).unwrap();
+    let entry = |list: &[serde_json::Value], id: &str| list.iter().find(|e| e["id"] == id).unwrap().clone();
+    let keys = |value: &serde_json::Value| value.as_object().unwrap().keys().cloned().collect::<Vec<_>>();
+    assert_eq!(
+        keys(&entry(&exported, "p1")["mediaMetadata"]),
+        keys(&entry(&original, "p1")["mediaMetadata"])
+    );
+    assert_eq!(entry(&exported, "v1")["mediaMetadata"]["video"]["fps"], 30.0);
+}
+
+#[test]
+fn test_open_with_progress_reports_pending_migrations() {
+    let file = NamedTempFile::new().unwrap();
+    let mut steps = Vec::new();
+    let cache = CacheManager::open_with_progress(file.path(), |step, total| steps.push((step, total))).unwrap();
+    assert_eq!(cache.schema_version().unwrap(), cache::SCHEMA_VERSION);
+    let total = steps.len();
+    assert!(total > 1);
+    assert_eq!(steps, (1..=total).map(|step| (step, total)).collect::<Vec<_>>());
+    drop(cache);
+
+    // An up-to-date database has nothing to report
+    let mut calls = 0;
+    CacheManager::open_with_progress(file.path(), |_, _| calls += 1).unwrap();
+    assert_eq!(calls, 0);
+}
+
+#[test]
+fn test_creation_time_keeps_its_utc_offset() {
+    let file = NamedTempFile::new().unwrap();
+    let cache = CacheManager::new(file.path()).unwrap();
+    let mut tokyo = sample_item("tokyo");
+    tokyo.media_metadata.creation_time = "2023-05-01T08:30:00+09:00".into();
+    let mut denver = sample_item("denver");
+    denver.media_metadata.creation_time = "2023-04-30T18:30:00-07:00".into();
+    cache.insert_media_item(&tokyo).unwrap();
+    cache.upsert_media_items_batch(&[denver.clone()]).unwrap();
+
+    let read = cache.get_media_item("tokyo").unwrap().unwrap();
+    assert_eq!(read.media_metadata.creation_time, "2023-05-01T08:30:00+09:00");
+    let read = cache.get_media_item("denver").unwrap().unwrap();
+    assert_eq!(read.media_metadata.creation_time, "2023-04-30T18:30:00-07:00");
+    let all = cache.query(&MediaQuery::new().sort(MediaSort::OldestFirst)).unwrap();
+    let times: Vec<_> = all.iter().map(|i| i.media_metadata.creation_time.as_str()).collect();
+    assert_eq!(times, vec!["2023-05-01T08:30:00+09:00", "2023-04-30T18:30:00-07:00"]);
+
+    // Same content again is not a change
+    let stats = cache.upsert_media_items_batch(&[denver]).unwrap();
+    assert_eq!(stats.unchanged, 1);
+}
+
+#[test]
+fn test_local_time_mode_applies_the_stored_offset() {
+    let file = NamedTempFile::new().unwrap();
+    let cache = CacheManager::new(file.path()).unwrap();
+    // 2023-04-30T23:30:00Z, taken on the morning of May 1st in Tokyo
+    let mut tokyo = sample_item("tokyo");
+    tokyo.media_metadata.creation_time = "2023-05-01T08:30:00+09:00".into();
+    // 2023-05-01T01:30:00Z, taken on the evening of April 30th in Denver
+    let mut denver = sample_item("denver");
+    denver.media_metadata.creation_time = "2023-04-30T18:30:00-07:00".into();
+    cache.insert_media_items_batch(&[tokyo, denver]).unwrap();
+
+    let utc_days = cache.count_media_items_by_period(TimeBucket::Day, TimeMode::Utc).unwrap();
+    assert_eq!(utc_days, vec![("2023-04-30".to_string(), 1), ("2023-05-01".to_string(), 1)]);
+    let local_days = cache.count_media_items_by_period(TimeBucket::Day, TimeMode::Local).unwrap();
+    assert_eq!(local_days, utc_days);
+
+    let start = Utc.with_ymd_and_hms(2023, 5, 1, 0, 0, 0).unwrap();
+    let end = Utc.with_ymd_and_hms(2023, 5, 1, 23, 59, 59).unwrap();
+    let ids = |items: Vec<MediaItem>| items.into_iter().map(|i| i.id).collect::<Vec<_>>();
+    assert_eq!(ids(cache.get_media_items_by_date_range(start, end, TimeMode::Utc).unwrap()), vec!["denver"]);
+    assert_eq!(ids(cache.get_media_items_by_date_range(start, end, TimeMode::Local).unwrap()), vec!["tokyo"]);
+
+    let local = MediaQuery::new().start(start).end(end).time_mode(TimeMode::Local);
+    assert_eq!(ids(cache.query(&local).unwrap()), vec!["tokyo"]);
+    assert_eq!(ids(cache.query(&MediaQuery::new().start(start).end(end)).unwrap()), vec!["denver"]);
+}
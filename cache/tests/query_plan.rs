@@ -0,0 +1,83 @@
+//! `EXPLAIN QUERY PLAN` checks that the common `MediaQuery` filters are
+//! answered from an index rather than a scan of every cached item.
+#![cfg(debug_assertions)]
+
+use cache::{CacheManager, MediaQuery, MediaSort};
+use chrono::{TimeZone, Utc};
+use tempfile::NamedTempFile;
+
+fn cache() -> (NamedTempFile, CacheManager) {
+    let file = NamedTempFile::new().unwrap();
+    let cache = CacheManager::new(file.path()).unwrap();
+    (file, cache)
+}
+
+fn year() -> MediaQuery {
+    MediaQuery::new()
+        .start(Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap())
+        .end(Utc.with_ymd_and_hms(2023, 12, 31, 23, 59, 59).unwrap())
+}
+
+/// Fails unless a step of `plan` searches or scans through `index`.
+fn assert_uses_index(plan: &str, index: &str) {
+    let uses = plan.lines().any(|step| {
+        let step = step.trim_start();
+        (step.starts_with("SEARCH") || step.starts_with("SCAN"))
+            && (step.contains(&format!("USING INDEX {index}"))
+                || step.contains(&format!("USING COVERING INDEX {index}")))
+    });
+    assert!(uses, "expected the plan to use {index}:\n{plan}");
+}
+
+#[test]
+fn date_range_uses_creation_time_index() {
+    let (_file, cache) = cache();
+    let plan = cache.explain(&year()).unwrap();
+    assert_uses_index(&plan, "idx_media_metadata_creation_time");
+    assert!(plan.contains("creation_time>? AND creation_time<?"), "{plan}");
+
+    let plan = cache.explain(&year().sort(MediaSort::Filename)).unwrap();
+    assert_uses_index(&plan, "idx_media_metadata_creation_time");
+}
+
+#[test]
+fn unfiltered_listing_walks_creation_time_index() {
+    let (_file, cache) = cache();
+    let plan = cache.explain(&MediaQuery::new()).unwrap();
+    assert_uses_index(&plan, "idx_media_metadata_creation_time");
+}
+
+#[test]
+fn equality_filters_use_their_indexes() {
+    let (_file, cache) = cache();
+    let cases = [
+        (MediaQuery::new().camera_model("EOS"), "idx_media_metadata_camera_model"),
+        (MediaQuery::new().camera_make("Canon"), "idx_media_metadata_camera_make"),
+        (MediaQuery::new().mime_type("video/mp4"), "idx_media_items_mime_type"),
+        (MediaQuery::new().favorite(true), "idx_media_items_is_favorite"),
+        (MediaQuery::new().favorite(true).text("beach"), "idx_media_items_is_favorite"),
+        (year().camera_model("EOS"), "idx_media_metadata_camera_model"),
+    ];
+    for (query, index) in cases {
+        assert_uses_index(&cache.explain(&query).unwrap(), index);
+    }
+}
+
+#[test]
+fn album_filter_looks_up_album_members() {
+    let (_file, cache) = cache();
+    for query in [MediaQuery::new().album("a1"), year().album("a1")] {
+        let plan = cache.explain(&query).unwrap();
+        assert_uses_index(&plan, "sqlite_autoindex_album_media_items_1");
+        assert_uses_index(&plan, "sqlite_autoindex_media_items_1");
+    }
+}
+
+#[test]
+fn deleted_at_index_is_left_to_the_trash() {
+    let (_file, cache) = cache();
+    for query in [MediaQuery::new(), year(), MediaQuery::new().album("a1")] {
+        let plan = cache.explain(&query).unwrap();
+        assert!(!plan.contains("idx_media_items_deleted_at"), "{plan}");
+    }
+}
@@ -2,15 +2,20 @@
 #![warn(rust_2018_idioms)]
 //! Face recognition module for GooglePicz.
 //!
-//! Faces are detected using OpenCV's Haar cascade classifier. Detected
-//! bounding boxes can be cached and presented in the UI.
+//! Faces are detected with one of two backends chosen at runtime through
+//! [`DetectorConfig`]: OpenCV's Haar cascade classifier (`haar` feature) or an
+//! ONNX face detection model such as YuNet run by OpenCV's DNN module (`onnx`
+//! feature). Detected bounding boxes can be cached and presented in the UI.
+//! When an ONNX face embedding model is installed, each face also gets an
+//! embedding so unnamed faces of the same person can be clustered.
 
 use api_client::MediaItem;
 #[cfg(feature = "cache")]
 use cache::CacheManager;
-use opencv::{core, imgcodecs, imgproc, objdetect, prelude::*};
+use opencv::{core, dnn, imgcodecs, imgproc, objdetect, prelude::*};
 use reqwest::blocking as reqwest_blocking;
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 use thiserror::Error;
 
 /// Details about a detected face.
@@ -20,30 +25,110 @@ pub struct Face {
     pub bbox: [i32; 4],
     /// Optional name of the person.
     pub name: Option<String>,
+    /// Set for faces too small to matter or hidden by the user.
+    #[serde(default)]
+    pub ignored: bool,
+    /// Detection score from 0 to 1; missing for faces detected by older versions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f32>,
     pub rect: (u32, u32, u32, u32),
 }
 
+/// Model used to find faces.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DetectorBackend {
+    /// OpenCV's Haar cascade classifier, built with the `haar` feature.
+    #[default]
+    Haar,
+    /// An ONNX model with YuNet's output format, built with the `onnx` feature.
+    Onnx,
+}
+
+/// Detections scoring below this are dropped by default.
+pub const DEFAULT_CONFIDENCE_THRESHOLD: f32 = 0.5;
+/// Most faces kept per photo by default.
+pub const DEFAULT_MAX_FACES: usize = 50;
+/// Longest side photos are scaled down to before detection by default.
+pub const DEFAULT_DETECTOR_INPUT_SIZE: u32 = 640;
+
+/// How `FaceRecognizer` finds faces.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DetectorConfig {
+    pub backend: DetectorBackend,
+    /// Model file of the backend; the default locations are searched when unset.
+    pub model_path: Option<PathBuf>,
+    /// Detections scoring below this (0 to 1) are dropped.
+    pub confidence_threshold: f32,
+    /// Most faces kept per photo, highest scores first; 0 keeps all.
+    pub max_faces: usize,
+    /// Longest side photos are scaled down to before detection; 0 keeps the full size.
+    pub input_size: u32,
+}
+
+impl Default for DetectorConfig {
+    fn default() -> Self {
+        Self {
+            backend: DetectorBackend::default(),
+            model_path: None,
+            confidence_threshold: DEFAULT_CONFIDENCE_THRESHOLD,
+            max_faces: DEFAULT_MAX_FACES,
+            input_size: DEFAULT_DETECTOR_INPUT_SIZE,
+        }
+    }
+}
+
 #[derive(Debug, Error)]
 pub enum FaceRecognitionError {
     #[error("Cache Error: {0}")]
     CacheError(String),
     #[error("Cascade model not found: {0}")]
     ModelNotFound(String),
+    #[error("Face detector backend {0:?} is not built in")]
+    BackendUnavailable(DetectorBackend),
     #[error("Other Error: {0}")]
     Other(String),
 }
 
+/// Side length of the face crops fed to the embedding model.
+const EMBEDDING_INPUT_SIZE: i32 = 112;
+
 /// Main struct providing face detection capabilities.
 #[derive(Default)]
-pub struct FaceRecognizer;
+pub struct FaceRecognizer {
+    config: DetectorConfig,
+    min_face_size: f32,
+}
 
 impl FaceRecognizer {
-    /// Create a new face recognizer instance.
-    pub fn new() -> Self {
-        Self::default()
+    /// Create a face recognizer detecting faces as described by `config`.
+    pub fn new(config: DetectorConfig) -> Self {
+        Self { config, min_face_size: 0.0 }
+    }
+
+    /// Mark faces covering less than `fraction` of the image area as ignored.
+    pub fn with_min_face_size(mut self, fraction: f32) -> Self {
+        self.min_face_size = fraction;
+        self
     }
 
-    fn find_model_path() -> Result<String, FaceRecognitionError> {
+    /// `config.model_path` if set, otherwise the first default location of the backend.
+    fn find_model_path(&self) -> Result<String, FaceRecognitionError> {
+        if let Some(p) = &self.config.model_path {
+            if p.exists() {
+                return Ok(p.display().to_string());
+            } else {
+                return Err(FaceRecognitionError::ModelNotFound(p.display().to_string()));
+            }
+        }
+        match self.config.backend {
+            DetectorBackend::Haar => Self::find_cascade_path(),
+            DetectorBackend::Onnx => Self::find_detection_model_path(),
+        }
+    }
+
+    fn find_cascade_path() -> Result<String, FaceRecognitionError> {
         if let Ok(p) = std::env::var("OPENCV_HAARCASCADE_PATH") {
             if std::path::Path::new(&p).exists() {
                 return Ok(p);
@@ -65,15 +150,57 @@ impl FaceRecognizer {
         Err(FaceRecognitionError::ModelNotFound(defaults.join(", ")))
     }
 
-    /// Detect faces in the given media item.
-    #[allow(clippy::too_many_lines)]
-    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self, item)))]
-    pub fn detect_faces(&self, item: &MediaItem) -> Result<Vec<Face>, FaceRecognitionError> {
-        let bytes = if item.base_url.starts_with("file://") {
-            let path = item.base_url.trim_start_matches("file://");
+    /// ONNX face detection model with YuNet's output format.
+    fn find_detection_model_path() -> Result<String, FaceRecognitionError> {
+        if let Ok(p) = std::env::var("GOOGLEPICZ_FACE_DETECTION_MODEL") {
+            if std::path::Path::new(&p).exists() {
+                return Ok(p);
+            } else {
+                return Err(FaceRecognitionError::ModelNotFound(p));
+            }
+        }
+
+        let defaults = [
+            "/usr/share/googlepicz/face_detection.onnx",
+            "/usr/local/share/googlepicz/face_detection.onnx",
+        ];
+        for p in &defaults {
+            if std::path::Path::new(p).exists() {
+                return Ok((*p).into());
+            }
+        }
+        Err(FaceRecognitionError::ModelNotFound(defaults.join(", ")))
+    }
+
+    /// ONNX model turning a face crop into a 128 value embedding, such as
+    /// OpenCV's SFace model.
+    fn find_embedding_model_path() -> Result<String, FaceRecognitionError> {
+        if let Ok(p) = std::env::var("GOOGLEPICZ_FACE_EMBEDDING_MODEL") {
+            if std::path::Path::new(&p).exists() {
+                return Ok(p);
+            } else {
+                return Err(FaceRecognitionError::ModelNotFound(p));
+            }
+        }
+
+        let defaults = [
+            "/usr/share/googlepicz/face_embedding.onnx",
+            "/usr/local/share/googlepicz/face_embedding.onnx",
+        ];
+        for p in &defaults {
+            if std::path::Path::new(p).exists() {
+                return Ok((*p).into());
+            }
+        }
+        Err(FaceRecognitionError::ModelNotFound(defaults.join(", ")))
+    }
+
+    fn load_image(base_url: &str) -> Result<Mat, FaceRecognitionError> {
+        let bytes = if base_url.starts_with("file://") {
+            let path = base_url.trim_start_matches("file://");
             std::fs::read(path).map_err(|e| FaceRecognitionError::Other(e.to_string()))?
         } else {
-            let url = format!("{}=d", item.base_url);
+            let url = format!("{}=d", base_url);
             let resp = reqwest_blocking::get(&url)
                 .map_err(|e| FaceRecognitionError::Other(e.to_string()))?;
             resp.bytes()
@@ -82,45 +209,210 @@ impl FaceRecognizer {
         };
 
         let data = core::Vector::from_slice(&bytes);
-        let img = imgcodecs::imdecode(&data, imgcodecs::IMREAD_COLOR)
-            .map_err(|e| FaceRecognitionError::Other(e.to_string()))?;
+        imgcodecs::imdecode(&data, imgcodecs::IMREAD_COLOR).map_err(|e| FaceRecognitionError::Other(e.to_string()))
+    }
+
+    /// Detect faces in the given media item.
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self, item)))]
+    pub fn detect_faces(&self, item: &MediaItem) -> Result<Vec<Face>, FaceRecognitionError> {
+        let img = Self::load_image(&item.base_url)?;
+        self.detect_in_image(&img)
+    }
+
+    /// Faces in `img` scoring at least `confidence_threshold`, highest scores
+    /// first and at most `max_faces` of them.
+    fn detect_in_image(&self, img: &Mat) -> Result<Vec<Face>, FaceRecognitionError> {
+        let other = |e: opencv::Error| FaceRecognitionError::Other(e.to_string());
+        let longest = img.cols().max(img.rows());
+        let input_size = self.config.input_size as i32;
+        let mut small = Mat::default();
+        let (scaled, scale) = if input_size > 0 && longest > input_size {
+            let factor = input_size as f64 / longest as f64;
+            imgproc::resize(img, &mut small, core::Size::new(0, 0), factor, factor, imgproc::INTER_AREA)
+                .map_err(other)?;
+            (&small, 1.0 / factor)
+        } else {
+            (img, 1.0)
+        };
+
+        let mut detections = match self.config.backend {
+            #[cfg(feature = "haar")]
+            DetectorBackend::Haar => self.detect_haar(scaled)?,
+            #[cfg(feature = "onnx")]
+            DetectorBackend::Onnx => self.detect_onnx(scaled)?,
+            #[allow(unreachable_patterns)]
+            backend => return Err(FaceRecognitionError::BackendUnavailable(backend)),
+        };
+        detections.retain(|(_, confidence)| *confidence >= self.config.confidence_threshold);
+        detections.sort_by(|a, b| b.1.total_cmp(&a.1));
+        if self.config.max_faces > 0 {
+            detections.truncate(self.config.max_faces);
+        }
+
+        let min_area = self.min_face_size as f64 * img.cols() as f64 * img.rows() as f64;
+        let faces = detections
+            .into_iter()
+            .map(|(r, confidence)| {
+                let [x, y, w, h] = [r.x, r.y, r.width, r.height].map(|v| (v as f64 * scale).round() as i32);
+                Face {
+                    bbox: [x, y, w, h],
+                    name: None,
+                    ignored: (w as f64 * h as f64) < min_area,
+                    confidence: Some(confidence),
+                    rect: (x.max(0) as u32, y.max(0) as u32, w as u32, h as u32),
+                }
+            })
+            .collect();
+        Ok(faces)
+    }
+
+    /// Haar cascade detections; the stage weights are mapped to 0..1 with a
+    /// logistic function so they compare with the ONNX scores.
+    #[cfg(feature = "haar")]
+    fn detect_haar(&self, img: &Mat) -> Result<Vec<(core::Rect, f32)>, FaceRecognitionError> {
+        let other = |e: opencv::Error| FaceRecognitionError::Other(e.to_string());
         let mut gray = Mat::default();
-        imgproc::cvt_color(&img, &mut gray, imgproc::COLOR_BGR2GRAY, 0)
-            .map_err(|e| FaceRecognitionError::Other(e.to_string()))?;
+        imgproc::cvt_color(img, &mut gray, imgproc::COLOR_BGR2GRAY, 0).map_err(other)?;
 
-        let cascade_path = Self::find_model_path()?;
-        let mut classifier = objdetect::CascadeClassifier::new(&cascade_path)
-            .map_err(|e| FaceRecognitionError::Other(e.to_string()))?;
+        let cascade_path = self.find_model_path()?;
+        let mut classifier = objdetect::CascadeClassifier::new(&cascade_path).map_err(other)?;
         let mut rects = core::Vector::<core::Rect>::new();
+        let mut levels = core::Vector::<i32>::new();
+        let mut weights = core::Vector::<f64>::new();
         classifier
-            .detect_multi_scale(
+            .detect_multi_scale3(
                 &gray,
                 &mut rects,
+                &mut levels,
+                &mut weights,
                 1.1,
                 3,
                 0,
                 core::Size::new(30, 30),
                 core::Size::new(0, 0),
+                true,
             )
-            .map_err(|e| FaceRecognitionError::Other(e.to_string()))?;
+            .map_err(other)?;
+        Ok(rects
+            .into_iter()
+            .zip(weights)
+            .map(|(r, weight)| (r, (1.0 / (1.0 + (-weight).exp())) as f32))
+            .collect())
+    }
+
+    /// Detections of an ONNX model with YuNet's output: one row per face
+    /// holding the box, five landmarks and the score.
+    #[cfg(feature = "onnx")]
+    fn detect_onnx(&self, img: &Mat) -> Result<Vec<(core::Rect, f32)>, FaceRecognitionError> {
+        let other = |e: opencv::Error| FaceRecognitionError::Other(e.to_string());
+        let model_path = self.find_model_path()?;
+        let mut detector = objdetect::FaceDetectorYN::create(
+            &model_path,
+            "",
+            core::Size::new(img.cols(), img.rows()),
+            self.config.confidence_threshold,
+            0.3,
+            5000,
+            dnn::DNN_BACKEND_OPENCV,
+            dnn::DNN_TARGET_CPU,
+        )
+        .map_err(other)?;
+        let mut faces = Mat::default();
+        detector.detect(img, &mut faces).map_err(other)?;
+        (0..faces.rows())
+            .map(|row| {
+                let at = |col| faces.at_2d::<f32>(row, col).copied().map_err(other);
+                let rect = core::Rect::new(at(0)? as i32, at(1)? as i32, at(2)? as i32, at(3)? as i32);
+                Ok((rect, at(14)?))
+            })
+            .collect()
+    }
+
+    /// Compute an embedding for each of `faces` in the given media item.
+    /// Fails with `ModelNotFound` when no embedding model is installed.
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self, item, faces)))]
+    pub fn embed_faces(&self, item: &MediaItem, faces: &[Face]) -> Result<Vec<Vec<f32>>, FaceRecognitionError> {
+        let img = Self::load_image(&item.base_url)?;
+        self.embed_in_image(&img, faces)
+    }
+
+    fn embed_in_image(&self, img: &Mat, faces: &[Face]) -> Result<Vec<Vec<f32>>, FaceRecognitionError> {
+        if faces.is_empty() {
+            return Ok(Vec::new());
+        }
+        let other = |e: opencv::Error| FaceRecognitionError::Other(e.to_string());
+        let model_path = Self::find_embedding_model_path()?;
+        let mut net = dnn::read_net_from_onnx(&model_path).map_err(other)?;
+        faces
+            .iter()
+            .map(|face| {
+                // Boxes may reach past the edge of the image
+                let [x, y, w, h] = face.bbox;
+                let (x0, y0) = (x.clamp(0, img.cols()), y.clamp(0, img.rows()));
+                let (x1, y1) = ((x + w).clamp(0, img.cols()), (y + h).clamp(0, img.rows()));
+                if x1 <= x0 || y1 <= y0 {
+                    return Err(FaceRecognitionError::Other(format!("Face outside the image: {:?}", face.bbox)));
+                }
+                let crop = Mat::roi(img, core::Rect::new(x0, y0, x1 - x0, y1 - y0))
+                    .and_then(|roi| roi.try_clone())
+                    .map_err(other)?;
+                let blob = dnn::blob_from_image(
+                    &crop,
+                    1.0 / 127.5,
+                    core::Size::new(EMBEDDING_INPUT_SIZE, EMBEDDING_INPUT_SIZE),
+                    core::Scalar::all(127.5),
+                    true,
+                    false,
+                    core::CV_32F,
+                )
+                .map_err(other)?;
+                net.set_input(&blob, "", 1.0, core::Scalar::default()).map_err(other)?;
+                let output = net.forward_single("").map_err(other)?;
+                Ok(output.data_typed::<f32>().map_err(other)?.to_vec())
+            })
+            .collect()
+    }
 
-        let faces = rects
+    /// Detect and embed faces in an encoded, downscaled copy of a photo whose
+    /// full size is `original_width` pixels wide. Boxes are scaled back to the
+    /// full size; embeddings are `None` when no embedding model is installed.
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self, bytes)))]
+    pub fn detect_scaled(
+        &self,
+        bytes: &[u8],
+        original_width: u32,
+    ) -> Result<(Vec<Face>, Option<Vec<Vec<f32>>>), FaceRecognitionError> {
+        let data = core::Vector::from_slice(bytes);
+        let img = imgcodecs::imdecode(&data, imgcodecs::IMREAD_COLOR)
+            .map_err(|e| FaceRecognitionError::Other(e.to_string()))?;
+        if img.cols() == 0 {
+            return Err(FaceRecognitionError::Other("Could not decode image".into()));
+        }
+        let faces = self.detect_in_image(&img)?;
+        let embeddings = match self.embed_in_image(&img, &faces) {
+            Ok(embeddings) => Some(embeddings),
+            Err(FaceRecognitionError::ModelNotFound(_)) => None,
+            Err(e) => return Err(e),
+        };
+        let scale = if original_width > 0 { original_width as f32 / img.cols() as f32 } else { 1.0 };
+        let faces = faces
             .into_iter()
-            .map(|r| Face {
-                bbox: [r.x, r.y, r.width, r.height],
-                name: None,
-                rect: (
-                    r.x.max(0) as u32,
-                    r.y.max(0) as u32,
-                    r.width as u32,
-                    r.height as u32,
-                ),
+            .map(|f| {
+                let [x, y, w, h] = f.bbox.map(|v| (v as f32 * scale).round() as i32);
+                Face {
+                    bbox: [x, y, w, h],
+                    name: None,
+                    ignored: f.ignored,
+                    confidence: f.confidence,
+                    rect: (x.max(0) as u32, y.max(0) as u32, w as u32, h as u32),
+                }
             })
             .collect();
-        Ok(faces)
+        Ok((faces, embeddings))
     }
 
-    /// Detect faces and persist the bounding boxes in the cache.
+    /// Detect faces and persist the bounding boxes in the cache, along with
+    /// their embeddings if an embedding model is installed.
     #[cfg(feature = "cache")]
     #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self, cache, item)))]
     pub fn detect_and_cache_faces(
@@ -129,8 +421,33 @@ impl FaceRecognizer {
         item: &MediaItem,
         preserve_names: bool,
     ) -> Result<Vec<Face>, FaceRecognitionError> {
-        let faces = self.detect_faces(item)?;
-        self.assign_to_cache(cache, item, &faces, preserve_names)?;
+        self.detect_and_cache_faces_at(cache, &item.id, &item.base_url, preserve_names)
+    }
+
+    /// Like `detect_and_cache_faces`, for the media item `id` whose image is
+    /// at `base_url`.
+    #[cfg(feature = "cache")]
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self, cache, base_url)))]
+    pub fn detect_and_cache_faces_at(
+        &self,
+        cache: &CacheManager,
+        id: &str,
+        base_url: &str,
+        preserve_names: bool,
+    ) -> Result<Vec<Face>, FaceRecognitionError> {
+        let img = Self::load_image(base_url)?;
+        let faces = self.detect_in_image(&img)?;
+        self.store_faces(cache, id, &faces, preserve_names)?;
+        match self.embed_in_image(&img, &faces) {
+            Ok(embeddings) => cache
+                .set_face_embeddings(id, &embeddings)
+                .map_err(|e| FaceRecognitionError::CacheError(e.to_string()))?,
+            // Clustering needs the model, detection does not
+            Err(FaceRecognitionError::ModelNotFound(path)) => {
+                tracing::debug!("No face embedding model at {}, skipping embeddings", path);
+            }
+            Err(e) => return Err(e),
+        }
         Ok(faces)
     }
 
@@ -143,15 +460,27 @@ impl FaceRecognizer {
         item: &MediaItem,
         faces: &[Face],
         preserve_names: bool,
+    ) -> Result<(), FaceRecognitionError> {
+        self.store_faces(cache, &item.id, faces, preserve_names)
+    }
+
+    #[cfg(feature = "cache")]
+    fn store_faces(
+        &self,
+        cache: &CacheManager,
+        id: &str,
+        faces: &[Face],
+        preserve_names: bool,
     ) -> Result<(), FaceRecognitionError> {
         let mut to_store = faces.to_vec();
         if preserve_names {
-            if let Ok(Some(existing)) = cache.get_faces(&item.id) {
+            if let Ok(Some(existing)) = cache.get_faces(id) {
                 for (i, old) in existing.into_iter().enumerate() {
                     if let Some(f) = to_store.get_mut(i) {
                         if f.name.is_none() {
                             f.name = old.name;
                         }
+                        f.ignored |= old.ignored;
                     }
                 }
             }
@@ -159,7 +488,7 @@ impl FaceRecognizer {
         let json = serde_json::to_string(&to_store)
             .map_err(|e| FaceRecognitionError::Other(e.to_string()))?;
         cache
-            .insert_faces(&item.id, &json)
+            .insert_faces(id, &json)
             .map_err(|e| FaceRecognitionError::CacheError(e.to_string()))
     }
 
@@ -1,4 +1,4 @@
-use face_recognition::FaceRecognizer;
+use face_recognition::{DetectorBackend, DetectorConfig, FaceRecognitionError, FaceRecognizer};
 use base64::Engine;
 use api_client::{MediaItem, MediaMetadata};
 
@@ -13,10 +13,9 @@ fn prepare_sample_file() -> std::path::PathBuf {
     path
 }
 
-#[test]
-fn test_detect_faces() {
+fn sample_item() -> MediaItem {
     let path = prepare_sample_file();
-    let item = MediaItem {
+    MediaItem {
         id: "1".into(),
         description: None,
         product_url: String::new(),
@@ -29,9 +28,34 @@ fn test_detect_faces() {
             video: None,
         },
         filename: "sample.jpg".into(),
-    };
+        is_favorite: false,
+    }
+}
 
-    let rec = FaceRecognizer::new();
-    let faces = rec.detect_faces(&item).expect("detect");
+#[test]
+fn test_detect_faces() {
+    let rec = FaceRecognizer::new(DetectorConfig::default());
+    let faces = rec.detect_faces(&sample_item()).expect("detect");
     assert!(!faces.is_empty());
+    assert!(faces.iter().all(|f| f.confidence.unwrap() >= DetectorConfig::default().confidence_threshold));
+}
+
+#[test]
+fn test_confidence_threshold_and_max_faces() {
+    let item = sample_item();
+    let strict = FaceRecognizer::new(DetectorConfig { confidence_threshold: 1.01, ..Default::default() });
+    assert!(strict.detect_faces(&item).expect("detect").is_empty());
+
+    let one = FaceRecognizer::new(DetectorConfig { confidence_threshold: 0.0, max_faces: 1, ..Default::default() });
+    assert_eq!(one.detect_faces(&item).expect("detect").len(), 1);
+}
+
+#[cfg(not(feature = "onnx"))]
+#[test]
+fn test_backend_not_built_in() {
+    let rec = FaceRecognizer::new(DetectorConfig { backend: DetectorBackend::Onnx, ..Default::default() });
+    assert!(matches!(
+        rec.detect_faces(&sample_item()),
+        Err(FaceRecognitionError::BackendUnavailable(DetectorBackend::Onnx))
+    ));
 }
@@ -1,4 +1,4 @@
-use face_recognition::FaceRecognizer;
+use face_recognition::{DetectorConfig, FaceRecognizer};
 use base64::Engine;
 use api_client::{MediaItem, MediaMetadata};
 use cache::CacheManager;
@@ -31,13 +31,14 @@ fn test_detect_and_cache_roundtrip() {
             video: None,
         },
         filename: "sample.jpg".into(),
+        is_favorite: false,
     };
 
     let cache_file = tempfile::NamedTempFile::new().expect("tmpfile");
     let cache = CacheManager::new(cache_file.path()).expect("cache");
     cache.insert_media_item(&item).expect("insert item");
 
-    let rec = FaceRecognizer::new();
+    let rec = FaceRecognizer::new(DetectorConfig::default());
     let faces = rec
         .detect_and_cache_faces(&cache, &item, true)
         .expect("detect");
@@ -11,7 +11,7 @@
 use thiserror::Error;
 use std::fs;
 use std::process::Command;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use which::which;
 
 pub mod utils;
@@ -62,7 +62,11 @@ fn verify_tools() -> Result<(), PackagingError> {
         let format = std::env::var("LINUX_PACKAGE_FORMAT").unwrap_or_else(|_| "deb".into());
         match format.as_str() {
             "rpm" => tools.push(("cargo-rpm", hint("cargo-rpm", "cargo install cargo-rpm"))),
-            "appimage" => tools.push(("appimagetool", hint("appimagetool", "install appimagetool from your distribution"))),
+            "appimage" => {
+                tools.push(("appimagetool", hint("appimagetool", "install appimagetool from your distribution")));
+                tools.push(("zsyncmake", hint("zsyncmake", "install zsync from your distribution")));
+            }
+            "flatpak" => tools.push(("flatpak-builder", hint("flatpak-builder", "install flatpak-builder from your distribution"))),
             _ => tools.push(("cargo-deb", hint("cargo-deb", "cargo install cargo-deb"))),
         }
         if std::env::var("LINUX_SIGN_KEY").map(|v| !v.is_empty()).unwrap_or(false) {
@@ -73,14 +77,21 @@ fn verify_tools() -> Result<(), PackagingError> {
         tools.push(("codesign", hint("codesign", "install Xcode command line tools")));
         tools.push(("hdiutil", hint("hdiutil", "install Xcode command line tools")));
         tools.push(("xcrun", hint("xcrun", "install Xcode command line tools")));
+        if utils::is_universal() {
+            tools.push(("lipo", hint("lipo", "install Xcode command line tools")));
+        }
     } else if cfg!(target_os = "windows") {
-        tools.push(("makensis", hint("makensis", "install NSIS")));
+        let format = std::env::var("WINDOWS_PACKAGE_FORMAT").unwrap_or_else(|_| "nsis".into());
+        match format.as_str() {
+            "msi" => tools.push(("cargo-wix", hint("cargo-wix", "cargo install cargo-wix and install the WiX Toolset"))),
+            _ => tools.push(("makensis", hint("makensis", "install NSIS"))),
+        }
         tools.push(("signtool", hint("signtool", "install Windows SDK")));
     }
 
     for (cmd, msg) in tools {
         if !command_available(cmd) {
-            return Err(PackagingError::MissingCommand(msg.into()));
+            return Err(PackagingError::MissingCommand(msg));
         }
     }
 
@@ -97,20 +108,17 @@ fn run_command(cmd: &str, args: &[&str]) -> Result<(), PackagingError> {
     if cmd == "cargo" {
         if let Some(sub) = args.first() {
             match *sub {
-                "deb" => {
-                    if !command_available("cargo-deb") {
-                        return Err(PackagingError::MissingCommand(hint("cargo-deb", "cargo install cargo-deb")));
-                    }
+                "deb" if !command_available("cargo-deb") => {
+                    return Err(PackagingError::MissingCommand(hint("cargo-deb", "cargo install cargo-deb")));
                 }
-                "bundle" => {
-                    if !command_available("cargo-bundle") {
-                        return Err(PackagingError::MissingCommand(hint("cargo-bundle", "cargo install cargo-bundle")));
-                    }
+                "bundle" if !command_available("cargo-bundle") => {
+                    return Err(PackagingError::MissingCommand(hint("cargo-bundle", "cargo install cargo-bundle")));
                 }
-                "bundle-licenses" => {
-                    if !command_available("cargo-bundle-licenses") {
-                        return Err(PackagingError::MissingCommand(hint("cargo-bundle-licenses", "cargo install cargo-bundle-licenses")));
-                    }
+                "wix" if !command_available("cargo-wix") => {
+                    return Err(PackagingError::MissingCommand(hint("cargo-wix", "cargo install cargo-wix")));
+                }
+                "bundle-licenses" if !command_available("cargo-bundle-licenses") => {
+                    return Err(PackagingError::MissingCommand(hint("cargo-bundle-licenses", "cargo install cargo-bundle-licenses")));
                 }
                 _ => {}
             }
@@ -159,6 +167,18 @@ fn run_command(cmd: &str, args: &[&str]) -> Result<(), PackagingError> {
 
 use utils::{artifact_path, get_project_root, workspace_version};
 
+/// Slices merged into a macOS universal binary.
+const UNIVERSAL_ARCHS: [&str; 2] = ["x86_64", "aarch64"];
+
+/// `args` followed by `--target <triple>` when cross compiling.
+fn with_target<'a>(args: &[&'a str], triple: Option<&'a str>) -> Vec<&'a str> {
+    let mut args = args.to_vec();
+    if let Some(triple) = triple {
+        args.extend(["--target", triple]);
+    }
+    args
+}
+
 #[cfg_attr(feature = "trace-spans", tracing::instrument)]
 pub fn bundle_licenses() -> Result<(), PackagingError> {
     tracing::info!("Bundling licenses...");
@@ -176,8 +196,16 @@ pub fn bundle_licenses() -> Result<(), PackagingError> {
 
 #[cfg_attr(feature = "trace-spans", tracing::instrument)]
 pub fn build_release() -> Result<(), PackagingError> {
-    tracing::info!("Building release binary...");
-    run_command("cargo", &["build", "--release"])
+    tracing::info!("Building release binary for {}...", utils::target_arch());
+    if utils::is_universal() {
+        for arch in UNIVERSAL_ARCHS {
+            let triple = utils::target_triple_for(arch);
+            run_command("cargo", &with_target(&["build", "--release"], Some(&triple)))?;
+        }
+        return Ok(());
+    }
+    let triple = utils::target_triple();
+    run_command("cargo", &with_target(&["build", "--release"], triple.as_deref()))
 }
 
 #[cfg_attr(feature = "trace-spans", tracing::instrument)]
@@ -201,8 +229,14 @@ pub fn clean_artifacts() -> Result<(), PackagingError> {
     remove_if_match(target.clone(), "GooglePicz-", "deb");
     remove_if_match(target.clone(), "GooglePicz-", "rpm");
     remove_if_match(target.clone(), "GooglePicz-", "AppImage");
+    remove_if_match(target.clone(), "GooglePicz-", "zsync");
+    remove_if_match(target.clone(), "GooglePicz-", "flatpak");
     remove_if_match(target.clone(), "GooglePicz-", "dmg");
-    remove_if_match(target, "GooglePicz-", "exe");
+    remove_if_match(target.clone(), "GooglePicz-", "exe");
+    remove_if_match(target, "GooglePicz-", "msi");
+    let _ = fs::remove_file(utils::update_manifest_path());
+    let _ = fs::remove_file(utils::update_signature_path());
+    let _ = fs::remove_file(root.join(utils::BUILD_INFO));
 
     Ok(())
 }
@@ -210,12 +244,21 @@ pub fn clean_artifacts() -> Result<(), PackagingError> {
 #[cfg_attr(feature = "trace-spans", tracing::instrument)]
 fn create_macos_installer() -> Result<(), PackagingError> {
     tracing::info!("Bundling macOS app...");
-    run_command("cargo", &["bundle", "--release"])?;
+    let triple = utils::target_triple();
+    utils::pin_source_date_epoch()?;
+    run_command("cargo", &with_target(&["bundle", "--release"], triple.as_deref()))?;
+
+    let release = utils::release_dir();
+    let app_path = release.join("bundle/osx/GooglePicz.app");
+    if utils::is_universal() {
+        create_universal_binary(&app_path)?;
+    }
+    if utils::is_reproducible() && app_path.exists() {
+        utils::normalize_mtimes(&app_path)?;
+    }
 
     tracing::info!("Signing macOS app...");
     let identity = std::env::var("MAC_SIGN_ID").unwrap_or_default();
-    let root = get_project_root();
-    let app_path = root.join("target/release/bundle/osx/GooglePicz.app");
     if !identity.is_empty() {
         run_command(
             "codesign",
@@ -227,7 +270,7 @@ fn create_macos_installer() -> Result<(), PackagingError> {
         )?;
     }
 
-    let dmg_path = root.join("target/release/GooglePicz.dmg");
+    let dmg_path = release.join("GooglePicz.dmg");
     run_command(
         "hdiutil",
         &[
@@ -278,42 +321,51 @@ fn create_macos_installer() -> Result<(), PackagingError> {
     Ok(())
 }
 
+/// Replace the binary inside the `.app` bundle with a universal binary of
+/// the x86_64 and aarch64 slices when both were built.
+fn create_universal_binary(app_path: &Path) -> Result<(), PackagingError> {
+    let target = get_project_root().join("target");
+    let slices: Vec<String> = UNIVERSAL_ARCHS
+        .iter()
+        .map(|arch| {
+            let triple = utils::target_triple_for(arch);
+            target.join(triple).join("release/googlepicz").to_string_lossy().into_owned()
+        })
+        .collect();
+    if let Some(missing) = slices.iter().find(|p| !Path::new(p).exists()) {
+        tracing::warn!("No binary at {}, keeping the bundled one", missing);
+        return Ok(());
+    }
+
+    tracing::info!("Creating universal binary...");
+    let output = app_path.join("Contents/MacOS/googlepicz");
+    let output = output.to_string_lossy();
+    let mut args = vec!["-create", "-output", &output];
+    args.extend(slices.iter().map(String::as_str));
+    run_command("lipo", &args)
+}
+
 #[cfg_attr(feature = "trace-spans", tracing::instrument)]
 fn create_windows_installer() -> Result<(), PackagingError> {
-    tracing::info!("Creating Windows installer...");
-    let root = get_project_root();
-    let release_exe = root.join("target").join("release").join("googlepicz.exe");
+    let format = std::env::var("WINDOWS_PACKAGE_FORMAT").unwrap_or_else(|_| "nsis".into());
+    let release_exe = utils::release_dir().join("googlepicz.exe");
 
     // Determine the version from the workspace Cargo.toml
     let version = workspace_version()?;
-    let mut parts = version.split('.');
-    let major = parts.next().unwrap_or("0");
-    let minor = parts.next().unwrap_or("0");
-    let patch = parts.next().unwrap_or("0");
-
-    let arg_major = format!("/DAPP_VERSION_MAJOR={}", major);
-    let arg_minor = format!("/DAPP_VERSION_MINOR={}", minor);
-    let arg_patch = format!("/DAPP_VERSION_PATCH={}", patch);
-
-    run_command(
-        "makensis",
-        &[
-            arg_major.as_str(),
-            arg_minor.as_str(),
-            arg_patch.as_str(),
-            "packaging/installer.nsi",
-        ],
-    )?;
+    let installer = match format.as_str() {
+        "msi" => create_msi_package(&version)?,
+        _ => create_nsis_installer(&version)?,
+    };
+    let Some(installer) = installer else {
+        return Ok(());
+    };
 
-    let exe_path = root
-        .join("target/windows")
-        .join(format!("GooglePicz-{}-Setup.exe", version));
     if let Ok(cert_path) = std::env::var("WINDOWS_CERT") {
         if !cert_path.is_empty() {
             let password = std::env::var("WINDOWS_CERT_PASSWORD").unwrap_or_default();
-            let exe_path_str = exe_path.to_str().unwrap();
+            let installer_str = installer.to_str().unwrap();
             let release_exe_str = release_exe.to_str().unwrap();
-            let targets = [release_exe_str, exe_path_str];
+            let targets = [release_exe_str, installer_str];
             for target in &targets {
                 run_command(
                     "signtool",
@@ -338,21 +390,88 @@ fn create_windows_installer() -> Result<(), PackagingError> {
     }
 
     let final_path = artifact_path(&version);
-    fs::rename(&exe_path, &final_path)
+    fs::rename(&installer, &final_path)
         .map_err(|e| PackagingError::Other(format!(
             "Failed to move {:?} to {:?}: {}",
-            exe_path, final_path, e
+            installer, final_path, e
         )))?;
 
     Ok(())
 }
 
+/// Build the NSIS setup executable; returns its path.
+fn create_nsis_installer(version: &str) -> Result<Option<PathBuf>, PackagingError> {
+    tracing::info!("Creating Windows installer...");
+    let mut parts = version.split('.');
+    let major = parts.next().unwrap_or("0");
+    let minor = parts.next().unwrap_or("0");
+    let patch = parts.next().unwrap_or("0");
+
+    let arg_major = format!("/DAPP_VERSION_MAJOR={}", major);
+    let arg_minor = format!("/DAPP_VERSION_MINOR={}", minor);
+    let arg_patch = format!("/DAPP_VERSION_PATCH={}", patch);
+    let arg_build_dir = format!("/DBUILD_DIR={}", utils::release_dir().display());
+
+    run_command(
+        "makensis",
+        &[
+            arg_major.as_str(),
+            arg_minor.as_str(),
+            arg_patch.as_str(),
+            arg_build_dir.as_str(),
+            "packaging/installer.nsi",
+        ],
+    )?;
+
+    Ok(Some(
+        get_project_root()
+            .join("target/windows")
+            .join(format!("GooglePicz-{}-Setup.exe", version)),
+    ))
+}
+
+/// Build an MSI for deployment through Group Policy; returns its path, or
+/// `None` when mocked commands produced nothing.
+fn create_msi_package(version: &str) -> Result<Option<PathBuf>, PackagingError> {
+    tracing::info!("Creating Windows MSI package...");
+    let root = get_project_root();
+    // `cargo wix` reads the WiX source that `cargo wix init` generates once
+    if !root.join("app/wix/main.wxs").exists() {
+        run_command("cargo", &["wix", "init", "--package", "googlepicz"])?;
+    }
+    let msi_path = root.join("target/wix").join(format!("GooglePicz-{}.msi", version));
+    let triple = utils::target_triple();
+    run_command(
+        "cargo",
+        &with_target(
+            &[
+                "wix",
+                "--package",
+                "googlepicz",
+                "--no-build",
+                "--nocapture",
+                "--output",
+                msi_path.to_str().unwrap(),
+            ],
+            triple.as_deref(),
+        ),
+    )?;
+
+    if !msi_path.exists() {
+        if std::env::var("MOCK_COMMANDS").is_ok() { return Ok(None); } else {
+            return Err(PackagingError::Other("No .msi package produced".into()));
+        }
+    }
+    Ok(Some(msi_path))
+}
+
 #[cfg_attr(feature = "trace-spans", tracing::instrument)]
 fn create_linux_package() -> Result<(), PackagingError> {
     let format = std::env::var("LINUX_PACKAGE_FORMAT").unwrap_or_else(|_| "deb".into());
     match format.as_str() {
         "rpm" => create_rpm_package(),
         "appimage" => create_appimage_package(),
+        "flatpak" => create_flatpak_package(),
         _ => create_deb_package(),
     }
 }
@@ -361,10 +480,11 @@ fn create_deb_package() -> Result<(), PackagingError> {
     tracing::info!("Creating Linux .deb package...");
 
     let version = workspace_version()?;
-    run_command("cargo", &["deb", "--deb-version", &version])?;
+    let triple = utils::target_triple();
+    utils::pin_source_date_epoch()?;
+    run_command("cargo", &with_target(&["deb", "--deb-version", &version], triple.as_deref()))?;
 
-    let root = get_project_root();
-    let deb_dir = root.join("target/debian");
+    let deb_dir = utils::target_dir().join("debian");
     let deb_entries = match fs::read_dir(&deb_dir) {
         Ok(entries) => entries,
         Err(_) => {
@@ -406,7 +526,8 @@ fn create_rpm_package() -> Result<(), PackagingError> {
     tracing::info!("Creating Linux .rpm package...");
 
     let version = workspace_version()?;
-    run_command("cargo", &["rpm", "build", "--release"])?;
+    let triple = utils::target_triple();
+    run_command("cargo", &with_target(&["rpm", "build", "--release"], triple.as_deref()))?;
 
     let root = get_project_root();
     let rpm_path = find_by_extension(&root.join("target"), "rpm");
@@ -425,25 +546,272 @@ fn create_rpm_package() -> Result<(), PackagingError> {
     Ok(())
 }
 
+/// Application id used for desktop entries and AppStream metadata.
+pub const APP_ID: &str = "org.googlepicz.GooglePicz";
+
+/// Icon sizes installed into the AppImage's hicolor theme.
+const ICON_SIZES: [u32; 7] = [16, 32, 48, 64, 128, 256, 512];
+
+/// Desktop entry of the AppImage; the icon is looked up in the bundled
+/// hicolor theme.
+pub fn appimage_desktop_entry(version: &str) -> String {
+    format!(
+        "[Desktop Entry]
+Type=Application
+Name=GooglePicz
+Comment=Google Photos Desktop Client
+Exec=googlepicz
+Icon=googlepicz
+Categories=Graphics;Photography;
+Terminal=false
+X-AppImage-Version={version}
+"
+    )
+}
+
+/// AppStream metadata shown by software centers and checked by `appstreamcli`.
+pub fn appimage_metainfo(version: &str) -> String {
+    let date = utils::build_time().format("%Y-%m-%d");
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<component type="desktop-application">
+  <id>{APP_ID}</id>
+  <name>GooglePicz</name>
+  <summary>Google Photos Desktop Client</summary>
+  <metadata_license>CC0-1.0</metadata_license>
+  <project_license>MIT</project_license>
+  <description>
+    <p>Browse, search and organize your Google Photos library with a local cache.</p>
+  </description>
+  <launchable type="desktop-id">{APP_ID}.desktop</launchable>
+  <url type="homepage">https://github.com/Christopher-Schulze/GooglePicz</url>
+  <releases>
+    <release version="{version}" date="{date}"/>
+  </releases>
+  <content_rating type="oars-1.1"/>
+</component>
+"#
+    )
+}
+
+/// Scale `logo/image.png` into the hicolor icon sizes below `app_dir`, plus
+/// the top level icon and `.DirIcon` an AppDir needs.
+fn write_icon_set(app_dir: &Path) -> Result<(), PackagingError> {
+    let logo_path = get_project_root().join("logo/image.png");
+    let logo = image::open(&logo_path)
+        .map_err(|e| PackagingError::Other(format!("Failed to read {:?}: {}", logo_path, e)))?;
+    for size in ICON_SIZES {
+        let dir = app_dir.join(format!("usr/share/icons/hicolor/{size}x{size}/apps"));
+        fs::create_dir_all(&dir)
+            .map_err(|e| PackagingError::Other(format!("Failed to create {:?}: {}", dir, e)))?;
+        let path = dir.join("googlepicz.png");
+        logo.resize_exact(size, size, image::imageops::FilterType::Triangle)
+            .save(&path)
+            .map_err(|e| PackagingError::Other(format!("Failed to write {:?}: {}", path, e)))?;
+    }
+    let icon = app_dir.join("usr/share/icons/hicolor/256x256/apps/googlepicz.png");
+    for name in ["googlepicz.png", ".DirIcon"] {
+        fs::copy(&icon, app_dir.join(name))
+            .map_err(|e| PackagingError::Other(format!("Failed to write {}: {}", name, e)))?;
+    }
+    Ok(())
+}
+
+/// Lay out `target/appimage/GooglePicz.AppDir` with the release binary,
+/// desktop entry, AppStream metadata and icons; returns its path.
+fn create_appdir(version: &str) -> Result<PathBuf, PackagingError> {
+    let app_dir = get_project_root().join("target/appimage/GooglePicz.AppDir");
+    let _ = fs::remove_dir_all(&app_dir);
+    let write = |rel: &str, contents: &str| {
+        let path = app_dir.join(rel);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| PackagingError::Other(format!("Failed to create {:?}: {}", parent, e)))?;
+        }
+        fs::write(&path, contents)
+            .map_err(|e| PackagingError::Other(format!("Failed to write {:?}: {}", path, e)))
+    };
+    let desktop = appimage_desktop_entry(version);
+    write(&format!("{APP_ID}.desktop"), &desktop)?;
+    write(&format!("usr/share/applications/{APP_ID}.desktop"), &desktop)?;
+    write(&format!("usr/share/metainfo/{APP_ID}.appdata.xml"), &appimage_metainfo(version))?;
+    write("AppRun", "#!/bin/sh\nHERE=\"$(dirname \"$(readlink -f \"$0\")\")\"\nexec \"$HERE/usr/bin/googlepicz\" \"$@\"\n")?;
+    write_icon_set(&app_dir)?;
+
+    let binary = utils::release_dir().join("googlepicz");
+    let bin_dir = app_dir.join("usr/bin");
+    fs::create_dir_all(&bin_dir)
+        .map_err(|e| PackagingError::Other(format!("Failed to create {:?}: {}", bin_dir, e)))?;
+    if binary.exists() {
+        fs::copy(&binary, bin_dir.join("googlepicz"))
+            .map_err(|e| PackagingError::Other(format!("Failed to copy {:?}: {}", binary, e)))?;
+    } else if std::env::var("MOCK_COMMANDS").is_err() {
+        return Err(PackagingError::Other(format!("Release binary {:?} not found", binary)));
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let _ = fs::set_permissions(app_dir.join("AppRun"), fs::Permissions::from_mode(0o755));
+    }
+    if utils::is_reproducible() {
+        // mksquashfs takes the file times from the AppDir
+        utils::normalize_mtimes(&app_dir)?;
+    }
+    Ok(app_dir)
+}
+
 fn create_appimage_package() -> Result<(), PackagingError> {
     tracing::info!("Creating Linux AppImage package...");
 
     let version = workspace_version()?;
-    run_command("cargo", &["appimage"])?;
+    let app_dir = create_appdir(&version)?;
+    let app_dir_str = app_dir.to_string_lossy();
+    let desktop = app_dir.join(format!("{APP_ID}.desktop"));
+    if command_available("desktop-file-validate") {
+        run_command("desktop-file-validate", &[&desktop.to_string_lossy()])?;
+    }
+    if command_available("appstreamcli") {
+        run_command("appstreamcli", &["validate-tree", "--no-net", &app_dir_str])?;
+    }
 
-    let root = get_project_root();
-    let app_path = find_by_extension(&root.join("target"), "AppImage");
-    let Some(app_path) = app_path else {
+    // appimagetool names the zsync file after the AppImage and refers to it
+    // by that name, so build under the final name right away
+    let versioned = artifact_path(&version);
+    let file_name = versioned.file_name().unwrap_or_default();
+    let built = app_dir.with_file_name(file_name);
+    let update_info = utils::appimage_update_information();
+    run_command(
+        "appimagetool",
+        &["--updateinformation", &update_info, &app_dir_str, &built.to_string_lossy()],
+    )?;
+
+    if !built.exists() {
         if std::env::var("MOCK_COMMANDS").is_ok() { return Ok(()); } else {
             return Err(PackagingError::Other("No AppImage produced".into()));
         }
+    }
+    let built_zsync = utils::zsync_path(&built);
+    if !built_zsync.exists() && std::env::var("MOCK_COMMANDS").is_err() {
+        return Err(PackagingError::Other("No .zsync file produced next to the AppImage".into()));
+    }
+
+    let moves = [(built.clone(), versioned.clone()), (built_zsync, utils::zsync_path(&versioned))];
+    for (from, to) in moves.iter().filter(|(from, _)| from.exists()) {
+        fs::rename(from, to)
+            .map_err(|e| PackagingError::Other(format!(
+                "Failed to move {:?} to {:?}: {}",
+                from, to, e
+            )))?;
+    }
+    Ok(())
+}
+
+/// Flatpak application id, also the name of the generated manifest.
+pub const FLATPAK_APP_ID: &str = APP_ID;
+
+const FLATPAK_DESKTOP_ENTRY: &str = "[Desktop Entry]
+Type=Application
+Name=GooglePicz
+Comment=Google Photos Desktop Client
+Exec=googlepicz
+Categories=Graphics;Photography;
+Terminal=false
+";
+
+/// Flatpak manifest packaging the release binary. The sandbox may use the
+/// network, a display with GPU rendering, audio for videos and the Secret
+/// Service holding the tokens; `~/.googlepicz` is kept inside the sandbox.
+pub fn flatpak_manifest() -> serde_json::Value {
+    let desktop = format!("{FLATPAK_APP_ID}.desktop");
+    // Relative to `target/flatpak`
+    let binary = match utils::target_triple() {
+        Some(triple) => format!("../{triple}/release/googlepicz"),
+        None => "../release/googlepicz".into(),
+    };
+    serde_json::json!({
+        "app-id": FLATPAK_APP_ID,
+        "runtime": "org.freedesktop.Platform",
+        "runtime-version": "23.08",
+        "sdk": "org.freedesktop.Sdk",
+        "command": "googlepicz",
+        "finish-args": [
+            "--share=network",
+            "--share=ipc",
+            "--socket=wayland",
+            "--socket=fallback-x11",
+            "--device=dri",
+            "--socket=pulseaudio",
+            "--talk-name=org.freedesktop.secrets",
+            "--persist=.googlepicz",
+        ],
+        "modules": [{
+            "name": "googlepicz",
+            "buildsystem": "simple",
+            "build-commands": [
+                "install -Dm755 googlepicz /app/bin/googlepicz",
+                format!("install -Dm644 {desktop} /app/share/applications/{desktop}"),
+            ],
+            "sources": [
+                { "type": "file", "path": binary },
+                { "type": "file", "path": desktop },
+            ],
+        }],
+    })
+}
+
+fn create_flatpak_package() -> Result<(), PackagingError> {
+    tracing::info!("Creating Linux Flatpak bundle...");
+
+    let version = workspace_version()?;
+    let root = get_project_root();
+    let dir = root.join("target/flatpak");
+    let write = |name: String, contents: String| {
+        fs::write(dir.join(&name), contents)
+            .map_err(|e| PackagingError::Other(format!("Failed to write {}: {}", name, e)))
     };
+    fs::create_dir_all(&dir)
+        .map_err(|e| PackagingError::Other(format!("Failed to create {:?}: {}", dir, e)))?;
+    let manifest = serde_json::to_string_pretty(&flatpak_manifest())
+        .map_err(|e| PackagingError::Other(format!("Failed to serialize Flatpak manifest: {}", e)))?;
+    write(format!("{FLATPAK_APP_ID}.json"), manifest)?;
+    write(format!("{FLATPAK_APP_ID}.desktop"), FLATPAK_DESKTOP_ENTRY.into())?;
+    if utils::is_reproducible() {
+        utils::normalize_mtimes(&dir)?;
+    }
+
+    let manifest_path = dir.join(format!("{FLATPAK_APP_ID}.json"));
+    let repo = dir.join("repo");
+    let bundle = dir.join("GooglePicz.flatpak");
+    let repo_arg = format!("--repo={}", repo.display());
+    let arch_arg = format!("--arch={}", utils::target_arch());
+    let build_dir = dir.join("build").to_string_lossy().into_owned();
+    let manifest_arg = manifest_path.to_string_lossy().into_owned();
+    let mut builder_args = vec!["--force-clean", &repo_arg];
+    if utils::target_triple().is_some() {
+        builder_args.push(&arch_arg);
+    }
+    builder_args.extend([build_dir.as_str(), manifest_arg.as_str()]);
+    run_command("flatpak-builder", &builder_args)?;
+    let repo_path = repo.to_string_lossy();
+    let bundle_path = bundle.to_string_lossy();
+    let mut bundle_args = vec!["build-bundle"];
+    if utils::target_triple().is_some() {
+        bundle_args.push(&arch_arg);
+    }
+    bundle_args.extend([&*repo_path, &*bundle_path, FLATPAK_APP_ID]);
+    run_command("flatpak", &bundle_args)?;
+
+    if !bundle.exists() {
+        if std::env::var("MOCK_COMMANDS").is_ok() { return Ok(()); } else {
+            return Err(PackagingError::Other("No Flatpak bundle produced".into()));
+        }
+    }
 
     let versioned = artifact_path(&version);
-    fs::rename(&app_path, &versioned)
+    fs::rename(&bundle, &versioned)
         .map_err(|e| PackagingError::Other(format!(
             "Failed to move {:?} to {:?}: {}",
-            app_path, versioned, e
+            bundle, versioned, e
         )))?;
     Ok(())
 }
@@ -481,18 +849,41 @@ pub fn create_installer() -> Result<(), PackagingError> {
     }
 }
 
+/// Pin everything a reproducible build would otherwise take from the build
+/// machine: `SOURCE_DATE_EPOCH` from the commit time, which cargo-deb and
+/// mksquashfs use for file times, and the workspace path compiled into the
+/// binary.
+fn prepare_reproducible_build(root: &Path) -> Result<(), PackagingError> {
+    utils::pin_source_date_epoch()?;
+    tracing::info!("Reproducible build with SOURCE_DATE_EPOCH={}", utils::source_date_epoch()?);
+    let remap = format!("--remap-path-prefix={}=.", root.display());
+    let rustflags = std::env::var("RUSTFLAGS").unwrap_or_default();
+    if !rustflags.contains(&remap) {
+        std::env::set_var("RUSTFLAGS", format!("{} {}", rustflags, remap).trim());
+    }
+    if cfg!(target_os = "macos") {
+        // Keeps ld64 from writing timestamps into the binary
+        std::env::set_var("ZERO_AR_DATE", "1");
+    }
+    Ok(())
+}
+
 #[cfg_attr(feature = "trace-spans", tracing::instrument)]
 pub fn package_all() -> Result<(), PackagingError> {
     verify_tools()?;
     let root = get_project_root();
     std::env::set_current_dir(&root)
         .map_err(|e| PackagingError::Other(format!("Failed to change directory: {}", e)))?;
+    if utils::is_reproducible() {
+        prepare_reproducible_build(&root)?;
+    }
 
     clean_artifacts()?;
     bundle_licenses()?;
     build_release()?;
     create_installer()?;
-    utils::write_checksums()
+    utils::write_checksums()?;
+    utils::write_update_manifest()
 }
 
 #[cfg(test)]
@@ -548,7 +939,7 @@ mod tests {
         };
         // In mock mode the binary won't exist
         if target_dir.join(binary_name).exists() {
-            assert!(true);
+            assert!(target_dir.join(binary_name).is_file());
         }
 
         std::env::set_current_dir(original_dir).unwrap();
@@ -738,15 +1129,148 @@ mod tests {
         let root = get_project_root();
         let img_dir = root.join("target/appimage");
         fs::create_dir_all(&img_dir).unwrap();
-        fs::write(img_dir.join("dummy.AppImage"), b"test").unwrap();
+        let version = workspace_version().unwrap();
+        let img = artifact_path(&version);
+        let built = img_dir.join(img.file_name().unwrap());
+        fs::write(&built, b"test").unwrap();
+        fs::write(utils::zsync_path(&built), b"zsync").unwrap();
+
+        let result = create_linux_package();
+        assert!(result.is_ok(), "{:?}", result.err());
+
+        let app_dir = img_dir.join("GooglePicz.AppDir");
+        let desktop = fs::read_to_string(app_dir.join(format!("{APP_ID}.desktop"))).unwrap();
+        assert!(desktop.contains("Icon=googlepicz"));
+        assert!(desktop.contains(&format!("X-AppImage-Version={}", version)));
+        assert!(app_dir.join(format!("usr/share/metainfo/{APP_ID}.appdata.xml")).exists());
+        assert!(app_dir.join("usr/share/icons/hicolor/256x256/apps/googlepicz.png").exists());
+        assert!(app_dir.join(".DirIcon").exists());
+
+        assert!(img.to_string_lossy().ends_with(".AppImage"));
+        assert_eq!(fs::read(&img).unwrap(), b"test");
+        let zsync = utils::zsync_path(&img);
+        assert_eq!(fs::read(&zsync).unwrap(), b"zsync");
+        fs::remove_file(img).unwrap();
+        fs::remove_file(zsync).unwrap();
+
+        std::env::remove_var("MOCK_COMMANDS");
+        std::env::remove_var("LINUX_PACKAGE_FORMAT");
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    #[serial]
+    fn test_create_linux_package_function_cross_arch() {
+        use crate::utils::{get_project_root, workspace_version};
+        std::env::set_var("MOCK_COMMANDS", "1");
+        std::env::set_var("LINUX_PACKAGE_FORMAT", "deb");
+        std::env::set_var("TARGET_ARCH", "aarch64");
+        let root = get_project_root();
+        let deb_dir = root.join("target/aarch64-unknown-linux-gnu/debian");
+        fs::create_dir_all(&deb_dir).unwrap();
+        fs::write(deb_dir.join("dummy.deb"), b"test").unwrap();
 
         let result = create_linux_package();
         assert!(result.is_ok());
 
         let version = workspace_version().unwrap();
-        let img = artifact_path(&version);
-        assert!(img.exists());
-        fs::remove_file(img).unwrap();
+        let deb = artifact_path(&version);
+        assert!(deb.to_string_lossy().ends_with("-linux-aarch64.deb"));
+        assert_eq!(fs::read(&deb).unwrap(), b"test");
+        fs::remove_file(deb).unwrap();
+
+        std::env::remove_var("MOCK_COMMANDS");
+        std::env::remove_var("LINUX_PACKAGE_FORMAT");
+        std::env::remove_var("TARGET_ARCH");
+    }
+
+    #[cfg(target_os = "macos")]
+    #[test]
+    #[serial]
+    fn test_create_macos_installer_universal() {
+        use crate::utils::{get_project_root, workspace_version};
+        std::env::set_var("MOCK_COMMANDS", "1");
+        std::env::set_var("TARGET_ARCH", "universal");
+        let root = get_project_root();
+        for triple in ["x86_64-apple-darwin", "aarch64-apple-darwin"] {
+            let dir = root.join("target").join(triple).join("release");
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(dir.join("googlepicz"), b"slice").unwrap();
+        }
+        let release = root.join("target/release");
+        fs::create_dir_all(release.join("bundle/osx/GooglePicz.app")).unwrap();
+        fs::write(release.join("GooglePicz.dmg"), b"test").unwrap();
+
+        let result = create_macos_installer();
+        assert!(result.is_ok());
+
+        let version = workspace_version().unwrap();
+        let dmg = artifact_path(&version);
+        assert!(dmg.to_string_lossy().ends_with("-macos-universal.dmg"));
+        fs::remove_file(dmg).unwrap();
+
+        std::env::remove_var("MOCK_COMMANDS");
+        std::env::remove_var("TARGET_ARCH");
+    }
+
+    #[cfg(target_os = "windows")]
+    #[test]
+    #[serial]
+    fn test_create_windows_installer_function_msi() {
+        use crate::utils::{get_project_root, verify_artifact_names, workspace_version};
+        std::env::set_var("MOCK_COMMANDS", "1");
+        std::env::set_var("WINDOWS_PACKAGE_FORMAT", "msi");
+        std::env::set_var("WINDOWS_CERT", "C:/dummy.pfx");
+        let root = get_project_root();
+        let wix_dir = root.join("target/wix");
+        fs::create_dir_all(&wix_dir).unwrap();
+        let version = workspace_version().unwrap();
+        fs::write(wix_dir.join(format!("GooglePicz-{}.msi", version)), b"test").unwrap();
+
+        let result = create_windows_installer();
+        assert!(result.is_ok());
+        verify_artifact_names().unwrap();
+
+        let msi = artifact_path(&version);
+        assert_eq!(msi.extension().unwrap(), "msi");
+        assert_eq!(fs::read(&msi).unwrap(), b"test");
+        fs::remove_file(msi).unwrap();
+
+        std::env::remove_var("MOCK_COMMANDS");
+        std::env::remove_var("WINDOWS_PACKAGE_FORMAT");
+        std::env::remove_var("WINDOWS_CERT");
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    #[serial]
+    fn test_create_linux_package_function_flatpak() {
+        use crate::utils::{get_project_root, workspace_version};
+        std::env::set_var("MOCK_COMMANDS", "1");
+        std::env::set_var("LINUX_PACKAGE_FORMAT", "flatpak");
+        let root = get_project_root();
+        let flatpak_dir = root.join("target/flatpak");
+        fs::create_dir_all(&flatpak_dir).unwrap();
+        fs::write(flatpak_dir.join("GooglePicz.flatpak"), b"test").unwrap();
+
+        let result = create_linux_package();
+        assert!(result.is_ok());
+
+        let manifest = fs::read_to_string(flatpak_dir.join(format!("{FLATPAK_APP_ID}.json"))).unwrap();
+        let manifest: serde_json::Value = serde_json::from_str(&manifest).unwrap();
+        assert_eq!(manifest["app-id"], FLATPAK_APP_ID);
+        let finish_args = manifest["finish-args"].as_array().unwrap();
+        assert!(finish_args.contains(&"--share=network".into()));
+        assert!(finish_args.contains(&"--talk-name=org.freedesktop.secrets".into()));
+        assert!(flatpak_dir.join(format!("{FLATPAK_APP_ID}.desktop")).exists());
+
+        let version = workspace_version().unwrap();
+        let bundle = artifact_path(&version);
+        assert!(bundle.exists());
+        assert!(bundle
+            .to_string_lossy()
+            .ends_with(&format!("-linux-{}.flatpak", std::env::consts::ARCH)));
+        fs::remove_file(bundle).unwrap();
 
         std::env::remove_var("MOCK_COMMANDS");
         std::env::remove_var("LINUX_PACKAGE_FORMAT");
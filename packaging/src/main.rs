@@ -4,15 +4,30 @@ use clap::Parser;
 
 #[derive(Parser)]
 struct Args {
-    /// Package format on Linux (deb, rpm or appimage)
-    #[arg(long, value_parser = ["deb", "rpm", "appimage"])]
+    /// Package format: deb, rpm, appimage or flatpak on Linux, nsis or msi on Windows
+    #[arg(long, value_parser = ["deb", "rpm", "appimage", "flatpak", "nsis", "msi"])]
     format: Option<String>,
+    /// Target architecture: x86_64, aarch64, or universal for a macOS universal binary
+    #[arg(long)]
+    arch: Option<String>,
+    /// Build artifacts that are bit for bit identical across builds of a commit
+    #[arg(long)]
+    reproducible: bool,
 }
 
 fn main() -> Result<(), packaging::PackagingError> {
     let args = Args::parse();
     if let Some(fmt) = args.format {
-        std::env::set_var("LINUX_PACKAGE_FORMAT", fmt);
+        match fmt.as_str() {
+            "nsis" | "msi" => std::env::set_var("WINDOWS_PACKAGE_FORMAT", fmt),
+            _ => std::env::set_var("LINUX_PACKAGE_FORMAT", fmt),
+        }
+    }
+    if let Some(arch) = args.arch {
+        std::env::set_var("TARGET_ARCH", arch);
+    }
+    if args.reproducible {
+        std::env::set_var("REPRODUCIBLE_BUILD", "1");
     }
     packaging::package_all()?;
     Ok(())
@@ -1,7 +1,11 @@
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use ring::signature::{Ed25519KeyPair, KeyPair, UnparsedPublicKey, ED25519};
+
 use toml::Value;
 use serde_json::Value as JsonValue;
 use sha2::{Digest, Sha256};
@@ -56,14 +60,71 @@ pub fn platform_name() -> &'static str {
     }
 }
 
+/// Architecture the artifacts are built for: `TARGET_ARCH` (`x86_64`,
+/// `aarch64`, or `universal` for a macOS universal binary), or the host
+/// architecture when unset.
+pub fn target_arch() -> String {
+    match std::env::var("TARGET_ARCH") {
+        Ok(arch) if !arch.is_empty() => arch,
+        _ => std::env::consts::ARCH.into(),
+    }
+}
+
+/// Rust target triple for `arch` on this platform.
+pub fn target_triple_for(arch: &str) -> String {
+    if cfg!(target_os = "macos") {
+        format!("{}-apple-darwin", arch)
+    } else if cfg!(target_os = "windows") {
+        format!("{}-pc-windows-msvc", arch)
+    } else {
+        format!("{}-unknown-linux-gnu", arch)
+    }
+}
+
+/// Triple passed to cargo as `--target`. `None` for a native build, which
+/// includes the macOS universal binary bundled from the host build.
+pub fn target_triple() -> Option<String> {
+    match std::env::var("TARGET_ARCH") {
+        Ok(arch) if !arch.is_empty() && !is_universal() => Some(target_triple_for(&arch)),
+        _ => None,
+    }
+}
+
+/// Whether a macOS universal binary of the x86_64 and aarch64 slices is built.
+pub fn is_universal() -> bool {
+    cfg!(target_os = "macos") && std::env::var("TARGET_ARCH").as_deref() == Ok("universal")
+}
+
+/// Cargo's output directory for the target: `target/<triple>` when cross
+/// compiling, otherwise `target`.
+pub fn target_dir() -> PathBuf {
+    let target = get_project_root().join("target");
+    match target_triple() {
+        Some(triple) => target.join(triple),
+        None => target,
+    }
+}
+
+/// Directory holding the release binary.
+pub fn release_dir() -> PathBuf {
+    target_dir().join("release")
+}
+
 /// Determine the expected file extension for the generated package.
 pub fn package_extension() -> String {
     if cfg!(target_os = "linux") {
-        std::env::var("LINUX_PACKAGE_FORMAT").unwrap_or_else(|_| "deb".into())
+        match std::env::var("LINUX_PACKAGE_FORMAT") {
+            Ok(format) if format == "appimage" => "AppImage".into(),
+            Ok(format) => format,
+            Err(_) => "deb".into(),
+        }
     } else if cfg!(target_os = "macos") {
         "dmg".into()
     } else if cfg!(target_os = "windows") {
-        "exe".into()
+        match std::env::var("WINDOWS_PACKAGE_FORMAT").as_deref() {
+            Ok("msi") => "msi".into(),
+            _ => "exe".into(),
+        }
     } else {
         String::new()
     }
@@ -71,10 +132,43 @@ pub fn package_extension() -> String {
 
 /// Construct the full path to the final installer artifact.
 pub fn artifact_path(version: &str) -> PathBuf {
+    artifact_file(version, &package_extension())
+}
+
+/// Final artifacts of every package format of this platform. On Windows the
+/// NSIS setup and the MSI come from separate runs and both get checksums.
+pub fn artifact_paths(version: &str) -> Vec<PathBuf> {
+    if cfg!(target_os = "windows") {
+        vec![artifact_file(version, "exe"), artifact_file(version, "msi")]
+    } else {
+        vec![artifact_path(version)]
+    }
+}
+
+fn artifact_file(version: &str, ext: &str) -> PathBuf {
     let root = get_project_root();
-    let ext = package_extension();
     let platform = platform_name();
-    root.join("target").join(format!("GooglePicz-{}-{}.{}", version, platform, ext))
+    let arch = target_arch();
+    root.join("target").join(format!("GooglePicz-{}-{}-{}.{}", version, platform, arch, ext))
+}
+
+/// zsync file produced next to an AppImage for delta updates.
+pub fn zsync_path(artifact: &Path) -> PathBuf {
+    let mut name = artifact.as_os_str().to_owned();
+    name.push(".zsync");
+    PathBuf::from(name)
+}
+
+/// Update information embedded in the AppImage: `APPIMAGE_UPDATE_INFO`, or
+/// the zsync file of the latest GitHub release for this architecture.
+pub fn appimage_update_information() -> String {
+    match std::env::var("APPIMAGE_UPDATE_INFO") {
+        Ok(info) if !info.is_empty() => info,
+        _ => format!(
+            "gh-releases-zsync|Christopher-Schulze|GooglePicz|latest|GooglePicz-*-linux-{}.AppImage.zsync",
+            target_arch()
+        ),
+    }
 }
 
 /// Verify that `cargo metadata` lists the expected package name.
@@ -112,24 +206,153 @@ pub fn verify_artifact_names() -> Result<(), PackagingError> {
     Ok(())
 }
 
-/// Calculate SHA256 checksums of produced artifacts and write them to `checksums.txt`.
+/// Hex encoded SHA256 of the file at `path`.
+fn sha256_file(path: &Path) -> Result<String, PackagingError> {
+    let data = fs::read(path)
+        .map_err(|e| PackagingError::Other(format!("Failed to read {:?}: {}", path, e)))?;
+    Ok(format!("{:x}", Sha256::digest(&data)))
+}
+
+/// Whether `REPRODUCIBLE_BUILD` asks for artifacts that do not depend on
+/// the build machine's clock or paths.
+pub fn is_reproducible() -> bool {
+    std::env::var("REPRODUCIBLE_BUILD").map(|v| !v.is_empty() && v != "0").unwrap_or(false)
+}
+
+/// Commit time of `HEAD` in seconds, the `SOURCE_DATE_EPOCH` of a
+/// reproducible build unless that is already set.
+pub fn source_date_epoch() -> Result<i64, PackagingError> {
+    if let Some(epoch) = std::env::var("SOURCE_DATE_EPOCH").ok().and_then(|v| v.parse().ok()) {
+        return Ok(epoch);
+    }
+    let output = Command::new("git")
+        .args(["log", "-1", "--format=%ct"])
+        .current_dir(get_project_root())
+        .output()
+        .map_err(|e| PackagingError::Other(format!("Failed to run git: {}", e)))?;
+    String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .parse()
+        .map_err(|_| PackagingError::Other("Cannot read the commit time of HEAD".into()))
+}
+
+/// In a reproducible build, make sure `SOURCE_DATE_EPOCH` is set in the
+/// environment of the packaging tools run after this, so cargo-deb and
+/// cargo-bundle do not fall back to the current time.
+pub fn pin_source_date_epoch() -> Result<(), PackagingError> {
+    if is_reproducible() {
+        std::env::set_var("SOURCE_DATE_EPOCH", source_date_epoch()?.to_string());
+    }
+    Ok(())
+}
+
+/// Time recorded in generated files: `SOURCE_DATE_EPOCH` when set, else now.
+pub fn build_time() -> chrono::DateTime<chrono::Utc> {
+    std::env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .and_then(|epoch| chrono::DateTime::from_timestamp(epoch, 0))
+        .unwrap_or_else(chrono::Utc::now)
+}
+
+/// Set the modification time of everything below `dir` to `build_time()` so
+/// archives built from it do not carry the time of the build.
+pub fn normalize_mtimes(dir: &Path) -> Result<(), PackagingError> {
+    let time = std::time::SystemTime::from(build_time());
+    let entries = fs::read_dir(dir)
+        .map_err(|e| PackagingError::Other(format!("Failed to read {:?}: {}", dir, e)))?;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        // Symlinks would have their target touched
+        if entry.file_type().map(|t| t.is_symlink()).unwrap_or(true) {
+            continue;
+        }
+        if path.is_dir() {
+            normalize_mtimes(&path)?;
+        }
+        fs::File::open(&path)
+            .and_then(|f| f.set_modified(time))
+            .map_err(|e| PackagingError::Other(format!("Failed to set mtime of {:?}: {}", path, e)))?;
+    }
+    Ok(())
+}
+
+/// Build description written next to `checksums.txt`.
+pub const BUILD_INFO: &str = "build-info.json";
+
+/// Write `build-info.json` with what the artifacts were built from: version,
+/// rustc, target, default features of the app and the `SOURCE_DATE_EPOCH`.
+pub fn write_build_info() -> Result<(), PackagingError> {
+    let rustc = Command::new("rustc")
+        .arg("-vV")
+        .output()
+        .map_err(|e| PackagingError::Other(format!("Failed to run rustc: {}", e)))?;
+    let rustc = String::from_utf8_lossy(&rustc.stdout).into_owned();
+    let rustc_version = rustc.lines().next().unwrap_or_default();
+    let host = rustc
+        .lines()
+        .find_map(|l| l.strip_prefix("host: "))
+        .unwrap_or_default();
+    let info = serde_json::json!({
+        "version": workspace_version()?,
+        "rustc": rustc_version,
+        "target": target_triple().unwrap_or_else(|| host.to_string()),
+        "arch": target_arch(),
+        "profile": "release",
+        "features": app_default_features()?,
+        "rustflags": user_rustflags(),
+        "reproducible": is_reproducible(),
+        "source_date_epoch": std::env::var("SOURCE_DATE_EPOCH").ok().and_then(|v| v.parse::<i64>().ok()),
+    });
+    let data = serde_json::to_string_pretty(&info)
+        .map_err(|e| PackagingError::Other(format!("Failed to serialize build info: {}", e)))?;
+    fs::write(get_project_root().join(BUILD_INFO), data + "\n")
+        .map_err(|e| PackagingError::Other(format!("Failed to write {}: {}", BUILD_INFO, e)))
+}
+
+/// `RUSTFLAGS` as the user set them, without the path remap a reproducible
+/// build adds: it holds the absolute checkout path, which differs between
+/// machines building the same sources.
+fn user_rustflags() -> String {
+    let remap = format!("--remap-path-prefix={}=.", get_project_root().display());
+    std::env::var("RUSTFLAGS")
+        .unwrap_or_default()
+        .split_whitespace()
+        .filter(|flag| *flag != remap)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Features the release build enables, i.e. the app's default features.
+fn app_default_features() -> Result<Vec<String>, PackagingError> {
+    let output = Command::new("cargo")
+        .args(["metadata", "--format-version", "1", "--no-deps"])
+        .current_dir(get_project_root())
+        .output()
+        .map_err(|e| PackagingError::Other(format!("Failed to run cargo metadata: {}", e)))?;
+    let metadata: JsonValue = serde_json::from_slice(&output.stdout)
+        .map_err(|e| PackagingError::Other(format!("Failed to parse cargo metadata: {}", e)))?;
+    let features = metadata["packages"]
+        .as_array()
+        .and_then(|p| p.iter().find(|pkg| pkg["name"] == "googlepicz"))
+        .and_then(|pkg| pkg["features"]["default"].as_array())
+        .map(|f| f.iter().filter_map(|v| v.as_str().map(String::from)).collect())
+        .unwrap_or_default();
+    Ok(features)
+}
+
+/// Calculate SHA256 checksums of produced artifacts and write them to
+/// `checksums.txt`, along with `build-info.json`.
 pub fn write_checksums() -> Result<(), PackagingError> {
     let root = get_project_root();
     let version = workspace_version()?;
 
-    let mut artifacts = Vec::new();
-    artifacts.push(artifact_path(&version));
+    let artifacts = artifact_paths(&version);
 
     let mut lines = Vec::new();
     for artifact in artifacts {
         if artifact.exists() {
-            let data = fs::read(&artifact).map_err(|e| {
-                PackagingError::Other(format!("Failed to read {:?}: {}", artifact, e))
-            })?;
-            let mut hasher = Sha256::new();
-            hasher.update(&data);
-            let digest = hasher.finalize();
-            let checksum = format!("{:x}", digest);
+            let checksum = sha256_file(&artifact)?;
             if let Some(name) = artifact.file_name().and_then(|n| n.to_str()) {
                 lines.push(format!("{}  {}", checksum, name));
             }
@@ -138,7 +361,135 @@ pub fn write_checksums() -> Result<(), PackagingError> {
 
     fs::write(root.join("checksums.txt"), lines.join("\n") + "\n").map_err(|e| {
         PackagingError::Other(format!("Failed to write checksums.txt: {}", e))
-    })
+    })?;
+    write_build_info()
+}
+
+/// Manifest describing the release for the app's updater, written next to
+/// the artifacts in `target/`.
+pub const UPDATE_MANIFEST: &str = "update-manifest.json";
+
+/// Path of the update manifest; its detached signature adds `.sig`.
+pub fn update_manifest_path() -> PathBuf {
+    get_project_root().join("target").join(UPDATE_MANIFEST)
+}
+
+pub fn update_signature_path() -> PathBuf {
+    get_project_root().join("target").join(format!("{}.sig", UPDATE_MANIFEST))
+}
+
+/// Key pair from `UPDATE_SIGN_KEY`, a base64 encoded 32 byte ed25519 seed.
+fn update_sign_key(seed: &str) -> Result<Ed25519KeyPair, PackagingError> {
+    let seed = BASE64
+        .decode(seed.trim())
+        .map_err(|e| PackagingError::Other(format!("UPDATE_SIGN_KEY is not base64: {}", e)))?;
+    Ed25519KeyPair::from_seed_unchecked(&seed)
+        .map_err(|_| PackagingError::Other("UPDATE_SIGN_KEY must be a 32 byte ed25519 seed".into()))
+}
+
+/// Base64 encoded public key matching `UPDATE_SIGN_KEY`, to embed in the app
+/// as `GOOGLEPICZ_UPDATE_PUBLIC_KEY`.
+pub fn update_public_key(seed: &str) -> Result<String, PackagingError> {
+    Ok(BASE64.encode(update_sign_key(seed)?.public_key().as_ref()))
+}
+
+/// Write `update-manifest.json` with the version, platform, architecture,
+/// file name, size, SHA256 and release time of the installer artifact. With
+/// `UPDATE_SIGN_KEY` set, a base64 ed25519 signature of the file goes to
+/// `update-manifest.json.sig`. Nothing is written without an artifact.
+pub fn write_update_manifest() -> Result<(), PackagingError> {
+    let version = workspace_version()?;
+    let artifact = artifact_path(&version);
+    if !artifact.exists() {
+        tracing::warn!("No artifact at {:?}, skipping the update manifest", artifact);
+        return Ok(());
+    }
+    let size = fs::metadata(&artifact)
+        .map_err(|e| PackagingError::Other(format!("Failed to read {:?}: {}", artifact, e)))?
+        .len();
+    let manifest = serde_json::json!({
+        "version": version,
+        "platform": platform_name(),
+        "arch": target_arch(),
+        "artifact": artifact.file_name().and_then(|n| n.to_str()).unwrap_or_default(),
+        "size": size,
+        "sha256": sha256_file(&artifact)?,
+        "released_at": build_time().to_rfc3339(),
+    });
+    let data = serde_json::to_vec_pretty(&manifest)
+        .map_err(|e| PackagingError::Other(format!("Failed to serialize update manifest: {}", e)))?;
+    let path = update_manifest_path();
+    fs::write(&path, &data)
+        .map_err(|e| PackagingError::Other(format!("Failed to write {:?}: {}", path, e)))?;
+
+    let sig_path = update_signature_path();
+    match std::env::var("UPDATE_SIGN_KEY") {
+        Ok(seed) if !seed.is_empty() => {
+            let key = update_sign_key(&seed)?;
+            let signature = BASE64.encode(key.sign(&data).as_ref());
+            fs::write(&sig_path, signature + "\n")
+                .map_err(|e| PackagingError::Other(format!("Failed to write {:?}: {}", sig_path, e)))?;
+            tracing::info!("Signed update manifest, public key {}", BASE64.encode(key.public_key().as_ref()));
+        }
+        // A signature left from an earlier run would not match
+        _ => {
+            let _ = fs::remove_file(&sig_path);
+        }
+    }
+    Ok(())
+}
+
+/// Check `update-manifest.json` against the artifact it describes and, when
+/// `public_key` (base64 ed25519) is given, its signature. Returns the manifest.
+pub fn verify_update_manifest(public_key: Option<&str>) -> Result<JsonValue, PackagingError> {
+    let path = update_manifest_path();
+    let data = fs::read(&path)
+        .map_err(|e| PackagingError::Other(format!("Failed to read {:?}: {}", path, e)))?;
+    if let Some(public_key) = public_key {
+        let key = BASE64
+            .decode(public_key.trim())
+            .map_err(|e| PackagingError::Other(format!("Invalid public key: {}", e)))?;
+        let sig_path = update_signature_path();
+        let signature = fs::read_to_string(&sig_path)
+            .map_err(|e| PackagingError::Other(format!("Failed to read {:?}: {}", sig_path, e)))?;
+        let signature = BASE64
+            .decode(signature.trim())
+            .map_err(|e| PackagingError::Other(format!("Invalid signature: {}", e)))?;
+        UnparsedPublicKey::new(&ED25519, key)
+            .verify(&data, &signature)
+            .map_err(|_| PackagingError::Other("Update manifest signature does not match".into()))?;
+    }
+
+    let manifest: JsonValue = serde_json::from_slice(&data)
+        .map_err(|e| PackagingError::Other(format!("Failed to parse update manifest: {}", e)))?;
+    let field = |name: &str| {
+        manifest
+            .get(name)
+            .ok_or_else(|| PackagingError::Other(format!("Update manifest lacks `{}`", name)))
+    };
+    let version = workspace_version()?;
+    if field("version")?.as_str() != Some(version.as_str()) {
+        return Err(PackagingError::Other(format!("Update manifest is not for version {}", version)));
+    }
+    if field("platform")?.as_str() != Some(platform_name()) {
+        return Err(PackagingError::Other("Update manifest is for another platform".into()));
+    }
+    if field("arch")?.as_str() != Some(target_arch().as_str()) {
+        return Err(PackagingError::Other("Update manifest is for another architecture".into()));
+    }
+    field("released_at")?;
+    let name = field("artifact")?.as_str().unwrap_or_default();
+    let artifact = get_project_root().join("target").join(name);
+    let size = fs::metadata(&artifact)
+        .map_err(|e| PackagingError::Other(format!("Missing artifact {:?}: {}", artifact, e)))?
+        .len();
+    if field("size")?.as_u64() != Some(size) {
+        return Err(PackagingError::Other(format!("Size of {:?} does not match the manifest", artifact)));
+    }
+    if field("sha256")?.as_str() != Some(sha256_file(&artifact)?.as_str()) {
+        return Err(PackagingError::Other(format!("SHA256 of {:?} does not match the manifest", artifact)));
+    }
+    Ok(manifest)
 }
 
 /// Verify that all external tools required for creating an installer are
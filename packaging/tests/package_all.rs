@@ -1,5 +1,8 @@
 use packaging::package_all;
-use packaging::utils::{artifact_path, get_project_root, workspace_version};
+use packaging::utils::{
+    artifact_path, get_project_root, verify_update_manifest, workspace_version, zsync_path,
+    BUILD_INFO,
+};
 use serial_test::serial;
 use std::fs;
 
@@ -22,7 +25,12 @@ fn test_package_all_mock() {
         } else if format == "appimage" {
             let img_dir = root.join("target/appimage");
             fs::create_dir_all(&img_dir).unwrap();
-            fs::write(img_dir.join("dummy.AppImage"), b"test").unwrap();
+            let img = artifact_path(&workspace_version().unwrap());
+            fs::write(img_dir.join(img.file_name().unwrap()), b"test").unwrap();
+        } else if format == "flatpak" {
+            let flatpak_dir = root.join("target/flatpak");
+            fs::create_dir_all(&flatpak_dir).unwrap();
+            fs::write(flatpak_dir.join("GooglePicz.flatpak"), b"test").unwrap();
         } else {
             let deb_dir = root.join("target/debian");
             fs::create_dir_all(&deb_dir).unwrap();
@@ -52,6 +60,7 @@ fn test_package_all_mock() {
         assert!(file.exists(), "Expected {:?} to exist", file);
         let data = fs::read(&file).unwrap();
         assert_eq!(data, b"test");
+        verify_update_manifest(None).unwrap();
         fs::remove_file(file).unwrap();
     }
 
@@ -75,6 +84,8 @@ fn test_package_all_mock() {
 
     let checksums = root.join("checksums.txt");
     assert!(checksums.exists(), "Expected {:?} to exist", checksums);
+    let _ = fs::remove_file(checksums);
+    let _ = fs::remove_file(root.join(BUILD_INFO));
 
     if !use_real {
         std::env::remove_var("MOCK_COMMANDS");
@@ -85,7 +96,7 @@ fn test_package_all_mock() {
 #[test]
 #[serial]
 fn test_package_all_all_formats() {
-    let formats = ["deb", "rpm", "appimage"];
+    let formats = ["deb", "rpm", "appimage", "flatpak"];
     for fmt in &formats {
         std::env::set_var("MOCK_COMMANDS", "1");
         std::env::set_var("LINUX_PACKAGE_FORMAT", fmt);
@@ -101,7 +112,14 @@ fn test_package_all_all_formats() {
             "appimage" => {
                 let dir = root.join("target/appimage");
                 fs::create_dir_all(&dir).unwrap();
-                fs::write(dir.join("dummy.AppImage"), b"test").unwrap();
+                let built = dir.join(artifact_path(&workspace_version().unwrap()).file_name().unwrap());
+                fs::write(&built, b"test").unwrap();
+                fs::write(zsync_path(&built), b"zsync").unwrap();
+            }
+            "flatpak" => {
+                let dir = root.join("target/flatpak");
+                fs::create_dir_all(&dir).unwrap();
+                fs::write(dir.join("GooglePicz.flatpak"), b"test").unwrap();
             }
             _ => {
                 let dir = root.join("target/debian");
@@ -116,7 +134,11 @@ fn test_package_all_all_formats() {
         let artifact = artifact_path(&version);
         assert!(artifact.exists());
         fs::remove_file(&artifact).unwrap();
+        if *fmt == "appimage" {
+            fs::remove_file(zsync_path(&artifact)).unwrap();
+        }
         let _ = fs::remove_file(root.join("checksums.txt"));
+        let _ = fs::remove_file(root.join(BUILD_INFO));
 
         std::env::remove_var("MOCK_COMMANDS");
         std::env::remove_var("LINUX_PACKAGE_FORMAT");
@@ -1,5 +1,5 @@
 use assert_cmd::Command;
-use packaging::utils::{artifact_path, get_project_root, workspace_version};
+use packaging::utils::{artifact_path, get_project_root, workspace_version, zsync_path};
 use serial_test::serial;
 use std::fs;
 
@@ -49,7 +49,10 @@ fn test_packager_cli_appimage() -> Result<(), Box<dyn std::error::Error>> {
     {
         let img_dir = root.join("target/appimage");
         fs::create_dir_all(&img_dir)?;
-        fs::write(img_dir.join("dummy.AppImage"), b"test")?;
+        let img = artifact_path(&workspace_version()?);
+        let built = img_dir.join(img.file_name().unwrap());
+        fs::write(&built, b"test")?;
+        fs::write(zsync_path(&built), b"zsync")?;
     }
 
     Command::cargo_bin("packager")?
@@ -63,6 +66,8 @@ fn test_packager_cli_appimage() -> Result<(), Box<dyn std::error::Error>> {
         let version = workspace_version()?;
         let img = artifact_path(&version);
         assert!(img.exists());
+        assert!(zsync_path(&img).exists());
+        fs::remove_file(zsync_path(&img))?;
         fs::remove_file(img)?;
     }
 
@@ -70,3 +75,73 @@ fn test_packager_cli_appimage() -> Result<(), Box<dyn std::error::Error>> {
     std::env::remove_var("LINUX_PACKAGE_FORMAT");
     Ok(())
 }
+
+#[test]
+#[serial]
+fn test_packager_cli_flatpak() -> Result<(), Box<dyn std::error::Error>> {
+    std::env::set_var("MOCK_COMMANDS", "1");
+    std::env::set_var("LINUX_PACKAGE_FORMAT", "flatpak");
+    let root = get_project_root();
+
+    #[cfg(target_os = "linux")]
+    {
+        let flatpak_dir = root.join("target/flatpak");
+        fs::create_dir_all(&flatpak_dir)?;
+        fs::write(flatpak_dir.join("GooglePicz.flatpak"), b"test")?;
+    }
+
+    Command::cargo_bin("packager")?
+        .arg("--format")
+        .arg("flatpak")
+        .assert()
+        .success();
+
+    #[cfg(target_os = "linux")]
+    {
+        let version = workspace_version()?;
+        let bundle = artifact_path(&version);
+        assert!(bundle.exists());
+        assert!(root.join("target/flatpak/org.googlepicz.GooglePicz.json").exists());
+        fs::remove_file(bundle)?;
+    }
+
+    std::env::remove_var("MOCK_COMMANDS");
+    std::env::remove_var("LINUX_PACKAGE_FORMAT");
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_packager_cli_arch() -> Result<(), Box<dyn std::error::Error>> {
+    std::env::set_var("MOCK_COMMANDS", "1");
+    std::env::set_var("LINUX_PACKAGE_FORMAT", "deb");
+    let root = get_project_root();
+
+    #[cfg(target_os = "linux")]
+    {
+        let deb_dir = root.join("target/aarch64-unknown-linux-gnu/debian");
+        fs::create_dir_all(&deb_dir)?;
+        fs::write(deb_dir.join("dummy.deb"), b"test")?;
+    }
+
+    Command::cargo_bin("packager")?
+        .arg("--arch")
+        .arg("aarch64")
+        .assert()
+        .success();
+
+    #[cfg(target_os = "linux")]
+    {
+        std::env::set_var("TARGET_ARCH", "aarch64");
+        let version = workspace_version()?;
+        let deb = artifact_path(&version);
+        std::env::remove_var("TARGET_ARCH");
+        assert!(deb.to_string_lossy().ends_with("-linux-aarch64.deb"));
+        assert!(deb.exists());
+        fs::remove_file(deb)?;
+    }
+
+    std::env::remove_var("MOCK_COMMANDS");
+    std::env::remove_var("LINUX_PACKAGE_FORMAT");
+    Ok(())
+}
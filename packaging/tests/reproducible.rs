@@ -0,0 +1,61 @@
+use packaging::package_all;
+use packaging::utils::{
+    artifact_path, get_project_root, update_manifest_path, workspace_version, zsync_path, BUILD_INFO,
+};
+use serial_test::serial;
+use std::fs;
+
+#[cfg(target_os = "linux")]
+#[test]
+#[serial]
+fn test_reproducible_package_all_twice() -> Result<(), Box<dyn std::error::Error>> {
+    std::env::set_var("MOCK_COMMANDS", "1");
+    std::env::set_var("LINUX_PACKAGE_FORMAT", "appimage");
+    std::env::set_var("REPRODUCIBLE_BUILD", "1");
+    std::env::remove_var("SOURCE_DATE_EPOCH");
+    std::env::set_var("RUSTFLAGS", "-C debuginfo=0");
+    let root = get_project_root();
+    let version = workspace_version()?;
+    let artifact = artifact_path(&version);
+    let built = root.join("target/appimage").join(artifact.file_name().unwrap());
+
+    let mut runs = Vec::new();
+    for _ in 0..2 {
+        fs::create_dir_all(built.parent().unwrap())?;
+        fs::write(&built, b"test")?;
+        fs::write(zsync_path(&built), b"zsync")?;
+
+        package_all()?;
+
+        let desktop = root.join("target/appimage/GooglePicz.AppDir/org.googlepicz.GooglePicz.desktop");
+        let mtime = fs::metadata(desktop)?.modified()?;
+        runs.push((
+            fs::read_to_string(root.join("checksums.txt"))?,
+            fs::read_to_string(root.join(BUILD_INFO))?,
+            fs::read_to_string(update_manifest_path())?,
+            mtime,
+        ));
+        fs::remove_file(&artifact)?;
+        fs::remove_file(zsync_path(&artifact))?;
+    }
+
+    let epoch: u64 = std::env::var("SOURCE_DATE_EPOCH")?.parse()?;
+    assert_eq!(runs[0], runs[1]);
+    let (_, build_info, _, mtime) = &runs[0];
+    let build_info: serde_json::Value = serde_json::from_str(build_info)?;
+    assert_eq!(build_info["source_date_epoch"], epoch);
+    assert_eq!(build_info["version"], version);
+    assert!(build_info["rustc"].as_str().unwrap().starts_with("rustc "));
+    // The path remap holds the checkout path and is left out
+    assert_eq!(build_info["rustflags"], "-C debuginfo=0");
+    assert_eq!(*mtime, std::time::UNIX_EPOCH + std::time::Duration::from_secs(epoch));
+
+    let _ = fs::remove_file(root.join("checksums.txt"));
+    let _ = fs::remove_file(root.join(BUILD_INFO));
+    std::env::remove_var("MOCK_COMMANDS");
+    std::env::remove_var("LINUX_PACKAGE_FORMAT");
+    std::env::remove_var("REPRODUCIBLE_BUILD");
+    std::env::remove_var("SOURCE_DATE_EPOCH");
+    std::env::remove_var("RUSTFLAGS");
+    Ok(())
+}
@@ -0,0 +1,64 @@
+use packaging::utils::{
+    artifact_path, update_manifest_path, update_public_key, update_signature_path, verify_update_manifest,
+    workspace_version, write_update_manifest,
+};
+use serial_test::serial;
+use std::fs;
+
+const SEED: &str = "BwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwc=";
+const OTHER_SEED: &str = "CAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAg=";
+
+#[test]
+#[serial]
+fn test_update_manifest_describes_artifact() -> Result<(), Box<dyn std::error::Error>> {
+    std::env::remove_var("UPDATE_SIGN_KEY");
+    let version = workspace_version()?;
+    let artifact = artifact_path(&version);
+    fs::create_dir_all(artifact.parent().unwrap())?;
+    fs::write(&artifact, b"test")?;
+
+    write_update_manifest()?;
+    assert!(!update_signature_path().exists());
+    let manifest = verify_update_manifest(None)?;
+    assert_eq!(manifest["version"], version.as_str());
+    assert_eq!(manifest["artifact"], artifact.file_name().unwrap().to_str().unwrap());
+    assert_eq!(manifest["size"], 4);
+    assert_eq!(
+        manifest["sha256"],
+        "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08"
+    );
+
+    // A rebuilt artifact no longer matches
+    fs::write(&artifact, b"changed")?;
+    assert!(verify_update_manifest(None).is_err());
+
+    fs::remove_file(artifact)?;
+    fs::remove_file(update_manifest_path())?;
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_update_manifest_signature() -> Result<(), Box<dyn std::error::Error>> {
+    std::env::set_var("UPDATE_SIGN_KEY", SEED);
+    let version = workspace_version()?;
+    let artifact = artifact_path(&version);
+    fs::create_dir_all(artifact.parent().unwrap())?;
+    fs::write(&artifact, b"test")?;
+
+    write_update_manifest()?;
+    std::env::remove_var("UPDATE_SIGN_KEY");
+    assert!(update_signature_path().exists());
+    verify_update_manifest(Some(&update_public_key(SEED)?))?;
+    assert!(verify_update_manifest(Some(&update_public_key(OTHER_SEED)?)).is_err());
+
+    // Editing the manifest breaks the signature
+    let edited = fs::read_to_string(update_manifest_path())?.replace("\"size\": 4", "\"size\": 4 ");
+    fs::write(update_manifest_path(), edited)?;
+    assert!(verify_update_manifest(Some(&update_public_key(SEED)?)).is_err());
+
+    fs::remove_file(artifact)?;
+    fs::remove_file(update_manifest_path())?;
+    fs::remove_file(update_signature_path())?;
+    Ok(())
+}
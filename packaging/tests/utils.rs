@@ -5,6 +5,10 @@ use packaging::utils::{
     verify_artifact_names,
     write_checksums,
     artifact_path,
+    artifact_paths,
+    release_dir,
+    target_triple,
+    BUILD_INFO,
 };
 use serial_test::serial;
 use toml::Value;
@@ -70,7 +74,66 @@ fn test_write_checksums() -> Result<(), Box<dyn std::error::Error>> {
 
     let checksums = std::fs::read_to_string(root.join("checksums.txt"))?;
     assert!(checksums.contains(path.file_name().unwrap().to_str().unwrap()));
+    assert!(root.join(BUILD_INFO).exists());
 
     std::fs::remove_file(path)?;
+    std::fs::remove_file(root.join("checksums.txt"))?;
+    std::fs::remove_file(root.join(BUILD_INFO))?;
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_artifact_paths_cover_windows_formats() -> Result<(), Box<dyn std::error::Error>> {
+    let version = workspace_version()?;
+    std::env::set_var("WINDOWS_PACKAGE_FORMAT", "msi");
+    let msi = artifact_path(&version);
+    std::env::remove_var("WINDOWS_PACKAGE_FORMAT");
+    let paths = artifact_paths(&version);
+    assert!(paths.contains(&msi));
+    assert!(paths.contains(&artifact_path(&version)));
+
+    if cfg!(target_os = "windows") {
+        assert_eq!(msi.extension().unwrap(), "msi");
+        assert_eq!(artifact_path(&version).extension().unwrap(), "exe");
+        for path in &paths {
+            std::fs::write(path, b"test")?;
+        }
+        write_checksums()?;
+        let checksums = std::fs::read_to_string(get_project_root().join("checksums.txt"))?;
+        for path in &paths {
+            assert!(checksums.contains(path.file_name().unwrap().to_str().unwrap()));
+            std::fs::remove_file(path)?;
+        }
+        std::fs::remove_file(get_project_root().join("checksums.txt"))?;
+        std::fs::remove_file(get_project_root().join(BUILD_INFO))?;
+    }
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_artifact_path_embeds_target_arch() -> Result<(), Box<dyn std::error::Error>> {
+    let version = workspace_version()?;
+    let root = get_project_root();
+
+    std::env::remove_var("TARGET_ARCH");
+    let native = artifact_path(&version);
+    let name = native.file_name().unwrap().to_str().unwrap().to_string();
+    assert!(name.contains(&format!("-{}.", std::env::consts::ARCH)));
+    assert_eq!(target_triple(), None);
+    assert_eq!(release_dir(), root.join("target/release"));
+
+    std::env::set_var("TARGET_ARCH", "aarch64");
+    let cross = artifact_path(&version);
+    let triple = target_triple().unwrap();
+    let release = release_dir();
+    std::env::remove_var("TARGET_ARCH");
+
+    let name = cross.file_name().unwrap().to_str().unwrap();
+    assert!(name.starts_with(&format!("GooglePicz-{}-", version)));
+    assert!(name.contains("-aarch64."));
+    assert!(triple.starts_with("aarch64-"));
+    assert_eq!(release, root.join("target").join(&triple).join("release"));
     Ok(())
 }
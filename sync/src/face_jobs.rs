@@ -0,0 +1,112 @@
+//! Face detection for the photos a sync run inserted or changed.
+//!
+//! Each job carries only the fields detection reads, moved out of the synced
+//! item rather than cloned from it. One worker on a blocking thread keeps the
+//! detector, the cache handle and the error senders for the whole run and
+//! takes jobs from a bounded queue, so a slow detector holds up paging instead
+//! of letting work pile up.
+
+use api_client::MediaItem;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+/// Jobs waiting for the worker before `FaceWorker::push` waits too.
+pub const FACE_QUEUE_CAPACITY: usize = 32;
+
+/// What face detection needs to know about a synced item.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FaceJob {
+    pub id: String,
+    pub base_url: String,
+    pub mime_type: String,
+}
+
+impl From<MediaItem> for FaceJob {
+    fn from(item: MediaItem) -> Self {
+        Self { id: item.id, base_url: item.base_url, mime_type: item.mime_type }
+    }
+}
+
+/// Detects and stores the faces of one job, on the worker's thread.
+pub type FaceJobFn = Box<dyn FnMut(&FaceJob) -> Result<(), String> + Send>;
+
+/// What a `FaceWorker` did with the jobs it was given.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FaceWorkerReport {
+    pub completed: u64,
+    pub failed: u64,
+    /// Videos, which face detection leaves alone like the background scan does
+    pub skipped: u64,
+}
+
+impl FaceWorkerReport {
+    /// Jobs the worker is done with, whatever the outcome.
+    pub fn processed(&self) -> u64 {
+        self.completed + self.failed + self.skipped
+    }
+}
+
+pub struct FaceWorker {
+    queue: mpsc::Sender<FaceJob>,
+    queued: Arc<AtomicU64>,
+    handle: JoinHandle<FaceWorkerReport>,
+}
+
+impl FaceWorker {
+    /// Start a worker running `detect` on each queued photo. `on_error` is
+    /// told about every job that fails.
+    pub fn spawn(detect: FaceJobFn, on_error: impl Fn(String) + Send + 'static) -> Self {
+        Self::spawn_with_progress(detect, on_error, |_, _| {})
+    }
+
+    /// Like `spawn`, also calling `on_progress` after each job with the jobs
+    /// done so far and the jobs queued so far.
+    pub fn spawn_with_progress(
+        mut detect: FaceJobFn,
+        on_error: impl Fn(String) + Send + 'static,
+        on_progress: impl Fn(u64, u64) + Send + 'static,
+    ) -> Self {
+        let (queue, mut jobs) = mpsc::channel::<FaceJob>(FACE_QUEUE_CAPACITY);
+        let queued = Arc::new(AtomicU64::new(0));
+        let total = queued.clone();
+        let handle = tokio::task::spawn_blocking(move || {
+            let mut report = FaceWorkerReport::default();
+            while let Some(job) = jobs.blocking_recv() {
+                if !job.mime_type.starts_with("image/") {
+                    report.skipped += 1;
+                } else {
+                    let result = detect(&job);
+                    crate::metrics::record_face_job_completed(result.is_ok());
+                    match result {
+                        Ok(()) => report.completed += 1,
+                        Err(e) => {
+                            report.failed += 1;
+                            tracing::error!(error = %e, id = %job.id, "Face detection failed");
+                            on_error(format!("Face detection failed: {}", e));
+                        }
+                    }
+                }
+                on_progress(report.processed(), total.load(Ordering::Relaxed));
+            }
+            report
+        });
+        Self { queue, queued, handle }
+    }
+
+    /// Queue `job`, waiting while the queue is full. False once the worker
+    /// has stopped.
+    pub async fn push(&self, job: FaceJob) -> bool {
+        crate::metrics::record_face_jobs_queued(1);
+        // Counted before sending so progress never has more done than queued
+        self.queued.fetch_add(1, Ordering::Relaxed);
+        self.queue.send(job).await.is_ok()
+    }
+
+    /// Wait until every queued job is done.
+    pub async fn finish(self) -> FaceWorkerReport {
+        drop(self.queue);
+        self.handle.await.unwrap_or_default()
+    }
+}
@@ -0,0 +1,230 @@
+//! Background face detection over photos cached without faces, for example
+//! those synced before face detection was turned on.
+//!
+//! Photos are scanned in id order, a few at a time, from thumbnails sized for
+//! detection. A checkpoint in the cache lets an interrupted scan resume where
+//! it stopped instead of starting over.
+
+use std::sync::Arc;
+
+use api_client::{ApiClient, MediaItem};
+use auth::ensure_access_token_valid;
+use cache::{CacheManager, FaceData, FaceScanCheckpoint};
+use tokio::sync::mpsc;
+use tokio::task::JoinSet;
+
+use crate::{SyncError, SyncProgress};
+
+/// Longest side of the thumbnails downloaded for detection.
+pub const DETECTION_SIZE: u32 = 1024;
+/// Photos downloaded and scanned at the same time.
+pub const DEFAULT_SCAN_CONCURRENCY: usize = 4;
+
+/// Faces found in one photo, with boxes in full size pixels.
+#[derive(Debug, Clone, Default)]
+pub struct Detection {
+    pub faces: Vec<FaceData>,
+    /// One per face, `None` when no embedding model is installed
+    pub embeddings: Option<Vec<Vec<f32>>>,
+}
+
+/// Finds the faces in the downloaded thumbnail of a media item. Runs on a
+/// blocking thread.
+pub type DetectFn = Arc<dyn Fn(&MediaItem, &[u8]) -> Result<Detection, String> + Send + Sync>;
+
+/// Summary of a `FaceScanner::run`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FaceScanReport {
+    pub scanned: u64,
+    pub faces: u64,
+    /// Photos that could not be downloaded or scanned; the next run retries them
+    pub failed: u64,
+}
+
+pub struct FaceScanner {
+    cache: CacheManager,
+    http: reqwest::Client,
+    api_client: Option<ApiClient>,
+    concurrency: usize,
+    detect: DetectFn,
+}
+
+impl FaceScanner {
+    /// Scanner detecting faces with `recognizer`.
+    #[cfg(feature = "face-recognition")]
+    pub fn new(cache: CacheManager, recognizer: face_recognition::FaceRecognizer) -> Self {
+        Self::with_detector(
+            cache,
+            Arc::new(move |item: &MediaItem, bytes: &[u8]| {
+                let width = item.media_metadata.width.parse().unwrap_or(0);
+                let (faces, embeddings) = recognizer.detect_scaled(bytes, width).map_err(|e| e.to_string())?;
+                Ok(Detection {
+                    faces: faces
+                        .into_iter()
+                        .map(|f| FaceData { bbox: f.bbox, name: None, ignored: f.ignored, confidence: f.confidence })
+                        .collect(),
+                    embeddings,
+                })
+            }),
+        )
+    }
+
+    pub fn with_detector(cache: CacheManager, detect: DetectFn) -> Self {
+        Self {
+            cache,
+            http: reqwest::Client::new(),
+            api_client: None,
+            concurrency: DEFAULT_SCAN_CONCURRENCY,
+            detect,
+        }
+    }
+
+    /// Refresh the cached base URLs, which expire after an hour, before each batch.
+    pub fn with_api_client(mut self, api_client: ApiClient) -> Self {
+        self.api_client = Some(api_client);
+        self
+    }
+
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Scan every cached photo without faces, resuming an interrupted scan.
+    /// Sends `SyncProgress::FacesScanned` after each batch and clusters the
+    /// unnamed faces once done.
+    pub async fn run(
+        &mut self,
+        progress: Option<mpsc::UnboundedSender<SyncProgress>>,
+    ) -> Result<FaceScanReport, SyncError> {
+        let cache_err = |e: cache::CacheError| SyncError::CacheError(e.to_string());
+        let checkpoint = self.cache.get_face_scan_checkpoint_async().await.map_err(cache_err)?;
+        let (mut after, mut processed) = match checkpoint {
+            Some(c) => (Some(c.last_media_item_id), c.processed),
+            None => (None, 0),
+        };
+        let remaining = self
+            .cache
+            .count_media_items_without_faces_async(after.clone())
+            .await
+            .map_err(cache_err)?;
+        let total = processed + remaining;
+        tracing::info!(processed, total, "Starting face scan");
+        let send = |processed: u64| {
+            if let Some(tx) = &progress {
+                let _ = tx.send(SyncProgress::FacesScanned { processed, total });
+            }
+        };
+        send(processed);
+
+        let mut report = FaceScanReport::default();
+        loop {
+            let mut batch = self
+                .cache
+                .get_media_items_without_faces_async(after.clone(), self.concurrency)
+                .await
+                .map_err(cache_err)?;
+            let Some(last) = batch.last() else {
+                break;
+            };
+            let last_id = last.id.clone();
+            self.refresh_urls(&mut batch).await;
+
+            let count = batch.len() as u64;
+            crate::metrics::record_face_jobs_queued(count);
+            let mut tasks = JoinSet::new();
+            for item in batch {
+                tasks.spawn(scan_item(self.cache.clone(), self.http.clone(), self.detect.clone(), item));
+            }
+            while let Some(result) = tasks.join_next().await {
+                match result.map_err(|e| e.to_string()).and_then(|r| r) {
+                    Ok(faces) => {
+                        crate::metrics::record_face_job_completed(true);
+                        report.scanned += 1;
+                        report.faces += faces;
+                    }
+                    Err(e) => {
+                        crate::metrics::record_face_job_completed(false);
+                        report.failed += 1;
+                        tracing::warn!(error = %e, "Face scan failed for an item");
+                    }
+                }
+            }
+
+            processed += count;
+            let checkpoint = FaceScanCheckpoint { last_media_item_id: last_id.clone(), processed };
+            self.cache.set_face_scan_checkpoint_async(checkpoint).await.map_err(cache_err)?;
+            after = Some(last_id);
+            send(processed.min(total));
+        }
+
+        self.cache.clear_face_scan_checkpoint_async().await.map_err(cache_err)?;
+        if report.faces > 0 {
+            if let Err(e) = self
+                .cache
+                .cluster_unnamed_faces_async(cache::DEFAULT_CLUSTER_THRESHOLD)
+                .await
+            {
+                tracing::warn!(error = ?e, "Failed to cluster faces");
+            }
+        }
+        tracing::info!(?report, "Face scan complete");
+        Ok(report)
+    }
+
+    async fn refresh_urls(&mut self, batch: &mut [MediaItem]) {
+        let Some(api_client) = self.api_client.as_mut() else {
+            return;
+        };
+        match ensure_access_token_valid().await {
+            Ok(token) => api_client.set_access_token(token),
+            Err(e) => tracing::warn!(error = ?e, "Failed to refresh access token for face scan"),
+        }
+        let ids: Vec<String> = batch.iter().map(|i| i.id.clone()).collect();
+        match api_client.batch_get_media_items(&ids).await {
+            Ok(fresh) => {
+                for item in batch.iter_mut() {
+                    if let Some(f) = fresh.iter().find(|f| f.id == item.id) {
+                        item.base_url = f.base_url.clone();
+                    }
+                }
+            }
+            Err(e) => tracing::warn!(error = ?e, "Failed to refresh base URLs, using cached ones"),
+        }
+    }
+}
+
+async fn download(http: &reqwest::Client, item: &MediaItem) -> Result<Vec<u8>, String> {
+    if let Some(path) = item.base_url.strip_prefix("file://") {
+        return tokio::fs::read(path).await.map_err(|e| e.to_string());
+    }
+    let url = format!("{}=w{}-h{}", item.base_url, DETECTION_SIZE, DETECTION_SIZE);
+    let response = http
+        .get(&url)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| e.to_string())?;
+    response.bytes().await.map(|b| b.to_vec()).map_err(|e| e.to_string())
+}
+
+/// Download, detect and store the faces of one item; returns how many were found.
+async fn scan_item(
+    cache: CacheManager,
+    http: reqwest::Client,
+    detect: DetectFn,
+    item: MediaItem,
+) -> Result<u64, String> {
+    let bytes = download(&http, &item).await?;
+    tokio::task::spawn_blocking(move || {
+        let detection = detect(&item, &bytes)?;
+        let json = serde_json::to_string(&detection.faces).map_err(|e| e.to_string())?;
+        cache.insert_faces(&item.id, &json).map_err(|e| e.to_string())?;
+        if let Some(embeddings) = &detection.embeddings {
+            cache.set_face_embeddings(&item.id, embeddings).map_err(|e| e.to_string())?;
+        }
+        Ok(detection.faces.len() as u64)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
@@ -2,19 +2,32 @@
 #![warn(rust_2018_idioms)]
 //! Synchronization module for Google Photos data.
 
-use api_client::ApiClient;
-use auth::ensure_access_token_valid;
-use cache::CacheManager;
+use api_client::{ApiClient, ApiClientError, TransferStats};
+use auth::ensure_access_token_valid_for;
+use cache::{CacheManager, Mutation};
 use chrono::{DateTime, Datelike, Utc};
 use serde_json::json;
 #[cfg(feature = "face-recognition")]
 use face_recognition::FaceRecognizer;
+#[cfg(feature = "face-recognition")]
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
 use thiserror::Error;
 use std::path::{Path, PathBuf};
-use tokio::sync::{mpsc, oneshot};
+use tokio::sync::{mpsc, oneshot, watch};
 use tokio::task::{spawn_local, JoinHandle};
 use tokio::time::{sleep, Duration};
 
+mod face_jobs;
+mod face_scan;
+pub mod metrics;
+pub use face_jobs::{FaceJob, FaceJobFn, FaceWorker, FaceWorkerReport, FACE_QUEUE_CAPACITY};
+pub use face_scan::{
+    DetectFn, Detection, FaceScanReport, FaceScanner, DEFAULT_SCAN_CONCURRENCY, DETECTION_SIZE,
+};
+
 #[derive(Debug, Error)]
 pub enum SyncError {
     #[error("Authentication Error: {0}")]
@@ -27,11 +40,33 @@ pub enum SyncError {
     Other(String),
 }
 
+/// Number of consecutive fully unchanged pages after which a sync run stops.
+pub const DEFAULT_UNCHANGED_PAGE_LIMIT: u32 = 3;
+
+/// Consecutive empty pages that still carry a next page token after which a
+/// sync run stops paging.
+pub const MAX_EMPTY_PAGES: u32 = 10;
+
+/// Title of the app-created album that mirrors local favorites, since the
+/// Library API cannot set the favorite flag itself.
+pub const FAVORITES_ALBUM_TITLE: &str = "Favorites (GooglePicz)";
+
 pub struct Syncer {
     api_client: ApiClient,
     cache_manager: CacheManager,
     state_path: PathBuf,
     detect_faces: bool,
+    min_face_size: f32,
+    #[cfg(feature = "face-recognition")]
+    detector: face_recognition::DetectorConfig,
+    unchanged_page_limit: u32,
+    control: Option<mpsc::UnboundedReceiver<SyncControl>>,
+    /// Time between periodic runs, see `SyncControl::SetInterval`
+    interval: Duration,
+    /// Mirror favorites into `FAVORITES_ALBUM_TITLE` and read them back
+    favorites_album_sync: bool,
+    /// Profile whose tokens the API calls use, see `profile_watch`
+    profile: watch::Sender<Option<String>>,
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Default)]
@@ -39,16 +74,82 @@ struct SyncState {
     page_token: Option<String>,
     total_synced: u64,
     last_success: Option<DateTime<Utc>>,
+    #[serde(default)]
+    unchanged_streak: u32,
+    /// Album mirroring favorites, created on first use
+    #[serde(default)]
+    favorites_album_id: Option<String>,
+    /// Network usage of the last completed run
+    #[serde(default)]
+    last_transfer: TransferStats,
+}
+
+/// Network usage of the last completed sync of the cache at `db_path`.
+pub fn last_transfer_stats(db_path: &Path) -> Option<TransferStats> {
+    let data = std::fs::read_to_string(db_path.with_extension("state.json")).ok()?;
+    let state: SyncState = serde_json::from_str(&data).ok()?;
+    state.last_success.map(|_| state.last_transfer)
+}
+
+/// Summary of a single `sync_media_items` run.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct SyncReport {
+    pub fetched: u64,
+    pub inserted: u64,
+    pub updated: u64,
+    pub skipped: u64,
+    pub pages: u64,
+    pub unchanged_streak: u32,
+    pub stopped_early: bool,
+    /// Items cached for the first time in this run
+    #[serde(default)]
+    pub inserted_ids: Vec<String>,
+    /// Network usage of this run
+    #[serde(default)]
+    pub transfer: TransferStats,
+}
+
+/// Part of a sync run reported by `SyncProgress::Progress`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncStage {
+    MediaItems,
+    Faces,
 }
 
 #[derive(Debug, Clone)]
 pub enum SyncProgress {
     Started,
     ItemSynced(u64),
+    /// Emitted after each page; `estimated_total` is the size of the cached library.
+    Progress {
+        stage: SyncStage,
+        processed: u64,
+        estimated_total: Option<u64>,
+        page: u64,
+    },
     Retrying(u64),
+    /// Summary of a completed run, sent just before `Finished`.
+    Report(SyncReport),
+    /// Sent by `FaceScanner` after each batch, independently of sync runs.
+    FacesScanned { processed: u64, total: u64 },
     Finished(u64),
 }
 
+/// Requests accepted by the periodic sync task between runs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyncControl {
+    /// Start the next run now instead of waiting for the interval or retry.
+    SyncNow,
+    /// Hold off further runs until `Resume` or `SyncNow`; a running sync finishes first.
+    Pause,
+    Resume,
+    /// Use a new time between runs, rescheduling the run being waited for.
+    SetInterval(Duration),
+    /// Sync into the cache database at `db_path` with the tokens of `profile`
+    /// from now on, starting a run for the newly active account right away.
+    SwitchAccount { db_path: PathBuf, profile: Option<String> },
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SyncErrorCode {
     Auth,
@@ -71,6 +172,65 @@ pub enum SyncTaskError {
     Other { code: SyncErrorCode, message: String },
     #[error("Status update ({last_synced}): {message}")]
     Status { last_synced: DateTime<Utc>, message: String },
+    /// The refresh token was revoked or lacks permissions; syncing waits until
+    /// the user signs in again
+    #[error("Sign-in required: {0}")]
+    ReauthRequired(String),
+}
+
+/// Where a sync run or a background task reports to. Every channel is
+/// optional; when a UI channel is the main channel itself, each message is
+/// sent only once.
+#[derive(Clone, Default)]
+struct SyncChannels {
+    progress: Option<mpsc::UnboundedSender<SyncProgress>>,
+    errors: Option<mpsc::UnboundedSender<SyncTaskError>>,
+    status: Option<mpsc::UnboundedSender<SyncTaskError>>,
+    ui_progress: Option<mpsc::UnboundedSender<SyncProgress>>,
+    ui_error: Option<mpsc::UnboundedSender<SyncTaskError>>,
+}
+
+impl SyncChannels {
+    /// Send `value` on `main` and on `ui`, unless both are the same channel.
+    /// Fails only when `main` is closed.
+    fn send_once<T: Clone>(
+        main: &Option<mpsc::UnboundedSender<T>>,
+        ui: &Option<mpsc::UnboundedSender<T>>,
+        value: T,
+    ) -> Result<(), mpsc::error::SendError<T>> {
+        let shared = matches!((main, ui), (Some(main), Some(ui)) if main.same_channel(ui));
+        let sent = main.as_ref().map_or(Ok(()), |tx| tx.send(value.clone()));
+        if !shared {
+            Syncer::forward(ui, value);
+        }
+        sent
+    }
+
+    /// Report `err` on the error channel and the UI's.
+    fn error(&self, err: SyncTaskError) {
+        if let Err(send_err) = Self::send_once(&self.errors, &self.ui_error, err) {
+            tracing::error!(error = ?send_err, "Failed to forward sync error");
+        }
+    }
+
+    /// Report a `SyncTaskError::Other` with `code`.
+    fn fail(&self, code: SyncErrorCode, message: String) {
+        self.error(SyncTaskError::Other { code, message });
+    }
+
+    /// Like `error`, and also on the status channel.
+    fn status(&self, update: SyncTaskError) {
+        self.error(update.clone());
+        Syncer::forward(&self.status, update);
+    }
+
+    /// Report `progress`; a closed progress channel is reported as an error.
+    fn progress(&self, progress: SyncProgress) {
+        if let Err(send_err) = Self::send_once(&self.progress, &self.ui_progress, progress) {
+            tracing::error!(error = ?send_err, "Failed to send progress update");
+            self.fail(SyncErrorCode::Other, format!("Failed to send progress update: {}", send_err));
+        }
+    }
 }
 
 impl Syncer {
@@ -104,9 +264,16 @@ impl Syncer {
             SyncError::Other(format!("Failed to write state file: {}", e))
         })
     }
-    #[cfg_attr(feature = "trace-spans", tracing::instrument)]
+    /// Sync into `db_path` with the tokens of the profile named in the
+    /// environment, see `auth::active_profile`.
     pub async fn new(db_path: &Path) -> Result<Self, SyncError> {
-        let access_token = ensure_access_token_valid().await.map_err(|e| {
+        Self::new_for(db_path, auth::active_profile()).await
+    }
+
+    /// Like `new`, with the tokens of `profile`.
+    #[cfg_attr(feature = "trace-spans", tracing::instrument)]
+    pub async fn new_for(db_path: &Path, profile: Option<String>) -> Result<Self, SyncError> {
+        let access_token = ensure_access_token_valid_for(profile.as_deref()).await.map_err(|e| {
             SyncError::AuthenticationError(format!("Failed to get access token: {}", e))
         })?;
 
@@ -123,13 +290,343 @@ impl Syncer {
             cache_manager,
             state_path,
             detect_faces: false,
+            min_face_size: cache::DEFAULT_MIN_FACE_SIZE,
+            #[cfg(feature = "face-recognition")]
+            detector: face_recognition::DetectorConfig::default(),
+            unchanged_page_limit: DEFAULT_UNCHANGED_PAGE_LIMIT,
+            control: None,
+            interval: Duration::ZERO,
+            favorites_album_sync: false,
+            profile: watch::channel(profile).0,
         })
     }
 
+    /// Follows the profile the syncer uses, so `start_token_refresh_task_for`
+    /// keeps refreshing the right tokens across `SyncControl::SwitchAccount`.
+    pub fn profile_watch(&self) -> watch::Receiver<Option<String>> {
+        self.profile.subscribe()
+    }
+
+    fn profile(&self) -> Option<String> {
+        self.profile.borrow().clone()
+    }
+
     pub fn set_face_detection(&mut self, enable: bool) {
         self.detect_faces = enable;
     }
 
+    /// Faces covering less of the image than `fraction` are stored as ignored.
+    pub fn set_min_face_size(&mut self, fraction: f32) {
+        self.min_face_size = fraction;
+    }
+
+    /// Backend, model and threshold used when detecting faces during sync.
+    #[cfg(feature = "face-recognition")]
+    pub fn set_detector_config(&mut self, config: face_recognition::DetectorConfig) {
+        self.detector = config;
+    }
+
+    /// Stop a run after `limit` consecutive unchanged pages; `0` disables early stopping.
+    pub fn set_unchanged_page_limit(&mut self, limit: u32) {
+        self.unchanged_page_limit = limit;
+    }
+
+    /// Push queued favorite changes to the favorites album and rebuild
+    /// favorites from it on every sync.
+    pub fn set_favorites_album_sync(&mut self, enable: bool) {
+        self.favorites_album_sync = enable;
+    }
+
+    /// Let `start_periodic_sync` take `SyncControl` requests from `rx`.
+    pub fn set_control(&mut self, rx: mpsc::UnboundedReceiver<SyncControl>) {
+        self.control = Some(rx);
+    }
+
+    /// Wait `delay`, or the sync interval when `None`, before the next
+    /// periodic run, honouring control requests.
+    async fn wait_for_next_run(&mut self, delay: Option<Duration>) {
+        let start = tokio::time::Instant::now();
+        let mut deadline = start + delay.unwrap_or(self.interval);
+        let mut paused = false;
+        loop {
+            let Some(rx) = self.control.as_mut() else {
+                if !paused {
+                    tokio::time::sleep_until(deadline).await;
+                }
+                return;
+            };
+            let request = if paused {
+                rx.recv().await
+            } else {
+                tokio::select! {
+                    _ = tokio::time::sleep_until(deadline) => return,
+                    request = rx.recv() => request,
+                }
+            };
+            match request {
+                Some(SyncControl::SyncNow) => return,
+                Some(SyncControl::Pause) => {
+                    tracing::info!("Periodic sync paused");
+                    paused = true;
+                }
+                Some(SyncControl::Resume) => paused = false,
+                Some(SyncControl::SetInterval(interval)) => {
+                    tracing::info!(?interval, "Sync interval changed");
+                    self.interval = interval;
+                    // A retry after a failure keeps its backoff
+                    if delay.is_none() {
+                        deadline = start + interval;
+                    }
+                }
+                Some(SyncControl::SwitchAccount { db_path, profile }) => {
+                    if self.switch_account(db_path, profile) {
+                        return;
+                    }
+                }
+                None => {
+                    self.control = None;
+                    paused = false;
+                }
+            }
+        }
+    }
+
+    /// Wait until the active profile has signed in again, honouring control
+    /// requests like `wait_for_next_run`; switching to another account ends
+    /// the wait as well.
+    async fn wait_for_sign_in(&mut self) {
+        let profile = self.profile();
+        let signed_in = auth::wait_for_reauth_for(profile.as_deref());
+        tokio::pin!(signed_in);
+        let mut paused = false;
+        loop {
+            let request = match self.control.as_mut() {
+                Some(rx) => tokio::select! {
+                    _ = &mut signed_in, if !paused => return,
+                    request = rx.recv() => request,
+                },
+                None => return signed_in.await,
+            };
+            match request {
+                Some(SyncControl::SyncNow) => return,
+                Some(SyncControl::Pause) => paused = true,
+                Some(SyncControl::Resume) => paused = false,
+                Some(SyncControl::SetInterval(interval)) => self.interval = interval,
+                Some(SyncControl::SwitchAccount { db_path, profile }) => {
+                    if self.switch_account(db_path, profile) {
+                        return;
+                    }
+                }
+                None => self.control = None,
+            }
+        }
+    }
+
+    /// Sync into the cache at `db_path` with the tokens of `profile` from now
+    /// on. Keeps the current account when the cache cannot be opened.
+    fn switch_account(&mut self, db_path: PathBuf, profile: Option<String>) -> bool {
+        match CacheManager::new(&db_path) {
+            Ok(cache_manager) => {
+                tracing::info!(?db_path, ?profile, "Switched sync to another account");
+                self.cache_manager = cache_manager;
+                self.state_path = db_path.with_extension("state.json");
+                self.profile.send_replace(profile);
+                true
+            }
+            Err(e) => {
+                tracing::error!("Failed to open cache {:?}: {}", db_path, e);
+                false
+            }
+        }
+    }
+
+    /// Replay mutations queued while offline, oldest first.
+    ///
+    /// Stops at the first network failure so the remaining entries keep their
+    /// order; entries the API rejects are dropped. Returns the number applied.
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
+    pub async fn flush_mutations(&mut self) -> Result<usize, SyncError> {
+        let pending = self
+            .cache_manager
+            .pending_mutations_async()
+            .await
+            .map_err(|e| SyncError::CacheError(format!("Failed to load pending mutations: {}", e)))?;
+        if pending.is_empty() {
+            return Ok(0);
+        }
+        let token = ensure_access_token_valid_for(self.profile().as_deref()).await.map_err(|e| {
+            SyncError::AuthenticationError(format!("Failed to refresh token: {}", e))
+        })?;
+        self.api_client.set_access_token(token);
+
+        let mut applied = 0;
+        for entry in pending {
+            let result = match &entry.mutation {
+                Mutation::UpdateDescription { media_item_id, description } => self
+                    .api_client
+                    .update_media_item_description(media_item_id, description)
+                    .await
+                    .map(|_| ()),
+                Mutation::AddFavorites { media_item_ids } => match self.album_favorites(media_item_ids).await? {
+                    // Only uploads can go in the album; the rest stay local favorites
+                    ids if ids.is_empty() => Ok(()),
+                    ids => match self.favorites_album(true).await {
+                        Ok(Some(album_id)) => self.api_client.batch_add_media_items(&album_id, &ids).await,
+                        // Queued while the option was on; nothing to mirror into now
+                        Ok(None) => Ok(()),
+                        Err(e) => Err(e),
+                    },
+                },
+                Mutation::RemoveFavorites { media_item_ids } => match self.album_favorites(media_item_ids).await? {
+                    ids if ids.is_empty() => Ok(()),
+                    ids => match self.favorites_album(false).await {
+                        Ok(Some(album_id)) => self.api_client.batch_remove_media_items(&album_id, &ids).await,
+                        // Without an album there is nothing to take them out of
+                        Ok(None) => Ok(()),
+                        Err(e) => Err(e),
+                    },
+                },
+            };
+            match result {
+                Ok(()) => applied += 1,
+                Err(ApiClientError::RequestError(e)) => {
+                    tracing::warn!(error = %e, "Mutation replay interrupted, keeping queue");
+                    break;
+                }
+                Err(e) => {
+                    tracing::warn!(error = %e, mutation = ?entry.mutation, "Dropping rejected mutation");
+                }
+            }
+            self.cache_manager
+                .remove_mutation_async(entry.id)
+                .await
+                .map_err(|e| SyncError::CacheError(format!("Failed to remove mutation: {}", e)))?;
+        }
+        Ok(applied)
+    }
+
+    /// The favorites among `ids` that can go in the favorites album. Google
+    /// only accepts items uploaded through GooglePicz there and rejects a
+    /// whole batch for a single other item.
+    async fn album_favorites(&self, ids: &[String]) -> Result<Vec<String>, SyncError> {
+        self.cache_manager
+            .uploaded_media_item_ids_async(ids.to_vec())
+            .await
+            .map_err(|e| SyncError::CacheError(format!("Failed to look up uploads: {}", e)))
+    }
+
+    /// Id of the favorites album, if favorites album sync is on.
+    ///
+    /// Looks the album up by title when the state file does not know it, so a
+    /// wiped cache finds it again, and creates it only when `create` is set.
+    async fn favorites_album(&mut self, create: bool) -> Result<Option<String>, ApiClientError> {
+        if !self.favorites_album_sync {
+            return Ok(None);
+        }
+        let mut state = self.load_state().map_err(|e| ApiClientError::Other(e.to_string()))?;
+        if let Some(id) = state.favorites_album_id {
+            return Ok(Some(id));
+        }
+        let mut page_token = None;
+        let found = loop {
+            let (albums, next) = self.api_client.list_albums(50, page_token).await?;
+            if let Some(album) = albums.into_iter().find(|a| a.title.as_deref() == Some(FAVORITES_ALBUM_TITLE)) {
+                break Some(album.id);
+            }
+            if next.is_none() {
+                break None;
+            }
+            page_token = next;
+        };
+        let id = match found {
+            Some(id) => id,
+            None if create => {
+                tracing::info!("Creating the favorites album");
+                self.api_client.create_album(FAVORITES_ALBUM_TITLE).await?.id
+            }
+            None => return Ok(None),
+        };
+        state.favorites_album_id = Some(id.clone());
+        self.save_state(&state).map_err(|e| ApiClientError::Other(e.to_string()))?;
+        Ok(Some(id))
+    }
+
+    /// Rebuild the favorite flags from the favorites album, so they survive a
+    /// cache wipe and pick up changes made on other devices. Favorites on
+    /// items GooglePicz did not upload never reach the album and are kept.
+    ///
+    /// Before the album exists, the local favorites are pushed into a new one
+    /// instead. Skipped while favorite changes are still queued, since the
+    /// album does not reflect them yet. Returns the number of favorites.
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
+    pub async fn sync_favorites_album(&mut self) -> Result<usize, SyncError> {
+        if !self.favorites_album_sync {
+            return Ok(0);
+        }
+        let pending = self
+            .cache_manager
+            .pending_mutations_async()
+            .await
+            .map_err(|e| SyncError::CacheError(format!("Failed to load pending mutations: {}", e)))?;
+        if pending
+            .iter()
+            .any(|p| matches!(p.mutation, Mutation::AddFavorites { .. } | Mutation::RemoveFavorites { .. }))
+        {
+            tracing::info!("Favorite changes still queued, not reading the favorites album");
+            return Ok(0);
+        }
+        let album = self
+            .favorites_album(false)
+            .await
+            .map_err(|e| SyncError::ApiClientError(format!("Failed to find favorites album: {}", e)))?;
+        let Some(album_id) = album else {
+            let favorites: Vec<String> = self
+                .cache_manager
+                .get_favorite_media_items_async()
+                .await
+                .map_err(|e| SyncError::CacheError(format!("Failed to load favorites: {}", e)))?
+                .into_iter()
+                .map(|i| i.id)
+                .collect();
+            let local = self.album_favorites(&favorites).await?;
+            if local.is_empty() {
+                return Ok(0);
+            }
+            let Some(album_id) = self
+                .favorites_album(true)
+                .await
+                .map_err(|e| SyncError::ApiClientError(format!("Failed to create favorites album: {}", e)))?
+            else {
+                return Ok(0);
+            };
+            self.api_client
+                .batch_add_media_items(&album_id, &local)
+                .await
+                .map_err(|e| SyncError::ApiClientError(format!("Failed to fill favorites album: {}", e)))?;
+            return Ok(local.len());
+        };
+        let mut ids = Vec::new();
+        let mut page_token = None;
+        loop {
+            let (items, next) = self
+                .api_client
+                .get_album_media_items(&album_id, 100, page_token)
+                .await
+                .map_err(|e| SyncError::ApiClientError(format!("Failed to read favorites album: {}", e)))?;
+            ids.extend(items.into_iter().map(|i| i.id));
+            if next.is_none() {
+                break;
+            }
+            page_token = next;
+        }
+        let count = ids.len();
+        self.cache_manager
+            .replace_favorites_async(ids)
+            .await
+            .map_err(|e| SyncError::CacheError(format!("Failed to update favorites: {}", e)))?;
+        Ok(count)
+    }
+
     #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self, progress, error)))]
     pub async fn sync_media_items(
         &mut self,
@@ -137,58 +634,92 @@ impl Syncer {
         error: Option<mpsc::UnboundedSender<SyncTaskError>>,
         ui_progress: Option<mpsc::UnboundedSender<SyncProgress>>,
         ui_error: Option<mpsc::UnboundedSender<SyncTaskError>>,
-    ) -> Result<(), SyncError> {
+    ) -> Result<SyncReport, SyncError> {
+        let channels = SyncChannels {
+            progress,
+            errors: error,
+            status: None,
+            ui_progress,
+            ui_error,
+        };
+        self.sync_with(&channels).await
+    }
+
+    /// `sync_media_items` reporting to `channels`.
+    async fn sync_with(&mut self, channels: &SyncChannels) -> Result<SyncReport, SyncError> {
+        let started = std::time::Instant::now();
+        let result = self.run_sync(channels).await;
+        metrics::record_sync(&result, started.elapsed());
+        result
+    }
+
+    /// Face detection for one sync run, reporting failures on the error
+    /// channels and each finished job as `SyncStage::Faces` progress of the
+    /// page in `page`.
+    #[cfg(feature = "face-recognition")]
+    fn spawn_face_worker(&self, channels: SyncChannels, page: Arc<AtomicU64>) -> FaceWorker {
+        let recognizer = FaceRecognizer::new(self.detector.clone()).with_min_face_size(self.min_face_size);
+        let cache = self.cache_manager.clone();
+        let errors = channels.clone();
+        FaceWorker::spawn_with_progress(
+            Box::new(move |job: &FaceJob| {
+                recognizer
+                    .detect_and_cache_faces_at(&cache, &job.id, &job.base_url, true)
+                    .map(|_| ())
+                    .map_err(|e| e.to_string())
+            }),
+            move |message| errors.fail(SyncErrorCode::Other, message),
+            move |done, queued| {
+                channels.progress(SyncProgress::Progress {
+                    stage: SyncStage::Faces,
+                    processed: done,
+                    estimated_total: Some(queued),
+                    page: page.load(Ordering::Relaxed),
+                })
+            },
+        )
+    }
+
+    async fn run_sync(&mut self, channels: &SyncChannels) -> Result<SyncReport, SyncError> {
         tracing::info!("Starting media item synchronization...");
-        if let Some(tx) = &progress {
-            if let Err(e) = tx.send(SyncProgress::Started) {
-                if let Some(err_tx) = &error {
-                    let _ = err_tx.send(SyncTaskError::Other {
-                        code: SyncErrorCode::Other,
-                        message: format!("Failed to send progress: {}", e),
-                    });
-                }
-                Self::forward(&ui_error, SyncTaskError::Other {
-                    code: SyncErrorCode::Other,
-                    message: format!("Failed to send progress: {}", e),
-                });
-            }
-        }
-        Self::forward(&ui_progress, SyncProgress::Started);
-        let status = SyncTaskError::Status {
+        let transfer_start = self.api_client.stats();
+        channels.progress(SyncProgress::Started);
+        channels.error(SyncTaskError::Status {
             last_synced: Utc::now(),
             message: "Sync started".into(),
-        };
-        if let Some(tx) = &error {
-            let _ = tx.send(status.clone());
+        });
+        match self.flush_mutations().await {
+            Ok(0) => {}
+            Ok(n) => tracing::info!("Applied {} queued changes", n),
+            Err(e) => tracing::warn!(error = %e, "Failed to apply queued changes"),
         }
-        Self::forward(&ui_error, status.clone());
         let mut state = self.load_state().map_err(|e| {
             let msg = format!("Failed to load state: {}", e);
-            if let Some(tx) = &error {
-                let _ = tx.send(SyncTaskError::Other { code: SyncErrorCode::Other, message: msg.clone() });
-            }
-            Self::forward(&ui_error, SyncTaskError::Other { code: SyncErrorCode::Other, message: msg.clone() });
+            channels.fail(SyncErrorCode::Other, msg.clone());
             SyncError::Other(msg)
         })?;
         let mut page_token: Option<String> = state.page_token.clone();
+        let mut empty_pages: u32 = 0;
         let mut total_synced = state.total_synced;
+        let mut report = SyncReport::default();
+        state.unchanged_streak = 0;
+        #[cfg(feature = "face-recognition")]
+        let face_page = Arc::new(AtomicU64::new(0));
+        #[cfg(feature = "face-recognition")]
+        let face_worker = self
+            .detect_faces
+            .then(|| self.spawn_face_worker(channels.clone(), face_page.clone()));
+        let cache = self.cache_manager.clone();
+        let estimated_total = tokio::task::spawn_blocking(move || cache.get_library_stats())
+            .await
+            .ok()
+            .and_then(|r| r.ok())
+            .map(|stats| stats.item_count);
 
         let last_sync = match self.cache_manager.get_last_sync_async().await {
             Ok(ts) => ts,
             Err(e) => {
-                let msg = format!("Failed to get last sync time: {}", e);
-                if let Some(tx) = &error {
-                    if let Err(send_err) = tx.send(SyncTaskError::Other {
-                        code: SyncErrorCode::Cache,
-                        message: msg.clone(),
-                    }) {
-                        tracing::error!("Failed to forward error: {}", send_err);
-                    }
-                }
-                Self::forward(&ui_error, SyncTaskError::Other {
-                    code: SyncErrorCode::Cache,
-                    message: msg.clone(),
-                });
+                channels.fail(SyncErrorCode::Cache, format!("Failed to get last sync time: {}", e));
                 DateTime::<Utc>::from(std::time::SystemTime::UNIX_EPOCH)
             }
         };
@@ -205,20 +736,9 @@ impl Syncer {
         });
 
         loop {
-            let token = ensure_access_token_valid().await.map_err(|e| {
+            let token = ensure_access_token_valid_for(self.profile().as_deref()).await.map_err(|e| {
                 let msg = format!("Failed to refresh token: {}", e);
-                if let Some(tx) = &error {
-                    if let Err(send_err) = tx.send(SyncTaskError::Other {
-                        code: SyncErrorCode::Auth,
-                        message: msg.clone(),
-                    }) {
-                        tracing::error!("Failed to forward error: {}", send_err);
-                    }
-                }
-                Self::forward(&ui_error, SyncTaskError::Other {
-                    code: SyncErrorCode::Auth,
-                    message: msg.clone(),
-                });
+                channels.fail(SyncErrorCode::Auth, msg.clone());
                 SyncError::AuthenticationError(msg)
             })?;
             self.api_client.set_access_token(token);
@@ -229,97 +749,85 @@ impl Syncer {
                 .await
                 .map_err(|e| {
                     let msg = format!("Failed to list media items from API: {}", e);
-                    if let Some(tx) = &error {
-                        if let Err(send_err) = tx.send(SyncTaskError::Other {
-                            code: SyncErrorCode::Network,
-                            message: msg.clone(),
-                        }) {
-                            tracing::error!("Failed to forward error: {}", send_err);
-                        }
-                    }
-                    Self::forward(&ui_error, SyncTaskError::Other {
-                        code: SyncErrorCode::Network,
-                        message: msg.clone(),
-                    });
+                    channels.fail(SyncErrorCode::Network, msg.clone());
                     SyncError::ApiClientError(msg)
                 })?;
 
             if media_items.is_empty() {
-                break;
+                // The API sometimes sends an empty page with more to follow, mostly with date filters
+                let Some(next) = next_page_token else { break };
+                empty_pages += 1;
+                if empty_pages >= MAX_EMPTY_PAGES {
+                    // Not a finished sync: keep the token so the next run resumes here
+                    // and leave last_sync alone, or the rest of the library is skipped
+                    tracing::warn!(empty_pages, "Stopping sync after consecutive empty pages");
+                    state.page_token = Some(next);
+                    state.total_synced = total_synced;
+                    if let Err(e) = self.save_state(&state) {
+                        tracing::error!(error = ?e, "Failed to save state");
+                    }
+                    let msg = format!("Sync incomplete: {} empty pages in a row from the API", empty_pages);
+                    channels.fail(SyncErrorCode::Network, msg.clone());
+                    return Err(SyncError::ApiClientError(msg));
+                }
+                tracing::debug!(empty_pages, "Empty page with a next page token");
+                page_token = Some(next);
+                sleep(Duration::from_millis(500)).await;
+                continue;
             }
+            empty_pages = 0;
 
-            for item in media_items {
-                self.cache_manager
-                    .insert_media_item_async(item.clone())
-                    .await
-                    .map_err(|e| {
-                        let msg = format!("Failed to insert media item into cache: {}", e);
-                        if let Some(tx) = &error {
-                            if let Err(send_err) = tx.send(SyncTaskError::Other {
-                                code: SyncErrorCode::Cache,
-                                message: msg.clone(),
-                            }) {
-                                tracing::error!("Failed to forward error: {}", send_err);
-                            }
-                        }
-                        Self::forward(&ui_error, SyncTaskError::Other {
-                            code: SyncErrorCode::Cache,
-                            message: msg.clone(),
-                        });
-                        SyncError::CacheError(msg)
-                    })?;
+            let stats = self
+                .cache_manager
+                .upsert_media_items_batch_async(media_items.clone())
+                .await
+                .map_err(|e| {
+                    let msg = format!("Failed to insert media items into cache: {}", e);
+                    channels.fail(SyncErrorCode::Cache, msg.clone());
+                    SyncError::CacheError(msg)
+                })?;
+            report.pages += 1;
+            report.fetched += media_items.len() as u64;
+            report.inserted += stats.inserted;
+            report.inserted_ids.extend(stats.inserted_ids.iter().cloned());
+            report.updated += stats.updated;
+            report.skipped += stats.unchanged;
+            tracing::info!(
+                inserted = stats.inserted,
+                updated = stats.updated,
+                unchanged = stats.unchanged,
+                "Processed page {}",
+                report.pages
+            );
+            if stats.changed_ids.is_empty() {
+                state.unchanged_streak += 1;
+            } else {
+                state.unchanged_streak = 0;
+            }
+            report.unchanged_streak = state.unchanged_streak;
+            channels.progress(SyncProgress::Progress {
+                stage: SyncStage::MediaItems,
+                processed: report.fetched,
+                estimated_total,
+                page: report.pages,
+            });
+
+            for _ in 0..media_items.len() {
                 total_synced += 1;
-                if let Some(tx) = &progress {
-                    if let Err(e) = tx.send(SyncProgress::ItemSynced(total_synced)) {
-                        if let Some(err) = &error {
-                            if let Err(send_err) = err.send(SyncTaskError::Other {
-                                code: SyncErrorCode::Other,
-                                message: format!("Failed to send progress update: {}", e),
-                            }) {
-                                tracing::error!("Failed to forward error: {}", send_err);
-                            }
-                        }
-                    }
-                }
-                Self::forward(&ui_progress, SyncProgress::ItemSynced(total_synced));
+                channels.progress(SyncProgress::ItemSynced(total_synced));
                 if total_synced % 50 == 0 {
-                    let status = SyncTaskError::Status {
+                    channels.error(SyncTaskError::Status {
                         last_synced: Utc::now(),
                         message: format!("Synced {total_synced} items"),
-                    };
-                    if let Some(tx) = &error {
-                        let _ = tx.send(status.clone());
-                    }
-                    Self::forward(&ui_error, status);
+                    });
                 }
+            }
 
-                #[cfg(feature = "face-recognition")]
-                if self.detect_faces {
-                    let cache = self.cache_manager.clone();
-                    let item_clone = item.clone();
-                    let err_tx = error.clone();
-                    let ui_err = ui_error.clone();
-                    tokio::task::spawn_blocking(move || {
-                        let rec = face_recognition::FaceRecognizer::new();
-                        if let Err(e) = rec.detect_and_cache_faces(&cache, &item_clone, true) {
-                            let msg = format!("Face detection failed: {}", e);
-                            if let Some(tx) = &err_tx {
-                                let _ = tx.send(SyncTaskError::Other {
-                                    code: SyncErrorCode::Other,
-                                    message: msg.clone(),
-                                });
-                            }
-                            if let Some(tx) = &ui_err {
-                                let _ = tx.send(SyncTaskError::Other {
-                                    code: SyncErrorCode::Other,
-                                    message: msg.clone(),
-                                });
-                            }
-                            tracing::error!(error = ?e, "Face detection failed");
-                        }
-                    })
-                    .await
-                    .ok();
+            #[cfg(feature = "face-recognition")]
+            if let Some(worker) = &face_worker {
+                face_page.store(report.pages, Ordering::Relaxed);
+                for item in media_items.into_iter().filter(|i| stats.changed_ids.contains(&i.id)) {
+                    worker.push(FaceJob::from(item)).await;
                 }
             }
 
@@ -329,41 +837,61 @@ impl Syncer {
             state.total_synced = total_synced;
             if let Err(e) = self.save_state(&state) {
                 tracing::error!(error = ?e, "Failed to save state");
-                if let Some(tx) = &error {
-                    let _ = tx.send(SyncTaskError::Other { code: SyncErrorCode::Other, message: e.to_string() });
-                }
-                Self::forward(&ui_error, SyncTaskError::Other { code: SyncErrorCode::Other, message: e.to_string() });
+                channels.fail(SyncErrorCode::Other, e.to_string());
             }
 
             if next_page_token.is_none() {
                 break;
             }
+            if self.unchanged_page_limit > 0 && state.unchanged_streak >= self.unchanged_page_limit {
+                tracing::info!(
+                    streak = state.unchanged_streak,
+                    "Stopping sync early after unchanged pages"
+                );
+                report.stopped_early = true;
+                break;
+            }
             page_token = next_page_token;
 
             // Be a good API citizen: wait a bit between pages
             sleep(Duration::from_millis(500)).await;
         }
 
+        // Once per sync rather than per page, since every pair of faces is compared
+        #[cfg(feature = "face-recognition")]
+        if let Some(worker) = face_worker {
+            let faces = worker.finish().await;
+            tracing::info!(?faces, "Face detection finished");
+            if let Err(e) = self
+                .cache_manager
+                .cluster_unnamed_faces_async(cache::DEFAULT_CLUSTER_THRESHOLD)
+                .await
+            {
+                tracing::warn!(error = ?e, "Failed to cluster faces");
+            }
+        }
+
+        match self.sync_favorites_album().await {
+            Ok(0) => {}
+            Ok(n) => tracing::info!("Favorites album holds {} favorites", n),
+            Err(e) => tracing::warn!(error = %e, "Failed to sync the favorites album"),
+        }
+        // Keep an album id the favorites sync just stored
+        if let Ok(saved) = self.load_state() {
+            state.favorites_album_id = saved.favorites_album_id;
+        }
+        report.transfer = self.api_client.stats().since(&transfer_start);
+
         tracing::info!(
             "Synchronization complete. Total media items synced: {}.",
             total_synced
         );
-        if let Some(tx) = &progress {
-            if let Err(e) = tx.send(SyncProgress::Finished(total_synced)) {
-                if let Some(err) = &error {
-                    if let Err(send_err) = err.send(SyncTaskError::Other {
-                        code: SyncErrorCode::Other,
-                        message: format!("Failed to send progress update: {}", e),
-                    }) {
-                        tracing::error!("Failed to forward error: {}", send_err);
-                    }
-                }
-            }
-        }
-        Self::forward(&ui_progress, SyncProgress::Finished(total_synced));
+        channels.progress(SyncProgress::Report(report.clone()));
+        channels.progress(SyncProgress::Finished(total_synced));
         state.page_token = None;
         state.total_synced = total_synced;
         state.last_success = Some(Utc::now());
+        state.last_transfer = report.transfer;
         if let Err(e) = self.save_state(&state) {
             tracing::warn!(error = ?e, "Failed to update state file");
         }
@@ -372,29 +900,22 @@ impl Syncer {
             .await
             .map_err(|e| {
                 let msg = format!("Failed to update last sync: {}", e);
-                if let Some(tx) = &error {
-                    if let Err(send_err) = tx.send(SyncTaskError::Other {
-                        code: SyncErrorCode::Cache,
-                        message: msg.clone(),
-                    }) {
-                        tracing::error!("Failed to forward error: {}", send_err);
-                    }
-                }
-                Self::forward(&ui_error, SyncTaskError::Other {
-                    code: SyncErrorCode::Cache,
-                    message: msg.clone(),
-                });
+                channels.fail(SyncErrorCode::Cache, msg.clone());
                 SyncError::CacheError(msg)
             })?;
-        let status = SyncTaskError::Status {
+        channels.error(SyncTaskError::Status {
             last_synced: Utc::now(),
             message: format!("Sync completed: {total_synced} items"),
-        };
-        if let Some(tx) = &error {
-            let _ = tx.send(status.clone());
-        }
-        Self::forward(&ui_error, status);
-        Ok(())
+        });
+        tracing::info!(
+            inserted = report.inserted,
+            updated = report.updated,
+            skipped = report.skipped,
+            bytes_down = report.transfer.bytes_down,
+            requests = report.transfer.requests,
+            "Sync report"
+        );
+        Ok(report)
     }
 
     #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self, progress_tx, error_tx)))]
@@ -408,13 +929,18 @@ impl Syncer {
         ui_error_tx: Option<mpsc::UnboundedSender<SyncTaskError>>,
     ) -> (JoinHandle<Result<(), SyncTaskError>>, oneshot::Sender<()>) {
         let (shutdown_tx, mut shutdown_rx) = oneshot::channel();
-        let forward_err = error_tx.clone();
-        let forward_ui_err = ui_error_tx.clone();
-        let forward_status = status_tx.clone();
-
-        let sync_status_tx = status_tx.clone();
+        let channels = SyncChannels {
+            progress: Some(progress_tx),
+            errors: Some(error_tx),
+            status: status_tx,
+            ui_progress: ui_progress_tx,
+            ui_error: ui_error_tx,
+        };
+        let task_channels = channels.clone();
         let sync_task = spawn_local(async move {
+            let channels = task_channels;
             let mut syncer = self;
+            syncer.interval = interval;
             let mut backoff = 1u64;
             let mut failures: u32 = 0;
             const MAX_FAILURES: u32 = 5;
@@ -434,16 +960,17 @@ impl Syncer {
                         return Ok(());
                     }
                     result = async {
-                        if let Err(e) =
-                            syncer
-                                .sync_media_items(
-                                    Some(progress_tx.clone()),
-                                    Some(error_tx.clone()),
-                                    ui_progress_tx.clone(),
-                                    ui_error_tx.clone(),
-                                )
-                                .await
-                        {
+                        if let Err(e) = syncer.sync_with(&channels).await {
+                            let profile = syncer.profile();
+                            if auth::reauth_required_for(profile.as_deref()) {
+                                // Retrying cannot help; hold off without spending the failure budget
+                                tracing::warn!(?e, "Periodic sync paused until the user signs in again");
+                                channels.status(SyncTaskError::ReauthRequired(e.to_string()));
+                                syncer.wait_for_sign_in().await;
+                                backoff = 1;
+                                failures = 0;
+                                return Ok::<(), SyncTaskError>(());
+                            }
                             let code = match e {
                                 SyncError::AuthenticationError(_) => SyncErrorCode::Auth,
                                 SyncError::ApiClientError(_) => SyncErrorCode::Network,
@@ -455,67 +982,27 @@ impl Syncer {
                                 e,
                                 last_success.to_rfc3339()
                             );
-                            if let Err(send_err) = error_tx.send(SyncTaskError::PeriodicSyncFailed {
-                                code,
-                                message: msg.clone(),
-                            }) {
-                                tracing::error!(error = ?send_err, "Failed to forward periodic sync error");
-                            }
-                            Self::forward(&ui_error_tx, SyncTaskError::PeriodicSyncFailed {
-                                code,
-                                message: msg.clone(),
-                            });
-                            let status = SyncTaskError::Status {
-                                last_synced: last_success,
-                                message: msg.clone(),
-                            };
-                            if ui_error_tx
-                                .as_ref()
-                                .map(|u| u.same_channel(&error_tx))
-                                .unwrap_or(false)
-                            {
-                                let _ = error_tx.send(status.clone());
-                            } else {
-                                let _ = error_tx.send(status.clone());
-                                Self::forward(&ui_error_tx, status);
-                            }
+                            channels.error(SyncTaskError::PeriodicSyncFailed { code, message: msg.clone() });
+                            channels.status(SyncTaskError::Status { last_synced: last_success, message: msg });
                             failures += 1;
-                            if let Some(tx) = &sync_status_tx {
-                                let _ = tx.send(SyncTaskError::RestartAttempt(failures));
-                            }
+                            channels.status(SyncTaskError::RestartAttempt(failures));
                             let wait = backoff.min(300);
-                            if let Err(send_err) = error_tx.send(SyncTaskError::RestartAttempt(failures)) {
-                                tracing::error!(error = ?send_err, "Failed to forward restart attempt");
-                            }
-                            Self::forward(&ui_error_tx, SyncTaskError::RestartAttempt(failures));
                             if failures > 3 {
                                 tracing::error!(?e, attempts = failures, backoff = wait, "Periodic sync failed");
                             } else {
                                 tracing::warn!(?e, attempts = failures, backoff = wait, "Periodic sync failed");
                             }
                             backoff = (backoff * 2).min(300);
-                            if let Err(send_err) = progress_tx.send(SyncProgress::Retrying(wait)) {
-                                tracing::error!(error = ?send_err, "Failed to send retry progress");
-                                let _ = error_tx.send(SyncTaskError::Other {
-                                    code: SyncErrorCode::Other,
-                                    message: format!("Failed to send progress update: {}", send_err),
-                                });
-                            }
-                            Self::forward(&ui_progress_tx, SyncProgress::Retrying(wait));
+                            channels.progress(SyncProgress::Retrying(wait));
                             if failures >= MAX_FAILURES {
                                 let abort_msg = format!("periodic sync aborted after {} failures", failures);
                                 tracing::error!("{}", abort_msg);
-                                let abort_err = SyncTaskError::Aborted(abort_msg.clone());
-                                let _ = error_tx.send(abort_err.clone());
-                                Self::forward(&ui_error_tx, abort_err.clone());
-                                if let Some(tx) = &sync_status_tx {
-                                    let _ = tx.send(abort_err.clone());
-                                }
+                                channels.status(SyncTaskError::Aborted(abort_msg));
                                 failures = 0;
                                 backoff = (backoff * 2).min(300);
-                                sleep(Duration::from_secs(backoff)).await;
+                                syncer.wait_for_next_run(Some(Duration::from_secs(backoff))).await;
                             } else {
-                                sleep(Duration::from_secs(wait)).await;
+                                syncer.wait_for_next_run(Some(Duration::from_secs(wait))).await;
                             }
                         } else {
                             last_success = Utc::now();
@@ -523,27 +1010,11 @@ impl Syncer {
                             let _ = syncer.save_state(&state);
                             backoff = 1;
                             failures = 0;
-                            if let Some(tx) = &sync_status_tx {
-                                let _ = tx.send(SyncTaskError::Status {
-                                    last_synced: last_success,
-                                    message: "Sync completed".into(),
-                                });
-                            }
-                            let status = SyncTaskError::Status {
+                            channels.status(SyncTaskError::Status {
                                 last_synced: last_success,
                                 message: "Sync completed".into(),
-                            };
-                            if ui_error_tx
-                                .as_ref()
-                                .map(|u| u.same_channel(&error_tx))
-                                .unwrap_or(false)
-                            {
-                                let _ = error_tx.send(status.clone());
-                            } else {
-                                let _ = error_tx.send(status.clone());
-                                Self::forward(&ui_error_tx, status);
-                            }
-                            sleep(interval).await;
+                            });
+                            syncer.wait_for_next_run(None).await;
                         }
                         Ok::<(), SyncTaskError>(())
                     } => match result {
@@ -560,22 +1031,14 @@ impl Syncer {
             match sync_task.await {
                 Ok(res) => {
                     if let Err(ref e) = res {
-                        let _ = forward_err.send(e.clone());
-                        Syncer::forward(&forward_ui_err, e.clone());
-                        if let Some(tx) = &forward_status {
-                            let _ = tx.send(e.clone());
-                        }
+                        channels.status(e.clone());
                     }
                     res
                 }
                 Err(join_err) => {
                     let msg = format!("task join error: {}", join_err);
                     let err = SyncTaskError::Other { code: SyncErrorCode::Other, message: msg };
-                    let _ = forward_err.send(err.clone());
-                    Syncer::forward(&forward_ui_err, err.clone());
-                    if let Some(tx) = &forward_status {
-                        let _ = tx.send(err.clone());
-                    }
+                    channels.status(err.clone());
                     Err(err)
                 }
             }
@@ -583,15 +1046,33 @@ impl Syncer {
         (handle, shutdown_tx)
     }
 
-    #[cfg_attr(feature = "trace-spans", tracing::instrument)]
 pub fn start_token_refresh_task(
         interval: Duration,
         error_tx: mpsc::UnboundedSender<SyncTaskError>,
         status_tx: Option<mpsc::UnboundedSender<SyncTaskError>>,
         ui_error_tx: Option<mpsc::UnboundedSender<SyncTaskError>>,
+    ) -> (JoinHandle<Result<(), SyncTaskError>>, oneshot::Sender<()>) {
+        let profile = watch::channel(auth::active_profile()).1;
+        Self::start_token_refresh_task_for(profile, interval, error_tx, status_tx, ui_error_tx)
+    }
+
+    /// Like `start_token_refresh_task`, refreshing the tokens of whichever
+    /// profile `profile` currently holds, see `Syncer::profile_watch`.
+    #[cfg_attr(feature = "trace-spans", tracing::instrument)]
+    pub fn start_token_refresh_task_for(
+        mut profile: watch::Receiver<Option<String>>,
+        interval: Duration,
+        error_tx: mpsc::UnboundedSender<SyncTaskError>,
+        status_tx: Option<mpsc::UnboundedSender<SyncTaskError>>,
+        ui_error_tx: Option<mpsc::UnboundedSender<SyncTaskError>>,
     ) -> (JoinHandle<Result<(), SyncTaskError>>, oneshot::Sender<()>) {
         let (shutdown_tx, mut shutdown_rx) = oneshot::channel();
-        let forward_status = status_tx.clone();
+        let channels = SyncChannels {
+            errors: Some(error_tx),
+            status: status_tx,
+            ui_error: ui_error_tx,
+            ..SyncChannels::default()
+        };
         let handle = spawn_local(async move {
             let mut interval = interval;
             let mut last_success = Utc::now();
@@ -605,79 +1086,64 @@ pub fn start_token_refresh_task(
                     }
                     result = async {
                         sleep(interval).await;
-                        if let Err(e) = ensure_access_token_valid().await {
-                            let _code = match &e {
+                        let active = profile.borrow_and_update().clone();
+                        let refreshed = ensure_access_token_valid_for(active.as_deref()).await;
+                        if let Err(auth::AuthError::ReauthRequired(reason)) = &refreshed {
+                            metrics::record_token_refresh("reauth");
+                            tracing::warn!(%reason, "Token refresh paused until the user signs in again");
+                            channels.status(SyncTaskError::ReauthRequired(reason.clone()));
+                            tokio::select! {
+                                _ = auth::wait_for_reauth_for(active.as_deref()) => {}
+                                // Another account has its own sign-in state
+                                Ok(()) = profile.changed() => {}
+                            }
+                            failures = 0;
+                        } else if let Err(e) = refreshed {
+                            let code = match &e {
                                 auth::AuthError::Keyring(_) => "keyring",
                                 auth::AuthError::OAuth(_) => "oauth",
                                 auth::AuthError::Other(_) => "other",
+                                auth::AuthError::ReauthRequired(_) => "reauth",
                             };
                             let msg = format!(
                                 "{} | last_success: {}",
                                 e,
                                 last_success.to_rfc3339()
                             );
+                            metrics::record_token_refresh(code);
                             tracing::error!(error = ?e, "Token refresh failed");
                             let err_variant = SyncTaskError::TokenRefreshFailed {
                                 code: match &e {
-                                    auth::AuthError::Keyring(_) | auth::AuthError::OAuth(_) | auth::AuthError::Other(_) => SyncErrorCode::Auth,
+                                    auth::AuthError::Keyring(_)
+                                    | auth::AuthError::OAuth(_)
+                                    | auth::AuthError::Other(_)
+                                    | auth::AuthError::ReauthRequired(_) => SyncErrorCode::Auth,
                                 },
                                 message: msg.clone(),
                             };
-                            if let Err(send_err) = error_tx.send(err_variant.clone()) {
-                                tracing::error!(error = ?send_err, "Failed to forward token refresh error");
-                            }
-                            Self::forward(&ui_error_tx, err_variant.clone());
-                            Self::forward(&forward_status, err_variant.clone());
-                            let status = SyncTaskError::Status {
+                            channels.status(err_variant);
+                            channels.status(SyncTaskError::Status {
                                 last_synced: last_success,
-                                message: msg.clone(),
-                            };
-                            let _ = error_tx.send(status.clone());
-                            Self::forward(&forward_status, status.clone());
-                            if ui_error_tx
-                                .as_ref()
-                                .map(|u| u.same_channel(&error_tx))
-                                .unwrap_or(false)
-                            {
-                                // already sent via error_tx
-                            } else {
-                                Self::forward(&ui_error_tx, status);
-                            }
+                                message: msg,
+                            });
                             failures += 1;
-                            if let Err(send_err) = error_tx.send(SyncTaskError::RestartAttempt(failures)) {
-                                tracing::error!(error = ?send_err, "Failed to forward restart attempt");
-                            }
-                            Self::forward(&ui_error_tx, SyncTaskError::RestartAttempt(failures));
-                            Self::forward(&forward_status, SyncTaskError::RestartAttempt(failures));
+                            channels.status(SyncTaskError::RestartAttempt(failures));
                             if failures >= MAX_FAILURES {
                                 let abort_msg = format!("token refresh aborted after {} failures", failures);
                                 tracing::error!("{}", abort_msg);
-                                let abort_err = SyncTaskError::Aborted(abort_msg.clone());
-                                let _ = error_tx.send(abort_err.clone());
-                                Self::forward(&ui_error_tx, abort_err.clone());
-                                Self::forward(&forward_status, abort_err.clone());
+                                channels.status(SyncTaskError::Aborted(abort_msg));
                                 failures = 0;
                                 interval = Duration::from_secs((interval.as_secs() * 2).min(300));
                                 sleep(interval).await;
                             }
                         } else {
+                            metrics::record_token_refresh("ok");
                             last_success = Utc::now();
                             failures = 0;
-                            let status = SyncTaskError::Status {
+                            channels.status(SyncTaskError::Status {
                                 last_synced: last_success,
                                 message: "Token refreshed".into(),
-                            };
-                            let _ = error_tx.send(status.clone());
-                            Self::forward(&forward_status, status.clone());
-                            if ui_error_tx
-                                .as_ref()
-                                .map(|u| u.same_channel(&error_tx))
-                                .unwrap_or(false)
-                            {
-                                // already sent via error_tx
-                            } else {
-                                Self::forward(&ui_error_tx, status);
-                            }
+                            });
                         }
                         Ok::<(), SyncTaskError>(())
                     } => match result {
@@ -752,4 +1218,328 @@ mod tests {
         std::env::remove_var("MOCK_REFRESH_TOKEN");
         std::env::remove_var("MOCK_KEYRING");
     }
+
+    fn drain<T>(rx: &mut mpsc::UnboundedReceiver<T>, label: impl Fn(&T) -> String) -> Vec<String> {
+        std::iter::from_fn(|| rx.try_recv().ok()).map(|m| label(&m)).collect()
+    }
+
+    fn error_label(e: &SyncTaskError) -> String {
+        match e {
+            SyncTaskError::PeriodicSyncFailed { code, .. } => format!("failed {:?}", code),
+            SyncTaskError::RestartAttempt(n) => format!("restart {}", n),
+            SyncTaskError::Status { message, .. } if message.contains("last_success") => "status failed".into(),
+            SyncTaskError::Status { message, .. } => format!("status {}", message),
+            SyncTaskError::Other { code, .. } => format!("other {:?}", code),
+            SyncTaskError::ReauthRequired(_) => "reauth".into(),
+            other => format!("{:?}", other),
+        }
+    }
+
+    fn progress_label(p: &SyncProgress) -> String {
+        match p {
+            SyncProgress::Started => "started".into(),
+            SyncProgress::Retrying(wait) => format!("retrying {}", wait),
+            SyncProgress::Finished(_) => "finished".into(),
+            SyncProgress::Report(_) => "report".into(),
+            SyncProgress::Progress { .. } | SyncProgress::ItemSynced(_) => "progress".into(),
+            other => format!("{:?}", other),
+        }
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    #[serial]
+    async fn test_periodic_sync_reports_to_every_channel() {
+        std::env::set_var("MOCK_KEYRING", "1");
+        std::env::set_var("MOCK_ACCESS_TOKEN", "token");
+        std::env::set_var("MOCK_REFRESH_TOKEN", "refresh");
+        std::env::remove_var("MOCK_API_CLIENT");
+        let file = NamedTempFile::new().unwrap();
+        let mut syncer = Syncer::new(file.path()).await.unwrap();
+        // Nothing listens here, so the first run fails without leaving the machine
+        let port = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        syncer.api_client = ApiClient::new("token".into()).with_base_url(format!("http://127.0.0.1:{}", port));
+
+        let (progress_tx, mut progress_rx) = mpsc::unbounded_channel();
+        let (error_tx, mut error_rx) = mpsc::unbounded_channel();
+        let (status_tx, mut status_rx) = mpsc::unbounded_channel();
+        let (ui_progress_tx, mut ui_progress_rx) = mpsc::unbounded_channel();
+        let (ui_error_tx, mut ui_error_rx) = mpsc::unbounded_channel();
+        let local = tokio::task::LocalSet::new();
+        local
+            .run_until(async {
+                let (handle, shutdown) = syncer.start_periodic_sync(
+                    Duration::from_secs(3600),
+                    progress_tx,
+                    error_tx,
+                    Some(status_tx),
+                    Some(ui_progress_tx),
+                    Some(ui_error_tx),
+                );
+                let mut seen = Vec::new();
+                while seen.len() < 3 {
+                    let update = tokio::time::timeout(Duration::from_secs(10), status_rx.recv()).await.unwrap().unwrap();
+                    if matches!(update, SyncTaskError::RestartAttempt(1)) {
+                        // The retry one second later succeeds
+                        std::env::set_var("MOCK_API_CLIENT", "1");
+                    }
+                    seen.push(error_label(&update));
+                }
+                assert_eq!(seen, ["status failed", "restart 1", "status Sync completed"]);
+                let _ = shutdown.send(());
+                let _ = handle.await;
+            })
+            .await;
+
+        let errors = drain(&mut error_rx, error_label);
+        let ui_errors = drain(&mut ui_error_rx, error_label);
+        let progress = drain(&mut progress_rx, progress_label);
+        let ui_progress = drain(&mut ui_progress_rx, progress_label);
+        let expected_errors = [
+            "status Sync started",
+            "other Network",
+            "failed Network",
+            "status failed",
+            "restart 1",
+            "status Sync started",
+            "status Sync completed: 1 items",
+            "status Sync completed",
+        ];
+        assert_eq!(errors, expected_errors);
+        assert_eq!(ui_errors, expected_errors);
+        let expected_progress = ["started", "retrying 1", "started", "progress", "progress", "report", "finished"];
+        assert_eq!(progress, expected_progress);
+        assert_eq!(ui_progress, expected_progress);
+        std::env::remove_var("MOCK_KEYRING");
+        std::env::remove_var("MOCK_ACCESS_TOKEN");
+        std::env::remove_var("MOCK_REFRESH_TOKEN");
+        std::env::remove_var("MOCK_API_CLIENT");
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    #[serial]
+    async fn test_periodic_sync_waits_for_sign_in_after_revocation() {
+        std::env::set_var("MOCK_KEYRING", "1");
+        std::env::set_var("MOCK_ACCESS_TOKEN", "token");
+        std::env::set_var("MOCK_REFRESH_TOKEN", "refresh");
+        std::env::set_var("MOCK_API_CLIENT", "1");
+        authenticate(0).await.unwrap();
+        let file = NamedTempFile::new().unwrap();
+        let syncer = Syncer::new(file.path()).await.unwrap();
+        std::env::set_var("MOCK_REFRESH_REVOKED", "1");
+        assert!(auth::refresh_access_token().await.is_err());
+        std::env::remove_var("MOCK_REFRESH_REVOKED");
+
+        let (progress_tx, _progress_rx) = mpsc::unbounded_channel();
+        let (error_tx, _error_rx) = mpsc::unbounded_channel();
+        let (status_tx, mut status_rx) = mpsc::unbounded_channel();
+        let local = tokio::task::LocalSet::new();
+        local
+            .run_until(async {
+                let (handle, shutdown) =
+                    syncer.start_periodic_sync(Duration::from_secs(3600), progress_tx, error_tx, Some(status_tx), None, None);
+                let first = tokio::time::timeout(Duration::from_secs(5), status_rx.recv()).await.unwrap().unwrap();
+                assert_eq!(error_label(&first), "reauth");
+                // Paused: no restart attempts while nobody has signed in
+                sleep(Duration::from_millis(200)).await;
+                assert!(status_rx.try_recv().is_err());
+
+                authenticate(0).await.unwrap();
+                let next = tokio::time::timeout(Duration::from_secs(5), status_rx.recv()).await.unwrap().unwrap();
+                assert_eq!(error_label(&next), "status Sync completed");
+                let _ = shutdown.send(());
+                let _ = handle.await;
+            })
+            .await;
+        std::env::remove_var("MOCK_KEYRING");
+        std::env::remove_var("MOCK_ACCESS_TOKEN");
+        std::env::remove_var("MOCK_REFRESH_TOKEN");
+        std::env::remove_var("MOCK_API_CLIENT");
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_empty_page_with_next_token_does_not_end_sync() {
+        use wiremock::matchers::{body_partial_json, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        std::env::set_var("MOCK_KEYRING", "1");
+        std::env::set_var("MOCK_ACCESS_TOKEN", "token");
+        std::env::set_var("MOCK_REFRESH_TOKEN", "refresh");
+        std::env::remove_var("MOCK_API_CLIENT");
+        let file = NamedTempFile::new().unwrap();
+        let mut syncer = Syncer::new(file.path()).await.unwrap();
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/mediaItems:search"))
+            .and(body_partial_json(serde_json::json!({ "pageToken": "page2" })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "mediaItems": [{
+                    "id": "late",
+                    "productUrl": "http://example.com",
+                    "baseUrl": "http://example.com/base",
+                    "mimeType": "image/jpeg",
+                    "mediaMetadata": { "creationTime": "2023-01-01T00:00:00Z", "width": "1", "height": "1" },
+                    "filename": "late.jpg"
+                }]
+            })))
+            .expect(1)
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/v1/mediaItems:search"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({ "mediaItems": [], "nextPageToken": "page2" })),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+        syncer.api_client = ApiClient::new("token".into()).with_base_url(server.uri());
+
+        let report = syncer.sync_media_items(None, None, None, None).await.unwrap();
+        assert_eq!(report.fetched, 1);
+        assert_eq!(report.inserted_ids, ["late"]);
+        server.verify().await;
+        let _ = std::fs::remove_file(file.path().with_extension("state.json"));
+        std::env::remove_var("MOCK_KEYRING");
+        std::env::remove_var("MOCK_ACCESS_TOKEN");
+        std::env::remove_var("MOCK_REFRESH_TOKEN");
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_unchanged_pages_stop_sync_early() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        std::env::set_var("MOCK_KEYRING", "1");
+        std::env::set_var("MOCK_ACCESS_TOKEN", "token");
+        std::env::set_var("MOCK_REFRESH_TOKEN", "refresh");
+        std::env::remove_var("MOCK_API_CLIENT");
+        let file = NamedTempFile::new().unwrap();
+        let mut syncer = Syncer::new(file.path()).await.unwrap();
+        syncer.set_unchanged_page_limit(2);
+        let server = MockServer::start().await;
+        // Every page repeats the same item, so only the first one brings anything new
+        Mock::given(method("POST"))
+            .and(path("/v1/mediaItems:search"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "mediaItems": [{
+                    "id": "same",
+                    "productUrl": "http://example.com",
+                    "baseUrl": "http://example.com/base",
+                    "mimeType": "image/jpeg",
+                    "mediaMetadata": { "creationTime": "2023-01-01T00:00:00Z", "width": "1", "height": "1" },
+                    "filename": "same.jpg"
+                }],
+                "nextPageToken": "more"
+            })))
+            .expect(3)
+            .mount(&server)
+            .await;
+        syncer.api_client = ApiClient::new("token".into()).with_base_url(server.uri());
+
+        let report = syncer.sync_media_items(None, None, None, None).await.unwrap();
+        assert!(report.stopped_early);
+        assert_eq!(report.pages, 3);
+        assert_eq!(report.unchanged_streak, 2);
+        assert_eq!(report.inserted_ids, ["same"]);
+        server.verify().await;
+        let _ = std::fs::remove_file(file.path().with_extension("state.json"));
+        std::env::remove_var("MOCK_KEYRING");
+        std::env::remove_var("MOCK_ACCESS_TOKEN");
+        std::env::remove_var("MOCK_REFRESH_TOKEN");
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_too_many_empty_pages_leave_sync_resumable() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        std::env::set_var("MOCK_KEYRING", "1");
+        std::env::set_var("MOCK_ACCESS_TOKEN", "token");
+        std::env::set_var("MOCK_REFRESH_TOKEN", "refresh");
+        std::env::remove_var("MOCK_API_CLIENT");
+        let file = NamedTempFile::new().unwrap();
+        let mut syncer = Syncer::new(file.path()).await.unwrap();
+        let before = syncer.cache_manager.get_last_sync().unwrap();
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/mediaItems:search"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({ "mediaItems": [], "nextPageToken": "stuck" })),
+            )
+            .expect(MAX_EMPTY_PAGES as u64)
+            .mount(&server)
+            .await;
+        syncer.api_client = ApiClient::new("token".into()).with_base_url(server.uri());
+        let (error_tx, mut error_rx) = mpsc::unbounded_channel();
+
+        let result = syncer.sync_media_items(None, Some(error_tx), None, None).await;
+        assert!(matches!(result, Err(SyncError::ApiClientError(_))), "{:?}", result);
+        let mut reported = Vec::new();
+        while let Ok(err) = error_rx.try_recv() {
+            reported.push(err);
+        }
+        assert!(
+            reported.iter().any(|e| matches!(e, SyncTaskError::Other { code: SyncErrorCode::Network, .. })),
+            "{:?}",
+            reported
+        );
+        assert_eq!(syncer.load_state().unwrap().page_token.as_deref(), Some("stuck"));
+        assert_eq!(syncer.cache_manager.get_last_sync().unwrap(), before);
+        server.verify().await;
+        let _ = std::fs::remove_file(file.path().with_extension("state.json"));
+        std::env::remove_var("MOCK_KEYRING");
+        std::env::remove_var("MOCK_ACCESS_TOKEN");
+        std::env::remove_var("MOCK_REFRESH_TOKEN");
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_sync_with_shared_ui_channels_reports_once() {
+        std::env::set_var("MOCK_API_CLIENT", "1");
+        std::env::set_var("MOCK_KEYRING", "1");
+        std::env::set_var("MOCK_ACCESS_TOKEN", "token");
+        std::env::set_var("MOCK_REFRESH_TOKEN", "refresh");
+        let file = NamedTempFile::new().unwrap();
+        let mut syncer = Syncer::new(file.path()).await.unwrap();
+        let (progress, mut progress_rx) = mpsc::unbounded_channel();
+        let (errors, mut error_rx) = mpsc::unbounded_channel();
+
+        syncer
+            .sync_media_items(Some(progress.clone()), Some(errors.clone()), Some(progress), Some(errors))
+            .await
+            .unwrap();
+        let statuses = drain(&mut error_rx, error_label);
+        assert_eq!(statuses.iter().filter(|s| *s == "status Sync started").count(), 1, "{:?}", statuses);
+        let progress = drain(&mut progress_rx, progress_label);
+        assert_eq!(progress.iter().filter(|p| *p == "started").count(), 1, "{:?}", progress);
+        let _ = std::fs::remove_file(file.path().with_extension("state.json"));
+        std::env::remove_var("MOCK_API_CLIENT");
+        std::env::remove_var("MOCK_KEYRING");
+        std::env::remove_var("MOCK_ACCESS_TOKEN");
+        std::env::remove_var("MOCK_REFRESH_TOKEN");
+    }
+
+    #[test]
+    fn test_shared_ui_error_channel_gets_each_message_once() {
+        let (progress, _progress_rx) = mpsc::unbounded_channel();
+        let (errors, mut error_rx) = mpsc::unbounded_channel();
+        let (status, mut status_rx) = mpsc::unbounded_channel();
+        let channels = SyncChannels {
+            progress: Some(progress),
+            ui_error: Some(errors.clone()),
+            errors: Some(errors),
+            status: Some(status),
+            ui_progress: None,
+        };
+        channels.status(SyncTaskError::RestartAttempt(2));
+        channels.error(SyncTaskError::RestartAttempt(3));
+        assert_eq!(drain(&mut error_rx, error_label), ["restart 2", "restart 3"]);
+        assert_eq!(drain(&mut status_rx, error_label), ["restart 2"]);
+    }
 }
@@ -0,0 +1,96 @@
+//! Counters and histograms for operations dashboards, recorded through the
+//! `metrics` facade when the `trace-spans` feature is enabled. Without a
+//! recorder installed, or without the feature, recording does nothing.
+//!
+//! The `prometheus` feature adds `serve_prometheus`, which installs a
+//! recorder and serves the values on `http://127.0.0.1:<port>/metrics`.
+
+use std::time::Duration;
+
+use crate::{SyncError, SyncReport};
+
+/// Finished `sync_media_items` runs, labelled `result` = `ok` or `error`.
+pub const SYNC_RUNS: &str = "googlepicz_sync_runs_total";
+/// Media items fetched from the API.
+pub const ITEMS_FETCHED: &str = "googlepicz_sync_items_fetched_total";
+/// Media items inserted into or updated in the cache.
+pub const ITEMS_SYNCED: &str = "googlepicz_sync_items_synced_total";
+/// Result pages fetched from the API.
+pub const PAGES_FETCHED: &str = "googlepicz_sync_pages_fetched_total";
+/// Failed sync runs, labelled `code` = `auth`, `api`, `cache` or `other`.
+pub const SYNC_ERRORS: &str = "googlepicz_sync_errors_total";
+/// Failed HTTP requests to the Library API during sync runs.
+pub const API_ERRORS: &str = "googlepicz_api_errors_total";
+/// Duration of a sync run in seconds.
+pub const SYNC_DURATION: &str = "googlepicz_sync_duration_seconds";
+/// Token refreshes, labelled `result` = `ok`, `keyring`, `oauth` or `other`.
+pub const TOKEN_REFRESHES: &str = "googlepicz_token_refreshes_total";
+/// Photos handed to face detection.
+pub const FACE_JOBS_QUEUED: &str = "googlepicz_face_jobs_queued_total";
+/// Face detection jobs that finished, labelled `result` = `ok` or `error`.
+pub const FACE_JOBS_COMPLETED: &str = "googlepicz_face_jobs_completed_total";
+
+#[cfg(feature = "trace-spans")]
+pub(crate) fn record_sync(result: &Result<SyncReport, SyncError>, elapsed: Duration) {
+    metrics::histogram!(SYNC_DURATION).record(elapsed.as_secs_f64());
+    match result {
+        Ok(report) => {
+            metrics::counter!(SYNC_RUNS, "result" => "ok").increment(1);
+            metrics::counter!(ITEMS_FETCHED).increment(report.fetched);
+            metrics::counter!(ITEMS_SYNCED).increment(report.inserted + report.updated);
+            metrics::counter!(PAGES_FETCHED).increment(report.pages);
+            metrics::counter!(API_ERRORS).increment(report.transfer.errors);
+        }
+        Err(e) => {
+            let code = match e {
+                SyncError::AuthenticationError(_) => "auth",
+                SyncError::ApiClientError(_) => "api",
+                SyncError::CacheError(_) => "cache",
+                SyncError::Other(_) => "other",
+            };
+            metrics::counter!(SYNC_RUNS, "result" => "error").increment(1);
+            metrics::counter!(SYNC_ERRORS, "code" => code).increment(1);
+        }
+    }
+}
+
+#[cfg(not(feature = "trace-spans"))]
+pub(crate) fn record_sync(_result: &Result<SyncReport, SyncError>, _elapsed: Duration) {}
+
+/// `result` is `ok` or the kind of failure.
+#[cfg(feature = "trace-spans")]
+pub(crate) fn record_token_refresh(result: &'static str) {
+    metrics::counter!(TOKEN_REFRESHES, "result" => result).increment(1);
+}
+
+#[cfg(not(feature = "trace-spans"))]
+pub(crate) fn record_token_refresh(_result: &'static str) {}
+
+#[cfg(feature = "trace-spans")]
+pub(crate) fn record_face_jobs_queued(count: u64) {
+    metrics::counter!(FACE_JOBS_QUEUED).increment(count);
+}
+
+#[cfg(not(feature = "trace-spans"))]
+pub(crate) fn record_face_jobs_queued(_count: u64) {}
+
+#[cfg(feature = "trace-spans")]
+pub(crate) fn record_face_job_completed(ok: bool) {
+    let result = if ok { "ok" } else { "error" };
+    metrics::counter!(FACE_JOBS_COMPLETED, "result" => result).increment(1);
+}
+
+#[cfg(not(feature = "trace-spans"))]
+pub(crate) fn record_face_job_completed(_ok: bool) {}
+
+/// Install a Prometheus recorder serving `/metrics` on localhost `port`.
+/// Must be called from within a Tokio runtime, at most once per process.
+#[cfg(feature = "prometheus")]
+pub fn serve_prometheus(port: u16) -> Result<std::net::SocketAddr, SyncError> {
+    let addr = std::net::SocketAddr::from(([127, 0, 0, 1], port));
+    metrics_exporter_prometheus::PrometheusBuilder::new()
+        .with_http_listener(addr)
+        .install()
+        .map_err(|e| SyncError::Other(format!("Failed to start metrics exporter: {}", e)))?;
+    Ok(addr)
+}
@@ -16,7 +16,7 @@ async fn test_periodic_sync_abort_and_restart() {
     let local = tokio::task::LocalSet::new();
     local
         .run_until(async {
-            let mut syncer = Syncer::new(file.path()).await.unwrap();
+            let syncer = Syncer::new(file.path()).await.unwrap();
             // drop mock to force failures
             std::env::remove_var("MOCK_API_CLIENT");
             let (p_tx, _p_rx) = mpsc::unbounded_channel();
@@ -45,7 +45,7 @@ async fn test_periodic_sync_abort_and_restart() {
             assert!(seen_abort, "no Aborted error emitted");
             // restart with working API
             std::env::set_var("MOCK_API_CLIENT", "1");
-            let mut syncer2 = Syncer::new(file.path()).await.unwrap();
+            let syncer2 = Syncer::new(file.path()).await.unwrap();
             let (p_tx2, mut p_rx2) = mpsc::unbounded_channel();
             let (e_tx2, _e_rx2) = mpsc::unbounded_channel();
             let (handle2, shutdown2) = syncer2.start_periodic_sync(
@@ -16,7 +16,7 @@ async fn test_periodic_sync_reports_error() {
     let local = tokio::task::LocalSet::new();
     local
         .run_until(async {
-            let mut syncer = Syncer::new(file.path()).await.unwrap();
+            let syncer = Syncer::new(file.path()).await.unwrap();
             // remove API mocking so periodic sync fails when calling the network
             std::env::remove_var("MOCK_API_CLIENT");
             let (prog_tx, mut prog_rx) = mpsc::unbounded_channel();
@@ -60,7 +60,7 @@ async fn test_periodic_sync_progress_send_failure_forwarded() {
     let local = tokio::task::LocalSet::new();
     local
         .run_until(async {
-            let mut syncer = Syncer::new(file.path()).await.unwrap();
+            let syncer = Syncer::new(file.path()).await.unwrap();
             // remove API mocking so periodic sync fails when calling the network
             std::env::remove_var("MOCK_API_CLIENT");
             let (prog_tx, mut prog_rx) = mpsc::unbounded_channel();
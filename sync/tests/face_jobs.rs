@@ -0,0 +1,157 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+
+use api_client::{MediaItem, MediaMetadata, VideoMetadata};
+use serial_test::serial;
+use sync::{FaceJob, FaceWorker, FaceWorkerReport};
+
+/// Counts allocations made while `COUNTING` is set, on any thread, so the
+/// tests here run one at a time.
+struct CountingAlloc;
+
+static COUNTING: AtomicBool = AtomicBool::new(false);
+static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        if COUNTING.load(Ordering::Relaxed) {
+            ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        }
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+async fn count_allocations<F: std::future::Future<Output = ()>>(run: F) -> u64 {
+    ALLOCATIONS.store(0, Ordering::Relaxed);
+    COUNTING.store(true, Ordering::Relaxed);
+    run.await;
+    COUNTING.store(false, Ordering::Relaxed);
+    ALLOCATIONS.load(Ordering::Relaxed)
+}
+
+fn item(id: usize, mime_type: &str) -> MediaItem {
+    MediaItem {
+        id: format!("id{id}"),
+        description: Some("Holiday".into()),
+        product_url: format!("https://photos.google.com/lr/photo/{id}"),
+        base_url: format!("https://lh3.googleusercontent.com/{id}"),
+        mime_type: mime_type.into(),
+        media_metadata: MediaMetadata {
+            creation_time: "2023-01-01T00:00:00Z".into(),
+            width: "4032".into(),
+            height: "3024".into(),
+            video: Some(VideoMetadata {
+                camera_make: Some("Google".into()),
+                camera_model: Some("Pixel 7".into()),
+                fps: None,
+                status: None,
+            }),
+        },
+        filename: format!("IMG_{id}.jpg"),
+        is_favorite: false,
+    }
+}
+
+fn page(len: usize) -> Vec<MediaItem> {
+    (0..len).map(|i| item(i, "image/jpeg")).collect()
+}
+
+#[tokio::test(flavor = "current_thread")]
+#[serial]
+async fn face_jobs_allocate_less_than_a_cloned_item_per_photo() {
+    const PAGE: usize = 100;
+    let detected = Arc::new(AtomicU64::new(0));
+    // Start the blocking pool so neither measurement pays for its threads
+    tokio::task::spawn_blocking(|| ()).await.unwrap();
+
+    // Before: every item was cloned into its own blocking task
+    let items = page(PAGE);
+    let count = detected.clone();
+    let per_item = count_allocations(async {
+        for item in items.iter() {
+            let item_clone = item.clone();
+            let count = count.clone();
+            tokio::task::spawn_blocking(move || {
+                std::hint::black_box(&item_clone);
+                count.fetch_add(1, Ordering::Relaxed);
+            })
+            .await
+            .unwrap();
+        }
+    })
+    .await;
+
+    // After: the fields detection needs are moved into one long-lived worker
+    let items = page(PAGE);
+    let count = detected.clone();
+    let worker = FaceWorker::spawn(
+        Box::new(move |job: &FaceJob| {
+            std::hint::black_box(job);
+            count.fetch_add(1, Ordering::Relaxed);
+            Ok(())
+        }),
+        |_| {},
+    );
+    let mut report = FaceWorkerReport::default();
+    let queued = count_allocations(async {
+        for item in items {
+            assert!(worker.push(FaceJob::from(item)).await);
+        }
+        report = worker.finish().await;
+    })
+    .await;
+
+    assert_eq!(detected.load(Ordering::Relaxed), 2 * PAGE as u64);
+    assert_eq!(report.completed, PAGE as u64);
+    println!("allocations for {PAGE} photos: {per_item} cloned, {queued} queued");
+    assert!(per_item >= 10 * PAGE as u64, "cloning should allocate per field: {per_item}");
+    assert!(queued < PAGE as u64, "queuing allocated {queued} times for {PAGE} photos");
+}
+
+#[tokio::test(flavor = "current_thread")]
+#[serial]
+async fn face_worker_reports_failures_and_skips_videos() {
+    let errors = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let seen = errors.clone();
+    let worker = FaceWorker::spawn(
+        Box::new(|job: &FaceJob| if job.id == "id1" { Err("cannot decode".into()) } else { Ok(()) }),
+        move |message| seen.lock().unwrap().push(message),
+    );
+    for item in [item(0, "image/jpeg"), item(1, "image/png"), item(2, "video/mp4")] {
+        assert!(worker.push(FaceJob::from(item)).await);
+    }
+    let report = worker.finish().await;
+    assert_eq!(report, FaceWorkerReport { completed: 1, failed: 1, skipped: 1 });
+    assert_eq!(*errors.lock().unwrap(), vec!["Face detection failed: cannot decode".to_string()]);
+}
+
+#[tokio::test(flavor = "current_thread")]
+#[serial]
+async fn face_worker_reports_progress_as_jobs_finish() {
+    let progress = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let seen = progress.clone();
+    let worker = FaceWorker::spawn_with_progress(
+        Box::new(|_: &FaceJob| Ok(())),
+        |_| {},
+        move |done, queued| seen.lock().unwrap().push((done, queued)),
+    );
+    for item in [item(0, "image/jpeg"), item(1, "video/mp4"), item(2, "image/png")] {
+        assert!(worker.push(FaceJob::from(item)).await);
+    }
+    let report = worker.finish().await;
+    assert_eq!(report.processed(), 3);
+
+    let progress = progress.lock().unwrap();
+    let done: Vec<u64> = progress.iter().map(|(done, _)| *done).collect();
+    assert_eq!(done, vec![1, 2, 3]);
+    assert!(progress.iter().all(|(done, queued)| done <= queued));
+    assert_eq!(progress.last(), Some(&(3, 3)));
+}
@@ -0,0 +1,109 @@
+use std::sync::Arc;
+
+use api_client::{MediaItem, MediaMetadata};
+use cache::{CacheManager, FaceData, FaceScanCheckpoint};
+use sync::{Detection, FaceScanReport, FaceScanner, SyncProgress};
+use tempfile::{NamedTempFile, TempDir};
+use tokio::sync::mpsc;
+
+fn photo(dir: &TempDir, id: &str, content: &str) -> MediaItem {
+    let path = dir.path().join(format!("{id}.jpg"));
+    std::fs::write(&path, content).unwrap();
+    MediaItem {
+        id: id.into(),
+        description: None,
+        product_url: "http://example.com".into(),
+        base_url: format!("file://{}", path.display()),
+        mime_type: "image/jpeg".into(),
+        media_metadata: MediaMetadata {
+            creation_time: "2023-01-01T00:00:00Z".into(),
+            width: "100".into(),
+            height: "100".into(),
+            video: None,
+        },
+        filename: format!("{id}.jpg"),
+        is_favorite: false,
+    }
+}
+
+/// One face per "face" in the file, failing on "bad".
+fn scanner(cache: CacheManager) -> FaceScanner {
+    FaceScanner::with_detector(
+        cache,
+        Arc::new(|_item: &MediaItem, bytes: &[u8]| {
+            let content = std::str::from_utf8(bytes).unwrap();
+            if content == "bad" {
+                return Err("cannot decode".into());
+            }
+            let n = content.matches("face").count();
+            Ok(Detection {
+                faces: vec![FaceData { bbox: [0, 0, 10, 10], name: None, ignored: false, confidence: None }; n],
+                embeddings: Some(vec![vec![1.0; cache::EMBEDDING_DIM]; n]),
+            })
+        }),
+    )
+    .with_concurrency(2)
+}
+
+fn scanned(rx: &mut mpsc::UnboundedReceiver<SyncProgress>) -> Vec<(u64, u64)> {
+    let mut updates = Vec::new();
+    while let Ok(p) = rx.try_recv() {
+        if let SyncProgress::FacesScanned { processed, total } = p {
+            updates.push((processed, total));
+        }
+    }
+    updates
+}
+
+#[tokio::test]
+async fn test_face_scan_stores_faces_and_reports_progress() {
+    let dir = TempDir::new().unwrap();
+    let file = NamedTempFile::new().unwrap();
+    let cache = CacheManager::new(file.path()).unwrap();
+    cache.insert_media_item(&photo(&dir, "a", "face face")).unwrap();
+    cache.insert_media_item(&photo(&dir, "b", "bad")).unwrap();
+    cache.insert_media_item(&photo(&dir, "c", "nobody")).unwrap();
+    cache.insert_media_item(&photo(&dir, "d", "face")).unwrap();
+    cache.insert_media_item(&photo(&dir, "e", "face")).unwrap();
+    cache.insert_faces("e", "[]").unwrap();
+
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let report = scanner(cache.clone()).run(Some(tx)).await.unwrap();
+    assert_eq!(report, FaceScanReport { scanned: 3, faces: 3, failed: 1 });
+    assert_eq!(scanned(&mut rx), vec![(0, 4), (2, 4), (4, 4)]);
+
+    assert_eq!(cache.get_faces("a").unwrap().unwrap().len(), 2);
+    assert_eq!(cache.get_faces("c").unwrap().unwrap().len(), 0);
+    assert!(cache.get_faces("b").unwrap().is_none());
+    // Already scanned before, left alone
+    assert!(cache.get_faces("e").unwrap().unwrap().is_empty());
+    assert_eq!(cache.get_face_scan_checkpoint().unwrap(), None);
+    // Identical embeddings end up in one cluster
+    let people = cache.get_people_with_counts().unwrap();
+    assert_eq!(people.iter().filter(|p| p.cluster == Some(1)).count(), 1);
+
+    // The failed photo is retried by the next run
+    let report = scanner(cache).run(None).await.unwrap();
+    assert_eq!(report, FaceScanReport { scanned: 0, faces: 0, failed: 1 });
+}
+
+#[tokio::test]
+async fn test_face_scan_resumes_from_checkpoint() {
+    let dir = TempDir::new().unwrap();
+    let file = NamedTempFile::new().unwrap();
+    let cache = CacheManager::new(file.path()).unwrap();
+    for id in ["a", "b", "c"] {
+        cache.insert_media_item(&photo(&dir, id, "face")).unwrap();
+    }
+    cache
+        .set_face_scan_checkpoint(&FaceScanCheckpoint { last_media_item_id: "b".into(), processed: 2 })
+        .unwrap();
+
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let report = scanner(cache.clone()).run(Some(tx)).await.unwrap();
+    assert_eq!(report.scanned, 1);
+    assert_eq!(scanned(&mut rx), vec![(2, 3), (3, 3)]);
+    assert!(cache.get_faces("a").unwrap().is_none());
+    assert_eq!(cache.get_faces("c").unwrap().unwrap().len(), 1);
+    assert_eq!(cache.get_face_scan_checkpoint().unwrap(), None);
+}
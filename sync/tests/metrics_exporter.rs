@@ -0,0 +1,50 @@
+#![cfg(feature = "prometheus")]
+
+use serial_test::serial;
+use sync::Syncer;
+use tempfile::NamedTempFile;
+
+/// Value of the sample line starting with `series`.
+fn sample(body: &str, series: &str) -> Option<f64> {
+    body.lines()
+        .find(|l| l.starts_with(series) && l[series.len()..].starts_with(' '))
+        .and_then(|l| l.rsplit(' ').next())
+        .and_then(|v| v.parse().ok())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[serial]
+async fn test_exporter_reports_sync_counters() {
+    std::env::set_var("MOCK_API_CLIENT", "1");
+    std::env::set_var("MOCK_KEYRING", "1");
+    std::env::set_var("MOCK_ACCESS_TOKEN", "token");
+    std::env::set_var("MOCK_REFRESH_TOKEN", "refresh");
+    let port = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+    let addr = sync::metrics::serve_prometheus(port).unwrap();
+
+    let file = NamedTempFile::new().unwrap();
+    let mut syncer = Syncer::new(file.path()).await.unwrap();
+    let report = syncer.sync_media_items(None, None, None, None).await.unwrap();
+
+    let url = format!("http://{}/metrics", addr);
+    let mut body = String::new();
+    for _ in 0..50 {
+        if let Ok(resp) = reqwest::get(&url).await {
+            body = resp.text().await.unwrap();
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+    let runs = format!("{}{{result=\"ok\"}}", sync::metrics::SYNC_RUNS);
+    assert_eq!(sample(&body, &runs), Some(1.0), "{}", body);
+    assert_eq!(sample(&body, sync::metrics::ITEMS_FETCHED), Some(report.fetched as f64));
+    assert_eq!(sample(&body, sync::metrics::PAGES_FETCHED), Some(report.pages as f64));
+    assert!(report.fetched > 0);
+    let duration = format!("{}_count", sync::metrics::SYNC_DURATION);
+    assert_eq!(sample(&body, &duration), Some(1.0), "{}", body);
+    let _ = std::fs::remove_file(file.path().with_extension("state.json"));
+    std::env::remove_var("MOCK_API_CLIENT");
+    std::env::remove_var("MOCK_KEYRING");
+    std::env::remove_var("MOCK_ACCESS_TOKEN");
+    std::env::remove_var("MOCK_REFRESH_TOKEN");
+}
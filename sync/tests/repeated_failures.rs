@@ -16,7 +16,7 @@ async fn test_periodic_sync_repeated_failures_reported() {
     let local = tokio::task::LocalSet::new();
     local
         .run_until(async {
-            let mut syncer = Syncer::new(file.path()).await.unwrap();
+            let syncer = Syncer::new(file.path()).await.unwrap();
             // drop mock so periodic sync fails
             std::env::remove_var("MOCK_API_CLIENT");
             let (prog_tx, _prog_rx) = mpsc::unbounded_channel();
@@ -0,0 +1,145 @@
+use sync::{SyncControl, SyncProgress, SyncTaskError, Syncer};
+use serial_test::serial;
+use cache::CacheManager;
+use tempfile::{NamedTempFile, TempDir};
+use tokio::sync::mpsc;
+use tokio::time::{timeout, Duration};
+
+async fn next_started(rx: &mut mpsc::UnboundedReceiver<SyncProgress>, wait: Duration) -> bool {
+    timeout(wait, async {
+        while let Some(p) = rx.recv().await {
+            if matches!(p, SyncProgress::Started) {
+                return true;
+            }
+        }
+        false
+    })
+    .await
+    .unwrap_or(false)
+}
+
+#[tokio::test(flavor = "current_thread")]
+#[serial]
+async fn test_sync_now_and_pause_between_runs() {
+    std::env::set_var("MOCK_KEYRING", "1");
+    std::env::set_var("MOCK_ACCESS_TOKEN", "token");
+    std::env::set_var("MOCK_REFRESH_TOKEN", "refresh");
+    std::env::set_var("MOCK_API_CLIENT", "1");
+    let file = NamedTempFile::new().unwrap();
+    let local = tokio::task::LocalSet::new();
+    local
+        .run_until(async {
+            let mut syncer = Syncer::new(file.path()).await.unwrap();
+            let (control_tx, control_rx) = mpsc::unbounded_channel();
+            syncer.set_control(control_rx);
+            let (prog_tx, mut prog_rx) = mpsc::unbounded_channel();
+            let (err_tx, _err_rx) = mpsc::unbounded_channel::<SyncTaskError>();
+            let (handle, shutdown) =
+                syncer.start_periodic_sync(Duration::from_secs(3600), prog_tx, err_tx, None, None, None);
+            assert!(next_started(&mut prog_rx, Duration::from_secs(5)).await);
+
+            control_tx.send(SyncControl::Pause).unwrap();
+            control_tx.send(SyncControl::Resume).unwrap();
+            assert!(!next_started(&mut prog_rx, Duration::from_millis(300)).await);
+
+            control_tx.send(SyncControl::SyncNow).unwrap();
+            assert!(next_started(&mut prog_rx, Duration::from_secs(5)).await);
+
+            let _ = shutdown.send(());
+            let _ = handle.await;
+        })
+        .await;
+    std::env::remove_var("MOCK_API_CLIENT");
+    std::env::remove_var("MOCK_KEYRING");
+    std::env::remove_var("MOCK_ACCESS_TOKEN");
+    std::env::remove_var("MOCK_REFRESH_TOKEN");
+}
+
+#[tokio::test(flavor = "current_thread")]
+#[serial]
+async fn test_set_interval_reschedules_next_run() {
+    std::env::set_var("MOCK_KEYRING", "1");
+    std::env::set_var("MOCK_ACCESS_TOKEN", "token");
+    std::env::set_var("MOCK_REFRESH_TOKEN", "refresh");
+    std::env::set_var("MOCK_API_CLIENT", "1");
+    let file = NamedTempFile::new().unwrap();
+    let local = tokio::task::LocalSet::new();
+    local
+        .run_until(async {
+            let mut syncer = Syncer::new(file.path()).await.unwrap();
+            let (control_tx, control_rx) = mpsc::unbounded_channel();
+            syncer.set_control(control_rx);
+            let (prog_tx, mut prog_rx) = mpsc::unbounded_channel();
+            let (err_tx, _err_rx) = mpsc::unbounded_channel::<SyncTaskError>();
+            let (handle, shutdown) =
+                syncer.start_periodic_sync(Duration::from_secs(3600), prog_tx, err_tx, None, None, None);
+            assert!(next_started(&mut prog_rx, Duration::from_secs(5)).await);
+
+            // The hour long wait already under way is shortened
+            control_tx.send(SyncControl::SetInterval(Duration::from_millis(200))).unwrap();
+            assert!(next_started(&mut prog_rx, Duration::from_secs(5)).await);
+            assert!(next_started(&mut prog_rx, Duration::from_secs(5)).await);
+
+            let _ = shutdown.send(());
+            let _ = handle.await;
+        })
+        .await;
+    std::env::remove_var("MOCK_API_CLIENT");
+    std::env::remove_var("MOCK_KEYRING");
+    std::env::remove_var("MOCK_ACCESS_TOKEN");
+    std::env::remove_var("MOCK_REFRESH_TOKEN");
+}
+
+#[tokio::test(flavor = "current_thread")]
+#[serial]
+async fn test_switch_account_syncs_into_new_cache() {
+    std::env::set_var("MOCK_KEYRING", "1");
+    std::env::set_var("MOCK_ACCESS_TOKEN", "token");
+    std::env::set_var("MOCK_REFRESH_TOKEN", "refresh");
+    std::env::set_var("MOCK_API_CLIENT", "1");
+    let file = NamedTempFile::new().unwrap();
+    let dir = TempDir::new().unwrap();
+    let other = dir.path().join("cache.sqlite");
+    let local = tokio::task::LocalSet::new();
+    local
+        .run_until(async {
+            auth::authenticate_for(Some("work"), 8080).await.unwrap();
+            let mut syncer = Syncer::new(file.path()).await.unwrap();
+            let (control_tx, control_rx) = mpsc::unbounded_channel();
+            syncer.set_control(control_rx);
+            let profile = syncer.profile_watch();
+            let (prog_tx, mut prog_rx) = mpsc::unbounded_channel();
+            let (err_tx, _err_rx) = mpsc::unbounded_channel::<SyncTaskError>();
+            let (handle, shutdown) =
+                syncer.start_periodic_sync(Duration::from_secs(3600), prog_tx, err_tx, None, None, None);
+            assert!(next_started(&mut prog_rx, Duration::from_secs(5)).await);
+            assert_eq!(*profile.borrow(), None);
+
+            control_tx
+                .send(SyncControl::SwitchAccount { db_path: other.clone(), profile: Some("work".into()) })
+                .unwrap();
+            assert!(next_started(&mut prog_rx, Duration::from_secs(5)).await);
+            timeout(Duration::from_secs(5), async {
+                while let Some(p) = prog_rx.recv().await {
+                    if matches!(p, SyncProgress::Finished(_)) {
+                        break;
+                    }
+                }
+            })
+            .await
+            .unwrap();
+            assert_eq!(profile.borrow().as_deref(), Some("work"));
+
+            let _ = shutdown.send(());
+            let _ = handle.await;
+        })
+        .await;
+    auth::logout_for(Some("work")).unwrap();
+    let items = CacheManager::new(&other).unwrap().get_all_media_items().unwrap();
+    assert!(!items.is_empty());
+    assert!(other.with_extension("state.json").exists());
+    std::env::remove_var("MOCK_API_CLIENT");
+    std::env::remove_var("MOCK_KEYRING");
+    std::env::remove_var("MOCK_ACCESS_TOKEN");
+    std::env::remove_var("MOCK_REFRESH_TOKEN");
+}
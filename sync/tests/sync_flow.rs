@@ -1,4 +1,4 @@
-use sync::{Syncer, SyncProgress};
+use sync::Syncer;
 use cache::CacheManager;
 use serial_test::serial;
 use tempfile::NamedTempFile;
@@ -30,3 +30,144 @@ async fn test_sync_flow_mock() {
     std::env::remove_var("MOCK_ACCESS_TOKEN");
     std::env::remove_var("MOCK_REFRESH_TOKEN");
 }
+
+#[tokio::test]
+#[serial]
+async fn test_resync_skips_unchanged_items() {
+    std::env::set_var("MOCK_API_CLIENT", "1");
+    std::env::set_var("MOCK_KEYRING", "1");
+    std::env::set_var("MOCK_ACCESS_TOKEN", "token");
+    std::env::set_var("MOCK_REFRESH_TOKEN", "refresh");
+    let file = NamedTempFile::new().unwrap();
+    let mut syncer = Syncer::new(file.path()).await.unwrap();
+    let first = syncer.sync_media_items(None, None, None, None).await.unwrap();
+    assert_eq!(first.inserted, first.fetched);
+    assert_eq!(first.inserted_ids.len() as u64, first.inserted);
+    assert_eq!(first.unchanged_streak, 0);
+    let second = syncer.sync_media_items(None, None, None, None).await.unwrap();
+    assert_eq!(second.inserted + second.updated, 0);
+    assert!(second.inserted_ids.is_empty());
+    assert_eq!(second.skipped, second.fetched);
+    assert_eq!(second.unchanged_streak, 1);
+    std::env::remove_var("MOCK_API_CLIENT");
+    std::env::remove_var("MOCK_KEYRING");
+    std::env::remove_var("MOCK_ACCESS_TOKEN");
+    std::env::remove_var("MOCK_REFRESH_TOKEN");
+}
+
+#[tokio::test]
+#[serial]
+async fn test_sync_flushes_queued_mutations() {
+    std::env::set_var("MOCK_API_CLIENT", "1");
+    std::env::set_var("MOCK_KEYRING", "1");
+    std::env::set_var("MOCK_ACCESS_TOKEN", "token");
+    std::env::set_var("MOCK_REFRESH_TOKEN", "refresh");
+    let file = NamedTempFile::new().unwrap();
+    let cache = CacheManager::new(file.path()).unwrap();
+    cache
+        .enqueue_mutation(&cache::Mutation::UpdateDescription {
+            media_item_id: "1".into(),
+            description: "offline edit".into(),
+        })
+        .unwrap();
+    let mut syncer = Syncer::new(file.path()).await.unwrap();
+    syncer.sync_media_items(None, None, None, None).await.unwrap();
+    assert!(cache.pending_mutations().unwrap().is_empty());
+    std::env::remove_var("MOCK_API_CLIENT");
+    std::env::remove_var("MOCK_KEYRING");
+    std::env::remove_var("MOCK_ACCESS_TOKEN");
+    std::env::remove_var("MOCK_REFRESH_TOKEN");
+}
+
+#[tokio::test]
+#[serial]
+async fn test_favorites_album_sync_pushes_and_reads_back() {
+    std::env::set_var("MOCK_API_CLIENT", "1");
+    std::env::set_var("MOCK_KEYRING", "1");
+    std::env::set_var("MOCK_ACCESS_TOKEN", "token");
+    std::env::set_var("MOCK_REFRESH_TOKEN", "refresh");
+    let file = NamedTempFile::new().unwrap();
+    let cache = CacheManager::new(file.path()).unwrap();
+    cache.record_uploaded_file("hash3", "3", "3.jpg").unwrap();
+    cache.enqueue_mutation(&cache::Mutation::favorites(vec!["3".into()], true)).unwrap();
+    let mut syncer = Syncer::new(file.path()).await.unwrap();
+    syncer.set_favorites_album_sync(true);
+    syncer.sync_media_items(None, None, None, None).await.unwrap();
+    assert!(cache.pending_mutations().unwrap().is_empty());
+    let state_path = file.path().with_extension("state.json");
+    let state: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&state_path).unwrap()).unwrap();
+    assert_eq!(state["favorites_album_id"], "1");
+
+    // A wiped cache gets its favorites back from the album
+    let wiped = NamedTempFile::new().unwrap();
+    std::fs::copy(&state_path, wiped.path().with_extension("state.json")).unwrap();
+    let mut syncer = Syncer::new(wiped.path()).await.unwrap();
+    syncer.set_favorites_album_sync(true);
+    syncer.sync_media_items(None, None, None, None).await.unwrap();
+    let cache = CacheManager::new(wiped.path()).unwrap();
+    let favorites: Vec<_> = cache.get_favorite_media_items().unwrap().into_iter().map(|i| i.id).collect();
+    assert_eq!(favorites, vec!["3"]);
+    let _ = std::fs::remove_file(state_path);
+    let _ = std::fs::remove_file(wiped.path().with_extension("state.json"));
+    std::env::remove_var("MOCK_API_CLIENT");
+    std::env::remove_var("MOCK_KEYRING");
+    std::env::remove_var("MOCK_ACCESS_TOKEN");
+    std::env::remove_var("MOCK_REFRESH_TOKEN");
+}
+
+#[tokio::test]
+#[serial]
+async fn test_favorites_album_sync_keeps_local_only_favorites() {
+    std::env::set_var("MOCK_API_CLIENT", "1");
+    std::env::set_var("MOCK_KEYRING", "1");
+    std::env::set_var("MOCK_ACCESS_TOKEN", "token");
+    std::env::set_var("MOCK_REFRESH_TOKEN", "refresh");
+    let file = NamedTempFile::new().unwrap();
+    let mut syncer = Syncer::new(file.path()).await.unwrap();
+    syncer.sync_media_items(None, None, None, None).await.unwrap();
+
+    // "1" came from another app and can never go in the album, "2" was uploaded here
+    let cache = CacheManager::new(file.path()).unwrap();
+    let synced = cache.get_media_item("3").unwrap().unwrap();
+    for id in ["1", "2"] {
+        let mut item = synced.clone();
+        item.id = id.into();
+        cache.insert_media_item(&item).unwrap();
+    }
+    cache.record_uploaded_file("hash2", "2", "2.jpg").unwrap();
+    cache.set_favorites(&["1".into(), "2".into()], true).unwrap();
+    cache.enqueue_mutation(&cache::Mutation::favorites(vec!["1".into(), "2".into()], true)).unwrap();
+
+    syncer.set_favorites_album_sync(true);
+    syncer.sync_media_items(None, None, None, None).await.unwrap();
+    assert!(cache.pending_mutations().unwrap().is_empty());
+    // The album read back holds "3" but not "2"; "1" is left alone
+    let mut favorites: Vec<_> = cache.get_favorite_media_items().unwrap().into_iter().map(|i| i.id).collect();
+    favorites.sort();
+    assert_eq!(favorites, vec!["1", "3"]);
+
+    let _ = std::fs::remove_file(file.path().with_extension("state.json"));
+    std::env::remove_var("MOCK_API_CLIENT");
+    std::env::remove_var("MOCK_KEYRING");
+    std::env::remove_var("MOCK_ACCESS_TOKEN");
+    std::env::remove_var("MOCK_REFRESH_TOKEN");
+}
+
+#[tokio::test]
+#[serial]
+async fn test_last_transfer_stats_saved_after_sync() {
+    std::env::set_var("MOCK_API_CLIENT", "1");
+    std::env::set_var("MOCK_KEYRING", "1");
+    std::env::set_var("MOCK_ACCESS_TOKEN", "token");
+    std::env::set_var("MOCK_REFRESH_TOKEN", "refresh");
+    let file = NamedTempFile::new().unwrap();
+    assert_eq!(sync::last_transfer_stats(file.path()), None);
+    let mut syncer = Syncer::new(file.path()).await.unwrap();
+    let report = syncer.sync_media_items(None, None, None, None).await.unwrap();
+    assert_eq!(sync::last_transfer_stats(file.path()), Some(report.transfer));
+    let _ = std::fs::remove_file(file.path().with_extension("state.json"));
+    std::env::remove_var("MOCK_API_CLIENT");
+    std::env::remove_var("MOCK_KEYRING");
+    std::env::remove_var("MOCK_ACCESS_TOKEN");
+    std::env::remove_var("MOCK_REFRESH_TOKEN");
+}
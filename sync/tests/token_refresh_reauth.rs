@@ -0,0 +1,37 @@
+use sync::{Syncer, SyncTaskError};
+use serial_test::serial;
+use tokio::sync::mpsc;
+use tokio::time::{sleep, timeout, Duration};
+
+#[tokio::test(flavor = "current_thread")]
+#[serial]
+async fn token_refresh_pauses_until_signed_in_again() {
+    std::env::set_var("MOCK_KEYRING", "1");
+    std::env::set_var("MOCK_REFRESH_REVOKED", "1");
+    let (err_tx, mut err_rx) = mpsc::unbounded_channel();
+    let local = tokio::task::LocalSet::new();
+    local
+        .run_until(async {
+            let (handle, shutdown) = Syncer::start_token_refresh_task(Duration::from_millis(20), err_tx, None, None);
+            let first = timeout(Duration::from_secs(1), err_rx.recv()).await.unwrap().unwrap();
+            assert!(matches!(first, SyncTaskError::ReauthRequired(_)), "unexpected {first:?}");
+            std::env::remove_var("MOCK_REFRESH_REVOKED");
+
+            // No retries, restart attempts or aborts while waiting for the sign-in
+            sleep(Duration::from_millis(200)).await;
+            assert!(err_rx.try_recv().is_err());
+
+            std::env::set_var("MOCK_ACCESS_TOKEN", "reauth_token");
+            auth::authenticate(0).await.unwrap();
+            let next = timeout(Duration::from_secs(1), err_rx.recv()).await.unwrap().unwrap();
+            assert!(
+                matches!(&next, SyncTaskError::Status { message, .. } if message == "Token refreshed"),
+                "unexpected {next:?}"
+            );
+            let _ = shutdown.send(());
+            let _ = handle.await;
+        })
+        .await;
+    std::env::remove_var("MOCK_ACCESS_TOKEN");
+    std::env::remove_var("MOCK_KEYRING");
+}
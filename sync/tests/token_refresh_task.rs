@@ -2,9 +2,10 @@ use sync::{Syncer, SyncTaskError};
 use serial_test::serial;
 use tokio::sync::mpsc;
 use tokio::time::{timeout, Duration};
+#[cfg(feature = "ui")]
 use tempfile::tempdir;
 #[cfg(feature = "ui")]
-use ui::{GooglePiczUI, Message};
+use ui::{GooglePiczUI, Message, UiFlags};
 #[cfg(feature = "ui")]
 use iced::Application;
 
@@ -54,7 +55,7 @@ fn test_token_refresh_error_forwarded_to_ui() {
     let dir = tempdir().unwrap();
     std::env::set_var("HOME", dir.path());
     std::fs::create_dir_all(dir.path().join(".googlepicz")).unwrap();
-    let (mut ui, _) = GooglePiczUI::new((None, None, None, 0, dir.path().join(".googlepicz")));
+    let (mut ui, _) = GooglePiczUI::new(UiFlags::new(dir.path().join(".googlepicz")));
     let _ = ui.update(Message::SyncError(err));
     assert!(ui.error_count() > 0);
     std::env::remove_var("MOCK_KEYRING");
@@ -24,10 +24,11 @@ async fn main() {
             video: None,
         },
         filename: "1.jpg".into(),
+        is_favorite: false,
     };
 
     cache1.insert_media_item(&item).expect("insert item");
-    let faces = vec![FaceData { bbox: [0, 0, 10, 10], name: Some("a".into()) }];
+    let faces = vec![FaceData { bbox: [0, 0, 10, 10], name: Some("a".into()), ignored: false, confidence: None }];
     let json = serde_json::to_string(&faces).unwrap();
     cache1.insert_faces(&item.id, &json).unwrap();
 
@@ -1,4 +1,4 @@
-use face_recognition::{FaceRecognizer, FaceRecognitionError};
+use face_recognition::{DetectorConfig, FaceRecognizer, FaceRecognitionError};
 use api_client::{MediaItem, MediaMetadata};
 use tempfile::TempDir;
 use base64::Engine;
@@ -31,9 +31,10 @@ async fn main() {
             video: None,
         },
         filename: "face.jpg".into(),
+        is_favorite: false,
     };
 
-    let recognizer = FaceRecognizer::new();
+    let recognizer = FaceRecognizer::new(DetectorConfig::default());
     let result = recognizer.detect_faces(&item);
     assert!(matches!(result, Err(FaceRecognitionError::ModelNotFound(_))));
 }
@@ -1,4 +1,4 @@
-use face_recognition::FaceRecognizer;
+use face_recognition::{DetectorConfig, FaceRecognizer};
 use api_client::{MediaItem, MediaMetadata};
 use cache::CacheManager;
 use tempfile::TempDir;
@@ -33,10 +33,11 @@ async fn main() {
             video: None,
         },
         filename: "face.jpg".into(),
+        is_favorite: false,
     };
 
     cache.insert_media_item(&item).expect("insert item");
-    let recognizer = FaceRecognizer::new();
+    let recognizer = FaceRecognizer::new(DetectorConfig::default());
     let faces = recognizer
         .detect_and_cache_faces(&cache, &item, true)
         .expect("detect faces");
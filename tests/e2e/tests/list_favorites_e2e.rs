@@ -24,6 +24,7 @@ async fn main() {
             video: None,
         },
         filename: "1.jpg".into(),
+        is_favorite: false,
     };
     let item2 = MediaItem {
         id: "2".into(),
@@ -38,6 +39,7 @@ async fn main() {
             video: None,
         },
         filename: "2.jpg".into(),
+        is_favorite: false,
     };
 
     cache.insert_media_item(&item1).expect("insert1");
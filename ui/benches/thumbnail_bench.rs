@@ -0,0 +1,56 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use std::io::Cursor;
+use ui::shrink_thumbnail;
+
+/// Thumbnails in the corpus, as if the API had ignored the crop hint for each.
+const CORPUS: usize = 500;
+const TILE: u32 = 150;
+
+fn oversized_corpus() -> Vec<Vec<u8>> {
+    (0..CORPUS as u32)
+        .map(|i| {
+            let img = image::RgbImage::from_fn(1024, 768, |x, y| {
+                image::Rgb([(x + i) as u8, (y + i) as u8, ((x ^ y) + i) as u8])
+            });
+            let mut jpeg = Cursor::new(Vec::new());
+            img.write_to(&mut jpeg, image::ImageOutputFormat::Jpeg(90)).unwrap();
+            jpeg.into_inner()
+        })
+        .collect()
+}
+
+fn bench_thumbnails(c: &mut Criterion) {
+    let originals = oversized_corpus();
+    let stored: Vec<Vec<u8>> = originals
+        .iter()
+        .map(|bytes| shrink_thumbnail(bytes, TILE).unwrap_or_else(|| bytes.clone()))
+        .collect();
+    let size = |files: &[Vec<u8>]| files.iter().map(Vec::len).sum::<usize>();
+    println!(
+        "disk usage for {} thumbnails: {} bytes as downloaded, {} bytes stored",
+        CORPUS,
+        size(&originals),
+        size(&stored)
+    );
+
+    let mut group = c.benchmark_group("thumbnail_decode");
+    group.sample_size(10);
+    group.bench_function("as_downloaded", |b| {
+        b.iter(|| {
+            for bytes in &originals {
+                image::load_from_memory(bytes).unwrap();
+            }
+        })
+    });
+    group.bench_function("stored", |b| {
+        b.iter(|| {
+            for bytes in &stored {
+                image::load_from_memory(bytes).unwrap();
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_thumbnails);
+criterion_main!(benches);
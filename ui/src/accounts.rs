@@ -0,0 +1,220 @@
+//! Account menu in the header: the signed in account with its avatar, the
+//! other configured accounts to switch to, adding one and signing out.
+//!
+//! Each account is a config profile with its own tokens and cache. The
+//! account used last is kept in the UI state next to the default profile so
+//! the next launch opens it again.
+
+use std::path::PathBuf;
+
+use iced::widget::image::Handle;
+use iced::widget::{button, column, container, image, row, text, Column};
+use iced::{Element, Length};
+
+use crate::app_config;
+use crate::i18n::tr;
+use crate::style::{self, Palette};
+use crate::ui_state::{self, UiState};
+use crate::{Icon, MaterialSymbol, Message};
+
+const AVATAR_SIZE: f32 = 24.0;
+
+/// A configured account; `profile` is `None` for the default profile.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Account {
+    pub profile: Option<String>,
+    /// Email stored with the tokens, `None` when not signed in
+    pub email: Option<String>,
+}
+
+impl Account {
+    pub fn label(&self) -> String {
+        match (&self.email, &self.profile) {
+            (Some(email), _) => email.clone(),
+            (None, Some(profile)) => profile.clone(),
+            (None, None) => tr("accounts.default").to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct AccountMenu {
+    pub open: bool,
+    /// Default profile first, then the others by name
+    pub accounts: Vec<Account>,
+    /// Email, name and avatar URL of the active account
+    pub user: Option<auth::UserInfo>,
+    pub avatar: Option<Handle>,
+    /// Profile created for the account being added, removed again if
+    /// signing in fails
+    pub adding: Option<String>,
+    /// Bumped on every switch so the sync subscriptions restart
+    pub generation: u64,
+    /// Google rejected the refresh token; the "Sign in again" prompt is shown
+    pub reauth_required: bool,
+    /// Signing in again from that prompt is under way
+    pub reauthenticating: bool,
+}
+
+/// Configured accounts with the email stored for each.
+pub fn list() -> Vec<Account> {
+    std::iter::once(None)
+        .chain(app_config::list_profiles().into_iter().map(Some))
+        .map(|profile| {
+            let email = auth::get_token_status_for(profile.as_deref())
+                .ok()
+                .and_then(|s| s.account);
+            Account { profile, email }
+        })
+        .collect()
+}
+
+/// First `account-N` without a profile folder yet.
+pub fn next_profile_name() -> String {
+    (1..)
+        .map(|n| format!("account-{}", n))
+        .find(|name| !app_config::profile_dir(Some(name)).exists())
+        .unwrap_or_default()
+}
+
+/// UI state file holding `last_account`, shared by all profiles.
+fn shared_state_path() -> PathBuf {
+    app_config::base_dir().join(ui_state::FILE_NAME)
+}
+
+/// Profile of the account used last; `None` for the default profile.
+pub fn last_account() -> Option<String> {
+    UiState::load(&shared_state_path())
+        .last_account
+        .filter(|name| app_config::valid_profile_name(name) && app_config::config_path(Some(name)).exists())
+}
+
+/// Record `profile` as the account to open on the next launch.
+pub fn remember(profile: Option<&str>) -> std::io::Result<()> {
+    let path = shared_state_path();
+    let mut state = UiState::load(&path);
+    state.last_account = profile.map(str::to_string);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    state.save(&path)
+}
+
+pub async fn load_avatar(url: String) -> Result<Handle, String> {
+    let bytes = reqwest::get(&url)
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| e.to_string())?
+        .bytes()
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(Handle::from_memory(bytes.to_vec()))
+}
+
+/// Header button showing the active account.
+pub fn view<'a>(ui: &crate::GooglePiczUI) -> Element<'a, Message> {
+    let menu = &ui.account_menu;
+    let avatar: Element<'a, Message> = match &menu.avatar {
+        Some(handle) => image(handle.clone())
+            .width(Length::Fixed(AVATAR_SIZE))
+            .height(Length::Fixed(AVATAR_SIZE))
+            .into(),
+        None => Icon::new(MaterialSymbol::AccountCircle).into(),
+    };
+    let label = match menu.user.as_ref().and_then(|u| u.email.clone()) {
+        Some(email) => email,
+        None => tr("accounts.signed_out").to_string(),
+    };
+    button(
+        row![avatar, text(label)]
+            .spacing(Palette::SPACING / 2)
+            .align_items(iced::Alignment::Center),
+    )
+    .style(iced::theme::Button::Text)
+    .on_press(Message::ToggleAccountMenu)
+    .into()
+}
+
+/// Menu opened from the header button.
+pub fn menu<'a>(ui: &crate::GooglePiczUI) -> Option<Element<'a, Message>> {
+    let menu = &ui.account_menu;
+    if !menu.open {
+        return None;
+    }
+    let mut list = Column::new().spacing(Palette::SPACING / 4);
+    for account in &menu.accounts {
+        let active = account.profile.as_deref() == ui.profile();
+        let marker: Element<'a, Message> = if active {
+            Icon::new(MaterialSymbol::Check).into()
+        } else {
+            text("").width(Length::Fixed(AVATAR_SIZE)).into()
+        };
+        list = list.push(
+            button(row![marker, text(account.label())].spacing(Palette::SPACING / 2))
+                .style(iced::theme::Button::Text)
+                .width(Length::Fill)
+                .on_press_maybe((!active && menu.adding.is_none()).then(|| Message::SwitchAccount(account.profile.clone()))),
+        );
+    }
+    let signed_in = menu.user.is_some();
+    let actions = match &menu.adding {
+        Some(_) => row![text(tr("accounts.adding"))],
+        None => row![
+            button(row![Icon::new(MaterialSymbol::PersonAdd), text(tr("accounts.add"))].spacing(Palette::SPACING / 4))
+                .style(style::button_secondary())
+                .on_press(Message::AddAccount),
+            button(row![Icon::new(MaterialSymbol::Logout), text(tr("accounts.sign_out"))].spacing(Palette::SPACING / 4))
+                .style(style::button_secondary())
+                .on_press_maybe(signed_in.then_some(Message::SignOut)),
+        ],
+    }
+    .spacing(Palette::SPACING / 2)
+    .align_items(iced::Alignment::Center);
+    let mut col = column![list, actions].spacing(Palette::SPACING / 2);
+    if let Some(name) = menu.user.as_ref().and_then(|u| u.name.clone()) {
+        col = column![text(name).size(16)].push(col).spacing(Palette::SPACING / 2);
+    }
+    Some(
+        container(col)
+            .style(style::card())
+            .padding(Palette::SPACING / 2)
+            .width(Length::Fixed(320.0))
+            .into(),
+    )
+}
+
+/// Prompt to sign in again after Google rejected the refresh token. Stays
+/// until the sign-in succeeds, since nothing syncs before.
+pub fn reauth_dialog<'a>(ui: &crate::GooglePiczUI) -> Option<Element<'a, Message>> {
+    let menu = &ui.account_menu;
+    if !menu.reauth_required {
+        return None;
+    }
+    let action: Element<'a, Message> = if menu.reauthenticating {
+        text(tr("accounts.reauthenticating")).size(14).into()
+    } else {
+        button(
+            row![
+                Icon::new(MaterialSymbol::Login).color(Palette::ON_PRIMARY),
+                text(tr("accounts.reauth")),
+            ]
+            .spacing(Palette::SPACING / 2),
+        )
+        .style(style::button_primary())
+        .on_press(Message::Reauthenticate)
+        .into()
+    };
+    Some(
+        container(
+            column![
+                text(tr("accounts.reauth_title")).size(16),
+                text(tr("accounts.reauth_body")).size(14),
+                action,
+            ]
+            .spacing(Palette::SPACING),
+        )
+        .style(style::dialog())
+        .padding(Palette::SPACING)
+        .into(),
+    )
+}
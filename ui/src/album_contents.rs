@@ -0,0 +1,77 @@
+//! Album contents: cache first, then the API.
+//!
+//! Selecting an album shows its cached items straight away. A refresh from
+//! the API then runs in the background once they are shown, walks every page
+//! of the album, writes new items and their album membership to the cache and
+//! merges them into the grid. Items removed from the album upstream leave both
+//! the cache membership and the grid once the last page is in. When the
+//! refresh fails the cached items stay on screen.
+
+use api_client::{ApiClient, MediaItem};
+use cache::CacheManager;
+
+/// Items requested per API page.
+const API_PAGE_SIZE: i32 = 100;
+
+/// Fetch all of the album's items from the API with the tokens of
+/// `profile`, page by page, and record each page in the cache as it arrives.
+/// After the last page the album's cached membership is replaced by the
+/// fresh set.
+pub async fn refresh(cache: CacheManager, album_id: String, profile: Option<String>) -> Result<Vec<MediaItem>, String> {
+    let token = auth::ensure_access_token_valid_for(profile.as_deref())
+        .await
+        .map_err(|e| e.to_string())?;
+    let client = ApiClient::new(token);
+    let mut all = Vec::new();
+    let mut page_token = None;
+    loop {
+        let (items, next_page_token) = client
+            .get_album_media_items(&album_id, API_PAGE_SIZE, page_token)
+            .await
+            .map_err(|e| e.to_string())?;
+        cache
+            .upsert_media_items_batch_async(items.clone())
+            .await
+            .map_err(|e| e.to_string())?;
+        let ids = items.iter().map(|item| item.id.clone()).collect();
+        cache
+            .associate_media_items_with_album_async(ids, album_id.clone())
+            .await
+            .map_err(|e| e.to_string())?;
+        all.extend(items);
+        match next_page_token {
+            Some(next) => page_token = Some(next),
+            None => break,
+        }
+    }
+    let ids = all.iter().map(|item| item.id.clone()).collect();
+    cache
+        .set_album_media_items_async(album_id, ids)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(all)
+}
+
+/// Update `photos` with `fresh`, adding items not shown yet, dropping those
+/// no longer in the album and skipping trashed ones. A favorite set locally
+/// stays set, as it does in the cache. Returns whether items were added or
+/// removed.
+pub fn merge(photos: &mut Vec<MediaItem>, fresh: Vec<MediaItem>, trash: &crate::trash::Trash) -> bool {
+    let shown = photos.len();
+    photos.retain(|p| fresh.iter().any(|item| item.id == p.id));
+    let mut changed = photos.len() != shown;
+    for item in fresh.into_iter().filter(|item| !trash.contains(&item.id)) {
+        match photos.iter_mut().find(|p| p.id == item.id) {
+            Some(existing) => {
+                let is_favorite = existing.is_favorite;
+                *existing = item;
+                existing.is_favorite = is_favorite;
+            }
+            None => {
+                photos.push(item);
+                changed = true;
+            }
+        }
+    }
+    changed
+}
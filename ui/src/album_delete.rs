@@ -0,0 +1,98 @@
+//! Deferred album deletion behind an undo toast.
+//!
+//! Deleting an album only hides it from the UI and the cache at first; the
+//! API call waits until the undo toast expires. Undo then only has to put the
+//! cached rows back, since the album never left Google Photos.
+
+use std::sync::Arc;
+
+use api_client::{Album, ApiClient};
+use cache::CacheManager;
+use tokio::sync::Mutex;
+
+/// How long the "Album deleted — Undo" toast stays on screen.
+pub const UNDO_DURATION: std::time::Duration = std::time::Duration::from_secs(8);
+
+/// An album removed locally whose API delete has not been sent yet.
+#[derive(Debug, Clone)]
+pub struct PendingAlbumDelete {
+    pub album: Album,
+    /// Position in the album list, so undo puts it back where it was
+    pub index: usize,
+    /// Cached membership captured before the cache rows were deleted
+    pub media_item_ids: Vec<String>,
+    pub notification: Option<u64>,
+}
+
+async fn cache(cache_manager: &Option<Arc<Mutex<CacheManager>>>) -> Option<CacheManager> {
+    match cache_manager {
+        Some(cm) => Some(cm.lock().await.clone()),
+        None => None,
+    }
+}
+
+/// Capture the album's membership, then drop it from the cache.
+pub async fn stage(
+    cache_manager: Option<Arc<Mutex<CacheManager>>>,
+    album_id: String,
+) -> Result<Vec<String>, String> {
+    let Some(cache) = cache(&cache_manager).await else {
+        return Ok(Vec::new());
+    };
+    let ids = cache
+        .get_media_items_by_album_async(album_id.clone())
+        .await
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|item| item.id)
+        .collect();
+    cache.delete_album_async(album_id).await.map_err(|e| e.to_string())?;
+    Ok(ids)
+}
+
+/// Put the album and its membership back into the cache.
+pub async fn restore(
+    cache_manager: Option<Arc<Mutex<CacheManager>>>,
+    pending: PendingAlbumDelete,
+) -> Result<(), String> {
+    let Some(cache) = cache(&cache_manager).await else {
+        return Ok(());
+    };
+    let album_id = pending.album.id.clone();
+    cache.insert_album_async(pending.album).await.map_err(|e| e.to_string())?;
+    cache
+        .associate_media_items_with_album_async(pending.media_item_ids, album_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Send the API delete once undo is no longer offered. If it fails the
+/// cached rows are restored so the cache keeps matching the server.
+pub async fn commit(
+    cache_manager: Option<Arc<Mutex<CacheManager>>>,
+    pending: PendingAlbumDelete,
+    profile: Option<String>,
+) -> Result<(), String> {
+    let album_id = pending.album.id.clone();
+    let deleted = async {
+        let token = auth::ensure_access_token_valid_for(profile.as_deref())
+            .await
+            .map_err(|e| e.to_string())?;
+        ApiClient::new(token)
+            .delete_album(&album_id)
+            .await
+            .map_err(|e| e.to_string())
+    }
+    .await;
+    if let Err(err) = deleted {
+        if let Err(restore_err) = restore(cache_manager, pending).await {
+            tracing::warn!("Failed to restore album {} after failed delete: {}", album_id, restore_err);
+        }
+        return Err(err);
+    }
+    // A sync during the undo window may have cached the album again
+    if let Some(cache) = cache(&cache_manager).await {
+        cache.delete_album_async(album_id).await.map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
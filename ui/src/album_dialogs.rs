@@ -1,5 +1,6 @@
-use iced::widget::{button, column, row, text, text_input};
+use iced::widget::{button, column, container, row, text, text_input};
 
+use crate::i18n::{tr, trf};
 use crate::{style, Icon, MaterialSymbol, Message};
 use crate::style::Palette;
 
@@ -20,13 +21,15 @@ pub fn create_dialog<'a>(ui: &crate::GooglePiczUI) -> Option<iced::Element<'a, M
         Some(
             container(
                 column![
-                text_input("Album title", &ui.new_album_title)
+                text_input(tr("album.title"), &ui.new_album_title)
                     .style(style::text_input())
                     .on_input(Message::AlbumTitleChanged),
                 row![
-                    button(Icon::new(MaterialSymbol::Add).color(Palette::ON_PRIMARY))
-                        .style(style::button_primary())
-                        .on_press(Message::CreateAlbum),
+                    crate::offline::online_only(
+                        ui,
+                        button(Icon::new(MaterialSymbol::Add).color(Palette::ON_PRIMARY)).style(style::button_primary()),
+                        Message::CreateAlbum,
+                    ),
                     button(Icon::new(MaterialSymbol::Cancel).color(Palette::ON_SECONDARY))
                         .style(style::button_secondary())
                         .on_press(Message::CancelCreateAlbum),
@@ -48,13 +51,15 @@ pub fn rename_dialog<'a>(ui: &crate::GooglePiczUI) -> Option<iced::Element<'a, M
         Some(
             container(
                 column![
-                text_input("New title", &ui.rename_album_title)
+                text_input(tr("album.new_title"), &ui.rename_album_title)
                     .style(style::text_input())
                     .on_input(Message::RenameAlbumTitleChanged),
                 row![
-                    button(Icon::new(MaterialSymbol::Save).color(Palette::ON_PRIMARY))
-                        .style(style::button_primary())
-                        .on_press(Message::ConfirmRenameAlbum),
+                    crate::offline::online_only(
+                        ui,
+                        button(Icon::new(MaterialSymbol::Save).color(Palette::ON_PRIMARY)).style(style::button_primary()),
+                        Message::ConfirmRenameAlbum,
+                    ),
                     button(Icon::new(MaterialSymbol::Cancel).color(Palette::ON_SECONDARY))
                         .style(style::button_secondary())
                         .on_press(Message::CancelRenameAlbum),
@@ -76,7 +81,7 @@ pub fn delete_dialog<'a>(ui: &crate::GooglePiczUI) -> Option<iced::Element<'a, M
         Some(
             container(
                 column![
-                text("Delete album?").size(16),
+                text(tr("album.delete_confirm")).size(16),
                 row![
                     button(Icon::new(MaterialSymbol::Delete).color(Palette::ON_PRIMARY))
                         .style(style::button_primary())
@@ -97,3 +102,33 @@ pub fn delete_dialog<'a>(ui: &crate::GooglePiczUI) -> Option<iced::Element<'a, M
     }
 }
 
+
+pub fn remove_photo_dialog<'a>(ui: &crate::GooglePiczUI) -> Option<iced::Element<'a, Message>> {
+    if let Some(ids) = &ui.removing_photos {
+        let prompt = match ids.len() {
+            1 => tr("album.remove_photo_confirm").to_string(),
+            n => trf("album.remove_photos_confirm", &[&n]),
+        };
+        Some(
+            container(
+                column![
+                text(prompt).size(16),
+                row![
+                    button(Icon::new(MaterialSymbol::Delete).color(Palette::ON_PRIMARY))
+                        .style(style::button_primary())
+                        .on_press(Message::ConfirmRemovePhoto),
+                    button(Icon::new(MaterialSymbol::Cancel).color(Palette::ON_SECONDARY))
+                        .style(style::button_secondary())
+                        .on_press(Message::CancelRemovePhoto),
+                ]
+                .spacing(Palette::SPACING),
+            ]
+            .spacing(Palette::SPACING))
+                .style(style::dialog())
+                .padding(Palette::SPACING)
+                .into(),
+        )
+    } else {
+        None
+    }
+}
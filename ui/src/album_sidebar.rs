@@ -0,0 +1,378 @@
+//! Left-hand sidebar listing the albums with their covers and item counts.
+//!
+//! "All photos", "Favorites" and "Trash" are pinned above the saved filters
+//! and the albums. Narrow windows collapse the sidebar to its covers and icons.
+//! When the list is taller than the sidebar, chevrons at either end page it,
+//! Home and End jump to its ends while the pointer is over it, and "All
+//! albums…" opens an alphabetical list of every album with a filter.
+
+use std::collections::HashMap;
+
+use api_client::Album;
+use iced::widget::image::Handle;
+use iced::widget::tooltip::Position;
+use iced::widget::{button, column, container, image, mouse_area, row, scrollable, text, text_input, tooltip};
+use iced::{Command, Element, Length};
+
+use crate::i18n::{tr, trf};
+use crate::search::ActiveFilters;
+use crate::style::{self, Palette};
+use crate::{Icon, MaterialSymbol, Message};
+
+/// Side length of the cover thumbnails.
+const COVER_SIZE: f32 = 40.0;
+/// Width of the expanded sidebar.
+const EXPANDED_WIDTH: f32 = 240.0;
+/// Width of the sidebar showing only covers and icons.
+const COLLAPSED_WIDTH: f32 = COVER_SIZE + 2.0 * Palette::SPACING as f32;
+/// Windows narrower than this collapse the sidebar.
+const COLLAPSE_BELOW: f32 = 900.0;
+
+/// Share of the visible height a chevron scrolls by.
+const PAGE_FRACTION: f32 = 0.8;
+/// Height of the album list in the "All albums…" menu.
+const MENU_HEIGHT: f32 = 320.0;
+
+pub fn scroll_id() -> scrollable::Id {
+    scrollable::Id::new("album-sidebar")
+}
+
+/// Position and size of the sidebar list, as last reported by its viewport.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ListViewport {
+    pub offset: f32,
+    pub height: f32,
+    pub content_height: f32,
+}
+
+impl ListViewport {
+    pub fn from_viewport(viewport: &scrollable::Viewport) -> Self {
+        Self {
+            offset: viewport.absolute_offset().y,
+            height: viewport.bounds().height,
+            content_height: viewport.content_bounds().height,
+        }
+    }
+
+    /// Whether the list is taller than the sidebar.
+    pub fn overflows(&self) -> bool {
+        self.content_height > self.height + 1.0
+    }
+
+    pub fn more_above(&self) -> bool {
+        self.overflows() && self.offset > 1.0
+    }
+
+    pub fn more_below(&self) -> bool {
+        self.overflows() && self.offset + self.height < self.content_height - 1.0
+    }
+}
+
+/// Where a chevron or key moves the sidebar list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scroll {
+    PageUp,
+    PageDown,
+    Start,
+    End,
+}
+
+#[derive(Debug, Default)]
+pub struct AlbumSidebar {
+    /// Cover thumbnails keyed by album id
+    pub covers: HashMap<String, Handle>,
+    /// Cached item counts keyed by album id
+    pub counts: HashMap<String, u64>,
+    pub viewport: ListViewport,
+    /// The pointer is over the sidebar, so Home and End move its list
+    pub hovered: bool,
+    /// Filter typed into the "All albums…" menu while it is open
+    pub menu: Option<String>,
+}
+
+impl AlbumSidebar {
+    /// Cached item count, falling back to the count reported by the API.
+    fn count(&self, album: &Album) -> Option<u64> {
+        self.counts.get(&album.id).copied().or_else(|| {
+            album.media_items_count.as_deref().and_then(|c| c.parse().ok())
+        })
+    }
+
+    pub fn scroll(&self, to: Scroll) -> Command<Message> {
+        let page = self.viewport.height * PAGE_FRACTION;
+        let max = (self.viewport.content_height - self.viewport.height).max(0.0);
+        match to {
+            Scroll::PageUp => scrollable::scroll_to(
+                scroll_id(),
+                scrollable::AbsoluteOffset { x: 0.0, y: (self.viewport.offset - page).max(0.0) },
+            ),
+            Scroll::PageDown => scrollable::scroll_to(
+                scroll_id(),
+                scrollable::AbsoluteOffset { x: 0.0, y: (self.viewport.offset + page).min(max) },
+            ),
+            Scroll::Start => scrollable::snap_to(scroll_id(), scrollable::RelativeOffset::START),
+            Scroll::End => scrollable::snap_to(scroll_id(), scrollable::RelativeOffset::END),
+        }
+    }
+}
+
+fn title(album: &Album) -> String {
+    album.title.clone().unwrap_or_else(|| tr("common.untitled").to_string())
+}
+
+/// Albums whose title contains `filter`, ignoring case, in alphabetical order.
+pub fn menu_albums<'a>(albums: &'a [Album], filter: &str) -> Vec<&'a Album> {
+    let filter = filter.trim().to_lowercase();
+    let mut matches: Vec<&Album> = albums
+        .iter()
+        .filter(|a| title(a).to_lowercase().contains(&filter))
+        .collect();
+    matches.sort_by_cached_key(|a| (title(a).to_lowercase(), a.id.clone()));
+    matches
+}
+
+pub fn collapsed(window_width: f32) -> bool {
+    window_width < COLLAPSE_BELOW
+}
+
+/// Width the sidebar takes from the grid at this window width.
+pub fn width(window_width: f32) -> f32 {
+    if collapsed(window_width) {
+        COLLAPSED_WIDTH
+    } else {
+        EXPANDED_WIDTH
+    }
+}
+
+fn cover<'a>(handle: Option<&Handle>, fallback: MaterialSymbol) -> Element<'a, Message> {
+    match handle {
+        Some(handle) => image(handle.clone())
+            .width(Length::Fixed(COVER_SIZE))
+            .height(Length::Fixed(COVER_SIZE))
+            .into(),
+        None => container(Icon::new(fallback))
+            .width(Length::Fixed(COVER_SIZE))
+            .height(Length::Fixed(COVER_SIZE))
+            .center_x()
+            .center_y()
+            .into(),
+    }
+}
+
+/// One sidebar line: the cover, plus title and count unless collapsed.
+fn entry<'a>(
+    cover: Element<'a, Message>,
+    title: String,
+    count: Option<u64>,
+    selected: bool,
+    collapsed: bool,
+    on_press: Message,
+) -> Element<'a, Message> {
+    let content: Element<'a, Message> = if collapsed {
+        cover
+    } else {
+        let mut label = column![text(title.clone()).size(14)];
+        if let Some(count) = count {
+            label = label.push(text(trf("albums.item_count", &[&count])).size(12));
+        }
+        row![cover, label]
+            .spacing(Palette::SPACING / 2)
+            .align_items(iced::Alignment::Center)
+            .into()
+    };
+    let entry = button(content)
+        .style(if selected { style::button_primary() } else { style::button_secondary() })
+        .width(Length::Fill)
+        .on_press(on_press);
+    if collapsed {
+        tooltip(entry, text(title).size(14), Position::Right)
+            .style(style::card())
+            .into()
+    } else {
+        entry.into()
+    }
+}
+
+pub fn view<'a>(ui: &crate::GooglePiczUI) -> Element<'a, Message> {
+    let collapsed = collapsed(ui.ui_state.width);
+    let library = ui.selected_album.is_none() && !ui.people.has_selection() && !ui.trash.open;
+    let mut col = column![
+        entry(
+            cover(None, MaterialSymbol::PhotoLibrary),
+            tr("albums.all_photos").to_string(),
+            None,
+            library && !ui.favorites_only,
+            collapsed,
+            Message::SelectAlbum(None),
+        ),
+        entry(
+            cover(None, MaterialSymbol::Favorite),
+            tr("albums.favorites").to_string(),
+            None,
+            library && ui.favorites_only,
+            collapsed,
+            Message::SelectFavorites,
+        ),
+        entry(
+            cover(None, MaterialSymbol::Delete),
+            tr("albums.trash").to_string(),
+            (!ui.trash.items.is_empty()).then_some(ui.trash.items.len() as u64),
+            ui.trash.open,
+            collapsed,
+            Message::SelectTrash,
+        ),
+    ]
+    .spacing(Palette::SPACING / 2);
+    let saved = &ui.ui_state.saved_searches;
+    if !saved.is_empty() && !collapsed {
+        col = col.push(text(tr("albums.saved_filters")).size(16));
+    }
+    let active = ui.active_filters();
+    for (i, search) in saved.iter().enumerate() {
+        let line = entry(
+            cover(None, MaterialSymbol::SavedSearch),
+            search.label(),
+            None,
+            !active.is_empty() && ActiveFilters::from_search(search) == active,
+            collapsed,
+            Message::SelectSavedSearch(search.clone()),
+        );
+        if collapsed {
+            col = col.push(line);
+        } else {
+            col = col.push(
+                row![
+                    line,
+                    button(Icon::new(MaterialSymbol::Delete).size(16))
+                        .style(style::button_secondary())
+                        .on_press(Message::DeleteSavedSearch(i)),
+                ]
+                .spacing(Palette::SPACING / 4)
+                .align_items(iced::Alignment::Center),
+            );
+        }
+    }
+    if !collapsed {
+        col = col.push(text(tr("albums.title")).size(16));
+    }
+    if ui.album_sidebar.viewport.overflows() {
+        col = col.push(entry(
+            cover(None, MaterialSymbol::List),
+            tr("albums.all_albums").to_string(),
+            None,
+            false,
+            collapsed,
+            Message::ShowAllAlbums,
+        ));
+    }
+    for album in &ui.albums {
+        let title = title(album);
+        let line = entry(
+            cover(ui.album_sidebar.covers.get(&album.id), MaterialSymbol::PhotoAlbum),
+            title.clone(),
+            ui.album_sidebar.count(album),
+            ui.selected_album.as_deref() == Some(album.id.as_str()),
+            collapsed,
+            Message::SelectAlbum(Some(album.id.clone())),
+        );
+        // Albums are drop targets while photos are dragged
+        let line: Element<Message> = if ui.drag.targets(&album.id) {
+            container(line).style(style::tile_focused()).padding(2).into()
+        } else {
+            line
+        };
+        let line = mouse_area(line)
+            .on_enter(Message::AlbumHovered(album.id.clone()))
+            .on_exit(Message::AlbumUnhovered(album.id.clone()));
+        if collapsed {
+            col = col.push(line);
+        } else {
+            col = col.push(
+                row![
+                    line,
+                    crate::offline::online_only(
+                        ui,
+                        button(Icon::new(MaterialSymbol::Edit).size(16)).style(style::button_secondary()),
+                        Message::ShowRenameAlbumDialog(album.id.clone(), title),
+                    ),
+                    button(Icon::new(MaterialSymbol::Delete).size(16))
+                        .style(style::button_secondary())
+                        .on_press(Message::ShowDeleteAlbumDialog(album.id.clone())),
+                ]
+                .spacing(Palette::SPACING / 4)
+                .align_items(iced::Alignment::Center),
+            );
+        }
+    }
+    let viewport = ui.album_sidebar.viewport;
+    let mut content = column![].spacing(Palette::SPACING / 4);
+    if viewport.more_above() {
+        content = content.push(chevron(MaterialSymbol::ExpandLess, Scroll::PageUp));
+    }
+    content = content.push(
+        scrollable(col)
+            .id(scroll_id())
+            .on_scroll(Message::AlbumSidebarScrolled)
+            .height(Length::Fill),
+    );
+    if viewport.more_below() {
+        content = content.push(chevron(MaterialSymbol::ExpandMore, Scroll::PageDown));
+    }
+    mouse_area(
+        container(content)
+            .style(style::card())
+            .padding(Palette::SPACING / 2)
+            .width(Length::Fixed(width(ui.ui_state.width)))
+            .height(Length::Fill),
+    )
+    .on_enter(Message::AlbumSidebarHovered(true))
+    .on_exit(Message::AlbumSidebarHovered(false))
+    .into()
+}
+
+/// Full-width button paging the album list, shown while there is more that way.
+fn chevron<'a>(symbol: MaterialSymbol, to: Scroll) -> Element<'a, Message> {
+    button(container(Icon::new(symbol).size(16)).width(Length::Fill).center_x())
+        .style(style::button_secondary())
+        .width(Length::Fill)
+        .padding(2)
+        .on_press(Message::ScrollAlbumSidebar(to))
+        .into()
+}
+
+/// "All albums…": every album in alphabetical order, narrowed by a filter.
+pub fn all_albums_dialog<'a>(ui: &crate::GooglePiczUI) -> Option<Element<'a, Message>> {
+    let filter = ui.album_sidebar.menu.as_ref()?;
+    let albums = menu_albums(&ui.albums, filter);
+    let list: Element<'a, Message> = if albums.is_empty() {
+        text(tr("albums.no_match")).size(14).into()
+    } else {
+        let mut list = column![].spacing(Palette::SPACING / 4);
+        for album in albums {
+            let selected = ui.selected_album.as_deref() == Some(album.id.as_str());
+            list = list.push(
+                button(text(title(album)).size(14))
+                    .style(if selected { style::button_primary() } else { style::button_secondary() })
+                    .width(Length::Fill)
+                    .on_press(Message::SelectAlbum(Some(album.id.clone()))),
+            );
+        }
+        scrollable(list).height(Length::Fixed(MENU_HEIGHT)).into()
+    };
+    Some(
+        container(
+            column![
+                text(tr("albums.all_albums")).size(16),
+                text_input(tr("albums.filter"), filter).on_input(Message::AllAlbumsFilterChanged),
+                list,
+                button(Icon::new(MaterialSymbol::Cancel).color(Palette::ON_SECONDARY))
+                    .style(style::button_secondary())
+                    .on_press(Message::CloseAllAlbums),
+            ]
+            .spacing(Palette::SPACING),
+        )
+        .style(style::dialog())
+        .padding(Palette::SPACING)
+        .into(),
+    )
+}
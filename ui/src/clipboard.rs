@@ -0,0 +1,46 @@
+//! Copying photos to the system clipboard.
+//!
+//! iced can only put text on the clipboard, so images go through `arboard`.
+
+use std::borrow::Cow;
+use std::sync::Mutex;
+
+use iced::widget::image::{self, Handle};
+
+/// Kept open for the whole session: on X11 and Wayland the image is served
+/// by this process and would vanish as soon as the clipboard is dropped.
+static CLIPBOARD: Mutex<Option<arboard::Clipboard>> = Mutex::new(None);
+
+/// Decode the pixels behind `handle` into RGBA.
+fn decode(handle: &Handle) -> Result<::image::RgbaImage, String> {
+    let img = match handle.data() {
+        image::Data::Path(path) => ::image::open(path),
+        image::Data::Bytes(bytes) => ::image::load_from_memory(bytes),
+        image::Data::Rgba { width, height, pixels } => {
+            return ::image::RgbaImage::from_raw(*width, *height, pixels.to_vec())
+                .ok_or_else(|| "invalid pixel buffer".to_string());
+        }
+    };
+    img.map(|i| i.to_rgba8()).map_err(|e| e.to_string())
+}
+
+/// Put the image behind `handle` on the clipboard.
+pub async fn copy_image(handle: Handle) -> Result<(), String> {
+    tokio::task::spawn_blocking(move || {
+        let rgba = decode(&handle)?;
+        let mut guard = CLIPBOARD.lock().map_err(|e| e.to_string())?;
+        if guard.is_none() {
+            *guard = Some(arboard::Clipboard::new().map_err(|e| e.to_string())?);
+        }
+        let clipboard = guard.as_mut().expect("clipboard initialized above");
+        clipboard
+            .set_image(arboard::ImageData {
+                width: rgba.width() as usize,
+                height: rgba.height() as usize,
+                bytes: Cow::Owned(rgba.into_raw()),
+            })
+            .map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
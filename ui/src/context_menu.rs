@@ -0,0 +1,345 @@
+//! Right-click menu for grid tiles.
+//!
+//! iced has no native context menu, so [`Anchor`] wraps the whole window and
+//! floats the menu as an overlay at the last right-click position.
+
+use iced::advanced::layout::{self, Layout};
+use iced::advanced::overlay;
+use iced::advanced::renderer;
+use iced::advanced::widget::{self, Operation, Tree, Widget};
+use iced::advanced::{Clipboard, Shell};
+use iced::event::{self, Event};
+use iced::mouse;
+use iced::widget::{button, column, container, horizontal_rule, row, scrollable, text};
+use iced::{Element, Length, Point, Rectangle, Renderer, Size, Theme, Vector};
+
+use crate::album_dialogs::AlbumOption;
+use crate::i18n::tr;
+use crate::style::{self, Palette};
+use crate::{Icon, MaterialSymbol, Message};
+
+/// Width of the menu and its album list.
+const MENU_WIDTH: f32 = 220.0;
+/// The album list scrolls beyond this height.
+const ALBUM_LIST_HEIGHT: f32 = 240.0;
+
+#[derive(Debug, Clone)]
+pub struct ContextMenu {
+    /// Media item the menu was opened on
+    pub media_id: String,
+    /// The album list replaces the actions
+    pub albums_open: bool,
+}
+
+fn entry<'a>(icon: MaterialSymbol, label: &str, message: Message) -> Element<'a, Message> {
+    button(
+        row![Icon::new(icon).size(16), text(label.to_string()).size(14)]
+            .spacing(Palette::SPACING / 2)
+            .align_items(iced::Alignment::Center),
+    )
+    .style(style::button_secondary())
+    .width(Length::Fill)
+    // Menu entries close the menu before running their action
+    .on_press(Message::ContextMenuPicked(Box::new(message)))
+    .into()
+}
+
+pub fn view<'a>(ui: &crate::GooglePiczUI) -> Option<Element<'a, Message>> {
+    let menu = ui.context_menu.as_ref()?;
+    let photo = ui.photos.iter().find(|p| p.id == menu.media_id)?;
+    let id = photo.id.clone();
+    let content: Element<'a, Message> = if menu.albums_open {
+        let mut albums = column![].spacing(2);
+        for album in &ui.albums {
+            let option = AlbumOption {
+                id: album.id.clone(),
+                title: album.title.clone().unwrap_or_else(|| tr("common.untitled").to_string()),
+            };
+            let title = option.title.clone();
+            albums = albums.push(entry(
+                MaterialSymbol::PhotoAlbum,
+                &title,
+                Message::AddToAlbum(option, vec![id.clone()]),
+            ));
+        }
+        column![
+            button(text(tr("menu.back")).size(14))
+                .style(style::button_secondary())
+                .width(Length::Fill)
+                .on_press(Message::ContextMenuAlbums(false)),
+            horizontal_rule(1),
+            scrollable(albums).height(Length::Shrink),
+        ]
+        .spacing(2)
+        .height(Length::Shrink)
+        .into()
+    } else {
+        let favorite = if photo.is_favorite { tr("menu.unfavorite") } else { tr("menu.favorite") };
+        column![
+            entry(MaterialSymbol::PlayCircle, tr("menu.open"), Message::SelectPhoto(photo.clone())),
+            entry(MaterialSymbol::Favorite, favorite, Message::ToggleFavorite(id.clone())),
+        ]
+        .push_maybe((!ui.albums.is_empty()).then(|| {
+            button(
+                row![
+                    Icon::new(MaterialSymbol::PhotoAlbum).size(16),
+                    text(tr("menu.add_to_album")).size(14).width(Length::Fill),
+                    text("▸").size(14),
+                ]
+                .spacing(Palette::SPACING / 2)
+                .align_items(iced::Alignment::Center),
+            )
+            .style(style::button_secondary())
+            .width(Length::Fill)
+            .on_press(Message::ContextMenuAlbums(true))
+        }))
+        .push_maybe(ui.selected_album.is_some().then(|| {
+            entry(MaterialSymbol::Remove, tr("menu.remove_from_album"), Message::ShowRemovePhotoDialog(id.clone()))
+        }))
+        .push_maybe((!photo.product_url.is_empty()).then(|| {
+            entry(MaterialSymbol::Link, tr("menu.copy_link"), Message::CopyPhotoLink(id.clone()))
+        }))
+        .push(entry(MaterialSymbol::Download, tr("menu.export"), Message::ExportPhotos(vec![id.clone()])))
+        .push(entry(MaterialSymbol::Delete, tr("menu.move_to_trash"), Message::MoveToTrash(vec![id.clone()])))
+        .spacing(2)
+        .into()
+    };
+    Some(
+        container(content)
+            .style(style::dialog())
+            .padding(4)
+            .width(Length::Fixed(MENU_WIDTH))
+            .max_height(ALBUM_LIST_HEIGHT + 40.0)
+            .into(),
+    )
+}
+
+/// Lays out `content` and floats `menu`, if any, at the last right-click.
+pub struct Anchor<'a> {
+    content: Element<'a, Message>,
+    menu: Option<Element<'a, Message>>,
+    on_dismiss: Message,
+}
+
+impl<'a> Anchor<'a> {
+    pub fn new(
+        content: impl Into<Element<'a, Message>>,
+        menu: Option<Element<'a, Message>>,
+        on_dismiss: Message,
+    ) -> Self {
+        Self { content: content.into(), menu, on_dismiss }
+    }
+}
+
+#[derive(Debug, Default)]
+struct State {
+    /// Cursor position of the last right-click, in window coordinates
+    position: Point,
+}
+
+impl<'a> Widget<Message, Theme, Renderer> for Anchor<'a> {
+    fn tag(&self) -> widget::tree::Tag {
+        widget::tree::Tag::of::<State>()
+    }
+
+    fn state(&self) -> widget::tree::State {
+        widget::tree::State::new(State::default())
+    }
+
+    fn children(&self) -> Vec<Tree> {
+        std::iter::once(&self.content).chain(&self.menu).map(Tree::new).collect()
+    }
+
+    fn diff(&self, tree: &mut Tree) {
+        let children: Vec<_> = std::iter::once(&self.content).chain(&self.menu).collect();
+        tree.diff_children(&children);
+    }
+
+    fn size(&self) -> Size<Length> {
+        self.content.as_widget().size()
+    }
+
+    fn layout(&self, tree: &mut Tree, renderer: &Renderer, limits: &layout::Limits) -> layout::Node {
+        self.content.as_widget().layout(&mut tree.children[0], renderer, limits)
+    }
+
+    fn operate(
+        &self,
+        tree: &mut Tree,
+        layout: Layout<'_>,
+        renderer: &Renderer,
+        operation: &mut dyn Operation<Message>,
+    ) {
+        self.content.as_widget().operate(&mut tree.children[0], layout, renderer, operation);
+    }
+
+    fn on_event(
+        &mut self,
+        tree: &mut Tree,
+        event: Event,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        renderer: &Renderer,
+        clipboard: &mut dyn Clipboard,
+        shell: &mut Shell<'_, Message>,
+        viewport: &Rectangle,
+    ) -> event::Status {
+        // Seen here before the scrollables below translate the cursor
+        if let Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Right)) = event {
+            if let Some(position) = cursor.position() {
+                tree.state.downcast_mut::<State>().position = position;
+            }
+        }
+        self.content.as_widget_mut().on_event(
+            &mut tree.children[0],
+            event,
+            layout,
+            cursor,
+            renderer,
+            clipboard,
+            shell,
+            viewport,
+        )
+    }
+
+    fn mouse_interaction(
+        &self,
+        tree: &Tree,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        viewport: &Rectangle,
+        renderer: &Renderer,
+    ) -> mouse::Interaction {
+        self.content
+            .as_widget()
+            .mouse_interaction(&tree.children[0], layout, cursor, viewport, renderer)
+    }
+
+    fn draw(
+        &self,
+        tree: &Tree,
+        renderer: &mut Renderer,
+        theme: &Theme,
+        style: &renderer::Style,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        viewport: &Rectangle,
+    ) {
+        self.content
+            .as_widget()
+            .draw(&tree.children[0], renderer, theme, style, layout, cursor, viewport);
+    }
+
+    fn overlay<'b>(
+        &'b mut self,
+        tree: &'b mut Tree,
+        layout: Layout<'_>,
+        renderer: &Renderer,
+        translation: Vector,
+    ) -> Option<overlay::Element<'b, Message, Theme, Renderer>> {
+        let position = tree.state.downcast_ref::<State>().position;
+        let mut children = tree.children.iter_mut();
+        let content = children.next()?;
+        match (self.menu.as_mut(), children.next()) {
+            (Some(menu), Some(state)) => Some(overlay::Element::new(Box::new(Menu {
+                menu,
+                state,
+                position,
+                on_dismiss: &self.on_dismiss,
+            }))),
+            _ => self.content.as_widget_mut().overlay(content, layout, renderer, translation),
+        }
+    }
+}
+
+impl<'a> From<Anchor<'a>> for Element<'a, Message> {
+    fn from(anchor: Anchor<'a>) -> Self {
+        Element::new(anchor)
+    }
+}
+
+struct Menu<'a, 'b> {
+    menu: &'b mut Element<'a, Message>,
+    state: &'b mut Tree,
+    position: Point,
+    on_dismiss: &'b Message,
+}
+
+impl<'a, 'b> overlay::Overlay<Message, Theme, Renderer> for Menu<'a, 'b> {
+    fn layout(&mut self, renderer: &Renderer, bounds: Size) -> layout::Node {
+        let node = self
+            .menu
+            .as_widget()
+            .layout(self.state, renderer, &layout::Limits::new(Size::ZERO, bounds));
+        let size = node.size();
+        // Open towards the inside of the window near its right and bottom edges
+        let x = if self.position.x + size.width > bounds.width {
+            (self.position.x - size.width).max(0.0)
+        } else {
+            self.position.x
+        };
+        let y = if self.position.y + size.height > bounds.height {
+            (self.position.y - size.height).max(0.0)
+        } else {
+            self.position.y
+        };
+        node.move_to(Point::new(x, y))
+    }
+
+    fn draw(
+        &self,
+        renderer: &mut Renderer,
+        theme: &Theme,
+        style: &renderer::Style,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+    ) {
+        self.menu
+            .as_widget()
+            .draw(self.state, renderer, theme, style, layout, cursor, &layout.bounds());
+    }
+
+    fn on_event(
+        &mut self,
+        event: Event,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        renderer: &Renderer,
+        clipboard: &mut dyn Clipboard,
+        shell: &mut Shell<'_, Message>,
+    ) -> event::Status {
+        if let Event::Mouse(mouse::Event::ButtonPressed(_)) = event {
+            if !cursor.is_over(layout.bounds()) {
+                shell.publish(self.on_dismiss.clone());
+                return event::Status::Captured;
+            }
+        }
+        self.menu.as_widget_mut().on_event(
+            self.state,
+            event,
+            layout,
+            cursor,
+            renderer,
+            clipboard,
+            shell,
+            &layout.bounds(),
+        )
+    }
+
+    fn mouse_interaction(
+        &self,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        viewport: &Rectangle,
+        renderer: &Renderer,
+    ) -> mouse::Interaction {
+        self.menu
+            .as_widget()
+            .mouse_interaction(self.state, layout, cursor, viewport, renderer)
+    }
+
+    /// The menu is modal: the window behind it ignores the cursor until dismissed.
+    fn is_over(&self, _layout: Layout<'_>, _renderer: &Renderer, _cursor_position: Point) -> bool {
+        true
+    }
+}
@@ -0,0 +1,333 @@
+//! Size cap for the images `ImageLoader` and the videos `VideoCache` keep on disk.
+//!
+//! Every file under the cached folders is tracked with its size and last
+//! access. Once a write pushes the total over the cap, the least recently
+//! used files are deleted. Access times are kept in an index file such as
+//! `image_cache.json`; sizes always come from the files themselves, so a lost
+//! or stale index only loses recency, never accounting.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+pub const INDEX_FILE: &str = "image_cache.json";
+/// Folders below the cache directory that hold loader-managed images.
+pub const FOLDERS: [&str; 3] = ["thumbnails", "full", "faces"];
+/// Access times are written back at most this often.
+const SAVE_INTERVAL: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy)]
+struct Entry {
+    size: u64,
+    /// Seconds since the Unix epoch
+    accessed: u64,
+}
+
+#[derive(Debug)]
+struct State {
+    entries: HashMap<PathBuf, Entry>,
+    total: u64,
+    /// Cap in bytes; `None` keeps everything
+    max_bytes: Option<u64>,
+    /// Open readers per file; these are never evicted
+    readers: HashMap<PathBuf, usize>,
+    dirty: bool,
+    saved_at: Instant,
+}
+
+#[derive(Debug)]
+pub struct DiskCache {
+    root: PathBuf,
+    /// Index file below `root`
+    index: &'static str,
+    state: Mutex<State>,
+}
+
+/// Keeps a file from being evicted while it is read.
+#[derive(Debug)]
+pub struct ReadGuard {
+    cache: Arc<DiskCache>,
+    path: PathBuf,
+}
+
+impl Drop for ReadGuard {
+    fn drop(&mut self) {
+        let mut state = self.cache.lock();
+        if let Some(count) = state.readers.get_mut(&self.path) {
+            *count -= 1;
+            if *count == 0 {
+                state.readers.remove(&self.path);
+            }
+        }
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn mtime(meta: &std::fs::Metadata) -> u64 {
+    meta.modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// All regular files below `dir`, recursively.
+fn walk(dir: &Path, out: &mut Vec<(PathBuf, std::fs::Metadata)>) {
+    let Ok(entries) = std::fs::read_dir(dir) else { return };
+    for entry in entries.flatten() {
+        let Ok(meta) = entry.metadata() else { continue };
+        if meta.is_dir() {
+            walk(&entry.path(), out);
+        } else if meta.is_file() {
+            out.push((entry.path(), meta));
+        }
+    }
+}
+
+impl DiskCache {
+    /// Scan the image folders below `root` and merge in the saved access times.
+    pub fn open(root: &Path, max_bytes: Option<u64>) -> Self {
+        Self::open_folders(root, &FOLDERS, INDEX_FILE, max_bytes)
+    }
+
+    /// Like `open`, for the files in `folders` with access times kept in `index`.
+    pub fn open_folders(root: &Path, folders: &[&str], index: &'static str, max_bytes: Option<u64>) -> Self {
+        let saved: HashMap<PathBuf, u64> = std::fs::read(root.join(index))
+            .ok()
+            .and_then(|data| serde_json::from_slice(&data).ok())
+            .unwrap_or_default();
+        let mut files = Vec::new();
+        for folder in folders {
+            walk(&root.join(folder), &mut files);
+        }
+        let mut entries = HashMap::new();
+        let mut total = 0;
+        for (path, meta) in files {
+            let Ok(relative) = path.strip_prefix(root) else { continue };
+            let accessed = saved.get(relative).copied().unwrap_or_else(|| mtime(&meta));
+            total += meta.len();
+            entries.insert(relative.to_path_buf(), Entry { size: meta.len(), accessed });
+        }
+        Self {
+            root: root.to_path_buf(),
+            index,
+            state: Mutex::new(State {
+                entries,
+                total,
+                max_bytes,
+                readers: HashMap::new(),
+                dirty: false,
+                saved_at: Instant::now(),
+            }),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn relative<'p>(&self, path: &'p Path) -> &'p Path {
+        path.strip_prefix(&self.root).unwrap_or(path)
+    }
+
+    /// Change the cap and evict right away if the cache is already over it.
+    pub fn set_max_bytes(&self, max_bytes: Option<u64>) {
+        self.lock().max_bytes = max_bytes;
+        self.evict(None);
+    }
+
+    /// Bytes and number of files currently cached.
+    pub fn usage(&self) -> (u64, usize) {
+        let state = self.lock();
+        (state.total, state.entries.len())
+    }
+
+    /// Protect `path` from eviction until the guard is dropped and mark it as used.
+    pub fn read(self: &Arc<Self>, path: &Path) -> ReadGuard {
+        let relative = self.relative(path).to_path_buf();
+        let mut state = self.lock();
+        *state.readers.entry(relative.clone()).or_default() += 1;
+        if let Some(entry) = state.entries.get_mut(&relative) {
+            entry.accessed = now();
+            state.dirty = true;
+        }
+        drop(state);
+        ReadGuard { cache: self.clone(), path: relative }
+    }
+
+    /// Account for a file just written, then evict older files if over the cap.
+    pub fn insert(&self, path: &Path, size: u64) {
+        let relative = self.relative(path).to_path_buf();
+        {
+            let mut state = self.lock();
+            if let Some(old) = state.entries.insert(relative.clone(), Entry { size, accessed: now() }) {
+                state.total -= old.size;
+            }
+            state.total += size;
+            state.dirty = true;
+        }
+        self.evict(Some(&relative));
+    }
+
+    /// Delete least recently used files until the total fits the cap. `keep`
+    /// and files with open readers are skipped.
+    fn evict(&self, keep: Option<&Path>) {
+        let mut state = self.lock();
+        let Some(max) = state.max_bytes else {
+            drop(state);
+            self.save(false);
+            return;
+        };
+        if state.total > max {
+            let mut candidates: Vec<(PathBuf, Entry)> = state
+                .entries
+                .iter()
+                .filter(|(path, _)| Some(path.as_path()) != keep && !state.readers.contains_key(*path))
+                .map(|(path, entry)| (path.clone(), *entry))
+                .collect();
+            candidates.sort_by_key(|(_, entry)| entry.accessed);
+            let mut evicted = 0;
+            for (path, entry) in candidates {
+                if state.total <= max {
+                    break;
+                }
+                match std::fs::remove_file(self.root.join(&path)) {
+                    Ok(()) => {}
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                    Err(e) => {
+                        tracing::warn!(path = %path.display(), error = %e, "Failed to evict cached file");
+                        continue;
+                    }
+                }
+                state.entries.remove(&path);
+                state.total -= entry.size;
+                evicted += 1;
+            }
+            tracing::info!(evicted, total = state.total, max, "Evicted cached files");
+            state.dirty = true;
+            drop(state);
+            self.save(true);
+            return;
+        }
+        drop(state);
+        self.save(false);
+    }
+
+    /// Delete `path` if it is cached. A missing file is not an error.
+    pub fn remove(&self, path: &Path) -> std::io::Result<()> {
+        match std::fs::remove_file(path) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+        let relative = self.relative(path).to_path_buf();
+        let mut state = self.lock();
+        if let Some(entry) = state.entries.remove(&relative) {
+            state.total -= entry.size;
+            state.dirty = true;
+        }
+        Ok(())
+    }
+
+    /// Delete every cached file not currently being read.
+    pub fn clear(&self) -> std::io::Result<()> {
+        let mut state = self.lock();
+        let paths: Vec<PathBuf> = state
+            .entries
+            .keys()
+            .filter(|path| !state.readers.contains_key(*path))
+            .cloned()
+            .collect();
+        for path in paths {
+            match std::fs::remove_file(self.root.join(&path)) {
+                Ok(()) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(e),
+            }
+            if let Some(entry) = state.entries.remove(&path) {
+                state.total -= entry.size;
+            }
+        }
+        state.dirty = true;
+        drop(state);
+        self.save(true);
+        Ok(())
+    }
+
+    /// Write the access times back, unless saved within `SAVE_INTERVAL` and not `forced`.
+    fn save(&self, forced: bool) {
+        let mut state = self.lock();
+        if !state.dirty || (!forced && state.saved_at.elapsed() < SAVE_INTERVAL) {
+            return;
+        }
+        let times: HashMap<&PathBuf, u64> = state.entries.iter().map(|(p, e)| (p, e.accessed)).collect();
+        let result = serde_json::to_vec(&times)
+            .map_err(std::io::Error::other)
+            .and_then(|data| std::fs::write(self.root.join(self.index), data));
+        if let Err(e) = result {
+            tracing::warn!(index = self.index, error = %e, "Failed to save cache index");
+        }
+        state.dirty = false;
+        state.saved_at = Instant::now();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn write(root: &Path, name: &str, size: usize) -> PathBuf {
+        let path = root.join("thumbnails").join(name);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, vec![0u8; size]).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_evicts_least_recently_used_but_not_open_files() {
+        let dir = tempdir().unwrap();
+        let cache = Arc::new(DiskCache::open(dir.path(), Some(250)));
+        let a = write(dir.path(), "a.jpg", 100);
+        cache.insert(&a, 100);
+        let b = write(dir.path(), "b.jpg", 100);
+        cache.insert(&b, 100);
+        {
+            let mut state = cache.lock();
+            state.entries.get_mut(Path::new("thumbnails/a.jpg")).unwrap().accessed = 1;
+            state.entries.get_mut(Path::new("thumbnails/b.jpg")).unwrap().accessed = 2;
+        }
+
+        // `a` is the oldest, but open, so `b` goes instead
+        let guard = cache.read(&a);
+        let c = write(dir.path(), "c.jpg", 100);
+        cache.insert(&c, 100);
+        assert!(a.exists());
+        assert!(!b.exists());
+        assert_eq!(cache.usage(), (200, 2));
+        drop(guard);
+
+        cache.clear().unwrap();
+        assert_eq!(cache.usage(), (0, 0));
+        assert!(!c.exists());
+        assert!(dir.path().join(INDEX_FILE).exists());
+    }
+
+    #[test]
+    fn test_open_accounts_for_existing_files() {
+        let dir = tempdir().unwrap();
+        write(dir.path(), "a.jpg", 10);
+        write(dir.path(), "240/a.jpg", 20);
+        let cache = DiskCache::open(dir.path(), None);
+        assert_eq!(cache.usage(), (30, 2));
+        cache.set_max_bytes(Some(25));
+        assert_eq!(cache.usage().1, 1);
+    }
+}
@@ -0,0 +1,47 @@
+//! Dragging grid tiles onto album entries.
+//!
+//! Widgets only report hover; the press, move and release come from a
+//! window event subscription so a tile button can still be clicked normally.
+
+use iced::widget::{container, text};
+use iced::Element;
+
+use crate::i18n::trf;
+use crate::style::{self, Palette};
+use crate::Message;
+
+#[derive(Debug, Default)]
+pub struct Drag {
+    /// Tile currently under the cursor
+    pub hovered_tile: Option<String>,
+    /// Album entry currently under the cursor
+    pub hovered_album: Option<String>,
+    /// Photos picked up by the last press on a tile
+    pub items: Vec<String>,
+    /// Set once the cursor moves while `items` are held
+    pub moving: bool,
+}
+
+impl Drag {
+    pub fn active(&self) -> bool {
+        self.moving && !self.items.is_empty()
+    }
+
+    /// Whether `album_id` would receive the photos if released now.
+    pub fn targets(&self, album_id: &str) -> bool {
+        self.active() && self.hovered_album.as_deref() == Some(album_id)
+    }
+}
+
+/// Hint shown above the grid while photos are being dragged.
+pub fn hint<'a>(ui: &crate::GooglePiczUI) -> Option<Element<'a, Message>> {
+    if !ui.drag.active() {
+        return None;
+    }
+    Some(
+        container(text(trf("drag.hint", &[&ui.drag.items.len()])).size(14))
+            .style(style::card())
+            .padding(Palette::SPACING / 2)
+            .into(),
+    )
+}
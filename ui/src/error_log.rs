@@ -0,0 +1,80 @@
+//! Reading and clearing `ui_errors.log` for the viewer in the settings dialog.
+
+use std::io::SeekFrom;
+use std::path::PathBuf;
+use tokio::fs::{File, OpenOptions};
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+/// Lines shown in the viewer.
+pub const TAIL_LINES: usize = 200;
+/// Only the end of the file is read; 200 lines of errors fit easily.
+const MAX_TAIL_BYTES: u64 = 256 * 1024;
+
+/// The last `count` lines of the log; a missing log is an empty one.
+pub async fn read_tail(path: PathBuf, count: usize) -> Result<Vec<String>, String> {
+    let mut file = match File::open(&path).await {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.to_string()),
+    };
+    let len = file.metadata().await.map_err(|e| e.to_string())?.len();
+    let start = len.saturating_sub(MAX_TAIL_BYTES);
+    file.seek(SeekFrom::Start(start)).await.map_err(|e| e.to_string())?;
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes).await.map_err(|e| e.to_string())?;
+    let content = String::from_utf8_lossy(&bytes);
+    let mut lines: Vec<&str> = content.lines().collect();
+    if start > 0 && !lines.is_empty() {
+        // The seek most likely landed inside a line
+        lines.remove(0);
+    }
+    let skip = lines.len().saturating_sub(count);
+    Ok(lines[skip..].iter().map(|l| l.to_string()).collect())
+}
+
+/// Empty the log in place. Writers open it in append mode per message, so
+/// truncating rather than replacing the file never loses a later write.
+pub async fn clear(path: PathBuf) -> Result<(), String> {
+    match OpenOptions::new().write(true).open(&path).await {
+        Ok(file) => file.set_len(0).await.map_err(|e| e.to_string()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_tail_and_clear() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("ui_errors.log");
+        assert!(read_tail(path.clone(), 2).await.unwrap().is_empty());
+
+        std::fs::write(&path, "one\ntwo\nthree\n").unwrap();
+        assert_eq!(read_tail(path.clone(), 2).await.unwrap(), vec!["two", "three"]);
+
+        // A writer that opened the log before clearing keeps appending at the new end
+        let mut writer = std::fs::OpenOptions::new().append(true).open(&path).unwrap();
+        clear(path.clone()).await.unwrap();
+        writeln!(writer, "four").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "four\n");
+    }
+
+    #[tokio::test]
+    async fn test_tail_of_large_log_skips_partial_line() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("ui_errors.log");
+        let line = "x".repeat(1000);
+        let content: String = (0..400).map(|i| format!("{} {}\n", i, line)).collect();
+        std::fs::write(&path, content).unwrap();
+
+        let tail = read_tail(path, TAIL_LINES).await.unwrap();
+        assert_eq!(tail.len(), TAIL_LINES);
+        assert!(tail[0].starts_with("200 "));
+        assert!(tail[TAIL_LINES - 1].starts_with("399 "));
+    }
+}
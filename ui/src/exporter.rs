@@ -0,0 +1,194 @@
+//! Download original photo and video files into a local folder.
+
+use api_client::MediaItem;
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+use tokio::fs::{self, File, OpenOptions};
+use tokio::io::AsyncWriteExt;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum ExportError {
+    #[error("network error: {0}")]
+    Network(String),
+    #[error("io error: {0}")]
+    Io(String),
+}
+
+/// Export in progress; files are downloaded one after another.
+#[derive(Debug)]
+pub struct ExportJob {
+    pub dir: PathBuf,
+    pub queue: VecDeque<MediaItem>,
+    pub total: usize,
+    pub done: usize,
+    pub failed: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct Exporter {
+    client: reqwest::Client,
+    access_token: String,
+}
+
+/// `name` with ` (n)` inserted before the extension; `n == 0` keeps it unchanged.
+fn numbered(name: &str, n: usize) -> String {
+    if n == 0 {
+        return name.to_string();
+    }
+    match name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => format!("{} ({}).{}", stem, n, ext),
+        _ => format!("{} ({})", name, n),
+    }
+}
+
+/// Create a new file for `name` in `dir`, appending ` (1)`, ` (2)`, … instead of overwriting.
+async fn create_unique(dir: &Path, name: &str) -> Result<(File, PathBuf), ExportError> {
+    for n in 0.. {
+        let path = dir.join(numbered(name, n));
+        match OpenOptions::new().write(true).create_new(true).open(&path).await {
+            Ok(file) => return Ok((file, path)),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(ExportError::Io(e.to_string())),
+        }
+    }
+    unreachable!("unbounded range")
+}
+
+impl Exporter {
+    pub fn new(access_token: String) -> Self {
+        Self { client: reqwest::Client::new(), access_token }
+    }
+
+    /// URL serving the original bytes; videos need `=dv` instead of `=d`.
+    pub fn download_url(item: &MediaItem) -> String {
+        if item.mime_type.starts_with("video/") {
+            format!("{}=dv", item.base_url)
+        } else {
+            format!("{}=d", item.base_url)
+        }
+    }
+
+    /// Save `item` under its original filename in `dir` and return the written path.
+    pub async fn export_item(&self, item: &MediaItem, dir: &Path) -> Result<PathBuf, ExportError> {
+        // Never let a filename from the API escape the chosen folder
+        let name = Path::new(&item.filename)
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or(&item.id)
+            .to_string();
+        let mut response = self
+            .client
+            .get(Self::download_url(item))
+            .bearer_auth(&self.access_token)
+            .send()
+            .await
+            .map_err(|e| ExportError::Network(e.to_string()))?;
+        if !response.status().is_success() {
+            return Err(ExportError::Network(format!("HTTP {}", response.status())));
+        }
+        let (mut file, path) = create_unique(dir, &name).await?;
+        let written = async {
+            while let Some(chunk) = response
+                .chunk()
+                .await
+                .map_err(|e| ExportError::Network(e.to_string()))?
+            {
+                file.write_all(&chunk)
+                    .await
+                    .map_err(|e| ExportError::Io(e.to_string()))?;
+            }
+            file.flush().await.map_err(|e| ExportError::Io(e.to_string()))
+        }
+        .await;
+        if let Err(e) = written {
+            // Don't leave a truncated file behind
+            let _ = fs::remove_file(&path).await;
+            return Err(e);
+        }
+        Ok(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use api_client::MediaMetadata;
+    use httpmock::prelude::*;
+    use tempfile::tempdir;
+
+    fn item(base_url: String, filename: &str, mime_type: &str) -> MediaItem {
+        MediaItem {
+            id: "1".into(),
+            description: None,
+            product_url: String::new(),
+            base_url,
+            mime_type: mime_type.into(),
+            media_metadata: MediaMetadata {
+                creation_time: "2023-01-01T00:00:00Z".into(),
+                width: "1".into(),
+                height: "1".into(),
+                video: None,
+            },
+            filename: filename.into(),
+            is_favorite: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_export_renames_on_collision() {
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(GET).path("/photo=d").header("authorization", "Bearer token");
+            then.status(200).body("original");
+        });
+        let dir = tempdir().unwrap();
+        std::fs::write(dir.path().join("IMG_1.jpg"), "existing").unwrap();
+        let exporter = Exporter::new("token".into());
+        let photo = item(server.url("/photo"), "IMG_1.jpg", "image/jpeg");
+
+        let first = exporter.export_item(&photo, dir.path()).await.unwrap();
+        let second = exporter.export_item(&photo, dir.path()).await.unwrap();
+        assert_eq!(first, dir.path().join("IMG_1 (1).jpg"));
+        assert_eq!(second, dir.path().join("IMG_1 (2).jpg"));
+        assert_eq!(std::fs::read(&first).unwrap(), b"original");
+        assert_eq!(std::fs::read(dir.path().join("IMG_1.jpg")).unwrap(), b"existing");
+        mock.assert_hits(2);
+    }
+
+    #[tokio::test]
+    async fn test_export_video_uses_dv() {
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(GET).path("/clip=dv");
+            then.status(200).body("video");
+        });
+        let dir = tempdir().unwrap();
+        let clip = item(server.url("/clip"), "clip.mp4", "video/mp4");
+        let path = Exporter::new("token".into()).export_item(&clip, dir.path()).await.unwrap();
+        assert_eq!(std::fs::read(path).unwrap(), b"video");
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_export_failure_leaves_no_file() {
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(GET).path("/gone=d");
+            then.status(404);
+        });
+        let dir = tempdir().unwrap();
+        let photo = item(server.url("/gone"), "gone.jpg", "image/jpeg");
+        let err = Exporter::new("token".into()).export_item(&photo, dir.path()).await.unwrap_err();
+        assert!(matches!(err, ExportError::Network(_)));
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_numbered_names() {
+        assert_eq!(numbered("a.jpg", 0), "a.jpg");
+        assert_eq!(numbered("a.b.jpg", 1), "a.b (1).jpg");
+        assert_eq!(numbered("README", 2), "README (2)");
+        assert_eq!(numbered(".hidden", 1), ".hidden (1)");
+    }
+}
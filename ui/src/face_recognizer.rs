@@ -18,24 +18,33 @@ impl FaceRecognizer {
     }
 }
 
+/// Where a face box lands when a photo of `source` pixels is drawn into `image`.
+pub fn face_bounds(rect: (u32, u32, u32, u32), source: Size, image: Rectangle) -> Rectangle {
+    let sx = image.width / source.width.max(1.0);
+    let sy = image.height / source.height.max(1.0);
+    let (x, y, w, h) = rect;
+    Rectangle {
+        x: image.x + x as f32 * sx,
+        y: image.y + y as f32 * sy,
+        width: w as f32 * sx,
+        height: h as f32 * sy,
+    }
+}
+
 impl<Message> Program<Message> for FaceRecognizer {
     type State = ();
 
     fn draw(&self, _state: &Self::State, bounds: Rectangle, _cursor: iced::mouse::Cursor) -> Vec<Geometry> {
         let mut frame = Frame::new(bounds.size());
-        let sx = bounds.width / self.width.max(1) as f32;
-        let sy = bounds.height / self.height.max(1) as f32;
+        let source = Size::new(self.width as f32, self.height as f32);
         for face in &self.faces {
-            let (x, y, w, h) = face.rect;
-            let path = Path::rectangle(
-                Point::new(x as f32 * sx, y as f32 * sy),
-                Size::new(w as f32 * sx, h as f32 * sy),
-            );
+            let r = face_bounds(face.rect, source, Rectangle::with_size(bounds.size()));
+            let path = Path::rectangle(r.position(), r.size());
             frame.stroke(&path, Stroke { color: Color::from_rgb(1.0, 0.0, 0.0), width: 2.0, ..Stroke::default() });
             if let Some(name) = &face.name {
                 frame.fill_text(Text {
                     content: name.clone(),
-                    position: Point::new(x as f32 * sx, (y as f32 * sy - 14.0).max(0.0)),
+                    position: Point::new(r.x, (r.y - 14.0).max(0.0)),
                     color: Color::from_rgb(1.0, 0.0, 0.0),
                     size: 16.0,
                     ..Default::default()
@@ -0,0 +1,278 @@
+//! Fullscreen presentation of the photo viewer and the slideshow.
+//!
+//! The window switches to fullscreen mode and the view drops the header and
+//! info panel. [`Floating`] lays the controls over the photo, so hiding them
+//! after a few idle seconds does not resize it.
+
+use std::time::Duration;
+
+use iced::advanced::layout::{self, Layout};
+use iced::advanced::overlay;
+use iced::advanced::renderer;
+use iced::advanced::widget::{Operation, Tree, Widget};
+use iced::advanced::{Clipboard, Shell};
+use iced::event::{self, Event};
+use iced::mouse;
+use iced::widget::{button, container, row, text, Row};
+use iced::{Element, Length, Point, Rectangle, Renderer, Size, Theme, Vector};
+
+use crate::style::{self, Palette};
+use crate::{Icon, MaterialSymbol, Message, ViewState};
+
+/// Idle time after which the overlay controls disappear.
+pub const CONTROLS_TIMEOUT: Duration = Duration::from_secs(3);
+/// Gap between the top of the photo and the controls.
+const CONTROLS_MARGIN: f32 = 16.0;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fullscreen {
+    /// Window size and position before entering, persisted and restored instead
+    /// of the fullscreen geometry
+    pub windowed: (f32, f32, Option<(i32, i32)>),
+    pub controls_visible: bool,
+    /// Bumped on every activity; only the hide timer of the latest one fires
+    pub activity: u64,
+}
+
+impl Fullscreen {
+    pub fn new(windowed: (f32, f32, Option<(i32, i32)>)) -> Self {
+        Self { windowed, controls_visible: true, activity: 0 }
+    }
+}
+
+fn control<'a>(icon: MaterialSymbol, color: iced::Color, message: Message) -> Element<'a, Message> {
+    button(Icon::new(icon).color(color))
+        .style(style::button_primary())
+        .on_press(message)
+        .into()
+}
+
+/// The photo or slide filling the window, with the controls floating above it.
+pub fn view<'a>(ui: &'a crate::GooglePiczUI) -> Option<Element<'a, Message>> {
+    let fullscreen = ui.fullscreen.as_ref()?;
+    let (content, controls): (Element<'a, Message>, Row<'a, Message>) = match &ui.state {
+        ViewState::SelectedPhoto { photo, faces } => {
+            let heart_color = if photo.is_favorite { Palette::SECONDARY } else { Palette::ON_PRIMARY };
+            (
+                ui.photo_image(photo, faces),
+                row![control(MaterialSymbol::Favorite, heart_color, Message::ToggleFavorite(photo.id.clone()))],
+            )
+        }
+        ViewState::Slideshow { index, playing } => {
+            let play_icon = if *playing { MaterialSymbol::Pause } else { MaterialSymbol::PlayArrow };
+            (
+                ui.slide_image(*index),
+                row![
+                    control(play_icon, Palette::ON_PRIMARY, Message::ToggleSlideshow),
+                    text(format!("{} / {}", index + 1, ui.photos.len())).size(16),
+                ],
+            )
+        }
+        #[cfg(feature = "gstreamer")]
+        ViewState::PlayingVideo { .. } => return None,
+        ViewState::Grid => return None,
+    };
+    let controls = fullscreen.controls_visible.then(|| {
+        controls
+            .push(control(MaterialSymbol::FullscreenExit, Palette::ON_PRIMARY, Message::ToggleFullscreen))
+            .push(control(MaterialSymbol::Close, Palette::ON_PRIMARY, Message::ClosePhoto))
+            .spacing(Palette::SPACING)
+            .align_items(iced::Alignment::Center)
+            .into()
+    });
+    Some(Floating::new(content, controls).into())
+}
+
+/// Shows `content` with `controls`, if any, floating centred along its top edge.
+pub struct Floating<'a> {
+    content: Element<'a, Message>,
+    controls: Option<Element<'a, Message>>,
+}
+
+impl<'a> Floating<'a> {
+    pub fn new(content: impl Into<Element<'a, Message>>, controls: Option<Element<'a, Message>>) -> Self {
+        let controls = controls.map(|c| {
+            container(c)
+                .style(style::dialog())
+                .padding(Palette::SPACING / 2)
+                .into()
+        });
+        Self { content: content.into(), controls }
+    }
+}
+
+impl<'a> Widget<Message, Theme, Renderer> for Floating<'a> {
+    fn children(&self) -> Vec<Tree> {
+        std::iter::once(&self.content).chain(&self.controls).map(Tree::new).collect()
+    }
+
+    fn diff(&self, tree: &mut Tree) {
+        let children: Vec<_> = std::iter::once(&self.content).chain(&self.controls).collect();
+        tree.diff_children(&children);
+    }
+
+    fn size(&self) -> Size<Length> {
+        self.content.as_widget().size()
+    }
+
+    fn layout(&self, tree: &mut Tree, renderer: &Renderer, limits: &layout::Limits) -> layout::Node {
+        self.content.as_widget().layout(&mut tree.children[0], renderer, limits)
+    }
+
+    fn operate(
+        &self,
+        tree: &mut Tree,
+        layout: Layout<'_>,
+        renderer: &Renderer,
+        operation: &mut dyn Operation<Message>,
+    ) {
+        self.content.as_widget().operate(&mut tree.children[0], layout, renderer, operation);
+    }
+
+    fn on_event(
+        &mut self,
+        tree: &mut Tree,
+        event: Event,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        renderer: &Renderer,
+        clipboard: &mut dyn Clipboard,
+        shell: &mut Shell<'_, Message>,
+        viewport: &Rectangle,
+    ) -> event::Status {
+        self.content.as_widget_mut().on_event(
+            &mut tree.children[0],
+            event,
+            layout,
+            cursor,
+            renderer,
+            clipboard,
+            shell,
+            viewport,
+        )
+    }
+
+    fn mouse_interaction(
+        &self,
+        tree: &Tree,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        viewport: &Rectangle,
+        renderer: &Renderer,
+    ) -> mouse::Interaction {
+        self.content
+            .as_widget()
+            .mouse_interaction(&tree.children[0], layout, cursor, viewport, renderer)
+    }
+
+    fn draw(
+        &self,
+        tree: &Tree,
+        renderer: &mut Renderer,
+        theme: &Theme,
+        style: &renderer::Style,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        viewport: &Rectangle,
+    ) {
+        self.content
+            .as_widget()
+            .draw(&tree.children[0], renderer, theme, style, layout, cursor, viewport);
+    }
+
+    fn overlay<'b>(
+        &'b mut self,
+        tree: &'b mut Tree,
+        layout: Layout<'_>,
+        renderer: &Renderer,
+        translation: Vector,
+    ) -> Option<overlay::Element<'b, Message, Theme, Renderer>> {
+        let bounds = layout.bounds() + translation;
+        let mut children = tree.children.iter_mut();
+        let content = children.next()?;
+        let mut overlays: Vec<_> = self
+            .content
+            .as_widget_mut()
+            .overlay(content, layout, renderer, translation)
+            .into_iter()
+            .collect();
+        if let (Some(controls), Some(state)) = (self.controls.as_mut(), children.next()) {
+            overlays.push(overlay::Element::new(Box::new(Bar { controls, state, bounds })));
+        }
+        (!overlays.is_empty()).then(|| overlay::Group::with_children(overlays).overlay())
+    }
+}
+
+impl<'a> From<Floating<'a>> for Element<'a, Message> {
+    fn from(floating: Floating<'a>) -> Self {
+        Element::new(floating)
+    }
+}
+
+struct Bar<'a, 'b> {
+    controls: &'b mut Element<'a, Message>,
+    state: &'b mut Tree,
+    /// Bounds of the content in window coordinates
+    bounds: Rectangle,
+}
+
+impl<'a, 'b> overlay::Overlay<Message, Theme, Renderer> for Bar<'a, 'b> {
+    fn layout(&mut self, renderer: &Renderer, _bounds: Size) -> layout::Node {
+        let node = self.controls.as_widget().layout(
+            self.state,
+            renderer,
+            &layout::Limits::new(Size::ZERO, self.bounds.size()),
+        );
+        let size = node.size();
+        node.move_to(Point::new(
+            self.bounds.x + (self.bounds.width - size.width).max(0.0) / 2.0,
+            self.bounds.y + CONTROLS_MARGIN,
+        ))
+    }
+
+    fn draw(
+        &self,
+        renderer: &mut Renderer,
+        theme: &Theme,
+        style: &renderer::Style,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+    ) {
+        self.controls
+            .as_widget()
+            .draw(self.state, renderer, theme, style, layout, cursor, &layout.bounds());
+    }
+
+    fn on_event(
+        &mut self,
+        event: Event,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        renderer: &Renderer,
+        clipboard: &mut dyn Clipboard,
+        shell: &mut Shell<'_, Message>,
+    ) -> event::Status {
+        self.controls.as_widget_mut().on_event(
+            self.state,
+            event,
+            layout,
+            cursor,
+            renderer,
+            clipboard,
+            shell,
+            &layout.bounds(),
+        )
+    }
+
+    fn mouse_interaction(
+        &self,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        viewport: &Rectangle,
+        renderer: &Renderer,
+    ) -> mouse::Interaction {
+        self.controls
+            .as_widget()
+            .mouse_interaction(self.state, layout, cursor, viewport, renderer)
+    }
+}
@@ -0,0 +1,223 @@
+//! Decoded image handles kept in memory, shared by `ImageLoader` and the UI.
+//!
+//! The loader fills the cache and the UI reads from it while drawing, so the
+//! grid and viewer hold no handles of their own. Thumbnails and full images
+//! are bounded separately by entry count, since one full image weighs as much
+//! as hundreds of thumbnails. The least recently used entry of a kind goes
+//! first.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use iced::widget::image::Handle;
+
+/// Thumbnails kept by default.
+pub const DEFAULT_THUMBNAILS: usize = 400;
+/// Full-size images kept by default.
+pub const DEFAULT_FULL_IMAGES: usize = 12;
+
+/// Which rendition of a media item a handle shows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ImageKind {
+    /// Square thumbnail of this many pixels
+    Thumbnail(u32),
+    Full,
+    /// Face crop for this bounding box
+    Face([i32; 4]),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ImageKey {
+    pub media_id: String,
+    pub kind: ImageKind,
+}
+
+impl ImageKey {
+    pub fn thumbnail(media_id: &str, size: u32) -> Self {
+        Self { media_id: media_id.to_string(), kind: ImageKind::Thumbnail(size) }
+    }
+
+    pub fn full(media_id: &str) -> Self {
+        Self { media_id: media_id.to_string(), kind: ImageKind::Full }
+    }
+
+    /// Full images have their own limit; face crops count as thumbnails.
+    fn is_full(&self) -> bool {
+        self.kind == ImageKind::Full
+    }
+}
+
+/// Counters for the status bar.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HandleCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+    pub entries: usize,
+}
+
+impl HandleCacheStats {
+    /// Share of lookups answered from memory, in percent.
+    pub fn hit_rate(&self) -> Option<u64> {
+        let lookups = self.hits + self.misses;
+        (lookups > 0).then(|| self.hits * 100 / lookups)
+    }
+}
+
+#[derive(Debug)]
+struct State {
+    /// Handle and the tick it was last used at
+    entries: HashMap<ImageKey, (Handle, u64)>,
+    max_thumbnails: usize,
+    max_full_images: usize,
+    tick: u64,
+    stats: HandleCacheStats,
+}
+
+#[derive(Debug, Clone)]
+pub struct HandleCache(Arc<Mutex<State>>);
+
+impl Default for HandleCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_THUMBNAILS, DEFAULT_FULL_IMAGES)
+    }
+}
+
+impl HandleCache {
+    pub fn new(max_thumbnails: usize, max_full_images: usize) -> Self {
+        Self(Arc::new(Mutex::new(State {
+            entries: HashMap::new(),
+            max_thumbnails,
+            max_full_images,
+            tick: 0,
+            stats: HandleCacheStats::default(),
+        })))
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Change the limits, evicting right away if there are too many entries.
+    pub fn set_limits(&self, max_thumbnails: usize, max_full_images: usize) {
+        let mut state = self.lock();
+        state.max_thumbnails = max_thumbnails;
+        state.max_full_images = max_full_images;
+        state.evict(true);
+        state.evict(false);
+    }
+
+    /// Look up `key` on behalf of a load; counted as a hit or miss.
+    pub fn get(&self, key: &ImageKey) -> Option<Handle> {
+        let mut state = self.lock();
+        let handle = state.touch(key);
+        if handle.is_some() {
+            state.stats.hits += 1;
+        } else {
+            state.stats.misses += 1;
+        }
+        handle
+    }
+
+    /// Look up `key` for drawing. Marks it as used without counting a lookup,
+    /// since views are redrawn far more often than images are requested.
+    pub fn peek(&self, key: &ImageKey) -> Option<Handle> {
+        self.lock().touch(key)
+    }
+
+    pub fn contains(&self, key: &ImageKey) -> bool {
+        self.lock().entries.contains_key(key)
+    }
+
+    pub fn insert(&self, key: ImageKey, handle: Handle) {
+        let mut state = self.lock();
+        let full = key.is_full();
+        state.tick += 1;
+        let tick = state.tick;
+        state.entries.insert(key, (handle, tick));
+        state.evict(full);
+    }
+
+    /// Forget every rendition of `media_id`, e.g. after its image changed.
+    pub fn invalidate(&self, media_id: &str) {
+        self.lock().entries.retain(|key, _| key.media_id != media_id);
+    }
+
+    pub fn clear(&self) {
+        self.lock().entries.clear();
+    }
+
+    /// Number of entries of each kind, thumbnails first.
+    pub fn counts(&self) -> (usize, usize) {
+        let state = self.lock();
+        let full = state.entries.keys().filter(|key| key.is_full()).count();
+        (state.entries.len() - full, full)
+    }
+
+    pub fn stats(&self) -> HandleCacheStats {
+        let state = self.lock();
+        HandleCacheStats { entries: state.entries.len(), ..state.stats }
+    }
+}
+
+impl State {
+    fn touch(&mut self, key: &ImageKey) -> Option<Handle> {
+        self.tick += 1;
+        let tick = self.tick;
+        let (handle, used) = self.entries.get_mut(key)?;
+        *used = tick;
+        Some(handle.clone())
+    }
+
+    /// Drop the least recently used entries of one kind until it fits its limit.
+    fn evict(&mut self, full: bool) {
+        let max = if full { self.max_full_images } else { self.max_thumbnails };
+        let mut kind: Vec<(u64, ImageKey)> = self
+            .entries
+            .iter()
+            .filter(|(key, _)| key.is_full() == full)
+            .map(|(key, (_, used))| (*used, key.clone()))
+            .collect();
+        if kind.len() <= max {
+            return;
+        }
+        kind.sort_unstable_by_key(|(used, _)| *used);
+        let excess = kind.len() - max;
+        for (_, key) in kind.into_iter().take(excess) {
+            self.entries.remove(&key);
+            self.stats.evictions += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn handle() -> Handle {
+        Handle::from_memory(Vec::new())
+    }
+
+    #[test]
+    fn test_least_recently_used_of_a_kind_is_evicted() {
+        let cache = HandleCache::new(2, 1);
+        cache.insert(ImageKey::thumbnail("a", 150), handle());
+        cache.insert(ImageKey::thumbnail("b", 150), handle());
+        cache.insert(ImageKey::full("a"), handle());
+        // `a` was used more recently than `b`
+        assert!(cache.peek(&ImageKey::thumbnail("a", 150)).is_some());
+        cache.insert(ImageKey::thumbnail("c", 150), handle());
+        assert!(!cache.contains(&ImageKey::thumbnail("b", 150)));
+        // Thumbnails never push out full images
+        assert_eq!(cache.counts(), (2, 1));
+
+        assert!(cache.get(&ImageKey::thumbnail("a", 150)).is_some());
+        assert!(cache.get(&ImageKey::thumbnail("b", 150)).is_none());
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses, stats.evictions), (1, 1, 1));
+        assert_eq!(stats.hit_rate(), Some(50));
+
+        cache.invalidate("a");
+        assert_eq!(cache.counts(), (1, 0));
+    }
+}
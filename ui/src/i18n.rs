@@ -0,0 +1,746 @@
+//! Compiled-in translations of user-facing strings.
+//!
+//! Views look strings up by key with [`tr`]; templates use `{}` placeholders
+//! that [`trf`] fills in order. Keys missing from the active bundle fall back
+//! to English.
+
+use std::sync::atomic::{AtomicU8, Ordering};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Language {
+    #[default]
+    En,
+    De,
+}
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::En, Language::De];
+
+    /// Value stored in `AppConfig::language`.
+    pub fn code(self) -> &'static str {
+        match self {
+            Language::En => "en",
+            Language::De => "de",
+        }
+    }
+
+    /// Unknown codes select English.
+    pub fn from_code(code: &str) -> Self {
+        Language::ALL
+            .into_iter()
+            .find(|l| l.code().eq_ignore_ascii_case(code.trim()))
+            .unwrap_or_default()
+    }
+
+    fn bundle(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Language::En => EN,
+            Language::De => DE,
+        }
+    }
+}
+
+impl std::fmt::Display for Language {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Shown in its own language so it can be found from any locale
+        let s = match self {
+            Language::En => "English",
+            Language::De => "Deutsch",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+static CURRENT: AtomicU8 = AtomicU8::new(0);
+
+pub fn set_language(language: Language) {
+    CURRENT.store(language as u8, Ordering::Relaxed);
+}
+
+pub fn language() -> Language {
+    match CURRENT.load(Ordering::Relaxed) {
+        1 => Language::De,
+        _ => Language::En,
+    }
+}
+
+/// Raw bundle entry, without fallback.
+pub fn translation(language: Language, key: &str) -> Option<&'static str> {
+    language
+        .bundle()
+        .iter()
+        .find(|(k, _)| *k == key)
+        .map(|(_, v)| *v)
+}
+
+/// All keys of a bundle, in declaration order.
+pub fn keys(language: Language) -> impl Iterator<Item = &'static str> {
+    language.bundle().iter().map(|(k, _)| *k)
+}
+
+/// String for `key` in the active language; the key itself if no bundle has it.
+pub fn tr(key: &'static str) -> &'static str {
+    translation(language(), key)
+        .or_else(|| translation(Language::En, key))
+        .unwrap_or(key)
+}
+
+/// [`tr`] with each `{}` replaced by the next argument.
+pub fn trf(key: &'static str, args: &[&dyn std::fmt::Display]) -> String {
+    let mut parts = tr(key).split("{}");
+    let mut out = parts.next().unwrap_or_default().to_string();
+    let mut args = args.iter();
+    for part in parts {
+        if let Some(arg) = args.next() {
+            out.push_str(&arg.to_string());
+        }
+        out.push_str(part);
+    }
+    out
+}
+
+const EN: &[(&str, &str)] = &[
+    ("app.title", "GooglePicz - Google Photos Manager"),
+    ("common.loading", "Loading..."),
+    ("common.untitled", "Untitled"),
+    ("common.unknown", "Unknown"),
+    ("status.never_synced", "Never synced"),
+    ("status.last_synced", "Last synced {}"),
+    ("status.errors", "Errors: {}"),
+    ("status.sync_started", "Sync started"),
+    ("status.retrying", "Retrying in {}s"),
+    ("status.syncing", "Syncing {} items"),
+    ("status.sync_completed", "Sync completed: {} items"),
+    ("status.scanning_faces", "Scanning faces: {}/{}"),
+    ("status.faces_scanned", "Face scan completed: {} photos"),
+    ("status.sync_error", "Sync error"),
+    ("status.reauth_required", "Sign-in required"),
+    ("status.description_queued", "Offline: description will be uploaded on next sync"),
+    ("menu.open", "Open"),
+    ("menu.favorite", "Add to favorites"),
+    ("menu.unfavorite", "Remove from favorites"),
+    ("menu.add_to_album", "Add to album"),
+    ("menu.back", "Back"),
+    ("menu.remove_from_album", "Remove from album"),
+    ("menu.copy_link", "Copy link"),
+    ("menu.export", "Export"),
+    ("menu.move_to_trash", "Move to trash"),
+    ("thumbnail.retry", "Retry"),
+    ("sync.title", "Sync activity"),
+    ("sync.now", "Sync now"),
+    ("sync.paused", "Paused"),
+    ("sync.idle", "Idle"),
+    ("sync.stage", "Stage: {}"),
+    ("sync.stage.media_items", "Media items"),
+    ("sync.stage.faces", "Faces"),
+    ("sync.processed", "{} items processed"),
+    ("sync.processed_of", "{} of about {} items processed"),
+    ("sync.page", "Page {}"),
+    ("sync.retry_in", "Retrying in {}s"),
+    ("sync.report", "Last run: {} fetched, {} new, {} updated, {} unchanged in {} pages"),
+    ("sync.last_error", "Last error ({}): {}"),
+    ("errors.title", "Operation failed"),
+    ("errors.dismiss", "Dismiss"),
+    ("errors.dismiss_all", "Dismiss All"),
+    ("toast.album_created", "Album \"{}\" created"),
+    ("toast.album_deleted", "Album \"{}\" deleted"),
+    ("toast.undo", "Undo"),
+    ("toast.photo_assigned", "Added to {}"),
+    ("toast.batch_added", "{} photos added to {}"),
+    ("drag.hint", "Drop on an album to add {} photos"),
+    ("toast.batch_favorites", "Updated favorites for {} photos"),
+    ("toast.batch_removed", "Removed {} photos from album"),
+    ("toast.photo_removed", "Removed photo from album"),
+    ("toast.photo_trashed", "Moved photo to trash"),
+    ("toast.batch_trashed", "Moved {} photos to trash"),
+    ("toast.trash_purged", "Deleted {} items for good"),
+    ("toast.batch_exported", "Exported {} photos to {}"),
+    ("toast.export_partial", "Exported {} of {} photos to {}"),
+    ("toast.export_failed", "Could not export {}: {}"),
+    ("toast.export_busy", "An export is already running"),
+    ("toast.album_refresh_failed", "Showing cached album contents, refresh failed: {}"),
+    ("export.progress", "Exporting {} / {}"),
+    ("upload.progress", "Uploaded {} of {}"),
+    ("upload.queued", "Waiting"),
+    ("upload.uploading", "Uploading..."),
+    ("upload.done", "Uploaded"),
+    ("upload.failed", "Failed: {}"),
+    ("upload.add_to_album", "Add {} uploads to {}"),
+    ("upload.unsupported", "{} is not a photo or video Google Photos accepts"),
+    ("thumbnail.loading", "Loading thumbnails {}/{}"),
+    ("thumbnail.cancel_preload", "Stop preloading"),
+    ("statusbar.shown", "{} shown"),
+    ("statusbar.shown_of", "{} of {} shown"),
+    ("statusbar.cache_size", "Cache {}"),
+    ("statusbar.online", "Online"),
+    ("statusbar.offline", "Offline"),
+    ("offline.pill", "Offline — showing cached data"),
+    ("offline.unavailable", "Needs a connection to Google Photos"),
+    ("offline.queued", "Offline — the change is sent with the next sync"),
+    ("startup.initializing", "Initializing…"),
+    ("startup.migrating", "Upgrading the library database: step {} of {}"),
+    ("statusbar.unknown", "Unknown"),
+    ("statusbar.signed_out", "Not signed in"),
+    ("statusbar.memory", "{} in memory"),
+    ("statusbar.memory_hits", "{} in memory, {}% hits"),
+    ("statusbar.memory_details", "{} hits, {} misses, {} evicted"),
+    ("statusbar.network", "↓ {} ↑ {}"),
+    ("statusbar.network_details", "{} requests, {} failed, {} s waiting"),
+    ("settings.account", "Account"),
+    ("settings.account_signed_in", "Signed in as {}"),
+    ("settings.account_unknown", "Signed in; the account address is not known"),
+    ("settings.account_none", "No stored credentials"),
+    ("settings.account_backend", "Tokens stored in: {}"),
+    ("settings.account_token_expired", "The access token has expired and is renewed on the next request"),
+    ("accounts.default", "Default account"),
+    ("accounts.signed_out", "Not signed in"),
+    ("accounts.add", "Add account…"),
+    ("accounts.adding", "Sign in to the new account in your browser…"),
+    ("accounts.sign_out", "Sign out"),
+    ("accounts.reauth_title", "Google no longer accepts this sign-in"),
+    ("accounts.reauth_body", "Access was revoked or has expired. Syncing is paused until you sign in again."),
+    ("accounts.reauth", "Sign in again"),
+    ("accounts.reauthenticating", "Sign in again in your browser…"),
+    ("toast.signed_out", "Signed out"),
+    ("toast.settings_saved", "Settings saved"),
+    ("toast.restart_required", "Restart GooglePicz to apply: {}"),
+    ("toast.error_log_copied", "Error log copied"),
+    ("toast.error_log_cleared", "Error log cleared"),
+    ("toast.image_cache_cleared", "Image cache cleared"),
+    ("toast.video_cache_cleared", "Video cache cleared"),
+    ("toast.cache_reset", "Local cache reset, syncing everything again"),
+    ("toast.image_copied", "Image copied"),
+    ("toast.thumbnail_copied", "Full image still loading, copied the thumbnail"),
+    ("toast.image_not_loaded", "Image is still loading"),
+    ("toast.link_copied", "Link copied"),
+    ("toast.person_renamed", "Renamed {} to {}"),
+    ("toast.people_merged", "Merged {} into {}"),
+    ("toast.cluster_named", "Named {} more faces {}"),
+    ("toast.no_faces_found", "No faces found in this photo"),
+    ("toast.face_ignored", "Face ignored"),
+    ("toast.face_restored", "Face shown again"),
+    ("grid.loading", "Loading photos..."),
+    ("grid.empty", "No photos found. Make sure you have authenticated and synced your photos."),
+    ("albums.title", "Albums"),
+    ("albums.saved_filters", "Saved filters"),
+    ("albums.all_photos", "All photos"),
+    ("albums.favorites", "Favorites"),
+    ("albums.trash", "Trash"),
+    ("albums.all_albums", "All albums…"),
+    ("albums.filter", "Filter albums"),
+    ("albums.no_match", "No album matches"),
+    ("trash.title", "Trash"),
+    ("trash.count", "{} items in trash"),
+    ("trash.deleted_at", "Deleted {}"),
+    ("trash.restore", "Restore"),
+    ("trash.delete_forever", "Delete forever"),
+    ("trash.restore_selected", "Restore selected"),
+    ("trash.delete_selected", "Delete selected forever"),
+    ("trash.empty", "Empty trash"),
+    ("trash.empty_hint", "The trash is empty."),
+    ("trash.empty_confirm", "Delete all {} items in the trash forever?"),
+    ("trash.local_only", "Items are removed from this computer only; Google Photos keeps its copy."),
+    ("albums.item_count", "{} items"),
+    ("grid.load_more", "Load more"),
+    ("grid.found", "Found {} photos"),
+    ("grid.jump", "Jump to YYYY-MM"),
+    ("selection.count", "{} selected"),
+    ("selection.add_to_album", "Add to album"),
+    ("faces.name", "Name"),
+    ("faces.label", "Face {} ({},{},{},{}): {}"),
+    ("faces.rename", "Rename"),
+    ("faces.ignore", "Ignore"),
+    ("faces.unignore", "Show again"),
+    ("faces.ignored_label", "{} (ignored)"),
+    ("faces.apply_to_cluster", "Name {} more photos of this person {} too?"),
+    ("faces.apply", "Apply"),
+    ("faces.detect", "Detect faces"),
+    ("faces.detecting", "Detecting faces..."),
+    ("video.unavailable", "Video playback not available"),
+    ("video.downloading", "Downloading video… {}"),
+    ("video.downloading_of", "Downloading video… {} of {}"),
+    ("video.buffering", "Buffering…"),
+    ("search.mode.filename", "Filename"),
+    ("search.mode.description", "Description"),
+    ("search.mode.text", "Filename/descr."),
+    ("search.mode.favorites", "Favorites"),
+    ("search.mode.date_range", "Date range"),
+    ("search.mode.mime_type", "File type"),
+    ("search.mode.camera_model", "Camera model"),
+    ("search.mode.camera_make", "Camera make"),
+    ("search.mode.faces", "Faces"),
+    ("search.placeholder.filename", "Filename"),
+    ("search.placeholder.description", "Description"),
+    ("search.placeholder.text", "Filename or description"),
+    ("search.placeholder.favorites", "Favorites"),
+    ("search.placeholder.date_range", "YYYY-MM-DD..YYYY-MM-DD"),
+    ("search.placeholder.mime_type", "Mime type"),
+    ("search.placeholder.camera_model", "Camera model"),
+    ("search.placeholder.camera_make", "Camera make"),
+    ("search.placeholder.faces", "Has faces"),
+    ("search.camera", "Camera"),
+    ("search.from", "From"),
+    ("search.to", "To"),
+    ("search.recent.title", "Recent searches"),
+    ("search.recent.empty", "No recent searches"),
+    ("search.saved.name", "Name of the saved filter"),
+    ("search.favorite", "Fav"),
+    ("search.faces", "Faces"),
+    ("search.person", "Person"),
+    ("search.more", "{} more results…"),
+    ("filters.favorites", "★ Favorites"),
+    ("filters.faces", "With faces"),
+    ("filters.person", "Person: {}"),
+    ("filters.album", "In album: {}"),
+    ("filters.clear_all", "Clear all"),
+    ("album.title", "Album title"),
+    ("album.new_title", "New title"),
+    ("album.delete_confirm", "Delete album?"),
+    ("album.remove_photo_confirm", "Remove photo from this album?"),
+    ("album.remove_photos_confirm", "Remove {} photos from this album?"),
+    ("info.title", "Info"),
+    ("info.filename", "Filename"),
+    ("info.description", "Description"),
+    ("info.add_description", "Add a description"),
+    ("info.created", "Created"),
+    ("info.dimensions", "Dimensions"),
+    ("info.type", "Type"),
+    ("info.camera", "Camera"),
+    ("info.frame_rate", "Frame rate"),
+    ("info.size", "Size"),
+    ("info.albums", "Albums"),
+    ("info.people", "People"),
+    ("people.title", "People"),
+    ("people.empty", "No faces detected yet"),
+    ("people.unnamed", "Unnamed faces"),
+    ("people.cluster", "Unnamed person #{}"),
+    ("people.photo_count", "{} photos"),
+    ("people.new_name", "New name"),
+    ("people.merge_into", "Merge into"),
+    ("settings.title", "Settings"),
+    ("settings.profile", "Profile: {}"),
+    ("settings.profile_default", "default"),
+    ("settings.oauth_port", "OAuth port"),
+    ("settings.thumbs_preload", "Thumbs preload"),
+    ("settings.preload_threads", "Preload threads"),
+    ("settings.sync_interval", "Sync interval"),
+    ("settings.debug_console", "Debug console"),
+    ("settings.trace_spans", "Trace spans"),
+    ("settings.cache_path", "Cache path"),
+    ("settings.image_cache_max", "Image cache limit (MB, 0 = unlimited)"),
+    ("settings.image_cache_usage", "Cached images: {} in {} files"),
+    ("settings.image_cache_clear", "Clear image cache"),
+    ("settings.video_cache_max", "Video cache limit (MB, 0 = unlimited)"),
+    ("settings.video_cache_usage", "Cached videos: {} in {} files"),
+    ("settings.video_cache_clear", "Clear video cache"),
+    ("settings.reset_cache", "Reset local cache"),
+    ("settings.reset_cache_confirm", "Remove all cached photos, albums and faces and sync everything again?"),
+    ("settings.reset_cache_kept", "Changes not uploaded yet are kept."),
+    ("settings.slideshow_interval", "Slideshow interval (s)"),
+    ("settings.slideshow_videos", "Play videos in slideshow"),
+    ("settings.invalid.range", "Enter a whole number from {} to {}"),
+    ("settings.invalid.min", "Enter a whole number of at least {}"),
+    ("settings.invalid.cache_path", "Folder does not exist and cannot be created"),
+    ("settings.invalid.allowed", "Allowed: {}"),
+    ("settings.restart_note", "Takes effect after a restart"),
+    ("settings.restart_pending", "Waiting for a restart: {}"),
+    ("settings.error_log", "View error log"),
+    ("settings.error_log_empty", "The error log is empty."),
+    ("settings.error_log_copy", "Copy to clipboard"),
+    ("settings.error_log_clear", "Clear log"),
+    ("timeline.day", "Day"),
+    ("timeline.month", "Month"),
+    ("timeline.year", "Year"),
+    ("timeline.unknown_date", "Unknown date"),
+    ("date.month", "{} {}"),
+    ("date.day", "{}, {} {} {}"),
+    ("month.1", "January"),
+    ("month.2", "February"),
+    ("month.3", "March"),
+    ("month.4", "April"),
+    ("month.5", "May"),
+    ("month.6", "June"),
+    ("month.7", "July"),
+    ("month.8", "August"),
+    ("month.9", "September"),
+    ("month.10", "October"),
+    ("month.11", "November"),
+    ("month.12", "December"),
+    ("weekday.mon", "Monday"),
+    ("weekday.tue", "Tuesday"),
+    ("weekday.wed", "Wednesday"),
+    ("weekday.thu", "Thursday"),
+    ("weekday.fri", "Friday"),
+    ("weekday.sat", "Saturday"),
+    ("weekday.sun", "Sunday"),
+    ("error.init_cache", "Failed to initialize cache"),
+    ("error.switch_account", "Failed to switch account"),
+    ("error.add_account", "Failed to add account"),
+    ("error.sign_out", "Failed to sign out"),
+    ("error.reauth", "Failed to sign in again"),
+    ("error.read_last_sync", "Failed to read last sync"),
+    ("error.load_photos", "Failed to load photos"),
+    ("error.load_albums", "Failed to load albums"),
+    ("error.batch", "Batch action failed"),
+    ("error.update_favorite", "Failed to update favorite"),
+    ("error.remove_photo", "Failed to remove photo"),
+    ("error.load_trash", "Failed to load trash"),
+    ("error.move_to_trash", "Failed to move to trash"),
+    ("error.restore_from_trash", "Failed to restore from trash"),
+    ("error.delete_forever", "Failed to delete items"),
+    ("error.delete_album", "Failed to delete album"),
+    ("error.sync_control", "Sync is not running"),
+    ("error.restore_album", "Failed to restore album"),
+    ("error.copy_image", "Failed to copy image"),
+    ("error.load_thumbnail", "Failed to load thumbnail for {}"),
+    ("error.load_thumbnails", "Thumbnails that failed to load: {}"),
+    ("error.load_photo_albums", "Failed to load albums for photo"),
+    ("error.update_description", "Failed to update description"),
+    ("error.slideshow_empty", "No photos to show in slideshow"),
+    ("error.invalid_jump", "Invalid date, use YYYY, YYYY-MM or YYYY-MM-DD"),
+    ("error.jump_not_found", "No photos on or before {}"),
+    ("error.load_period_counts", "Failed to load timeline counts"),
+    ("error.load_video_durations", "Failed to load video durations"),
+    ("error.load_image", "Failed to load image"),
+    ("error.load_faces", "Failed to load faces"),
+    ("error.gstreamer_missing", "GStreamer not available"),
+    ("error.start_video", "Failed to start video: {}. Missing codecs?"),
+    ("error.video_path", "Invalid video file path"),
+    ("error.save_settings", "Failed to save settings"),
+    ("error.settings_invalid", "Invalid {}: {}"),
+    ("error.config_invalid", "Settings cannot be saved: {}"),
+    ("error.read_error_log", "Failed to read the error log"),
+    ("error.clear_error_log", "Failed to clear the error log"),
+    ("error.clear_image_cache", "Failed to clear the image cache"),
+    ("error.clear_video_cache", "Failed to clear the video cache"),
+    ("error.reset_cache", "Failed to reset the local cache"),
+    ("error.create_album", "Failed to create album"),
+    ("error.assign_photo", "Failed to assign photo"),
+    ("error.load_people", "Failed to load people"),
+    ("error.update_people", "Failed to update people"),
+    ("error.save_face_name", "Failed to save face name"),
+    ("error.detect_faces", "Face detection failed"),
+];
+
+const DE: &[(&str, &str)] = &[
+    ("app.title", "GooglePicz - Google Fotos verwalten"),
+    ("common.loading", "Wird geladen..."),
+    ("common.untitled", "Ohne Titel"),
+    ("common.unknown", "Unbekannt"),
+    ("status.never_synced", "Noch nie synchronisiert"),
+    ("status.last_synced", "Zuletzt synchronisiert {}"),
+    ("status.errors", "Fehler: {}"),
+    ("status.sync_started", "Synchronisierung gestartet"),
+    ("status.retrying", "Neuer Versuch in {}s"),
+    ("status.syncing", "Synchronisiere {} Elemente"),
+    ("status.sync_completed", "Synchronisierung abgeschlossen: {} Elemente"),
+    ("status.scanning_faces", "Gesichter werden gescannt: {}/{}"),
+    ("status.faces_scanned", "Gesichtersuche abgeschlossen: {} Fotos"),
+    ("status.sync_error", "Synchronisierungsfehler"),
+    ("status.reauth_required", "Anmeldung erforderlich"),
+    ("status.description_queued", "Offline: Beschreibung wird bei der nächsten Synchronisierung hochgeladen"),
+    ("menu.open", "Öffnen"),
+    ("menu.favorite", "Zu Favoriten hinzufügen"),
+    ("menu.unfavorite", "Aus Favoriten entfernen"),
+    ("menu.add_to_album", "Zu Album hinzufügen"),
+    ("menu.back", "Zurück"),
+    ("menu.remove_from_album", "Aus Album entfernen"),
+    ("menu.copy_link", "Link kopieren"),
+    ("menu.export", "Exportieren"),
+    ("menu.move_to_trash", "In den Papierkorb"),
+    ("thumbnail.retry", "Erneut versuchen"),
+    ("sync.title", "Synchronisierung"),
+    ("sync.now", "Jetzt synchronisieren"),
+    ("sync.paused", "Pausiert"),
+    ("sync.idle", "Inaktiv"),
+    ("sync.stage", "Schritt: {}"),
+    ("sync.stage.media_items", "Medienelemente"),
+    ("sync.stage.faces", "Gesichter"),
+    ("sync.processed", "{} Elemente verarbeitet"),
+    ("sync.processed_of", "{} von etwa {} Elementen verarbeitet"),
+    ("sync.page", "Seite {}"),
+    ("sync.retry_in", "Neuer Versuch in {}s"),
+    ("sync.report", "Letzter Lauf: {} abgerufen, {} neu, {} aktualisiert, {} unverändert auf {} Seiten"),
+    ("sync.last_error", "Letzter Fehler ({}): {}"),
+    ("errors.title", "Vorgang fehlgeschlagen"),
+    ("errors.dismiss", "Schließen"),
+    ("errors.dismiss_all", "Alle schließen"),
+    ("toast.album_created", "Album „{}“ erstellt"),
+    ("toast.album_deleted", "Album „{}“ gelöscht"),
+    ("toast.undo", "Rückgängig"),
+    ("toast.photo_assigned", "Zu {} hinzugefügt"),
+    ("toast.batch_added", "{} Fotos zu {} hinzugefügt"),
+    ("drag.hint", "Auf ein Album ziehen, um {} Fotos hinzuzufügen"),
+    ("toast.batch_favorites", "Favoriten für {} Fotos aktualisiert"),
+    ("toast.batch_removed", "{} Fotos aus dem Album entfernt"),
+    ("toast.photo_removed", "Foto aus dem Album entfernt"),
+    ("toast.photo_trashed", "Foto in den Papierkorb verschoben"),
+    ("toast.batch_trashed", "{} Fotos in den Papierkorb verschoben"),
+    ("toast.trash_purged", "{} Elemente endgültig gelöscht"),
+    ("toast.batch_exported", "{} Fotos nach {} exportiert"),
+    ("toast.export_partial", "{} von {} Fotos nach {} exportiert"),
+    ("toast.export_failed", "{} konnte nicht exportiert werden: {}"),
+    ("toast.export_busy", "Es läuft bereits ein Export"),
+    ("toast.album_refresh_failed", "Zwischengespeicherter Albuminhalt, Aktualisierung fehlgeschlagen: {}"),
+    ("export.progress", "Exportiere {} / {}"),
+    ("upload.progress", "{} von {} hochgeladen"),
+    ("upload.queued", "Wartet"),
+    ("upload.uploading", "Wird hochgeladen..."),
+    ("upload.done", "Hochgeladen"),
+    ("upload.failed", "Fehlgeschlagen: {}"),
+    ("upload.add_to_album", "{} Uploads zu {} hinzufügen"),
+    ("upload.unsupported", "{} ist kein von Google Photos unterstütztes Foto oder Video"),
+    ("thumbnail.loading", "Lade Vorschaubilder {}/{}"),
+    ("thumbnail.cancel_preload", "Vorladen abbrechen"),
+    ("statusbar.shown", "{} angezeigt"),
+    ("statusbar.shown_of", "{} von {} angezeigt"),
+    ("statusbar.cache_size", "Cache {}"),
+    ("statusbar.online", "Online"),
+    ("statusbar.offline", "Offline"),
+    ("offline.pill", "Offline – zeige zwischengespeicherte Daten"),
+    ("offline.unavailable", "Benötigt eine Verbindung zu Google Photos"),
+    ("offline.queued", "Offline – die Änderung wird bei der nächsten Synchronisierung übertragen"),
+    ("startup.initializing", "Wird initialisiert…"),
+    ("startup.migrating", "Bibliotheksdatenbank wird aktualisiert: Schritt {} von {}"),
+    ("statusbar.unknown", "Unbekannt"),
+    ("statusbar.signed_out", "Nicht angemeldet"),
+    ("statusbar.memory", "{} im Speicher"),
+    ("statusbar.memory_hits", "{} im Speicher, {}% Treffer"),
+    ("statusbar.memory_details", "{} Treffer, {} Fehlzugriffe, {} verdrängt"),
+    ("statusbar.network", "↓ {} ↑ {}"),
+    ("statusbar.network_details", "{} Anfragen, {} fehlgeschlagen, {} s Wartezeit"),
+    ("settings.account", "Konto"),
+    ("settings.account_signed_in", "Angemeldet als {}"),
+    ("settings.account_unknown", "Angemeldet; die Kontoadresse ist nicht bekannt"),
+    ("settings.account_none", "Keine gespeicherten Zugangsdaten"),
+    ("settings.account_backend", "Tokens gespeichert in: {}"),
+    ("settings.account_token_expired", "Das Zugriffstoken ist abgelaufen und wird bei der nächsten Anfrage erneuert"),
+    ("accounts.default", "Standardkonto"),
+    ("accounts.signed_out", "Nicht angemeldet"),
+    ("accounts.add", "Konto hinzufügen…"),
+    ("accounts.adding", "Bitte im Browser beim neuen Konto anmelden…"),
+    ("accounts.sign_out", "Abmelden"),
+    ("accounts.reauth_title", "Google akzeptiert diese Anmeldung nicht mehr"),
+    ("accounts.reauth_body", "Der Zugriff wurde widerrufen oder ist abgelaufen. Die Synchronisierung pausiert bis zur erneuten Anmeldung."),
+    ("accounts.reauth", "Erneut anmelden"),
+    ("accounts.reauthenticating", "Bitte im Browser erneut anmelden…"),
+    ("toast.signed_out", "Abgemeldet"),
+    ("toast.settings_saved", "Einstellungen gespeichert"),
+    ("toast.restart_required", "GooglePicz neu starten, um Folgendes zu übernehmen: {}"),
+    ("toast.error_log_copied", "Fehlerprotokoll kopiert"),
+    ("toast.error_log_cleared", "Fehlerprotokoll geleert"),
+    ("toast.image_cache_cleared", "Bildcache geleert"),
+    ("toast.video_cache_cleared", "Videocache geleert"),
+    ("toast.cache_reset", "Lokaler Cache zurückgesetzt, alles wird neu synchronisiert"),
+    ("toast.image_copied", "Bild kopiert"),
+    ("toast.thumbnail_copied", "Bild lädt noch, Vorschaubild kopiert"),
+    ("toast.image_not_loaded", "Bild lädt noch"),
+    ("toast.link_copied", "Link kopiert"),
+    ("toast.person_renamed", "{} in {} umbenannt"),
+    ("toast.people_merged", "{} mit {} zusammengeführt"),
+    ("toast.cluster_named", "{} weitere Gesichter {} genannt"),
+    ("toast.no_faces_found", "Keine Gesichter in diesem Foto gefunden"),
+    ("toast.face_ignored", "Gesicht ignoriert"),
+    ("toast.face_restored", "Gesicht wird wieder angezeigt"),
+    ("grid.loading", "Fotos werden geladen..."),
+    ("grid.empty", "Keine Fotos gefunden. Bitte anmelden und die Fotos synchronisieren."),
+    ("albums.title", "Alben"),
+    ("albums.saved_filters", "Gespeicherte Filter"),
+    ("albums.all_photos", "Alle Fotos"),
+    ("albums.favorites", "Favoriten"),
+    ("albums.trash", "Papierkorb"),
+    ("albums.all_albums", "Alle Alben…"),
+    ("albums.filter", "Alben filtern"),
+    ("albums.no_match", "Kein Album gefunden"),
+    ("trash.title", "Papierkorb"),
+    ("trash.count", "{} Elemente im Papierkorb"),
+    ("trash.deleted_at", "Gelöscht am {}"),
+    ("trash.restore", "Wiederherstellen"),
+    ("trash.delete_forever", "Endgültig löschen"),
+    ("trash.restore_selected", "Auswahl wiederherstellen"),
+    ("trash.delete_selected", "Auswahl endgültig löschen"),
+    ("trash.empty", "Papierkorb leeren"),
+    ("trash.empty_hint", "Der Papierkorb ist leer."),
+    ("trash.empty_confirm", "Alle {} Elemente im Papierkorb endgültig löschen?"),
+    ("trash.local_only", "Elemente werden nur von diesem Computer entfernt; Google Fotos behält seine Kopie."),
+    ("albums.item_count", "{} Elemente"),
+    ("grid.load_more", "Mehr laden"),
+    ("grid.found", "{} Fotos gefunden"),
+    ("grid.jump", "Springe zu JJJJ-MM"),
+    ("selection.count", "{} ausgewählt"),
+    ("selection.add_to_album", "Zu Album hinzufügen"),
+    ("faces.name", "Name"),
+    ("faces.label", "Gesicht {} ({},{},{},{}): {}"),
+    ("faces.rename", "Umbenennen"),
+    ("faces.ignore", "Ignorieren"),
+    ("faces.unignore", "Wieder anzeigen"),
+    ("faces.ignored_label", "{} (ignoriert)"),
+    ("faces.apply_to_cluster", "{} weitere Fotos dieser Person ebenfalls {} nennen?"),
+    ("faces.apply", "Übernehmen"),
+    ("faces.detect", "Gesichter erkennen"),
+    ("faces.detecting", "Gesichter werden erkannt..."),
+    ("video.unavailable", "Videowiedergabe nicht verfügbar"),
+    ("video.downloading", "Video wird geladen… {}"),
+    ("video.downloading_of", "Video wird geladen… {} von {}"),
+    ("video.buffering", "Puffern…"),
+    ("search.mode.filename", "Dateiname"),
+    ("search.mode.description", "Beschreibung"),
+    ("search.mode.text", "Dateiname/Beschr."),
+    ("search.mode.favorites", "Favoriten"),
+    ("search.mode.date_range", "Datum von/bis"),
+    ("search.mode.mime_type", "Dateityp"),
+    ("search.mode.camera_model", "Kamera-Modell"),
+    ("search.mode.camera_make", "Kamera-Hersteller"),
+    ("search.mode.faces", "Gesichter"),
+    ("search.placeholder.filename", "Dateiname"),
+    ("search.placeholder.description", "Beschreibung"),
+    ("search.placeholder.text", "Dateiname oder Beschreibung"),
+    ("search.placeholder.favorites", "Favoriten"),
+    ("search.placeholder.date_range", "JJJJ-MM-TT..JJJJ-MM-TT"),
+    ("search.placeholder.mime_type", "MIME-Typ"),
+    ("search.placeholder.camera_model", "Kamera-Modell"),
+    ("search.placeholder.camera_make", "Kamera-Hersteller"),
+    ("search.placeholder.faces", "Mit Gesichtern"),
+    ("search.camera", "Kamera"),
+    ("search.from", "Von"),
+    ("search.to", "Bis"),
+    ("search.recent.title", "Letzte Suchen"),
+    ("search.recent.empty", "Keine letzten Suchen"),
+    ("search.saved.name", "Name des gespeicherten Filters"),
+    ("search.favorite", "Fav"),
+    ("search.faces", "Gesichter"),
+    ("search.person", "Person"),
+    ("search.more", "{} weitere Treffer…"),
+    ("filters.favorites", "★ Favoriten"),
+    ("filters.faces", "Mit Gesichtern"),
+    ("filters.person", "Person: {}"),
+    ("filters.album", "Im Album: {}"),
+    ("filters.clear_all", "Alle entfernen"),
+    ("album.title", "Albumtitel"),
+    ("album.new_title", "Neuer Titel"),
+    ("album.delete_confirm", "Album löschen?"),
+    ("album.remove_photo_confirm", "Foto aus diesem Album entfernen?"),
+    ("album.remove_photos_confirm", "{} Fotos aus diesem Album entfernen?"),
+    ("info.title", "Info"),
+    ("info.filename", "Dateiname"),
+    ("info.description", "Beschreibung"),
+    ("info.add_description", "Beschreibung hinzufügen"),
+    ("info.created", "Erstellt"),
+    ("info.dimensions", "Abmessungen"),
+    ("info.type", "Typ"),
+    ("info.camera", "Kamera"),
+    ("info.frame_rate", "Bildrate"),
+    ("info.size", "Größe"),
+    ("info.albums", "Alben"),
+    ("info.people", "Personen"),
+    ("people.title", "Personen"),
+    ("people.empty", "Noch keine Gesichter erkannt"),
+    ("people.unnamed", "Unbenannte Gesichter"),
+    ("people.cluster", "Unbenannte Person #{}"),
+    ("people.photo_count", "{} Fotos"),
+    ("people.new_name", "Neuer Name"),
+    ("people.merge_into", "Zusammenführen mit"),
+    ("settings.title", "Einstellungen"),
+    ("settings.profile", "Profil: {}"),
+    ("settings.profile_default", "Standard"),
+    ("settings.oauth_port", "OAuth-Port"),
+    ("settings.thumbs_preload", "Vorschaubilder vorladen"),
+    ("settings.preload_threads", "Vorlade-Threads"),
+    ("settings.sync_interval", "Sync-Intervall"),
+    ("settings.debug_console", "Debug-Konsole"),
+    ("settings.trace_spans", "Trace-Spans"),
+    ("settings.cache_path", "Cache-Pfad"),
+    ("settings.image_cache_max", "Bildcache-Limit (MB, 0 = unbegrenzt)"),
+    ("settings.image_cache_usage", "Zwischengespeicherte Bilder: {} in {} Dateien"),
+    ("settings.image_cache_clear", "Bildcache leeren"),
+    ("settings.video_cache_max", "Videocache-Limit (MB, 0 = unbegrenzt)"),
+    ("settings.video_cache_usage", "Zwischengespeicherte Videos: {} in {} Dateien"),
+    ("settings.video_cache_clear", "Videocache leeren"),
+    ("settings.reset_cache", "Lokalen Cache zurücksetzen"),
+    ("settings.reset_cache_confirm", "Alle zwischengespeicherten Fotos, Alben und Gesichter entfernen und alles neu synchronisieren?"),
+    ("settings.reset_cache_kept", "Noch nicht hochgeladene Änderungen bleiben erhalten."),
+    ("settings.slideshow_interval", "Diashow-Intervall (s)"),
+    ("settings.slideshow_videos", "Videos in der Diashow abspielen"),
+    ("settings.invalid.range", "Ganze Zahl von {} bis {} eingeben"),
+    ("settings.invalid.min", "Ganze Zahl ab {} eingeben"),
+    ("settings.invalid.cache_path", "Ordner existiert nicht und kann nicht angelegt werden"),
+    ("settings.invalid.allowed", "Erlaubt: {}"),
+    ("settings.restart_note", "Wirkt erst nach einem Neustart"),
+    ("settings.restart_pending", "Wartet auf einen Neustart: {}"),
+    ("settings.error_log", "Fehlerprotokoll anzeigen"),
+    ("settings.error_log_empty", "Das Fehlerprotokoll ist leer."),
+    ("settings.error_log_copy", "In die Zwischenablage kopieren"),
+    ("settings.error_log_clear", "Protokoll leeren"),
+    ("timeline.day", "Tag"),
+    ("timeline.month", "Monat"),
+    ("timeline.year", "Jahr"),
+    ("timeline.unknown_date", "Unbekanntes Datum"),
+    ("date.month", "{} {}"),
+    ("date.day", "{}, {}. {} {}"),
+    ("month.1", "Januar"),
+    ("month.2", "Februar"),
+    ("month.3", "März"),
+    ("month.4", "April"),
+    ("month.5", "Mai"),
+    ("month.6", "Juni"),
+    ("month.7", "Juli"),
+    ("month.8", "August"),
+    ("month.9", "September"),
+    ("month.10", "Oktober"),
+    ("month.11", "November"),
+    ("month.12", "Dezember"),
+    ("weekday.mon", "Montag"),
+    ("weekday.tue", "Dienstag"),
+    ("weekday.wed", "Mittwoch"),
+    ("weekday.thu", "Donnerstag"),
+    ("weekday.fri", "Freitag"),
+    ("weekday.sat", "Samstag"),
+    ("weekday.sun", "Sonntag"),
+    ("error.init_cache", "Cache konnte nicht initialisiert werden"),
+    ("error.switch_account", "Kontowechsel fehlgeschlagen"),
+    ("error.add_account", "Konto konnte nicht hinzugefügt werden"),
+    ("error.sign_out", "Abmelden fehlgeschlagen"),
+    ("error.reauth", "Erneute Anmeldung fehlgeschlagen"),
+    ("error.read_last_sync", "Letzte Synchronisierung konnte nicht gelesen werden"),
+    ("error.load_photos", "Fotos konnten nicht geladen werden"),
+    ("error.load_albums", "Alben konnten nicht geladen werden"),
+    ("error.batch", "Sammelaktion fehlgeschlagen"),
+    ("error.update_favorite", "Favorit konnte nicht aktualisiert werden"),
+    ("error.remove_photo", "Foto konnte nicht entfernt werden"),
+    ("error.load_trash", "Papierkorb konnte nicht geladen werden"),
+    ("error.move_to_trash", "Verschieben in den Papierkorb fehlgeschlagen"),
+    ("error.restore_from_trash", "Wiederherstellen aus dem Papierkorb fehlgeschlagen"),
+    ("error.delete_forever", "Elemente konnten nicht gelöscht werden"),
+    ("error.delete_album", "Album konnte nicht gelöscht werden"),
+    ("error.sync_control", "Die Synchronisierung läuft nicht"),
+    ("error.restore_album", "Album konnte nicht wiederhergestellt werden"),
+    ("error.copy_image", "Bild konnte nicht kopiert werden"),
+    ("error.load_thumbnail", "Vorschaubild für {} konnte nicht geladen werden"),
+    ("error.load_thumbnails", "Nicht geladene Vorschaubilder: {}"),
+    ("error.load_photo_albums", "Alben des Fotos konnten nicht geladen werden"),
+    ("error.update_description", "Beschreibung konnte nicht aktualisiert werden"),
+    ("error.slideshow_empty", "Keine Fotos für die Diashow"),
+    ("error.invalid_jump", "Ungültiges Datum, erwartet JJJJ, JJJJ-MM oder JJJJ-MM-TT"),
+    ("error.jump_not_found", "Keine Fotos am oder vor {}"),
+    ("error.load_period_counts", "Zeitleiste konnte nicht geladen werden"),
+    ("error.load_video_durations", "Videolängen konnten nicht geladen werden"),
+    ("error.load_image", "Bild konnte nicht geladen werden"),
+    ("error.load_faces", "Gesichter konnten nicht geladen werden"),
+    ("error.gstreamer_missing", "GStreamer nicht verfügbar"),
+    ("error.start_video", "Video konnte nicht gestartet werden: {}. Fehlende Codecs?"),
+    ("error.video_path", "Ungültiger Videodateipfad"),
+    ("error.save_settings", "Einstellungen konnten nicht gespeichert werden"),
+    ("error.settings_invalid", "Ungültige Eingabe für {}: {}"),
+    ("error.config_invalid", "Einstellungen können nicht gespeichert werden: {}"),
+    ("error.read_error_log", "Fehlerprotokoll konnte nicht gelesen werden"),
+    ("error.clear_error_log", "Fehlerprotokoll konnte nicht geleert werden"),
+    ("error.clear_image_cache", "Bildcache konnte nicht geleert werden"),
+    ("error.clear_video_cache", "Videocache konnte nicht geleert werden"),
+    ("error.reset_cache", "Lokaler Cache konnte nicht zurückgesetzt werden"),
+    ("error.create_album", "Album konnte nicht erstellt werden"),
+    ("error.assign_photo", "Foto konnte nicht zugeordnet werden"),
+    ("error.load_people", "Personen konnten nicht geladen werden"),
+    ("error.update_people", "Personen konnten nicht aktualisiert werden"),
+    ("error.save_face_name", "Name des Gesichts konnte nicht gespeichert werden"),
+    ("error.detect_faces", "Gesichtserkennung fehlgeschlagen"),
+];
@@ -11,7 +11,8 @@ pub type MaterialSymbol = GoogleMaterialSymbols;
 pub struct Icon {
     symbol: MaterialSymbol,
     size: u16,
-    color: Color,
+    /// Explicit color; `None` uses the active theme's text color
+    color: Option<Color>,
 }
 
 impl Icon {
@@ -20,7 +21,7 @@ impl Icon {
         Self {
             symbol,
             size: Palette::ICON_SIZE,
-            color: Palette::ICON_COLOR,
+            color: None,
         }
     }
 
@@ -32,17 +33,17 @@ impl Icon {
 
     /// Change icon color
     pub fn color(mut self, color: Color) -> Self {
-        self.color = color;
+        self.color = Some(color);
         self
     }
 }
 
 impl<'a, Message> From<Icon> for Element<'a, Message> {
     fn from(icon: Icon) -> Self {
-        icon
-            .symbol
-            .into_text(icon.size)
-            .style(theme::Text::Color(icon.color))
-            .into()
+        let style = match icon.color {
+            Some(color) => theme::Text::Color(color),
+            None => theme::Text::Default,
+        };
+        icon.symbol.into_text(icon.size).style(style).into()
     }
 }
@@ -0,0 +1,182 @@
+//! Turning downloaded images into bytes iced can display.
+//!
+//! JPEG and PNG are stored as they arrive. WebP and, with the `heic`
+//! feature, HEIC are decoded here and re-encoded as JPEG, so everything in
+//! the cache opens with the same decoders as the rest of the app.
+
+use std::io::Cursor;
+
+/// Quality for images re-encoded as JPEG.
+const JPEG_QUALITY: u8 = 90;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Jpeg,
+    Png,
+    WebP,
+    Heic,
+    Unknown,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FormatError {
+    /// This build cannot decode the format
+    Unsupported(Format),
+    Decode(String),
+}
+
+impl std::fmt::Display for FormatError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FormatError::Unsupported(format) => write!(f, "{:?} images are not supported", format),
+            FormatError::Decode(e) => f.write_str(e),
+        }
+    }
+}
+
+/// ISO-BMFF brands used by HEIF files with HEVC images.
+const HEIC_BRANDS: [&[u8; 4]; 7] = [b"heic", b"heix", b"heim", b"heis", b"hevc", b"hevx", b"mif1"];
+
+/// Guess the format from the leading bytes.
+pub fn sniff(bytes: &[u8]) -> Format {
+    if bytes.len() >= 12 && &bytes[4..8] == b"ftyp" && HEIC_BRANDS.iter().any(|b| &bytes[8..12] == *b) {
+        return Format::Heic;
+    }
+    match image::guess_format(bytes) {
+        Ok(image::ImageFormat::Jpeg) => Format::Jpeg,
+        Ok(image::ImageFormat::Png) => Format::Png,
+        Ok(image::ImageFormat::WebP) => Format::WebP,
+        _ => Format::Unknown,
+    }
+}
+
+/// Whether `bytes` can be stored and shown without converting them first.
+pub fn is_displayable(bytes: &[u8]) -> bool {
+    matches!(sniff(bytes), Format::Jpeg | Format::Png | Format::Unknown)
+}
+
+/// `bytes` as JPEG or PNG. Unknown formats pass through for iced to try.
+pub fn normalize(bytes: Vec<u8>) -> Result<Vec<u8>, FormatError> {
+    let image = match sniff(&bytes) {
+        Format::Jpeg | Format::Png | Format::Unknown => return Ok(bytes),
+        Format::WebP => image::load_from_memory_with_format(&bytes, image::ImageFormat::WebP)
+            .map_err(|e| FormatError::Decode(e.to_string()))?,
+        Format::Heic => decode_heic(&bytes)?,
+    };
+    let mut out = Cursor::new(Vec::new());
+    image::codecs::jpeg::JpegEncoder::new_with_quality(&mut out, JPEG_QUALITY)
+        .encode_image(&image.to_rgb8())
+        .map_err(|e| FormatError::Decode(e.to_string()))?;
+    Ok(out.into_inner())
+}
+
+/// Quality for thumbnails that had to be scaled down.
+const THUMBNAIL_QUALITY: u8 = 80;
+
+/// Thumbnails are only scaled down when an edge exceeds the requested size by this factor.
+const OVERSIZE_FACTOR: u32 = 2;
+
+/// A smaller JPEG of `bytes` if they are far larger than a `size` pixel tile,
+/// which happens when the API ignores the crop hint. The result still covers
+/// the tile. `None` keeps `bytes` as they are.
+pub fn shrink_thumbnail(bytes: &[u8], size: u32) -> Option<Vec<u8>> {
+    let reader = image::io::Reader::new(Cursor::new(bytes)).with_guessed_format().ok()?;
+    // Reading the header is enough to skip images that already fit
+    let (width, height) = reader.into_dimensions().ok()?;
+    let limit = size.saturating_mul(OVERSIZE_FACTOR);
+    if width <= limit && height <= limit {
+        return None;
+    }
+    let image = image::load_from_memory(bytes).ok()?;
+    let scale = f64::from(size) / f64::from(width.min(height));
+    let target = |edge: u32| ((f64::from(edge) * scale).round() as u32).max(1);
+    let small = image::imageops::thumbnail(&image.to_rgb8(), target(width), target(height));
+    let mut out = Cursor::new(Vec::new());
+    image::codecs::jpeg::JpegEncoder::new_with_quality(&mut out, THUMBNAIL_QUALITY)
+        .encode_image(&small)
+        .ok()?;
+    Some(out.into_inner())
+}
+
+#[cfg(feature = "heic")]
+fn decode_heic(bytes: &[u8]) -> Result<image::DynamicImage, FormatError> {
+    use libheif_rs::{ColorSpace, HeifContext, LibHeif, RgbChroma};
+
+    let decode = |e: libheif_rs::HeifError| FormatError::Decode(e.to_string());
+    let context = HeifContext::read_from_bytes(bytes).map_err(decode)?;
+    let handle = context.primary_image_handle().map_err(decode)?;
+    let image = LibHeif::new()
+        .decode(&handle, ColorSpace::Rgb(RgbChroma::Rgb), None)
+        .map_err(decode)?;
+    let plane = image
+        .planes()
+        .interleaved
+        .ok_or_else(|| FormatError::Decode("HEIC image without RGB plane".into()))?;
+    let row = plane.width as usize * 3;
+    let mut rgb = Vec::with_capacity(row * plane.height as usize);
+    for line in plane.data.chunks(plane.stride).take(plane.height as usize) {
+        rgb.extend_from_slice(&line[..row]);
+    }
+    image::RgbImage::from_raw(plane.width, plane.height, rgb)
+        .map(image::DynamicImage::ImageRgb8)
+        .ok_or_else(|| FormatError::Decode("HEIC plane has the wrong size".into()))
+}
+
+#[cfg(not(feature = "heic"))]
+fn decode_heic(_bytes: &[u8]) -> Result<image::DynamicImage, FormatError> {
+    Err(FormatError::Unsupported(Format::Heic))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WEBP: &[u8] = include_bytes!("../tests/fixtures/tiny.webp");
+    const HEIC: &[u8] = include_bytes!("../tests/fixtures/ftyp_only.heic");
+
+    #[test]
+    fn test_sniff_formats() {
+        let mut png = Cursor::new(Vec::new());
+        image::RgbImage::new(1, 1)
+            .write_to(&mut png, image::ImageOutputFormat::Png)
+            .unwrap();
+        assert_eq!(sniff(png.get_ref()), Format::Png);
+        assert_eq!(sniff(WEBP), Format::WebP);
+        assert_eq!(sniff(HEIC), Format::Heic);
+        assert_eq!(sniff(b"not an image"), Format::Unknown);
+        assert!(is_displayable(png.get_ref()));
+        assert!(!is_displayable(WEBP));
+    }
+
+    #[test]
+    fn test_webp_is_converted_to_jpeg() {
+        let jpeg = normalize(WEBP.to_vec()).unwrap();
+        assert_eq!(sniff(&jpeg), Format::Jpeg);
+        let image = image::load_from_memory(&jpeg).unwrap();
+        assert_eq!((image.width(), image.height()), (1, 1));
+    }
+
+    #[test]
+    fn test_only_oversized_thumbnails_shrink() {
+        let encode = |width, height| {
+            let mut jpeg = Cursor::new(Vec::new());
+            image::RgbImage::new(width, height)
+                .write_to(&mut jpeg, image::ImageOutputFormat::Jpeg(90))
+                .unwrap();
+            jpeg.into_inner()
+        };
+        assert!(shrink_thumbnail(&encode(300, 200), 150).is_none());
+        assert!(shrink_thumbnail(b"not an image", 150).is_none());
+
+        let small = shrink_thumbnail(&encode(1200, 800), 150).unwrap();
+        let image = image::load_from_memory(&small).unwrap();
+        // Still covers the square tile
+        assert_eq!((image.width(), image.height()), (225, 150));
+    }
+
+    #[test]
+    fn test_heic_without_image_data_fails() {
+        // Unsupported without the `heic` feature, undecodable with it; either way the loader falls back
+        assert!(normalize(HEIC.to_vec()).is_err());
+    }
+}
@@ -1,36 +1,270 @@
 //! Image loading and caching functionality for GooglePicz UI.
 
 use api_client;
+use crate::disk_cache::{DiskCache, ReadGuard};
+use crate::handle_cache::{HandleCache, ImageKey, ImageKind};
+use crate::image_format::{self, FormatError};
+use crate::load_scheduler::{CancelToken, LoadScheduler, Priority, Slot};
+use futures::future::{BoxFuture, FutureExt, Shared};
 use iced::widget::image::Handle;
 use reqwest;
-use std::path::PathBuf;
-use std::sync::Arc;
-use tokio::sync::Semaphore;
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 use tracing::Instrument;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use thiserror::Error;
 use tokio::fs;
+use tokio::sync::mpsc;
 
-#[derive(Debug, Error, PartialEq)]
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
 pub enum ImageLoaderError {
     #[error("network error: {0}")]
     Network(String),
+    /// The server answered with an unexpected status code.
+    #[error("HTTP {0}")]
+    Status(u16),
     #[error("timeout")]
     Timeout,
     #[error("not found")]
     NotFound,
+    /// The base URL is no longer valid; a fresh one comes from `batchGet`.
+    #[error("base URL expired")]
+    Expired,
     #[error("io error: {0}")]
     Io(String),
     #[error("semaphore closed")]
     SemaphoreClosed,
+    #[error("decode error: {0}")]
+    Decode(String),
+    /// The request was cancelled before a worker picked it up.
+    #[error("cancelled")]
+    Cancelled,
+}
+
+impl ImageLoaderError {
+    /// Whether trying the same request again later may succeed.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            ImageLoaderError::Network(_) | ImageLoaderError::Timeout => true,
+            ImageLoaderError::Status(code) => *code >= 500 || *code == 429,
+            _ => false,
+        }
+    }
+
+    /// Whether the image can never be shown, so offering a retry is pointless.
+    pub fn is_permanent(&self) -> bool {
+        matches!(self, ImageLoaderError::NotFound | ImageLoaderError::Decode(_))
+    }
+}
+
+/// Attempts per download when it fails with a transient error.
+const DOWNLOAD_ATTEMPTS: u32 = 3;
+/// Wait before the first retry; doubled for each further one.
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(250);
+
+/// Delay before retry number `attempt` (1-based), with up to 50% jitter so
+/// tiles that failed together do not hit the server together again.
+fn retry_delay(attempt: u32) -> Duration {
+    let base = RETRY_BASE_DELAY * 2u32.pow(attempt - 1);
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or(0);
+    base + base.mul_f64(f64::from(nanos % 1000) / 2000.0)
+}
+
+/// Longest edge requested when the original cannot be decoded locally.
+const TRANSCODED_SIZE: u32 = 4096;
+
+/// Edge length of the square face crops shown in the people panel.
+const FACE_CROP_SIZE: u32 = 96;
+
+/// Space added on each side of a face by `export_face_crops`, relative to its size.
+const EXPORT_CROP_MARGIN: f32 = 0.2;
+
+/// Thumbnail sizes requested from the API; tiles use the smallest one at
+/// least as large as themselves so they are never upscaled.
+pub const THUMBNAIL_TIERS: [u32; 3] = [150, 240, 320];
+
+/// Thumbnail size to request for tiles of `tile` pixels.
+pub fn thumbnail_tier(tile: f32) -> u32 {
+    THUMBNAIL_TIERS
+        .into_iter()
+        .find(|t| *t as f32 >= tile)
+        .unwrap_or(THUMBNAIL_TIERS[THUMBNAIL_TIERS.len() - 1])
+}
+
+/// What `ImageLoader::prefetch` downloads for each item.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Variant {
+    /// Square thumbnail of this many pixels
+    Thumbnail(u32),
+    Full,
+}
+
+/// Sent by `ImageLoader::prefetch` after every item.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PrefetchProgress {
+    pub done: usize,
+    pub total: usize,
+}
+
+/// Outcome of an `ImageLoader::prefetch` batch.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PrefetchSummary {
+    pub downloaded: usize,
+    /// Already on disk
+    pub skipped: usize,
+    pub failed: usize,
+}
+
+impl PrefetchSummary {
+    pub fn done(&self) -> usize {
+        self.downloaded + self.skipped + self.failed
+    }
+}
+
+/// Outcome of `ImageLoader::export_face_crops`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FaceCropSummary {
+    pub exported: usize,
+    /// Faces whose photo could not be fetched or cropped
+    pub skipped: usize,
+}
+
+impl FaceCropSummary {
+    pub fn done(&self) -> usize {
+        self.exported + self.skipped
+    }
+}
+
+/// A refreshed URL that is rejected again is not refreshed again within this time.
+const REFRESH_COOLDOWN: Duration = Duration::from_secs(300);
+
+type SharedLoad = Shared<BoxFuture<'static, Result<Handle, ImageLoaderError>>>;
+
+type RefreshFn = dyn Fn(Vec<String>) -> BoxFuture<'static, Result<Vec<api_client::MediaItem>, String>> + Send + Sync;
+
+/// Looks up the current media items for ids whose base URLs expired.
+#[derive(Clone)]
+pub struct UrlRefresher(Arc<RefreshFn>);
+
+impl UrlRefresher {
+    pub fn new<F, Fut>(refresh: F) -> Self
+    where
+        F: Fn(Vec<String>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<Vec<api_client::MediaItem>, String>> + Send + 'static,
+    {
+        Self(Arc::new(move |ids| refresh(ids).boxed()))
+    }
+}
+
+impl std::fmt::Debug for UrlRefresher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("UrlRefresher")
+    }
+}
+
+/// Convert WebP or HEIC bytes to JPEG, off the async workers when there is work to do.
+async fn normalize(bytes: Vec<u8>) -> Result<Vec<u8>, FormatError> {
+    if image_format::is_displayable(&bytes) {
+        return Ok(bytes);
+    }
+    tokio::task::spawn_blocking(move || image_format::normalize(bytes))
+        .await
+        .map_err(|e| FormatError::Decode(e.to_string()))?
+}
+
+/// `normalize` for thumbnails, which are also scaled down when the API sent
+/// one far larger than the `size` pixel tile it was requested for.
+async fn normalize_thumbnail(media_id: &str, bytes: Vec<u8>, size: u32) -> Result<Vec<u8>, FormatError> {
+    let original = bytes.len();
+    let bytes = tokio::task::spawn_blocking(move || {
+        let bytes = image_format::normalize(bytes)?;
+        Ok(image_format::shrink_thumbnail(&bytes, size).unwrap_or(bytes))
+    })
+    .await
+    .map_err(|e| FormatError::Decode(e.to_string()))??;
+    if bytes.len() < original {
+        tracing::info!(id = media_id, original, stored = bytes.len(), "Downscaled oversized thumbnail");
+    }
+    Ok(bytes)
+}
+
+/// `bbox` (x, y, width, height) cut out of `img`, clamped to the image.
+fn crop_bbox(img: &image::DynamicImage, bbox: [i32; 4]) -> Result<image::DynamicImage, ImageLoaderError> {
+    let x = (bbox[0].max(0) as u32).min(img.width());
+    let y = (bbox[1].max(0) as u32).min(img.height());
+    let w = (bbox[2].max(0) as u32).min(img.width() - x);
+    let h = (bbox[3].max(0) as u32).min(img.height() - y);
+    if w == 0 || h == 0 {
+        return Err(ImageLoaderError::Decode("face lies outside the image".into()));
+    }
+    Ok(img.crop_imm(x, y, w, h))
+}
+
+/// Cut `bbox` out of `source` and save it to `target` at people panel size.
+fn crop_face(source: &Path, target: &Path, bbox: [i32; 4]) -> Result<(), ImageLoaderError> {
+    let img = image::open(source).map_err(|e| ImageLoaderError::Decode(e.to_string()))?;
+    let face = crop_bbox(&img, bbox)?.thumbnail(FACE_CROP_SIZE, FACE_CROP_SIZE);
+    if let Some(parent) = target.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| ImageLoaderError::Io(e.to_string()))?;
+    }
+    face.save(target).map_err(|e| ImageLoaderError::Io(e.to_string()))
+}
+
+/// Save each `(bbox, target)` of `source` at full resolution with
+/// `EXPORT_CROP_MARGIN` around it, decoding the image once.
+fn export_crops(source: &Path, crops: &[([i32; 4], PathBuf)]) -> Vec<Result<(), ImageLoaderError>> {
+    let img = match image::open(source) {
+        Ok(img) => img,
+        Err(e) => return crops.iter().map(|_| Err(ImageLoaderError::Decode(e.to_string()))).collect(),
+    };
+    crops
+        .iter()
+        .map(|([x, y, w, h], target)| {
+            let dx = (*w as f32 * EXPORT_CROP_MARGIN) as i32;
+            let dy = (*h as f32 * EXPORT_CROP_MARGIN) as i32;
+            let face = crop_bbox(&img, [x - dx, y - dy, w + 2 * dx, h + 2 * dy])?;
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent).map_err(|e| ImageLoaderError::Io(e.to_string()))?;
+            }
+            // JPEG has no alpha channel
+            face.to_rgb8().save(target).map_err(|e| ImageLoaderError::Io(e.to_string()))
+        })
+        .collect()
+}
+
+/// Folder name for a person in `export_face_crops`; unnamed faces go to `unnamed`.
+fn person_folder(name: Option<&str>) -> String {
+    let folder: String = name
+        .unwrap_or("unnamed")
+        .chars()
+        .map(|c| if c.is_alphanumeric() || matches!(c, ' ' | '-' | '_') { c } else { '_' })
+        .collect();
+    match folder.trim() {
+        "" => "_".into(),
+        trimmed => trimmed.to_string(),
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct ImageLoader {
     cache_dir: PathBuf,
     client: reqwest::Client,
-    semaphore: Arc<Semaphore>,
+    scheduler: Arc<LoadScheduler>,
     threads: usize,
+    disk: Arc<DiskCache>,
+    memory: HandleCache,
+    /// Downloads in progress, awaited by every caller asking for the same image
+    in_flight: Arc<Mutex<HashMap<ImageKey, SharedLoad>>>,
+    refresher: Option<UrlRefresher>,
+    /// Base URLs fetched after the caller's copy expired, with when they were fetched
+    fresh_urls: Arc<Mutex<HashMap<String, (String, Instant)>>>,
+    /// Take a slot of `api_client::rate_limit` before every download
+    rate_limited: bool,
 }
 
 impl ImageLoader {
@@ -44,13 +278,227 @@ impl ImageLoader {
 
     pub fn with_client(cache_dir: PathBuf, client: reqwest::Client, threads: usize) -> Self {
         Self {
+            disk: Arc::new(DiskCache::open(&cache_dir, None)),
+            memory: HandleCache::default(),
+            in_flight: Arc::default(),
+            refresher: None,
+            fresh_urls: Arc::default(),
             cache_dir,
             client,
-            semaphore: Arc::new(Semaphore::new(threads)),
+            scheduler: Arc::new(LoadScheduler::new(threads)),
             threads,
+            rate_limited: false,
         }
     }
 
+    /// Keep downloads, retries included, within the API rate limit shared by
+    /// the whole process. Meant for bulk jobs; the grid loads unthrottled.
+    pub fn with_rate_limit(mut self) -> Self {
+        self.rate_limited = true;
+        self
+    }
+
+    /// Run up to `threads` downloads at once, also for the clones sharing
+    /// this loader's worker slots.
+    pub fn set_threads(&mut self, threads: usize) {
+        self.threads = threads.max(1);
+        self.scheduler.set_workers(self.threads);
+    }
+
+    /// Cap the images kept on disk at `max_mb` megabytes; 0 keeps everything.
+    pub fn with_cache_limit(self, max_mb: u64) -> Self {
+        self.set_cache_limit(max_mb);
+        self
+    }
+
+    /// Change the disk cap, evicting right away if the cache is already over it.
+    pub fn set_cache_limit(&self, max_mb: u64) {
+        let max_bytes = (max_mb > 0).then(|| max_mb.saturating_mul(1024 * 1024));
+        self.disk.set_max_bytes(max_bytes);
+    }
+
+    /// Keep at most `thumbnails` thumbnails and `full_images` full images in memory.
+    pub fn with_memory_limits(self, thumbnails: usize, full_images: usize) -> Self {
+        self.memory.set_limits(thumbnails, full_images);
+        self
+    }
+
+    /// The in-memory handles this loader fills, for drawing without going through the loader.
+    pub fn handles(&self) -> HandleCache {
+        self.memory.clone()
+    }
+
+    /// Fetch a fresh base URL and retry once when a download reports it expired.
+    pub fn with_url_refresher(mut self, refresher: UrlRefresher) -> Self {
+        self.refresher = Some(refresher);
+        self
+    }
+
+    /// Set the refresher after construction, e.g. once the cache is open.
+    pub fn set_url_refresher(&mut self, refresher: UrlRefresher) {
+        self.refresher = Some(refresher);
+    }
+
+    /// Bytes and number of images cached on disk.
+    pub fn cache_usage(&self) -> (u64, usize) {
+        self.disk.usage()
+    }
+
+    /// Delete all cached images except those being read right now.
+    pub async fn clear_cache(&self) -> Result<(), ImageLoaderError> {
+        self.memory.clear();
+        let disk = self.disk.clone();
+        tokio::task::spawn_blocking(move || disk.clear())
+            .await
+            .map_err(|e| ImageLoaderError::Io(e.to_string()))?
+            .map_err(|e| ImageLoaderError::Io(e.to_string()))
+    }
+
+    /// Drop every cached rendition of `media_id`, in memory and on disk, so
+    /// the next request downloads it again.
+    pub async fn invalidate(&self, media_id: &str) -> Result<(), ImageLoaderError> {
+        self.memory.invalidate(media_id);
+        let mut paths: Vec<PathBuf> = THUMBNAIL_TIERS.iter().map(|size| self.thumbnail_path(media_id, *size)).collect();
+        paths.push(self.full_path(media_id));
+        let prefix = format!("{}_", media_id);
+        if let Ok(mut faces) = fs::read_dir(self.cache_dir.join("faces")).await {
+            while let Ok(Some(entry)) = faces.next_entry().await {
+                if entry.file_name().to_string_lossy().starts_with(&prefix) {
+                    paths.push(entry.path());
+                }
+            }
+        }
+        let disk = self.disk.clone();
+        tokio::task::spawn_blocking(move || paths.iter().try_for_each(|path| disk.remove(path)))
+            .await
+            .map_err(|e| ImageLoaderError::Io(e.to_string()))?
+            .map_err(|e| ImageLoaderError::Io(e.to_string()))
+    }
+
+    /// Serve `key` from memory, or join the load already running for it, or
+    /// start one with `load`. Concurrent callers share a single download.
+    async fn load_once<F, Fut>(&self, key: ImageKey, cancel: &CancelToken, load: F) -> Result<Handle, ImageLoaderError>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<Handle, ImageLoaderError>> + Send + 'static,
+    {
+        loop {
+            if let Some(handle) = self.memory.get(&key) {
+                return Ok(handle);
+            }
+            let shared = {
+                let mut in_flight = self.in_flight.lock().unwrap_or_else(|e| e.into_inner());
+                in_flight
+                    .entry(key.clone())
+                    .or_insert_with(|| load().boxed().shared())
+                    .clone()
+            };
+            let result = shared.clone().await;
+            if let Ok(handle) = &result {
+                self.memory.insert(key.clone(), handle.clone());
+            }
+            {
+                let mut in_flight = self.in_flight.lock().unwrap_or_else(|e| e.into_inner());
+                // A later load may have replaced ours after a failure
+                if in_flight.get(&key).is_some_and(|current| current.ptr_eq(&shared)) {
+                    in_flight.remove(&key);
+                }
+            }
+            match result {
+                // Whoever started the load gave up on it, but this caller did not
+                Err(ImageLoaderError::Cancelled) if !cancel.is_cancelled() => continue,
+                result => return result,
+            }
+        }
+    }
+
+    /// Wait for a worker slot at `priority`.
+    async fn slot(&self, priority: Priority, cancel: &CancelToken) -> Result<Slot, ImageLoaderError> {
+        self.scheduler
+            .acquire(priority, cancel)
+            .await
+            .ok_or(ImageLoaderError::Cancelled)
+    }
+
+    fn fresh_urls(&self) -> std::sync::MutexGuard<'_, HashMap<String, (String, Instant)>> {
+        self.fresh_urls.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Run `load` with the newest known base URL of `media_id`. If that URL
+    /// has expired, fetch a fresh one and run `load` once more.
+    async fn with_fresh_url<F, Fut>(&self, media_id: &str, base_url: &str, load: F) -> Result<Handle, ImageLoaderError>
+    where
+        F: Fn(String) -> Fut,
+        Fut: Future<Output = Result<Handle, ImageLoaderError>>,
+    {
+        let known = self.fresh_urls().get(media_id).map(|(url, _)| url.clone());
+        let base_url = known.unwrap_or_else(|| base_url.to_string());
+        match load(base_url.clone()).await {
+            Err(ImageLoaderError::Expired) => match self.refresh_url(media_id, &base_url).await? {
+                Some(fresh) => load(fresh).await,
+                None => Err(ImageLoaderError::Expired),
+            },
+            result => result,
+        }
+    }
+
+    /// A base URL for `media_id` newer than `expired`, or `None` if there is
+    /// no refresher, the lookup failed, or `expired` was itself just refreshed.
+    async fn refresh_url(&self, media_id: &str, expired: &str) -> Result<Option<String>, ImageLoaderError> {
+        let Some(refresher) = &self.refresher else {
+            return Ok(None);
+        };
+        let known = self.fresh_urls().get(media_id).cloned();
+        if let Some((url, fetched)) = known {
+            if url != expired {
+                return Ok(Some(url));
+            }
+            if fetched.elapsed() < REFRESH_COOLDOWN {
+                return Ok(None);
+            }
+        }
+        let items = match (refresher.0)(vec![media_id.to_string()]).await {
+            Ok(items) => items,
+            Err(e) => {
+                tracing::warn!(id = media_id, error = %e, "Failed to refresh base URL");
+                return Ok(None);
+            }
+        };
+        // Missing from batchGet means the item is gone
+        let item = items
+            .into_iter()
+            .find(|i| i.id == media_id)
+            .ok_or(ImageLoaderError::NotFound)?;
+        tracing::info!(id = media_id, "Refreshed expired base URL");
+        self.fresh_urls()
+            .insert(media_id.to_string(), (item.base_url.clone(), Instant::now()));
+        Ok(Some(item.base_url))
+    }
+
+    /// Read a cached file into memory, or `None` if it is not cached. The file
+    /// cannot be evicted while it is read, and the handle no longer needs it.
+    async fn read_cached(&self, path: &Path) -> Option<Handle> {
+        if !path.exists() {
+            return None;
+        }
+        let _guard = self.disk.read(path);
+        fs::read(path).await.ok().map(Handle::from_memory)
+    }
+
+    /// Write `bytes` to the cache and evict older files if that exceeds the cap.
+    async fn write_cached(&self, path: &Path, bytes: &[u8]) -> Result<(), ImageLoaderError> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .await
+                .map_err(|e| ImageLoaderError::Io(e.to_string()))?;
+        }
+        fs::write(path, bytes)
+            .await
+            .map_err(|e| ImageLoaderError::Io(e.to_string()))?;
+        self.disk.insert(path, bytes.len() as u64);
+        Ok(())
+    }
+
     /// Return path to the cache directory used by this loader
     pub fn cache_dir(&self) -> PathBuf {
         self.cache_dir.clone()
@@ -61,78 +509,109 @@ impl ImageLoader {
         &self,
         media_id: &str,
         base_url: &str,
+    ) -> Result<Handle, ImageLoaderError> {
+        self.load_thumbnail_sized(media_id, base_url, THUMBNAIL_TIERS[0], false).await
+    }
+
+    /// Square thumbnail of `size` pixels; videos get their poster frame
+    /// without the play button Google draws on top, the grid adds its own badge.
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
+    pub async fn load_thumbnail_sized(
+        &self,
+        media_id: &str,
+        base_url: &str,
+        size: u32,
+        video: bool,
+    ) -> Result<Handle, ImageLoaderError> {
+        self.load_thumbnail_with(media_id, base_url, size, video, Priority::Visible, &CancelToken::default())
+            .await
+    }
+
+    /// `load_thumbnail_sized` queued at `priority`. Cancelling `cancel` drops
+    /// the request if it is still waiting for a worker.
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self, cancel)))]
+    pub async fn load_thumbnail_with(
+        &self,
+        media_id: &str,
+        base_url: &str,
+        size: u32,
+        video: bool,
+        priority: Priority,
+        cancel: &CancelToken,
+    ) -> Result<Handle, ImageLoaderError> {
+        let suffix = if video { "-no" } else { "" };
+        self.load_once(ImageKey::thumbnail(media_id, size), cancel, || {
+            let loader = self.clone();
+            let (id, base_url, cancel) = (media_id.to_string(), base_url.to_string(), cancel.clone());
+            async move {
+                let (loader, id, cancel) = (&loader, &id, &cancel);
+                loader
+                    .with_fresh_url(id, &base_url, |base_url| async move {
+                        let thumbnail_url = format!("{}=w{}-h{}-c{}", base_url, size, size, suffix);
+                        loader.fetch_thumbnail(id, &thumbnail_url, size, priority, cancel).await
+                    })
+                    .await
+            }
+        })
+        .await
+    }
+
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
+    pub async fn load_video_thumbnail(
+        &self,
+        media_id: &str,
+        base_url: &str,
+    ) -> Result<Handle, ImageLoaderError> {
+        self.load_thumbnail_sized(media_id, base_url, THUMBNAIL_TIERS[0], true).await
+    }
+
+    /// Replace the cached smallest thumbnail with `bytes`, e.g. a frame grabbed from a video.
+    pub async fn store_thumbnail(&self, media_id: &str, bytes: Vec<u8>) -> Result<Handle, ImageLoaderError> {
+        let cache_path = self.thumbnail_path(media_id, THUMBNAIL_TIERS[0]);
+        self.write_cached(&cache_path, &bytes).await?;
+        // A path handle could be served from the image cache's copy of the old file
+        let handle = Handle::from_memory(bytes);
+        self.memory.insert(ImageKey::thumbnail(media_id, THUMBNAIL_TIERS[0]), handle.clone());
+        Ok(handle)
+    }
+
+    /// The smallest tier keeps the original flat layout so existing caches stay valid.
+    fn thumbnail_path(&self, media_id: &str, size: u32) -> PathBuf {
+        let dir = self.cache_dir.join("thumbnails");
+        let dir = if size == THUMBNAIL_TIERS[0] { dir } else { dir.join(size.to_string()) };
+        dir.join(format!("{}.jpg", media_id))
+    }
+
+    async fn fetch_thumbnail(
+        &self,
+        media_id: &str,
+        thumbnail_url: &str,
+        size: u32,
+        priority: Priority,
+        cancel: &CancelToken,
     ) -> Result<Handle, ImageLoaderError> {
         #[cfg(feature = "trace-spans")]
         let span = tracing::info_span!("load_thumbnail", id = %media_id);
         #[cfg(feature = "trace-spans")]
         let _enter = span.enter();
         let start = Instant::now();
-        let _permit = self
-            .semaphore
-            .acquire()
-            .await
-            .map_err(|_| ImageLoaderError::SemaphoreClosed)?;
-        // Create thumbnail URL (150x150 pixels)
-        let thumbnail_url = format!("{}=w150-h150-c", base_url);
-
-        // Check if cached on disk
-        let cache_path = self
-            .cache_dir
-            .join("thumbnails")
-            .join(format!("{}.jpg", media_id));
-
-        if cache_path.exists() {
-            let handle = Handle::from_path(&cache_path);
+        // Check if cached on disk; that needs no worker
+        let cache_path = self.thumbnail_path(media_id, size);
+
+        if let Some(handle) = self.read_cached(&cache_path).await {
             return Ok(handle);
         }
+        let _slot = self.slot(priority, cancel).await?;
 
         // Download thumbnail
-        let response = self
-            .client
-            .get(&thumbnail_url)
-            .send()
-            .await
-            .map_err(|e| {
-                if e.is_timeout() {
-                    ImageLoaderError::Timeout
-                } else {
-                    ImageLoaderError::Network(e.to_string())
-                }
-            })?;
-        if response.status() == reqwest::StatusCode::NOT_FOUND {
-            return Err(ImageLoaderError::NotFound);
-        }
-        if !response.status().is_success() {
-            return Err(ImageLoaderError::Network(format!(
-                "HTTP {}",
-                response.status()
-            )));
-        }
-        let bytes = response
-            .bytes()
+        let bytes = self.download(thumbnail_url).await?;
+        let bytes = normalize_thumbnail(media_id, bytes, size)
             .await
-            .map_err(|e| {
-                if e.is_timeout() {
-                    ImageLoaderError::Timeout
-                } else {
-                    ImageLoaderError::Network(e.to_string())
-                }
-            })?;
-
-        // Ensure cache directory exists
-        if let Some(parent) = cache_path.parent() {
-            fs::create_dir_all(parent)
-                .await
-                .map_err(|e| ImageLoaderError::Io(e.to_string()))?;
-        }
+            .map_err(|e| ImageLoaderError::Decode(e.to_string()))?;
 
-        // Save to cache
-        fs::write(&cache_path, &bytes)
-            .await
-            .map_err(|e| ImageLoaderError::Io(e.to_string()))?;
-
-        // Create handle
-        let handle = Handle::from_path(&cache_path);
+        // Save to cache; the handle keeps its own copy in case the file is evicted
+        self.write_cached(&cache_path, &bytes).await?;
+        let handle = Handle::from_memory(bytes);
 
         tracing::info!("thumbnail_time_ms" = %start.elapsed().as_millis(), "id" = media_id);
         Ok(handle)
@@ -143,30 +622,127 @@ impl ImageLoader {
         &self,
         media_id: &str,
         base_url: &str,
+    ) -> Result<Handle, ImageLoaderError> {
+        self.load_full_image_with(media_id, base_url, Priority::Visible, &CancelToken::default())
+            .await
+    }
+
+    /// `load_full_image` queued at `priority`, dropped if `cancel` fires while waiting.
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self, cancel)))]
+    pub async fn load_full_image_with(
+        &self,
+        media_id: &str,
+        base_url: &str,
+        priority: Priority,
+        cancel: &CancelToken,
+    ) -> Result<Handle, ImageLoaderError> {
+        self.load_once(ImageKey::full(media_id), cancel, || {
+            let loader = self.clone();
+            let (id, base_url, cancel) = (media_id.to_string(), base_url.to_string(), cancel.clone());
+            async move {
+                let (loader, id, cancel) = (&loader, &id, &cancel);
+                loader
+                    .with_fresh_url(id, &base_url, |base_url| async move {
+                        loader.download_full_image(id, &base_url, priority, cancel).await
+                    })
+                    .await
+            }
+        })
+        .await
+    }
+
+    fn full_path(&self, media_id: &str) -> PathBuf {
+        self.cache_dir.join("full").join(format!("{}.jpg", media_id))
+    }
+
+    /// The cached original of `media_id` on disk, downloaded first if
+    /// missing, for tools that read files. It is not evicted while the
+    /// returned guard lives.
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
+    pub async fn full_image_file(
+        &self,
+        media_id: &str,
+        base_url: &str,
+    ) -> Result<(PathBuf, ReadGuard), ImageLoaderError> {
+        let path = self.full_path(media_id);
+        let guard = self.disk.read(&path);
+        // A handle in memory does not mean the file is still on disk
+        if !path.exists() {
+            let cancel = CancelToken::default();
+            let (loader, cancel) = (self, &cancel);
+            self.with_fresh_url(media_id, base_url, |base_url| async move {
+                loader.download_full_image(media_id, &base_url, Priority::Visible, cancel).await
+            })
+            .await?;
+        }
+        Ok((path, guard))
+    }
+
+    async fn download_full_image(
+        &self,
+        media_id: &str,
+        base_url: &str,
+        priority: Priority,
+        cancel: &CancelToken,
     ) -> Result<Handle, ImageLoaderError> {
         #[cfg(feature = "trace-spans")]
         let span = tracing::info_span!("load_full_image", id = %media_id);
         #[cfg(feature = "trace-spans")]
         let _enter = span.enter();
         let start = Instant::now();
-        let _permit = self
-            .semaphore
-            .acquire()
-            .await
-            .map_err(|_| ImageLoaderError::SemaphoreClosed)?;
         let full_url = format!("{}=d", base_url);
-        let cache_path = self
-            .cache_dir
-            .join("full")
-            .join(format!("{}.jpg", media_id));
+        let cache_path = self.full_path(media_id);
 
-        if cache_path.exists() {
-            return Ok(Handle::from_path(&cache_path));
+        if let Some(handle) = self.read_cached(&cache_path).await {
+            return Ok(handle);
         }
+        let _slot = self.slot(priority, cancel).await?;
+
+        let original = self.download(&full_url).await?;
+        let bytes = match normalize(original).await {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                // Sized variants always come back as JPEG
+                tracing::info!(id = media_id, error = %e, "Falling back to a transcoded full image");
+                let url = format!("{}=w{}-h{}", base_url, TRANSCODED_SIZE, TRANSCODED_SIZE);
+                let bytes = self.download(&url).await?;
+                normalize(bytes)
+                    .await
+                    .map_err(|e| ImageLoaderError::Decode(e.to_string()))?
+            }
+        };
+
+        self.write_cached(&cache_path, &bytes).await?;
+
+        tracing::info!("full_image_time_ms" = %start.elapsed().as_millis(), "id" = media_id);
+        Ok(Handle::from_memory(bytes))
+    }
 
+    /// GET `url`, retrying timeouts, dropped connections and server errors
+    /// with backoff. 404 and 403 are returned right away.
+    async fn download(&self, url: &str) -> Result<Vec<u8>, ImageLoaderError> {
+        let mut attempt = 1;
+        loop {
+            if self.rate_limited {
+                api_client::rate_limit().await;
+            }
+            match self.download_once(url).await {
+                Err(e) if e.is_transient() && attempt < DOWNLOAD_ATTEMPTS => {
+                    let delay = retry_delay(attempt);
+                    tracing::warn!(error = %e, attempt, delay_ms = %delay.as_millis(), "Retrying image download");
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// A single GET of `url`, mapping Google's status codes to loader errors.
+    async fn download_once(&self, url: &str) -> Result<Vec<u8>, ImageLoaderError> {
         let response = self
             .client
-            .get(&full_url)
+            .get(url)
             .send()
             .await
             .map_err(|e| {
@@ -179,11 +755,11 @@ impl ImageLoader {
         if response.status() == reqwest::StatusCode::NOT_FOUND {
             return Err(ImageLoaderError::NotFound);
         }
+        if response.status() == reqwest::StatusCode::FORBIDDEN {
+            return Err(ImageLoaderError::Expired);
+        }
         if !response.status().is_success() {
-            return Err(ImageLoaderError::Network(format!(
-                "HTTP {}",
-                response.status()
-            )));
+            return Err(ImageLoaderError::Status(response.status().as_u16()));
         }
         let bytes = response
             .bytes()
@@ -195,24 +771,127 @@ impl ImageLoader {
                     ImageLoaderError::Network(e.to_string())
                 }
             })?;
+        Ok(bytes.to_vec())
+    }
 
-        if let Some(parent) = cache_path.parent() {
-            fs::create_dir_all(parent)
-                .await
-                .map_err(|e| ImageLoaderError::Io(e.to_string()))?;
-        }
+    /// Face crop from the cached original, which is downloaded first if missing.
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
+    pub async fn load_face_crop(
+        &self,
+        media_id: &str,
+        base_url: &str,
+        bbox: [i32; 4],
+    ) -> Result<Handle, ImageLoaderError> {
+        let crop_path = self.cache_dir.join("faces").join(format!(
+            "{}_{}_{}_{}_{}.png",
+            media_id, bbox[0], bbox[1], bbox[2], bbox[3]
+        ));
+        let key = ImageKey { media_id: media_id.to_string(), kind: ImageKind::Face(bbox) };
+        self.load_once(key, &CancelToken::default(), || {
+            let loader = self.clone();
+            let (id, url, target) = (media_id.to_string(), base_url.to_string(), crop_path.clone());
+            async move { loader.crop_cached(&id, &url, target, bbox).await }
+        })
+        .await
+    }
 
-        fs::write(&cache_path, &bytes)
+    async fn crop_cached(
+        &self,
+        media_id: &str,
+        base_url: &str,
+        crop_path: PathBuf,
+        bbox: [i32; 4],
+    ) -> Result<Handle, ImageLoaderError> {
+        if let Some(handle) = self.read_cached(&crop_path).await {
+            return Ok(handle);
+        }
+        // The guard keeps the original from being evicted while it is cropped
+        let (full_path, _guard) = self.full_image_file(media_id, base_url).await?;
+        let target = crop_path.clone();
+        tokio::task::spawn_blocking(move || crop_face(&full_path, &target, bbox))
+            .await
+            .map_err(|e| ImageLoaderError::Io(e.to_string()))??;
+        let bytes = fs::read(&crop_path)
             .await
             .map_err(|e| ImageLoaderError::Io(e.to_string()))?;
+        self.disk.insert(&crop_path, bytes.len() as u64);
+        Ok(Handle::from_memory(bytes))
+    }
 
-        tracing::info!("full_image_time_ms" = %start.elapsed().as_millis(), "id" = media_id);
-        Ok(Handle::from_path(&cache_path))
+    /// Write crops of the faces in the cache, or only those named `person`,
+    /// to `dir/<person>/<media id>_<face index>.jpg`. Originals are fetched
+    /// like `full_image_file`; faces of photos that cannot be fetched are
+    /// skipped. Ignored faces are left out.
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self, cache, progress)))]
+    pub async fn export_face_crops(
+        &self,
+        cache: &cache::CacheManager,
+        dir: &Path,
+        person: Option<&str>,
+        progress: Option<mpsc::Sender<PrefetchProgress>>,
+    ) -> Result<FaceCropSummary, ImageLoaderError> {
+        let (cache, wanted) = (cache.clone(), person.map(str::to_string));
+        let jobs = tokio::task::spawn_blocking(move || {
+            let mut jobs = Vec::new();
+            for entry in cache.get_all_faces()? {
+                let faces: Vec<(usize, cache::FaceData)> = entry
+                    .faces
+                    .into_iter()
+                    .enumerate()
+                    .filter(|(_, f)| !f.ignored && (wanted.is_none() || f.name == wanted))
+                    .collect();
+                if faces.is_empty() {
+                    continue;
+                }
+                if let Some(item) = cache.get_media_item(&entry.media_item_id)? {
+                    jobs.push((item, faces));
+                }
+            }
+            Ok::<_, cache::CacheError>(jobs)
+        })
+        .await
+        .map_err(|e| ImageLoaderError::Io(e.to_string()))?
+        .map_err(|e| ImageLoaderError::Io(e.to_string()))?;
+
+        let total = jobs.iter().map(|(_, faces)| faces.len()).sum();
+        let mut summary = FaceCropSummary::default();
+        for (item, faces) in jobs {
+            let crops: Vec<([i32; 4], PathBuf)> = faces
+                .iter()
+                .map(|(index, face)| {
+                    let name = format!("{}_{}.jpg", item.id, index);
+                    (face.bbox, dir.join(person_folder(face.name.as_deref())).join(name))
+                })
+                .collect();
+            let results = match self.full_image_file(&item.id, &item.base_url).await {
+                Ok((source, guard)) => tokio::task::spawn_blocking(move || {
+                    let _guard = guard;
+                    export_crops(&source, &crops)
+                })
+                .await
+                .map_err(|e| ImageLoaderError::Io(e.to_string()))?,
+                Err(e) => vec![Err(e); crops.len()],
+            };
+            for result in results {
+                match result {
+                    Ok(()) => summary.exported += 1,
+                    Err(e) => {
+                        summary.skipped += 1;
+                        tracing::warn!(id = %item.id, error = %e, "Skipped face crop");
+                    }
+                }
+            }
+            if let Some(tx) = &progress {
+                let _ = tx.send(PrefetchProgress { done: summary.done(), total }).await;
+            }
+        }
+        Ok(summary)
     }
 
+    /// Smallest thumbnail of `media_id` if it was loaded recently.
     #[allow(dead_code)]
-    pub fn get_cached_thumbnail(&self, _media_id: &str) -> Option<Handle> {
-        None // Since we are not caching in memory anymore
+    pub fn get_cached_thumbnail(&self, media_id: &str) -> Option<Handle> {
+        self.memory.get(&ImageKey::thumbnail(media_id, THUMBNAIL_TIERS[0]))
     }
 
     #[allow(dead_code)]
@@ -226,10 +905,21 @@ impl ImageLoader {
                 let loader = self.clone();
                 let id = item.id.clone();
                 let base = item.base_url.clone();
+                let video = item.mime_type.starts_with("video/");
                 tokio::spawn(async move {
                     let span = tracing::info_span!("preload_thumbnail", id = %id);
                     async move {
-                        if let Err(e) = loader.load_thumbnail(&id, &base).await {
+                        let result = loader
+                            .load_thumbnail_with(
+                                &id,
+                                &base,
+                                THUMBNAIL_TIERS[0],
+                                video,
+                                Priority::Background,
+                                &CancelToken::default(),
+                            )
+                            .await;
+                        if let Err(e) = result {
                             tracing::error!("Failed to preload thumbnail for {}: {}", id, e);
                         }
                     }
@@ -244,11 +934,102 @@ impl ImageLoader {
         }
         tracing::info!("preload_time_ms" = %start.elapsed().as_millis(), "count" = count);
     }
+
+    fn variant_path(&self, media_id: &str, variant: Variant) -> PathBuf {
+        match variant {
+            Variant::Thumbnail(size) => self.thumbnail_path(media_id, size),
+            Variant::Full => self.full_path(media_id),
+        }
+    }
+
+    /// Download `variant` of each `(media_id, base_url)` into the disk cache,
+    /// `concurrency` at a time and queued behind interactive loads. Images
+    /// already on disk are skipped. Nothing is kept in memory, so a large
+    /// batch does not push out what is on screen.
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self, items, progress)))]
+    pub async fn prefetch(
+        &self,
+        items: Vec<(String, String)>,
+        variant: Variant,
+        concurrency: usize,
+        progress: Option<mpsc::Sender<PrefetchProgress>>,
+    ) -> PrefetchSummary {
+        let start = Instant::now();
+        let total = items.len();
+        let mut summary = PrefetchSummary::default();
+        let report = |summary: &PrefetchSummary| {
+            let progress = progress.clone();
+            let update = PrefetchProgress { done: summary.done(), total };
+            async move {
+                if let Some(tx) = progress {
+                    // Nobody listening is fine, the batch still runs
+                    let _ = tx.send(update).await;
+                }
+            }
+        };
+        let mut queue = items.into_iter();
+        let mut tasks = tokio::task::JoinSet::new();
+        loop {
+            while tasks.len() < concurrency.max(1) {
+                let Some((id, base_url)) = queue.next() else { break };
+                if self.variant_path(&id, variant).exists() {
+                    summary.skipped += 1;
+                    report(&summary).await;
+                    continue;
+                }
+                let loader = self.clone();
+                tasks.spawn(async move {
+                    let result = loader.prefetch_one(&id, &base_url, variant).await;
+                    (id, result)
+                });
+            }
+            let Some(joined) = tasks.join_next().await else { break };
+            match joined {
+                Ok((_, Ok(()))) => summary.downloaded += 1,
+                Ok((id, Err(e))) => {
+                    summary.failed += 1;
+                    tracing::warn!(id = %id, error = %e, "Failed to prefetch image");
+                }
+                Err(e) => {
+                    summary.failed += 1;
+                    tracing::error!(error = %e, "Prefetch task failed");
+                }
+            }
+            report(&summary).await;
+        }
+        tracing::info!(
+            "prefetch_time_ms" = %start.elapsed().as_millis(),
+            downloaded = summary.downloaded,
+            skipped = summary.skipped,
+            failed = summary.failed
+        );
+        summary
+    }
+
+    /// Fetch one image for `prefetch`, bypassing the memory cache.
+    async fn prefetch_one(&self, media_id: &str, base_url: &str, variant: Variant) -> Result<(), ImageLoaderError> {
+        let cancel = CancelToken::default();
+        let cancel = &cancel;
+        self.with_fresh_url(media_id, base_url, |base_url| async move {
+            match variant {
+                Variant::Thumbnail(size) => {
+                    let url = format!("{}=w{}-h{}-c", base_url, size, size);
+                    self.fetch_thumbnail(media_id, &url, size, Priority::Background, cancel).await
+                }
+                Variant::Full => {
+                    self.download_full_image(media_id, &base_url, Priority::Background, cancel)
+                        .await
+                }
+            }
+        })
+        .await
+        .map(|_| ())
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::ImageLoader;
+    use super::{ImageLoader, ImageLoaderError};
     use httpmock::prelude::*;
     use tempfile::tempdir;
 
@@ -266,4 +1047,57 @@ mod tests {
         assert!(dir.path().join("thumbnails/1.jpg").exists());
         mock.assert();
     }
+
+    #[tokio::test]
+    async fn test_forbidden_thumbnail_reports_expired_url() {
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(GET).path("/old.jpg=w150-h150-c");
+            then.status(403);
+        });
+        let dir = tempdir().unwrap();
+        let loader = ImageLoader::new(dir.path().to_path_buf(), 4);
+        let url = format!("{}/old.jpg", server.url(""));
+        let err = loader.load_thumbnail("1", &url).await.unwrap_err();
+        assert!(matches!(err, ImageLoaderError::Expired));
+        assert!(!dir.path().join("thumbnails/1.jpg").exists());
+    }
+
+    #[tokio::test]
+    async fn test_video_thumbnail_variant_and_stored_frame() {
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(GET).path("/clip=w150-h150-c-no");
+            then.status(200).body("poster");
+        });
+        let dir = tempdir().unwrap();
+        let loader = ImageLoader::new(dir.path().to_path_buf(), 4);
+        let url = server.url("/clip");
+        loader.load_video_thumbnail("v", &url).await.unwrap();
+        mock.assert();
+
+        loader.store_thumbnail("v", b"frame".to_vec()).await.unwrap();
+        assert_eq!(std::fs::read(dir.path().join("thumbnails/v.jpg")).unwrap(), b"frame");
+        // Served from disk from now on
+        loader.load_video_thumbnail("v", &url).await.unwrap();
+        mock.assert_hits(1);
+    }
+
+    #[tokio::test]
+    async fn test_larger_tiles_request_larger_thumbnails() {
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(GET).path("/thumb.jpg=w240-h240-c");
+            then.status(200).body("big");
+        });
+        let dir = tempdir().unwrap();
+        let loader = ImageLoader::new(dir.path().to_path_buf(), 4);
+        let url = server.url("/thumb.jpg");
+        let size = super::thumbnail_tier(200.0);
+        loader.load_thumbnail_sized("1", &url, size, false).await.unwrap();
+        assert!(dir.path().join("thumbnails/240/1.jpg").exists());
+        mock.assert();
+        assert_eq!(super::thumbnail_tier(100.0), 150);
+        assert_eq!(super::thumbnail_tier(320.0), 320);
+    }
 }
@@ -0,0 +1,143 @@
+//! Side panel listing the metadata of the photo shown in the detail view.
+
+use api_client::MediaItem;
+use chrono::{DateTime, Local};
+use iced::widget::tooltip::Position;
+use iced::widget::{button, column, container, row, scrollable, text, text_input, tooltip};
+use iced::{Element, Length};
+
+use crate::i18n::tr;
+use crate::style::{self, Palette};
+use crate::{Icon, MaterialSymbol, Message};
+
+/// Rough on-disk size of an original, assuming ~10:1 JPEG compression.
+fn size_estimate(item: &MediaItem) -> Option<String> {
+    if item.mime_type.starts_with("video/") {
+        return None;
+    }
+    let w: u64 = item.media_metadata.width.parse().ok()?;
+    let h: u64 = item.media_metadata.height.parse().ok()?;
+    let bytes = w * h * 3 / 10;
+    Some(if bytes >= 1024 * 1024 {
+        format!("~{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+    } else {
+        format!("~{} KB", bytes / 1024)
+    })
+}
+
+fn local_time(rfc3339: &str) -> String {
+    DateTime::parse_from_rfc3339(rfc3339)
+        .map(|dt| dt.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_else(|_| rfc3339.to_string())
+}
+
+fn entry<'a>(label: &str, value: String) -> iced::Element<'a, Message> {
+    column![text(label.to_string()).size(12), text(value).size(14)].into()
+}
+
+fn description<'a>(ui: &crate::GooglePiczUI, photo: &MediaItem) -> iced::Element<'a, Message> {
+    if ui.editing_description {
+        return column![
+            text(tr("info.description")).size(12),
+            text_input(tr("info.add_description"), &ui.description_input)
+                .style(style::text_input())
+                .on_input(Message::DescriptionChanged)
+                .on_submit(Message::SaveDescription),
+            row![
+                button(Icon::new(MaterialSymbol::Save).color(Palette::ON_PRIMARY))
+                    .style(style::button_primary())
+                    .on_press(Message::SaveDescription),
+                button(Icon::new(MaterialSymbol::Cancel).color(Palette::ON_SECONDARY))
+                    .style(style::button_secondary())
+                    .on_press(Message::CancelEditDescription),
+            ]
+            .spacing(Palette::SPACING / 2),
+        ]
+        .spacing(Palette::SPACING / 2)
+        .into();
+    }
+    let edit = button(Icon::new(MaterialSymbol::Edit).color(Palette::ON_SECONDARY))
+        .style(style::button_secondary())
+        .on_press(Message::StartEditDescription);
+    // Description edits are queued offline, so the button stays enabled
+    let edit: Element<'a, Message> = if ui.offline() {
+        tooltip(edit, text(tr("offline.queued")).size(12), Position::Bottom)
+            .style(style::card())
+            .into()
+    } else {
+        edit.into()
+    };
+    row![
+        container(entry(tr("info.description"), photo.description.clone().unwrap_or_else(|| "-".into())))
+            .width(Length::Fill),
+        edit,
+    ]
+    .into()
+}
+
+pub fn view<'a>(
+    ui: &crate::GooglePiczUI,
+    photo: &MediaItem,
+    faces: &[face_recognition::Face],
+) -> Option<iced::Element<'a, Message>> {
+    if !ui.info_open {
+        return None;
+    }
+    let meta = &photo.media_metadata;
+    let video = meta.video.as_ref();
+    let camera = [
+        video.and_then(|v| v.camera_make.clone()),
+        video.and_then(|v| v.camera_model.clone()),
+    ]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>()
+    .join(" ");
+
+    let mut col = column![
+        text(tr("info.title")).size(16),
+        entry(tr("info.filename"), photo.filename.clone()),
+        description(ui, photo),
+        entry(tr("info.created"), local_time(&meta.creation_time)),
+        entry(tr("info.dimensions"), format!("{} × {}", meta.width, meta.height)),
+        entry(tr("info.type"), photo.mime_type.clone()),
+    ]
+    .spacing(Palette::SPACING / 2);
+    if !camera.is_empty() {
+        col = col.push(entry(tr("info.camera"), camera));
+    }
+    if photo.mime_type.starts_with("video/") {
+        if let Some(fps) = video.and_then(|v| v.fps) {
+            col = col.push(entry(tr("info.frame_rate"), format!("{:.0} fps", fps)));
+        }
+    }
+    if let Some(size) = size_estimate(photo) {
+        col = col.push(entry(tr("info.size"), size));
+    }
+    let albums = if ui.item_albums.is_empty() {
+        "-".to_string()
+    } else {
+        ui.item_albums
+            .iter()
+            .map(|a| a.title.clone().unwrap_or_else(|| tr("common.untitled").into()))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    col = col.push(entry(tr("info.albums"), albums));
+    let mut people: Vec<String> = faces.iter().filter_map(|f| f.name.clone()).collect();
+    people.sort();
+    people.dedup();
+    col = col.push(entry(
+        tr("info.people"),
+        if people.is_empty() { "-".into() } else { people.join(", ") },
+    ));
+
+    Some(
+        container(scrollable(col))
+            .style(style::card())
+            .padding(Palette::SPACING)
+            .width(Length::Fixed(260.0))
+            .height(Length::Fill)
+            .into(),
+    )
+}
@@ -3,8 +3,15 @@
 //! User Interface module for GooglePicz.
 
 mod image_loader;
+mod disk_cache;
+mod handle_cache;
+mod image_format;
+mod load_scheduler;
 mod video_downloader;
+mod video_cache;
+mod video_stream;
 #[path = "../../app/src/config.rs"]
+#[allow(dead_code)]
 mod app_config;
 mod style;
 mod icon;
@@ -12,20 +19,65 @@ mod search;
 mod album_dialogs;
 mod settings;
 mod face_recognizer;
+mod info_panel;
+mod timeline;
+mod i18n;
+mod notifications;
+mod people;
+mod drag;
+mod photo_view;
+mod exporter;
+mod ui_state;
+mod error_log;
+mod album_delete;
+mod album_sidebar;
+mod album_contents;
+mod sync_panel;
+mod context_menu;
+mod fullscreen;
+mod upload;
+mod preload;
+mod status_bar;
+mod accounts;
+mod trash;
+mod offline;
+mod startup;
+#[cfg(feature = "gstreamer")]
+mod video_thumbnail;
+#[cfg(feature = "clipboard")]
+mod clipboard;
 
 pub use icon::{Icon, MaterialSymbol};
-pub use search::SearchMode;
+pub use search::{ActiveFilters, FilterKind, SavedSearch, SearchMode};
+pub use timeline::{Granularity, ThumbnailState};
+pub use i18n::{keys as i18n_keys, translation, Language};
+pub use notifications::{Notification, NotificationAction, NotificationLevel};
 pub use album_dialogs::AlbumOption;
+pub use album_contents::refresh as refresh_album_contents;
 pub use face_recognizer::FaceRecognizer;
+pub use photo_view::{Zoom, ZOOM_STEP};
 
-pub use image_loader::{ImageLoader, ImageLoaderError};
-pub use video_downloader::{VideoDownloader, VideoDownloadError};
+pub use image_loader::{ImageLoader, ImageLoaderError, FaceCropSummary, PrefetchProgress, PrefetchSummary, UrlRefresher, Variant};
+pub use handle_cache::{HandleCache, HandleCacheStats, ImageKey};
+pub use image_format::shrink_thumbnail;
+pub use load_scheduler::{CancelToken, Priority};
+pub use video_downloader::{VideoDownloader, VideoDownloadError, VideoProgress};
+pub use video_cache::{CachedVideo, VideoCache};
+pub use video_stream::{BufferAction, StreamBuffer};
+pub use exporter::{ExportError, ExportJob, Exporter};
+pub use upload::{UploadEntry, UploadStatus};
+pub use status_bar::StatusSnapshot;
+pub use ui_state::{UiState, MAX_TILE_SIZE, MIN_TILE_SIZE};
+pub use settings::SettingsField;
+pub use accounts::{last_account, Account};
+pub use startup::{CacheStatus, OpenedCache};
 
 use api_client::{Album, ApiClient, MediaItem};
 use app_config::AppConfig;
 use auth;
 use cache::CacheManager;
 use google_material_symbols;
+use crate::i18n::{tr, trf};
 use crate::style::{self, Palette};
 use face_recognition;
 use chrono::{DateTime, Utc};
@@ -42,7 +94,7 @@ use iced::{event, keyboard, executor, Application, Command, Element, Length, Set
 use std::path::{Path, PathBuf};
 use std::io::Write;
 use std::sync::Arc;
-use sync::{SyncProgress, SyncTaskError};
+use sync::{SyncControl, SyncErrorCode, SyncProgress, SyncTaskError};
 use tokio::sync::mpsc;
 use tokio::sync::Mutex;
 use tokio::time::{sleep, Duration};
@@ -54,11 +106,23 @@ use gstreamer_iced::{GstreamerIcedBase, GStreamerMessage, PlayStatus};
 use gstreamer_iced::reexport::url;
 #[cfg(feature = "gstreamer")]
 use gstreamer as gst;
-#[cfg(feature = "gstreamer")]
-use tempfile::TempPath;
 
 const ERROR_DISPLAY_DURATION: Duration = Duration::from_secs(5);
 const PAGE_SIZE: usize = 40;
+/// Results fetched per search; "more results" raises the limit by this much.
+const SEARCH_LIMIT: usize = 500;
+/// Quiet period after the last keystroke before the search runs.
+const SEARCH_DEBOUNCE: Duration = Duration::from_millis(300);
+/// Button padding around a thumbnail plus the spacing to the next tile or row.
+const TILE_GAP: f32 = 20.0;
+/// Window width the grid loses to padding and the scrollbar.
+const GRID_MARGIN: f32 = 40.0;
+/// Rows below the viewport whose thumbnails are requested ahead of time.
+const LOOKAHEAD_ROWS: usize = 2;
+
+fn grid_scroll_id() -> scrollable::Id {
+    scrollable::Id::new("photo-grid")
+}
 
 fn error_container_style() -> iced::theme::Container {
     iced::theme::Container::Custom(Box::new(|_theme: &Theme| Appearance {
@@ -73,15 +137,43 @@ fn error_container_style() -> iced::theme::Container {
     }))
 }
 
-#[cfg_attr(feature = "trace-spans", tracing::instrument(skip(progress, errors)))]
-pub fn run(
-    progress: Option<mpsc::UnboundedReceiver<SyncProgress>>,
-    errors: Option<mpsc::UnboundedReceiver<SyncTaskError>>,
-    status: Option<mpsc::UnboundedReceiver<SyncTaskError>>,
-    preload: usize,
-    preload_threads: usize,
-    cache_dir: PathBuf,
-) -> iced::Result {
+/// What the UI is started with: the channels to the sync tasks, thumbnail
+/// preloading and the cache directory. Channels left out are not listened to.
+pub struct UiFlags {
+    pub progress: Option<mpsc::UnboundedReceiver<SyncProgress>>,
+    pub errors: Option<mpsc::UnboundedReceiver<SyncTaskError>>,
+    pub status: Option<mpsc::UnboundedReceiver<SyncTaskError>>,
+    pub control: Option<mpsc::UnboundedSender<SyncControl>>,
+    /// Requests from later instances to raise the window
+    pub focus: Option<mpsc::UnboundedReceiver<()>>,
+    pub preload: usize,
+    pub preload_threads: usize,
+    pub cache_dir: PathBuf,
+}
+
+impl UiFlags {
+    pub fn new(cache_dir: PathBuf) -> Self {
+        Self { cache_dir, ..Default::default() }
+    }
+}
+
+impl Default for UiFlags {
+    fn default() -> Self {
+        Self {
+            progress: None,
+            errors: None,
+            status: None,
+            control: None,
+            focus: None,
+            preload: 0,
+            preload_threads: 4,
+            cache_dir: PathBuf::new(),
+        }
+    }
+}
+
+#[cfg_attr(feature = "trace-spans", tracing::instrument(skip(flags)))]
+pub fn run(flags: UiFlags) -> iced::Result {
     use std::borrow::Cow;
     #[cfg(feature = "trace-spans")]
     let start = std::time::Instant::now();
@@ -91,8 +183,15 @@ pub fn run(
     sys.refresh_memory();
     #[cfg(feature = "trace-spans")]
     let mem_before = sys.used_memory();
-    let mut settings = Settings::with_flags((progress, errors, status, preload, preload_threads, cache_dir));
+    let saved = UiState::load(&flags.cache_dir.join(ui_state::FILE_NAME));
+    let mut settings = Settings::with_flags(flags);
     settings.fonts.push(Cow::Borrowed(google_material_symbols::FONT_BYTES));
+    settings.window.size = iced::Size::new(saved.width, saved.height);
+    if let Some((x, y)) = saved.position {
+        settings.window.position = iced::window::Position::Specific(iced::Point::new(x as f32, y as f32));
+    }
+    // Closing goes through `WindowCloseRequested` so the window state can be saved first
+    settings.window.exit_on_close_request = false;
     let res = GooglePiczUI::run(settings);
     #[cfg(feature = "trace-spans")]
     {
@@ -107,25 +206,84 @@ pub fn run(
 pub enum Message {
     LoadPhotos,
     PhotosLoaded(Result<Vec<MediaItem>, String>),
+    /// Album contents fetched from the API after the cached ones were shown
+    AlbumRefreshed(String, Result<Vec<MediaItem>, String>),
     LoadAlbums,
     AlbumsLoaded(Result<Vec<Album>, String>),
+    /// Albums read from the cache while offline
+    CachedAlbumsLoaded(Result<Vec<Album>, String>),
+    /// Schema migration `step` of `steps` is running on the cache being opened
+    CacheMigrating(usize, usize),
+    /// The cache at this path was opened in the background
+    CacheReady(PathBuf, Result<OpenedCache, String>),
     RefreshPhotos,
-    ThumbnailLoaded(String, Result<Handle, String>),
-    LoadThumbnail(String, String), // media_id, base_url
+    ThumbnailLoaded(String, Result<Handle, ImageLoaderError>),
+    VideoDurationsLoaded(Result<std::collections::HashMap<String, u64>, String>),
+    /// Poster frame and duration taken from a played video
+    #[cfg(feature = "gstreamer")]
+    VideoFrameGrabbed(String, Result<(Handle, u64), String>),
+    LoadThumbnail(String, String, bool, Priority), // media_id, base_url, is_video, priority
+    CancelThumbnailPreload,
+    RetryThumbnail(String),
+    /// Fresh base URLs for the requested ids, fetched after they expired
+    BaseUrlsRefreshed(Vec<String>, Result<Vec<MediaItem>, String>),
     LoadFullImage(String, String),
     FullImageLoaded(String, Result<Handle, String>),
     LoadFaces(String),
     FacesLoaded(String, Result<Vec<face_recognition::Face>, String>),
+    /// Run face detection on one photo now instead of during sync
+    DetectFaces(String),
+    FacesDetected(String, Result<Vec<face_recognition::Face>, String>),
     StartRenameFace(usize),
+    /// Hide a face of the open photo from people and clustering, or show it again
+    ToggleFaceIgnored(usize),
     FaceNameChanged(String),
     SaveFaceName,
+    /// Name stored for a face of the media item, with the cluster it belongs to
+    FaceNameSaved(String, String, Result<Option<cache::FaceCluster>, String>),
     CancelFaceName,
+    /// Give the name just saved to the rest of the face's cluster
+    NameCluster,
+    DismissClusterOffer,
     SelectPhoto(MediaItem),
     SelectAlbum(Option<String>),
+    /// Show the favorites pinned at the top of the album sidebar
+    SelectFavorites,
+    /// Show the trash in place of the grid
+    SelectTrash,
+    AlbumSidebarScrolled(scrollable::Viewport),
+    /// The pointer entered (`true`) or left the album sidebar
+    AlbumSidebarHovered(bool),
+    ScrollAlbumSidebar(album_sidebar::Scroll),
+    /// Open the "All albums…" menu
+    ShowAllAlbums,
+    AllAlbumsFilterChanged(String),
+    CloseAllAlbums,
+    TrashLoaded(Result<Vec<cache::TrashedMediaItem>, String>),
+    MoveToTrash(Vec<String>),
+    PhotosTrashed(Vec<String>, Result<(), String>),
+    RestoreFromTrash(Vec<String>),
+    TrashRestored(Vec<String>, Result<(), String>),
+    /// Remove trashed items from the cache for good
+    DeleteForever(Vec<String>),
+    TrashPurged(Result<u64, String>),
+    ToggleTrashSelection(String),
+    ShowEmptyTrashDialog,
+    ConfirmEmptyTrash,
+    CancelEmptyTrash,
+    AlbumCountsLoaded(Result<std::collections::HashMap<String, u64>, String>),
+    AlbumCoverLoaded(String, Result<Handle, String>),
     ClosePhoto,
     SyncProgress(SyncProgress),
     SyncError(SyncTaskError),
     SyncStatusUpdated(DateTime<Utc>, String),
+    /// Expand or collapse the sync activity panel
+    ToggleSyncPanel,
+    SyncNow,
+    PauseSync,
+    ResumeSync,
+    /// Once a second while a retry is pending, to count it down
+    SyncTick,
     DismissError(usize),
     ShowCreateAlbumDialog,
     AlbumTitleChanged(String),
@@ -136,6 +294,11 @@ pub enum Message {
     AlbumAssigned(Result<(), String>),
     RenameAlbum(String, String),
     DeleteAlbum(String),
+    AlbumDeleteStaged(String, Result<Vec<String>, String>),
+    UndoDeleteAlbum(String),
+    AlbumDeleteExpired(String),
+    AlbumRestored(Result<(), String>),
+    AlbumDeleted(Result<(), String>),
     ShowRenameAlbumDialog(String, String),
     RenameAlbumTitleChanged(String),
     ConfirmRenameAlbum,
@@ -152,7 +315,27 @@ pub enum Message {
     SearchEndChanged(String),
     SearchFavoriteToggled(bool),
     SearchFacesToggled(bool),
+    /// Person picked in the search bar; searches right away
+    SearchPersonSelected(String),
+    PersonOptionsLoaded(Result<Vec<cache::PersonSummary>, String>),
     PerformSearch,
+    /// Debounce timer for the search input fired for this generation
+    SearchDebounced(u64),
+    /// Search results for a generation plus the number of matches cut off by the limit
+    SearchResults(u64, Result<(Vec<MediaItem>, usize), String>),
+    LoadMoreResults,
+    /// Remove one constraint from the search and run it again
+    ClearFilter(FilterKind),
+    ClearAllFilters,
+    ToggleRecentSearches,
+    /// Fill the search inputs from a recent or saved search and run it
+    SelectSavedSearch(SavedSearch),
+    /// Start naming the recent search at this index to pin it
+    PinSearch(usize),
+    PinNameChanged(String),
+    ConfirmPinSearch,
+    DeleteRecentSearch(usize),
+    DeleteSavedSearch(usize),
     #[cfg(feature = "gstreamer")]
     PlayVideo(MediaItem),
     #[cfg(feature = "gstreamer")]
@@ -164,12 +347,54 @@ pub enum Message {
     #[cfg(feature = "gstreamer")]
     SeekVideo(f64),
     #[cfg(feature = "gstreamer")]
-    VideoDownloaded(tempfile::TempPath),
+    VideoDownloadProgress(String, VideoProgress),
     #[cfg(feature = "gstreamer")]
-    VideoDownloadFailed(String),
+    VideoDownloaded(String, CachedVideo),
+    #[cfg(feature = "gstreamer")]
+    VideoDownloadFailed(String, VideoDownloadError),
     ClearErrors,
+    DismissNotification(u64),
     ShowSettings,
+    /// Open the settings dialog with the account section expanded
+    ShowAccountSettings,
+    ToggleAccountSettings,
+    /// Open or close the account menu in the header
+    ToggleAccountMenu,
+    AccountsLoaded(Vec<Account>),
+    /// Profile info of the active account, for the switch generation it was requested in
+    UserInfoLoaded(u64, Result<auth::UserInfo, String>),
+    AvatarLoaded(u64, Result<Handle, String>),
+    /// Switch to the account of this profile, `None` for the default one
+    SwitchAccount(Option<String>),
+    AddAccount,
+    AccountAdded(Result<(), String>),
+    /// Sign in again after Google rejected the refresh token
+    Reauthenticate,
+    Reauthenticated(Result<(), String>),
+    SignOut,
+    StatusBarTick,
+    StatusBarUpdated(StatusSnapshot),
     CloseSettings,
+    ToggleErrorLog,
+    ErrorLogLoaded(Result<Vec<String>, String>),
+    CopyErrorLog,
+    ClearErrorLog,
+    ErrorLogCleared(Result<(), String>),
+    /// Bytes and files the image loader keeps on disk
+    ImageCacheUsage((u64, usize)),
+    ClearImageCache,
+    ImageCacheCleared(Result<(u64, usize), String>),
+    /// Bytes and files of cached videos
+    VideoCacheUsage((u64, usize)),
+    ClearVideoCache,
+    VideoCacheCleared(Result<(u64, usize), String>),
+    /// Ask before removing everything synced into the cache
+    ShowResetCacheDialog,
+    ConfirmResetCache,
+    CancelResetCache,
+    CacheReset(Result<(), String>),
+    /// Thumbnails of newly synced items were fetched in the background
+    PrefetchFinished(PrefetchSummary),
     SettingsLogLevelChanged(String),
     SettingsCachePathChanged(String),
     SettingsOauthPortChanged(String),
@@ -179,9 +404,114 @@ pub enum Message {
     SettingsDebugConsoleToggled(bool),
     SettingsTraceSpansToggled(bool),
     SaveSettings,
+    /// Look for edits to the config file, see `app_config::ConfigWatcher`
+    CheckConfig,
+    /// Settings saved in the dialog or edited in the file; those that do not
+    /// need a restart are applied right away
+    ConfigChanged(Box<AppConfig>),
     ChooseCachePath,
     CachePathChosen(Option<String>),
     LoadMorePhotos,
+    GridScrolled(scrollable::Viewport),
+    ModifiersChanged(keyboard::Modifiers),
+    ToggleSelect(String),
+    SelectRangeTo(String),
+    ClearSelection,
+    BatchAssignAlbum(AlbumOption),
+    BatchToggleFavorite,
+    BatchRemoveFromAlbum,
+    ExportPhotos(Vec<String>),
+    ExportTo(Vec<String>, Option<PathBuf>),
+    ExportProgress(String, Result<PathBuf, String>),
+    /// A file dropped onto the window, to be uploaded
+    FileDropped(PathBuf),
+    UploadFinished(PathBuf, Result<MediaItem, String>),
+    RetryUpload(PathBuf),
+    AddUploadsToAlbum,
+    DismissUploads,
+    BatchCompleted(Result<String, String>),
+    ToggleFavorite(String),
+    FavoriteUpdated(String, Result<(), String>),
+    KeyPressed(keyboard::KeyCode),
+    ShowRemovePhotoDialog(String),
+    ConfirmRemovePhoto,
+    CancelRemovePhoto,
+    /// Album id, removed media ids and the outcome of the API and cache update
+    PhotoRemoved(String, Vec<String>, Result<(), String>),
+    UndoRemoveFromAlbum(String, Vec<String>),
+    ToggleInfoPanel,
+    TileHovered(String),
+    /// Right-click on a grid tile
+    OpenContextMenu(String),
+    CloseContextMenu,
+    /// Switch the context menu between its actions and the album list
+    ContextMenuAlbums(bool),
+    /// A context menu entry; closes the menu and runs the wrapped message
+    ContextMenuPicked(Box<Message>),
+    AddToAlbum(AlbumOption, Vec<String>),
+    CopyPhotoLink(String),
+    TileUnhovered(String),
+    AlbumHovered(String),
+    AlbumUnhovered(String),
+    DragPressed,
+    DragMoved,
+    DragReleased,
+    ZoomChanged(Zoom),
+    WindowResized(u32, u32),
+    TileSizeChanged(f32),
+    TileSizeReleased,
+    WindowMoved(i32, i32),
+    WindowCloseRequested(iced::window::Id),
+    /// Another instance was started and hands over to this one
+    FocusRequested,
+    TogglePeoplePanel,
+    PeopleLoaded(Result<Vec<cache::PersonEntry>, String>),
+    FaceCropLoaded(people::PersonKey, Result<Handle, String>),
+    /// Show the photos of a person; `None` selects the unnamed faces
+    SelectPerson(Option<String>),
+    /// Show the photos of a cluster of unnamed faces
+    SelectCluster(u32),
+    StartRenamePerson(String),
+    PersonNameChanged(String),
+    ConfirmRenamePerson,
+    StartMergePerson(String),
+    MergePersonInto(String),
+    CancelPersonAction,
+    PeopleChanged(Result<String, String>),
+    ItemAlbumsLoaded(String, Result<Vec<Album>, String>),
+    #[cfg(feature = "clipboard")]
+    CopyImage,
+    /// Whether the full image was copied rather than the thumbnail
+    #[cfg(feature = "clipboard")]
+    ImageCopied(Result<bool, String>),
+    CopyLink,
+    StartEditDescription,
+    DescriptionChanged(String),
+    SaveDescription,
+    CancelEditDescription,
+    /// Saved description and whether it was queued for the next sync
+    DescriptionSaved(String, Result<(String, bool), String>),
+    StartSlideshow,
+    /// Start the slideshow with the window in fullscreen mode
+    StartFullscreenSlideshow,
+    SlideshowTick,
+    ToggleSlideshow,
+    ToggleFullscreen,
+    /// Mouse movement while fullscreen, revealing the controls
+    FullscreenActivity,
+    /// Hide the fullscreen controls unless there was activity since the given one
+    HideFullscreenControls(u64),
+    SettingsSlideshowIntervalChanged(String),
+    SettingsImageCacheMaxChanged(String),
+    SettingsVideoCacheMaxChanged(String),
+    SettingsSlideshowVideosToggled(bool),
+    SettingsThemeChanged(String),
+    SettingsLanguageChanged(Language),
+    TimelineGranularityChanged(Granularity),
+    ToggleSection(String),
+    JumpDateChanged(String),
+    JumpToDate,
+    PeriodCountsLoaded(Result<Vec<(String, u64)>, String>),
     EscapePressed,
 }
 
@@ -198,49 +528,17 @@ impl std::fmt::Display for AlbumOption {
 }
 
 
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum SearchMode {
-    Filename,
-    Description,
-    Text,
-    Favoriten,
-    DateRange,
-    MimeType,
-    CameraModel,
-    CameraMake,
-    Faces,
-}
-
-impl SearchMode {
-    const ALL: [SearchMode; 9] = [
-        SearchMode::Filename,
-        SearchMode::Description,
-        SearchMode::Text,
-        SearchMode::Favoriten,
-        SearchMode::DateRange,
-        SearchMode::MimeType,
-        SearchMode::CameraModel,
-        SearchMode::CameraMake,
-        SearchMode::Faces,
-    ];
-}
-
-impl std::fmt::Display for SearchMode {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let s = match self {
-            SearchMode::Filename => "Filename",
-            SearchMode::Description => "Beschreibung",
-            SearchMode::Text => "Dateiname/Beschr.",
-            SearchMode::Favoriten => "Favoriten",
-            SearchMode::DateRange => "Datum von/bis",
-            SearchMode::MimeType => "Dateityp",
-            SearchMode::CameraModel => "Kamera-Modell",
-            SearchMode::CameraMake => "Kamera-Hersteller",
-            SearchMode::Faces => "Gesichter",
-        };
-        write!(f, "{}", s)
-    }
+/// A video being downloaded before it can play.
+#[cfg(feature = "gstreamer")]
+#[derive(Debug)]
+struct VideoDownload {
+    media_id: String,
+    /// Received bytes, and whether playback from the partial file is waiting for more
+    buffer: StreamBuffer,
+    /// Playback from the partial file was attempted
+    stream_tried: bool,
+    cancel: CancelToken,
+    updates: Arc<Mutex<mpsc::UnboundedReceiver<VideoProgress>>>,
 }
 
 #[derive(Debug)]
@@ -253,29 +551,62 @@ enum ViewState {
     #[cfg(feature = "gstreamer")]
     PlayingVideo {
         player: GstreamerIcedBase,
-        file: TempPath,
+        /// `None` while playing from the partial download
+        file: Option<CachedVideo>,
+    },
+    Slideshow {
+        index: usize,
+        playing: bool,
     },
 }
 
 pub struct GooglePiczUI {
     photos: Vec<MediaItem>,
+    /// Position of each photo in `photos`, rebuilt by `index_photos`
+    photo_index: std::collections::HashMap<String, usize>,
     albums: Vec<Album>,
     loading: bool,
     cache_manager: Option<Arc<Mutex<CacheManager>>>,
+    cache_status: startup::CacheStatus,
     image_loader: Arc<Mutex<ImageLoader>>,
-    thumbnails: std::collections::HashMap<String, Handle>,
-    full_images: std::collections::HashMap<String, Handle>,
+    /// Thumbnails and full images in memory, filled by the loader
+    handles: HandleCache,
+    /// Video lengths in milliseconds, known once a video has been played
+    video_durations: std::collections::HashMap<String, u64>,
+    pending_thumbnails: std::collections::HashSet<String>,
+    /// Thumbnails whose download failed, with the error; not re-requested until retried
+    failed_thumbnails: std::collections::HashMap<String, ImageLoaderError>,
+    /// Banner entry summarising thumbnail failures, replaced as more arrive
+    thumbnail_error: Option<String>,
+    /// Range of photo indices currently shown in the grid, including look-ahead.
+    thumb_window: std::ops::Range<usize>,
+    /// Part of `thumb_window` actually on screen; loaded ahead of the look-ahead rows
+    on_screen: std::ops::Range<usize>,
     progress_receiver: Option<Arc<Mutex<mpsc::UnboundedReceiver<SyncProgress>>>>,
     error_receiver: Option<Arc<Mutex<mpsc::UnboundedReceiver<SyncTaskError>>>>,
     status_receiver: Option<Arc<Mutex<mpsc::UnboundedReceiver<SyncTaskError>>>>,
+    /// Requests from later instances to raise the window
+    focus_receiver: Option<Arc<Mutex<mpsc::UnboundedReceiver<()>>>>,
     synced: u64,
     syncing: bool,
     last_synced: Option<DateTime<Utc>>,
     sync_status: String,
+    sync_activity: sync_panel::SyncActivity,
+    context_menu: Option<context_menu::ContextMenu>,
+    fullscreen: Option<fullscreen::Fullscreen>,
+    /// Requests for the periodic sync task; `None` without a running syncer
+    sync_control: Option<mpsc::UnboundedSender<SyncControl>>,
     state: ViewState,
     selected_album: Option<String>,
+    /// Album to refresh from the API once its cached items are on screen
+    album_refresh_pending: Option<String>,
+    /// The favorites pseudo-album is selected
+    favorites_only: bool,
+    album_sidebar: album_sidebar::AlbumSidebar,
+    trash: trash::Trash,
     errors: Vec<String>,
     preload_count: usize,
+    thumbnail_queue: preload::ThumbnailQueue,
     display_limit: usize,
     creating_album: bool,
     new_album_title: String,
@@ -283,8 +614,16 @@ pub struct GooglePiczUI {
     renaming_album: Option<String>,
     rename_album_title: String,
     deleting_album: Option<String>,
+    /// Deleted albums still offering undo, keyed by album id
+    pending_album_deletes: std::collections::HashMap<String, album_delete::PendingAlbumDelete>,
     search_mode: SearchMode,
     search_query: String,
+    /// Bumped by every keystroke and search so stale timers and results are ignored
+    search_generation: u64,
+    search_history: search::SearchHistory,
+    search_limit: usize,
+    /// Matches beyond the current limit
+    search_more: usize,
     search_camera: String,
     search_camera_make: Option<String>,
     search_mime: Option<String>,
@@ -294,9 +633,28 @@ pub struct GooglePiczUI {
     search_end: String,
     search_favorite: bool,
     search_faces: bool,
+    search_person: Option<String>,
+    /// Album the running search is limited to; removing its chip widens the search
+    search_album: Option<search::AlbumScope>,
+    /// Named people offered by the person filter
+    person_options: iced::widget::combo_box::State<String>,
     error_log_path: PathBuf,
     settings_open: bool,
+    /// Tail of the error log while its section in the settings dialog is open
+    error_log: Option<Vec<String>>,
+    settings_account_open: bool,
+    status_bar: status_bar::StatusBar,
+    account_menu: accounts::AccountMenu,
+    cache_db_path: PathBuf,
     config_path: PathBuf,
+    /// Config profile of the active account, `None` for the default one
+    profile: Option<String>,
+    config_watcher: app_config::ConfigWatcher,
+    /// Settings the app started with, to tell which edits need a restart
+    startup_config: AppConfig,
+    /// Keys of `app_config::RESTART_KEYS` changed since startup
+    restart_required: Vec<&'static str>,
+    sync_interval_minutes: u64,
     settings_log_level: String,
     settings_cache_path: String,
     settings_oauth_port: String,
@@ -307,6 +665,78 @@ pub struct GooglePiczUI {
     settings_trace_spans: bool,
     editing_face: Option<usize>,
     face_name_input: String,
+    /// Photos with face detection running, so a second click does not start another
+    detecting_faces: std::collections::HashSet<String>,
+    /// Cluster of the face just named, offered to take the same name
+    cluster_offer: Option<(cache::FaceCluster, String)>,
+    selection: std::collections::HashSet<String>,
+    selection_anchor: Option<String>,
+    modifiers: keyboard::Modifiers,
+    focused: Option<usize>,
+    /// Last known grid scroll offset and viewport height
+    grid_viewport: (f32, f32),
+    /// Photos awaiting confirmation before leaving the selected album
+    removing_photos: Option<Vec<String>>,
+    info_open: bool,
+    /// Albums containing the photo in the detail view
+    item_albums: Vec<Album>,
+    editing_description: bool,
+    description_input: String,
+    /// Seconds per slide
+    slideshow_interval: u64,
+    slideshow_play_videos: bool,
+    /// Warm the thumbnail cache with items a sync just added
+    prefetch_after_sync: bool,
+    /// Queue favorite changes for the favorites album; read only at startup
+    favorites_album_sync: bool,
+    /// Faces detected on demand smaller than this fraction of the image are ignored
+    min_face_size: f32,
+    /// Backend and threshold for faces detected on demand
+    face_detector: face_recognition::DetectorConfig,
+    /// Concurrent downloads for bulk thumbnail loads
+    preload_threads: usize,
+    /// Slide to return to once a video started from the slideshow ends
+    #[cfg(feature = "gstreamer")]
+    slideshow_resume: Option<usize>,
+    #[cfg(feature = "gstreamer")]
+    video_download: Option<VideoDownload>,
+    /// Videos played before, and partial downloads to resume
+    video_cache: VideoCache,
+    settings_slideshow_interval: String,
+    settings_image_cache_max: String,
+    /// Image loader disk usage, fetched when the settings dialog opens
+    image_cache_usage: Option<(u64, usize)>,
+    settings_video_cache_max: String,
+    video_cache_usage: Option<(u64, usize)>,
+    /// Confirmation for "Reset local cache" is shown
+    confirm_reset_cache: bool,
+    /// Validation message per invalid settings input; Save is disabled while any exist
+    settings_errors: std::collections::HashMap<SettingsField, String>,
+    settings_slideshow_videos: bool,
+    timeline: timeline::Timeline,
+    /// Active `theme` setting, previewed live while the settings dialog is open
+    theme_setting: String,
+    settings_theme: String,
+    system_dark: bool,
+    settings_language: Language,
+    notifications: Vec<Notification>,
+    people: people::People,
+    drag: drag::Drag,
+    /// Zoom of the photo detail view, reset whenever another photo opens
+    zoom: Zoom,
+    export: Option<ExportJob>,
+    uploads: upload::Uploads,
+    ui_state: UiState,
+    ui_state_path: PathBuf,
+    /// Saved grid offset, applied once the first photos have loaded
+    restore_scroll: Option<f32>,
+    /// Photo to focus once the photos have loaded
+    restore_focus: Option<String>,
+    /// Grid offset to return to when the viewer or slideshow closes
+    grid_return_offset: Option<f32>,
+    /// Grid offset and focused photo from before the current search
+    pre_search: Option<(f32, Option<String>)>,
+    next_notification_id: u64,
 }
 
 impl GooglePiczUI {
@@ -320,6 +750,10 @@ impl GooglePiczUI {
         self.errors.len()
     }
 
+    pub fn errors(&self) -> &[String] {
+        &self.errors
+    }
+
     pub fn photo_count(&self) -> usize {
         self.photos.len()
     }
@@ -344,6 +778,34 @@ impl GooglePiczUI {
         self.search_mode
     }
 
+    /// Duration badge of a video tile, once its length is known.
+    pub fn duration_badge(&self, media_id: &str) -> Option<String> {
+        self.video_durations.get(media_id).map(|ms| timeline::format_duration(*ms))
+    }
+
+    /// Search constraints currently set, as shown by the filter chips
+    pub fn active_filters(&self) -> ActiveFilters {
+        ActiveFilters::from_ui(self)
+    }
+
+    pub fn search_person(&self) -> Option<String> {
+        self.search_person.clone()
+    }
+
+    /// Id of the album the current search is limited to
+    pub fn search_album(&self) -> Option<&str> {
+        self.search_album.as_ref().map(|a| a.id.as_str())
+    }
+
+    pub fn search_generation(&self) -> u64 {
+        self.search_generation
+    }
+
+    /// Matches not shown because of the search result limit.
+    pub fn search_more(&self) -> usize {
+        self.search_more
+    }
+
     pub fn rename_album_title(&self) -> String {
         self.rename_album_title.clone()
     }
@@ -352,10 +814,80 @@ impl GooglePiczUI {
         self.settings_open
     }
 
+    pub fn settings_account_open(&self) -> bool {
+        self.settings_account_open
+    }
+
+    /// Whether the last API call reached the server; `None` before the first one
+    pub fn online(&self) -> Option<bool> {
+        self.status_bar.online
+    }
+
+    /// Whether the cache is still being opened or migrated
+    pub fn cache_status(&self) -> CacheStatus {
+        self.cache_status
+    }
+
+    /// The last API call could not reach Google, so cached data is shown
+    pub fn offline(&self) -> bool {
+        self.status_bar.online == Some(false)
+    }
+
+    pub fn status_snapshot(&self) -> &StatusSnapshot {
+        &self.status_bar.snapshot
+    }
+
     pub fn settings_log_level(&self) -> String {
         self.settings_log_level.clone()
     }
 
+    pub fn error_log(&self) -> Option<&[String]> {
+        self.error_log.as_deref()
+    }
+
+    pub fn settings_error(&self, field: SettingsField) -> Option<String> {
+        self.settings_errors.get(&field).cloned()
+    }
+
+    pub fn profile(&self) -> Option<&str> {
+        self.profile.as_deref()
+    }
+
+    pub fn account_menu_open(&self) -> bool {
+        self.account_menu.open
+    }
+
+    /// Accounts listed in the account menu.
+    pub fn accounts(&self) -> &[Account] {
+        &self.account_menu.accounts
+    }
+
+    /// Email of the signed in account, once fetched.
+    pub fn account_email(&self) -> Option<String> {
+        self.account_menu.user.as_ref().and_then(|u| u.email.clone())
+    }
+
+    /// Whether the "Sign in again" prompt is shown.
+    pub fn reauth_required(&self) -> bool {
+        self.account_menu.reauth_required
+    }
+
+    pub fn cache_db_path(&self) -> &Path {
+        &self.cache_db_path
+    }
+
+    pub fn restart_required(&self) -> &[&'static str] {
+        &self.restart_required
+    }
+
+    pub fn preload_count(&self) -> usize {
+        self.preload_count
+    }
+
+    pub fn preload_threads(&self) -> usize {
+        self.preload_threads
+    }
+
     pub fn settings_cache_path(&self) -> String {
         self.settings_cache_path.clone()
     }
@@ -392,6 +924,54 @@ impl GooglePiczUI {
         self.syncing
     }
 
+    /// Media item the open context menu belongs to.
+    pub fn uploads(&self) -> &[UploadEntry] {
+        &self.uploads.entries
+    }
+
+    pub fn context_menu_target(&self) -> Option<String> {
+        self.context_menu.as_ref().map(|m| m.media_id.clone())
+    }
+
+    pub fn is_fullscreen(&self) -> bool {
+        self.fullscreen.is_some()
+    }
+
+    /// Whether the overlay controls are shown; `None` outside fullscreen
+    pub fn fullscreen_controls_visible(&self) -> Option<bool> {
+        self.fullscreen.as_ref().map(|f| f.controls_visible)
+    }
+
+    pub fn sync_panel_open(&self) -> bool {
+        self.sync_activity.open
+    }
+
+    pub fn sync_paused(&self) -> bool {
+        self.sync_activity.paused
+    }
+
+    /// Current page and items processed in the running sync.
+    pub fn sync_page_progress(&self) -> (u64, u64) {
+        (self.sync_activity.page, self.sync_activity.processed)
+    }
+
+    /// Photos scanned and total while the background face scan runs.
+    pub fn face_scan_progress(&self) -> Option<(u64, u64)> {
+        self.sync_activity.face_scan
+    }
+
+    pub fn sync_retry_remaining(&self) -> Option<u64> {
+        self.sync_activity.retry_remaining()
+    }
+
+    pub fn sync_last_error(&self) -> Option<String> {
+        self.sync_activity.last_error.as_ref().map(|(_, msg)| msg.clone())
+    }
+
+    pub fn sync_report(&self) -> Option<sync::SyncReport> {
+        self.sync_activity.report.clone()
+    }
+
     pub fn face_count(&self) -> usize {
         match &self.state {
             ViewState::SelectedPhoto { faces, .. } => faces.len(),
@@ -399,6 +979,13 @@ impl GooglePiczUI {
         }
     }
 
+    pub fn face_ignored(&self, idx: usize) -> bool {
+        match &self.state {
+            ViewState::SelectedPhoto { faces, .. } => faces.get(idx).is_some_and(|f| f.ignored),
+            _ => false,
+        }
+    }
+
     pub fn face_name(&self, idx: usize) -> Option<String> {
         match &self.state {
             ViewState::SelectedPhoto { faces, .. } => faces.get(idx).and_then(|f| f.name.clone()),
@@ -409,357 +996,3283 @@ impl GooglePiczUI {
     pub fn editing_face(&self) -> Option<usize> {
         self.editing_face
     }
-    fn log_error(&self, msg: &str) {
-        tracing::error!("{}", msg);
-        match std::fs::OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open(&self.error_log_path)
-        {
-            Ok(mut file) => {
-                if let Err(e) = writeln!(file, "{}", msg) {
-                    tracing::error!(error = ?e, "Failed to write to error log");
-                }
-            }
-            Err(e) => {
-                tracing::error!(error = ?e, "Failed to open error log file");
-            }
-        }
+
+    pub fn is_detecting_faces(&self, media_id: &str) -> bool {
+        self.detecting_faces.contains(media_id)
     }
-    fn error_timeout() -> Command<Message> {
-        Command::perform(
-            async {
-                sleep(ERROR_DISPLAY_DURATION).await;
-            },
-            |_| Message::ClearErrors,
-        )
+
+    /// Cluster offered to take the name of the face just named, and that name
+    pub fn cluster_offer(&self) -> Option<(u32, &str)> {
+        self.cluster_offer.as_ref().map(|(cluster, name)| (cluster.id, name.as_str()))
     }
 
-    fn init_cache_manager(
-        cache_path: &Path,
-        error_log_path: &Path,
-        errors: &mut Vec<String>,
-    ) -> Option<Arc<Mutex<CacheManager>>> {
-        match CacheManager::new(cache_path) {
-            Ok(cm) => Some(Arc::new(Mutex::new(cm))),
-            Err(e) => {
-                let msg = format!("Failed to initialize cache: {}", e);
-                errors.push(msg.clone());
-                if let Ok(mut f) = std::fs::OpenOptions::new()
-                    .create(true)
-                    .append(true)
-                    .open(error_log_path)
-                {
-                    let _ = writeln!(f, "{}", msg);
-                }
-                None
-            }
+    /// Number of photos in the current multi-selection
+    pub fn selection_count(&self) -> usize {
+        self.selection.len()
+    }
+
+    fn selected_ids(&self) -> Vec<String> {
+        // Keep grid order so batch API calls and exports are deterministic
+        self.photos
+            .iter()
+            .filter(|p| self.selection.contains(&p.id))
+            .map(|p| p.id.clone())
+            .collect()
+    }
+
+    /// Index of the keyboard-focused grid tile
+    pub fn focused_index(&self) -> Option<usize> {
+        self.focused
+    }
+
+    /// ID of the photo shown in the detail view
+    pub fn selected_photo_id(&self) -> Option<String> {
+        match &self.state {
+            ViewState::SelectedPhoto { photo, .. } => Some(photo.id.clone()),
+            _ => None,
         }
     }
-}
 
-impl Application for GooglePiczUI {
-    type Executor = executor::Default;
-    type Message = Message;
-    type Theme = Theme;
-    type Flags = (
-        Option<mpsc::UnboundedReceiver<SyncProgress>>,
-        Option<mpsc::UnboundedReceiver<SyncTaskError>>,
-        Option<mpsc::UnboundedReceiver<SyncTaskError>>,
-        usize,
-        usize,
-        PathBuf,
-    );
+    pub fn info_open(&self) -> bool {
+        self.info_open
+    }
 
-    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(flags)))]
-    fn new(flags: Self::Flags) -> (Self, Command<Message>) {
-        let (progress_flag, error_flag, status_flag, preload_count, preload_threads, cache_dir) = flags;
-        #[cfg(feature = "trace-spans")]
-        let start = std::time::Instant::now();
-        #[cfg(feature = "trace-spans")]
-        let mut sys = System::new();
-        #[cfg(feature = "trace-spans")]
-        sys.refresh_memory();
-        #[cfg(feature = "trace-spans")]
-        let mem_before = sys.used_memory();
-        let mut init_errors = Vec::new();
-        let error_log_path = cache_dir.join("ui_errors.log");
-        let cache_path = cache_dir.join("cache.sqlite");
-        let config_path = cache_dir.join("config");
+    pub fn removing_photos(&self) -> Option<Vec<String>> {
+        self.removing_photos.clone()
+    }
 
-        #[cfg(feature = "gstreamer")]
-        if let Err(e) = gst::init() {
-            init_errors.push(format!("GStreamer initialization failed: {}", e));
+    pub fn editing_description(&self) -> bool {
+        self.editing_description
+    }
+
+    /// Current slide index and whether the slideshow is advancing
+    pub fn slideshow_state(&self) -> Option<(usize, bool)> {
+        match &self.state {
+            ViewState::Slideshow { index, playing } => Some((*index, *playing)),
+            _ => None,
         }
+    }
 
-        let cache_manager = Self::init_cache_manager(&cache_path, &error_log_path, &mut init_errors);
+    pub fn settings_slideshow_interval(&self) -> String {
+        self.settings_slideshow_interval.clone()
+    }
 
-        let last_synced = if let Some(cm) = &cache_manager {
-            let cache = cm.blocking_lock();
-            match cache.get_last_sync() {
-                Ok(ts) => Some(ts),
-                Err(e) => {
-                    let msg = format!("Failed to read last sync: {}", e);
-                    init_errors.push(msg.clone());
-                    if let Ok(mut f) = std::fs::OpenOptions::new()
-                        .create(true)
-                        .append(true)
-                        .open(&error_log_path)
-                    {
-                        if let Err(e) = writeln!(f, "{}", msg) {
-                            tracing::error!(error = ?e, "Failed to write to error log");
-                        }
-                    } else {
-                        tracing::error!("Failed to open error log file");
-                    }
-                    None
-                }
-            }
-        } else {
-            None
-        };
+    pub fn settings_image_cache_max(&self) -> String {
+        self.settings_image_cache_max.clone()
+    }
 
-        let image_loader = Arc::new(Mutex::new(ImageLoader::new(cache_dir.clone(), preload_threads)));
+    /// Image loader disk usage as (bytes, files), once known
+    pub fn image_cache_usage(&self) -> Option<(u64, usize)> {
+        self.image_cache_usage
+    }
 
-        let progress_receiver = progress_flag.map(|rx| Arc::new(Mutex::new(rx)));
-        let error_receiver = error_flag.map(|rx| Arc::new(Mutex::new(rx)));
-        let status_receiver = status_flag.map(|rx| Arc::new(Mutex::new(rx)));
+    pub fn reset_cache_confirm_open(&self) -> bool {
+        self.confirm_reset_cache
+    }
 
-        let status = match last_synced {
-            Some(ts) => format!("Last synced {}", ts.to_rfc3339()),
-            None => "Never synced".to_string(),
-        };
+    /// Titles listed by the open "All albums…" menu.
+    pub fn all_albums_menu(&self) -> Option<Vec<String>> {
+        let filter = self.album_sidebar.menu.as_ref()?;
+        Some(
+            album_sidebar::menu_albums(&self.albums, filter)
+                .into_iter()
+                .map(|a| a.title.clone().unwrap_or_default())
+                .collect(),
+        )
+    }
 
-        let cfg = AppConfig::load_from(Some(config_path.clone()));
-        let open_settings = std::env::var("OPEN_SETTINGS").unwrap_or_default() == "1";
+    pub fn settings_video_cache_max(&self) -> String {
+        self.settings_video_cache_max.clone()
+    }
 
-        let app = Self {
-            photos: Vec::new(),
-            albums: Vec::new(),
-            loading: false,
-            cache_manager,
-            image_loader,
-            thumbnails: std::collections::HashMap::new(),
-            full_images: std::collections::HashMap::new(),
-            progress_receiver,
-            error_receiver,
-            status_receiver,
-            synced: 0,
-            syncing: false,
-            last_synced,
-            sync_status: status,
-            state: ViewState::Grid,
-            selected_album: None,
-            errors: init_errors,
-            preload_count,
-            display_limit: 0,
-            creating_album: false,
-            new_album_title: String::new(),
-            assign_selection: None,
-            renaming_album: None,
-            rename_album_title: String::new(),
-            deleting_album: None,
-            search_mode: SearchMode::Filename,
-            search_query: String::new(),
-            search_camera: String::new(),
-            search_camera_make: None,
-            search_mime: None,
-            camera_make_options: Vec::new(),
-            mime_options: Vec::new(),
-            search_start: String::new(),
-            search_end: String::new(),
-            search_favorite: false,
-            search_faces: false,
-            error_log_path,
-            settings_open: open_settings,
-            config_path,
-            settings_log_level: cfg.log_level.clone(),
-            settings_cache_path: cfg.cache_path.to_string_lossy().to_string(),
-            settings_oauth_port: cfg.oauth_redirect_port.to_string(),
-            settings_thumbnails_preload: cfg.thumbnails_preload.to_string(),
-            settings_preload_threads: cfg.preload_threads.to_string(),
-            settings_sync_interval: cfg.sync_interval_minutes.to_string(),
-            settings_debug_console: cfg.debug_console,
-            settings_trace_spans: cfg.trace_spans,
-            editing_face: None,
-            face_name_input: String::new(),
-        };
-        #[cfg(feature = "trace-spans")]
-        {
-            sys.refresh_memory();
-            tracing::info!(target = "ui", "init_time_ms" = start.elapsed().as_millis(), "mem_before_kb" = mem_before, "mem_after_kb" = sys.used_memory());
+    /// Video cache disk usage as (bytes, files), once known
+    pub fn video_cache_usage(&self) -> Option<(u64, usize)> {
+        self.video_cache_usage
+    }
+
+    /// Labels of the people panel entries, unnamed faces last
+    pub fn people_labels(&self) -> Vec<String> {
+        self.people.entries.iter().map(people::People::label).collect()
+    }
+
+    pub fn selected_person(&self) -> Option<Option<String>> {
+        self.people.selected.clone()
+    }
+
+    pub fn selected_cluster(&self) -> Option<u32> {
+        self.people.selected_cluster
+    }
+
+    /// Photos held by the current drag, empty until the cursor moves
+    pub fn dragged_items(&self) -> &[String] {
+        if self.drag.active() {
+            &self.drag.items
+        } else {
+            &[]
         }
+    }
 
-        (
-            app,
-            Command::batch(vec![
-                Command::perform(async {}, |_| Message::LoadPhotos),
-                Command::perform(async {}, |_| Message::LoadAlbums),
-            ]),
-        )
+    pub fn selected_album(&self) -> Option<&str> {
+        self.selected_album.as_deref()
     }
 
-    fn title(&self) -> String {
-        String::from("GooglePicz - Google Photos Manager")
+    pub fn ui_state(&self) -> &UiState {
+        &self.ui_state
     }
 
-    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
-    fn update(&mut self, message: Message) -> Command<Message> {
-        match message {
-            Message::LoadPhotos => {
-                self.loading = true;
-                if let Some(album_id) = &self.selected_album {
+    /// Files finished and total of the running export
+    pub fn export_progress(&self) -> Option<(usize, usize)> {
+        self.export.as_ref().map(|job| (job.done, job.total))
+    }
+
+    pub fn zoom(&self) -> Zoom {
+        self.zoom
+    }
+
+    /// Album that would receive the dragged photos on release
+    pub fn drop_target(&self) -> Option<&str> {
+        self.drag.hovered_album.as_deref().filter(|id| self.drag.targets(id))
+    }
+
+    pub fn notifications(&self) -> &[Notification] {
+        &self.notifications
+    }
+
+    pub fn albums(&self) -> &[Album] {
+        &self.albums
+    }
+
+    /// Item count shown next to an album in the sidebar
+    pub fn album_item_count(&self, id: &str) -> Option<u64> {
+        self.album_sidebar.counts.get(id).copied()
+    }
+
+    pub fn favorites_only(&self) -> bool {
+        self.favorites_only
+    }
+
+    pub fn trash_open(&self) -> bool {
+        self.trash.open
+    }
+
+    /// Ids in the trash, most recently deleted first
+    pub fn trashed_ids(&self) -> Vec<String> {
+        self.trash.items.iter().map(|t| t.item.id.clone()).collect()
+    }
+
+    pub fn trash_selection(&self) -> Vec<String> {
+        self.trash.selected_ids()
+    }
+
+    pub fn empty_trash_confirm_open(&self) -> bool {
+        self.trash.confirm_empty
+    }
+
+    /// Whether an album delete is still waiting for its undo toast to expire
+    pub fn album_delete_pending(&self, id: &str) -> bool {
+        self.pending_album_deletes.contains_key(id)
+    }
+
+    /// Theme currently applied to the window
+    pub fn active_theme(&self) -> Theme {
+        style::theme_for(&self.theme_setting, self.system_dark)
+    }
+
+    /// Header titles of the timeline sections currently shown
+    pub fn section_titles(&self) -> Vec<String> {
+        let (sections, _) = timeline::layout(self);
+        sections.iter().map(|s| self.timeline.title(s)).collect()
+    }
+
+    /// Store a frame from the middle of a just downloaded video as its
+    /// thumbnail and remember its duration, so later loads need neither.
+    #[cfg(feature = "gstreamer")]
+    fn grab_video_frame(&self, media_id: String, path: PathBuf) -> Command<Message> {
+        let image_loader = self.image_loader.clone();
+        let cache_manager = self.cache_manager.clone();
+        let id = media_id.clone();
+        Command::perform(
+            async move {
+                let frame = tokio::task::spawn_blocking(move || video_thumbnail::grab_midpoint(&path))
+                    .await
+                    .map_err(|e| e.to_string())??;
+                let handle = {
+                    let loader = image_loader.lock().await;
+                    loader
+                        .store_thumbnail(&id, frame.jpeg)
+                        .await
+                        .map_err(|e| e.to_string())?
+                };
+                if let Some(cm) = cache_manager {
+                    let cache = {
+                        let guard = cm.lock().await;
+                        guard.clone()
+                    };
+                    cache
+                        .set_video_duration_async(id, frame.duration_ms)
+                        .await
+                        .map_err(|e| e.to_string())?;
+                }
+                Ok::<_, String>((handle, frame.duration_ms))
+            },
+            move |result| Message::VideoFrameGrabbed(media_id, result),
+        )
+    }
+
+    fn load_error_log(&self) -> Command<Message> {
+        Command::perform(
+            error_log::read_tail(self.error_log_path.clone(), error_log::TAIL_LINES),
+            Message::ErrorLogLoaded,
+        )
+    }
+
+    /// Re-check one settings input and update its inline error.
+    fn check_setting(&mut self, field: SettingsField) {
+        match settings::validate(field, settings::input(self, field)) {
+            Ok(()) => {
+                self.settings_errors.remove(&field);
+            }
+            Err(e) => {
+                self.settings_errors.insert(field, e);
+            }
+        }
+    }
+
+    /// Collect the MIME types and camera makes offered by the filter menus.
+    fn update_filter_options(&mut self) {
+        use std::collections::HashSet;
+        let mut mimes: HashSet<String> = HashSet::new();
+        let mut makes: HashSet<String> = HashSet::new();
+        for photo in &self.photos {
+            mimes.insert(photo.mime_type.clone());
+            if let Some(make) = photo
+                .media_metadata
+                .video
+                .as_ref()
+                .and_then(|v| v.camera_make.clone())
+            {
+                makes.insert(make);
+            }
+        }
+        self.mime_options = mimes.into_iter().collect();
+        self.mime_options.sort();
+        self.camera_make_options = makes.into_iter().collect();
+        self.camera_make_options.sort();
+    }
+
+    fn load_period_counts(&self) -> Command<Message> {
+        let Some(cm) = self.cache_manager.clone() else {
+            return Command::none();
+        };
+        let bucket = self.timeline.granularity.bucket();
+        Command::perform(
+            async move {
+                let cache = {
+                    let guard = cm.lock().await;
+                    guard.clone()
+                };
+                cache
+                    .count_media_items_by_period_async(bucket, cache::TimeMode::Utc)
+                    .await
+                    .map_err(|e| e.to_string())
+            },
+            Message::PeriodCountsLoaded,
+        )
+    }
+
+    fn load_accounts(&self) -> Command<Message> {
+        Command::perform(
+            async { tokio::task::spawn_blocking(accounts::list).await.unwrap_or_default() },
+            Message::AccountsLoaded,
+        )
+    }
+
+    fn load_user_info(&self) -> Command<Message> {
+        let generation = self.account_menu.generation;
+        let profile = self.profile.clone();
+        Command::perform(
+            async move { auth::get_user_info_for(profile.as_deref()).await.map_err(|e| e.to_string()) },
+            move |result| Message::UserInfoLoaded(generation, result),
+        )
+    }
+
+    /// Forget the photos, albums and thumbnails of the account being left.
+    fn clear_library(&mut self) {
+        self.thumbnail_queue.cancel_in_flight(|_| false);
+        self.thumbnail_queue.clear();
+        self.photos.clear();
+        self.index_photos();
+        self.albums.clear();
+        self.video_durations.clear();
+        self.pending_thumbnails.clear();
+        self.failed_thumbnails.clear();
+        self.thumbnail_error = None;
+        self.thumb_window = 0..0;
+        self.on_screen = 0..0;
+        self.display_limit = 0;
+        self.selection.clear();
+        self.selection_anchor = None;
+        self.focused = None;
+        self.context_menu = None;
+        self.item_albums.clear();
+        self.state = ViewState::Grid;
+        self.favorites_only = false;
+        self.album_sidebar = album_sidebar::AlbumSidebar::default();
+        self.trash = trash::Trash::default();
+        self.people = people::People::default();
+        self.timeline = timeline::Timeline::default();
+        self.search_query.clear();
+        self.search_person = None;
+        self.search_album = None;
+        self.person_options = iced::widget::combo_box::State::new(Vec::new());
+        self.pre_search = None;
+        self.synced = 0;
+        self.syncing = false;
+        self.sync_activity = sync_panel::SyncActivity {
+            open: self.sync_activity.open,
+            paused: self.sync_activity.paused,
+            ..Default::default()
+        };
+    }
+
+    /// Make the account of `profile` the active one: its tokens, settings,
+    /// cache and UI state replace those of the current account, and the
+    /// syncer moves over to its cache.
+    fn switch_account(&mut self, profile: Option<String>) -> Command<Message> {
+        self.account_menu.open = false;
+        self.save_ui_state();
+        let config_path = app_config::config_path(profile.as_deref());
+        let mut cfg = AppConfig::load_from(Some(config_path.clone()), profile.as_deref());
+        for issue in cfg.clamp_recoverable() {
+            tracing::warn!("Setting out of range, clamped: {}", issue);
+        }
+        let cache_dir = cfg.cache_path.clone();
+        if let Err(e) = std::fs::create_dir_all(&cache_dir) {
+            let msg = format!("{}: {}", tr("error.switch_account"), e);
+            self.errors.push(msg.clone());
+            self.log_error(&msg);
+            return GooglePiczUI::error_timeout();
+        }
+        self.clear_library();
+        self.error_log_path = cache_dir.join("ui_errors.log");
+        self.cache_db_path = cache_dir.join("cache.sqlite");
+        self.cache_manager = None;
+        self.cache_status = startup::CacheStatus::Opening;
+        let image_loader = ImageLoader::new(cache_dir.clone(), self.preload_threads).with_cache_limit(cfg.image_cache_max_mb);
+        self.handles = image_loader.handles();
+        self.image_loader = Arc::new(Mutex::new(image_loader));
+        self.video_cache = VideoCache::open(cache_dir.join("videos"), cfg.video_cache_max_mb);
+        self.last_synced = None;
+        self.sync_status = tr("startup.initializing").to_string();
+
+        self.ui_state_path = cache_dir.join(ui_state::FILE_NAME);
+        self.ui_state = UiState::load(&self.ui_state_path);
+        self.selected_album = self.ui_state.album.clone();
+        self.search_mode = self.ui_state.search_mode;
+        self.restore_scroll = (self.ui_state.scroll_offset > 0.0).then_some(self.ui_state.scroll_offset);
+        if let Err(e) = accounts::remember(profile.as_deref()) {
+            tracing::warn!("Failed to remember the active account: {}", e);
+        }
+        if self.ui_state_path == app_config::base_dir().join(ui_state::FILE_NAME) {
+            self.ui_state.last_account = profile.clone();
+        }
+
+        self.config_watcher = app_config::ConfigWatcher::new(config_path.clone(), profile.clone());
+        self.config_path = config_path;
+        self.profile = profile;
+        self.startup_config = cfg.clone();
+        self.account_menu.user = None;
+        self.account_menu.avatar = None;
+        // Restarts the sync subscriptions, dropping what the old account had queued
+        self.account_menu.generation += 1;
+
+        let mut commands = vec![startup::open(self.cache_db_path.clone())];
+        commands.push(self.send_sync_control(SyncControl::SwitchAccount {
+            db_path: self.cache_db_path.clone(),
+            profile: self.profile.clone(),
+        }));
+        commands.push(self.update(Message::ConfigChanged(Box::new(cfg))));
+        commands.push(self.load_accounts());
+        commands.push(self.load_user_info());
+        Command::batch(commands)
+    }
+
+    fn load_video_durations(&self) -> Command<Message> {
+        let Some(cm) = self.cache_manager.clone() else {
+            return Command::none();
+        };
+        Command::perform(
+            async move {
+                let cache = {
+                    let guard = cm.lock().await;
+                    guard.clone()
+                };
+                cache
+                    .get_video_durations_async()
+                    .await
+                    .map_err(|e| e.to_string())
+            },
+            Message::VideoDurationsLoaded,
+        )
+    }
+
+    fn load_trash(&self) -> Command<Message> {
+        let Some(cm) = self.cache_manager.clone() else {
+            return Command::none();
+        };
+        Command::perform(
+            async move {
+                let cache = {
+                    let guard = cm.lock().await;
+                    guard.clone()
+                };
+                cache
+                    .get_trashed_media_items_async()
+                    .await
+                    .map_err(|e| e.to_string())
+            },
+            Message::TrashLoaded,
+        )
+    }
+
+    fn load_people(&self) -> Command<Message> {
+        let Some(cm) = self.cache_manager.clone() else {
+            return Command::none();
+        };
+        Command::perform(
+            async move {
+                let cache = {
+                    let guard = cm.lock().await;
+                    guard.clone()
+                };
+                cache
+                    .get_people_with_counts_async()
+                    .await
+                    .map_err(|e| e.to_string())
+            },
+            Message::PeopleLoaded,
+        )
+    }
+
+    fn load_person_options(&self) -> Command<Message> {
+        let Some(cm) = self.cache_manager.clone() else {
+            return Command::none();
+        };
+        Command::perform(
+            async move {
+                let cache = {
+                    let guard = cm.lock().await;
+                    guard.clone()
+                };
+                cache.list_people_async().await.map_err(|e| e.to_string())
+            },
+            Message::PersonOptionsLoaded,
+        )
+    }
+
+    fn load_album_counts(&self) -> Command<Message> {
+        let Some(cm) = self.cache_manager.clone() else {
+            return Command::none();
+        };
+        Command::perform(
+            async move {
+                let cache = {
+                    let guard = cm.lock().await;
+                    guard.clone()
+                };
+                cache.get_album_item_counts_async().await.map_err(|e| e.to_string())
+            },
+            Message::AlbumCountsLoaded,
+        )
+    }
+
+    /// Fetch the cover thumbnails the sidebar does not have yet.
+    fn load_album_covers(&self) -> Command<Message> {
+        let commands: Vec<_> = self
+            .albums
+            .iter()
+            .filter(|a| !self.album_sidebar.covers.contains_key(&a.id))
+            .filter_map(|a| {
+                let url = a.cover_photo_base_url.clone()?;
+                // Covers share the disk cache with the grid when the item is known
+                let key = a.cover_photo_media_item_id.clone().unwrap_or_else(|| a.id.clone());
+                let album_id = a.id.clone();
+                let loader = self.image_loader.clone();
+                Some(Command::perform(
+                    async move {
+                        let loader = loader.lock().await;
+                        loader
+                            .load_thumbnail_sized(&key, &url, image_loader::THUMBNAIL_TIERS[0], false)
+                            .await
+                            .map_err(|e| e.to_string())
+                    },
+                    move |res| Message::AlbumCoverLoaded(album_id, res),
+                ))
+            })
+            .collect();
+        Command::batch(commands)
+    }
+
+    fn copy_link(&mut self, url: String) -> Command<Message> {
+        if url.is_empty() {
+            return Command::none();
+        }
+        Command::batch([
+            iced::clipboard::write(url),
+            self.notify(NotificationLevel::Success, tr("toast.link_copied")),
+        ])
+    }
+
+    fn send_sync_control(&mut self, request: SyncControl) -> Command<Message> {
+        let Some(tx) = &self.sync_control else {
+            return Command::none();
+        };
+        if tx.send(request.clone()).is_err() {
+            // The periodic sync task has stopped
+            self.sync_control = None;
+            let msg = tr("error.sync_control").to_string();
+            self.errors.push(msg.clone());
+            self.log_error(&msg);
+            return GooglePiczUI::error_timeout();
+        }
+        match request {
+            SyncControl::SyncNow => {
+                self.sync_activity.paused = false;
+                self.sync_activity.retry_at = None;
+            }
+            SyncControl::Pause => self.sync_activity.paused = true,
+            SyncControl::Resume => self.sync_activity.paused = false,
+            SyncControl::SetInterval(_) => {}
+            SyncControl::SwitchAccount(_) => {
+                self.sync_activity.paused = false;
+                self.sync_activity.retry_at = None;
+            }
+        }
+        Command::none()
+    }
+
+    /// Crop the sample face of `entry`, fetching the original if it is not cached yet.
+    fn load_face_crop(&self, entry: &cache::PersonEntry) -> Command<Message> {
+        let Some(cm) = self.cache_manager.clone() else {
+            return Command::none();
+        };
+        let loader = self.image_loader.clone();
+        let key = people::People::key(entry);
+        let media_id = entry.sample_media_item_id.clone();
+        let bbox = entry.sample_bbox;
+        Command::perform(
+            async move {
+                let cache = {
+                    let guard = cm.lock().await;
+                    guard.clone()
+                };
+                let item = cache
+                    .get_media_item_async(media_id.clone())
+                    .await
+                    .map_err(|e| e.to_string())?
+                    .ok_or_else(|| format!("media item {} not cached", media_id))?;
+                let loader = loader.lock().await.clone();
+                loader
+                    .load_face_crop(&media_id, &item.base_url, bbox)
+                    .await
+                    .map_err(|e| e.to_string())
+            },
+            move |res| Message::FaceCropLoaded(key, res),
+        )
+    }
+
+    /// Download thumbnails of `ids` in the background so they show up
+    /// instantly when scrolled to. Does nothing unless enabled in the settings.
+    fn prefetch_new_items(&self, ids: &[String]) -> Command<Message> {
+        let Some(cm) = self.cache_manager.clone() else {
+            return Command::none();
+        };
+        if !self.prefetch_after_sync || ids.is_empty() {
+            return Command::none();
+        }
+        let ids = ids.to_vec();
+        let loader = self.image_loader.clone();
+        let variant = Variant::Thumbnail(image_loader::thumbnail_tier(self.ui_state.tile_size));
+        let threads = self.preload_threads;
+        Command::perform(
+            async move {
+                let cache = {
+                    let guard = cm.lock().await;
+                    guard.clone()
+                };
+                let mut items = Vec::with_capacity(ids.len());
+                for id in ids {
+                    if let Ok(Some(item)) = cache.get_media_item_async(id).await {
+                        items.push((item.id, item.base_url));
+                    }
+                }
+                let loader = loader.lock().await.clone();
+                loader.prefetch(items, variant, threads, None).await
+            },
+            Message::PrefetchFinished,
+        )
+    }
+
+    /// A player for the video at `path`, already playing, or the error to show.
+    #[cfg(feature = "gstreamer")]
+    fn open_player(path: &Path) -> Result<GstreamerIcedBase, String> {
+        let url = url::Url::from_file_path(path).map_err(|_| tr("error.video_path").to_string())?;
+        let mut player = GstreamerIcedBase::new_url(&url, false).map_err(|e| {
+            let detail = e.to_string();
+            if detail.to_lowercase().contains("initialize") {
+                tr("error.gstreamer_missing").to_string()
+            } else {
+                trf("error.start_video", &[&detail])
+            }
+        })?;
+        let _ = player.update(GStreamerMessage::PlayStatusChanged(PlayStatus::Playing));
+        Ok(player)
+    }
+
+    /// Pause a video playing from its partial download when playback catches
+    /// up with the download, and continue once more has arrived.
+    #[cfg(feature = "gstreamer")]
+    fn check_stream_buffer(&mut self) -> Command<Message> {
+        let (ViewState::PlayingVideo { player, .. }, Some(download)) = (&mut self.state, self.video_download.as_mut()) else {
+            return Command::none();
+        };
+        let position = player.position_seconds();
+        let status = match download.buffer.update(position, player.duration_seconds()) {
+            Some(BufferAction::Pause) => PlayStatus::Stop,
+            Some(BufferAction::Resume) => {
+                // The pipeline may have hit the end of the file and needs to read on from here
+                let _ = player.seek(std::time::Duration::from_secs_f64(position));
+                PlayStatus::Playing
+            }
+            None => return Command::none(),
+        };
+        player.update(GStreamerMessage::PlayStatusChanged(status)).map(Message::VideoEvent)
+    }
+
+    /// Run a rename or merge against the cache and report it as `PeopleChanged`.
+    fn change_people<F, Fut>(&self, op: F) -> Command<Message>
+    where
+        F: FnOnce(CacheManager) -> Fut + Send + 'static,
+        Fut: std::future::Future<Output = Result<String, cache::CacheError>> + Send + 'static,
+    {
+        let Some(cm) = self.cache_manager.clone() else {
+            return Command::none();
+        };
+        Command::perform(
+            async move {
+                let cache = {
+                    let guard = cm.lock().await;
+                    guard.clone()
+                };
+                op(cache).await.map_err(|e| e.to_string())
+            },
+            Message::PeopleChanged,
+        )
+    }
+
+    /// Whether the slideshow stops on this item; videos need playback support and the setting.
+    fn slide_eligible(&self, item: &MediaItem) -> bool {
+        !item.mime_type.starts_with("video/") || (cfg!(feature = "gstreamer") && self.slideshow_play_videos)
+    }
+
+    /// Next eligible slide after `from`, wrapping around; `None` if nothing can be shown.
+    fn next_slide(&self, from: usize, step: isize) -> Option<usize> {
+        let len = self.photos.len() as isize;
+        (1..=len)
+            .map(|i| (from as isize + step * i).rem_euclid(len) as usize)
+            .find(|&i| self.slide_eligible(&self.photos[i]))
+    }
+
+    /// Show slide `index` and fetch the full image of the slide after it.
+    fn show_slide(&mut self, index: usize, playing: bool) -> Command<Message> {
+        self.leave_grid();
+        self.state = ViewState::Slideshow { index, playing };
+        self.focused = Some(index);
+        let mut commands = Vec::new();
+        #[cfg(feature = "gstreamer")]
+        if self.photos[index].mime_type.starts_with("video/") {
+            self.slideshow_resume = Some(index);
+            return self.update(Message::PlayVideo(self.photos[index].clone()));
+        }
+        let upcoming = std::iter::once(index).chain(self.next_slide(index, 1));
+        for i in upcoming {
+            let p = &self.photos[i];
+            if !self.handles.contains(&ImageKey::full(&p.id)) && !p.mime_type.starts_with("video/") {
+                let (id, url) = (p.id.clone(), p.base_url.clone());
+                commands.push(Command::perform(async {}, move |_| {
+                    Message::LoadFullImage(id.clone(), url.clone())
+                }));
+            }
+        }
+        Command::batch(commands)
+    }
+
+    /// Description of a loaded photo
+    pub fn photo_description(&self, id: &str) -> Option<String> {
+        self.photos.iter().find(|p| p.id == id).and_then(|p| p.description.clone())
+    }
+
+    /// Persist window geometry and the current view; failures only cost the restore.
+    fn save_ui_state(&mut self) {
+        self.ui_state.scroll_offset = self.grid_viewport.0;
+        let mut state = self.ui_state.clone();
+        if let Some(fullscreen) = &self.fullscreen {
+            (state.width, state.height, state.position) = fullscreen.windowed;
+        }
+        if let Err(e) = state.save(&self.ui_state_path) {
+            tracing::warn!("Failed to save UI state: {}", e);
+        }
+    }
+
+    /// Favorites mode implies the favorite filter, so leave it as well.
+    fn clear_favorite_filter(&mut self) {
+        self.search_favorite = false;
+        if self.search_mode == SearchMode::Favoriten {
+            self.search_mode = SearchMode::Filename;
+            self.ui_state.search_mode = self.search_mode;
+            self.save_ui_state();
+        }
+    }
+
+    /// Faces mode implies the faces filter, so leave it as well.
+    fn clear_faces_filter(&mut self) {
+        self.search_faces = false;
+        if self.search_mode == SearchMode::Faces {
+            self.search_mode = SearchMode::Filename;
+            self.ui_state.search_mode = self.search_mode;
+            self.save_ui_state();
+        }
+    }
+
+    /// Scroll the freshly loaded grid back to where the last session left it.
+    fn restore_grid_offset(&mut self, top: f32) -> Command<Message> {
+        // Grow the page until the saved offset is covered; headers only add height
+        let rows = (top / self.grid_row_height()).ceil() as usize + 1;
+        self.display_limit = self
+            .display_limit
+            .max(rows * self.grid_columns() + PAGE_SIZE)
+            .min(self.photos.len());
+        let height = self.grid_viewport.1;
+        self.grid_viewport.0 = top;
+        self.set_thumb_window(top, height);
+        Command::batch([
+            scrollable::scroll_to(grid_scroll_id(), scrollable::AbsoluteOffset { x: 0.0, y: top }),
+            self.request_visible_thumbnails(),
+        ])
+    }
+
+    /// Start the next queued upload unless one is already running.
+    fn upload_next(&mut self) -> Command<Message> {
+        let Some(path) = self.uploads.next() else {
+            return Command::none();
+        };
+        let cache_manager = self.cache_manager.clone();
+        let file = path.clone();
+        let profile = self.profile.clone();
+        Command::perform(
+            async move {
+                let token = auth::ensure_access_token_valid_for(profile.as_deref())
+                    .await
+                    .map_err(|e| e.to_string())?;
+                let item = upload::upload_file(&ApiClient::new(token), &file).await?;
+                if let Some(cm) = cache_manager {
+                    let cache = {
+                        let guard = cm.lock().await;
+                        guard.clone()
+                    };
+                    cache
+                        .insert_media_item_async(item.clone())
+                        .await
+                        .map_err(|e| e.to_string())?;
+                    // Recorded so the favorites album knows it may hold this item
+                    let hashed = file.clone();
+                    match tokio::task::spawn_blocking(move || cache::hash_file(&hashed)).await {
+                        Ok(Ok(hash)) => {
+                            if let Err(e) = cache
+                                .record_uploaded_file_async(hash, item.id.clone(), item.filename.clone())
+                                .await
+                            {
+                                tracing::warn!(error = %e, "Failed to record upload");
+                            }
+                        }
+                        Ok(Err(e)) => tracing::warn!(error = %e, "Failed to hash upload"),
+                        Err(e) => tracing::warn!(error = %e, "Failed to hash upload"),
+                    }
+                }
+                Ok(item)
+            },
+            move |res| Message::UploadFinished(path, res),
+        )
+    }
+
+    /// Download the next queued export, or report the finished job.
+    fn export_next(&mut self) -> Command<Message> {
+        let Some(job) = self.export.as_mut() else {
+            return Command::none();
+        };
+        match job.queue.pop_front() {
+            Some(item) => {
+                let dir = job.dir.clone();
+                let filename = item.filename.clone();
+                let profile = self.profile.clone();
+                Command::perform(
+                    async move {
+                        let token = auth::ensure_access_token_valid_for(profile.as_deref())
+                            .await
+                            .map_err(|e| e.to_string())?;
+                        Exporter::new(token)
+                            .export_item(&item, &dir)
+                            .await
+                            .map_err(|e| e.to_string())
+                    },
+                    move |res| Message::ExportProgress(filename, res),
+                )
+            }
+            None => {
+                let job = self.export.take().expect("export job checked above");
+                let exported = job.done - job.failed;
+                if job.failed == 0 {
+                    self.selection.clear();
+                    self.selection_anchor = None;
+                    self.notify(
+                        NotificationLevel::Success,
+                        trf("toast.batch_exported", &[&exported, &job.dir.display()]),
+                    )
+                } else {
+                    self.notify(
+                        NotificationLevel::Warning,
+                        trf("toast.export_partial", &[&exported, &job.total, &job.dir.display()]),
+                    )
+                }
+            }
+        }
+    }
+
+    /// Add `ids` to `album` remotely and in the cache; the result arrives as `BatchCompleted`.
+    fn add_to_album(&self, album: AlbumOption, ids: Vec<String>) -> Command<Message> {
+        let cache_manager = self.cache_manager.clone();
+        let profile = self.profile.clone();
+        Command::perform(
+            async move {
+                let token = auth::ensure_access_token_valid_for(profile.as_deref())
+                    .await
+                    .map_err(|e| e.to_string())?;
+                let client = ApiClient::new(token);
+                client
+                    .batch_add_media_items(&album.id, &ids)
+                    .await
+                    .map_err(|e| e.to_string())?;
+                if let Some(cm) = cache_manager {
+                    let cache = {
+                        let guard = cm.lock().await;
+                        guard.clone()
+                    };
+                    cache
+                        .associate_media_items_with_album_async(ids.clone(), album.id.clone())
+                        .await
+                        .map_err(|e| e.to_string())?;
+                }
+                Ok(trf("toast.batch_added", &[&ids.len(), &album.title]))
+            },
+            Message::BatchCompleted,
+        )
+    }
+
+    fn dialog_open(&self) -> bool {
+        self.settings_open
+            || self.creating_album
+            || self.renaming_album.is_some()
+            || self.deleting_album.is_some()
+            || self.trash.confirm_empty
+            || self.removing_photos.is_some()
+            || self.editing_face.is_some()
+            || self.editing_description
+            || self.people.renaming.is_some()
+            || self.confirm_reset_cache
+            || self.account_menu.reauth_required
+            || self.album_sidebar.menu.is_some()
+    }
+
+    /// Move the grid focus by `delta` tiles and scroll the focused row into view.
+    fn move_focus(&mut self, delta: isize) -> Command<Message> {
+        if self.photos.is_empty() {
+            return Command::none();
+        }
+        let idx = match self.focused {
+            Some(i) => (i as isize + delta).clamp(0, self.photos.len() as isize - 1) as usize,
+            None => 0,
+        };
+        self.focused = Some(idx);
+        self.scroll_to_focused()
+    }
+
+    /// Scroll the focused tile's row into view, expanding its section if needed.
+    fn scroll_to_focused(&mut self) -> Command<Message> {
+        let Some(idx) = self.focused.filter(|&i| i < self.photos.len()) else {
+            return Command::none();
+        };
+        if idx >= self.display_limit {
+            self.display_limit = (idx + PAGE_SIZE).min(self.photos.len());
+        }
+        let key = timeline::period_key(&self.photos[idx], self.timeline.granularity);
+        self.timeline.expand(&key);
+        let (_, layout) = timeline::layout(self);
+        let Some((top, row_height)) = layout.item_row(idx) else {
+            return Command::none();
+        };
+        let (offset, height) = self.grid_viewport;
+        let target = if top < offset {
+            top
+        } else if top + row_height > offset + height {
+            top + row_height - height
+        } else {
+            return Command::none();
+        };
+        scrollable::scroll_to(grid_scroll_id(), scrollable::AbsoluteOffset { x: 0.0, y: target.max(0.0) })
+    }
+
+    /// Remember where the grid was before a full-screen view replaces it.
+    fn leave_grid(&mut self) {
+        self.context_menu = None;
+        if let ViewState::Grid = self.state {
+            self.grid_return_offset = Some(self.grid_viewport.0);
+        }
+    }
+
+    /// Switch back to the grid at the offset it was left at, with the last
+    /// viewed photo focused and scrolled into view.
+    fn return_to_grid(&mut self) -> Command<Message> {
+        self.state = ViewState::Grid;
+        let exit = self.exit_fullscreen();
+        let top = self.grid_return_offset.take().unwrap_or(self.grid_viewport.0);
+        let restore = self.restore_grid_offset(top);
+        // Stepping through photos may have moved past the old viewport
+        Command::batch([exit, restore, self.scroll_to_focused()])
+    }
+
+    fn enter_fullscreen(&mut self) -> Command<Message> {
+        if self.fullscreen.is_some() {
+            return Command::none();
+        }
+        self.context_menu = None;
+        self.fullscreen = Some(fullscreen::Fullscreen::new((
+            self.ui_state.width,
+            self.ui_state.height,
+            self.ui_state.position,
+        )));
+        Command::batch([
+            iced::window::change_mode(iced::window::Id::MAIN, iced::window::Mode::Fullscreen),
+            self.fullscreen_activity(),
+        ])
+    }
+
+    /// Leave fullscreen and go back to the window geometry from before.
+    fn exit_fullscreen(&mut self) -> Command<Message> {
+        let Some(fullscreen) = self.fullscreen.take() else {
+            return Command::none();
+        };
+        (self.ui_state.width, self.ui_state.height, self.ui_state.position) = fullscreen.windowed;
+        iced::window::change_mode(iced::window::Id::MAIN, iced::window::Mode::Windowed)
+    }
+
+    /// Show the fullscreen controls and restart their hide timer.
+    fn fullscreen_activity(&mut self) -> Command<Message> {
+        let Some(fullscreen) = &mut self.fullscreen else {
+            return Command::none();
+        };
+        fullscreen.controls_visible = true;
+        fullscreen.activity += 1;
+        let activity = fullscreen.activity;
+        Command::perform(
+            async {
+                sleep(fullscreen::CONTROLS_TIMEOUT).await;
+            },
+            move |_| Message::HideFullscreenControls(activity),
+        )
+    }
+
+    /// The viewer's zoomable photo with its face boxes.
+    fn photo_image<'a>(&self, photo: &MediaItem, faces: &'a [face_recognition::Face]) -> Element<'a, Message> {
+        if let Some(handle) = self.full_image(&photo.id) {
+            let w = photo.media_metadata.width.parse::<u32>().unwrap_or(0);
+            let h = photo.media_metadata.height.parse::<u32>().unwrap_or(0);
+            container(
+                photo_view::PhotoView::new(handle, self.zoom, Message::ZoomChanged)
+                    .faces(faces, w, h),
+            )
+            .style(style::card())
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .into()
+        } else {
+            container(text(tr("common.loading")))
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .into()
+        }
+    }
+
+    /// Current slide, shown from its thumbnail until the full image arrives.
+    fn slide_image<'a>(&self, index: usize) -> Element<'a, Message> {
+        let handle = self
+            .photos
+            .get(index)
+            .and_then(|photo| self.full_image(&photo.id).or_else(|| self.thumbnail(&photo.id)));
+        match handle {
+            Some(handle) => image(handle)
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .into(),
+            None => container(text(tr("common.loading")).size(16))
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .center_x()
+                .center_y()
+                .into(),
+        }
+    }
+
+    /// Leave the search results for the library, back where the search started.
+    fn end_search(&mut self) -> Command<Message> {
+        self.search_album = None;
+        if let Some((offset, focus)) = self.pre_search.take() {
+            self.restore_scroll = Some(offset);
+            self.restore_focus = focus;
+        }
+        self.update(Message::LoadPhotos)
+    }
+
+    /// Top of the grid viewport
+    pub fn grid_offset(&self) -> f32 {
+        self.grid_viewport.0
+    }
+
+    /// Show the previous or next photo in the viewer and preload its neighbours.
+    fn step_photo(&mut self, delta: isize) -> Command<Message> {
+        let current = match &self.state {
+            ViewState::SelectedPhoto { photo, .. } => self.photos.iter().position(|p| p.id == photo.id),
+            _ => None,
+        };
+        let Some(current) = current else {
+            return Command::none();
+        };
+        let next = current as isize + delta;
+        if next < 0 || next as usize >= self.photos.len() {
+            return Command::none();
+        }
+        let next = next as usize;
+        self.focused = Some(next);
+        self.update(Message::SelectPhoto(self.photos[next].clone()))
+    }
+
+    /// Id of the photo shown full size, in the viewer or the slideshow.
+    fn viewed_photo_id(&self) -> Option<&str> {
+        match &self.state {
+            ViewState::SelectedPhoto { photo, .. } => Some(&photo.id),
+            ViewState::Slideshow { index, .. } => self.photos.get(*index).map(|p| p.id.as_str()),
+            _ => None,
+        }
+    }
+
+    /// Number of full-size images currently held in memory
+    pub fn full_image_count(&self) -> usize {
+        self.handles.counts().1
+    }
+
+    pub fn has_full_image(&self, id: &str) -> bool {
+        self.handles.contains(&ImageKey::full(id))
+    }
+
+    fn full_image(&self, id: &str) -> Option<Handle> {
+        self.handles.peek(&ImageKey::full(id))
+    }
+
+    /// Key of the thumbnail tiles of the current size show for `id`.
+    fn thumbnail_key(&self, id: &str) -> ImageKey {
+        ImageKey::thumbnail(id, image_loader::thumbnail_tier(self.ui_state.tile_size))
+    }
+
+    fn thumbnail(&self, id: &str) -> Option<Handle> {
+        self.handles.peek(&self.thumbnail_key(id))
+    }
+
+    /// Lookups, hits and evictions of the in-memory image cache.
+    pub fn handle_stats(&self) -> HandleCacheStats {
+        self.handles.stats()
+    }
+
+    /// Favorite flag of a loaded photo
+    pub fn photo_is_favorite(&self, id: &str) -> Option<bool> {
+        self.photos.iter().find(|p| p.id == id).map(|p| p.is_favorite)
+    }
+
+    /// Flip the local favorite flag in the grid and the open photo, returning the new value.
+    fn flip_favorite(&mut self, id: &str) -> Option<bool> {
+        let mut value = None;
+        if let Some(photo) = self.photos.iter_mut().find(|p| p.id == id) {
+            photo.is_favorite = !photo.is_favorite;
+            value = Some(photo.is_favorite);
+        }
+        if let ViewState::SelectedPhoto { photo, .. } = &mut self.state {
+            if photo.id == id {
+                photo.is_favorite = value.unwrap_or(!photo.is_favorite);
+                value = Some(photo.is_favorite);
+            }
+        }
+        value
+    }
+
+    /// Number of thumbnail handles currently held in memory
+    pub fn thumbnail_count(&self) -> usize {
+        self.handles.counts().0
+    }
+
+    /// Loaded and total thumbnails while a large batch is loading
+    pub fn thumbnail_progress(&self) -> Option<(usize, usize)> {
+        self.thumbnail_queue.progress()
+    }
+
+    pub fn thumbnail_state(&self, media_id: &str) -> ThumbnailState {
+        if let Some(handle) = self.thumbnail(media_id) {
+            ThumbnailState::Loaded(handle)
+        } else if let Some(error) = self.failed_thumbnails.get(media_id) {
+            ThumbnailState::Failed(error.clone())
+        } else if self.pending_thumbnails.contains(media_id) {
+            ThumbnailState::Loading
+        } else {
+            ThumbnailState::NotLoaded
+        }
+    }
+
+    /// Point `thumb_window` at the rows visible from `offset`, plus a few rows of lookahead.
+    fn set_thumb_window(&mut self, offset: f32, height: f32) {
+        let (_, layout) = timeline::layout(self);
+        self.thumb_window = layout.visible(offset, height, LOOKAHEAD_ROWS as f32 * self.grid_row_height());
+        self.on_screen = layout.visible(offset, height, 0.0);
+    }
+
+    /// Tiles per row that fit the window at the current tile size.
+    pub fn grid_columns(&self) -> usize {
+        let mut width = self.ui_state.width - GRID_MARGIN;
+        width -= album_sidebar::width(self.ui_state.width) + Palette::SPACING as f32;
+        if self.people.open {
+            width -= people::PANEL_WIDTH + Palette::SPACING as f32;
+        }
+        ((width / (self.ui_state.tile_size + TILE_GAP)).floor() as usize).max(1)
+    }
+
+    fn grid_row_height(&self) -> f32 {
+        self.ui_state.tile_size + TILE_GAP
+    }
+
+    pub fn tile_size(&self) -> f32 {
+        self.ui_state.tile_size
+    }
+
+    /// Recompute the visible rows after the column count or row height changed.
+    fn relayout_grid(&mut self) -> Command<Message> {
+        let (offset, height) = self.grid_viewport;
+        self.set_thumb_window(offset, height);
+        self.request_visible_thumbnails()
+    }
+
+    /// Request thumbnails inside `thumb_window` that are neither loaded nor in flight.
+    fn request_visible_thumbnails(&mut self) -> Command<Message> {
+        // Requests still waiting for a loader worker are not worth finishing after a long scroll
+        self.thumbnail_queue.cancel_in_flight(|id| self.near_window(id));
+        let end = self.thumb_window.end.min(self.photos.len());
+        let start = self.thumb_window.start.min(end);
+        for (index, photo) in self.photos[start..end].iter().enumerate() {
+            if self.timeline.is_collapsed(&timeline::period_key(photo, self.timeline.granularity)) {
+                continue;
+            }
+            if self.handles.contains(&self.thumbnail_key(&photo.id))
+                || self.failed_thumbnails.contains_key(&photo.id)
+                || !self.pending_thumbnails.insert(photo.id.clone())
+            {
+                continue;
+            }
+            self.thumbnail_queue.push(preload::ThumbnailRequest {
+                media_id: photo.id.clone(),
+                base_url: photo.base_url.clone(),
+                video: photo.mime_type.starts_with("video/"),
+                priority: if self.on_screen.contains(&(start + index)) {
+                    Priority::Visible
+                } else {
+                    Priority::Prefetch
+                },
+            });
+        }
+        self.load_queued_thumbnails()
+    }
+
+    /// Issue `LoadThumbnail` for queued requests while below the concurrency limit.
+    fn load_queued_thumbnails(&mut self) -> Command<Message> {
+        let commands: Vec<_> = self
+            .thumbnail_queue
+            .next_batch()
+            .into_iter()
+            .map(|request| {
+                Command::perform(async {}, move |_| {
+                    Message::LoadThumbnail(request.media_id, request.base_url, request.video, request.priority)
+                })
+            })
+            .collect();
+        Command::batch(commands)
+    }
+
+    /// Whether the photo lies within the window, allowing one screen of slack on either side.
+    fn near_window(&self, media_id: &str) -> bool {
+        let slack = self.thumb_window.len();
+        let start = self.thumb_window.start.saturating_sub(slack);
+        let end = self.thumb_window.end + slack;
+        self.photo_index
+            .get(media_id)
+            .is_some_and(|&idx| idx >= start && idx < end)
+    }
+
+    /// Rebuild `photo_index` after photos were added, removed or reordered.
+    fn index_photos(&mut self) {
+        self.photo_index = self
+            .photos
+            .iter()
+            .enumerate()
+            .map(|(idx, p)| (p.id.clone(), idx))
+            .collect();
+    }
+
+    /// Start the API refresh of the album whose cached items were just shown.
+    fn album_refresh(&mut self) -> Command<Message> {
+        let Some(album_id) = self.album_refresh_pending.take() else {
+            return Command::none();
+        };
+        let Some(cm) = self.cache_manager.clone() else {
+            return Command::none();
+        };
+        if self.selected_album.as_deref() != Some(album_id.as_str()) || self.offline() {
+            return Command::none();
+        }
+        Command::perform(
+            {
+                let album_id = album_id.clone();
+                let profile = self.profile.clone();
+                async move {
+                    let cache = {
+                        let guard = cm.lock().await;
+                        guard.clone()
+                    };
+                    album_contents::refresh(cache, album_id, profile).await
+                }
+            },
+            move |result| Message::AlbumRefreshed(album_id, result),
+        )
+    }
+
+    /// Mark a thumbnail as failed and fold it into a single banner entry, so a
+    /// flaky connection does not add one error per tile.
+    fn thumbnail_failed(&mut self, media_id: String, error: ImageLoaderError) -> Command<Message> {
+        self.pending_thumbnails.remove(&media_id);
+        self.log_error(&format!("{}: {}", trf("error.load_thumbnail", &[&media_id]), error));
+        self.failed_thumbnails.insert(media_id, error);
+        let msg = trf("error.load_thumbnails", &[&self.failed_thumbnails.len()]);
+        let existing = self
+            .thumbnail_error
+            .as_ref()
+            .and_then(|current| self.errors.iter().position(|e| e == current));
+        self.thumbnail_error = Some(msg.clone());
+        match existing {
+            Some(index) => {
+                self.errors[index] = msg;
+                Command::none()
+            }
+            None => {
+                self.errors.push(msg);
+                GooglePiczUI::error_timeout()
+            }
+        }
+    }
+
+    fn log_error(&self, msg: &str) {
+        tracing::error!("{}", msg);
+        match std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.error_log_path)
+        {
+            Ok(mut file) => {
+                if let Err(e) = writeln!(file, "{}", msg) {
+                    tracing::error!(error = ?e, "Failed to write to error log");
+                }
+            }
+            Err(e) => {
+                tracing::error!(error = ?e, "Failed to open error log file");
+            }
+        }
+    }
+    fn error_timeout() -> Command<Message> {
+        Command::perform(
+            async {
+                sleep(ERROR_DISPLAY_DURATION).await;
+            },
+            |_| Message::ClearErrors,
+        )
+    }
+
+    /// Show and log `msg`. While offline every retry fails the same way, so
+    /// an error already shown since going offline is not shown again.
+    fn report_error(&mut self, msg: String) -> Command<Message> {
+        if self.offline() && !self.status_bar.shown_offline.insert(msg.clone()) {
+            tracing::debug!("Offline, not repeating: {}", msg);
+            return Command::none();
+        }
+        self.log_error(&msg);
+        self.errors.push(msg);
+        GooglePiczUI::error_timeout()
+    }
+
+    /// Queue a toast without scheduling its dismissal.
+    fn push_notification(
+        &mut self,
+        level: NotificationLevel,
+        text: impl Into<String>,
+        action: Option<NotificationAction>,
+    ) -> u64 {
+        let id = self.next_notification_id;
+        self.next_notification_id += 1;
+        self.notifications.push(Notification {
+            id,
+            level,
+            text: text.into(),
+            created_at: std::time::Instant::now(),
+            action,
+        });
+        if self.notifications.len() > notifications::MAX_NOTIFICATIONS {
+            self.notifications.remove(0);
+        }
+        id
+    }
+
+    /// Show a toast and schedule its own dismissal.
+    fn notify(&mut self, level: NotificationLevel, text: impl Into<String>) -> Command<Message> {
+        self.notify_with_action(level, text, None)
+    }
+
+    /// Like [`Self::notify`], with a button such as undo next to the text.
+    fn notify_with_action(
+        &mut self,
+        level: NotificationLevel,
+        text: impl Into<String>,
+        action: Option<NotificationAction>,
+    ) -> Command<Message> {
+        let id = self.push_notification(level, text, action);
+        Command::perform(
+            async {
+                sleep(notifications::NOTIFICATION_DURATION).await;
+            },
+            move |_| Message::DismissNotification(id),
+        )
+    }
+
+    /// Run the search from the inputs as a new query, remembering where the grid was.
+    fn start_search(&mut self) -> Command<Message> {
+        self.trash.open = false;
+        if self.pre_search.is_none() {
+            let focus = self.focused.and_then(|i| self.photos.get(i)).map(|p| p.id.clone());
+            self.pre_search = Some((self.grid_viewport.0, focus));
+            // A search started from an album view stays inside the album
+            self.search_album = self.selected_album.as_ref().map(|id| search::AlbumScope {
+                id: id.clone(),
+                title: self
+                    .albums
+                    .iter()
+                    .find(|a| &a.id == id)
+                    .and_then(|a| a.title.clone())
+                    .unwrap_or_else(|| tr("common.untitled").to_string()),
+            });
+        }
+        self.search_generation += 1;
+        self.search_limit = SEARCH_LIMIT;
+        self.run_search()
+    }
+
+    /// Query the cache for the current search, tagging the results with the
+    /// search generation so a superseded query cannot overwrite newer results.
+    fn run_search(&mut self) -> Command<Message> {
+        self.timeline.clear_counts();
+        let Some(cm) = self.cache_manager.clone() else {
+            return Command::none();
+        };
+        self.loading = true;
+        let mut query = self.active_filters().to_query();
+        let limit = self.search_limit;
+        let generation = self.search_generation;
+        Command::perform(
+            async move {
+                let cache = {
+                    let guard = cm.lock().await;
+                    guard.clone()
+                };
+                // One row past the limit tells whether counting the rest is needed
+                query.limit = Some(limit + 1);
+                let mut items = cache.query_async(query.clone()).await.map_err(|e| e.to_string())?;
+                let more = if items.len() > limit {
+                    items.truncate(limit);
+                    query.limit = None;
+                    let total = cache.query_count_async(query).await.map_err(|e| e.to_string())?;
+                    (total as usize).saturating_sub(limit)
+                } else {
+                    0
+                };
+                Ok::<_, String>((items, more))
+            },
+            move |result| Message::SearchResults(generation, result),
+        )
+    }
+
+    /// Fetches fresh base URLs for the image loader and stores them in the cache,
+    /// so the next start does not begin with expired ones.
+    fn url_refresher(cache_manager: Arc<Mutex<CacheManager>>, profile: Option<String>) -> UrlRefresher {
+        UrlRefresher::new(move |ids: Vec<String>| {
+            let cache_manager = cache_manager.clone();
+            let profile = profile.clone();
+            async move {
+                let token = auth::ensure_access_token_valid_for(profile.as_deref())
+                    .await
+                    .map_err(|e| e.to_string())?;
+                let items = ApiClient::new(token)
+                    .batch_get_media_items(&ids)
+                    .await
+                    .map_err(|e| e.to_string())?;
+                let cache = cache_manager.lock().await.clone();
+                if let Err(e) = cache.upsert_media_items_batch_async(items.clone()).await {
+                    tracing::warn!(error = %e, "Failed to store refreshed base URLs");
+                }
+                Ok(items)
+            }
+        })
+    }
+}
+
+impl Application for GooglePiczUI {
+    type Executor = executor::Default;
+    type Message = Message;
+    type Theme = Theme;
+    type Flags = UiFlags;
+
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(flags)))]
+    fn new(flags: Self::Flags) -> (Self, Command<Message>) {
+        let UiFlags {
+            progress: progress_flag,
+            errors: error_flag,
+            status: status_flag,
+            control: sync_control,
+            focus: focus_flag,
+            preload: preload_count,
+            preload_threads,
+            cache_dir,
+        } = flags;
+        #[cfg(feature = "trace-spans")]
+        let start = std::time::Instant::now();
+        #[cfg(feature = "trace-spans")]
+        let mut sys = System::new();
+        #[cfg(feature = "trace-spans")]
+        sys.refresh_memory();
+        #[cfg(feature = "trace-spans")]
+        let mem_before = sys.used_memory();
+        let mut init_errors = Vec::new();
+        let error_log_path = cache_dir.join("ui_errors.log");
+        let cache_path = cache_dir.join("cache.sqlite");
+        let config_path = cache_dir.join("config");
+        let profile = app_config::active_profile(None);
+        let mut cfg = AppConfig::load_from(Some(config_path.clone()), profile.as_deref());
+        for issue in cfg.clamp_recoverable() {
+            tracing::warn!("Setting out of range, clamped: {}", issue);
+        }
+        i18n::set_language(Language::from_code(&cfg.language));
+        let ui_state_path = cache_dir.join(ui_state::FILE_NAME);
+        let saved = UiState::load(&ui_state_path);
+
+        #[cfg(feature = "gstreamer")]
+        if let Err(e) = gst::init() {
+            init_errors.push(format!("GStreamer initialization failed: {}", e));
+        }
+
+        // The URL refresher is added once the cache it stores the URLs in is open
+        let image_loader = ImageLoader::new(cache_dir.clone(), preload_threads).with_cache_limit(cfg.image_cache_max_mb);
+        let handles = image_loader.handles();
+        let image_loader = Arc::new(Mutex::new(image_loader));
+
+        let progress_receiver = progress_flag.map(|rx| Arc::new(Mutex::new(rx)));
+        let error_receiver = error_flag.map(|rx| Arc::new(Mutex::new(rx)));
+        let status_receiver = status_flag.map(|rx| Arc::new(Mutex::new(rx)));
+        let focus_receiver = focus_flag.map(|rx| Arc::new(Mutex::new(rx)));
+
+        let open_settings = std::env::var("OPEN_SETTINGS").unwrap_or_default() == "1";
+
+        let app = Self {
+            photos: Vec::new(),
+            photo_index: std::collections::HashMap::new(),
+            albums: Vec::new(),
+            loading: false,
+            cache_manager: None,
+            cache_status: startup::CacheStatus::Opening,
+            image_loader,
+            handles,
+            video_durations: std::collections::HashMap::new(),
+            pending_thumbnails: std::collections::HashSet::new(),
+            failed_thumbnails: std::collections::HashMap::new(),
+            thumbnail_error: None,
+            thumb_window: 0..0,
+            on_screen: 0..0,
+            progress_receiver,
+            error_receiver,
+            status_receiver,
+            focus_receiver,
+            synced: 0,
+            syncing: false,
+            last_synced: None,
+            sync_status: tr("startup.initializing").to_string(),
+            sync_activity: sync_panel::SyncActivity::default(),
+            context_menu: None,
+            fullscreen: None,
+            sync_control,
+            state: ViewState::Grid,
+            selected_album: saved.album.clone(),
+            album_refresh_pending: None,
+            favorites_only: false,
+            album_sidebar: album_sidebar::AlbumSidebar::default(),
+            trash: trash::Trash::default(),
+            errors: init_errors,
+            preload_count,
+            thumbnail_queue: preload::ThumbnailQueue::new(preload_threads),
+            display_limit: 0,
+            creating_album: false,
+            new_album_title: String::new(),
+            assign_selection: None,
+            renaming_album: None,
+            rename_album_title: String::new(),
+            deleting_album: None,
+            pending_album_deletes: std::collections::HashMap::new(),
+            search_mode: saved.search_mode,
+            search_query: String::new(),
+            search_generation: 0,
+            search_history: search::SearchHistory::default(),
+            search_limit: SEARCH_LIMIT,
+            search_more: 0,
+            search_camera: String::new(),
+            search_camera_make: None,
+            search_mime: None,
+            camera_make_options: Vec::new(),
+            mime_options: Vec::new(),
+            search_start: String::new(),
+            search_end: String::new(),
+            search_favorite: false,
+            search_faces: false,
+            search_person: None,
+            search_album: None,
+            person_options: iced::widget::combo_box::State::new(Vec::new()),
+            error_log_path,
+            settings_open: open_settings,
+            error_log: None,
+            settings_account_open: false,
+            status_bar: status_bar::StatusBar::default(),
+            account_menu: accounts::AccountMenu::default(),
+            cache_db_path: cache_path.clone(),
+            config_watcher: app_config::ConfigWatcher::new(config_path.clone(), profile.clone()),
+            config_path,
+            profile,
+            startup_config: cfg.clone(),
+            restart_required: Vec::new(),
+            sync_interval_minutes: cfg.sync_interval_minutes,
+            settings_log_level: cfg.log_level.clone(),
+            settings_cache_path: cfg.cache_path.to_string_lossy().to_string(),
+            settings_oauth_port: cfg.oauth_redirect_port.to_string(),
+            settings_thumbnails_preload: cfg.thumbnails_preload.to_string(),
+            settings_preload_threads: cfg.preload_threads.to_string(),
+            settings_sync_interval: cfg.sync_interval_minutes.to_string(),
+            settings_debug_console: cfg.debug_console,
+            settings_trace_spans: cfg.trace_spans,
+            editing_face: None,
+            face_name_input: String::new(),
+            detecting_faces: std::collections::HashSet::new(),
+            cluster_offer: None,
+            selection: std::collections::HashSet::new(),
+            selection_anchor: None,
+            modifiers: keyboard::Modifiers::default(),
+            focused: None,
+            grid_viewport: (0.0, 0.0),
+            removing_photos: None,
+            info_open: false,
+            item_albums: Vec::new(),
+            editing_description: false,
+            description_input: String::new(),
+            slideshow_interval: cfg.slideshow_interval_secs.max(1),
+            slideshow_play_videos: cfg.slideshow_play_videos,
+            prefetch_after_sync: cfg.prefetch_after_sync,
+            favorites_album_sync: cfg.favorites_album_sync,
+            min_face_size: cfg.min_face_size,
+            face_detector: cfg.face_detector.clone(),
+            preload_threads,
+            #[cfg(feature = "gstreamer")]
+            slideshow_resume: None,
+            #[cfg(feature = "gstreamer")]
+            video_download: None,
+            video_cache: VideoCache::open(cache_dir.join("videos"), cfg.video_cache_max_mb),
+            settings_slideshow_interval: cfg.slideshow_interval_secs.to_string(),
+            settings_image_cache_max: cfg.image_cache_max_mb.to_string(),
+            image_cache_usage: None,
+            settings_video_cache_max: cfg.video_cache_max_mb.to_string(),
+            video_cache_usage: None,
+            confirm_reset_cache: false,
+            settings_errors: std::collections::HashMap::new(),
+            settings_slideshow_videos: cfg.slideshow_play_videos,
+            timeline: timeline::Timeline::default(),
+            theme_setting: cfg.theme.clone(),
+            settings_theme: cfg.theme.clone(),
+            system_dark: style::system_prefers_dark(),
+            settings_language: i18n::language(),
+            notifications: Vec::new(),
+            people: people::People::default(),
+            drag: drag::Drag::default(),
+            zoom: Zoom::default(),
+            export: None,
+            uploads: upload::Uploads::default(),
+            restore_scroll: (saved.scroll_offset > 0.0).then_some(saved.scroll_offset),
+            restore_focus: None,
+            grid_return_offset: None,
+            pre_search: None,
+            ui_state: saved,
+            ui_state_path,
+            next_notification_id: 0,
+        };
+        #[cfg(feature = "trace-spans")]
+        {
+            sys.refresh_memory();
+            tracing::info!(target = "ui", "init_time_ms" = start.elapsed().as_millis(), "mem_before_kb" = mem_before, "mem_after_kb" = sys.used_memory());
+        }
+
+        // Photos, albums and the rest of the library follow on `CacheReady`
+        let commands = Command::batch(vec![
+            startup::open(cache_path),
+            Command::perform(async {}, |_| Message::StatusBarTick),
+            app.load_accounts(),
+            app.load_user_info(),
+        ]);
+        (app, commands)
+    }
+
+    fn title(&self) -> String {
+        tr("app.title").to_string()
+    }
+
+    fn theme(&self) -> Theme {
+        style::theme_for(&self.theme_setting, self.system_dark)
+    }
+
+    #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
+    fn update(&mut self, message: Message) -> Command<Message> {
+        match message {
+            Message::LoadPhotos => {
+                self.loading = true;
+                // Results of a search still running must not replace the library
+                self.search_generation += 1;
+                self.search_more = 0;
+                self.timeline.clear_counts();
+                self.album_refresh_pending = None;
+                if let (Some(cluster), Some(cm)) = (self.people.selected_cluster, &self.cache_manager) {
+                    let cm = cm.clone();
+                    return Command::perform(
+                        async move {
+                            let cache = {
+                                let guard = cm.lock().await;
+                                guard.clone()
+                            };
+                            cache
+                                .get_media_items_by_cluster_async(cluster)
+                                .await
+                                .map_err(|e| e.to_string())
+                        },
+                        Message::PhotosLoaded,
+                    );
+                }
+                if let (Some(person), Some(cm)) = (&self.people.selected, &self.cache_manager) {
+                    let person = person.clone();
+                    let cm = cm.clone();
+                    return Command::perform(
+                        async move {
+                            let cache = {
+                                let guard = cm.lock().await;
+                                guard.clone()
+                            };
+                            cache
+                                .get_media_items_by_person_async(person)
+                                .await
+                                .map_err(|e| e.to_string())
+                        },
+                        Message::PhotosLoaded,
+                    );
+                }
+                if let Some(cm) = self.cache_manager.as_ref().filter(|_| self.favorites_only) {
+                    let cm = cm.clone();
+                    return Command::perform(
+                        async move {
+                            let cache = {
+                                let guard = cm.lock().await;
+                                guard.clone()
+                            };
+                            cache
+                                .get_favorite_media_items_async()
+                                .await
+                                .map_err(|e| e.to_string())
+                        },
+                        Message::PhotosLoaded,
+                    );
+                }
+                if let (Some(album_id), Some(cm)) = (&self.selected_album, &self.cache_manager) {
+                    // The refresh starts once these are shown, so it cannot be overwritten by them
+                    if !self.offline() {
+                        self.album_refresh_pending = Some(album_id.clone());
+                    }
+                    let album_id = album_id.clone();
+                    let cm = cm.clone();
+                    return Command::perform(
+                        async move {
+                            let cache = {
+                                let guard = cm.lock().await;
+                                guard.clone()
+                            };
+                            cache
+                                .get_media_items_by_album_async(album_id)
+                                .await
+                                .map_err(|e| e.to_string())
+                        },
+                        Message::PhotosLoaded,
+                    );
+                }
+                if let Some(album_id) = &self.selected_album {
                     let album_id = album_id.clone();
+                    let profile = self.profile.clone();
+                    return Command::perform(
+                        async move {
+                            let token = auth::ensure_access_token_valid_for(profile.as_deref())
+                                .await
+                                .map_err(|e| e.to_string())?;
+                            let client = ApiClient::new(token);
+                            client
+                                .get_album_media_items(&album_id, 100, None)
+                                .await
+                                .map(|r| r.0)
+                                .map_err(|e| e.to_string())
+                        },
+                        Message::PhotosLoaded,
+                    );
+                } else if let Some(cache_manager) = &self.cache_manager {
+                    let cache_manager = cache_manager.clone();
+                    return Command::batch([
+                        Command::perform(
+                            async move {
+                                let cache = {
+                                    let guard = cache_manager.lock().await;
+                                    guard.clone()
+                                };
+                                cache
+                                    .get_all_media_items_async()
+                                    .await
+                                    .map_err(|e| e.to_string())
+                            },
+                            Message::PhotosLoaded,
+                        ),
+                        self.load_period_counts(),
+                    ]);
+                }
+            }
+            Message::PhotosLoaded(result) => {
+                self.loading = false;
+                let refresh = self.album_refresh();
+                match result {
+                    Ok(mut photos) => {
+                        // Album contents come from the API, which still lists trashed items
+                        photos.retain(|p| !self.trash.contains(&p.id));
+                        self.photos = photos;
+                        timeline::sort_photos(&mut self.photos);
+                        self.index_photos();
+                        self.update_filter_options();
+                        self.display_limit = PAGE_SIZE.min(self.photos.len());
+                        self.pending_thumbnails.clear();
+                        self.failed_thumbnails.clear();
+                        self.thumbnail_queue.clear();
+                        // Start with the configured preload count; scrolling extends the window
+                        self.thumb_window = 0..self.preload_count;
+                        if let Some(id) = self.restore_focus.take() {
+                            self.focused = self.photos.iter().position(|p| p.id == id);
+                        }
+                        if let Some(top) = self.restore_scroll.take() {
+                            return Command::batch([self.restore_grid_offset(top), refresh]);
+                        }
+                        return Command::batch([self.request_visible_thumbnails(), refresh]);
+                    }
+                    Err(error) => {
+                        // Without a cache album contents come from the API
+                        if self.selected_album.is_some() && self.cache_manager.is_none() {
+                            self.status_bar.record(Some(&error));
+                        }
+                        return Command::batch([
+                            self.report_error(format!("{}: {}", tr("error.load_photos"), error)),
+                            refresh,
+                        ]);
+                    }
+                }
+            }
+            Message::AlbumRefreshed(album_id, result) => {
+                self.status_bar.record(result.as_ref().err().map(String::as_str));
+                // Another view or a search took over the grid meanwhile
+                if self.selected_album.as_deref() != Some(album_id.as_str())
+                    || self.pre_search.is_some()
+                    || self.trash.open
+                {
+                    return Command::none();
+                }
+                match result {
+                    Ok(items) => {
+                        if album_contents::merge(&mut self.photos, items, &self.trash) {
+                            timeline::sort_photos(&mut self.photos);
+                            self.index_photos();
+                            self.update_filter_options();
+                            let photo_index = &self.photo_index;
+                            self.selection.retain(|id| photo_index.contains_key(id));
+                            let len = self.photos.len();
+                            self.display_limit = self.display_limit.min(len).max(PAGE_SIZE.min(len));
+                            self.focused = self.focused.map(|i| i.min(len.saturating_sub(1)));
+                            let mut commands = vec![self.request_visible_thumbnails()];
+                            // The open photo was removed from the album upstream
+                            if self.selected_photo_id().is_some_and(|id| !self.photo_index.contains_key(&id)) {
+                                commands.push(self.return_to_grid());
+                            }
+                            return Command::batch(commands);
+                        }
+                    }
+                    Err(error) => {
+                        tracing::warn!("Album refresh failed: {}", error);
+                        return self.notify(NotificationLevel::Warning, trf("toast.album_refresh_failed", &[&error]));
+                    }
+                }
+            }
+            Message::LoadAlbums => {
+                let cache_manager = self.cache_manager.clone();
+                if let Some(cm) = cache_manager.clone().filter(|_| self.offline()) {
+                    return Command::perform(
+                        async move {
+                            let cache = {
+                                let guard = cm.lock().await;
+                                guard.clone()
+                            };
+                            cache.get_all_albums_async().await.map_err(|e| e.to_string())
+                        },
+                        Message::CachedAlbumsLoaded,
+                    );
+                }
+                let profile = self.profile.clone();
+                return Command::perform(
+                    async move {
+                        let token = auth::ensure_access_token_valid_for(profile.as_deref())
+                            .await
+                            .map_err(|e| e.to_string())?;
+                        let client = ApiClient::new(token);
+                        let albums = client
+                            .list_albums(50, None)
+                            .await
+                            .map(|r| r.0)
+                            .map_err(|e| e.to_string())?;
+                        // Kept for offline use
+                        if let Some(cm) = cache_manager {
+                            let cache = {
+                                let guard = cm.lock().await;
+                                guard.clone()
+                            };
+                            for album in &albums {
+                                if let Err(e) = cache.insert_album_async(album.clone()).await {
+                                    tracing::warn!(error = %e, "Failed to cache album");
+                                }
+                            }
+                        }
+                        Ok(albums)
+                    },
+                    Message::AlbumsLoaded,
+                );
+            }
+            Message::AlbumsLoaded(result) => match result {
+                Ok(mut albums) => {
+                    self.status_bar.record(None);
+                    self.status_bar.reconnected = false;
+                    // The server still has albums whose delete waits on undo
+                    albums.retain(|a| !self.pending_album_deletes.contains_key(&a.id));
+                    self.albums = albums;
+                    return Command::batch([self.load_album_counts(), self.load_album_covers()]);
+                }
+                Err(err) => {
+                    self.status_bar.record(Some(&err));
+                    let error = self.report_error(format!("{}: {}", tr("error.load_albums"), err));
+                    if self.offline() && self.cache_manager.is_some() {
+                        return Command::batch([error, Command::perform(async {}, |_| Message::LoadAlbums)]);
+                    }
+                    return error;
+                }
+            },
+            Message::CachedAlbumsLoaded(result) => match result {
+                Ok(mut albums) => {
+                    albums.retain(|a| !self.pending_album_deletes.contains_key(&a.id));
+                    // Albums listed before going offline are better than none
+                    if !albums.is_empty() || self.albums.is_empty() {
+                        self.albums = albums;
+                    }
+                    return Command::batch([self.load_album_counts(), self.load_album_covers()]);
+                }
+                Err(err) => return self.report_error(format!("{}: {}", tr("error.load_albums"), err)),
+            },
+            Message::CacheMigrating(step, steps) => {
+                if self.cache_status != startup::CacheStatus::Ready {
+                    self.cache_status = startup::CacheStatus::Migrating { step, steps };
+                }
+            }
+            Message::CacheReady(db_path, result) => {
+                // Left over from before an account switch
+                if db_path != self.cache_db_path {
+                    return Command::none();
+                }
+                self.cache_status = startup::CacheStatus::Ready;
+                let mut commands = Vec::new();
+                match result {
+                    Ok(opened) => {
+                        let cm = Arc::new(Mutex::new(opened.cache));
+                        self.cache_manager = Some(cm.clone());
+                        match opened.last_synced {
+                            Ok(ts) => self.last_synced = Some(ts),
+                            Err(e) => commands.push(self.report_error(format!("{}: {}", tr("error.read_last_sync"), e))),
+                        }
+                        let loader = self.image_loader.clone();
+                        let refresher = Self::url_refresher(cm, self.profile.clone());
+                        // Photos load once the loader can refresh their expired URLs
+                        commands.push(Command::perform(
+                            async move { loader.lock().await.set_url_refresher(refresher) },
+                            |_| Message::LoadPhotos,
+                        ));
+                        commands.push(self.load_video_durations());
+                        commands.push(self.load_trash());
+                        commands.push(self.load_person_options());
+                    }
+                    Err(e) => {
+                        commands.push(self.report_error(format!("{}: {}", tr("error.init_cache"), e)));
+                        commands.push(Command::perform(async {}, |_| Message::LoadPhotos));
+                    }
+                }
+                if !self.syncing {
+                    self.sync_status = match self.last_synced {
+                        Some(ts) => trf("status.last_synced", &[&ts.to_rfc3339()]),
+                        None => tr("status.never_synced").to_string(),
+                    };
+                }
+                commands.push(Command::perform(async {}, |_| Message::LoadAlbums));
+                return Command::batch(commands);
+            }
+            Message::RefreshPhotos => {
+                return Command::batch(vec![
+                    Command::perform(async {}, |_| Message::LoadPhotos),
+                    Command::perform(async {}, |_| Message::LoadAlbums),
+                ]);
+            }
+            Message::LoadMorePhotos => {
+                self.display_limit = (self.display_limit + PAGE_SIZE).min(self.photos.len());
+            }
+            Message::GridScrolled(viewport) => {
+                let offset = viewport.absolute_offset().y;
+                let height = viewport.bounds().height;
+                self.grid_viewport = (offset, height);
+                self.set_thumb_window(offset, height);
+                if viewport.relative_offset().y >= 0.9 && self.display_limit < self.photos.len() {
+                    self.display_limit = (self.display_limit + PAGE_SIZE).min(self.photos.len());
+                }
+                return self.request_visible_thumbnails();
+            }
+            Message::ModifiersChanged(modifiers) => {
+                self.modifiers = modifiers;
+            }
+            Message::ToggleSelect(id) => {
+                if !self.selection.remove(&id) {
+                    self.selection.insert(id.clone());
+                }
+                self.selection_anchor = Some(id);
+            }
+            Message::SelectRangeTo(id) => {
+                let anchor = self
+                    .selection_anchor
+                    .as_ref()
+                    .and_then(|a| self.photos.iter().position(|p| &p.id == a));
+                let target = self.photos.iter().position(|p| p.id == id);
+                match (anchor, target) {
+                    (Some(a), Some(t)) => {
+                        let (from, to) = if a <= t { (a, t) } else { (t, a) };
+                        for photo in &self.photos[from..=to] {
+                            self.selection.insert(photo.id.clone());
+                        }
+                    }
+                    _ => return self.update(Message::ToggleSelect(id)),
+                }
+            }
+            Message::ClearSelection => {
+                self.selection.clear();
+                self.selection_anchor = None;
+            }
+            Message::BatchAssignAlbum(album) => {
+                let ids = self.selected_ids();
+                return self.add_to_album(album, ids);
+            }
+            Message::BatchToggleFavorite => {
+                if let Some(cm) = &self.cache_manager {
+                    let cm = cm.clone();
+                    let ids = self.selected_ids();
+                    let mirror = self.favorites_album_sync;
+                    // Unfavorite only when every selected photo already is a favorite
+                    let fav = !ids.iter().all(|id| self.photo_is_favorite(id) == Some(true));
+                    return Command::perform(
+                        async move {
+                            let cache = {
+                                let guard = cm.lock().await;
+                                guard.clone()
+                            };
+                            let count = ids.len();
+                            cache
+                                .set_favorites_async(ids.clone(), fav)
+                                .await
+                                .map_err(|e| e.to_string())?;
+                            if mirror {
+                                cache
+                                    .enqueue_mutation_async(cache::Mutation::favorites(ids, fav))
+                                    .await
+                                    .map_err(|e| e.to_string())?;
+                            }
+                            Ok(trf("toast.batch_favorites", &[&count]))
+                        },
+                        Message::BatchCompleted,
+                    );
+                }
+            }
+            Message::BatchRemoveFromAlbum => {
+                if self.selected_album.is_some() && !self.selection.is_empty() {
+                    self.removing_photos = Some(self.selected_ids());
+                }
+            }
+            Message::ExportPhotos(ids) => {
+                if self.export.is_some() {
+                    return self.notify(NotificationLevel::Warning, tr("toast.export_busy"));
+                }
+                return Command::perform(
+                    async {
+                        AsyncFileDialog::new()
+                            .pick_folder()
+                            .await
+                            .map(|f| f.path().to_path_buf())
+                    },
+                    move |dir| Message::ExportTo(ids, dir),
+                );
+            }
+            Message::ExportTo(ids, Some(dir)) => {
+                let queue: std::collections::VecDeque<MediaItem> = self
+                    .photos
+                    .iter()
+                    .filter(|p| ids.contains(&p.id))
+                    .cloned()
+                    .collect();
+                self.export = Some(ExportJob { dir, total: queue.len(), queue, done: 0, failed: 0 });
+                return self.export_next();
+            }
+            Message::ExportTo(_, None) => {}
+            Message::ExportProgress(filename, result) => {
+                let Some(job) = self.export.as_mut() else {
+                    return Command::none();
+                };
+                job.done += 1;
+                let mut commands = Vec::new();
+                match result {
+                    Ok(path) => tracing::info!("Exported {} to {}", filename, path.display()),
+                    Err(err) => {
+                        job.failed += 1;
+                        self.log_error(&format!("Export of {} failed: {}", filename, err));
+                        commands.push(self.notify(
+                            NotificationLevel::Warning,
+                            trf("toast.export_failed", &[&filename, &err]),
+                        ));
+                    }
+                }
+                commands.push(self.export_next());
+                return Command::batch(commands);
+            }
+            Message::FileDropped(path) => {
+                if !upload::supported(&path) {
+                    return self.notify(
+                        NotificationLevel::Warning,
+                        trf("upload.unsupported", &[&path.display()]),
+                    );
+                }
+                // A new batch goes to the album open when it is dropped
+                if !self.uploads.is_busy() {
+                    self.uploads.album = self.selected_album.as_ref().and_then(|id| {
+                        self.albums.iter().find(|a| &a.id == id).map(|a| AlbumOption {
+                            id: a.id.clone(),
+                            title: a.title.clone().unwrap_or_else(|| tr("common.untitled").into()),
+                        })
+                    });
+                }
+                self.uploads.queue(path);
+                return self.upload_next();
+            }
+            Message::UploadFinished(path, result) => {
+                self.status_bar.record(result.as_ref().err().map(String::as_str));
+                let mut commands = Vec::new();
+                match result {
+                    Ok(item) => {
+                        self.uploads.finish(&path, upload::UploadStatus::Done(item.id.clone()));
+                        // Album and search views only show the upload once reloaded
+                        let library = self.selected_album.is_none()
+                            && !self.people.has_selection()
+                            && self.pre_search.is_none()
+                            && !self.favorites_only;
+                        if library && !self.photos.iter().any(|p| p.id == item.id) {
+                            self.photos.push(item);
+                            timeline::sort_photos(&mut self.photos);
+                            self.index_photos();
+                            self.display_limit = (self.display_limit + 1).min(self.photos.len());
+                            commands.push(self.relayout_grid());
+                        }
+                    }
+                    Err(err) => {
+                        self.log_error(&format!("Upload of {} failed: {}", path.display(), err));
+                        self.uploads.finish(&path, upload::UploadStatus::Failed(err));
+                        // Sign-ins from before uploads were supported lack the permission
+                        if auth::reauth_required_for(self.profile.as_deref()) {
+                            self.account_menu.reauth_required = true;
+                            self.sync_status = tr("status.reauth_required").into();
+                        }
+                    }
+                }
+                commands.push(self.upload_next());
+                return Command::batch(commands);
+            }
+            Message::RetryUpload(path) => {
+                if self.uploads.queue(path) {
+                    return self.upload_next();
+                }
+            }
+            Message::AddUploadsToAlbum => {
+                let ids = self.uploads.uploaded_ids();
+                if let (Some(album), false) = (self.uploads.album.take(), ids.is_empty()) {
+                    return self.add_to_album(album, ids);
+                }
+            }
+            Message::DismissUploads => {
+                if !self.uploads.is_busy() {
+                    self.uploads = upload::Uploads::default();
+                }
+            }
+            Message::BatchCompleted(result) => {
+                self.status_bar.record(result.as_ref().err().map(String::as_str));
+                match result {
+                    Ok(summary) => {
+                        tracing::info!("{}", summary);
+                        self.selection.clear();
+                        self.selection_anchor = None;
+                        return Command::batch(vec![
+                            self.notify(NotificationLevel::Success, summary),
+                            Command::perform(async {}, |_| Message::LoadPhotos),
+                            self.load_album_counts(),
+                        ]);
+                    }
+                    Err(err) => {
+                        let msg = format!("{}: {}", tr("error.batch"), err);
+                        self.errors.push(msg.clone());
+                        self.log_error(&msg);
+                        return GooglePiczUI::error_timeout();
+                    }
+                }
+            }
+            Message::ToggleFavorite(id) => {
+                // The Library API cannot set favorites; with favorites album sync the
+                // next sync mirrors the change into the favorites album instead
+                if let Some(fav) = self.flip_favorite(&id) {
+                    if let Some(cm) = &self.cache_manager {
+                        let cm = cm.clone();
+                        let id_clone = id.clone();
+                        let mirror = self.favorites_album_sync;
+                        return Command::perform(
+                            async move {
+                                let cache = {
+                                    let guard = cm.lock().await;
+                                    guard.clone()
+                                };
+                                cache
+                                    .set_favorite_async(id_clone.clone(), fav)
+                                    .await
+                                    .map_err(|e| e.to_string())?;
+                                if mirror {
+                                    cache
+                                        .enqueue_mutation_async(cache::Mutation::favorites(vec![id_clone], fav))
+                                        .await
+                                        .map_err(|e| e.to_string())?;
+                                }
+                                Ok(())
+                            },
+                            move |res| Message::FavoriteUpdated(id, res),
+                        );
+                    }
+                }
+            }
+            Message::FavoriteUpdated(id, result) => {
+                if let Err(e) = result {
+                    self.flip_favorite(&id);
+                    let msg = format!("{}: {}", tr("error.update_favorite"), e);
+                    self.errors.push(msg.clone());
+                    self.log_error(&msg);
+                    return GooglePiczUI::error_timeout();
+                }
+            }
+            Message::KeyPressed(key) => {
+                if self.dialog_open() {
+                    return Command::none();
+                }
+                use iced::keyboard::KeyCode;
+                if matches!(key, KeyCode::Home | KeyCode::End) {
+                    if !self.album_sidebar.hovered {
+                        return Command::none();
+                    }
+                    let to = if key == KeyCode::Home { album_sidebar::Scroll::Start } else { album_sidebar::Scroll::End };
+                    return self.album_sidebar.scroll(to);
+                }
+                match &self.state {
+                    ViewState::Grid => match key {
+                        KeyCode::Left => return self.move_focus(-1),
+                        KeyCode::Right => return self.move_focus(1),
+                        KeyCode::Up => return self.move_focus(-(self.grid_columns() as isize)),
+                        KeyCode::Down => return self.move_focus(self.grid_columns() as isize),
+                        KeyCode::Enter => {
+                            if let Some(photo) = self.focused.and_then(|i| self.photos.get(i)).cloned() {
+                                return self.update(Message::SelectPhoto(photo));
+                            }
+                        }
+                        KeyCode::Space => {
+                            if let Some(id) = self.focused.and_then(|i| self.photos.get(i)).map(|p| p.id.clone()) {
+                                return self.update(Message::ToggleFavorite(id));
+                            }
+                        }
+                        KeyCode::Delete => {
+                            if let Some(id) = self.focused.and_then(|i| self.photos.get(i)).map(|p| p.id.clone()) {
+                                return self.update(Message::ShowRemovePhotoDialog(id));
+                            }
+                        }
+                        _ => {}
+                    },
+                    ViewState::SelectedPhoto { photo, .. } => {
+                        let id = photo.id.clone();
+                        match key {
+                            KeyCode::Left => return self.step_photo(-1),
+                            KeyCode::Right => return self.step_photo(1),
+                            KeyCode::Space => return self.update(Message::ToggleFavorite(id)),
+                            KeyCode::I => return self.update(Message::ToggleInfoPanel),
+                            KeyCode::F11 => return self.update(Message::ToggleFullscreen),
+                            #[cfg(feature = "clipboard")]
+                            KeyCode::C => return self.update(Message::CopyImage),
+                            KeyCode::Delete => return self.update(Message::ShowRemovePhotoDialog(id)),
+                            KeyCode::Plus | KeyCode::Equals | KeyCode::NumpadAdd => {
+                                self.zoom = self.zoom.zoomed(ZOOM_STEP);
+                            }
+                            KeyCode::Minus | KeyCode::NumpadSubtract => {
+                                self.zoom = self.zoom.zoomed(1.0 / ZOOM_STEP);
+                            }
+                            KeyCode::Key0 | KeyCode::Numpad0 => self.zoom = Zoom::default(),
+                            _ => {}
+                        }
+                    }
+                    ViewState::Slideshow { index, .. } => {
+                        let index = *index;
+                        match key {
+                            KeyCode::Space => return self.update(Message::ToggleSlideshow),
+                            KeyCode::F11 => return self.update(Message::ToggleFullscreen),
+                            KeyCode::Left | KeyCode::Right => {
+                                let step = if key == KeyCode::Left { -1 } else { 1 };
+                                if let Some(next) = self.next_slide(index, step) {
+                                    return self.show_slide(next, false);
+                                }
+                            }
+                            _ => {}
+                        }
+                    }
+                    #[cfg(feature = "gstreamer")]
+                    ViewState::PlayingVideo { .. } => {}
+                }
+            }
+            Message::ShowRemovePhotoDialog(id) => {
+                // Photos can only be taken out of albums; the library itself is read-only
+                if self.selected_album.is_some() {
+                    self.removing_photos = Some(vec![id]);
+                }
+            }
+            Message::CancelRemovePhoto => {
+                self.removing_photos = None;
+            }
+            Message::ConfirmRemovePhoto => {
+                if let (Some(ids), Some(album_id)) = (self.removing_photos.take(), self.selected_album.clone()) {
+                    let cache_manager = self.cache_manager.clone();
+                    let (album, removed) = (album_id.clone(), ids.clone());
+                    let profile = self.profile.clone();
+                    return Command::perform(
+                        async move {
+                            let token = auth::ensure_access_token_valid_for(profile.as_deref())
+                                .await
+                                .map_err(|e| e.to_string())?;
+                            let client = ApiClient::new(token);
+                            match ids.as_slice() {
+                                [id] => client.remove_media_item_from_album(&album_id, id).await,
+                                _ => client.batch_remove_media_items(&album_id, &ids).await,
+                            }
+                            .map_err(|e| e.to_string())?;
+                            // The cache only follows once the server accepted the change
+                            if let Some(cm) = cache_manager {
+                                let cache = {
+                                    let guard = cm.lock().await;
+                                    guard.clone()
+                                };
+                                cache
+                                    .remove_media_items_from_album_async(ids, album_id)
+                                    .await
+                                    .map_err(|e| e.to_string())?;
+                            }
+                            Ok(())
+                        },
+                        move |result| Message::PhotoRemoved(album, removed, result),
+                    );
+                }
+            }
+            Message::PhotoRemoved(album_id, ids, result) => {
+                self.status_bar.record(result.as_ref().err().map(String::as_str));
+                match result {
+                    Ok(()) => {
+                        let mut commands = vec![self.load_album_counts()];
+                        if self.selected_album.as_deref() == Some(album_id.as_str()) {
+                            self.photos.retain(|p| !ids.contains(&p.id));
+                            self.index_photos();
+                            self.selection.retain(|id| !ids.contains(id));
+                            self.display_limit = self.display_limit.min(self.photos.len());
+                            self.focused = self.focused.map(|i| i.min(self.photos.len().saturating_sub(1)));
+                            if self.selected_photo_id().is_some_and(|id| ids.contains(&id)) {
+                                commands.push(self.return_to_grid());
+                            }
+                        }
+                        let text = if ids.len() == 1 {
+                            tr("toast.photo_removed").to_string()
+                        } else {
+                            trf("toast.batch_removed", &[&ids.len()])
+                        };
+                        commands.push(self.notify_with_action(
+                            NotificationLevel::Success,
+                            text,
+                            Some(NotificationAction {
+                                label: tr("toast.undo").to_string(),
+                                message: Message::UndoRemoveFromAlbum(album_id, ids),
+                            }),
+                        ));
+                        return Command::batch(commands);
+                    }
+                    Err(err) => {
+                        let msg = format!("{}: {}", tr("error.remove_photo"), err);
+                        self.errors.push(msg.clone());
+                        self.log_error(&msg);
+                        return GooglePiczUI::error_timeout();
+                    }
+                }
+            }
+            Message::UndoRemoveFromAlbum(album_id, ids) => {
+                self.notifications.retain(|n| {
+                    !matches!(
+                        &n.action,
+                        Some(NotificationAction { message: Message::UndoRemoveFromAlbum(a, i), .. })
+                            if *a == album_id && *i == ids
+                    )
+                });
+                let title = self
+                    .albums
+                    .iter()
+                    .find(|a| a.id == album_id)
+                    .and_then(|a| a.title.clone())
+                    .unwrap_or_else(|| tr("common.untitled").into());
+                // Adding them back reloads the photos, which brings the tiles back
+                return self.add_to_album(AlbumOption { id: album_id, title }, ids);
+            }
+            Message::LoadThumbnail(media_id, base_url, video, priority) => {
+                // Requests queued before a long scroll are dropped instead of downloaded
+                if !self.near_window(&media_id) {
+                    self.pending_thumbnails.remove(&media_id);
+                    self.thumbnail_queue.finish(&media_id);
+                    return self.load_queued_thumbnails();
+                }
+                let image_loader = self.image_loader.clone();
+                let id_clone = media_id.clone();
+                let base_clone = base_url.clone();
+                let size = image_loader::thumbnail_tier(self.ui_state.tile_size);
+                let cancel = self.thumbnail_queue.token(&media_id).unwrap_or_default();
+                return Command::perform(
+                    async move {
+                        // Release the lock right away so loads run side by side
+                        let loader = image_loader.lock().await.clone();
+                        loader
+                            .load_thumbnail_with(&id_clone, &base_clone, size, video, priority, &cancel)
+                            .await
+                    },
+                    move |result| Message::ThumbnailLoaded(media_id, result),
+                );
+            }
+            Message::ThumbnailLoaded(media_id, result) => {
+                self.thumbnail_queue.finish(&media_id);
+                let more = self.load_queued_thumbnails();
+                match result {
+                    Ok(handle) => {
+                        self.pending_thumbnails.remove(&media_id);
+                        self.failed_thumbnails.remove(&media_id);
+                        self.handles.insert(self.thumbnail_key(&media_id), handle);
+                        return more;
+                    }
+                    // Scrolled away before a worker was free; requested again when back in view
+                    Err(ImageLoaderError::Cancelled) => {
+                        self.pending_thumbnails.remove(&media_id);
+                        return more;
+                    }
+                    Err(error) => return Command::batch([more, self.thumbnail_failed(media_id, error)]),
+                }
+            }
+            Message::CancelThumbnailPreload => {
+                for media_id in self.thumbnail_queue.cancel() {
+                    self.pending_thumbnails.remove(&media_id);
+                }
+                // Narrow the preload window to what is on screen; those tiles still load
+                return self.relayout_grid();
+            }
+            Message::RetryThumbnail(media_id) => {
+                let Some((base_url, video)) = self
+                    .photos
+                    .iter()
+                    .find(|p| p.id == media_id)
+                    .map(|p| (p.base_url.clone(), p.mime_type.starts_with("video/")))
+                else {
+                    return Command::none();
+                };
+                let Some(error) = self.failed_thumbnails.remove(&media_id) else {
+                    return Command::none();
+                };
+                self.pending_thumbnails.insert(media_id.clone());
+                // Base URLs only live for about an hour; fetch a fresh one first
+                if error == ImageLoaderError::Expired {
+                    let ids = vec![media_id];
+                    let request = ids.clone();
+                    let profile = self.profile.clone();
+                    return Command::perform(
+                        async move {
+                            let token = auth::ensure_access_token_valid_for(profile.as_deref())
+                                .await
+                                .map_err(|e| e.to_string())?;
+                            ApiClient::new(token)
+                                .batch_get_media_items(&request)
+                                .await
+                                .map_err(|e| e.to_string())
+                        },
+                        move |result| Message::BaseUrlsRefreshed(ids, result),
+                    );
+                }
+                return self.update(Message::LoadThumbnail(media_id, base_url, video, Priority::Visible));
+            }
+            Message::BaseUrlsRefreshed(ids, result) => {
+                self.status_bar.record(result.as_ref().err().map(String::as_str));
+                match result {
+                    Ok(items) => {
+                        let mut commands = Vec::new();
+                        for id in ids {
+                            let base_url = items.iter().find(|i| i.id == id).map(|i| i.base_url.clone());
+                            let photo = self.photos.iter_mut().find(|p| p.id == id);
+                            let video = match (&base_url, photo) {
+                                (Some(base_url), Some(photo)) => {
+                                    photo.base_url = base_url.clone();
+                                    photo.mime_type.starts_with("video/")
+                                }
+                                _ => {
+                                    commands.push(self.thumbnail_failed(id, ImageLoaderError::NotFound));
+                                    continue;
+                                }
+                            };
+                            let base_url = base_url.unwrap_or_default();
+                            commands.push(self.update(Message::LoadThumbnail(id, base_url, video, Priority::Visible)));
+                        }
+                        return Command::batch(commands);
+                    }
+                    Err(error) => {
+                        let commands: Vec<_> = ids
+                            .into_iter()
+                            .map(|id| self.thumbnail_failed(id, ImageLoaderError::Network(error.clone())))
+                            .collect();
+                        return Command::batch(commands);
+                    }
+                }
+            }
+            Message::VideoDurationsLoaded(result) => match result {
+                Ok(durations) => self.video_durations = durations,
+                Err(e) => {
+                    let msg = format!("{}: {}", tr("error.load_video_durations"), e);
+                    self.errors.push(msg.clone());
+                    self.log_error(&msg);
+                    return GooglePiczUI::error_timeout();
+                }
+            },
+            #[cfg(feature = "gstreamer")]
+            Message::VideoFrameGrabbed(media_id, result) => match result {
+                Ok((handle, duration_ms)) => {
+                    self.handles.insert(self.thumbnail_key(&media_id), handle);
+                    self.video_durations.insert(media_id, duration_ms);
+                }
+                // The video still plays; only the nicer thumbnail is missing
+                Err(e) => tracing::warn!(id = %media_id, error = %e, "Failed to grab video frame"),
+            },
+            Message::SelectPhoto(photo) => {
+                let id = photo.id.clone();
+                let url = photo.base_url.clone();
+                self.leave_grid();
+                if let Some(index) = self.photos.iter().position(|p| p.id == id) {
+                    self.focused = Some(index);
+                }
+                self.state = ViewState::SelectedPhoto { photo, faces: Vec::new() };
+                // The grid is gone, so its hover state would go stale
+                self.drag = drag::Drag::default();
+                self.zoom = Zoom::default();
+                self.item_albums.clear();
+                self.editing_description = false;
+                self.description_input.clear();
+                self.cluster_offer = None;
+                let mut commands = vec![
+                    Command::perform(async {}, {
+                        let id = id.clone();
+                        move |_| Message::LoadFullImage(id.clone(), url.clone())
+                    }),
+                    Command::perform(async {}, {
+                        let id = id.clone();
+                        move |_| Message::LoadFaces(id.clone())
+                    }),
+                ];
+                // Fetch the neighbours too so next/prev shows up instantly
+                if let Some(index) = self.photos.iter().position(|p| p.id == id) {
+                    for neighbour in [index.checked_sub(1), Some(index + 1)].into_iter().flatten() {
+                        if let Some(p) = self.photos.get(neighbour) {
+                            if !self.handles.contains(&ImageKey::full(&p.id)) && !p.mime_type.starts_with("video/") {
+                                let (id, url) = (p.id.clone(), p.base_url.clone());
+                                commands.push(Command::perform(async {}, move |_| {
+                                    Message::LoadFullImage(id.clone(), url.clone())
+                                }));
+                            }
+                        }
+                    }
+                }
+                if let Some(cm) = &self.cache_manager {
+                    let cm = cm.clone();
+                    let id_clone = id.clone();
+                    commands.push(Command::perform(
+                        async move {
+                            let cache = { let guard = cm.lock().await; guard.clone() };
+                            cache
+                                .get_albums_for_media_item_async(id_clone)
+                                .await
+                                .map_err(|e| e.to_string())
+                        },
+                        move |res| Message::ItemAlbumsLoaded(id, res),
+                    ));
+                }
+                return Command::batch(commands);
+            }
+            Message::ToggleInfoPanel => {
+                self.info_open = !self.info_open;
+            }
+            Message::OpenContextMenu(id) => {
+                if !self.dialog_open() {
+                    self.context_menu = Some(context_menu::ContextMenu { media_id: id, albums_open: false });
+                }
+            }
+            Message::CloseContextMenu => {
+                self.context_menu = None;
+            }
+            Message::ContextMenuAlbums(open) => {
+                if let Some(menu) = &mut self.context_menu {
+                    menu.albums_open = open;
+                }
+            }
+            Message::ContextMenuPicked(message) => {
+                self.context_menu = None;
+                return self.update(*message);
+            }
+            Message::AddToAlbum(album, ids) => {
+                return self.add_to_album(album, ids);
+            }
+            Message::TileHovered(id) => {
+                self.drag.hovered_tile = Some(id);
+            }
+            Message::TileUnhovered(id) => {
+                if self.drag.hovered_tile.as_ref() == Some(&id) {
+                    self.drag.hovered_tile = None;
+                }
+            }
+            Message::AlbumHovered(id) => {
+                self.drag.hovered_album = Some(id);
+            }
+            Message::AlbumSidebarScrolled(viewport) => {
+                self.album_sidebar.viewport = album_sidebar::ListViewport::from_viewport(&viewport);
+            }
+            Message::AlbumSidebarHovered(hovered) => {
+                self.album_sidebar.hovered = hovered;
+            }
+            Message::ScrollAlbumSidebar(to) => {
+                return self.album_sidebar.scroll(to);
+            }
+            Message::ShowAllAlbums => {
+                self.album_sidebar.menu = Some(String::new());
+            }
+            Message::AllAlbumsFilterChanged(filter) => {
+                if self.album_sidebar.menu.is_some() {
+                    self.album_sidebar.menu = Some(filter);
+                }
+            }
+            Message::CloseAllAlbums => {
+                self.album_sidebar.menu = None;
+            }
+            Message::AlbumUnhovered(id) => {
+                if self.drag.hovered_album.as_ref() == Some(&id) {
+                    self.drag.hovered_album = None;
+                }
+            }
+            Message::DragPressed => {
+                if !matches!(self.state, ViewState::Grid) || self.dialog_open() {
+                    return Command::none();
+                }
+                if let Some(id) = self.drag.hovered_tile.clone() {
+                    // A selected tile carries the whole selection along
+                    self.drag.items = if self.selection.contains(&id) {
+                        self.selected_ids()
+                    } else {
+                        vec![id]
+                    };
+                    self.drag.moving = false;
+                }
+            }
+            Message::DragMoved => {
+                if !self.drag.items.is_empty() {
+                    self.drag.moving = true;
+                }
+            }
+            Message::DragReleased => {
+                let items = std::mem::take(&mut self.drag.items);
+                let moving = std::mem::replace(&mut self.drag.moving, false);
+                if !moving || items.is_empty() {
+                    return Command::none();
+                }
+                let target = self.drag.hovered_album.as_ref().and_then(|id| {
+                    self.albums.iter().find(|a| &a.id == id)
+                });
+                if let Some(album) = target {
+                    let album = AlbumOption {
+                        id: album.id.clone(),
+                        title: album.title.clone().unwrap_or_else(|| tr("common.untitled").into()),
+                    };
+                    return self.add_to_album(album, items);
+                }
+            }
+            Message::ZoomChanged(zoom) => {
+                self.zoom = zoom;
+            }
+            Message::WindowResized(width, height) => {
+                self.ui_state.width = width as f32;
+                self.ui_state.height = height as f32;
+                return self.relayout_grid();
+            }
+            Message::TileSizeChanged(size) => {
+                let before = image_loader::thumbnail_tier(self.ui_state.tile_size);
+                self.ui_state.tile_size = size.clamp(MIN_TILE_SIZE, MAX_TILE_SIZE);
+                if image_loader::thumbnail_tier(self.ui_state.tile_size) != before {
+                    // Refetch at the new size instead of stretching the old thumbnails
+                    self.pending_thumbnails.clear();
+                    self.failed_thumbnails.clear();
+                }
+                return self.relayout_grid();
+            }
+            Message::TileSizeReleased => {
+                self.save_ui_state();
+            }
+            Message::WindowMoved(x, y) => {
+                // Fullscreen moves the window to the screen origin
+                if self.fullscreen.is_none() {
+                    self.ui_state.position = Some((x, y));
+                }
+            }
+            Message::WindowCloseRequested(id) => {
+                if !self.pending_album_deletes.is_empty() {
+                    // Undo ends with the window; send the deletes before closing
+                    let pending: Vec<_> = self.pending_album_deletes.drain().map(|(_, p)| p).collect();
+                    let cache_manager = self.cache_manager.clone();
+                    let profile = self.profile.clone();
+                    return Command::perform(
+                        async move {
+                            for p in pending {
+                                let id = p.album.id.clone();
+                                if let Err(e) = album_delete::commit(cache_manager.clone(), p, profile.clone()).await {
+                                    tracing::warn!("Failed to delete album {}: {}", id, e);
+                                }
+                            }
+                        },
+                        move |_| Message::WindowCloseRequested(id),
+                    );
+                }
+                self.save_ui_state();
+                return iced::window::close(id);
+            }
+            Message::FocusRequested => {
+                return Command::batch([
+                    iced::window::minimize(iced::window::Id::MAIN, false),
+                    iced::window::gain_focus(iced::window::Id::MAIN),
+                ]);
+            }
+            Message::TogglePeoplePanel => {
+                self.people.open = !self.people.open;
+                let relayout = self.relayout_grid();
+                if self.people.open {
+                    return Command::batch([relayout, self.load_people()]);
+                }
+                return relayout;
+            }
+            Message::PeopleLoaded(result) => match result {
+                Ok(entries) => {
+                    // Samples may have moved after a rename or merge
+                    self.people.crops.clear();
+                    let commands: Vec<_> = entries.iter().map(|e| self.load_face_crop(e)).collect();
+                    self.people.entries = entries;
+                    return Command::batch(commands);
+                }
+                Err(e) => {
+                    let msg = format!("{}: {}", tr("error.load_people"), e);
+                    self.errors.push(msg.clone());
+                    self.log_error(&msg);
+                    return GooglePiczUI::error_timeout();
+                }
+            },
+            Message::FaceCropLoaded(key, result) => match result {
+                Ok(handle) => {
+                    self.people.crops.insert(key, handle);
+                }
+                Err(e) => {
+                    // The panel falls back to a placeholder icon
+                    tracing::warn!("Failed to crop face for {:?}: {}", key, e);
+                }
+            },
+            Message::SelectPerson(name) => {
+                self.trash.open = false;
+                self.selected_album = None;
+                self.favorites_only = false;
+                self.people.selected = Some(name);
+                self.people.selected_cluster = None;
+                self.pre_search = None;
+                self.search_album = None;
+                return Command::perform(async {}, |_| Message::LoadPhotos);
+            }
+            Message::SelectCluster(cluster) => {
+                self.trash.open = false;
+                self.selected_album = None;
+                self.favorites_only = false;
+                self.people.selected = None;
+                self.people.selected_cluster = Some(cluster);
+                self.pre_search = None;
+                self.search_album = None;
+                return Command::perform(async {}, |_| Message::LoadPhotos);
+            }
+            Message::StartRenamePerson(name) => {
+                self.people.merging = None;
+                self.people.rename_input = name.clone();
+                self.people.renaming = Some(name);
+            }
+            Message::PersonNameChanged(val) => {
+                self.people.rename_input = val;
+            }
+            Message::ConfirmRenamePerson => {
+                let Some(old) = self.people.renaming.take() else {
+                    return Command::none();
+                };
+                let new = self.people.rename_input.trim().to_string();
+                if new.is_empty() || new == old {
+                    return Command::none();
+                }
+                if self.people.selected.as_ref() == Some(&Some(old.clone())) {
+                    self.people.selected = Some(Some(new.clone()));
+                }
+                return self.change_people(move |cache| async move {
+                    cache
+                        .rename_person_async(old.clone(), new.clone())
+                        .await
+                        .map(|_| trf("toast.person_renamed", &[&old, &new]))
+                });
+            }
+            Message::StartMergePerson(name) => {
+                self.people.renaming = None;
+                self.people.merging = Some(name);
+            }
+            Message::MergePersonInto(keep) => {
+                let Some(remove) = self.people.merging.take() else {
+                    return Command::none();
+                };
+                if self.people.selected.as_ref() == Some(&Some(remove.clone())) {
+                    self.people.selected = Some(Some(keep.clone()));
+                }
+                return self.change_people(move |cache| async move {
+                    cache
+                        .merge_people_async(keep.clone(), remove.clone())
+                        .await
+                        .map(|_| trf("toast.people_merged", &[&remove, &keep]))
+                });
+            }
+            Message::CancelPersonAction => {
+                self.people.renaming = None;
+                self.people.merging = None;
+            }
+            Message::PeopleChanged(result) => match result {
+                Ok(summary) => {
+                    let mut commands = vec![
+                        self.notify(NotificationLevel::Success, summary),
+                        self.load_people(),
+                        self.load_person_options(),
+                    ];
+                    if self.people.has_selection() {
+                        commands.push(Command::perform(async {}, |_| Message::LoadPhotos));
+                    }
+                    return Command::batch(commands);
+                }
+                Err(e) => {
+                    let msg = format!("{}: {}", tr("error.update_people"), e);
+                    self.errors.push(msg.clone());
+                    self.log_error(&msg);
+                    return GooglePiczUI::error_timeout();
+                }
+            },
+            Message::ItemAlbumsLoaded(media_id, result) => {
+                if self.selected_photo_id().as_deref() == Some(media_id.as_str()) {
+                    match result {
+                        Ok(albums) => self.item_albums = albums,
+                        Err(e) => {
+                            let msg = format!("{}: {}", tr("error.load_photo_albums"), e);
+                            self.errors.push(msg.clone());
+                            self.log_error(&msg);
+                            return GooglePiczUI::error_timeout();
+                        }
+                    }
+                }
+            }
+            Message::StartEditDescription => {
+                if let ViewState::SelectedPhoto { photo, .. } = &self.state {
+                    self.description_input = photo.description.clone().unwrap_or_default();
+                    self.editing_description = true;
+                    self.info_open = true;
+                }
+            }
+            Message::DescriptionChanged(value) => {
+                self.description_input = value;
+            }
+            Message::CancelEditDescription => {
+                self.editing_description = false;
+                self.description_input.clear();
+            }
+            Message::SaveDescription => {
+                let Some(id) = self.selected_photo_id() else {
+                    return Command::none();
+                };
+                self.editing_description = false;
+                let description = std::mem::take(&mut self.description_input);
+                let cache_manager = self.cache_manager.clone();
+                let id_clone = id.clone();
+                let profile = self.profile.clone();
+                return Command::perform(
+                    async move {
+                        let token = auth::ensure_access_token_valid_for(profile.as_deref())
+                            .await
+                            .map_err(|e| e.to_string());
+                        let result = match token {
+                            Ok(token) => ApiClient::new(token)
+                                .update_media_item_description(&id_clone, &description)
+                                .await
+                                .map(|_| ()),
+                            Err(e) => Err(api_client::ApiClientError::RequestError(e)),
+                        };
+                        // Network failures keep the edit locally and replay it on the next sync
+                        let queued = match result {
+                            Ok(()) => false,
+                            Err(api_client::ApiClientError::RequestError(_)) if cache_manager.is_some() => true,
+                            Err(e) => return Err(e.to_string()),
+                        };
+                        if let Some(cm) = cache_manager {
+                            let cache = {
+                                let guard = cm.lock().await;
+                                guard.clone()
+                            };
+                            if queued {
+                                cache
+                                    .enqueue_mutation_async(cache::Mutation::UpdateDescription {
+                                        media_item_id: id_clone.clone(),
+                                        description: description.clone(),
+                                    })
+                                    .await
+                                    .map_err(|e| e.to_string())?;
+                            }
+                            cache
+                                .update_media_item_description_async(id_clone, description.clone())
+                                .await
+                                .map_err(|e| e.to_string())?;
+                        }
+                        Ok((description, queued))
+                    },
+                    move |res| Message::DescriptionSaved(id, res),
+                );
+            }
+            Message::DescriptionSaved(id, result) => match result {
+                Ok((description, queued)) => {
+                    let value = (!description.is_empty()).then_some(description);
+                    if let Some(photo) = self.photos.iter_mut().find(|p| p.id == id) {
+                        photo.description = value.clone();
+                    }
+                    if let ViewState::SelectedPhoto { photo, .. } = &mut self.state {
+                        if photo.id == id {
+                            photo.description = value;
+                        }
+                    }
+                    if queued {
+                        self.sync_status = tr("status.description_queued").into();
+                        return self.notify(NotificationLevel::Warning, tr("status.description_queued"));
+                    }
+                }
+                Err(err) => {
+                    let msg = format!("{}: {}", tr("error.update_description"), err);
+                    self.errors.push(msg.clone());
+                    self.log_error(&msg);
+                    return GooglePiczUI::error_timeout();
+                }
+            },
+            Message::StartSlideshow => {
+                let start = self.focused.unwrap_or(0).min(self.photos.len().saturating_sub(1));
+                let first = match self.photos.get(start) {
+                    Some(p) if self.slide_eligible(p) => Some(start),
+                    Some(_) => self.next_slide(start, 1),
+                    None => None,
+                };
+                match first {
+                    Some(index) => return self.show_slide(index, true),
+                    None => {
+                        let msg = tr("error.slideshow_empty").to_string();
+                        self.errors.push(msg.clone());
+                        self.log_error(&msg);
+                        return GooglePiczUI::error_timeout();
+                    }
+                }
+            }
+            Message::StartFullscreenSlideshow => {
+                let start = self.update(Message::StartSlideshow);
+                if let ViewState::Slideshow { .. } = self.state {
+                    return Command::batch([start, self.enter_fullscreen()]);
+                }
+                return start;
+            }
+            Message::ToggleFullscreen => {
+                if self.fullscreen.is_some() {
+                    return self.exit_fullscreen();
+                }
+                if let ViewState::SelectedPhoto { .. } | ViewState::Slideshow { .. } = self.state {
+                    return self.enter_fullscreen();
+                }
+            }
+            Message::FullscreenActivity => return self.fullscreen_activity(),
+            Message::HideFullscreenControls(activity) => {
+                if let Some(fullscreen) = &mut self.fullscreen {
+                    if fullscreen.activity == activity {
+                        fullscreen.controls_visible = false;
+                    }
+                }
+            }
+            Message::SlideshowTick => {
+                if let ViewState::Slideshow { index, playing: true } = self.state {
+                    if let Some(next) = self.next_slide(index, 1) {
+                        return self.show_slide(next, true);
+                    }
+                }
+            }
+            Message::ToggleSlideshow => {
+                if let ViewState::Slideshow { playing, .. } = &mut self.state {
+                    *playing = !*playing;
+                }
+            }
+            Message::TimelineGranularityChanged(granularity) => {
+                self.timeline.set_granularity(granularity);
+                if self.timeline.uses_library_counts() {
+                    return self.load_period_counts();
+                }
+            }
+            Message::ToggleSection(key) => {
+                self.timeline.toggle(&key);
+                if !self.timeline.is_collapsed(&key) {
+                    let (offset, height) = self.grid_viewport;
+                    self.set_thumb_window(offset, height);
+                    return self.request_visible_thumbnails();
+                }
+            }
+            Message::JumpDateChanged(value) => {
+                self.timeline.jump_input = value;
+            }
+            Message::JumpToDate => {
+                let Some(date) = timeline::parse_jump(&self.timeline.jump_input) else {
+                    let msg = tr("error.invalid_jump").to_string();
+                    self.errors.push(msg.clone());
+                    self.log_error(&msg);
+                    return GooglePiczUI::error_timeout();
+                };
+                // Search every loaded photo, not just the displayed page
+                let all = timeline::sections(&self.photos, self.timeline.granularity);
+                let Some(target) = timeline::jump_target(&all, &date).cloned() else {
+                    let msg = trf("error.jump_not_found", &[&date]);
+                    self.errors.push(msg.clone());
+                    self.log_error(&msg);
+                    return GooglePiczUI::error_timeout();
+                };
+                self.timeline.expand(&target.key);
+                if target.items.start >= self.display_limit {
+                    self.display_limit = (target.items.start + PAGE_SIZE).min(self.photos.len());
+                }
+                self.focused = Some(target.items.start);
+                let (_, layout) = timeline::layout(self);
+                let top = layout.section_top(&target.key).unwrap_or(0.0);
+                let height = self.grid_viewport.1;
+                self.grid_viewport.0 = top;
+                self.set_thumb_window(top, height);
+                return Command::batch([
+                    scrollable::scroll_to(grid_scroll_id(), scrollable::AbsoluteOffset { x: 0.0, y: top }),
+                    self.request_visible_thumbnails(),
+                ]);
+            }
+            Message::PeriodCountsLoaded(result) => match result {
+                Ok(counts) => self.timeline.set_counts(counts),
+                Err(e) => {
+                    let msg = format!("{}: {}", tr("error.load_period_counts"), e);
+                    self.errors.push(msg.clone());
+                    self.log_error(&msg);
+                    return GooglePiczUI::error_timeout();
+                }
+            },
+            Message::SelectAlbum(album_id) => {
+                self.trash.open = false;
+                self.album_sidebar.menu = None;
+                self.selected_album = album_id;
+                self.favorites_only = false;
+                self.people.selected = None;
+                self.people.selected_cluster = None;
+                self.pre_search = None;
+                self.search_album = None;
+                self.ui_state.album = self.selected_album.clone();
+                self.save_ui_state();
+                return Command::perform(async {}, |_| Message::LoadPhotos);
+            }
+            Message::SelectFavorites => {
+                self.trash.open = false;
+                self.selected_album = None;
+                self.people.selected = None;
+                self.people.selected_cluster = None;
+                self.favorites_only = true;
+                self.pre_search = None;
+                self.search_album = None;
+                self.ui_state.album = None;
+                self.save_ui_state();
+                return Command::perform(async {}, |_| Message::LoadPhotos);
+            }
+            Message::SelectTrash => {
+                self.trash.open = true;
+                self.context_menu = None;
+                self.selection.clear();
+                return self.load_trash();
+            }
+            Message::TrashLoaded(result) => match result {
+                Ok(items) => self.trash.set_items(items),
+                Err(e) => {
+                    let msg = format!("{}: {}", tr("error.load_trash"), e);
+                    self.errors.push(msg.clone());
+                    self.log_error(&msg);
+                    return GooglePiczUI::error_timeout();
+                }
+            },
+            Message::MoveToTrash(ids) => {
+                if let Some(cm) = self.cache_manager.clone() {
+                    let trashed = ids.clone();
                     return Command::perform(
                         async move {
-                            let token = auth::ensure_access_token_valid()
-                                .await
-                                .map_err(|e| e.to_string())?;
-                            let client = ApiClient::new(token);
-                            client
-                                .get_album_media_items(&album_id, 100, None)
+                            let cache = {
+                                let guard = cm.lock().await;
+                                guard.clone()
+                            };
+                            cache
+                                .trash_media_items_async(trashed)
                                 .await
-                                .map(|r| r.0)
                                 .map_err(|e| e.to_string())
                         },
-                        Message::PhotosLoaded,
+                        move |result| Message::PhotosTrashed(ids, result),
                     );
-                } else if let Some(cache_manager) = &self.cache_manager {
-                    let cache_manager = cache_manager.clone();
+                }
+            }
+            Message::PhotosTrashed(ids, result) => match result {
+                Ok(()) => {
+                    self.photos.retain(|p| !ids.contains(&p.id));
+                    self.index_photos();
+                    self.selection.retain(|id| !ids.contains(id));
+                    self.display_limit = self.display_limit.min(self.photos.len());
+                    self.focused = self.focused.map(|i| i.min(self.photos.len().saturating_sub(1)));
+                    let mut commands = vec![self.load_trash(), self.load_album_counts()];
+                    if self.selected_photo_id().is_some_and(|id| ids.contains(&id)) {
+                        commands.push(self.return_to_grid());
+                    }
+                    let text = if ids.len() == 1 {
+                        tr("toast.photo_trashed").to_string()
+                    } else {
+                        trf("toast.batch_trashed", &[&ids.len()])
+                    };
+                    commands.push(self.notify_with_action(
+                        NotificationLevel::Success,
+                        text,
+                        Some(NotificationAction {
+                            label: tr("toast.undo").to_string(),
+                            message: Message::RestoreFromTrash(ids),
+                        }),
+                    ));
+                    return Command::batch(commands);
+                }
+                Err(e) => {
+                    let msg = format!("{}: {}", tr("error.move_to_trash"), e);
+                    self.errors.push(msg.clone());
+                    self.log_error(&msg);
+                    return GooglePiczUI::error_timeout();
+                }
+            },
+            Message::RestoreFromTrash(ids) => {
+                self.notifications.retain(|n| {
+                    !matches!(
+                        &n.action,
+                        Some(NotificationAction { message: Message::RestoreFromTrash(i), .. }) if *i == ids
+                    )
+                });
+                if let Some(cm) = self.cache_manager.clone() {
+                    let restored = ids.clone();
                     return Command::perform(
                         async move {
                             let cache = {
-                                let guard = cache_manager.lock().await;
+                                let guard = cm.lock().await;
                                 guard.clone()
                             };
                             cache
-                                .get_all_media_items_async()
+                                .restore_media_items_async(restored)
                                 .await
                                 .map_err(|e| e.to_string())
                         },
-                        Message::PhotosLoaded,
+                        move |result| Message::TrashRestored(ids, result),
                     );
                 }
             }
-            Message::PhotosLoaded(result) => {
-                self.loading = false;
-                match result {
-                    Ok(photos) => {
-                        self.photos = photos;
-                        use std::collections::HashSet;
-                        let mut mimes: HashSet<String> = HashSet::new();
-                        let mut makes: HashSet<String> = HashSet::new();
-                        for photo in &self.photos {
-                            mimes.insert(photo.mime_type.clone());
-                            if let Some(make) = photo
-                                .media_metadata
-                                .video
-                                .as_ref()
-                                .and_then(|v| v.camera_make.clone())
-                            {
-                                makes.insert(make);
-                            }
-                        }
-                        self.mime_options = mimes.into_iter().collect();
-                        self.mime_options.sort();
-                        self.camera_make_options = makes.into_iter().collect();
-                        self.camera_make_options.sort();
-                        self.display_limit = PAGE_SIZE.min(self.photos.len());
-                        // Start loading thumbnails for configured number of photos
-                        let mut commands = Vec::new();
-                        for photo in self.photos.iter().take(self.preload_count) {
-                            let media_id = photo.id.clone();
-                            let base_url = photo.base_url.clone();
-                            commands.push(Command::perform(async {}, move |_| {
-                                Message::LoadThumbnail(media_id.clone(), base_url.clone())
-                            }));
-                        }
-                        return Command::batch(commands);
-                    }
-                    Err(error) => {
-                        self.errors
-                            .push(format!("Failed to load photos: {}", error));
-                        return GooglePiczUI::error_timeout();
-                    }
-                }
-            }
-            Message::LoadAlbums => {
-                return Command::perform(
-                    async {
-                        let token = auth::ensure_access_token_valid()
-                            .await
-                            .map_err(|e| e.to_string())?;
-                        let client = ApiClient::new(token);
-                        client
-                            .list_albums(50, None)
-                            .await
-                            .map(|r| r.0)
-                            .map_err(|e| e.to_string())
-                    },
-                    Message::AlbumsLoaded,
-                );
-            }
-            Message::AlbumsLoaded(result) => match result {
-                Ok(albums) => {
-                    self.albums = albums;
+            Message::TrashRestored(ids, result) => match result {
+                Ok(()) => {
+                    self.trash.items.retain(|t| !ids.contains(&t.item.id));
+                    self.trash.selected.retain(|id| !ids.contains(id));
+                    // The items kept their albums, so the grid, the counts and
+                    // a running search pick them up again right away
+                    let reload = if self.pre_search.is_some() {
+                        self.run_search()
+                    } else {
+                        Command::perform(async {}, |_| Message::LoadPhotos)
+                    };
+                    return Command::batch([reload, self.load_album_counts(), self.load_trash()]);
                 }
-                Err(err) => {
-                    let msg = format!("Failed to load albums: {}", err);
+                Err(e) => {
+                    let msg = format!("{}: {}", tr("error.restore_from_trash"), e);
                     self.errors.push(msg.clone());
                     self.log_error(&msg);
                     return GooglePiczUI::error_timeout();
                 }
             },
-            Message::RefreshPhotos => {
-                return Command::batch(vec![
-                    Command::perform(async {}, |_| Message::LoadPhotos),
-                    Command::perform(async {}, |_| Message::LoadAlbums),
-                ]);
-            }
-            Message::LoadMorePhotos => {
-                self.display_limit = (self.display_limit + PAGE_SIZE).min(self.photos.len());
-            }
-            Message::LoadThumbnail(media_id, base_url) => {
-                let image_loader = self.image_loader.clone();
-                let id_clone = media_id.clone();
-                let base_clone = base_url.clone();
-                return Command::perform(
-                    async move {
-                        let loader = image_loader.lock().await;
-                        loader.load_thumbnail(&id_clone, &base_clone).await
-                    },
-                    move |result| {
-                        Message::ThumbnailLoaded(media_id, result.map_err(|e| e.to_string()))
-                    },
-                );
+            Message::DeleteForever(ids) => {
+                if let Some(cm) = self.cache_manager.clone() {
+                    return Command::perform(
+                        async move {
+                            let cache = {
+                                let guard = cm.lock().await;
+                                guard.clone()
+                            };
+                            cache
+                                .purge_media_items_async(ids)
+                                .await
+                                .map_err(|e| e.to_string())
+                        },
+                        Message::TrashPurged,
+                    );
+                }
             }
-            Message::ThumbnailLoaded(media_id, result) => match result {
-                Ok(handle) => {
-                    self.thumbnails.insert(media_id, handle);
+            Message::TrashPurged(result) => match result {
+                Ok(removed) => {
+                    let text = trf("toast.trash_purged", &[&removed]);
+                    return Command::batch([self.load_trash(), self.notify(NotificationLevel::Success, text)]);
                 }
-                Err(error) => {
-                    let msg = format!("Failed to load thumbnail for {}: {}", media_id, error);
+                Err(e) => {
+                    let msg = format!("{}: {}", tr("error.delete_forever"), e);
                     self.errors.push(msg.clone());
                     self.log_error(&msg);
-                    return GooglePiczUI::error_timeout();
+                    return Command::batch([self.load_trash(), GooglePiczUI::error_timeout()]);
                 }
             },
-            Message::SelectPhoto(photo) => {
-                let id = photo.id.clone();
-                let url = photo.base_url.clone();
-                self.state = ViewState::SelectedPhoto { photo, faces: Vec::new() };
-                return Command::batch(vec![
-                    Command::perform(async {}, move |_| {
-                        Message::LoadFullImage(id.clone(), url.clone())
-                    }),
-                    Command::perform(async {}, move |_| Message::LoadFaces(id.clone())),
-                ]);
+            Message::ToggleTrashSelection(id) => {
+                if !self.trash.selected.remove(&id) {
+                    self.trash.selected.insert(id);
+                }
             }
-            Message::SelectAlbum(album_id) => {
-                self.selected_album = album_id;
-                return Command::perform(async {}, |_| Message::LoadPhotos);
+            Message::ShowEmptyTrashDialog => {
+                self.trash.confirm_empty = !self.trash.items.is_empty();
+            }
+            Message::ConfirmEmptyTrash => {
+                self.trash.confirm_empty = false;
+                if let Some(cm) = self.cache_manager.clone() {
+                    return Command::perform(
+                        async move {
+                            let cache = {
+                                let guard = cm.lock().await;
+                                guard.clone()
+                            };
+                            cache.empty_trash_async().await.map_err(|e| e.to_string())
+                        },
+                        Message::TrashPurged,
+                    );
+                }
             }
+            Message::CancelEmptyTrash => {
+                self.trash.confirm_empty = false;
+            }
+            Message::AlbumCountsLoaded(result) => match result {
+                Ok(counts) => self.album_sidebar.counts = counts,
+                // The sidebar falls back to the counts the API reported
+                Err(e) => tracing::warn!(error = %e, "Failed to load album item counts"),
+            },
+            Message::AlbumCoverLoaded(album_id, result) => match result {
+                Ok(handle) => {
+                    self.album_sidebar.covers.insert(album_id, handle);
+                }
+                Err(e) => tracing::warn!(id = %album_id, error = %e, "Failed to load album cover"),
+            },
             Message::LoadFullImage(media_id, base_url) => {
+                // Preloads queued just before the viewer closed
+                if self.viewed_photo_id().is_none() {
+                    return Command::none();
+                }
                 let loader = self.image_loader.clone();
                 let id_clone = media_id.clone();
                 let base_clone = base_url.clone();
@@ -786,10 +4299,18 @@ impl Application for GooglePiczUI {
             }
             Message::FullImageLoaded(media_id, result) => match result {
                 Ok(handle) => {
-                    self.full_images.insert(media_id, handle);
+                    // Preloads that finish after the viewer closed are dropped
+                    if self.viewed_photo_id().is_some() {
+                        self.handles.insert(ImageKey::full(&media_id), handle);
+                    }
                 }
                 Err(error) => {
-                    let msg = format!("Failed to load image: {}", error);
+                    // A failed neighbour preload is retried when it is opened
+                    if self.viewed_photo_id() != Some(media_id.as_str()) {
+                        tracing::warn!(id = %media_id, error = %error, "Failed to preload image");
+                        return Command::none();
+                    }
+                    let msg = format!("{}: {}", tr("error.load_image"), error);
                     self.errors.push(msg.clone());
                     self.log_error(&msg);
                     return GooglePiczUI::error_timeout();
@@ -808,7 +4329,7 @@ impl Application for GooglePiczUI {
                                 }
                                 #[cfg(not(feature = "face_recognition"))]
                                 {
-                                    let msg = format!("Failed to load faces: {}", e);
+                                    let msg = format!("{}: {}", tr("error.load_faces"), e);
                                     self.errors.push(msg.clone());
                                     self.log_error(&msg);
                                 }
@@ -818,14 +4339,114 @@ impl Application for GooglePiczUI {
                     }
                 }
             }
+            Message::DetectFaces(media_id) => {
+                let Some(cm) = self.cache_manager.clone() else {
+                    return Command::none();
+                };
+                let ViewState::SelectedPhoto { photo, .. } = &self.state else {
+                    return Command::none();
+                };
+                if photo.id != media_id || !self.detecting_faces.insert(media_id.clone()) {
+                    return Command::none();
+                }
+                let item = photo.clone();
+                let loader = self.image_loader.clone();
+                let min_face_size = self.min_face_size;
+                let detector = self.face_detector.clone();
+                return Command::perform(
+                    async move {
+                        let loader = loader.lock().await.clone();
+                        // The recognizer reads files; the guard keeps the original until it is done
+                        let (path, _guard) = loader
+                            .full_image_file(&item.id, &item.base_url)
+                            .await
+                            .map_err(|e| e.to_string())?;
+                        let local = MediaItem { base_url: format!("file://{}", path.display()), ..item.clone() };
+                        let (faces, embeddings) = tokio::task::spawn_blocking(move || {
+                            let recognizer = face_recognition::FaceRecognizer::new(detector).with_min_face_size(min_face_size);
+                            let faces = recognizer.detect_faces(&local)?;
+                            let embeddings = match recognizer.embed_faces(&local, &faces) {
+                                Ok(embeddings) => Some(embeddings),
+                                // Clustering needs the model, detection does not
+                                Err(face_recognition::FaceRecognitionError::ModelNotFound(_)) => None,
+                                Err(e) => return Err(e),
+                            };
+                            Ok((faces, embeddings))
+                        })
+                        .await
+                        .map_err(|e| e.to_string())?
+                        .map_err(|e: face_recognition::FaceRecognitionError| e.to_string())?;
+                        let cache = {
+                            let guard = cm.lock().await;
+                            guard.clone()
+                        };
+                        let json = serde_json::to_string(&faces).map_err(|e| e.to_string())?;
+                        cache.insert_faces_async(item.id.clone(), json).await.map_err(|e| e.to_string())?;
+                        if let Some(embeddings) = embeddings {
+                            cache
+                                .set_face_embeddings_async(item.id, embeddings)
+                                .await
+                                .map_err(|e| e.to_string())?;
+                        }
+                        Ok(faces)
+                    },
+                    move |res| Message::FacesDetected(media_id, res),
+                );
+            }
+            Message::FacesDetected(media_id, result) => {
+                self.detecting_faces.remove(&media_id);
+                match result {
+                    Ok(found) => {
+                        let none_found = found.is_empty();
+                        if let ViewState::SelectedPhoto { photo, faces } = &mut self.state {
+                            if photo.id == media_id {
+                                *faces = found;
+                            }
+                        }
+                        let mut commands = Vec::new();
+                        if none_found {
+                            commands.push(self.notify(NotificationLevel::Info, tr("toast.no_faces_found")));
+                        }
+                        if self.people.open {
+                            commands.push(self.load_people());
+                        }
+                        return Command::batch(commands);
+                    }
+                    Err(e) => {
+                        let msg = format!("{}: {}", tr("error.detect_faces"), e);
+                        self.errors.push(msg.clone());
+                        self.log_error(&msg);
+                        return GooglePiczUI::error_timeout();
+                    }
+                }
+            }
             Message::StartRenameFace(idx) => {
                 self.editing_face = Some(idx);
+                self.cluster_offer = None;
                 if let ViewState::SelectedPhoto { faces, .. } = &self.state {
                     if let Some(f) = faces.get(idx) {
                         self.face_name_input = f.name.clone().unwrap_or_default();
                     }
                 }
             }
+            Message::ToggleFaceIgnored(idx) => {
+                let ViewState::SelectedPhoto { photo, faces } = &mut self.state else {
+                    return Command::none();
+                };
+                let Some(face) = faces.get_mut(idx) else {
+                    return Command::none();
+                };
+                face.ignored = !face.ignored;
+                let (media_id, ignored) = (photo.id.clone(), face.ignored);
+                if ignored && self.editing_face == Some(idx) {
+                    self.editing_face = None;
+                    self.face_name_input.clear();
+                }
+                return self.change_people(move |cache| async move {
+                    cache.set_face_ignored_async(media_id, idx, ignored).await?;
+                    Ok(tr(if ignored { "toast.face_ignored" } else { "toast.face_restored" }).to_string())
+                });
+            }
             Message::FaceNameChanged(name) => {
                 self.face_name_input = name;
             }
@@ -840,72 +4461,153 @@ impl Application for GooglePiczUI {
                             let media_id = photo.id.clone();
                             let name = self.face_name_input.clone();
                             return Command::perform(
-                                async move {
-                                    let cache = { let guard = cm.lock().await; guard.clone() };
-                                    cache.update_face_name(&media_id, idx, &name).await.map_err(|e| e.to_string())
+                                {
+                                    let (media_id, name) = (media_id.clone(), name.clone());
+                                    async move {
+                                        let cache = { let guard = cm.lock().await; guard.clone() };
+                                        cache.update_face_name(&media_id, idx, &name).await.map_err(|e| e.to_string())?;
+                                        cache.face_cluster_async(media_id, idx).await.map_err(|e| e.to_string())
+                                    }
                                 },
-                                |_| Message::CancelFaceName,
+                                move |res| Message::FaceNameSaved(media_id.clone(), name.clone(), res),
                             );
                         }
                     }
                 }
                 self.face_name_input.clear();
             }
+            Message::FaceNameSaved(media_id, name, result) => {
+                self.editing_face = None;
+                self.face_name_input.clear();
+                match result {
+                    // Only worth asking while other photos of the cluster are unnamed
+                    Ok(Some(cluster)) if cluster.photo_count > 0 => {
+                        if self.selected_photo_id().as_deref() == Some(media_id.as_str()) {
+                            self.cluster_offer = Some((cluster, name));
+                        }
+                        return self.load_person_options();
+                    }
+                    Ok(_) => return self.load_person_options(),
+                    Err(e) => {
+                        let msg = format!("{}: {}", tr("error.save_face_name"), e);
+                        self.errors.push(msg.clone());
+                        self.log_error(&msg);
+                        return GooglePiczUI::error_timeout();
+                    }
+                }
+            }
             Message::CancelFaceName => {
                 self.editing_face = None;
                 self.face_name_input.clear();
             }
+            Message::NameCluster => {
+                let Some((cluster, name)) = self.cluster_offer.take() else {
+                    return Command::none();
+                };
+                return self.change_people(move |cache| async move {
+                    cache
+                        .name_cluster_async(cluster.id, name.clone())
+                        .await
+                        .map(|changed| trf("toast.cluster_named", &[&changed, &name]))
+                });
+            }
+            Message::DismissClusterOffer => {
+                self.cluster_offer = None;
+            }
             Message::ClosePhoto => {
-                self.state = ViewState::Grid;
+                self.editing_description = false;
+                return self.return_to_grid();
             }
             #[cfg(feature = "gstreamer")]
             Message::PlayVideo(item) => {
+                self.leave_grid();
+                if let Some(previous) = self.video_download.take() {
+                    previous.cancel.cancel();
+                }
                 let url = format!("{}=dv", item.base_url);
+                let id = item.id.clone();
+                let cache = self.video_cache.clone();
+                let media_id = id.clone();
+                let (tx, rx) = mpsc::unbounded_channel();
+                let cancel = CancelToken::default();
+                self.video_download = Some(VideoDownload {
+                    media_id: id.clone(),
+                    buffer: StreamBuffer::default(),
+                    stream_tried: false,
+                    cancel: cancel.clone(),
+                    updates: Arc::new(Mutex::new(rx)),
+                });
                 return Command::perform(
                     async move {
-                        VideoDownloader::new()
-                            .download_to_tempfile(&url, ".mp4")
-                            .await
-                            .map_err(|e| e.to_string())
+                        cache.get_or_download(&media_id, &url, Some(tx), &cancel).await
                     },
                     |res| match res {
-                        Ok(p) => Message::VideoDownloaded(p),
-                        Err(e) => Message::VideoDownloadFailed(e),
+                        Ok(p) => Message::VideoDownloaded(id, p),
+                        Err(e) => Message::VideoDownloadFailed(id, e),
                     },
                 );
             }
             #[cfg(feature = "gstreamer")]
-            Message::VideoDownloaded(temp) => {
-                match url::Url::from_file_path(&temp) {
-                    Ok(u) => match GstreamerIcedBase::new_url(&u, false) {
-                        Ok(mut player) => {
-                            let _ = player.update(GStreamerMessage::PlayStatusChanged(PlayStatus::Playing));
-                            self.state = ViewState::PlayingVideo { player, file: temp };
-                        }
-                        Err(e) => {
-                            let detail = e.to_string();
-                            let msg = if detail.to_lowercase().contains("initialize") {
-                                "GStreamer not available".to_string()
-                            } else {
-                                format!("Failed to start video: {detail}. Missing codecs?")
-                            };
+            Message::VideoDownloadProgress(id, progress) => {
+                let Some(download) = self.video_download.as_mut().filter(|d| d.media_id == id) else {
+                    return Command::none();
+                };
+                download.buffer.progress = progress;
+                if let ViewState::PlayingVideo { .. } = self.state {
+                    return self.check_stream_buffer();
+                }
+                if download.stream_tried || !download.buffer.can_start() {
+                    return Command::none();
+                }
+                download.stream_tried = true;
+                match Self::open_player(&self.video_cache.partial_path(&id)) {
+                    Ok(player) => self.state = ViewState::PlayingVideo { player, file: None },
+                    // Not every file plays before it is complete; it still will once it is
+                    Err(e) => tracing::info!("Cannot stream video {} while downloading: {}", id, e),
+                }
+            }
+            #[cfg(feature = "gstreamer")]
+            Message::VideoDownloaded(id, video) => {
+                let Some(download) = self.video_download.take_if(|d| d.media_id == id) else {
+                    // Replaced by another video meanwhile; it stays cached for next time
+                    return Command::none();
+                };
+                let path = video.path().to_path_buf();
+                let mut commands = Vec::new();
+                if let ViewState::PlayingVideo { player, file } = &mut self.state {
+                    // Already playing from the partial file, which is now the cached one
+                    *file = Some(video);
+                    if download.buffer.is_stalled() {
+                        commands.push(
+                            player
+                                .update(GStreamerMessage::PlayStatusChanged(PlayStatus::Playing))
+                                .map(Message::VideoEvent),
+                        );
+                    }
+                } else {
+                    match Self::open_player(&path) {
+                        Ok(player) => self.state = ViewState::PlayingVideo { player, file: Some(video) },
+                        Err(msg) => {
                             self.errors.push(msg.clone());
                             self.log_error(&msg);
-                            drop(temp); // ensure temp file cleanup
                             return GooglePiczUI::error_timeout();
                         }
-                    },
-                    Err(_) => {
-                        let msg = "Invalid video file path".to_string();
-                        self.errors.push(msg.clone());
-                        self.log_error(&msg);
-                        drop(temp);
-                        return GooglePiczUI::error_timeout();
                     }
                 }
+                if !self.video_durations.contains_key(&id) {
+                    commands.push(self.grab_video_frame(id, path));
+                }
+                return Command::batch(commands);
             }
             #[cfg(feature = "gstreamer")]
-            Message::VideoDownloadFailed(err) => {
+            Message::VideoDownloadFailed(id, err) => {
+                if self.video_download.as_ref().is_some_and(|d| d.media_id == id) {
+                    self.video_download = None;
+                }
+                if err == VideoDownloadError::Cancelled {
+                    return Command::none();
+                }
+                let err = err.to_string();
                 self.errors.push(err.clone());
                 self.log_error(&err);
                 return GooglePiczUI::error_timeout();
@@ -914,10 +4616,19 @@ impl Application for GooglePiczUI {
             Message::VideoEvent(msg) => {
                 if let ViewState::PlayingVideo { player, .. } = &mut self.state {
                     if let GStreamerMessage::BusGoToEnd = msg {
-                        self.state = ViewState::Grid;
-                        return Command::none();
+                        if let Some(download) = self.video_download.as_mut() {
+                            // Reached the end of what has been downloaded, not of the video
+                            download.buffer.stall();
+                            return Command::none();
+                        }
+                        if let Some(index) = self.slideshow_resume.take() {
+                            self.state = ViewState::Slideshow { index, playing: true };
+                            return self.update(Message::SlideshowTick);
+                        }
+                        return self.return_to_grid();
                     }
-                    return player.update(msg).map(Message::VideoEvent);
+                    let command = player.update(msg).map(Message::VideoEvent);
+                    return Command::batch([command, self.check_stream_buffer()]);
                 }
             }
             #[cfg(feature = "gstreamer")]
@@ -936,33 +4647,80 @@ impl Application for GooglePiczUI {
             #[cfg(feature = "gstreamer")]
             Message::SeekVideo(pos) => {
                 if let ViewState::PlayingVideo { player, .. } = &mut self.state {
+                    let pos = match &self.video_download {
+                        Some(download) => download.buffer.clamp_seek(pos, player.duration_seconds()),
+                        None => pos,
+                    };
                     let _ = player.seek(std::time::Duration::from_secs_f64(pos));
                 }
             }
             #[cfg(feature = "gstreamer")]
             Message::CloseVideo => {
-                self.state = ViewState::Grid;
+                self.slideshow_resume = None;
+                if let Some(download) = self.video_download.take() {
+                    // Stay on the photo the download was started from
+                    download.cancel.cancel();
+                    if !matches!(self.state, ViewState::PlayingVideo { .. }) {
+                        return Command::none();
+                    }
+                }
+                return self.return_to_grid();
             }
             Message::SyncProgress(progress) => match progress {
                 SyncProgress::Started => {
                     self.synced = 0;
                     self.syncing = true;
-                    self.sync_status = "Sync started".into();
+                    self.sync_status = tr("status.sync_started").into();
+                    self.sync_activity.stage = None;
+                    self.sync_activity.processed = 0;
+                    self.sync_activity.page = 0;
+                    self.sync_activity.retry_at = None;
                 }
                 SyncProgress::Retrying(wait) => {
                     self.syncing = false;
-                    self.sync_status = format!("Retrying in {}s", wait);
+                    self.sync_status = trf("status.retrying", &[&wait]);
+                    self.sync_activity.retry_at =
+                        Some(std::time::Instant::now() + Duration::from_secs(wait));
+                }
+                SyncProgress::Progress { stage, processed, estimated_total, page } => {
+                    self.syncing = true;
+                    self.sync_activity.stage = Some(stage);
+                    self.sync_activity.processed = processed;
+                    self.sync_activity.estimated_total = estimated_total;
+                    self.sync_activity.page = page;
+                }
+                SyncProgress::Report(report) => {
+                    self.status_bar.set_online(true);
+                    let prefetch = self.prefetch_new_items(&report.inserted_ids);
+                    self.sync_activity.report = Some(report);
+                    return prefetch;
                 }
                 SyncProgress::ItemSynced(count) => {
                     self.synced = count;
                     self.syncing = true;
-                    self.sync_status = format!("Syncing {} items", count);
+                    self.sync_status = trf("status.syncing", &[&count]);
+                }
+                SyncProgress::FacesScanned { processed, total } => {
+                    self.sync_activity.face_scan = (processed < total).then_some((processed, total));
+                    // A running sync keeps its own status
+                    if !self.syncing {
+                        self.sync_status = if processed < total {
+                            trf("status.scanning_faces", &[&processed, &total])
+                        } else {
+                            trf("status.faces_scanned", &[&total])
+                        };
+                    }
                 }
                 SyncProgress::Finished(total) => {
+                    // Progress channels also report `Finished` when they close
+                    let was_syncing = self.syncing;
                     self.synced = total;
                     self.syncing = false;
                     self.last_synced = Some(Utc::now());
-                    self.sync_status = format!("Sync completed: {} items", total);
+                    self.sync_status = trf("status.sync_completed", &[&total]);
+                    if was_syncing {
+                        return self.notify(NotificationLevel::Info, self.sync_status.clone());
+                    }
                 }
             },
             Message::SyncStatusUpdated(ts, message) => {
@@ -976,13 +4734,21 @@ impl Application for GooglePiczUI {
             },
             Message::SyncError(err_msg) => {
                 match err_msg {
+                    SyncTaskError::ReauthRequired(reason) => {
+                        // Both sync tasks wait for the sign-in, so there is nothing to retry
+                        tracing::warn!("Sign-in required: {}", reason);
+                        self.account_menu.reauth_required = true;
+                        self.sync_status = tr("status.reauth_required").into();
+                        self.syncing = false;
+                    }
                     other => {
                         tracing::error!("Sync error: {}", other);
                         let detail = match &other {
                             SyncTaskError::TokenRefreshFailed { message, .. }
                             | SyncTaskError::PeriodicSyncFailed { message, .. }
                             | SyncTaskError::Other { message, .. }
-                            | SyncTaskError::Aborted(message) => message.clone(),
+                            | SyncTaskError::Aborted(message)
+                            | SyncTaskError::ReauthRequired(message) => message.clone(),
                             SyncTaskError::RestartAttempt(attempt) => format!("Restart attempt {attempt}"),
                         };
                         if let Some(idx) = detail.find("last_success:") {
@@ -993,14 +4759,32 @@ impl Application for GooglePiczUI {
                                 }
                             }
                         }
-                        self.errors.push(other.to_string());
-                        self.log_error(&other.to_string());
-                        self.sync_status = "Sync error".into();
+                        self.sync_activity.last_error = Some((Utc::now(), detail.clone()));
+                        // Token refreshes fail with `Auth` whether or not Google is
+                        // reachable, so only network failures tell about connectivity
+                        if let SyncTaskError::PeriodicSyncFailed { code: SyncErrorCode::Network, .. }
+                        | SyncTaskError::TokenRefreshFailed { code: SyncErrorCode::Network, .. }
+                        | SyncTaskError::Other { code: SyncErrorCode::Network, .. } = &other
+                        {
+                            self.status_bar.set_online(false);
+                        }
+                        self.sync_status = tr("status.sync_error").into();
                         self.syncing = false;
-                        return GooglePiczUI::error_timeout();
+                        return self.report_error(other.to_string());
                     }
                 }
             }
+            Message::ToggleSyncPanel => {
+                self.sync_activity.open = !self.sync_activity.open;
+            }
+            Message::SyncNow => return self.send_sync_control(SyncControl::SyncNow),
+            Message::PauseSync => return self.send_sync_control(SyncControl::Pause),
+            Message::ResumeSync => return self.send_sync_control(SyncControl::Resume),
+            Message::SyncTick => {
+                if self.sync_activity.retry_remaining() == Some(0) {
+                    self.sync_activity.retry_at = None;
+                }
+            }
             Message::DismissError(index) => {
                 if index < self.errors.len() {
                     self.errors.remove(index);
@@ -1009,9 +4793,12 @@ impl Application for GooglePiczUI {
             Message::ClearErrors => {
                 self.errors.clear();
             }
+            Message::DismissNotification(id) => {
+                self.notifications.retain(|n| n.id != id);
+            }
             Message::ShowSettings => {
                 self.settings_open = true;
-                let cfg = AppConfig::load_from(Some(self.config_path.clone()));
+                let cfg = AppConfig::load_from(Some(self.config_path.clone()), self.profile.as_deref());
                 self.settings_log_level = cfg.log_level;
                 self.settings_cache_path = cfg.cache_path.to_string_lossy().to_string();
                 self.settings_oauth_port = cfg.oauth_redirect_port.to_string();
@@ -1020,27 +4807,296 @@ impl Application for GooglePiczUI {
                 self.settings_sync_interval = cfg.sync_interval_minutes.to_string();
                 self.settings_debug_console = cfg.debug_console;
                 self.settings_trace_spans = cfg.trace_spans;
+                self.settings_slideshow_interval = cfg.slideshow_interval_secs.to_string();
+                self.settings_slideshow_videos = cfg.slideshow_play_videos;
+                self.settings_image_cache_max = cfg.image_cache_max_mb.to_string();
+                self.settings_video_cache_max = cfg.video_cache_max_mb.to_string();
+                self.settings_theme = cfg.theme;
+                self.settings_language = Language::from_code(&cfg.language);
+                // A hand-edited config may already hold values the dialog rejects
+                self.settings_errors.clear();
+                for field in SettingsField::ALL {
+                    self.check_setting(field);
+                }
+                let loader = self.image_loader.clone();
+                self.video_cache_usage = Some(self.video_cache.usage());
+                return Command::perform(
+                    async move { loader.lock().await.cache_usage() },
+                    Message::ImageCacheUsage,
+                );
+            }
+            Message::ShowAccountSettings => {
+                let command = self.update(Message::ShowSettings);
+                self.settings_account_open = true;
+                return command;
+            }
+            Message::ToggleAccountSettings => {
+                self.settings_account_open = !self.settings_account_open;
+            }
+            Message::ToggleAccountMenu => {
+                self.account_menu.open = !self.account_menu.open;
+                if self.account_menu.open {
+                    return self.load_accounts();
+                }
+            }
+            Message::AccountsLoaded(accounts) => {
+                self.account_menu.accounts = accounts;
+            }
+            Message::UserInfoLoaded(generation, result) => {
+                if generation != self.account_menu.generation {
+                    return Command::none();
+                }
+                match result {
+                    Ok(info) => {
+                        let picture = info.picture.clone();
+                        self.account_menu.user = Some(info);
+                        if let Some(url) = picture {
+                            return Command::perform(accounts::load_avatar(url), move |result| {
+                                Message::AvatarLoaded(generation, result)
+                            });
+                        }
+                    }
+                    // Not signed in; the menu offers to add the account
+                    Err(e) => tracing::info!("No user info for the active account: {}", e),
+                }
+            }
+            Message::AvatarLoaded(generation, result) => {
+                if generation != self.account_menu.generation {
+                    return Command::none();
+                }
+                match result {
+                    Ok(handle) => self.account_menu.avatar = Some(handle),
+                    Err(e) => tracing::warn!("Failed to load the account avatar: {}", e),
+                }
+            }
+            Message::SwitchAccount(profile) => {
+                if profile == self.profile || self.account_menu.adding.is_some() {
+                    self.account_menu.open = false;
+                    return Command::none();
+                }
+                return self.switch_account(profile);
+            }
+            Message::AddAccount => {
+                if self.account_menu.adding.is_some() {
+                    return Command::none();
+                }
+                let name = accounts::next_profile_name();
+                if let Err(e) = app_config::create_profile(&name) {
+                    let msg = format!("{}: {}", tr("error.add_account"), e);
+                    self.errors.push(msg.clone());
+                    self.log_error(&msg);
+                    return GooglePiczUI::error_timeout();
+                }
+                // Sign in with the new profile's token storage; the rest follows once that worked
+                self.account_menu.adding = Some(name.clone());
+                let pause = self.send_sync_control(SyncControl::Pause);
+                let port = self.startup_config.oauth_redirect_port;
+                return Command::batch([
+                    pause,
+                    Command::perform(
+                        async move { auth::authenticate_for(Some(&name), port).await.map_err(|e| e.to_string()) },
+                        Message::AccountAdded,
+                    ),
+                ]);
+            }
+            Message::AccountAdded(result) => {
+                let Some(name) = self.account_menu.adding.take() else {
+                    return Command::none();
+                };
+                if let Err(e) = result {
+                    if let Err(err) = std::fs::remove_dir_all(app_config::profile_dir(Some(&name))) {
+                        tracing::warn!("Failed to remove profile {}: {}", name, err);
+                    }
+                    let msg = format!("{}: {}", tr("error.add_account"), e);
+                    self.errors.push(msg.clone());
+                    self.log_error(&msg);
+                    return Command::batch([
+                        self.send_sync_control(SyncControl::Resume),
+                        GooglePiczUI::error_timeout(),
+                    ]);
+                }
+                return self.switch_account(Some(name));
+            }
+            Message::Reauthenticate => {
+                if self.account_menu.reauthenticating {
+                    return Command::none();
+                }
+                self.account_menu.reauthenticating = true;
+                let port = self.startup_config.oauth_redirect_port;
+                let profile = self.profile.clone();
+                return Command::perform(
+                    async move { auth::authenticate_for(profile.as_deref(), port).await.map_err(|e| e.to_string()) },
+                    Message::Reauthenticated,
+                );
+            }
+            Message::Reauthenticated(result) => {
+                self.account_menu.reauthenticating = false;
+                if let Err(e) = result {
+                    let msg = format!("{}: {}", tr("error.reauth"), e);
+                    self.errors.push(msg.clone());
+                    self.log_error(&msg);
+                    return GooglePiczUI::error_timeout();
+                }
+                // The paused sync tasks pick up on their own once the new tokens are stored
+                self.account_menu.reauth_required = false;
+                self.sync_status.clear();
+                return self.load_user_info();
+            }
+            Message::SignOut => {
+                self.account_menu.open = false;
+                if let Err(e) = auth::logout_for(self.profile.as_deref()) {
+                    let msg = format!("{}: {}", tr("error.sign_out"), e);
+                    self.errors.push(msg.clone());
+                    self.log_error(&msg);
+                    return GooglePiczUI::error_timeout();
+                }
+                self.clear_library();
+                self.account_menu.user = None;
+                self.account_menu.avatar = None;
+                self.account_menu.generation += 1;
+                return Command::batch([
+                    self.send_sync_control(SyncControl::Pause),
+                    self.load_accounts(),
+                    self.notify(NotificationLevel::Info, tr("toast.signed_out")),
+                ]);
+            }
+            Message::StatusBarTick => {
+                // The cached albums and album contents shown while offline
+                if std::mem::take(&mut self.status_bar.reconnected) {
+                    let mut commands = vec![Command::perform(async {}, |_| Message::LoadAlbums)];
+                    if self.selected_album.is_some() {
+                        commands.push(Command::perform(async {}, |_| Message::LoadPhotos));
+                    }
+                    return Command::batch(commands);
+                }
+                if !self.status_bar.refreshing {
+                    self.status_bar.refreshing = true;
+                    return Command::perform(
+                        status_bar::query(self.cache_manager.clone(), self.cache_db_path.clone(), self.profile.clone()),
+                        Message::StatusBarUpdated,
+                    );
+                }
+            }
+            Message::StatusBarUpdated(snapshot) => {
+                self.status_bar.refreshing = false;
+                self.status_bar.snapshot = snapshot;
             }
             Message::CloseSettings => {
                 self.settings_open = false;
+                self.settings_account_open = false;
+                self.error_log = None;
+                // Drop an unsaved theme preview
+                self.theme_setting = AppConfig::load_from(Some(self.config_path.clone()), self.profile.as_deref()).theme;
+            }
+            Message::ToggleErrorLog => {
+                if self.error_log.take().is_none() {
+                    self.error_log = Some(Vec::new());
+                    return self.load_error_log();
+                }
+            }
+            Message::ErrorLogLoaded(result) => match result {
+                Ok(lines) => {
+                    // Closed again while reading
+                    if self.error_log.is_some() {
+                        self.error_log = Some(lines);
+                    }
+                }
+                // Not written to the log that just failed to read
+                Err(e) => {
+                    let msg = format!("{}: {}", tr("error.read_error_log"), e);
+                    self.errors.push(msg);
+                    return GooglePiczUI::error_timeout();
+                }
+            },
+            Message::CopyErrorLog => {
+                if let Some(lines) = &self.error_log {
+                    let content = lines.join("\n");
+                    return Command::batch([
+                        iced::clipboard::write(content),
+                        self.notify(NotificationLevel::Info, tr("toast.error_log_copied")),
+                    ]);
+                }
+            }
+            #[cfg(feature = "clipboard")]
+            Message::CopyImage => {
+                let ViewState::SelectedPhoto { photo, .. } = &self.state else {
+                    return Command::none();
+                };
+                // Fall back to the thumbnail while the full image is loading
+                let (handle, full) = match (self.full_image(&photo.id), self.thumbnail(&photo.id)) {
+                    (Some(handle), _) => (handle, true),
+                    (None, Some(handle)) => (handle, false),
+                    (None, None) => {
+                        return self.notify(NotificationLevel::Warning, tr("toast.image_not_loaded"));
+                    }
+                };
+                return Command::perform(clipboard::copy_image(handle), move |res| {
+                    Message::ImageCopied(res.map(|_| full))
+                });
+            }
+            #[cfg(feature = "clipboard")]
+            Message::ImageCopied(result) => match result {
+                Ok(true) => return self.notify(NotificationLevel::Success, tr("toast.image_copied")),
+                Ok(false) => return self.notify(NotificationLevel::Info, tr("toast.thumbnail_copied")),
+                Err(err) => {
+                    let msg = format!("{}: {}", tr("error.copy_image"), err);
+                    self.errors.push(msg.clone());
+                    self.log_error(&msg);
+                    return GooglePiczUI::error_timeout();
+                }
+            },
+            Message::CopyLink => {
+                if let ViewState::SelectedPhoto { photo, .. } = &self.state {
+                    let url = photo.product_url.clone();
+                    return self.copy_link(url);
+                }
+            }
+            Message::CopyPhotoLink(id) => {
+                if let Some(photo) = self.photos.iter().find(|p| p.id == id) {
+                    let url = photo.product_url.clone();
+                    return self.copy_link(url);
+                }
             }
+            Message::ClearErrorLog => {
+                let path = self.error_log_path.clone();
+                return Command::perform(error_log::clear(path), Message::ErrorLogCleared);
+            }
+            Message::ErrorLogCleared(result) => match result {
+                Ok(()) => {
+                    if self.error_log.is_some() {
+                        self.error_log = Some(Vec::new());
+                    }
+                    return self.notify(NotificationLevel::Success, tr("toast.error_log_cleared"));
+                }
+                Err(e) => {
+                    let msg = format!("{}: {}", tr("error.clear_error_log"), e);
+                    self.errors.push(msg.clone());
+                    self.log_error(&msg);
+                    return GooglePiczUI::error_timeout();
+                }
+            },
             Message::SettingsLogLevelChanged(val) => {
                 self.settings_log_level = val;
             }
             Message::SettingsCachePathChanged(val) => {
                 self.settings_cache_path = val;
+                self.check_setting(SettingsField::CachePath);
             }
             Message::SettingsOauthPortChanged(val) => {
                 self.settings_oauth_port = val;
+                self.check_setting(SettingsField::OauthPort);
             }
             Message::SettingsThumbsPreloadChanged(val) => {
                 self.settings_thumbnails_preload = val;
+                self.check_setting(SettingsField::ThumbsPreload);
             }
             Message::SettingsPreloadThreadsChanged(val) => {
                 self.settings_preload_threads = val;
+                self.check_setting(SettingsField::PreloadThreads);
             }
             Message::SettingsSyncIntervalChanged(val) => {
                 self.settings_sync_interval = val;
+                self.check_setting(SettingsField::SyncInterval);
             }
             Message::SettingsDebugConsoleToggled(val) => {
                 self.settings_debug_console = val;
@@ -1048,6 +5104,127 @@ impl Application for GooglePiczUI {
             Message::SettingsTraceSpansToggled(val) => {
                 self.settings_trace_spans = val;
             }
+            Message::SettingsSlideshowIntervalChanged(val) => {
+                self.settings_slideshow_interval = val;
+                self.check_setting(SettingsField::SlideshowInterval);
+            }
+            Message::SettingsSlideshowVideosToggled(val) => {
+                self.settings_slideshow_videos = val;
+            }
+            Message::SettingsImageCacheMaxChanged(val) => {
+                self.settings_image_cache_max = val;
+                self.check_setting(SettingsField::ImageCacheMaxMb);
+            }
+            Message::ImageCacheUsage(usage) => {
+                self.image_cache_usage = Some(usage);
+            }
+            Message::SettingsVideoCacheMaxChanged(val) => {
+                self.settings_video_cache_max = val;
+                self.check_setting(SettingsField::VideoCacheMaxMb);
+            }
+            Message::VideoCacheUsage(usage) => {
+                self.video_cache_usage = Some(usage);
+            }
+            Message::ClearVideoCache => {
+                let cache = self.video_cache.clone();
+                return Command::perform(
+                    async move {
+                        cache.clear().await.map_err(|e| e.to_string())?;
+                        Ok(cache.usage())
+                    },
+                    Message::VideoCacheCleared,
+                );
+            }
+            Message::VideoCacheCleared(result) => match result {
+                Ok(usage) => {
+                    self.video_cache_usage = Some(usage);
+                    return self.notify(NotificationLevel::Success, tr("toast.video_cache_cleared"));
+                }
+                Err(e) => {
+                    let msg = format!("{}: {}", tr("error.clear_video_cache"), e);
+                    self.errors.push(msg.clone());
+                    self.log_error(&msg);
+                    return GooglePiczUI::error_timeout();
+                }
+            },
+            Message::ClearImageCache => {
+                let loader = self.image_loader.clone();
+                return Command::perform(
+                    async move {
+                        let loader = loader.lock().await.clone();
+                        loader.clear_cache().await.map_err(|e| e.to_string())?;
+                        Ok(loader.cache_usage())
+                    },
+                    Message::ImageCacheCleared,
+                );
+            }
+            Message::PrefetchFinished(summary) => {
+                tracing::info!(
+                    downloaded = summary.downloaded,
+                    skipped = summary.skipped,
+                    failed = summary.failed,
+                    "Prefetched thumbnails of new items"
+                );
+            }
+            Message::ImageCacheCleared(result) => match result {
+                Ok(usage) => {
+                    self.image_cache_usage = Some(usage);
+                    // Handles in memory went too, so the grid loads its tiles again
+                    let reload = self.request_visible_thumbnails();
+                    return Command::batch([self.notify(NotificationLevel::Success, tr("toast.image_cache_cleared")), reload]);
+                }
+                Err(e) => {
+                    let msg = format!("{}: {}", tr("error.clear_image_cache"), e);
+                    self.errors.push(msg.clone());
+                    self.log_error(&msg);
+                    return GooglePiczUI::error_timeout();
+                }
+            },
+            Message::ShowResetCacheDialog => {
+                self.confirm_reset_cache = self.cache_manager.is_some();
+            }
+            Message::ConfirmResetCache => {
+                self.confirm_reset_cache = false;
+                if let Some(cm) = self.cache_manager.clone() {
+                    return Command::perform(
+                        async move {
+                            let cache = {
+                                let guard = cm.lock().await;
+                                guard.clone()
+                            };
+                            cache.clear_cache_async().await.map_err(|e| e.to_string())
+                        },
+                        Message::CacheReset,
+                    );
+                }
+            }
+            Message::CancelResetCache => {
+                self.confirm_reset_cache = false;
+            }
+            Message::CacheReset(result) => {
+                if let Err(e) = result {
+                    let msg = format!("{}: {}", tr("error.reset_cache"), e);
+                    self.errors.push(msg.clone());
+                    self.log_error(&msg);
+                    return GooglePiczUI::error_timeout();
+                }
+                // The last sync time went with the rest, so the next sync fetches everything
+                self.clear_library();
+                self.last_synced = None;
+                return Command::batch([
+                    self.notify(NotificationLevel::Success, tr("toast.cache_reset")),
+                    Command::perform(async {}, |_| Message::LoadPhotos),
+                    Command::perform(async {}, |_| Message::LoadAlbums),
+                    self.send_sync_control(SyncControl::SyncNow),
+                ]);
+            }
+            Message::SettingsThemeChanged(val) => {
+                self.theme_setting = val.clone();
+                self.settings_theme = val;
+            }
+            Message::SettingsLanguageChanged(val) => {
+                self.settings_language = val;
+            }
             Message::ChooseCachePath => {
                 return Command::perform(async {
                     AsyncFileDialog::new()
@@ -1059,36 +5236,161 @@ impl Application for GooglePiczUI {
             Message::CachePathChosen(opt) => {
                 if let Some(p) = opt {
                     self.settings_cache_path = p.to_string_lossy().to_string();
+                    self.check_setting(SettingsField::CachePath);
                 }
             }
             Message::SaveSettings => {
-                let mut cfg = AppConfig::load_from(Some(self.config_path.clone()));
+                for field in SettingsField::ALL {
+                    self.check_setting(field);
+                }
+                // Save nothing rather than the valid half of the dialog
+                if let Some(field) = SettingsField::ALL.into_iter().find(|f| self.settings_errors.contains_key(f)) {
+                    let msg = trf("error.settings_invalid", &[&field.label(), &self.settings_errors[&field]]);
+                    self.errors.push(msg.clone());
+                    self.log_error(&msg);
+                    return GooglePiczUI::error_timeout();
+                }
+                let mut cfg = AppConfig::load_from(Some(self.config_path.clone()), self.profile.as_deref());
                 cfg.log_level = self.settings_log_level.clone();
-                cfg.cache_path = PathBuf::from(self.settings_cache_path.clone());
-                if let Ok(p) = self.settings_oauth_port.parse() {
+                cfg.cache_path = PathBuf::from(self.settings_cache_path.trim());
+                if let Ok(p) = self.settings_oauth_port.trim().parse() {
                     cfg.oauth_redirect_port = p;
                 }
-                if let Ok(t) = self.settings_thumbnails_preload.parse() {
+                if let Ok(t) = self.settings_thumbnails_preload.trim().parse() {
                     cfg.thumbnails_preload = t;
                 }
-                if let Ok(t) = self.settings_preload_threads.parse() {
+                if let Ok(t) = self.settings_preload_threads.trim().parse() {
                     cfg.preload_threads = t;
                 }
-                if let Ok(s) = self.settings_sync_interval.parse() {
+                if let Ok(s) = self.settings_sync_interval.trim().parse() {
                     cfg.sync_interval_minutes = s;
                 }
+                if let Ok(s) = self.settings_slideshow_interval.trim().parse() {
+                    cfg.slideshow_interval_secs = s;
+                }
                 cfg.debug_console = self.settings_debug_console;
                 cfg.trace_spans = self.settings_trace_spans;
+                cfg.slideshow_play_videos = self.settings_slideshow_videos;
+                if let Ok(m) = self.settings_image_cache_max.trim().parse() {
+                    cfg.image_cache_max_mb = m;
+                }
+                if let Ok(m) = self.settings_video_cache_max.trim().parse() {
+                    cfg.video_cache_max_mb = m;
+                }
+                cfg.theme = self.settings_theme.clone();
+                cfg.language = self.settings_language.code().to_string();
+                for issue in cfg.clamp_recoverable() {
+                    tracing::warn!("Setting out of range, clamped: {}", issue);
+                }
+                if let Err(issues) = cfg.validate() {
+                    for issue in &issues {
+                        if let Some(field) = SettingsField::from_config_key(issue.field) {
+                            self.settings_errors.insert(field, trf("settings.invalid.allowed", &[&issue.allowed]));
+                        }
+                    }
+                    let list = issues.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ");
+                    let msg = trf("error.config_invalid", &[&list]);
+                    self.errors.push(msg.clone());
+                    self.log_error(&msg);
+                    return GooglePiczUI::error_timeout();
+                }
                 if let Err(e) = cfg.save_to(Some(self.config_path.clone())) {
-                    let msg = format!("Failed to save settings: {}", e);
+                    let msg = format!("{}: {}", tr("error.save_settings"), e);
                     self.errors.push(msg.clone());
                     self.log_error(&msg);
                     return GooglePiczUI::error_timeout();
                 }
+                // Applied here, not again when the watcher sees the file change
+                self.config_watcher.reset();
                 self.settings_open = false;
+                let apply = self.update(Message::ConfigChanged(Box::new(cfg)));
+                return Command::batch([
+                    apply,
+                    self.notify(NotificationLevel::Success, tr("toast.settings_saved")),
+                ]);
+            }
+            Message::CheckConfig => {
+                let Some(mut cfg) = self.config_watcher.poll() else {
+                    return Command::none();
+                };
+                for issue in cfg.clamp_recoverable() {
+                    tracing::warn!("Setting out of range, clamped: {}", issue);
+                }
+                if let Err(issues) = cfg.validate() {
+                    let list = issues.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ");
+                    let msg = trf("error.config_invalid", &[&list]);
+                    self.errors.push(msg.clone());
+                    self.log_error(&msg);
+                    return GooglePiczUI::error_timeout();
+                }
+                tracing::info!("Config file changed, applying settings");
+                return self.update(Message::ConfigChanged(Box::new(cfg)));
+            }
+            Message::ConfigChanged(cfg) => {
+                self.slideshow_interval = cfg.slideshow_interval_secs.max(1);
+                self.slideshow_play_videos = cfg.slideshow_play_videos;
+                self.prefetch_after_sync = cfg.prefetch_after_sync;
+                // Leave a theme being previewed in the open dialog alone
+                if !self.settings_open {
+                    self.theme_setting = cfg.theme.clone();
+                }
+                i18n::set_language(Language::from_code(&cfg.language));
+                self.preload_count = cfg.thumbnails_preload;
+                self.preload_threads = cfg.preload_threads;
+                self.thumbnail_queue.set_threads(cfg.preload_threads);
+                let previous = std::mem::replace(
+                    &mut self.restart_required,
+                    cfg.restart_required(&self.startup_config),
+                );
+                let mut commands = Vec::new();
+                if cfg.sync_interval_minutes != self.sync_interval_minutes {
+                    self.sync_interval_minutes = cfg.sync_interval_minutes;
+                    let interval = Duration::from_secs(cfg.sync_interval_minutes * 60);
+                    commands.push(self.send_sync_control(SyncControl::SetInterval(interval)));
+                }
+                if !self.restart_required.is_empty() && self.restart_required != previous {
+                    let keys = self.restart_required.join(", ");
+                    commands.push(self.notify(NotificationLevel::Warning, trf("toast.restart_required", &[&keys])));
+                }
+                let loader = self.image_loader.clone();
+                let (threads, max_mb) = (cfg.preload_threads, cfg.image_cache_max_mb);
+                // Evicting over a lowered cap deletes files, so keep it off the UI thread
+                commands.push(Command::perform(
+                    async move {
+                        let loader = {
+                            let mut loader = loader.lock().await;
+                            loader.set_threads(threads);
+                            loader.clone()
+                        };
+                        tokio::task::spawn_blocking(move || {
+                            loader.set_cache_limit(max_mb);
+                            loader.cache_usage()
+                        })
+                        .await
+                        .unwrap_or_default()
+                    },
+                    Message::ImageCacheUsage,
+                ));
+                let videos = self.video_cache.clone();
+                let max_mb = cfg.video_cache_max_mb;
+                commands.push(Command::perform(
+                    async move {
+                        tokio::task::spawn_blocking(move || {
+                            videos.set_limit(max_mb);
+                            videos.usage()
+                        })
+                        .await
+                        .unwrap_or_default()
+                    },
+                    Message::VideoCacheUsage,
+                ));
+                // More threads can take more of the queued thumbnails
+                commands.push(self.load_queued_thumbnails());
+                return Command::batch(commands);
             }
             Message::ShowCreateAlbumDialog => {
-                self.creating_album = true;
+                // Creating and renaming albums needs the API
+                self.creating_album = !self.offline();
             }
             Message::AlbumTitleChanged(title) => {
                 self.new_album_title = title;
@@ -1098,9 +5400,10 @@ impl Application for GooglePiczUI {
                 self.new_album_title.clear();
                 self.creating_album = false;
                 let cache_manager = self.cache_manager.clone();
+                let profile = self.profile.clone();
                 return Command::perform(
                     async move {
-                        let token = auth::ensure_access_token_valid()
+                        let token = auth::ensure_access_token_valid_for(profile.as_deref())
                             .await
                             .map_err(|e| e.to_string())?;
                         let client = ApiClient::new(token);
@@ -1124,10 +5427,12 @@ impl Application for GooglePiczUI {
             }
             Message::AlbumCreated(result) => match result {
                 Ok(album) => {
+                    let title = album.title.clone().unwrap_or_else(|| tr("common.untitled").into());
                     self.albums.push(album);
+                    return self.notify(NotificationLevel::Success, trf("toast.album_created", &[&title]));
                 }
                 Err(err) => {
-                    let msg = format!("Failed to create album: {}", err);
+                    let msg = format!("{}: {}", tr("error.create_album"), err);
                     self.errors.push(msg.clone());
                     self.log_error(&msg);
                     return GooglePiczUI::error_timeout();
@@ -1161,17 +5466,32 @@ impl Application for GooglePiczUI {
                 }
             }
             Message::AlbumAssigned(res) => {
-                self.assign_selection = None;
-                if let Err(e) = res {
-                    let msg = format!("Failed to assign photo: {}", e);
-                    self.errors.push(msg.clone());
-                    self.log_error(&msg);
-                    return GooglePiczUI::error_timeout();
+                let album = self.assign_selection.take();
+                match res {
+                    Ok(()) => {
+                        if let Some(album) = album {
+                            return Command::batch([
+                                self.notify(
+                                    NotificationLevel::Success,
+                                    trf("toast.photo_assigned", &[&album.title]),
+                                ),
+                                self.load_album_counts(),
+                            ]);
+                        }
+                    }
+                    Err(e) => {
+                        let msg = format!("{}: {}", tr("error.assign_photo"), e);
+                        self.errors.push(msg.clone());
+                        self.log_error(&msg);
+                        return GooglePiczUI::error_timeout();
+                    }
                 }
             }
             Message::ShowRenameAlbumDialog(id, title) => {
-                self.renaming_album = Some(id);
-                self.rename_album_title = title;
+                if !self.offline() {
+                    self.renaming_album = Some(id);
+                    self.rename_album_title = title;
+                }
             }
             Message::RenameAlbumTitleChanged(t) => {
                 self.rename_album_title = t;
@@ -1199,6 +5519,16 @@ impl Application for GooglePiczUI {
                 self.deleting_album = None;
             }
             Message::EscapePressed => {
+                if self.context_menu.is_some() {
+                    self.context_menu = None;
+                    return Command::none();
+                }
+                if self.confirm_reset_cache {
+                    return self.update(Message::CancelResetCache);
+                }
+                if self.album_sidebar.menu.is_some() {
+                    return self.update(Message::CloseAllAlbums);
+                }
                 if self.settings_open {
                     return self.update(Message::CloseSettings);
                 }
@@ -1208,176 +5538,204 @@ impl Application for GooglePiczUI {
                 if self.deleting_album.is_some() {
                     return self.update(Message::CancelDeleteAlbum);
                 }
+                if self.trash.confirm_empty {
+                    return self.update(Message::CancelEmptyTrash);
+                }
                 if self.editing_face.is_some() {
                     return self.update(Message::CancelFaceName);
                 }
-                if let ViewState::SelectedPhoto { .. } = &self.state {
-                    self.state = ViewState::Grid;
+                if self.editing_description {
+                    return self.update(Message::CancelEditDescription);
+                }
+                if self.people.renaming.is_some() || self.people.merging.is_some() {
+                    return self.update(Message::CancelPersonAction);
+                }
+                if self.removing_photos.is_some() {
+                    return self.update(Message::CancelRemovePhoto);
+                }
+                if self.search_history.pinning.is_some() {
+                    self.search_history.pinning = None;
+                    return Command::none();
+                }
+                if self.search_history.open {
+                    self.search_history.open = false;
+                    return Command::none();
+                }
+                if !self.selection.is_empty() {
+                    return self.update(Message::ClearSelection);
+                }
+                #[cfg(feature = "gstreamer")]
+                if self.video_download.is_some() {
+                    return self.update(Message::CloseVideo);
+                }
+                if self.fullscreen.is_some() {
+                    return self.exit_fullscreen();
+                }
+                if let ViewState::SelectedPhoto { .. } | ViewState::Slideshow { .. } = &self.state {
+                    return self.return_to_grid();
                 }
             }
             Message::SearchInputChanged(q) => {
                 self.search_query = q;
+                self.search_generation += 1;
+                let generation = self.search_generation;
+                return Command::perform(
+                    async {
+                        sleep(SEARCH_DEBOUNCE).await;
+                    },
+                    move |_| Message::SearchDebounced(generation),
+                );
+            }
+            Message::SearchModeChanged(mode) => {
+                self.search_mode = mode;
+                self.ui_state.search_mode = mode;
+                self.save_ui_state();
+            }
+            Message::SearchCameraChanged(v) => {
+                self.search_camera = v;
+            }
+            Message::SearchCameraMakeChanged(v) => {
+                self.search_camera_make = v;
+            }
+            Message::SearchMimeChanged(v) => {
+                self.search_mime = v;
+            }
+            Message::SearchStartChanged(v) => {
+                self.search_start = v;
+            }
+            Message::SearchEndChanged(v) => {
+                self.search_end = v;
+            }
+            Message::SearchFavoriteToggled(v) => {
+                self.search_favorite = v;
+            }
+            Message::SearchFacesToggled(v) => {
+                self.search_faces = v;
+            }
+            Message::SearchPersonSelected(name) => {
+                self.search_person = Some(name);
+                return self.update(Message::PerformSearch);
+            }
+            Message::PersonOptionsLoaded(result) => match result {
+                Ok(people) => {
+                    let names = people.into_iter().map(|p| p.name).collect();
+                    self.person_options = iced::widget::combo_box::State::new(names);
+                }
+                // The person filter just offers nobody
+                Err(e) => tracing::warn!(error = %e, "Failed to load people for the search filter"),
+            },
+            Message::PerformSearch => {
+                // Searches run by the typing debounce are not remembered
+                let search = SavedSearch::from_ui(self);
+                if !ActiveFilters::from_search(&search).is_empty() {
+                    search::remember(&mut self.ui_state.recent_searches, search);
+                    self.save_ui_state();
+                }
+                return self.start_search();
+            }
+            Message::LoadMoreResults => {
+                self.search_generation += 1;
+                self.search_limit += SEARCH_LIMIT;
+                return self.run_search();
+            }
+            Message::ClearFilter(kind) => {
+                match kind {
+                    FilterKind::Query => self.search_query.clear(),
+                    FilterKind::CameraModel => self.search_camera.clear(),
+                    FilterKind::CameraMake => self.search_camera_make = None,
+                    FilterKind::MimeType => self.search_mime = None,
+                    FilterKind::DateRange => {
+                        self.search_start.clear();
+                        self.search_end.clear();
+                    }
+                    FilterKind::Favorites => self.clear_favorite_filter(),
+                    FilterKind::Faces => self.clear_faces_filter(),
+                    FilterKind::Person => self.search_person = None,
+                    FilterKind::Album => self.search_album = None,
+                }
+                if self.active_filters().is_empty() {
+                    return self.end_search();
+                }
+                return self.update(Message::PerformSearch);
+            }
+            Message::ClearAllFilters => {
+                self.search_query.clear();
+                self.search_camera.clear();
+                self.search_camera_make = None;
+                self.search_mime = None;
+                self.search_start.clear();
+                self.search_end.clear();
+                self.clear_faces_filter();
+                self.search_person = None;
+                self.clear_favorite_filter();
+                return self.end_search();
             }
-            Message::SearchModeChanged(mode) => {
-                self.search_mode = mode;
+            Message::ToggleRecentSearches => {
+                self.search_history.open = !self.search_history.open;
+                self.search_history.pinning = None;
             }
-            Message::SearchCameraChanged(v) => {
-                self.search_camera = v;
+            Message::SelectSavedSearch(search) => {
+                self.search_history = search::SearchHistory::default();
+                search.apply(self);
+                return self.update(Message::PerformSearch);
             }
-            Message::SearchCameraMakeChanged(v) => {
-                self.search_camera_make = v;
+            Message::PinSearch(index) => {
+                if let Some(search) = self.ui_state.recent_searches.get(index) {
+                    self.search_history.pinning = Some((index, search.label()));
+                }
             }
-            Message::SearchMimeChanged(v) => {
-                self.search_mime = v;
+            Message::PinNameChanged(name) => {
+                if let Some((_, current)) = &mut self.search_history.pinning {
+                    *current = name;
+                }
             }
-            Message::SearchStartChanged(v) => {
-                self.search_start = v;
+            Message::ConfirmPinSearch => {
+                let Some((index, name)) = self.search_history.pinning.take() else {
+                    return Command::none();
+                };
+                let name = name.trim().to_string();
+                if let (false, Some(search)) = (name.is_empty(), self.ui_state.recent_searches.get(index)) {
+                    let pinned = SavedSearch { name: Some(name), ..search.clone() };
+                    // Pinning under a name already in use replaces that filter
+                    self.ui_state.saved_searches.retain(|s| s.name != pinned.name);
+                    self.ui_state.saved_searches.push(pinned);
+                    self.save_ui_state();
+                }
             }
-            Message::SearchEndChanged(v) => {
-                self.search_end = v;
+            Message::DeleteRecentSearch(index) => {
+                if index < self.ui_state.recent_searches.len() {
+                    self.ui_state.recent_searches.remove(index);
+                    self.search_history.pinning = None;
+                    self.save_ui_state();
+                }
             }
-            Message::SearchFavoriteToggled(v) => {
-                self.search_favorite = v;
+            Message::DeleteSavedSearch(index) => {
+                if index < self.ui_state.saved_searches.len() {
+                    self.ui_state.saved_searches.remove(index);
+                    self.save_ui_state();
+                }
             }
-            Message::SearchFacesToggled(v) => {
-                self.search_faces = v;
+            Message::SearchDebounced(generation) => {
+                // Later typing bumped the generation and scheduled its own timer
+                if generation == self.search_generation {
+                    return self.start_search();
+                }
             }
-            Message::PerformSearch => {
-                if let Some(cm) = &self.cache_manager {
-                    let cm = cm.clone();
-                    let query = self.search_query.clone();
-                    let mode = self.search_mode;
-                    let camera = self.search_camera.clone();
-                    let start = self.search_start.clone();
-                    let end = self.search_end.clone();
-                    let fav = self.search_favorite;
-                    let make_sel = self.search_camera_make.clone();
-                    let mime_sel = self.search_mime.clone();
-                    let faces = self.search_faces;
-                    return Command::perform(
-                        async move {
-                            let cache = {
-                                let guard = cm.lock().await;
-                                guard.clone()
-                            };
-                            let base = match mode {
-                                SearchMode::Filename => Some(
-                                    cache
-                                        .get_media_items_by_filename(&query)
-                                        .map_err(|e| e.to_string())?,
-                                ),
-                                SearchMode::Description => Some(
-                                    cache
-                                        .get_media_items_by_description(&query)
-                                        .map_err(|e| e.to_string())?,
-                                ),
-                                SearchMode::Text => Some(
-                                    cache
-                                        .get_media_items_by_text(&query)
-                                        .map_err(|e| e.to_string())?,
-                                ),
-                                _ => None,
-                            };
-
-                            let camera_model_param = if mode == SearchMode::CameraModel {
-                                Some(query.clone())
-                            } else if camera.is_empty() {
-                                None
-                            } else {
-                                Some(camera)
-                            };
-                            let camera_make_param = if mode == SearchMode::CameraMake {
-                                Some(query.clone())
-                            } else {
-                                make_sel.clone()
-                            };
-                            let mime_param = if mode == SearchMode::MimeType {
-                                Some(query.clone())
-                            } else {
-                                mime_sel.clone()
-                            };
-                            let fav_param = if mode == SearchMode::Favoriten || fav {
-                                Some(true)
-                            } else {
-                                None
-                            };
-
-                          
-                          let faces_only = faces;
-let media_items = match search_mode {
-    SearchMode::DateRange => {
-        if let Some((s, e)) = search::parse_date_query(&query) {
-            cache
-                .get_media_items_by_date_range(s, e)
-                .map_err(|e| e.to_string())
-        } else {
-            Ok(Vec::new())
-        }
-    }
-    SearchMode::Description => cache
-        .get_media_items_by_description(&query)
-        .map_err(|e| e.to_string()),
-    SearchMode::Favoriten => cache
-        .get_favorite_media_items()
-        .map_err(|e| e.to_string()),
-    SearchMode::Filename => cache
-        .get_media_items_by_filename(&query)
-        .map_err(|e| e.to_string()),
-    SearchMode::MimeType => cache
-        .get_media_items_by_mime_type(&query)
-        .map_err(|e| e.to_string()),
-    SearchMode::CameraModel => cache
-        .get_media_items_by_camera_model(&query)
-        .map_err(|e| e.to_string()),
-    SearchMode::CameraMake => cache
-        .get_media_items_by_camera_make(&query)
-        .map_err(|e| e.to_string()),
-    SearchMode::Text => cache
-        .get_media_items_by_text(&query)
-        .map_err(|e| e.to_string()),
-}?;
-
-                            let start_dt = search::parse_single_date(&start, false);
-                            let end_dt = search::parse_single_date(&end, true);
-                            let mut extra = cache
-                                .query_media_items_async(
-                                    camera_model_param,
-                                    camera_make_param,
-                                    start_dt,
-                                    end_dt,
-                                    fav_param,
-                                    mime_param,
-                                    if mode == SearchMode::Text { Some(query.clone()) } else { None },
-                                )
-                                .await
-                                .map_err(|e| e.to_string())?;
-
-                            if faces_only {
-                                let mut filtered = Vec::new();
-                                for item in extra.into_iter() {
-                                    if let Ok(list) = cache.get_faces_for_media_item(&item.id).await {
-                                        if !list.is_empty() {
-                                            filtered.push(item);
-                                        }
-                                    }
-                                }
-                                extra = filtered;
-                            }
-
-                            if let Some(base) = &base {
-                                extra.retain(|i| base.iter().any(|b| b.id == i.id));
-                            }
-                            Ok::<_, String>(extra)
-                        },
-                        Message::PhotosLoaded,
-                    );
+            Message::SearchResults(generation, result) => {
+                if generation != self.search_generation {
+                    return Command::none();
+                }
+                if let Ok((_, more)) = &result {
+                    self.search_more = *more;
                 }
+                return self.update(Message::PhotosLoaded(result.map(|(items, _)| items)));
             }
             Message::RenameAlbum(id, title) => {
                 let cache_manager = self.cache_manager.clone();
+                let profile = self.profile.clone();
                 return Command::perform(
                     async move {
-                        let token = auth::ensure_access_token_valid()
+                        let token = auth::ensure_access_token_valid_for(profile.as_deref())
                             .await
                             .map_err(|e| e.to_string())?;
                         let client = ApiClient::new(token);
@@ -1398,28 +5756,104 @@ let media_items = match search_mode {
                 );
             }
             Message::DeleteAlbum(id) => {
-                let cache_manager = self.cache_manager.clone();
-                return Command::perform(
-                    async move {
-                        let token = auth::ensure_access_token_valid()
-                            .await
-                            .map_err(|e| e.to_string())?;
-                        let client = ApiClient::new(token);
-                        client.delete_album(&id).await.map_err(|e| e.to_string())?;
-                        if let Some(cm) = cache_manager {
-                            let cache = {
-                                let guard = cm.lock().await;
-                                guard.clone()
-                            };
-                            cache
-                                .delete_album_async(id.clone())
-                                .await
-                                .map_err(|e| e.to_string())?;
-                        }
-                        Ok::<(), String>(())
+                let Some(index) = self.albums.iter().position(|a| a.id == id) else {
+                    return Command::none();
+                };
+                let album = self.albums.remove(index);
+                self.pending_album_deletes.insert(
+                    id.clone(),
+                    album_delete::PendingAlbumDelete {
+                        album,
+                        index,
+                        media_item_ids: Vec::new(),
+                        notification: None,
                     },
-                    |_: Result<_, _>| Message::LoadAlbums,
                 );
+                return Command::perform(
+                    album_delete::stage(self.cache_manager.clone(), id.clone()),
+                    move |res| Message::AlbumDeleteStaged(id, res),
+                );
+            }
+            Message::AlbumDeleteStaged(id, result) => match result {
+                Ok(ids) => {
+                    let Some(title) = self.pending_album_deletes.get(&id).map(|p| p.album.title.clone()) else {
+                        return Command::none();
+                    };
+                    let notification = self.push_notification(
+                        NotificationLevel::Success,
+                        trf("toast.album_deleted", &[&title.unwrap_or_default()]),
+                        Some(NotificationAction {
+                            label: tr("toast.undo").to_string(),
+                            message: Message::UndoDeleteAlbum(id.clone()),
+                        }),
+                    );
+                    if let Some(pending) = self.pending_album_deletes.get_mut(&id) {
+                        pending.media_item_ids = ids;
+                        pending.notification = Some(notification);
+                    }
+                    return Command::perform(
+                        async {
+                            sleep(album_delete::UNDO_DURATION).await;
+                        },
+                        move |_| Message::AlbumDeleteExpired(id),
+                    );
+                }
+                Err(err) => {
+                    if let Some(pending) = self.pending_album_deletes.remove(&id) {
+                        let index = pending.index.min(self.albums.len());
+                        self.albums.insert(index, pending.album);
+                    }
+                    let msg = format!("{}: {}", tr("error.delete_album"), err);
+                    self.errors.push(msg.clone());
+                    self.log_error(&msg);
+                    return GooglePiczUI::error_timeout();
+                }
+            },
+            Message::UndoDeleteAlbum(id) => {
+                let Some(pending) = self.pending_album_deletes.remove(&id) else {
+                    return Command::none();
+                };
+                if let Some(n) = pending.notification {
+                    self.notifications.retain(|t| t.id != n);
+                }
+                let index = pending.index.min(self.albums.len());
+                self.albums.insert(index, pending.album.clone());
+                return Command::perform(
+                    album_delete::restore(self.cache_manager.clone(), pending),
+                    Message::AlbumRestored,
+                );
+            }
+            Message::AlbumDeleteExpired(id) => {
+                let Some(pending) = self.pending_album_deletes.remove(&id) else {
+                    return Command::none();
+                };
+                if let Some(n) = pending.notification {
+                    self.notifications.retain(|t| t.id != n);
+                }
+                return Command::perform(
+                    album_delete::commit(self.cache_manager.clone(), pending, self.profile.clone()),
+                    Message::AlbumDeleted,
+                );
+            }
+            Message::AlbumRestored(result) => {
+                if let Err(err) = result {
+                    let msg = format!("{}: {}", tr("error.restore_album"), err);
+                    self.errors.push(msg.clone());
+                    self.log_error(&msg);
+                    return GooglePiczUI::error_timeout();
+                }
+            }
+            Message::AlbumDeleted(result) => {
+                if let Err(err) = result {
+                    let msg = format!("{}: {}", tr("error.delete_album"), err);
+                    self.errors.push(msg.clone());
+                    self.log_error(&msg);
+                    // The album is still on the server, so show it again
+                    return Command::batch(vec![
+                        GooglePiczUI::error_timeout(),
+                        Command::perform(async {}, |_| Message::LoadAlbums),
+                    ]);
+                }
             }
         }
         Command::none()
@@ -1428,10 +5862,12 @@ let media_items = match search_mode {
     #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
     fn subscription(&self) -> Subscription<Message> {
         let mut subs: Vec<Subscription<Message>> = Vec::new();
+        // A new id per account switch restarts the sync subscriptions
+        let generation = self.account_menu.generation;
 
         if let Some(progress_rx) = &self.progress_receiver {
             let progress_rx = progress_rx.clone();
-            subs.push(subscription::unfold("progress", progress_rx, |rx| async move {
+            subs.push(subscription::unfold(("progress", generation), progress_rx, |rx| async move {
                 let mut lock = rx.lock().await;
                 let msg = match lock.recv().await {
                     Some(p) => Message::SyncProgress(p),
@@ -1444,7 +5880,7 @@ let media_items = match search_mode {
 
         if let Some(error_rx) = &self.error_receiver {
             let error_rx = error_rx.clone();
-            subs.push(subscription::unfold("errors", error_rx, |rx| async move {
+            subs.push(subscription::unfold(("errors", generation), error_rx, |rx| async move {
                 let mut lock = rx.lock().await;
                 let msg = match lock.recv().await {
                     Some(SyncTaskError::Status { last_synced, message }) => {
@@ -1460,7 +5896,7 @@ let media_items = match search_mode {
 
         if let Some(status_rx) = &self.status_receiver {
             let status_rx = status_rx.clone();
-            subs.push(subscription::unfold("status", status_rx, |rx| async move {
+            subs.push(subscription::unfold(("status", generation), status_rx, |rx| async move {
                 let mut lock = rx.lock().await;
                 let msg = match lock.recv().await {
                     Some(SyncTaskError::Status { last_synced, message }) => {
@@ -1474,47 +5910,149 @@ let media_items = match search_mode {
             }));
         }
 
+        if let Some(focus_rx) = &self.focus_receiver {
+            let focus_rx = focus_rx.clone();
+            subs.push(subscription::unfold("focus", focus_rx, |rx| async move {
+                let mut lock = rx.lock().await;
+                if lock.recv().await.is_none() {
+                    // No more instances can reach this one
+                    std::future::pending::<()>().await;
+                }
+                drop(lock);
+                (Message::FocusRequested, rx)
+            }));
+        }
+
         subs.push(iced::subscription::events().filter_map(|event| match event {
-            iced::Event::Keyboard(iced::keyboard::Event::KeyPressed { key_code, .. }) => {
-                if key_code == iced::keyboard::KeyCode::Escape {
-                    Some(Message::EscapePressed)
-                } else {
-                    None
+            iced::Event::Keyboard(iced::keyboard::Event::KeyPressed { key_code, modifiers }) => {
+                use iced::keyboard::KeyCode;
+                match key_code {
+                    KeyCode::Escape => Some(Message::EscapePressed),
+                    KeyCode::C if modifiers.command() => Some(Message::KeyPressed(key_code)),
+                    KeyCode::Left
+                    | KeyCode::Right
+                    | KeyCode::Up
+                    | KeyCode::Down
+                    | KeyCode::Enter
+                    | KeyCode::Space
+                    | KeyCode::Delete
+                    | KeyCode::I
+                    | KeyCode::Plus
+                    | KeyCode::Equals
+                    | KeyCode::NumpadAdd
+                    | KeyCode::Minus
+                    | KeyCode::NumpadSubtract
+                    | KeyCode::Key0
+                    | KeyCode::Numpad0
+                    | KeyCode::Home
+                    | KeyCode::End
+                    | KeyCode::F11 => Some(Message::KeyPressed(key_code)),
+                    _ => None,
                 }
             }
+            iced::Event::Keyboard(iced::keyboard::Event::ModifiersChanged(modifiers)) => {
+                Some(Message::ModifiersChanged(modifiers))
+            }
             _ => None
         }));
 
+        subs.push(iced::event::listen_with(|event, _status| match event {
+            iced::Event::Window(_, iced::window::Event::Resized { width, height }) => {
+                Some(Message::WindowResized(width, height))
+            }
+            iced::Event::Window(_, iced::window::Event::Moved { x, y }) => Some(Message::WindowMoved(x, y)),
+            iced::Event::Window(_, iced::window::Event::FileDropped(path)) => Some(Message::FileDropped(path)),
+            iced::Event::Window(id, iced::window::Event::CloseRequested) => {
+                Some(Message::WindowCloseRequested(id))
+            }
+            _ => None,
+        }));
+
+        // Ignores capture status: the tile buttons capture the press that starts a drag
+        subs.push(iced::event::listen_with(|event, _status| match event {
+            iced::Event::Mouse(iced::mouse::Event::ButtonPressed(iced::mouse::Button::Left)) => {
+                Some(Message::DragPressed)
+            }
+            iced::Event::Mouse(iced::mouse::Event::ButtonReleased(iced::mouse::Button::Left)) => {
+                Some(Message::DragReleased)
+            }
+            _ => None,
+        }));
+        if !self.drag.items.is_empty() && !self.drag.moving {
+            subs.push(iced::event::listen_with(|event, _status| match event {
+                iced::Event::Mouse(iced::mouse::Event::CursorMoved { .. }) => Some(Message::DragMoved),
+                _ => None,
+            }));
+        }
+
         #[cfg(feature = "gstreamer")]
         if let ViewState::PlayingVideo { player, .. } = &self.state {
             subs.push(player.subscription().map(Message::VideoEvent));
         }
 
+        #[cfg(feature = "gstreamer")]
+        if let Some(download) = &self.video_download {
+            let id = download.media_id.clone();
+            subs.push(subscription::unfold(
+                ("video-download", id.clone()),
+                download.updates.clone(),
+                move |rx| {
+                    let id = id.clone();
+                    async move {
+                        let next = rx.lock().await.recv().await;
+                        match next {
+                            Some(p) => (Message::VideoDownloadProgress(id, p), rx),
+                            // The download is over; its result arrives as a message of its own
+                            None => std::future::pending().await,
+                        }
+                    }
+                },
+            ));
+        }
+
+        if self.fullscreen.is_some() {
+            subs.push(iced::event::listen_with(|event, _status| match event {
+                iced::Event::Mouse(iced::mouse::Event::CursorMoved { .. }) => Some(Message::FullscreenActivity),
+                _ => None,
+            }));
+        }
+
+        subs.push(iced::time::every(status_bar::REFRESH_INTERVAL).map(|_| Message::StatusBarTick));
+        subs.push(iced::time::every(app_config::CONFIG_POLL_INTERVAL).map(|_| Message::CheckConfig));
+
+        if self.sync_activity.retry_at.is_some() {
+            subs.push(iced::time::every(Duration::from_secs(1)).map(|_| Message::SyncTick));
+        }
+
+        if let ViewState::Slideshow { playing: true, .. } = &self.state {
+            subs.push(
+                iced::time::every(Duration::from_secs(self.slideshow_interval))
+                    .map(|_| Message::SlideshowTick),
+            );
+        }
+
         Subscription::batch(subs)
     }
 
     #[cfg_attr(feature = "trace-spans", tracing::instrument(skip(self)))]
     fn view(&self) -> Element<Message> {
-        let placeholder = match self.search_mode {
-            SearchMode::Filename => "Filename",
-            SearchMode::Description => "Description",
-            SearchMode::Text => "Filename or description",
-            SearchMode::Favoriten => "Favorites", 
-            SearchMode::MimeType => "Mime type",
-            SearchMode::CameraModel => "Camera model",
-            SearchMode::CameraMake => "Camera make",
-            SearchMode::DateRange => "YYYY-MM-DD..YYYY-MM-DD",
-        };
+        let placeholder = self.search_mode.placeholder();
 
         let mut header = row![
             text("GooglePicz").size(24),
             button(Icon::new(MaterialSymbol::Refresh).color(Palette::ON_PRIMARY)).style(style::button_primary()).on_press(Message::RefreshPhotos),
-            button(Icon::new(MaterialSymbol::Add).color(Palette::ON_PRIMARY)).style(style::button_primary()).on_press(Message::ShowCreateAlbumDialog),
+            offline::online_only(
+                self,
+                button(Icon::new(MaterialSymbol::Add).color(Palette::ON_PRIMARY)).style(style::button_primary()),
+                Message::ShowCreateAlbumDialog,
+            ),
             button(Icon::new(MaterialSymbol::Settings).color(Palette::ON_PRIMARY)).style(style::button_primary()).on_press(Message::ShowSettings),
+            button(Icon::new(MaterialSymbol::Group).color(Palette::ON_PRIMARY)).style(style::button_primary()).on_press(Message::TogglePeoplePanel),
             text_input(placeholder, &self.search_query)
                 .style(style::text_input())
-                .on_input(Message::SearchInputChanged),
-            text_input("Camera", &self.search_camera)
+                .on_input(Message::SearchInputChanged)
+                .on_submit(Message::PerformSearch),
+            text_input(tr("search.camera"), &self.search_camera)
                 .style(style::text_input())
                 .on_input(Message::SearchCameraChanged),
             pick_list(
@@ -1527,13 +6065,13 @@ let media_items = match search_mode {
                 self.search_mime.clone(),
                 Message::SearchMimeChanged,
             ),
-            text_input("From", &self.search_start)
+            text_input(tr("search.from"), &self.search_start)
                 .style(style::text_input())
                 .on_input(Message::SearchStartChanged),
-            text_input("To", &self.search_end)
+            text_input(tr("search.to"), &self.search_end)
                 .style(style::text_input())
                 .on_input(Message::SearchEndChanged),
-            checkbox("Fav", self.search_favorite, Message::SearchFavoriteToggled)
+            checkbox(tr("search.favorite"), self.search_favorite, Message::SearchFavoriteToggled)
                 .style(style::checkbox_primary()),
             pick_list(
                 &SearchMode::ALL[..],
@@ -1544,23 +6082,26 @@ let media_items = match search_mode {
                 .style(style::button_primary())
                 .on_press(Message::PerformSearch)
         ];
-        header = header
-            .push(search::view(self));
+        header = header.push(search::view(self));
+        if let Some(pill) = offline::pill(self) {
+            header = header.push(pill);
+        }
+        header = header.push(accounts::view(self));
 
         if let Some(album_id) = &self.selected_album {
             header = header
-                .push(
-                    button(Icon::new(MaterialSymbol::Edit).color(Palette::ON_PRIMARY))
-                        .style(style::button_primary())
-                        .on_press(Message::ShowRenameAlbumDialog(
+                .push(offline::online_only(
+                    self,
+                    button(Icon::new(MaterialSymbol::Edit).color(Palette::ON_PRIMARY)).style(style::button_primary()),
+                    Message::ShowRenameAlbumDialog(
                         album_id.clone(),
                         self.albums
                             .iter()
                             .find(|a| a.id == *album_id)
                             .and_then(|a| a.title.clone())
                             .unwrap_or_default(),
-                    )),
-                )
+                    ),
+                ))
                 .push(
                     button(Icon::new(MaterialSymbol::Delete).color(Palette::ON_PRIMARY))
                         .style(style::button_primary())
@@ -1568,21 +6109,82 @@ let media_items = match search_mode {
                 );
         }
 
+        if !self.selection.is_empty() {
+            let album_opts: Vec<AlbumOption> = self
+                .albums
+                .iter()
+                .map(|a| AlbumOption {
+                    id: a.id.clone(),
+                    title: a.title.clone().unwrap_or_else(|| tr("common.untitled").into()),
+                })
+                .collect();
+            let mut chip = row![
+                text(trf("selection.count", &[&self.selection.len()])),
+                pick_list(album_opts, None::<AlbumOption>, Message::BatchAssignAlbum)
+                    .placeholder(tr("selection.add_to_album")),
+                button(Icon::new(MaterialSymbol::Favorite).color(Palette::ON_PRIMARY))
+                    .style(style::button_primary())
+                    .on_press(Message::BatchToggleFavorite),
+                button(Icon::new(MaterialSymbol::Download).color(Palette::ON_PRIMARY))
+                    .style(style::button_primary())
+                    .on_press(Message::ExportPhotos(self.selected_ids())),
+                button(Icon::new(MaterialSymbol::Delete).color(Palette::ON_PRIMARY))
+                    .style(style::button_primary())
+                    .on_press(Message::MoveToTrash(self.selected_ids())),
+            ]
+            .spacing(5)
+            .align_items(iced::Alignment::Center);
+            if self.selected_album.is_some() {
+                chip = chip.push(
+                    button(Icon::new(MaterialSymbol::Remove).color(Palette::ON_SECONDARY))
+                        .style(style::button_secondary())
+                        .on_press(Message::BatchRemoveFromAlbum),
+                );
+            }
+            chip = chip.push(
+                button(Icon::new(MaterialSymbol::Close).color(Palette::ON_PRIMARY))
+                    .style(style::button_primary())
+                    .on_press(Message::ClearSelection),
+            );
+            header = header.push(container(chip).style(style::chip()).padding(4));
+        }
+
         header = header
-            .push(text(self.sync_status.clone()))
+            .push(
+                button(text(self.sync_status.clone()))
+                    .style(iced::theme::Button::Text)
+                    .on_press(Message::ToggleSyncPanel),
+            )
             .push(if self.syncing {
                 progress_bar(0.0..=1.0, ((self.synced % PAGE_SIZE as u64) as f32) / PAGE_SIZE as f32)
                     .width(Length::Fixed(120.0))
             } else {
                 progress_bar(0.0..=1.0, 0.0).width(Length::Fixed(0.0))
             })
+            .push_maybe(self.export.as_ref().map(|job| {
+                row![
+                    text(trf("export.progress", &[&job.done, &job.total])),
+                    progress_bar(0.0..=job.total.max(1) as f32, job.done as f32).width(Length::Fixed(120.0)),
+                ]
+                .spacing(Palette::SPACING / 2)
+                .align_items(iced::Alignment::Center)
+            }))
             .push(text(match self.last_synced {
-                Some(ts) => format!("Last synced {}", ts.to_rfc3339()),
-                None => "Never synced".to_string(),
+                Some(ts) => trf("status.last_synced", &[&ts.to_rfc3339()]),
+                None => tr("status.never_synced").to_string(),
             }))
-            .push(text(format!("Errors: {}", self.errors.len())))
+            .push(text(trf("status.errors", &[&self.errors.len()])))
             .spacing(Palette::SPACING)
             .align_items(iced::Alignment::Center);
+        let header = column![header]
+            .push_maybe(accounts::menu(self))
+            .push_maybe(startup::view(self))
+            .push_maybe(preload::view(self))
+            .push_maybe(sync_panel::view(self))
+            .push_maybe(upload::view(self))
+            .push_maybe(search::recent(self))
+            .push_maybe(search::chips(self))
+            .spacing(Palette::SPACING / 2);
 
         let error_banner = if self.errors.is_empty() {
             None
@@ -1591,7 +6193,7 @@ let media_items = match search_mode {
             for (i, msg) in self.errors.iter().enumerate() {
                 let row = row![
                     text(msg.clone()).size(16),
-                    button("Dismiss")
+                    button(tr("errors.dismiss"))
                         .style(style::button_primary())
                         .on_press(Message::DismissError(i))
                 ]
@@ -1601,8 +6203,8 @@ let media_items = match search_mode {
             }
             let banner = column![
                 row![
-                    text("Operation failed").size(16),
-                    button("Dismiss All")
+                    text(tr("errors.title")).size(16),
+                    button(tr("errors.dismiss_all"))
                         .style(style::button_primary())
                         .on_press(Message::ClearErrors)
                 ]
@@ -1617,121 +6219,95 @@ let media_items = match search_mode {
         let album_dialog = album_dialogs::create_dialog(self);
         let rename_dialog = album_dialogs::rename_dialog(self);
         let delete_dialog = album_dialogs::delete_dialog(self);
+        let empty_trash_dialog = trash::empty_dialog(self);
+        let remove_photo_dialog = album_dialogs::remove_photo_dialog(self);
         let settings_dialog = settings::dialog(self);
+        let reset_cache_dialog = settings::reset_cache_dialog(self);
+        let reauth_dialog = accounts::reauth_dialog(self);
+        let all_albums_dialog = album_sidebar::all_albums_dialog(self);
 
         let content = match &self.state {
             ViewState::Grid => {
-                if self.loading {
-                    column![header, text("Loading photos...").size(16),]
+                if self.trash.open {
+                    column![header, trash::view(self)]
+                } else if self.loading {
+                    column![header, text(tr("grid.loading")).size(16),]
                 } else if self.photos.is_empty() {
                     column![
                         header,
-                        text("No photos found. Make sure you have authenticated and synced your photos.").size(16),
+                        text(tr("grid.empty")).size(16),
                     ]
                 } else {
-                    let mut album_row =
-                        row![
-                            button(text("All"))
-                                .style(style::button_primary())
-                                .on_press(Message::SelectAlbum(None))
-                        ]
-                        .spacing(10);
-                    for album in &self.albums {
-                        let title = album.title.clone().unwrap_or_else(|| "Untitled".to_string());
-                        let controls = row![
-                            button(text(title.clone()))
-                                .style(style::button_primary())
-                                .on_press(Message::SelectAlbum(Some(album.id.clone()))),
-                            button(Icon::new(MaterialSymbol::Edit))
-                                .style(style::button_primary())
-                                .on_press(Message::ShowRenameAlbumDialog(album.id.clone(), title.clone())),
-                            button(Icon::new(MaterialSymbol::Delete))
-                                .style(style::button_secondary())
-                                .on_press(Message::ShowDeleteAlbumDialog(album.id.clone()))
-                        ]
-                        .spacing(5);
-                        album_row = album_row.push(controls);
-                    }
-
-                    let mut rows = column![].spacing(10);
-                    let mut current = row![].spacing(10);
-                    let mut count = 0;
-                    for photo in self.photos.iter().take(self.display_limit) {
-                        let thumb: Element<Message> =
-                            if let Some(handle) = self.thumbnails.get(&photo.id) {
-                                image(handle.clone())
-                                    .width(Length::Fixed(150.0))
-                                    .height(Length::Fixed(150.0))
-                                    .into()
-                            } else {
-                                container(text("Loading..."))
-                                    .width(Length::Fixed(150.0))
-                                    .height(Length::Fixed(150.0))
-                                    .into()
-                            };
-                        let btn = button(thumb)
-                            .style(style::button_primary())
-                            .on_press(Message::SelectPhoto(photo.clone()));
-                        current = current.push(btn);
-                        count += 1;
-                        if count == 4 {
-                            rows = rows.push(current);
-                            current = row![].spacing(10);
-                            count = 0;
-                        }
-                    }
-                    if count > 0 {
-                        rows = rows.push(current);
-                    }
+                    let rows = timeline::view(self);
                     let mut grid = column![].spacing(10);
                     if self.display_limit < self.photos.len() {
                         grid = grid.push(
-                            button("Load more")
+                            button(tr("grid.load_more"))
                                 .style(style::button_primary())
                                 .on_press(Message::LoadMorePhotos),
                         );
+                    } else if self.search_more > 0 {
+                        grid = grid.push(
+                            button(text(trf("search.more", &[&self.search_more])))
+                                .style(style::button_primary())
+                                .on_press(Message::LoadMoreResults),
+                        );
                     }
                     column![
                         header,
-                        scrollable(album_row).height(Length::Shrink),
-                        text(format!("Found {} photos", self.photos.len())).size(16),
-                        scrollable(rows).height(Length::Fill),
-                        grid,
+                        row![
+                            text(trf("grid.found", &[&self.photos.len()])).size(16),
+                            button(Icon::new(MaterialSymbol::PlayArrow).color(Palette::ON_PRIMARY))
+                                .style(style::button_primary())
+                                .on_press(Message::StartSlideshow),
+                            button(Icon::new(MaterialSymbol::Slideshow).color(Palette::ON_PRIMARY))
+                                .style(style::button_primary())
+                                .on_press(Message::StartFullscreenSlideshow),
+                            pick_list(
+                                &Granularity::ALL[..],
+                                Some(self.timeline.granularity),
+                                Message::TimelineGranularityChanged,
+                            ),
+                            text_input(tr("grid.jump"), &self.timeline.jump_input)
+                                .style(style::text_input())
+                                .on_input(Message::JumpDateChanged)
+                                .on_submit(Message::JumpToDate)
+                                .width(Length::Fixed(160.0)),
+                            Icon::new(MaterialSymbol::PhotoSizeSelectLarge),
+                            slider(MIN_TILE_SIZE..=MAX_TILE_SIZE, self.ui_state.tile_size, Message::TileSizeChanged)
+                                .on_release(Message::TileSizeReleased)
+                                .step(10.0)
+                                .width(Length::Fixed(140.0)),
+                        ]
+                        .spacing(Palette::SPACING)
+                        .align_items(iced::Alignment::Center),
+                        text(timeline::current_title(self).unwrap_or_default()).size(18),
                     ]
+                    .push_maybe(drag::hint(self))
+                    .push(
+                        scrollable(rows)
+                            .id(grid_scroll_id())
+                            .height(Length::Fill)
+                            .on_scroll(Message::GridScrolled),
+                    )
+                    .push(grid)
                 }
             }
             ViewState::SelectedPhoto { photo, faces } => {
-                let img: Element<Message> = if let Some(handle) = self.full_images.get(&photo.id) {
-                    let base = image(handle.clone())
-                        .width(Length::Fill)
-                        .height(Length::Fill);
-                    let w = photo.media_metadata.width.parse::<u32>().unwrap_or(0);
-                    let h = photo.media_metadata.height.parse::<u32>().unwrap_or(0);
-                    container(base)
-                        .style(style::card())
-                        .width(Length::Fill)
-                        .height(Length::Fill)
-                        .overlay(FaceRecognizer::new(faces.clone(), w, h).view())
-                        .into()
-                } else {
-                    container(text("Loading..."))
-                        .width(Length::Fill)
-                        .height(Length::Fill)
-                        .into()
-                };
+                let img = self.photo_image(photo, faces);
                 let album_opts: Vec<AlbumOption> = self
                     .albums
                     .iter()
                     .map(|a| AlbumOption {
                         id: a.id.clone(),
-                        title: a.title.clone().unwrap_or_else(|| "Untitled".into()),
+                        title: a.title.clone().unwrap_or_else(|| tr("common.untitled").into()),
                     })
                     .collect();
                 let mut faces_col = column![];
                 for (i, face) in faces.iter().enumerate() {
                     let row_elem = if self.editing_face == Some(i) {
                         row![
-                            text_input("Name", &self.face_name_input)
+                            text_input(tr("faces.name"), &self.face_name_input)
                                 .style(style::text_input())
                                 .on_input(Message::FaceNameChanged),
                             button(Icon::new(MaterialSymbol::Save).color(Palette::ON_PRIMARY))
@@ -1743,30 +6319,107 @@ let media_items = match search_mode {
                         ]
                     } else {
                         let (x, y, w, h) = face.rect;
-                        let label = format!(
-                            "Face {} ({},{},{},{}): {}",
-                            i + 1,
-                            x,
-                            y,
-                            w,
-                            h,
-                            face.name.clone().unwrap_or_else(|| "Unknown".into())
-                        );
+                        let name = face.name.clone().unwrap_or_else(|| tr("common.unknown").into());
+                        let label = trf("faces.label", &[&(i + 1), &x, &y, &w, &h, &name]);
+                        if face.ignored {
+                            row![
+                                text(trf("faces.ignored_label", &[&label])),
+                                button(tr("faces.unignore"))
+                                    .style(style::button_secondary())
+                                    .on_press(Message::ToggleFaceIgnored(i))
+                            ]
+                        } else {
+                            row![
+                                text(label),
+                                button(tr("faces.rename"))
+                                    .style(style::button_primary())
+                                    .on_press(Message::StartRenameFace(i)),
+                                button(tr("faces.ignore"))
+                                    .style(style::button_secondary())
+                                    .on_press(Message::ToggleFaceIgnored(i))
+                            ]
+                        }
+                    };
+                    faces_col = faces_col.push(row_elem);
+                }
+                if faces.is_empty() {
+                    let detecting = self.detecting_faces.contains(&photo.id);
+                    let (icon, label) = if detecting {
+                        (MaterialSymbol::HourglassEmpty, tr("faces.detecting"))
+                    } else {
+                        (MaterialSymbol::Face, tr("faces.detect"))
+                    };
+                    faces_col = faces_col.push(
+                        button(
+                            row![Icon::new(icon).size(16).color(Palette::ON_PRIMARY), text(label)]
+                                .spacing(Palette::SPACING / 4)
+                                .align_items(iced::Alignment::Center),
+                        )
+                        .style(style::button_primary())
+                        .on_press_maybe((!detecting).then(|| Message::DetectFaces(photo.id.clone()))),
+                    );
+                }
+                if let Some((cluster, name)) = &self.cluster_offer {
+                    faces_col = faces_col.push(
                         row![
-                            text(label),
-                            button("Rename")
+                            text(trf("faces.apply_to_cluster", &[&cluster.photo_count, name])),
+                            button(tr("faces.apply"))
                                 .style(style::button_primary())
-                                .on_press(Message::StartRenameFace(i))
+                                .on_press(Message::NameCluster),
+                            button(Icon::new(MaterialSymbol::Cancel).color(Palette::ON_SECONDARY))
+                                .style(style::button_secondary())
+                                .on_press(Message::DismissClusterOffer),
                         ]
-                    };
-                    faces_col = faces_col.push(row_elem);
+                        .spacing(Palette::SPACING / 4)
+                        .align_items(iced::Alignment::Center),
+                    );
                 }
+                #[cfg(feature = "clipboard")]
+                let copy_image_button = Some(
+                    button(Icon::new(MaterialSymbol::ContentCopy).color(Palette::ON_PRIMARY))
+                        .style(style::button_primary())
+                        .on_press(Message::CopyImage),
+                );
+                #[cfg(not(feature = "clipboard"))]
+                let copy_image_button: Option<Element<Message>> = None;
+                let heart_color = if photo.is_favorite {
+                    Palette::SECONDARY
+                } else {
+                    Palette::ON_PRIMARY
+                };
                 let mut col = column![
                     header,
-                    button(Icon::new(MaterialSymbol::Close).color(Palette::ON_PRIMARY))
-                        .style(style::button_primary())
-                        .on_press(Message::ClosePhoto),
-                    img,
+                    row![
+                        button(Icon::new(MaterialSymbol::Close).color(Palette::ON_PRIMARY))
+                            .style(style::button_primary())
+                            .on_press(Message::ClosePhoto),
+                        button(Icon::new(MaterialSymbol::Favorite).color(heart_color))
+                            .style(style::button_primary())
+                            .on_press(Message::ToggleFavorite(photo.id.clone())),
+                        button(Icon::new(MaterialSymbol::Info).color(Palette::ON_PRIMARY))
+                            .style(style::button_primary())
+                            .on_press(Message::ToggleInfoPanel),
+                        button(Icon::new(MaterialSymbol::Fullscreen).color(Palette::ON_PRIMARY))
+                            .style(style::button_primary())
+                            .on_press(Message::ToggleFullscreen),
+                        button(Icon::new(MaterialSymbol::Download).color(Palette::ON_PRIMARY))
+                            .style(style::button_primary())
+                            .on_press(Message::ExportPhotos(vec![photo.id.clone()])),
+                        button(Icon::new(MaterialSymbol::Link).color(Palette::ON_PRIMARY))
+                            .style(style::button_primary())
+                            .on_press_maybe((!photo.product_url.is_empty()).then_some(Message::CopyLink)),
+                    ]
+                    .push_maybe(copy_image_button)
+                    .push_maybe(self.selected_album.is_some().then(|| {
+                        button(Icon::new(MaterialSymbol::Remove).color(Palette::ON_SECONDARY))
+                            .style(style::button_secondary())
+                            .on_press(Message::ShowRemovePhotoDialog(photo.id.clone()))
+                    }))
+                    .spacing(Palette::SPACING),
+                    match info_panel::view(self, photo, faces) {
+                        Some(panel) => Element::from(row![img, panel].spacing(Palette::SPACING)),
+                        None => img,
+                    },
                     faces_col,
                     pick_list(
                         album_opts,
@@ -1775,7 +6428,27 @@ let media_items = match search_mode {
                     )
                 ];
                 #[cfg(feature = "gstreamer")]
-                if photo.mime_type.starts_with("video/") {
+                if let Some(download) = self.video_download.as_ref().filter(|d| d.media_id == photo.id) {
+                    let progress = download.buffer.progress;
+                    let label = match progress.total {
+                        Some(total) => trf(
+                            "video.downloading_of",
+                            &[&status_bar::format_size(progress.downloaded), &status_bar::format_size(total)],
+                        ),
+                        None => trf("video.downloading", &[&status_bar::format_size(progress.downloaded)]),
+                    };
+                    col = col.push(
+                        row![
+                            progress_bar(0.0..=1.0, progress.fraction().unwrap_or(0.0)).width(Length::Fill),
+                            text(label),
+                            button(Icon::new(MaterialSymbol::Close).color(Palette::ON_PRIMARY))
+                                .style(style::button_primary())
+                                .on_press(Message::CloseVideo),
+                        ]
+                        .spacing(Palette::SPACING)
+                        .align_items(iced::Alignment::Center),
+                    );
+                } else if photo.mime_type.starts_with("video/") {
                     col = col.push(
                         button(Icon::new(MaterialSymbol::PlayArrow).color(Palette::ON_PRIMARY))
                             .style(style::button_primary())
@@ -1784,7 +6457,7 @@ let media_items = match search_mode {
                 }
                 #[cfg(not(feature = "gstreamer"))]
                 if photo.mime_type.starts_with("video/") {
-                    col = col.push(text("Video playback not available"));
+                    col = col.push(text(tr("video.unavailable")));
                 }
                 col
             }
@@ -1795,6 +6468,9 @@ let media_items = match search_mode {
                     .unwrap_or_else(|| image::Handle::from_pixels(1, 1, vec![0, 0, 0, 0]));
                 let duration = player.duration_seconds();
                 let position = player.position_seconds();
+                let download = self.video_download.as_ref();
+                let buffered = download.map_or(duration, |d| d.buffer.buffered_secs(duration));
+                let buffered_fraction = if duration > 0.0 { (buffered / duration) as f32 } else { 1.0 };
                 let play_icon = if matches!(player.play_status(), PlayStatus::Playing) {
                     MaterialSymbol::Pause
                 } else {
@@ -1806,18 +6482,63 @@ let media_items = match search_mode {
                         button(Icon::new(play_icon).color(Palette::ON_PRIMARY))
                             .style(style::button_primary())
                             .on_press(Message::ToggleVideoPlay),
-                        slider(0.0..=duration, position, Message::SeekVideo)
-                            .style(style::slider_primary())
-                            .width(Length::Fill),
+                        column![
+                            slider(0.0..=duration, position, Message::SeekVideo)
+                                .style(style::slider_primary())
+                                .width(Length::Fill),
+                            // Downloaded part of the video; seeking stops at its end
+                            progress_bar(0.0..=1.0, buffered_fraction).height(Length::Fixed(4.0)),
+                        ]
+                        .spacing(2)
+                        .width(Length::Fill),
+                    ]
+                    .push_maybe(
+                        download
+                            .filter(|d| d.buffer.is_stalled())
+                            .map(|_| text(tr("video.buffering"))),
+                    )
+                    .push(
                         button(Icon::new(MaterialSymbol::Close).color(Palette::ON_PRIMARY))
                             .style(style::button_primary())
-                            .on_press(Message::CloseVideo)
-                    ]
+                            .on_press(Message::CloseVideo),
+                    )
                     .spacing(Palette::SPACING)
                     .align_items(iced::Alignment::Center),
                     image(frame).width(Length::Fill).height(Length::Fill)
                 ]
             }
+            ViewState::Slideshow { index, playing } => {
+                let play_icon = if *playing { MaterialSymbol::Pause } else { MaterialSymbol::PlayArrow };
+                column![
+                    row![
+                        button(Icon::new(play_icon).color(Palette::ON_PRIMARY))
+                            .style(style::button_primary())
+                            .on_press(Message::ToggleSlideshow),
+                        text(format!("{} / {}", index + 1, self.photos.len())).size(16),
+                        iced::widget::horizontal_space(),
+                        button(Icon::new(MaterialSymbol::Fullscreen).color(Palette::ON_PRIMARY))
+                            .style(style::button_primary())
+                            .on_press(Message::ToggleFullscreen),
+                        button(Icon::new(MaterialSymbol::Close).color(Palette::ON_PRIMARY))
+                            .style(style::button_primary())
+                            .on_press(Message::EscapePressed),
+                    ]
+                    .spacing(Palette::SPACING)
+                    .align_items(iced::Alignment::Center),
+                    self.slide_image(*index),
+                ]
+                .spacing(Palette::SPACING)
+            }
+        };
+
+        let content: Element<Message> = match (&self.state, fullscreen::view(self)) {
+            (_, Some(view)) => view,
+            (ViewState::Grid, None) => row![album_sidebar::view(self)]
+                .push_maybe(people::view(self))
+                .push(content)
+                .spacing(Palette::SPACING)
+                .into(),
+            _ => content.into(),
         };
 
         let mut base = column![].spacing(Palette::SPACING);
@@ -1834,15 +6555,36 @@ let media_items = match search_mode {
         if let Some(d) = delete_dialog {
             base = base.push(d);
         }
+        if let Some(d) = empty_trash_dialog {
+            base = base.push(d);
+        }
+        if let Some(d) = remove_photo_dialog {
+            base = base.push(d);
+        }
         if let Some(d) = settings_dialog {
             base = base.push(d);
         }
+        if let Some(d) = reset_cache_dialog {
+            base = base.push(d);
+        }
+        if let Some(d) = all_albums_dialog {
+            base = base.push(d);
+        }
+        if let Some(d) = reauth_dialog {
+            base = base.push(d);
+        }
+        if let Some(toasts) = notifications::view(self) {
+            base = base.push(toasts);
+        }
+        if self.fullscreen.is_none() {
+            base = base.push(status_bar::view(self));
+        }
 
-        container(base)
+        let window = container(base)
             .style(style::card())
             .width(Length::Fill)
             .height(Length::Fill)
-            .padding(Palette::SPACING)
-            .into()
+            .padding(if self.fullscreen.is_some() { 0 } else { Palette::SPACING });
+        context_menu::Anchor::new(window, context_menu::view(self), Message::CloseContextMenu).into()
     }
 }
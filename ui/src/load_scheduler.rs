@@ -0,0 +1,273 @@
+//! Worker slots for `ImageLoader`, handed out by priority.
+//!
+//! A set number of loads run at once. When a slot frees up it goes to the
+//! highest priority request waiting, oldest first within a priority, so
+//! look-ahead and background loads never hold up tiles on screen. Waiting
+//! requests can be cancelled through their `CancelToken`.
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
+use std::sync::{Arc, Mutex};
+
+use tokio::sync::{oneshot, Notify};
+
+/// How urgently an image is needed; later variants are served first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Priority {
+    /// Bulk preloading nobody is waiting for
+    Background,
+    /// Tiles just beyond the visible rows
+    Prefetch,
+    /// On screen right now
+    Visible,
+}
+
+/// Cancels the requests it was passed to while they wait for a slot.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<TokenState>);
+
+#[derive(Debug, Default)]
+struct TokenState {
+    cancelled: AtomicBool,
+    notify: Notify,
+}
+
+impl CancelToken {
+    pub fn cancel(&self) {
+        self.0.cancelled.store(true, AtomicOrdering::SeqCst);
+        self.0.notify.notify_waiters();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.cancelled.load(AtomicOrdering::SeqCst)
+    }
+
+    /// Resolves once `cancel` has been called.
+    pub async fn cancelled(&self) {
+        loop {
+            // Registered before the check, so a cancel in between still wakes it
+            let notified = self.0.notify.notified();
+            if self.is_cancelled() {
+                return;
+            }
+            notified.await;
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Waiter {
+    priority: Priority,
+    seq: u64,
+    wake: oneshot::Sender<()>,
+}
+
+impl Ord for Waiter {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Max-heap: higher priority first, then the earlier request
+        self.priority
+            .cmp(&other.priority)
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+impl PartialOrd for Waiter {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Waiter {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Waiter {}
+
+#[derive(Debug, Default)]
+struct State {
+    workers: usize,
+    running: usize,
+    waiting: BinaryHeap<Waiter>,
+    next_seq: u64,
+}
+
+#[derive(Debug)]
+pub struct LoadScheduler {
+    state: Mutex<State>,
+}
+
+/// A running load; dropping it passes the slot on.
+#[derive(Debug)]
+pub struct Slot {
+    scheduler: Arc<LoadScheduler>,
+}
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        self.scheduler.release();
+    }
+}
+
+/// A queued request. If it is dropped after being woken but before taking
+/// the slot, the slot is passed on instead of lost.
+struct Waiting {
+    wake: oneshot::Receiver<()>,
+    scheduler: Arc<LoadScheduler>,
+    granted: bool,
+}
+
+impl Drop for Waiting {
+    fn drop(&mut self) {
+        if self.granted {
+            return;
+        }
+        self.wake.close();
+        if self.wake.try_recv().is_ok() {
+            self.scheduler.release();
+        }
+    }
+}
+
+impl LoadScheduler {
+    pub fn new(workers: usize) -> Self {
+        Self {
+            state: Mutex::new(State { workers: workers.max(1), ..State::default() }),
+        }
+    }
+
+    /// Run up to `workers` loads at once from now on. Extra slots go to
+    /// waiting requests right away; with fewer, running loads finish first.
+    pub fn set_workers(&self, workers: usize) {
+        let mut state = self.lock();
+        state.workers = workers.max(1);
+        while state.running < state.workers {
+            let Some(waiter) = state.waiting.pop() else {
+                break;
+            };
+            if waiter.wake.send(()).is_ok() {
+                state.running += 1;
+            }
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Wait for a free slot, or `None` if `cancel` fires first.
+    pub async fn acquire(self: &Arc<Self>, priority: Priority, cancel: &CancelToken) -> Option<Slot> {
+        if cancel.is_cancelled() {
+            return None;
+        }
+        let wake = {
+            let mut state = self.lock();
+            if state.running < state.workers {
+                state.running += 1;
+                return Some(Slot { scheduler: self.clone() });
+            }
+            let (wake, woken) = oneshot::channel();
+            let seq = state.next_seq;
+            state.next_seq += 1;
+            state.waiting.push(Waiter { priority, seq, wake });
+            woken
+        };
+        let mut waiting = Waiting { wake, scheduler: self.clone(), granted: false };
+        let granted = tokio::select! {
+            woken = &mut waiting.wake => woken.is_ok(),
+            _ = cancel.cancelled() => false,
+        };
+        waiting.granted = granted;
+        granted.then(|| Slot { scheduler: self.clone() })
+    }
+
+    /// Hand a finished slot to the most urgent request still waiting.
+    fn release(&self) {
+        let mut state = self.lock();
+        // Over the limit after `set_workers` lowered it
+        if state.running <= state.workers {
+            while let Some(waiter) = state.waiting.pop() {
+                // Fails if the request was cancelled meanwhile
+                if waiter.wake.send(()).is_ok() {
+                    return;
+                }
+            }
+        }
+        state.running -= 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_free_slot_goes_to_highest_priority() {
+        let scheduler = Arc::new(LoadScheduler::new(1));
+        let first = scheduler.acquire(Priority::Visible, &CancelToken::default()).await.unwrap();
+
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let cancelled = CancelToken::default();
+        let mut tasks = Vec::new();
+        for (priority, token) in [
+            (Priority::Background, CancelToken::default()),
+            (Priority::Prefetch, cancelled.clone()),
+            (Priority::Prefetch, CancelToken::default()),
+            (Priority::Visible, CancelToken::default()),
+        ] {
+            let (scheduler, order) = (scheduler.clone(), order.clone());
+            tasks.push(tokio::spawn(async move {
+                let slot = scheduler.acquire(priority, &token).await;
+                if slot.is_some() {
+                    order.lock().unwrap().push(priority);
+                }
+            }));
+            // Queue them in this order
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        cancelled.cancel();
+        drop(first);
+        for task in tasks {
+            task.await.unwrap();
+        }
+        assert_eq!(
+            *order.lock().unwrap(),
+            vec![Priority::Visible, Priority::Prefetch, Priority::Background]
+        );
+        assert_eq!(scheduler.lock().running, 0);
+    }
+
+    #[tokio::test]
+    async fn test_set_workers_resizes_running_loads() {
+        let scheduler = Arc::new(LoadScheduler::new(1));
+        let token = CancelToken::default();
+        let first = scheduler.acquire(Priority::Visible, &token).await.unwrap();
+        let waiting = {
+            let scheduler = scheduler.clone();
+            tokio::spawn(async move { scheduler.acquire(Priority::Visible, &CancelToken::default()).await })
+        };
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        // The waiting request gets the new slot without a release
+        scheduler.set_workers(2);
+        let second = waiting.await.unwrap().unwrap();
+        assert_eq!(scheduler.lock().running, 2);
+
+        // Lowered again: the next slot freed is not handed on
+        scheduler.set_workers(1);
+        let queued = {
+            let scheduler = scheduler.clone();
+            tokio::spawn(async move { scheduler.acquire(Priority::Visible, &CancelToken::default()).await })
+        };
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        drop(first);
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert!(!queued.is_finished());
+        drop(second);
+        assert!(queued.await.unwrap().is_some());
+        assert_eq!(scheduler.lock().running, 0);
+    }
+}
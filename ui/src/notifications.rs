@@ -0,0 +1,78 @@
+//! Short-lived toasts for confirmations and other transient messages.
+//!
+//! Each notification carries its own timer; genuine errors still go to the
+//! error banner.
+
+use std::time::Instant;
+
+use iced::alignment::Horizontal;
+use iced::widget::{button, column, container, row, text};
+use iced::{Element, Length};
+
+use crate::style::{self, Palette};
+use crate::{Icon, MaterialSymbol, Message};
+
+/// How long a toast stays on screen unless dismissed earlier.
+pub const NOTIFICATION_DURATION: std::time::Duration = std::time::Duration::from_secs(4);
+/// Older toasts are dropped once more than this many are queued.
+pub const MAX_NOTIFICATIONS: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotificationLevel {
+    Info,
+    Success,
+    Warning,
+}
+
+#[derive(Debug, Clone)]
+pub struct Notification {
+    pub id: u64,
+    pub level: NotificationLevel,
+    pub text: String,
+    pub created_at: Instant,
+    /// Button shown next to the text, e.g. undo
+    pub action: Option<NotificationAction>,
+}
+
+#[derive(Debug, Clone)]
+pub struct NotificationAction {
+    pub label: String,
+    pub message: Message,
+}
+
+/// Toasts stacked in the bottom right corner, newest last.
+pub fn view<'a>(ui: &crate::GooglePiczUI) -> Option<Element<'a, Message>> {
+    if ui.notifications.is_empty() {
+        return None;
+    }
+    let mut col = column![].spacing(Palette::SPACING / 2).align_items(iced::Alignment::End);
+    for n in &ui.notifications {
+        let action = n.action.as_ref().map(|a| {
+            button(text(a.label.clone()).size(14))
+                .style(style::button_secondary())
+                .on_press(a.message.clone())
+        });
+        col = col.push(
+            container(
+                row![text(n.text.clone()).size(14)]
+                    .push_maybe(action)
+                    .push(
+                        button(Icon::new(MaterialSymbol::Close).size(14))
+                            .style(style::button_secondary())
+                            .on_press(Message::DismissNotification(n.id)),
+                    )
+                    .spacing(Palette::SPACING / 2)
+                    .align_items(iced::Alignment::Center),
+            )
+            .style(style::toast(n.level))
+            .padding(Palette::SPACING / 2)
+            .max_width(360),
+        );
+    }
+    Some(
+        container(col)
+            .width(Length::Fill)
+            .align_x(Horizontal::Right)
+            .into(),
+    )
+}
@@ -0,0 +1,43 @@
+//! Offline mode. Once an API call fails to reach Google the library, albums
+//! and album contents come from the cache, a pill in the header says so, and
+//! actions that only work online are disabled until a call succeeds again.
+
+use iced::widget::tooltip::Position;
+use iced::widget::{container, row, text, tooltip, Button};
+use iced::Element;
+
+use crate::i18n::tr;
+use crate::style::{self, Palette};
+use crate::{Icon, MaterialSymbol, Message};
+
+/// "Offline — showing cached data", only while offline.
+pub fn pill<'a>(ui: &crate::GooglePiczUI) -> Option<Element<'a, Message>> {
+    if !ui.offline() {
+        return None;
+    }
+    Some(
+        container(
+            row![Icon::new(MaterialSymbol::CloudOff).size(14), text(tr("offline.pill")).size(12)]
+                .spacing(4)
+                .align_items(iced::Alignment::Center),
+        )
+        .style(style::chip())
+        .padding([2, Palette::SPACING / 2])
+        .into(),
+    )
+}
+
+/// `button` sending `message`, or disabled with an explanation while offline.
+pub fn online_only<'a>(
+    ui: &crate::GooglePiczUI,
+    button: Button<'a, Message>,
+    message: Message,
+) -> Element<'a, Message> {
+    if ui.offline() {
+        tooltip(button, text(tr("offline.unavailable")).size(12), Position::Bottom)
+            .style(style::card())
+            .into()
+    } else {
+        button.on_press(message).into()
+    }
+}
@@ -0,0 +1,179 @@
+//! Left-hand panel listing the people found in the library.
+
+use std::collections::HashMap;
+
+use cache::PersonEntry;
+use iced::widget::image::Handle;
+use iced::widget::{button, column, container, image, pick_list, row, scrollable, text, text_input};
+use iced::{Element, Length};
+
+use crate::i18n::{tr, trf};
+use crate::style::{self, Palette};
+use crate::{Icon, MaterialSymbol, Message};
+
+/// Side length of the face crops drawn next to each person.
+const AVATAR_SIZE: f32 = 48.0;
+/// Width of the panel beside the grid; the grid fits its columns into the rest.
+pub const PANEL_WIDTH: f32 = 280.0;
+
+/// Identifies a panel entry: a name, or `None` with the cluster of unnamed faces.
+pub type PersonKey = (Option<String>, Option<u32>);
+
+#[derive(Debug, Default)]
+pub struct People {
+    pub open: bool,
+    pub entries: Vec<PersonEntry>,
+    /// Face crops keyed by person; `(None, None)` is the unnamed-faces group
+    pub crops: HashMap<PersonKey, Handle>,
+    /// Person whose photos fill the grid, if any
+    pub selected: Option<Option<String>>,
+    /// Cluster of unnamed faces whose photos fill the grid instead
+    pub selected_cluster: Option<u32>,
+    pub renaming: Option<String>,
+    pub rename_input: String,
+    pub merging: Option<String>,
+}
+
+impl People {
+    /// Names other than `name`, offered as merge targets.
+    fn others(&self, name: &str) -> Vec<String> {
+        self.entries
+            .iter()
+            .filter_map(|p| p.name.clone())
+            .filter(|n| n != name)
+            .collect()
+    }
+
+    pub fn key(entry: &PersonEntry) -> PersonKey {
+        (entry.name.clone(), entry.cluster)
+    }
+
+    pub fn label(entry: &PersonEntry) -> String {
+        match (&entry.name, entry.cluster) {
+            (Some(name), _) => name.clone(),
+            (None, Some(cluster)) => trf("people.cluster", &[&cluster]),
+            (None, None) => tr("people.unnamed").to_string(),
+        }
+    }
+
+    /// Whether the grid shows the photos of a person or cluster.
+    pub fn has_selection(&self) -> bool {
+        self.selected.is_some() || self.selected_cluster.is_some()
+    }
+
+    fn is_selected(&self, entry: &PersonEntry) -> bool {
+        match entry.cluster.filter(|_| entry.name.is_none()) {
+            Some(cluster) => self.selected_cluster == Some(cluster),
+            None => self.selected.as_ref() == Some(&entry.name),
+        }
+    }
+}
+
+fn avatar<'a>(people: &People, entry: &PersonEntry) -> Element<'a, Message> {
+    match people.crops.get(&People::key(entry)) {
+        Some(handle) => image(handle.clone())
+            .width(Length::Fixed(AVATAR_SIZE))
+            .height(Length::Fixed(AVATAR_SIZE))
+            .into(),
+        None => container(Icon::new(MaterialSymbol::Person))
+            .width(Length::Fixed(AVATAR_SIZE))
+            .height(Length::Fixed(AVATAR_SIZE))
+            .center_x()
+            .center_y()
+            .into(),
+    }
+}
+
+fn entry<'a>(people: &People, entry: &PersonEntry) -> Element<'a, Message> {
+    let label = People::label(entry);
+    if let (Some(name), Some(renaming)) = (&entry.name, &people.renaming) {
+        if name == renaming {
+            return row![
+                text_input(tr("people.new_name"), &people.rename_input)
+                    .style(style::text_input())
+                    .on_input(Message::PersonNameChanged)
+                    .on_submit(Message::ConfirmRenamePerson),
+                button(Icon::new(MaterialSymbol::Save).color(Palette::ON_PRIMARY))
+                    .style(style::button_primary())
+                    .on_press(Message::ConfirmRenamePerson),
+                button(Icon::new(MaterialSymbol::Cancel).color(Palette::ON_SECONDARY))
+                    .style(style::button_secondary())
+                    .on_press(Message::CancelPersonAction),
+            ]
+            .spacing(Palette::SPACING / 4)
+            .align_items(iced::Alignment::Center)
+            .into();
+        }
+    }
+    if let (Some(name), Some(merging)) = (&entry.name, &people.merging) {
+        if name == merging {
+            return row![
+                pick_list(people.others(name), None::<String>, Message::MergePersonInto)
+                    .placeholder(tr("people.merge_into")),
+                button(Icon::new(MaterialSymbol::Cancel).color(Palette::ON_SECONDARY))
+                    .style(style::button_secondary())
+                    .on_press(Message::CancelPersonAction),
+            ]
+            .spacing(Palette::SPACING / 4)
+            .align_items(iced::Alignment::Center)
+            .into();
+        }
+    }
+    let selected = people.is_selected(entry);
+    let select = match (&entry.name, entry.cluster) {
+        (None, Some(cluster)) => Message::SelectCluster(cluster),
+        (name, _) => Message::SelectPerson(name.clone()),
+    };
+    let person = button(
+        row![
+            avatar(people, entry),
+            column![
+                text(label).size(14),
+                text(trf("people.photo_count", &[&entry.photo_count])).size(12),
+            ]
+        ]
+        .spacing(Palette::SPACING / 2)
+        .align_items(iced::Alignment::Center),
+    )
+    .style(if selected { style::button_primary() } else { style::button_secondary() })
+    .width(Length::Fill)
+    .on_press(select);
+    let mut line = row![person].spacing(Palette::SPACING / 4).align_items(iced::Alignment::Center);
+    // The unnamed group has no name to rename or merge
+    if let Some(name) = &entry.name {
+        line = line
+            .push(
+                button(Icon::new(MaterialSymbol::Edit).size(16))
+                    .style(style::button_secondary())
+                    .on_press(Message::StartRenamePerson(name.clone())),
+            )
+            .push(
+                button(Icon::new(MaterialSymbol::Merge).size(16))
+                    .style(style::button_secondary())
+                    .on_press(Message::StartMergePerson(name.clone())),
+            );
+    }
+    line.into()
+}
+
+pub fn view<'a>(ui: &crate::GooglePiczUI) -> Option<Element<'a, Message>> {
+    let people = &ui.people;
+    if !people.open {
+        return None;
+    }
+    let mut col = column![text(tr("people.title")).size(16)].spacing(Palette::SPACING / 2);
+    if people.entries.is_empty() {
+        col = col.push(text(tr("people.empty")).size(14));
+    }
+    for person in &people.entries {
+        col = col.push(entry(people, person));
+    }
+    Some(
+        container(scrollable(col))
+            .style(style::card())
+            .padding(Palette::SPACING / 2)
+            .width(Length::Fixed(PANEL_WIDTH))
+            .height(Length::Fill)
+            .into(),
+    )
+}
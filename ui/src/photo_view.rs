@@ -0,0 +1,328 @@
+//! Full-size photo with wheel zoom, drag panning and face boxes that follow the view.
+//!
+//! The zoom itself lives in the application state so keyboard shortcuts can
+//! change it and switching photos can reset it; the widget only reports the
+//! zoom it wants next.
+
+use std::time::{Duration, Instant};
+
+use iced::advanced::image::{self as image_renderer, FilterMethod};
+use iced::advanced::layout::{self, Layout};
+use iced::advanced::renderer::{self, Quad};
+use iced::advanced::text::{self as text_renderer, LineHeight, Shaping, Text};
+use iced::advanced::widget::{self, tree, Tree, Widget};
+use iced::advanced::{Clipboard, Shell};
+use iced::alignment::{Horizontal, Vertical};
+use iced::event::{self, Event};
+use iced::mouse;
+use iced::widget::image::Handle;
+use iced::{Border, Color, Element, Length, Pixels, Point, Rectangle, Size, Theme, Vector};
+
+use crate::face_recognizer::face_bounds;
+
+/// Fitting the whole photo into the view.
+pub const MIN_SCALE: f32 = 1.0;
+pub const MAX_SCALE: f32 = 16.0;
+/// Magnification applied per wheel notch or `+`/`-` press.
+pub const ZOOM_STEP: f32 = 1.25;
+/// Wheels reporting pixels scroll this far per notch.
+const PIXELS_PER_NOTCH: f32 = 60.0;
+const DOUBLE_CLICK: Duration = Duration::from_millis(400);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Zoom {
+    /// Magnification relative to fitting the photo; 1.0 shows all of it
+    pub scale: f32,
+    /// Point of the photo at the centre of the view, as fractions of its width and height
+    pub center: (f32, f32),
+}
+
+impl Default for Zoom {
+    fn default() -> Self {
+        Self { scale: MIN_SCALE, center: (0.5, 0.5) }
+    }
+}
+
+fn fit_scale(view: Size, image: Size) -> f32 {
+    (view.width / image.width.max(1.0)).min(view.height / image.height.max(1.0))
+}
+
+/// Centre the photo along an axis it fits into, otherwise keep the view covered.
+fn clamp_axis(pos: f32, len: f32, start: f32, extent: f32) -> f32 {
+    if len <= extent {
+        start + (extent - len) / 2.0
+    } else {
+        pos.clamp(start + extent - len, start)
+    }
+}
+
+impl Zoom {
+    pub fn is_fit(&self) -> bool {
+        self.scale <= MIN_SCALE
+    }
+
+    /// Zoom around the centre of the view, as the keyboard shortcuts do.
+    pub fn zoomed(self, factor: f32) -> Self {
+        let scale = (self.scale * factor).clamp(MIN_SCALE, MAX_SCALE);
+        if scale <= MIN_SCALE {
+            Self::default()
+        } else {
+            Self { scale, ..self }
+        }
+    }
+
+    /// Screen rectangle the photo of `image` pixels covers inside `bounds`.
+    pub fn image_rect(&self, bounds: Rectangle, image: Size) -> Rectangle {
+        let s = fit_scale(bounds.size(), image) * self.scale;
+        let (width, height) = (image.width * s, image.height * s);
+        let center = bounds.center();
+        Rectangle {
+            x: clamp_axis(center.x - self.center.0 * width, width, bounds.x, bounds.width),
+            y: clamp_axis(center.y - self.center.1 * height, height, bounds.y, bounds.height),
+            width,
+            height,
+        }
+    }
+
+    fn from_rect(scale: f32, rect: Rectangle, bounds: Rectangle) -> Self {
+        if scale <= MIN_SCALE {
+            return Self::default();
+        }
+        let x = clamp_axis(rect.x, rect.width, bounds.x, bounds.width);
+        let y = clamp_axis(rect.y, rect.height, bounds.y, bounds.height);
+        let center = bounds.center();
+        Self {
+            scale,
+            center: ((center.x - x) / rect.width, (center.y - y) / rect.height),
+        }
+    }
+
+    /// Zoom by `factor` keeping the photo point under `cursor` in place.
+    pub fn zoomed_at(self, factor: f32, cursor: Point, bounds: Rectangle, image: Size) -> Self {
+        let rect = self.image_rect(bounds, image);
+        let scale = (self.scale * factor).clamp(MIN_SCALE, MAX_SCALE);
+        let ratio = scale / self.scale;
+        let zoomed = Rectangle {
+            x: cursor.x - (cursor.x - rect.x) * ratio,
+            y: cursor.y - (cursor.y - rect.y) * ratio,
+            width: rect.width * ratio,
+            height: rect.height * ratio,
+        };
+        Self::from_rect(scale, zoomed, bounds)
+    }
+
+    pub fn panned(self, delta: Vector, bounds: Rectangle, image: Size) -> Self {
+        Self::from_rect(self.scale, self.image_rect(bounds, image) + delta, bounds)
+    }
+
+    /// Switch between fitting the photo and showing it at 100% around `cursor`.
+    pub fn toggled_actual(self, cursor: Point, bounds: Rectangle, image: Size) -> Self {
+        if !self.is_fit() {
+            return Self::default();
+        }
+        let actual = 1.0 / fit_scale(bounds.size(), image);
+        self.zoomed_at(actual / self.scale, cursor, bounds, image)
+    }
+}
+
+#[derive(Debug, Default)]
+struct State {
+    /// Cursor position and zoom when the current pan started
+    grab: Option<(Point, Zoom)>,
+    last_click: Option<Instant>,
+}
+
+pub struct PhotoView<'a, Message> {
+    handle: Handle,
+    zoom: Zoom,
+    faces: &'a [face_recognition::Face],
+    /// Pixel size the face boxes were detected at
+    source: Size,
+    on_change: fn(Zoom) -> Message,
+}
+
+impl<'a, Message> PhotoView<'a, Message> {
+    pub fn new(handle: Handle, zoom: Zoom, on_change: fn(Zoom) -> Message) -> Self {
+        Self { handle, zoom, faces: &[], source: Size::ZERO, on_change }
+    }
+
+    pub fn faces(mut self, faces: &'a [face_recognition::Face], width: u32, height: u32) -> Self {
+        self.faces = faces;
+        self.source = Size::new(width as f32, height as f32);
+        self
+    }
+
+    fn image_size(&self, renderer: &iced::Renderer) -> Size {
+        let size = image_renderer::Renderer::dimensions(renderer, &self.handle);
+        if size.width == 0 || size.height == 0 {
+            self.source
+        } else {
+            Size::new(size.width as f32, size.height as f32)
+        }
+    }
+}
+
+impl<'a, Message> Widget<Message, Theme, iced::Renderer> for PhotoView<'a, Message> {
+    fn tag(&self) -> tree::Tag {
+        tree::Tag::of::<State>()
+    }
+
+    fn state(&self) -> tree::State {
+        tree::State::new(State::default())
+    }
+
+    fn size(&self) -> Size<Length> {
+        Size::new(Length::Fill, Length::Fill)
+    }
+
+    fn layout(
+        &self,
+        _tree: &mut widget::Tree,
+        _renderer: &iced::Renderer,
+        limits: &layout::Limits,
+    ) -> layout::Node {
+        layout::Node::new(limits.max())
+    }
+
+    fn on_event(
+        &mut self,
+        tree: &mut Tree,
+        event: Event,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        renderer: &iced::Renderer,
+        _clipboard: &mut dyn Clipboard,
+        shell: &mut Shell<'_, Message>,
+        _viewport: &Rectangle,
+    ) -> event::Status {
+        let bounds = layout.bounds();
+        let image = self.image_size(renderer);
+        let state = tree.state.downcast_mut::<State>();
+        match event {
+            Event::Mouse(mouse::Event::WheelScrolled { delta }) => {
+                let Some(position) = cursor.position_over(bounds) else {
+                    return event::Status::Ignored;
+                };
+                let notches = match delta {
+                    mouse::ScrollDelta::Lines { y, .. } => y,
+                    mouse::ScrollDelta::Pixels { y, .. } => y / PIXELS_PER_NOTCH,
+                };
+                let zoom = self.zoom.zoomed_at(ZOOM_STEP.powf(notches), position, bounds, image);
+                shell.publish((self.on_change)(zoom));
+                event::Status::Captured
+            }
+            Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) => {
+                let Some(position) = cursor.position_over(bounds) else {
+                    return event::Status::Ignored;
+                };
+                let now = Instant::now();
+                if state.last_click.is_some_and(|t| now.duration_since(t) < DOUBLE_CLICK) {
+                    state.last_click = None;
+                    state.grab = None;
+                    let zoom = self.zoom.toggled_actual(position, bounds, image);
+                    shell.publish((self.on_change)(zoom));
+                } else {
+                    state.last_click = Some(now);
+                    if !self.zoom.is_fit() {
+                        state.grab = Some((position, self.zoom));
+                    }
+                }
+                event::Status::Captured
+            }
+            Event::Mouse(mouse::Event::CursorMoved { position }) => match state.grab {
+                Some((origin, start)) => {
+                    let zoom = start.panned(position - origin, bounds, image);
+                    shell.publish((self.on_change)(zoom));
+                    event::Status::Captured
+                }
+                None => event::Status::Ignored,
+            },
+            Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)) => {
+                if state.grab.take().is_some() {
+                    event::Status::Captured
+                } else {
+                    event::Status::Ignored
+                }
+            }
+            _ => event::Status::Ignored,
+        }
+    }
+
+    fn mouse_interaction(
+        &self,
+        tree: &Tree,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        _viewport: &Rectangle,
+        _renderer: &iced::Renderer,
+    ) -> mouse::Interaction {
+        let state = tree.state.downcast_ref::<State>();
+        if state.grab.is_some() {
+            mouse::Interaction::Grabbing
+        } else if !cursor.is_over(layout.bounds()) {
+            mouse::Interaction::Idle
+        } else if self.zoom.is_fit() {
+            mouse::Interaction::ZoomIn
+        } else {
+            mouse::Interaction::Grab
+        }
+    }
+
+    fn draw(
+        &self,
+        _tree: &Tree,
+        renderer: &mut iced::Renderer,
+        _theme: &Theme,
+        _style: &renderer::Style,
+        layout: Layout<'_>,
+        _cursor: mouse::Cursor,
+        _viewport: &Rectangle,
+    ) {
+        let bounds = layout.bounds();
+        let image = self.image_size(renderer);
+        let rect = self.zoom.image_rect(bounds, image);
+        // Boxes were detected on the original, which may differ from the downloaded size
+        let source = if self.source.width > 0.0 { self.source } else { image };
+        let red = Color::from_rgb(1.0, 0.0, 0.0);
+        renderer::Renderer::with_layer(renderer, bounds, |renderer| {
+            image_renderer::Renderer::draw(renderer, self.handle.clone(), FilterMethod::Linear, rect);
+            for face in self.faces.iter().filter(|f| !f.ignored) {
+                let r = face_bounds(face.rect, source, rect);
+                renderer::Renderer::fill_quad(
+                    renderer,
+                    Quad {
+                        bounds: r,
+                        border: Border { color: red, width: 2.0, radius: 0.0.into() },
+                        ..Quad::default()
+                    },
+                    Color::TRANSPARENT,
+                );
+                if let Some(name) = &face.name {
+                    let font = text_renderer::Renderer::default_font(renderer);
+                    text_renderer::Renderer::fill_text(
+                        renderer,
+                        Text {
+                            content: name,
+                            bounds: Size::new(f32::INFINITY, 20.0),
+                            size: Pixels(16.0),
+                            line_height: LineHeight::default(),
+                            font,
+                            horizontal_alignment: Horizontal::Left,
+                            vertical_alignment: Vertical::Top,
+                            shaping: Shaping::Basic,
+                        },
+                        Point::new(r.x, (r.y - 18.0).max(bounds.y)),
+                        red,
+                        bounds,
+                    );
+                }
+            }
+        });
+    }
+}
+
+impl<'a, Message: 'a> From<PhotoView<'a, Message>> for Element<'a, Message> {
+    fn from(view: PhotoView<'a, Message>) -> Self {
+        Element::new(view)
+    }
+}
@@ -0,0 +1,187 @@
+//! Chunked thumbnail loading with progress for large batches.
+//!
+//! Requests are queued and only a few are in flight at once, so a large
+//! preload neither floods the loader nor leaves the window looking frozen.
+
+use std::collections::{HashMap, VecDeque};
+
+use iced::widget::{button, container, progress_bar, row, text};
+use iced::{Element, Length};
+
+use crate::i18n::{tr, trf};
+use crate::load_scheduler::{CancelToken, Priority};
+use crate::style::{self, Palette};
+use crate::Message;
+
+/// A thumbnail waiting for its `LoadThumbnail` command.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThumbnailRequest {
+    pub media_id: String,
+    pub base_url: String,
+    pub video: bool,
+    pub priority: Priority,
+}
+
+#[derive(Debug, Default)]
+pub struct ThumbnailQueue {
+    queued: VecDeque<ThumbnailRequest>,
+    /// Requests handed to the loader, with the token that cancels them
+    in_flight: HashMap<String, CancelToken>,
+    /// Concurrent `LoadThumbnail` commands allowed
+    limit: usize,
+    /// Progress of the current batch, reset once the queue drains
+    done: usize,
+    total: usize,
+}
+
+impl ThumbnailQueue {
+    /// Allow twice as many commands in flight as the loader has threads.
+    pub fn new(preload_threads: usize) -> Self {
+        Self { limit: (preload_threads * 2).max(1), ..Self::default() }
+    }
+
+    /// Follow a new thread count of the loader; requests already in flight
+    /// are left alone.
+    pub fn set_threads(&mut self, preload_threads: usize) {
+        self.limit = (preload_threads * 2).max(1);
+    }
+
+    /// Queue `request` behind those of equal or higher priority.
+    pub fn push(&mut self, request: ThumbnailRequest) {
+        self.total += 1;
+        let index = self
+            .queued
+            .iter()
+            .position(|r| r.priority < request.priority)
+            .unwrap_or(self.queued.len());
+        self.queued.insert(index, request);
+    }
+
+    /// Move queued requests in flight up to the concurrency limit.
+    pub fn next_batch(&mut self) -> Vec<ThumbnailRequest> {
+        let mut batch = Vec::new();
+        while self.in_flight.len() < self.limit {
+            let Some(request) = self.queued.pop_front() else { break };
+            self.in_flight.insert(request.media_id.clone(), CancelToken::default());
+            batch.push(request);
+        }
+        batch
+    }
+
+    /// Token the loader request for `media_id` was issued with.
+    pub fn token(&self, media_id: &str) -> Option<CancelToken> {
+        self.in_flight.get(media_id).cloned()
+    }
+
+    /// Cancel in-flight requests not accepted by `keep`, e.g. after scrolling
+    /// away. Their commands still complete, with `ImageLoaderError::Cancelled`.
+    pub fn cancel_in_flight(&self, keep: impl Fn(&str) -> bool) {
+        for (media_id, token) in &self.in_flight {
+            if !keep(media_id) {
+                token.cancel();
+            }
+        }
+    }
+
+    /// Record that the command for `media_id` completed, successfully or not.
+    pub fn finish(&mut self, media_id: &str) {
+        if self.in_flight.remove(media_id).is_some() {
+            self.done += 1;
+        }
+        if self.is_idle() {
+            self.done = 0;
+            self.total = 0;
+        }
+    }
+
+    /// Drop everything not yet in flight and return the ids dropped.
+    pub fn cancel(&mut self) -> Vec<String> {
+        let dropped: Vec<String> = self.queued.drain(..).map(|r| r.media_id).collect();
+        self.total -= dropped.len();
+        if self.is_idle() {
+            self.done = 0;
+            self.total = 0;
+        }
+        dropped
+    }
+
+    /// Forget all requests, e.g. when the photo list is replaced.
+    pub fn clear(&mut self) {
+        *self = Self { limit: self.limit, ..Self::default() };
+    }
+
+    pub fn is_idle(&self) -> bool {
+        self.queued.is_empty() && self.in_flight.is_empty()
+    }
+
+    /// Completed and total requests, for batches too large to load in one go.
+    pub fn progress(&self) -> Option<(usize, usize)> {
+        (!self.is_idle() && self.total > self.limit).then_some((self.done, self.total))
+    }
+}
+
+/// Slim progress bar under the header while a large batch loads.
+pub fn view<'a>(ui: &crate::GooglePiczUI) -> Option<Element<'a, Message>> {
+    let (done, total) = ui.thumbnail_queue.progress()?;
+    Some(
+        container(
+            row![
+                text(trf("thumbnail.loading", &[&done, &total])).size(14),
+                progress_bar(0.0..=total as f32, done as f32)
+                    .height(Length::Fixed(6.0))
+                    .width(Length::Fill),
+                button(text(tr("thumbnail.cancel_preload")).size(14))
+                    .style(style::button_secondary())
+                    .on_press(Message::CancelThumbnailPreload),
+            ]
+            .spacing(Palette::SPACING / 2)
+            .align_items(iced::Alignment::Center),
+        )
+        .width(Length::Fill)
+        .into(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(id: usize) -> ThumbnailRequest {
+        ThumbnailRequest { media_id: id.to_string(), base_url: String::new(), video: false, priority: Priority::Prefetch }
+    }
+
+    #[test]
+    fn test_batches_respect_limit_and_track_progress() {
+        let mut queue = ThumbnailQueue::new(1);
+        for i in 0..5 {
+            queue.push(request(i));
+        }
+        assert_eq!(queue.next_batch().len(), 2);
+        assert!(queue.next_batch().is_empty());
+        assert_eq!(queue.progress(), Some((0, 5)));
+
+        queue.finish("0");
+        assert_eq!(queue.next_batch(), vec![request(2)]);
+        assert_eq!(queue.progress(), Some((1, 5)));
+
+        assert_eq!(queue.cancel(), vec!["3".to_string(), "4".to_string()]);
+        queue.cancel_in_flight(|id| id != "1");
+        assert!(queue.token("1").unwrap().is_cancelled());
+        assert!(!queue.token("2").unwrap().is_cancelled());
+        queue.finish("1");
+        queue.finish("2");
+        assert!(queue.is_idle());
+        assert_eq!(queue.progress(), None);
+    }
+
+    #[test]
+    fn test_visible_requests_jump_the_queue() {
+        let mut queue = ThumbnailQueue::new(1);
+        for i in 0..3 {
+            queue.push(request(i));
+        }
+        queue.push(ThumbnailRequest { priority: Priority::Visible, ..request(3) });
+        let order: Vec<String> = queue.next_batch().into_iter().map(|r| r.media_id).collect();
+        assert_eq!(order, vec!["3".to_string(), "0".to_string()]);
+    }
+}
@@ -1,11 +1,17 @@
 use chrono::{DateTime, Utc};
-use iced::widget::{button, checkbox, pick_list, row, text_input};
+use iced::widget::{button, checkbox, column, combo_box, container, pick_list, row, text, text_input};
+use serde::{Deserialize, Serialize};
 
+use crate::i18n::{tr, trf};
 use crate::{style, Icon, MaterialSymbol, Message};
 use crate::style::Palette;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Number of executed searches kept in the recent list.
+pub const RECENT_LIMIT: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
 pub enum SearchMode {
+    #[default]
     Filename,
     Description,
     Text,
@@ -32,15 +38,15 @@ impl SearchMode {
 
     pub fn placeholder(self) -> &'static str {
         match self {
-            SearchMode::Filename => "Filename",
-            SearchMode::Description => "Description",
-            SearchMode::Text => "Filename or description",
-            SearchMode::Favoriten => "Favorites",
-            SearchMode::MimeType => "Mime type",
-            SearchMode::CameraModel => "Camera model",
-            SearchMode::CameraMake => "Camera make",
-            SearchMode::Faces => "Has faces",
-            SearchMode::DateRange => "YYYY-MM-DD..YYYY-MM-DD",
+            SearchMode::Filename => tr("search.placeholder.filename"),
+            SearchMode::Description => tr("search.placeholder.description"),
+            SearchMode::Text => tr("search.placeholder.text"),
+            SearchMode::Favoriten => tr("search.placeholder.favorites"),
+            SearchMode::MimeType => tr("search.placeholder.mime_type"),
+            SearchMode::CameraModel => tr("search.placeholder.camera_model"),
+            SearchMode::CameraMake => tr("search.placeholder.camera_make"),
+            SearchMode::Faces => tr("search.placeholder.faces"),
+            SearchMode::DateRange => tr("search.placeholder.date_range"),
         }
     }
 }
@@ -48,15 +54,15 @@ impl SearchMode {
 impl std::fmt::Display for SearchMode {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let s = match self {
-            SearchMode::Filename => "Filename",
-            SearchMode::Description => "Beschreibung",
-            SearchMode::Text => "Dateiname/Beschr.",
-            SearchMode::Favoriten => "Favoriten",
-            SearchMode::DateRange => "Datum von/bis",
-            SearchMode::MimeType => "Dateityp",
-            SearchMode::CameraModel => "Kamera-Modell",
-            SearchMode::CameraMake => "Kamera-Hersteller",
-            SearchMode::Faces => "Gesichter",
+            SearchMode::Filename => tr("search.mode.filename"),
+            SearchMode::Description => tr("search.mode.description"),
+            SearchMode::Text => tr("search.mode.text"),
+            SearchMode::Favoriten => tr("search.mode.favorites"),
+            SearchMode::DateRange => tr("search.mode.date_range"),
+            SearchMode::MimeType => tr("search.mode.mime_type"),
+            SearchMode::CameraModel => tr("search.mode.camera_model"),
+            SearchMode::CameraMake => tr("search.mode.camera_make"),
+            SearchMode::Faces => tr("search.mode.faces"),
         };
         write!(f, "{}", s)
     }
@@ -92,28 +98,353 @@ pub(crate) fn parse_single_date(query: &str, end: bool) -> Option<DateTime<Utc>>
     None
 }
 
-pub fn view<'a>(ui: &crate::GooglePiczUI) -> iced::Element<'a, Message> {
+/// A single search constraint that can be removed on its own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterKind {
+    Query,
+    CameraModel,
+    CameraMake,
+    MimeType,
+    DateRange,
+    Favorites,
+    Faces,
+    Person,
+    Album,
+}
+
+/// Album a search is limited to, kept with its title for the chip.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AlbumScope {
+    pub id: String,
+    pub title: String,
+}
+
+/// Search constraints normalized from the individual search inputs.
+///
+/// Empty text fields and unparsable dates are left out, since the search
+/// ignores them as well.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ActiveFilters {
+    pub query: Option<(SearchMode, String)>,
+    pub camera_model: Option<String>,
+    pub camera_make: Option<String>,
+    pub mime_type: Option<String>,
+    pub start: Option<String>,
+    pub end: Option<String>,
+    pub favorites: bool,
+    pub faces: bool,
+    pub person: Option<String>,
+    /// Album the search stays inside
+    pub album: Option<AlbumScope>,
+}
+
+impl ActiveFilters {
+    pub fn from_ui(ui: &crate::GooglePiczUI) -> Self {
+        Self {
+            album: ui.search_album.clone(),
+            ..Self::from_search(&SavedSearch::from_ui(ui))
+        }
+    }
+
+    pub fn from_search(search: &SavedSearch) -> Self {
+        let non_empty = |s: &str| {
+            let s = s.trim();
+            (!s.is_empty()).then(|| s.to_string())
+        };
+        let valid_date = |s: &str, end| {
+            parse_single_date(s.trim(), end).map(|_| s.trim().to_string())
+        };
+        Self {
+            // Favorites and faces modes have no query text of their own
+            query: non_empty(&search.query)
+                .filter(|_| !matches!(search.mode, SearchMode::Favoriten | SearchMode::Faces))
+                .filter(|q| search.mode != SearchMode::DateRange || parse_date_query(q).is_some())
+                .map(|q| (search.mode, q)),
+            camera_model: non_empty(&search.camera_model),
+            camera_make: search.camera_make.clone(),
+            mime_type: search.mime_type.clone(),
+            start: valid_date(&search.start, false),
+            end: valid_date(&search.end, true),
+            favorites: search.favorites || search.mode == SearchMode::Favoriten,
+            faces: search.faces || search.mode == SearchMode::Faces,
+            person: search.person.clone(),
+            album: None,
+        }
+    }
+
+    /// Whether nothing is searched for; the album scope alone is just the album view.
+    pub fn is_empty(&self) -> bool {
+        Self { album: None, ..self.clone() } == Self::default()
+    }
+
+    /// The cache query finding what the filters describe. The query text
+    /// goes to the field its mode searches, taking precedence over the input
+    /// for that field.
+    pub fn to_query(&self) -> cache::MediaQuery {
+        let mut query = cache::MediaQuery {
+            camera_model: self.camera_model.clone(),
+            camera_make: self.camera_make.clone(),
+            mime_type: self.mime_type.clone(),
+            start: self.start.as_deref().and_then(|s| parse_single_date(s, false)),
+            end: self.end.as_deref().and_then(|s| parse_single_date(s, true)),
+            favorite: self.favorites.then_some(true),
+            faces: self.faces.then_some(true),
+            person: self.person.clone(),
+            album_id: self.album.as_ref().map(|a| a.id.clone()),
+            ..Default::default()
+        };
+        if let Some((mode, text)) = self.query.clone() {
+            match mode {
+                SearchMode::Filename => query.filename = Some(text),
+                SearchMode::Description => query.description = Some(text),
+                SearchMode::Text => query.text = Some(text),
+                SearchMode::MimeType => query.mime_type = Some(text),
+                SearchMode::CameraModel => query.camera_model = Some(text),
+                SearchMode::CameraMake => query.camera_make = Some(text),
+                SearchMode::DateRange => {
+                    if let Some((start, end)) = parse_date_query(&text) {
+                        query.start = Some(start);
+                        query.end = Some(end);
+                    }
+                }
+                SearchMode::Favoriten | SearchMode::Faces => {}
+            }
+        }
+        query
+    }
+
+    /// Chip labels in display order.
+    pub fn chips(&self) -> Vec<(FilterKind, String)> {
+        let mut chips = Vec::new();
+        if let Some((mode, q)) = &self.query {
+            chips.push((FilterKind::Query, format!("{}: {}", mode, q)));
+        }
+        if let Some(person) = &self.person {
+            chips.push((FilterKind::Person, trf("filters.person", &[person])));
+        }
+        if self.favorites {
+            chips.push((FilterKind::Favorites, tr("filters.favorites").to_string()));
+        }
+        if self.start.is_some() || self.end.is_some() {
+            let start = self.start.as_deref().unwrap_or("…");
+            let end = self.end.as_deref().unwrap_or("…");
+            chips.push((FilterKind::DateRange, format!("{} → {}", start, end)));
+        }
+        if let Some(make) = &self.camera_make {
+            chips.push((FilterKind::CameraMake, make.clone()));
+        }
+        if let Some(model) = &self.camera_model {
+            chips.push((FilterKind::CameraModel, model.clone()));
+        }
+        if let Some(mime) = &self.mime_type {
+            chips.push((FilterKind::MimeType, mime.clone()));
+        }
+        if self.faces {
+            chips.push((FilterKind::Faces, tr("filters.faces").to_string()));
+        }
+        if let Some(album) = self.album.as_ref().filter(|_| !chips.is_empty()) {
+            chips.insert(0, (FilterKind::Album, trf("filters.album", &[&album.title])));
+        }
+        chips
+    }
+}
+
+/// The search inputs as they were when a search ran, so it can be run again.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SavedSearch {
+    /// Name given when pinned; recent searches have none
+    pub name: Option<String>,
+    pub mode: SearchMode,
+    pub query: String,
+    pub camera_model: String,
+    pub camera_make: Option<String>,
+    pub mime_type: Option<String>,
+    pub start: String,
+    pub end: String,
+    pub favorites: bool,
+    pub faces: bool,
+    pub person: Option<String>,
+}
+
+impl SavedSearch {
+    pub fn from_ui(ui: &crate::GooglePiczUI) -> Self {
+        Self {
+            name: None,
+            mode: ui.search_mode,
+            query: ui.search_query.clone(),
+            camera_model: ui.search_camera.clone(),
+            camera_make: ui.search_camera_make.clone(),
+            mime_type: ui.search_mime.clone(),
+            start: ui.search_start.clone(),
+            end: ui.search_end.clone(),
+            favorites: ui.search_favorite,
+            faces: ui.search_faces,
+            person: ui.search_person.clone(),
+        }
+    }
+
+    /// Put the search back into the search inputs.
+    pub fn apply(&self, ui: &mut crate::GooglePiczUI) {
+        ui.search_mode = self.mode;
+        ui.ui_state.search_mode = self.mode;
+        ui.search_query = self.query.clone();
+        ui.search_camera = self.camera_model.clone();
+        ui.search_camera_make = self.camera_make.clone();
+        ui.search_mime = self.mime_type.clone();
+        ui.search_start = self.start.clone();
+        ui.search_end = self.end.clone();
+        ui.search_favorite = self.favorites;
+        ui.search_faces = self.faces;
+        ui.search_person = self.person.clone();
+    }
+
+    /// The name if pinned, otherwise the constraints it applies.
+    pub fn label(&self) -> String {
+        if let Some(name) = &self.name {
+            return name.clone();
+        }
+        let chips = ActiveFilters::from_search(self).chips();
+        chips.into_iter().map(|(_, label)| label).collect::<Vec<_>>().join(", ")
+    }
+}
+
+/// Put `search` at the top of the recent list, dropping an identical older
+/// entry and anything past [`RECENT_LIMIT`].
+pub fn remember(recent: &mut Vec<SavedSearch>, search: SavedSearch) {
+    recent.retain(|s| *s != search);
+    recent.insert(0, search);
+    recent.truncate(RECENT_LIMIT);
+}
+
+#[derive(Debug, Default)]
+pub struct SearchHistory {
+    /// Whether the recent searches are listed below the search input
+    pub open: bool,
+    /// Recent search being pinned and the name typed for it so far
+    pub pinning: Option<(usize, String)>,
+}
+
+/// Recent searches with pin and delete buttons, while the list is open.
+pub fn recent<'a>(ui: &crate::GooglePiczUI) -> Option<iced::Element<'a, Message>> {
+    if !ui.search_history.open {
+        return None;
+    }
+    let searches = &ui.ui_state.recent_searches;
+    if searches.is_empty() {
+        return Some(
+            container(text(tr("search.recent.empty")).size(14))
+                .style(style::card())
+                .padding(Palette::SPACING / 2)
+                .into(),
+        );
+    }
+    let mut list = column![text(tr("search.recent.title")).size(16)].spacing(4);
+    for (i, search) in searches.iter().enumerate() {
+        let line: iced::Element<'a, Message> = match &ui.search_history.pinning {
+            Some((index, name)) if *index == i => row![
+                text_input(tr("search.saved.name"), name)
+                    .style(style::text_input())
+                    .on_input(Message::PinNameChanged)
+                    .on_submit(Message::ConfirmPinSearch),
+                button(Icon::new(MaterialSymbol::Save).color(Palette::ON_PRIMARY))
+                    .style(style::button_primary())
+                    .on_press_maybe((!name.trim().is_empty()).then_some(Message::ConfirmPinSearch)),
+            ]
+            .spacing(4)
+            .align_items(iced::Alignment::Center)
+            .into(),
+            _ => row![
+                button(text(search.label()).size(14))
+                    .style(style::button_secondary())
+                    .width(iced::Length::Fill)
+                    .on_press(Message::SelectSavedSearch(search.clone())),
+                button(Icon::new(MaterialSymbol::PushPin).size(16))
+                    .style(style::button_secondary())
+                    .on_press(Message::PinSearch(i)),
+                button(Icon::new(MaterialSymbol::Close).size(16))
+                    .style(style::button_secondary())
+                    .on_press(Message::DeleteRecentSearch(i)),
+            ]
+            .spacing(4)
+            .align_items(iced::Alignment::Center)
+            .into(),
+        };
+        list = list.push(line);
+    }
+    Some(
+        container(list)
+            .style(style::card())
+            .padding(Palette::SPACING / 2)
+            .max_width(480)
+            .into(),
+    )
+}
+
+/// Removable chips for the active search constraints, if there are any.
+pub fn chips<'a>(ui: &crate::GooglePiczUI) -> Option<iced::Element<'a, Message>> {
+    let chips = ui.active_filters().chips();
+    if chips.is_empty() {
+        return None;
+    }
+    let mut list = row![].spacing(Palette::SPACING / 2).align_items(iced::Alignment::Center);
+    for (kind, label) in chips {
+        list = list.push(
+            container(
+                row![
+                    text(label).size(14),
+                    button(Icon::new(MaterialSymbol::Close).size(14))
+                        .style(style::button_secondary())
+                        .on_press(Message::ClearFilter(kind)),
+                ]
+                .spacing(4)
+                .align_items(iced::Alignment::Center),
+            )
+            .style(style::chip())
+            .padding(4),
+        );
+    }
+    list = list.push(
+        button(text(tr("filters.clear_all")).size(14))
+            .style(style::button_secondary())
+            .on_press(Message::ClearAllFilters),
+    );
+    Some(list.into())
+}
+
+pub fn view<'a>(ui: &'a crate::GooglePiczUI) -> iced::Element<'a, Message> {
     row![
         text_input(ui.search_mode.placeholder(), &ui.search_query)
             .style(style::text_input())
-            .on_input(Message::SearchInputChanged),
-        text_input("Camera", &ui.search_camera)
+            .on_input(Message::SearchInputChanged)
+            .on_submit(Message::PerformSearch),
+        text_input(tr("search.camera"), &ui.search_camera)
             .style(style::text_input())
             .on_input(Message::SearchCameraChanged),
-        text_input("From", &ui.search_start)
+        text_input(tr("search.from"), &ui.search_start)
             .style(style::text_input())
             .on_input(Message::SearchStartChanged),
-        text_input("To", &ui.search_end)
+        text_input(tr("search.to"), &ui.search_end)
             .style(style::text_input())
             .on_input(Message::SearchEndChanged),
-        checkbox("Fav", ui.search_favorite, Message::SearchFavoriteToggled)
+        checkbox(tr("search.favorite"), ui.search_favorite, Message::SearchFavoriteToggled)
             .style(style::checkbox_primary()),
-        checkbox("Faces", ui.search_faces, Message::SearchFacesToggled)
+        checkbox(tr("search.faces"), ui.search_faces, Message::SearchFacesToggled)
             .style(style::checkbox_primary()),
+        combo_box(
+            &ui.person_options,
+            tr("search.person"),
+            ui.search_person.as_ref(),
+            Message::SearchPersonSelected,
+        )
+        .width(iced::Length::Fixed(160.0)),
         pick_list(&SearchMode::ALL[..], Some(ui.search_mode), Message::SearchModeChanged),
         button(Icon::new(MaterialSymbol::Search).color(Palette::ON_PRIMARY))
             .style(style::button_primary())
-            .on_press(Message::PerformSearch)
+            .on_press(Message::PerformSearch),
+        button(Icon::new(MaterialSymbol::History).color(Palette::ON_PRIMARY))
+            .style(style::button_primary())
+            .on_press(Message::ToggleRecentSearches),
     ]
     .spacing(Palette::SPACING)
     .align_items(iced::Alignment::Center)
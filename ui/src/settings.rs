@@ -1,58 +1,390 @@
-use iced::widget::{button, checkbox, column, container, pick_list, row, text, text_input};
+use std::path::Path;
 
+use iced::widget::{button, checkbox, column, container, pick_list, row, scrollable, text, text_input, Column};
+use iced::{Font, Length};
+
+use crate::app_config::{usable_dir, MAX_PRELOAD_THREADS, MAX_THUMBS_PRELOAD};
+use crate::i18n::{tr, trf, Language};
 use crate::{style, Icon, MaterialSymbol, Message};
+use crate::status_bar::format_size;
 use crate::style::Palette;
 
 pub const LOG_LEVELS: [&str; 5] = ["trace", "debug", "info", "warn", "error"];
 
+/// Settings entered as free text and checked before they can be saved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SettingsField {
+    OauthPort,
+    ThumbsPreload,
+    PreloadThreads,
+    SyncInterval,
+    CachePath,
+    SlideshowInterval,
+    ImageCacheMaxMb,
+    VideoCacheMaxMb,
+}
+
+impl SettingsField {
+    /// In dialog order, so the first failure reported is the topmost one.
+    pub const ALL: [SettingsField; 8] = [
+        SettingsField::OauthPort,
+        SettingsField::ThumbsPreload,
+        SettingsField::PreloadThreads,
+        SettingsField::SyncInterval,
+        SettingsField::CachePath,
+        SettingsField::ImageCacheMaxMb,
+        SettingsField::VideoCacheMaxMb,
+        SettingsField::SlideshowInterval,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SettingsField::OauthPort => tr("settings.oauth_port"),
+            SettingsField::ThumbsPreload => tr("settings.thumbs_preload"),
+            SettingsField::PreloadThreads => tr("settings.preload_threads"),
+            SettingsField::SyncInterval => tr("settings.sync_interval"),
+            SettingsField::CachePath => tr("settings.cache_path"),
+            SettingsField::SlideshowInterval => tr("settings.slideshow_interval"),
+            SettingsField::ImageCacheMaxMb => tr("settings.image_cache_max"),
+            SettingsField::VideoCacheMaxMb => tr("settings.video_cache_max"),
+        }
+    }
+
+    /// Read only at startup, see `app_config::RESTART_KEYS`.
+    pub fn needs_restart(self) -> bool {
+        matches!(self, SettingsField::OauthPort | SettingsField::CachePath)
+    }
+
+    /// Field showing the config file key `key`, see `ConfigIssue::field`.
+    pub fn from_config_key(key: &str) -> Option<SettingsField> {
+        match key {
+            "oauth_redirect_port" => Some(SettingsField::OauthPort),
+            "thumbnails_preload" => Some(SettingsField::ThumbsPreload),
+            "preload_threads" => Some(SettingsField::PreloadThreads),
+            "sync_interval_minutes" => Some(SettingsField::SyncInterval),
+            "cache_path" => Some(SettingsField::CachePath),
+            "slideshow_interval_secs" => Some(SettingsField::SlideshowInterval),
+            "image_cache_max_mb" => Some(SettingsField::ImageCacheMaxMb),
+            "video_cache_max_mb" => Some(SettingsField::VideoCacheMaxMb),
+            _ => None,
+        }
+    }
+}
+
+/// Current text of `field` in the dialog.
+pub fn input(ui: &crate::GooglePiczUI, field: SettingsField) -> &str {
+    match field {
+        SettingsField::OauthPort => &ui.settings_oauth_port,
+        SettingsField::ThumbsPreload => &ui.settings_thumbnails_preload,
+        SettingsField::PreloadThreads => &ui.settings_preload_threads,
+        SettingsField::SyncInterval => &ui.settings_sync_interval,
+        SettingsField::CachePath => &ui.settings_cache_path,
+        SettingsField::SlideshowInterval => &ui.settings_slideshow_interval,
+        SettingsField::ImageCacheMaxMb => &ui.settings_image_cache_max,
+        SettingsField::VideoCacheMaxMb => &ui.settings_video_cache_max,
+    }
+}
+
+fn whole_number(value: &str, min: u64, max: u64) -> Result<(), String> {
+    match value.trim().parse::<u64>() {
+        Ok(n) if (min..=max).contains(&n) => Ok(()),
+        _ if max == u64::MAX => Err(trf("settings.invalid.min", &[&min])),
+        _ => Err(trf("settings.invalid.range", &[&min, &max])),
+    }
+}
+
+/// Check `value` for `field`; the error is shown under the input.
+pub fn validate(field: SettingsField, value: &str) -> Result<(), String> {
+    match field {
+        SettingsField::OauthPort => whole_number(value, 1, u16::MAX as u64),
+        SettingsField::ThumbsPreload => whole_number(value, 0, MAX_THUMBS_PRELOAD as u64),
+        SettingsField::PreloadThreads => whole_number(value, 1, MAX_PRELOAD_THREADS as u64),
+        SettingsField::SyncInterval | SettingsField::SlideshowInterval => {
+            whole_number(value, 1, u64::MAX)
+        }
+        SettingsField::ImageCacheMaxMb | SettingsField::VideoCacheMaxMb => whole_number(value, 0, u64::MAX),
+        SettingsField::CachePath => {
+            if usable_dir(Path::new(value.trim())) {
+                Ok(())
+            } else {
+                Err(tr("settings.invalid.cache_path").to_string())
+            }
+        }
+    }
+}
+
+/// `input` with the validation error of `field` underneath.
+fn validated<'a>(
+    ui: &crate::GooglePiczUI,
+    field: SettingsField,
+    input: impl Into<iced::Element<'a, Message>>,
+) -> iced::Element<'a, Message> {
+    column![input.into()]
+        .push_maybe(
+            ui.settings_errors
+                .get(&field)
+                .map(|e| text(e.clone()).size(12).style(Palette::ERROR)),
+        )
+        .push_maybe(field.needs_restart().then(|| text(tr("settings.restart_note")).size(12)))
+        .spacing(4)
+        .into()
+}
+
+/// A checkbox for a setting only read at startup, with a note saying so.
+fn restart_checkbox<'a>(label: &'static str, checked: bool, on_toggle: fn(bool) -> Message) -> iced::Element<'a, Message> {
+    column![
+        checkbox(label, checked, on_toggle).style(style::checkbox_primary()),
+        text(tr("settings.restart_note")).size(12),
+    ]
+    .spacing(4)
+    .into()
+}
+
+/// Saved settings that are waiting for a restart.
+fn restart_pending<'a>(ui: &crate::GooglePiczUI) -> Option<iced::Element<'a, Message>> {
+    if ui.restart_required().is_empty() {
+        return None;
+    }
+    let keys = ui.restart_required().join(", ");
+    Some(text(trf("settings.restart_pending", &[&keys])).size(12).style(Palette::WARNING).into())
+}
+
+/// Toggle for the account section, with the stored account and token
+/// details below it while open.
+fn account<'a>(ui: &crate::GooglePiczUI) -> iced::Element<'a, Message> {
+    let toggle = button(text(tr("settings.account")))
+        .style(style::button_secondary())
+        .on_press(Message::ToggleAccountSettings);
+    if !ui.settings_account_open {
+        return toggle.into();
+    }
+    let details = match &ui.status_bar.snapshot.token {
+        Some(token) if token.has_access_token || token.has_refresh_token => column![
+            text(match &token.account {
+                Some(account) => trf("settings.account_signed_in", &[account]),
+                None => tr("settings.account_unknown").to_string(),
+            }),
+            text(trf("settings.account_backend", &[&token.backend.as_str()])).size(12),
+        ]
+        .push_maybe(
+            (token.has_refresh_token && token.access_token_expired())
+                .then(|| text(tr("settings.account_token_expired")).size(12)),
+        ),
+        _ => column![text(tr("settings.account_none"))],
+    };
+    column![toggle, details.spacing(4)].spacing(4).into()
+}
+
+/// Disk usage of cached images with a button to delete them.
+fn image_cache<'a>(ui: &crate::GooglePiczUI) -> iced::Element<'a, Message> {
+    let usage = match ui.image_cache_usage {
+        Some((bytes, files)) => trf("settings.image_cache_usage", &[&format_size(bytes), &files]),
+        None => tr("statusbar.unknown").to_string(),
+    };
+    row![
+        text(usage).size(12).width(Length::Fill),
+        button(text(tr("settings.image_cache_clear")))
+            .style(style::button_secondary())
+            .on_press(Message::ClearImageCache),
+    ]
+    .spacing(Palette::SPACING)
+    .align_items(iced::Alignment::Center)
+    .into()
+}
+
+/// Disk usage of cached videos with a button to delete them.
+fn video_cache<'a>(ui: &crate::GooglePiczUI) -> iced::Element<'a, Message> {
+    let usage = match ui.video_cache_usage {
+        Some((bytes, files)) => trf("settings.video_cache_usage", &[&format_size(bytes), &files]),
+        None => tr("statusbar.unknown").to_string(),
+    };
+    row![
+        text(usage).size(12).width(Length::Fill),
+        button(text(tr("settings.video_cache_clear")))
+            .style(style::button_secondary())
+            .on_press(Message::ClearVideoCache),
+    ]
+    .spacing(Palette::SPACING)
+    .align_items(iced::Alignment::Center)
+    .into()
+}
+
+/// Confirmation before "Reset local cache" removes everything synced.
+pub fn reset_cache_dialog<'a>(ui: &crate::GooglePiczUI) -> Option<iced::Element<'a, Message>> {
+    if !ui.confirm_reset_cache {
+        return None;
+    }
+    Some(
+        container(
+            column![
+                text(tr("settings.reset_cache_confirm")).size(16),
+                text(tr("settings.reset_cache_kept")).size(14),
+                row![
+                    button(text(tr("settings.reset_cache")))
+                        .style(style::button_primary())
+                        .on_press(Message::ConfirmResetCache),
+                    button(Icon::new(MaterialSymbol::Cancel).color(Palette::ON_SECONDARY))
+                        .style(style::button_secondary())
+                        .on_press(Message::CancelResetCache),
+                ]
+                .spacing(Palette::SPACING),
+            ]
+            .spacing(Palette::SPACING),
+        )
+        .style(style::dialog())
+        .padding(Palette::SPACING)
+        .into(),
+    )
+}
+
+/// Path, tail and actions of the error log, once the section is open.
+fn error_log<'a>(ui: &crate::GooglePiczUI) -> Option<iced::Element<'a, Message>> {
+    let lines = ui.error_log.as_ref()?;
+    let body: iced::Element<'a, Message> = if lines.is_empty() {
+        text(tr("settings.error_log_empty")).size(12).into()
+    } else {
+        Column::with_children(lines.iter().map(|line| {
+            text(line.clone()).font(Font::MONOSPACE).size(12).into()
+        }))
+        .into()
+    };
+    let path = std::path::absolute(&ui.error_log_path).unwrap_or_else(|_| ui.error_log_path.clone());
+    Some(
+        column![
+            text(path.display().to_string()).size(12),
+            scrollable(body).height(Length::Fixed(200.0)),
+            row![
+                button(text(tr("settings.error_log_copy")))
+                    .style(style::button_primary())
+                    .on_press(Message::CopyErrorLog),
+                button(text(tr("settings.error_log_clear")))
+                    .style(style::button_secondary())
+                    .on_press(Message::ClearErrorLog),
+            ]
+            .spacing(Palette::SPACING),
+        ]
+        .spacing(4)
+        .into(),
+    )
+}
+
 pub fn dialog<'a>(ui: &crate::GooglePiczUI) -> Option<iced::Element<'a, Message>> {
     if ui.settings_open {
         Some(
             container(
                 column![
-                text("Settings").size(16),
+                text(tr("settings.title")).size(16),
+                text(trf("settings.profile", &[&ui.profile().unwrap_or(tr("settings.profile_default"))])).size(12),
+                account(ui),
                 pick_list(
                     &LOG_LEVELS[..],
                     Some(ui.settings_log_level.as_str()),
                     |v| Message::SettingsLogLevelChanged(v.to_string()),
                 ),
-                text_input("OAuth port", &ui.settings_oauth_port)
-                    .style(style::text_input())
-                    .on_input(Message::SettingsOauthPortChanged),
-                text_input("Thumbs preload", &ui.settings_thumbnails_preload)
-                    .style(style::text_input())
-                    .on_input(Message::SettingsThumbsPreloadChanged),
-                text_input("Preload threads", &ui.settings_preload_threads)
-                    .style(style::text_input())
-                    .on_input(Message::SettingsPreloadThreadsChanged),
-                text_input("Sync interval", &ui.settings_sync_interval)
-                    .style(style::text_input())
-                    .on_input(Message::SettingsSyncIntervalChanged),
-                checkbox(
-                    "Debug console",
+                pick_list(
+                    &style::THEMES[..],
+                    Some(ui.settings_theme.as_str()),
+                    |v| Message::SettingsThemeChanged(v.to_string()),
+                ),
+                pick_list(
+                    &Language::ALL[..],
+                    Some(ui.settings_language),
+                    Message::SettingsLanguageChanged,
+                ),
+                validated(
+                    ui,
+                    SettingsField::OauthPort,
+                    text_input(tr("settings.oauth_port"), &ui.settings_oauth_port)
+                        .style(style::text_input())
+                        .on_input(Message::SettingsOauthPortChanged),
+                ),
+                validated(
+                    ui,
+                    SettingsField::ThumbsPreload,
+                    text_input(tr("settings.thumbs_preload"), &ui.settings_thumbnails_preload)
+                        .style(style::text_input())
+                        .on_input(Message::SettingsThumbsPreloadChanged),
+                ),
+                validated(
+                    ui,
+                    SettingsField::PreloadThreads,
+                    text_input(tr("settings.preload_threads"), &ui.settings_preload_threads)
+                        .style(style::text_input())
+                        .on_input(Message::SettingsPreloadThreadsChanged),
+                ),
+                validated(
+                    ui,
+                    SettingsField::SyncInterval,
+                    text_input(tr("settings.sync_interval"), &ui.settings_sync_interval)
+                        .style(style::text_input())
+                        .on_input(Message::SettingsSyncIntervalChanged),
+                ),
+                restart_checkbox(
+                    tr("settings.debug_console"),
                     ui.settings_debug_console,
                     Message::SettingsDebugConsoleToggled,
-                )
-                .style(style::checkbox_primary()),
-                checkbox(
-                    "Trace spans",
+                ),
+                restart_checkbox(
+                    tr("settings.trace_spans"),
                     ui.settings_trace_spans,
                     Message::SettingsTraceSpansToggled,
+                ),
+                validated(
+                    ui,
+                    SettingsField::CachePath,
+                    text_input(tr("settings.cache_path"), &ui.settings_cache_path)
+                        .style(style::text_input())
+                        .on_input(Message::SettingsCachePathChanged),
+                ),
+                validated(
+                    ui,
+                    SettingsField::ImageCacheMaxMb,
+                    text_input(tr("settings.image_cache_max"), &ui.settings_image_cache_max)
+                        .style(style::text_input())
+                        .on_input(Message::SettingsImageCacheMaxChanged),
+                ),
+                image_cache(ui),
+                validated(
+                    ui,
+                    SettingsField::VideoCacheMaxMb,
+                    text_input(tr("settings.video_cache_max"), &ui.settings_video_cache_max)
+                        .style(style::text_input())
+                        .on_input(Message::SettingsVideoCacheMaxChanged),
+                ),
+                video_cache(ui),
+                button(text(tr("settings.reset_cache")))
+                    .style(style::button_secondary())
+                    .on_press(Message::ShowResetCacheDialog),
+                validated(
+                    ui,
+                    SettingsField::SlideshowInterval,
+                    text_input(tr("settings.slideshow_interval"), &ui.settings_slideshow_interval)
+                        .style(style::text_input())
+                        .on_input(Message::SettingsSlideshowIntervalChanged),
+                ),
+                checkbox(
+                    tr("settings.slideshow_videos"),
+                    ui.settings_slideshow_videos,
+                    Message::SettingsSlideshowVideosToggled,
                 )
                 .style(style::checkbox_primary()),
-                text_input("Cache path", &ui.settings_cache_path)
-                    .style(style::text_input())
-                    .on_input(Message::SettingsCachePathChanged),
+                button(text(tr("settings.error_log")))
+                    .style(style::button_secondary())
+                    .on_press(Message::ToggleErrorLog),
+            ]
+            .push_maybe(error_log(ui))
+            .push_maybe(restart_pending(ui))
+            .push(
                 row![
                     button(Icon::new(MaterialSymbol::Save).color(Palette::ON_PRIMARY))
                         .style(style::button_primary())
-                        .on_press(Message::SaveSettings),
+                        .on_press_maybe(ui.settings_errors.is_empty().then_some(Message::SaveSettings)),
                     button(Icon::new(MaterialSymbol::Cancel).color(Palette::ON_SECONDARY))
                         .style(style::button_secondary())
                         .on_press(Message::CloseSettings),
                 ]
                 .spacing(Palette::SPACING),
-            ]
+            )
             .spacing(Palette::SPACING))
                 .style(style::dialog())
                 .padding(Palette::SPACING)
@@ -0,0 +1,94 @@
+//! Opening the cache without holding up the window.
+//!
+//! The UI is built straight away in an initializing state. Opening the
+//! database, applying pending schema migrations and reading the last sync
+//! time run on a blocking task; migrations report their progress as they go
+//! and the result arrives as `Message::CacheReady`.
+
+use std::path::PathBuf;
+
+use cache::CacheManager;
+use chrono::{DateTime, Utc};
+use iced::widget::{container, progress_bar, row, text};
+use iced::{Command, Element, Length};
+
+use crate::i18n::{tr, trf};
+use crate::style::Palette;
+use crate::Message;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CacheStatus {
+    /// The database is being opened
+    #[default]
+    Opening,
+    /// Applying schema migration `step` of `steps`
+    Migrating { step: usize, steps: usize },
+    /// Open, or failed to open; either way nothing is left to wait for
+    Ready,
+}
+
+/// A cache opened in the background, with what it knows about the last sync.
+#[derive(Clone)]
+pub struct OpenedCache {
+    pub cache: CacheManager,
+    pub last_synced: Result<DateTime<Utc>, String>,
+}
+
+impl std::fmt::Debug for OpenedCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OpenedCache").field("last_synced", &self.last_synced).finish_non_exhaustive()
+    }
+}
+
+enum Event {
+    Migrating(usize, usize),
+    Ready(Result<OpenedCache, String>),
+}
+
+/// Open the cache at `db_path` off the UI thread. Migration steps arrive as
+/// `CacheMigrating`, followed by one `CacheReady` for `db_path`.
+pub fn open(db_path: PathBuf) -> Command<Message> {
+    let (tx, rx) = futures::channel::mpsc::unbounded();
+    let path = db_path.clone();
+    let opened = async move {
+        #[cfg(feature = "trace-spans")]
+        let start = std::time::Instant::now();
+        let result = tokio::task::spawn_blocking(move || {
+            let cache = CacheManager::open_with_progress(&path, |step, steps| {
+                let _ = tx.unbounded_send(Event::Migrating(step, steps));
+            })
+            .map_err(|e| e.to_string())?;
+            let last_synced = cache.get_last_sync().map_err(|e| e.to_string());
+            Ok(OpenedCache { cache, last_synced })
+        })
+        .await
+        .unwrap_or_else(|e| Err(e.to_string()));
+        #[cfg(feature = "trace-spans")]
+        tracing::info!(target = "ui", "cache_open_ms" = start.elapsed().as_millis(), "ok" = result.is_ok());
+        Event::Ready(result)
+    };
+    let events = futures::stream::select(rx, futures::stream::once(opened));
+    Command::run(events, move |event| match event {
+        Event::Migrating(step, steps) => Message::CacheMigrating(step, steps),
+        Event::Ready(result) => Message::CacheReady(db_path.clone(), result),
+    })
+}
+
+/// "Initializing…" with the migration progress, until the cache is ready.
+pub fn view<'a>(ui: &crate::GooglePiczUI) -> Option<Element<'a, Message>> {
+    let content = match ui.cache_status {
+        CacheStatus::Ready => return None,
+        CacheStatus::Opening => row![text(tr("startup.initializing")).size(14)],
+        CacheStatus::Migrating { step, steps } => row![
+            text(trf("startup.migrating", &[&step, &steps])).size(14),
+            progress_bar(0.0..=steps as f32, step.saturating_sub(1) as f32)
+                .height(Length::Fixed(6.0))
+                .width(Length::Fill),
+        ],
+    };
+    Some(
+        container(content.spacing(Palette::SPACING / 2).align_items(iced::Alignment::Center))
+            .width(Length::Fill)
+            .into(),
+    )
+}
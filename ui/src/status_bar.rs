@@ -0,0 +1,205 @@
+//! Slim status bar along the bottom of the window.
+//!
+//! The cache count, database size and stored account come from cheap queries
+//! run on a timer rather than after every message. Whether the app is online
+//! follows the outcome of the most recent API call. Images held in memory are
+//! read from the loader's handle cache as the bar is drawn, and network usage
+//! covers every API request of the session, syncs included.
+
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use cache::CacheManager;
+use iced::widget::tooltip::Position;
+use iced::widget::{button, container, row, text, tooltip, Space};
+use iced::{Element, Length};
+use tokio::sync::Mutex;
+
+use crate::i18n::{tr, trf};
+use crate::style::{self, Palette};
+use crate::{Icon, MaterialSymbol, Message};
+
+/// How often the cache and account figures are refreshed.
+pub const REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StatusSnapshot {
+    /// Media items in the cache
+    pub cached: Option<u64>,
+    /// Size of the cache database on disk, in bytes
+    pub db_size: Option<u64>,
+    pub token: Option<auth::TokenStatus>,
+    /// API traffic since the app started
+    pub network: api_client::TransferStats,
+}
+
+#[derive(Debug, Default)]
+pub struct StatusBar {
+    pub snapshot: StatusSnapshot,
+    /// Outcome of the last API call; `None` until one completes
+    pub online: Option<bool>,
+    /// A refresh is running, so the next tick is skipped
+    pub refreshing: bool,
+    /// Back online since the last tick, which then reloads the albums
+    pub reconnected: bool,
+    /// Errors shown since going offline, not shown again until back online
+    pub shown_offline: HashSet<String>,
+}
+
+impl StatusBar {
+    /// Record the outcome of an API call. Only failures to reach the server
+    /// count as offline; an error response still proves connectivity.
+    pub fn record(&mut self, error: Option<&str>) {
+        self.set_online(error.map_or(true, |e| !unreachable(e)));
+    }
+
+    pub fn set_online(&mut self, online: bool) {
+        if online {
+            self.reconnected |= self.online == Some(false);
+            self.shown_offline.clear();
+        }
+        self.online = Some(online);
+    }
+}
+
+/// Whether `error` says the request never got an answer.
+fn unreachable(error: &str) -> bool {
+    error.starts_with("Request Error") || error.contains("error sending request")
+}
+
+/// Gather the figures shown in the bar.
+pub async fn query(
+    cache_manager: Option<Arc<Mutex<CacheManager>>>,
+    db_path: PathBuf,
+    profile: Option<String>,
+) -> StatusSnapshot {
+    let cached = match cache_manager {
+        Some(cm) => {
+            let cache = cm.lock().await.clone();
+            cache.count_media_items_async().await.ok()
+        }
+        None => None,
+    };
+    let db_size = tokio::fs::metadata(&db_path).await.ok().map(|m| m.len());
+    // The keyring may block, so keep it off the async workers
+    let token = tokio::task::spawn_blocking(move || auth::get_token_status_for(profile.as_deref()))
+        .await
+        .ok()
+        .and_then(Result::ok);
+    StatusSnapshot { cached, db_size, token, network: api_client::session_stats() }
+}
+
+/// Human readable size, e.g. `12.3 MB`.
+pub fn format_size(bytes: u64) -> String {
+    const KB: f64 = 1024.0;
+    let bytes = bytes as f64;
+    if bytes >= KB * KB * KB {
+        format!("{:.1} GB", bytes / (KB * KB * KB))
+    } else if bytes >= KB * KB {
+        format!("{:.1} MB", bytes / (KB * KB))
+    } else {
+        format!("{:.0} KB", bytes / KB)
+    }
+}
+
+fn segment<'a>(icon: MaterialSymbol, label: String) -> Element<'a, Message> {
+    row![Icon::new(icon).size(14), text(label).size(12)]
+        .spacing(4)
+        .align_items(iced::Alignment::Center)
+        .into()
+}
+
+pub fn view<'a>(ui: &crate::GooglePiczUI) -> Element<'a, Message> {
+    let bar = &ui.status_bar;
+    let snapshot = &bar.snapshot;
+    let shown = match snapshot.cached {
+        Some(cached) => trf("statusbar.shown_of", &[&ui.photos.len(), &cached]),
+        None => trf("statusbar.shown", &[&ui.photos.len()]),
+    };
+    let size = snapshot
+        .db_size
+        .map(format_size)
+        .unwrap_or_else(|| tr("statusbar.unknown").to_string());
+    let (online_icon, online) = match bar.online {
+        Some(true) => (MaterialSymbol::CloudDone, tr("statusbar.online")),
+        Some(false) => (MaterialSymbol::CloudOff, tr("statusbar.offline")),
+        None => (MaterialSymbol::Cloud, tr("statusbar.unknown")),
+    };
+    let account = match &snapshot.token {
+        Some(token) if token.has_access_token || token.has_refresh_token => {
+            token.account.clone().unwrap_or_else(|| tr("settings.account").to_string())
+        }
+        _ => tr("statusbar.signed_out").to_string(),
+    };
+    let memory = ui.handle_stats();
+    let memory_label = match memory.hit_rate() {
+        Some(rate) => trf("statusbar.memory_hits", &[&memory.entries, &rate]),
+        None => trf("statusbar.memory", &[&memory.entries]),
+    };
+    let memory_details = trf("statusbar.memory_details", &[&memory.hits, &memory.misses, &memory.evictions]);
+    let network = &snapshot.network;
+    let network_label = trf("statusbar.network", &[&format_size(network.bytes_down), &format_size(network.bytes_up)]);
+    let network_details = trf(
+        "statusbar.network_details",
+        &[&network.requests, &network.errors, &(network.duration_ms / 1000)],
+    );
+    let sync = if ui.sync_activity.paused {
+        tr("sync.paused").to_string()
+    } else if ui.syncing {
+        ui.sync_status.clone()
+    } else {
+        tr("sync.idle").to_string()
+    };
+    container(
+        row![
+            segment(MaterialSymbol::PhotoLibrary, shown),
+            segment(MaterialSymbol::Database, trf("statusbar.cache_size", &[&size])),
+            tooltip(segment(MaterialSymbol::Memory, memory_label), text(memory_details).size(12), Position::Top)
+                .style(style::card()),
+            segment(online_icon, online.to_string()),
+            tooltip(segment(MaterialSymbol::SwapVert, network_label), text(network_details).size(12), Position::Top)
+                .style(style::card()),
+            segment(MaterialSymbol::Sync, sync),
+            Space::with_width(Length::Fill),
+            button(segment(MaterialSymbol::AccountCircle, account))
+                .style(iced::theme::Button::Text)
+                .padding(0)
+                .on_press(Message::ShowAccountSettings),
+        ]
+        .spacing(Palette::SPACING)
+        .align_items(iced::Alignment::Center),
+    )
+    .width(Length::Fill)
+    .padding([2, Palette::SPACING / 2])
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(2048), "2 KB");
+        assert_eq!(format_size(3 * 1024 * 1024 + 300 * 1024), "3.3 MB");
+        assert_eq!(format_size(2 * 1024 * 1024 * 1024), "2.0 GB");
+    }
+
+    #[test]
+    fn test_only_unreachable_errors_mean_offline() {
+        let mut bar = StatusBar::default();
+        bar.record(Some("Request Error: error sending request for url"));
+        assert_eq!(bar.online, Some(false));
+        bar.record(Some("Google API Error: quota exceeded"));
+        assert_eq!(bar.online, Some(true));
+        assert!(bar.reconnected);
+        bar.reconnected = false;
+        bar.record(Some("Request Error: timed out"));
+        assert!(!bar.reconnected);
+        bar.record(None);
+        assert_eq!(bar.online, Some(true));
+        assert!(bar.reconnected);
+    }
+}
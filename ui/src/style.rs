@@ -4,89 +4,258 @@
 //! New components should be built on top of these helpers so the
 //! application keeps a consistent Material look.
 
-use iced::{Color, Border};
+use iced::{Color, Border, Shadow, Vector};
 use iced::widget::{self, button, checkbox, container, slider, text_input};
 use iced::theme;
 
+use crate::notifications::NotificationLevel;
+
 /// Material color palette
-pub struct Palette;
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Palette {
+    pub primary: Color,
+    pub secondary: Color,
+    pub background: Color,
+    pub surface: Color,
+    pub error: Color,
+    pub on_primary: Color,
+    pub on_secondary: Color,
+    pub on_background: Color,
+    pub on_surface: Color,
+}
 
 impl Palette {
-    pub const PRIMARY: Color = Color { r: 0.25, g: 0.32, b: 0.71, a: 1.0 }; // Indigo 700
+    pub const LIGHT: Palette = Palette {
+        primary: Color { r: 0.25, g: 0.32, b: 0.71, a: 1.0 }, // Indigo 700
+        secondary: Self::SECONDARY,
+        background: Color::WHITE,
+        surface: Color { r: 0.98, g: 0.98, b: 0.98, a: 1.0 },
+        error: Self::ERROR,
+        on_primary: Self::ON_PRIMARY,
+        on_secondary: Self::ON_SECONDARY,
+        on_background: Color { r: 0.1, g: 0.1, b: 0.1, a: 1.0 },
+        on_surface: Color { r: 0.1, g: 0.1, b: 0.1, a: 1.0 },
+    };
+
+    pub const DARK: Palette = Palette {
+        primary: Color { r: 0.36, g: 0.42, b: 0.75, a: 1.0 }, // Indigo 400
+        secondary: Self::SECONDARY,
+        background: Color { r: 0.07, g: 0.07, b: 0.07, a: 1.0 },
+        surface: Color { r: 0.12, g: 0.12, b: 0.12, a: 1.0 },
+        error: Self::ERROR,
+        on_primary: Self::ON_PRIMARY,
+        on_secondary: Self::ON_SECONDARY,
+        on_background: Color { r: 0.92, g: 0.92, b: 0.92, a: 1.0 },
+        on_surface: Color { r: 0.92, g: 0.92, b: 0.92, a: 1.0 },
+    };
+
+    // Shared by both variants, so views may use them without the active theme
     pub const SECONDARY: Color = Color { r: 0.96, g: 0.26, b: 0.21, a: 1.0 }; // Red 500
-    pub const BACKGROUND: Color = Color::WHITE;
-    pub const SURFACE: Color = Color { r: 0.98, g: 0.98, b: 0.98, a: 1.0 };
     pub const ERROR: Color = Color { r: 0.80, g: 0.0, b: 0.0, a: 1.0 };
-
     pub const ON_PRIMARY: Color = Color::WHITE;
     pub const ON_SECONDARY: Color = Color::WHITE;
-    pub const ON_BACKGROUND: Color = Color { r: 0.1, g: 0.1, b: 0.1, a: 1.0 };
-    pub const ON_SURFACE: Color = Self::ON_BACKGROUND;
+    pub const SUCCESS: Color = Color { r: 0.26, g: 0.63, b: 0.28, a: 1.0 }; // Green 600
+    pub const WARNING: Color = Color { r: 1.0, g: 0.63, b: 0.0, a: 1.0 }; // Amber 700
 
     pub const SPACING: u16 = 16;
-    pub const ICON_COLOR: Color = Self::ON_SURFACE;
     pub const ICON_SIZE: u16 = 20;
+
+    /// Variant matching the theme returned by `Application::theme`.
+    pub fn of(theme: &iced::Theme) -> Palette {
+        if theme.extended_palette().is_dark {
+            Self::DARK
+        } else {
+            Self::LIGHT
+        }
+    }
+}
+
+/// Theme choices offered in the settings dialog.
+pub const THEMES: [&str; 3] = ["system", "light", "dark"];
+
+/// Resolve a `theme` setting; `system` follows the desktop preference.
+pub fn theme_for(setting: &str, system_dark: bool) -> iced::Theme {
+    match setting {
+        "dark" => iced::Theme::Dark,
+        "light" => iced::Theme::Light,
+        _ if system_dark => iced::Theme::Dark,
+        _ => iced::Theme::Light,
+    }
+}
+
+/// Whether the desktop currently prefers a dark color scheme.
+pub fn system_prefers_dark() -> bool {
+    matches!(dark_light::detect(), dark_light::Mode::Dark)
 }
 
 /// Container style used for dialogs and overlays.
 pub fn dialog() -> theme::Container {
-    theme::Container::Custom(Box::new(|_theme: &iced::Theme| container::Appearance {
-        background: Some(Palette::SURFACE.into()),
-        text_color: Some(Palette::ON_SURFACE),
-        border: Border {
-            color: Palette::PRIMARY,
-            width: 1.0,
-            radius: 8.0.into(),
-        },
-        shadow: widget::container::Shadow::default(),
+    theme::Container::Custom(Box::new(|theme: &iced::Theme| {
+        let p = Palette::of(theme);
+        container::Appearance {
+            background: Some(p.surface.into()),
+            text_color: Some(p.on_surface),
+            border: Border {
+                color: p.primary,
+                width: 1.0,
+                radius: 8.0.into(),
+            },
+            shadow: widget::container::Shadow::default(),
+        }
     }))
 }
 
 /// Style for primary action buttons.
 pub fn button_primary() -> theme::Button {
-    theme::Button::Custom(Box::new(|_theme: &iced::Theme| button::Appearance {
-        background: Some(Palette::PRIMARY.into()),
-        border_radius: 4.0,
-        text_color: Palette::ON_PRIMARY,
-        ..Default::default()
+    theme::Button::Custom(Box::new(|theme: &iced::Theme| {
+        let p = Palette::of(theme);
+        button::Appearance {
+            background: Some(p.primary.into()),
+            border_radius: 4.0,
+            text_color: p.on_primary,
+            ..Default::default()
+        }
     }))
 }
 
 /// Style for secondary action buttons.
 pub fn button_secondary() -> theme::Button {
-    theme::Button::Custom(Box::new(|_theme: &iced::Theme| button::Appearance {
-        background: Some(Palette::SECONDARY.into()),
-        border_radius: 4.0,
-        text_color: Palette::ON_SECONDARY,
-        ..Default::default()
+    theme::Button::Custom(Box::new(|theme: &iced::Theme| {
+        let p = Palette::of(theme);
+        button::Appearance {
+            background: Some(p.secondary.into()),
+            border_radius: 4.0,
+            text_color: p.on_secondary,
+            ..Default::default()
+        }
     }))
 }
 
 /// Basic text input styling.
 pub fn text_input() -> theme::TextInput {
-    theme::TextInput::Custom(Box::new(|_theme: &iced::Theme| text_input::Appearance {
-        background: Palette::SURFACE.into(),
-        border_radius: 4.0,
-        border_width: 1.0,
-        border_color: Palette::PRIMARY,
-        icon_color: Palette::ON_SURFACE,
-        placeholder_color: Palette::ON_SURFACE,
-        value_color: Palette::ON_SURFACE,
-        selection_color: Palette::PRIMARY,
+    theme::TextInput::Custom(Box::new(|theme: &iced::Theme| {
+        let p = Palette::of(theme);
+        text_input::Appearance {
+            background: p.surface.into(),
+            border_radius: 4.0,
+            border_width: 1.0,
+            border_color: p.primary,
+            icon_color: p.on_surface,
+            placeholder_color: p.on_surface,
+            value_color: p.on_surface,
+            selection_color: p.primary,
+        }
     }))
 }
 
 /// Container style that mimics Material "cards".
 pub fn card() -> theme::Container {
-    theme::Container::Custom(Box::new(|_theme: &iced::Theme| container::Appearance {
-        background: Some(Palette::SURFACE.into()),
-        text_color: Some(Palette::ON_SURFACE),
-        border: Border {
-            color: Palette::PRIMARY,
-            width: 1.0,
-            radius: 4.0.into(),
-        },
-        shadow: Default::default(),
+    theme::Container::Custom(Box::new(|theme: &iced::Theme| {
+        let p = Palette::of(theme);
+        container::Appearance {
+            background: Some(p.surface.into()),
+            text_color: Some(p.on_surface),
+            border: Border {
+                color: p.primary,
+                width: 1.0,
+                radius: 4.0.into(),
+            },
+            shadow: Default::default(),
+        }
+    }))
+}
+
+/// Highlight border drawn around selected grid tiles.
+pub fn tile_selected() -> theme::Container {
+    theme::Container::Custom(Box::new(|theme: &iced::Theme| {
+        let p = Palette::of(theme);
+        container::Appearance {
+            background: Some(Color { a: 0.2, ..p.primary }.into()),
+            text_color: None,
+            border: Border {
+                color: p.secondary,
+                width: 3.0,
+                radius: 4.0.into(),
+            },
+            shadow: Default::default(),
+        }
+    }))
+}
+
+/// Border marking the keyboard-focused grid tile.
+pub fn tile_focused() -> theme::Container {
+    theme::Container::Custom(Box::new(|theme: &iced::Theme| {
+        let p = Palette::of(theme);
+        container::Appearance {
+            background: None,
+            text_color: None,
+            border: Border {
+                color: p.primary,
+                width: 2.0,
+                radius: 4.0.into(),
+            },
+            shadow: Default::default(),
+        }
+    }))
+}
+
+/// Placeholder block shown where a thumbnail has not arrived yet.
+pub fn skeleton() -> theme::Container {
+    theme::Container::Custom(Box::new(|theme: &iced::Theme| {
+        let p = Palette::of(theme);
+        container::Appearance {
+            background: Some(Color { a: 0.12, ..p.on_surface }.into()),
+            text_color: Some(p.on_surface),
+            border: Border {
+                color: Color::TRANSPARENT,
+                width: 0.0,
+                radius: 4.0.into(),
+            },
+            shadow: Default::default(),
+        }
+    }))
+}
+
+/// Rounded container for compact status chips in the header.
+pub fn chip() -> theme::Container {
+    theme::Container::Custom(Box::new(|theme: &iced::Theme| {
+        let p = Palette::of(theme);
+        container::Appearance {
+            background: Some(p.surface.into()),
+            text_color: Some(p.on_surface),
+            border: Border {
+                color: p.primary,
+                width: 1.0,
+                radius: 12.0.into(),
+            },
+            shadow: Default::default(),
+        }
+    }))
+}
+
+/// Toast card whose border carries the accent of its level.
+pub fn toast(level: NotificationLevel) -> theme::Container {
+    theme::Container::Custom(Box::new(move |theme: &iced::Theme| {
+        let p = Palette::of(theme);
+        let accent = match level {
+            NotificationLevel::Info => p.primary,
+            NotificationLevel::Success => Palette::SUCCESS,
+            NotificationLevel::Warning => Palette::WARNING,
+        };
+        container::Appearance {
+            background: Some(p.surface.into()),
+            text_color: Some(p.on_surface),
+            border: Border {
+                color: accent,
+                width: 2.0,
+                radius: 8.0.into(),
+            },
+            shadow: Shadow {
+                color: Color { a: 0.3, ..Color::BLACK },
+                offset: Vector::new(0.0, 2.0),
+                blur_radius: 6.0,
+            },
+        }
     }))
 }
 
@@ -98,13 +267,14 @@ pub fn card() -> theme::Container {
 /// use iced::theme;
 ///
 /// pub fn checkbox_primary() -> theme::Checkbox {
-///     theme::Checkbox::Custom(Box::new(|_theme: &iced::Theme, is_checked: bool| {
+///     theme::Checkbox::Custom(Box::new(|theme: &iced::Theme, is_checked: bool| {
+///         let p = Palette::of(theme);
 ///         checkbox::Appearance {
-///             background: Palette::SURFACE.into(),
-///             checkmark_color: if is_checked { Palette::PRIMARY } else { Palette::ON_SURFACE },
+///             background: p.surface.into(),
+///             checkmark_color: if is_checked { p.primary } else { p.on_surface },
 ///             border_radius: 2.0,
 ///             border_width: 1.0,
-///             border_color: Palette::PRIMARY,
+///             border_color: p.primary,
 ///         }
 ///     }))
 /// }
@@ -114,12 +284,13 @@ pub fn card() -> theme::Container {
 
 /// Checkbox styled with the primary color palette.
 pub fn checkbox_primary() -> theme::Checkbox {
-    theme::Checkbox::Custom(Box::new(|_theme: &iced::Theme, is_checked: bool| {
+    theme::Checkbox::Custom(Box::new(|theme: &iced::Theme, is_checked: bool| {
+        let p = Palette::of(theme);
         checkbox::Appearance {
-            background: Palette::SURFACE.into(),
-            icon_color: if is_checked { Palette::PRIMARY } else { Palette::ON_SURFACE },
+            background: p.surface.into(),
+            icon_color: if is_checked { p.primary } else { p.on_surface },
             border: Border {
-                color: Palette::PRIMARY,
+                color: p.primary,
                 width: 1.0,
                 radius: 2.0.into(),
             },
@@ -131,27 +302,28 @@ pub fn checkbox_primary() -> theme::Checkbox {
 struct SliderPrimary;
 
 impl slider::StyleSheet for SliderPrimary {
-    type Style = ();
+    type Style = iced::Theme;
 
-    fn active(&self, _style: &Self::Style) -> slider::Appearance {
+    fn active(&self, style: &Self::Style) -> slider::Appearance {
+        let p = Palette::of(style);
         slider::Appearance {
             rail: slider::Rail {
-                colors: (Palette::PRIMARY, Palette::PRIMARY),
+                colors: (p.primary, p.primary),
                 width: 4.0,
                 border_radius: 2.0.into(),
             },
             handle: slider::Handle {
                 shape: slider::HandleShape::Circle { radius: 8.0 },
-                color: Palette::ON_PRIMARY,
+                color: p.on_primary,
                 border_width: 1.0,
-                border_color: Palette::PRIMARY,
+                border_color: p.primary,
             },
         }
     }
 
     fn hovered(&self, style: &Self::Style) -> slider::Appearance {
         let mut a = self.active(style);
-        a.handle.color = Palette::PRIMARY;
+        a.handle.color = Palette::of(style).primary;
         a
     }
 
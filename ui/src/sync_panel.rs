@@ -0,0 +1,127 @@
+//! Expandable panel below the header breaking down what sync is doing.
+//!
+//! Opened by clicking the sync status text; updated live from the progress
+//! and error subscriptions.
+
+use std::time::Instant;
+
+use chrono::{DateTime, Utc};
+use iced::widget::{button, column, container, progress_bar, row, text};
+use iced::{Element, Length};
+use sync::{SyncReport, SyncStage};
+
+use crate::i18n::{tr, trf};
+use crate::style::{self, Palette};
+use crate::{Icon, MaterialSymbol, Message};
+
+#[derive(Debug, Default)]
+pub struct SyncActivity {
+    pub open: bool,
+    pub stage: Option<SyncStage>,
+    pub processed: u64,
+    pub estimated_total: Option<u64>,
+    pub page: u64,
+    /// Most recent sync error and when it arrived
+    pub last_error: Option<(DateTime<Utc>, String)>,
+    /// When the pending retry starts
+    pub retry_at: Option<Instant>,
+    /// Summary of the last completed run
+    pub report: Option<SyncReport>,
+    /// Photos scanned and total of a running face scan
+    pub face_scan: Option<(u64, u64)>,
+    pub paused: bool,
+}
+
+impl SyncActivity {
+    /// Whole seconds left before the pending retry, rounded up.
+    pub fn retry_remaining(&self) -> Option<u64> {
+        self.retry_at
+            .map(|at| at.saturating_duration_since(Instant::now()).as_secs_f32().ceil() as u64)
+    }
+}
+
+fn stage_label(stage: SyncStage) -> &'static str {
+    match stage {
+        SyncStage::MediaItems => tr("sync.stage.media_items"),
+        SyncStage::Faces => tr("sync.stage.faces"),
+    }
+}
+
+pub fn view<'a>(ui: &crate::GooglePiczUI) -> Option<Element<'a, Message>> {
+    let activity = &ui.sync_activity;
+    if !activity.open {
+        return None;
+    }
+    let can_control = ui.sync_control.is_some();
+    let pause = if activity.paused {
+        button(Icon::new(MaterialSymbol::PlayArrow).color(Palette::ON_SECONDARY))
+            .style(style::button_secondary())
+            .on_press_maybe(can_control.then_some(Message::ResumeSync))
+    } else {
+        button(Icon::new(MaterialSymbol::Pause).color(Palette::ON_SECONDARY))
+            .style(style::button_secondary())
+            .on_press_maybe(can_control.then_some(Message::PauseSync))
+    };
+    let controls = row![
+        text(tr("sync.title")).size(16).width(Length::Fill),
+        button(text(tr("sync.now")))
+            .style(style::button_primary())
+            .on_press_maybe(can_control.then_some(Message::SyncNow)),
+        pause,
+    ]
+    .spacing(Palette::SPACING / 2)
+    .align_items(iced::Alignment::Center);
+
+    let stage = match (activity.paused, ui.syncing, activity.stage) {
+        (true, _, _) => tr("sync.paused").to_string(),
+        (false, true, Some(stage)) => trf("sync.stage", &[&stage_label(stage)]),
+        _ => tr("sync.idle").to_string(),
+    };
+    let mut col = column![controls, text(stage)].spacing(Palette::SPACING / 2);
+    if ui.syncing {
+        let processed: Element<'a, Message> = match activity.estimated_total {
+            Some(total) => row![
+                text(trf("sync.processed_of", &[&activity.processed, &total])),
+                progress_bar(0.0..=total.max(1) as f32, activity.processed as f32)
+                    .width(Length::Fixed(160.0)),
+            ]
+            .spacing(Palette::SPACING / 2)
+            .align_items(iced::Alignment::Center)
+            .into(),
+            None => text(trf("sync.processed", &[&activity.processed])).into(),
+        };
+        col = col.push(processed).push(text(trf("sync.page", &[&activity.page])));
+    }
+    if let Some((processed, total)) = activity.face_scan {
+        col = col.push(
+            row![
+                text(trf("status.scanning_faces", &[&processed, &total])),
+                progress_bar(0.0..=total.max(1) as f32, processed as f32).width(Length::Fixed(160.0)),
+            ]
+            .spacing(Palette::SPACING / 2)
+            .align_items(iced::Alignment::Center),
+        );
+    }
+    if let Some(secs) = activity.retry_remaining() {
+        col = col.push(text(trf("sync.retry_in", &[&secs])));
+    }
+    if let Some(report) = &activity.report {
+        col = col.push(text(trf(
+            "sync.report",
+            &[&report.fetched, &report.inserted, &report.updated, &report.skipped, &report.pages],
+        )));
+    }
+    if let Some((at, message)) = &activity.last_error {
+        col = col.push(
+            text(trf("sync.last_error", &[&at.to_rfc3339(), message]))
+                .style(iced::theme::Text::Color(Palette::ERROR)),
+        );
+    }
+    Some(
+        container(col)
+            .style(style::card())
+            .padding(Palette::SPACING / 2)
+            .width(Length::Fill)
+            .into(),
+    )
+}
@@ -0,0 +1,443 @@
+//! Date-grouped photo grid with collapsible section headers.
+
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+
+use api_client::MediaItem;
+use cache::TimeBucket;
+use chrono::{DateTime, Datelike, NaiveDate, Utc, Weekday};
+use iced::widget::image::Handle;
+use iced::widget::tooltip::Position;
+use iced::widget::{button, column, container, image, mouse_area, row, text, tooltip, Column, Space};
+use iced::{Color, Element, Length};
+
+use crate::i18n::{tr, trf};
+use crate::style::{self, Palette};
+use crate::{Icon, ImageLoaderError, MaterialSymbol, Message};
+
+/// Height reserved for a section header, including the spacing below it.
+const HEADER_HEIGHT: f32 = 40.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Granularity {
+    Day,
+    #[default]
+    Month,
+    Year,
+}
+
+impl Granularity {
+    pub const ALL: [Granularity; 3] = [Granularity::Day, Granularity::Month, Granularity::Year];
+
+    pub fn bucket(self) -> TimeBucket {
+        match self {
+            Granularity::Day => TimeBucket::Day,
+            Granularity::Month => TimeBucket::Month,
+            Granularity::Year => TimeBucket::Year,
+        }
+    }
+
+    /// Key format, identical to the one the cache groups by.
+    fn format(self) -> &'static str {
+        match self {
+            Granularity::Day => "%Y-%m-%d",
+            Granularity::Month => "%Y-%m",
+            Granularity::Year => "%Y",
+        }
+    }
+}
+
+impl std::fmt::Display for Granularity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Granularity::Day => tr("timeline.day"),
+            Granularity::Month => tr("timeline.month"),
+            Granularity::Year => tr("timeline.year"),
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// Where a tile's thumbnail is in its lifecycle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ThumbnailState {
+    NotLoaded,
+    Loading,
+    Loaded(Handle),
+    /// The download failed with this error; the tile offers a retry unless it is permanent
+    Failed(ImageLoaderError),
+}
+
+/// Photos of one period as a contiguous range of the date-sorted photo list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Section {
+    pub key: String,
+    pub items: Range<usize>,
+}
+
+#[derive(Debug, Default)]
+pub struct Timeline {
+    pub granularity: Granularity,
+    collapsed: HashSet<String>,
+    /// Library-wide totals per period; `None` counts only the loaded photos
+    counts: Option<HashMap<String, u64>>,
+    pub jump_input: String,
+}
+
+impl Timeline {
+    pub fn set_granularity(&mut self, granularity: Granularity) {
+        self.granularity = granularity;
+        self.collapsed.clear();
+    }
+
+    pub fn set_counts(&mut self, counts: Vec<(String, u64)>) {
+        self.counts = Some(counts.into_iter().collect());
+    }
+
+    pub fn clear_counts(&mut self) {
+        self.counts = None;
+    }
+
+    /// Whether headers show totals from the cache rather than local counts.
+    pub fn uses_library_counts(&self) -> bool {
+        self.counts.is_some()
+    }
+
+    pub fn toggle(&mut self, key: &str) {
+        if !self.collapsed.remove(key) {
+            self.collapsed.insert(key.to_string());
+        }
+    }
+
+    pub fn expand(&mut self, key: &str) {
+        self.collapsed.remove(key);
+    }
+
+    pub fn is_collapsed(&self, key: &str) -> bool {
+        self.collapsed.contains(key)
+    }
+
+    fn count(&self, section: &Section) -> u64 {
+        self.counts
+            .as_ref()
+            .and_then(|c| c.get(&section.key).copied())
+            .unwrap_or(section.items.len() as u64)
+    }
+
+    /// Header text such as "March 2024 (213)".
+    pub fn title(&self, section: &Section) -> String {
+        format!("{} ({})", label(&section.key, self.granularity), self.count(section))
+    }
+}
+
+fn timestamp(item: &MediaItem) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(&item.media_metadata.creation_time)
+        .ok()
+        .map(|d| d.with_timezone(&Utc))
+}
+
+/// Newest first; items without a parseable creation time go last.
+pub fn sort_photos(photos: &mut [MediaItem]) {
+    photos.sort_by_key(|p| std::cmp::Reverse(timestamp(p)));
+}
+
+pub fn period_key(item: &MediaItem, granularity: Granularity) -> String {
+    timestamp(item)
+        .map(|t| t.format(granularity.format()).to_string())
+        .unwrap_or_else(|| "unknown".into())
+}
+
+/// Split date-sorted photos into consecutive runs sharing a period.
+pub fn sections(photos: &[MediaItem], granularity: Granularity) -> Vec<Section> {
+    let mut out: Vec<Section> = Vec::new();
+    for (idx, photo) in photos.iter().enumerate() {
+        let key = period_key(photo, granularity);
+        match out.last_mut() {
+            Some(s) if s.key == key => s.items.end = idx + 1,
+            _ => out.push(Section { key, items: idx..idx + 1 }),
+        }
+    }
+    out
+}
+
+fn month_name(date: NaiveDate) -> &'static str {
+    match date.month() {
+        1 => tr("month.1"),
+        2 => tr("month.2"),
+        3 => tr("month.3"),
+        4 => tr("month.4"),
+        5 => tr("month.5"),
+        6 => tr("month.6"),
+        7 => tr("month.7"),
+        8 => tr("month.8"),
+        9 => tr("month.9"),
+        10 => tr("month.10"),
+        11 => tr("month.11"),
+        _ => tr("month.12"),
+    }
+}
+
+fn weekday_name(date: NaiveDate) -> &'static str {
+    match date.weekday() {
+        Weekday::Mon => tr("weekday.mon"),
+        Weekday::Tue => tr("weekday.tue"),
+        Weekday::Wed => tr("weekday.wed"),
+        Weekday::Thu => tr("weekday.thu"),
+        Weekday::Fri => tr("weekday.fri"),
+        Weekday::Sat => tr("weekday.sat"),
+        Weekday::Sun => tr("weekday.sun"),
+    }
+}
+
+fn label(key: &str, granularity: Granularity) -> String {
+    if key == "unknown" {
+        return tr("timeline.unknown_date").into();
+    }
+    // Names come from the bundles since chrono only formats English ones
+    let parsed = match granularity {
+        Granularity::Year => return key.to_string(),
+        Granularity::Month => NaiveDate::parse_from_str(&format!("{}-01", key), "%Y-%m-%d")
+            .map(|d| trf("date.month", &[&month_name(d), &d.year()])),
+        Granularity::Day => NaiveDate::parse_from_str(key, "%Y-%m-%d").map(|d| {
+            trf("date.day", &[&weekday_name(d), &d.day(), &month_name(d), &d.year()])
+        }),
+    };
+    parsed.unwrap_or_else(|_| tr("timeline.unknown_date").into())
+}
+
+/// Normalise "2024", "2024-03" or "2024-03-15" for comparison with section keys.
+pub fn parse_jump(query: &str) -> Option<String> {
+    let query = query.trim();
+    let padded = match query.len() {
+        4 => format!("{}-01-01", query),
+        7 => format!("{}-01", query),
+        _ => query.to_string(),
+    };
+    NaiveDate::parse_from_str(&padded, "%Y-%m-%d").ok()?;
+    Some(query.to_string())
+}
+
+/// First (newest) section at or before the normalised date.
+pub fn jump_target<'s>(sections: &'s [Section], date: &str) -> Option<&'s Section> {
+    sections.iter().find(|s| {
+        let n = s.key.len().min(date.len());
+        s.key.as_bytes()[..n] <= date.as_bytes()[..n]
+    })
+}
+
+struct Block {
+    top: f32,
+    height: f32,
+    items: Range<usize>,
+    header: Option<String>,
+}
+
+/// Vertical positions of headers and tile rows inside the grid scrollable.
+pub struct Layout {
+    blocks: Vec<Block>,
+}
+
+impl Layout {
+    pub fn new(sections: &[Section], timeline: &Timeline, columns: usize, row_height: f32) -> Self {
+        let mut blocks = Vec::new();
+        let mut top = 0.0;
+        for section in sections {
+            blocks.push(Block {
+                top,
+                height: HEADER_HEIGHT,
+                items: section.items.start..section.items.start,
+                header: Some(section.key.clone()),
+            });
+            top += HEADER_HEIGHT;
+            if timeline.is_collapsed(&section.key) {
+                continue;
+            }
+            let mut start = section.items.start;
+            while start < section.items.end {
+                let end = (start + columns).min(section.items.end);
+                blocks.push(Block { top, height: row_height, items: start..end, header: None });
+                top += row_height;
+                start = end;
+            }
+        }
+        Self { blocks }
+    }
+
+    /// Span of photo indices in rows overlapping the viewport widened by `lookahead`.
+    pub fn visible(&self, offset: f32, height: f32, lookahead: f32) -> Range<usize> {
+        let (lo, hi) = (offset - lookahead, offset + height + lookahead);
+        self.blocks
+            .iter()
+            .filter(|b| !b.items.is_empty() && b.top + b.height >= lo && b.top <= hi)
+            .fold(None, |acc: Option<Range<usize>>, b| {
+                Some(match acc {
+                    Some(r) => r.start.min(b.items.start)..r.end.max(b.items.end),
+                    None => b.items.clone(),
+                })
+            })
+            .unwrap_or(0..0)
+    }
+
+    /// Top and height of the row holding photo `idx`, if it is not collapsed.
+    pub fn item_row(&self, idx: usize) -> Option<(f32, f32)> {
+        self.blocks
+            .iter()
+            .find(|b| b.items.contains(&idx))
+            .map(|b| (b.top, b.height))
+    }
+
+    pub fn section_top(&self, key: &str) -> Option<f32> {
+        self.blocks
+            .iter()
+            .find(|b| b.header.as_deref() == Some(key))
+            .map(|b| b.top)
+    }
+
+    /// Key of the section whose header was last scrolled past.
+    pub fn section_at(&self, offset: f32) -> Option<&str> {
+        self.blocks
+            .iter()
+            .take_while(|b| b.top <= offset)
+            .filter_map(|b| b.header.as_deref())
+            .last()
+            .or_else(|| self.blocks.first().and_then(|b| b.header.as_deref()))
+    }
+}
+
+/// `m:ss`, or `h:mm:ss` from one hour on, like other video players.
+pub fn format_duration(ms: u64) -> String {
+    let secs = ms / 1000;
+    let (h, m, s) = (secs / 3600, secs / 60 % 60, secs % 60);
+    if h > 0 {
+        format!("{}:{:02}:{:02}", h, m, s)
+    } else {
+        format!("{}:{:02}", m, s)
+    }
+}
+
+fn tile<'a>(ui: &crate::GooglePiczUI, idx: usize, photo: &MediaItem) -> Element<'a, Message> {
+    let size = Length::Fixed(ui.tile_size());
+    let thumb: Element<'a, Message> = match ui.thumbnail_state(&photo.id) {
+        ThumbnailState::Loaded(handle) => image(handle).width(size).height(size).into(),
+        ThumbnailState::Failed(error) => {
+            let broken = tooltip(
+                Icon::new(MaterialSymbol::BrokenImage).size(32),
+                text(error.to_string()).size(12),
+                Position::Bottom,
+            )
+            .style(style::card());
+            let mut content = column![broken].spacing(4).align_items(iced::Alignment::Center);
+            // Missing or undecodable images stay broken however often they are retried
+            if !error.is_permanent() {
+                content = content.push(
+                    tooltip(
+                        button(Icon::new(MaterialSymbol::Refresh).size(16))
+                            .style(style::button_secondary())
+                            .on_press(Message::RetryThumbnail(photo.id.clone())),
+                        text(tr("thumbnail.retry")).size(12),
+                        Position::Bottom,
+                    )
+                    .style(style::card()),
+                );
+            }
+            container(content)
+                .width(size)
+                .height(size)
+                .center_x()
+                .center_y()
+                .into()
+        }
+        ThumbnailState::NotLoaded | ThumbnailState::Loading => container(Space::new(size, size))
+            .style(style::skeleton())
+            .into(),
+    };
+    let heart_color = if photo.is_favorite {
+        Palette::SECONDARY
+    } else {
+        // Drawn on the primary tile button in either theme
+        Color { a: 0.4, ..Palette::ON_PRIMARY }
+    };
+    let mut badges = row![].spacing(4).align_items(iced::Alignment::Center);
+    if photo.mime_type.starts_with("video/") {
+        badges = badges.push(Icon::new(MaterialSymbol::PlayCircle).size(16).color(Palette::ON_PRIMARY));
+        if let Some(ms) = ui.video_durations.get(&photo.id) {
+            badges = badges.push(text(format_duration(*ms)).size(12).style(Palette::ON_PRIMARY));
+        }
+    }
+    let thumb: Element<'a, Message> = column![
+        thumb,
+        badges.push(Icon::new(MaterialSymbol::Favorite).size(16).color(heart_color))
+    ]
+    .align_items(iced::Alignment::End)
+    .into();
+    let thumb: Element<'a, Message> = if ui.selection.contains(&photo.id) {
+        container(thumb).style(style::tile_selected()).padding(3).into()
+    } else {
+        thumb
+    };
+    let thumb: Element<'a, Message> = if ui.focused == Some(idx) {
+        container(thumb).style(style::tile_focused()).padding(2).into()
+    } else {
+        thumb
+    };
+    let on_press = if ui.modifiers.shift() {
+        Message::SelectRangeTo(photo.id.clone())
+    } else if ui.modifiers.command() || !ui.selection.is_empty() {
+        Message::ToggleSelect(photo.id.clone())
+    } else {
+        Message::SelectPhoto(photo.clone())
+    };
+    // Hover feeds the drag-to-album gesture and right-clicks open the context menu;
+    // the button still handles clicks
+    mouse_area(button(thumb).style(style::button_primary()).on_press(on_press))
+        .on_enter(Message::TileHovered(photo.id.clone()))
+        .on_exit(Message::TileUnhovered(photo.id.clone()))
+        .on_right_press(Message::OpenContextMenu(photo.id.clone()))
+        .into()
+}
+
+/// Sections of the photos shown so far and their scroll layout.
+pub fn layout(ui: &crate::GooglePiczUI) -> (Vec<Section>, Layout) {
+    let photos = &ui.photos[..ui.display_limit.min(ui.photos.len())];
+    let sections = sections(photos, ui.timeline.granularity);
+    let layout = Layout::new(&sections, &ui.timeline, ui.grid_columns(), ui.grid_row_height());
+    (sections, layout)
+}
+
+/// Title of the section at the top of the viewport, shown above the grid.
+pub fn current_title(ui: &crate::GooglePiczUI) -> Option<String> {
+    let (sections, layout) = layout(ui);
+    let key = layout.section_at(ui.grid_viewport.0)?;
+    sections.iter().find(|s| s.key == key).map(|s| ui.timeline.title(s))
+}
+
+/// Section headers followed by their tile rows; collapsed sections show only the header.
+pub fn view<'a>(ui: &crate::GooglePiczUI) -> Column<'a, Message> {
+    let photos = &ui.photos[..ui.display_limit.min(ui.photos.len())];
+    let columns = ui.grid_columns();
+    let mut col = column![].spacing(10);
+    for section in sections(photos, ui.timeline.granularity) {
+        let collapsed = ui.timeline.is_collapsed(&section.key);
+        let marker = if collapsed { "▸" } else { "▾" };
+        col = col.push(
+            button(text(format!("{} {}", marker, ui.timeline.title(&section))).size(16))
+                .style(style::button_secondary())
+                .height(Length::Fixed(HEADER_HEIGHT - 10.0))
+                .on_press(Message::ToggleSection(section.key.clone())),
+        );
+        if collapsed {
+            continue;
+        }
+        let mut start = section.items.start;
+        while start < section.items.end {
+            let end = (start + columns).min(section.items.end);
+            let mut current = row![].spacing(10);
+            for idx in start..end {
+                current = current.push(tile(ui, idx, &photos[idx]));
+            }
+            col = col.push(current);
+            start = end;
+        }
+    }
+    col
+}
@@ -0,0 +1,144 @@
+//! Trash view listing the items moved to the trash, newest first.
+//!
+//! Trashed items stay in the cache with their album associations, so
+//! restoring one puts it straight back into its albums and search results.
+//! "Delete forever" only drops the cached copy; the Library API cannot
+//! delete media items from Google Photos.
+
+use std::collections::HashSet;
+
+use cache::TrashedMediaItem;
+use chrono::Local;
+use iced::widget::{button, checkbox, column, container, row, scrollable, text, Column};
+use iced::{Element, Length};
+
+use crate::i18n::{tr, trf};
+use crate::style::{self, Palette};
+use crate::{Icon, MaterialSymbol, Message};
+
+#[derive(Debug, Default)]
+pub struct Trash {
+    /// The trash replaces the grid
+    pub open: bool,
+    pub items: Vec<TrashedMediaItem>,
+    pub selected: HashSet<String>,
+    /// The "Empty trash" confirmation is showing
+    pub confirm_empty: bool,
+}
+
+impl Trash {
+    pub fn contains(&self, id: &str) -> bool {
+        self.items.iter().any(|t| t.item.id == id)
+    }
+
+    /// Selected ids in list order.
+    pub fn selected_ids(&self) -> Vec<String> {
+        self.items
+            .iter()
+            .map(|t| t.item.id.clone())
+            .filter(|id| self.selected.contains(id))
+            .collect()
+    }
+
+    pub fn set_items(&mut self, items: Vec<TrashedMediaItem>) {
+        self.selected.retain(|id| items.iter().any(|t| t.item.id == *id));
+        self.items = items;
+    }
+}
+
+fn item_row<'a>(trash: &Trash, entry: &TrashedMediaItem) -> Element<'a, Message> {
+    let id = entry.item.id.clone();
+    let deleted = entry.deleted_at.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string();
+    row![
+        checkbox("", trash.selected.contains(&id), {
+            let id = id.clone();
+            move |_| Message::ToggleTrashSelection(id.clone())
+        }),
+        column![
+            text(entry.item.filename.clone()).size(14),
+            text(trf("trash.deleted_at", &[&deleted])).size(12),
+        ]
+        .width(Length::Fill),
+        button(
+            row![Icon::new(MaterialSymbol::RestoreFromTrash).size(16), text(tr("trash.restore")).size(14)]
+                .spacing(Palette::SPACING / 4)
+                .align_items(iced::Alignment::Center),
+        )
+        .style(style::button_secondary())
+        .on_press(Message::RestoreFromTrash(vec![id.clone()])),
+        button(
+            row![Icon::new(MaterialSymbol::DeleteForever).size(16), text(tr("trash.delete_forever")).size(14)]
+                .spacing(Palette::SPACING / 4)
+                .align_items(iced::Alignment::Center),
+        )
+        .style(style::button_secondary())
+        .on_press(Message::DeleteForever(vec![id])),
+    ]
+    .spacing(Palette::SPACING / 2)
+    .align_items(iced::Alignment::Center)
+    .into()
+}
+
+/// The trash list with its bulk actions, shown instead of the grid.
+pub fn view<'a>(ui: &crate::GooglePiczUI) -> Element<'a, Message> {
+    let trash = &ui.trash;
+    let selected = trash.selected_ids();
+    let actions = row![
+        text(trf("trash.count", &[&trash.items.len()])).size(16).width(Length::Fill),
+        button(text(tr("trash.restore_selected")))
+            .style(style::button_primary())
+            .on_press_maybe((!selected.is_empty()).then(|| Message::RestoreFromTrash(selected.clone()))),
+        button(text(tr("trash.delete_selected")))
+            .style(style::button_secondary())
+            .on_press_maybe((!selected.is_empty()).then(|| Message::DeleteForever(selected.clone()))),
+        button(
+            row![Icon::new(MaterialSymbol::DeleteForever).color(Palette::ON_PRIMARY), text(tr("trash.empty"))]
+                .spacing(Palette::SPACING / 4)
+                .align_items(iced::Alignment::Center),
+        )
+        .style(style::button_primary())
+        .on_press_maybe((!trash.items.is_empty()).then_some(Message::ShowEmptyTrashDialog)),
+    ]
+    .spacing(Palette::SPACING)
+    .align_items(iced::Alignment::Center);
+    let body: Element<'a, Message> = if trash.items.is_empty() {
+        text(tr("trash.empty_hint")).size(16).into()
+    } else {
+        let list = trash
+            .items
+            .iter()
+            .fold(Column::new().spacing(Palette::SPACING / 2), |col, entry| col.push(item_row(trash, entry)));
+        scrollable(list).height(Length::Fill).into()
+    };
+    column![text(tr("trash.title")).size(18), actions, body]
+        .spacing(Palette::SPACING)
+        .into()
+}
+
+/// Confirmation before everything in the trash is removed for good.
+pub fn empty_dialog<'a>(ui: &crate::GooglePiczUI) -> Option<Element<'a, Message>> {
+    if !ui.trash.confirm_empty {
+        return None;
+    }
+    Some(
+        container(
+            column![
+                text(trf("trash.empty_confirm", &[&ui.trash.items.len()])).size(16),
+                text(tr("trash.local_only")).size(14),
+                row![
+                    button(Icon::new(MaterialSymbol::DeleteForever).color(Palette::ON_PRIMARY))
+                        .style(style::button_primary())
+                        .on_press(Message::ConfirmEmptyTrash),
+                    button(Icon::new(MaterialSymbol::Cancel).color(Palette::ON_SECONDARY))
+                        .style(style::button_secondary())
+                        .on_press(Message::CancelEmptyTrash),
+                ]
+                .spacing(Palette::SPACING),
+            ]
+            .spacing(Palette::SPACING),
+        )
+        .style(style::dialog())
+        .padding(Palette::SPACING)
+        .into(),
+    )
+}
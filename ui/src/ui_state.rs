@@ -0,0 +1,165 @@
+//! Window geometry and the last view, restored on the next launch.
+//!
+//! Stored as `ui_state.json` next to the cache. Anything unreadable falls
+//! back to defaults so a bad file never keeps the app from starting.
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::search::{SavedSearch, SearchMode, RECENT_LIMIT};
+
+pub const FILE_NAME: &str = "ui_state.json";
+
+const DEFAULT_WIDTH: f32 = 1024.0;
+const DEFAULT_HEIGHT: f32 = 768.0;
+const MIN_WIDTH: f32 = 400.0;
+const MIN_HEIGHT: f32 = 300.0;
+/// Larger than any real display; bigger values come from a corrupt file.
+const MAX_EXTENT: f32 = 16384.0;
+/// Range of the grid zoom slider, in pixels per tile edge.
+pub const MIN_TILE_SIZE: f32 = 100.0;
+pub const MAX_TILE_SIZE: f32 = 320.0;
+pub const DEFAULT_TILE_SIZE: f32 = 150.0;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UiState {
+    pub width: f32,
+    pub height: f32,
+    /// Outer window position; `None` lets the platform place the window
+    pub position: Option<(i32, i32)>,
+    pub album: Option<String>,
+    pub search_mode: SearchMode,
+    pub scroll_offset: f32,
+    pub tile_size: f32,
+    /// Last executed searches, newest first
+    pub recent_searches: Vec<SavedSearch>,
+    /// Searches pinned under a name, listed in the sidebar
+    pub saved_searches: Vec<SavedSearch>,
+    /// Profile of the account opened last; only read from the file of the
+    /// default profile, see `accounts::last_account`
+    pub last_account: Option<String>,
+}
+
+impl Default for UiState {
+    fn default() -> Self {
+        Self {
+            width: DEFAULT_WIDTH,
+            height: DEFAULT_HEIGHT,
+            position: None,
+            album: None,
+            search_mode: SearchMode::Filename,
+            scroll_offset: 0.0,
+            tile_size: DEFAULT_TILE_SIZE,
+            recent_searches: Vec::new(),
+            saved_searches: Vec::new(),
+            last_account: None,
+        }
+    }
+}
+
+fn clamp_extent(value: f32, min: f32, default: f32) -> f32 {
+    if value.is_finite() && value > 0.0 {
+        value.clamp(min, MAX_EXTENT)
+    } else {
+        default
+    }
+}
+
+impl UiState {
+    /// Read the saved state, or defaults if it is missing or unreadable.
+    pub fn load(path: &Path) -> Self {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|s| serde_json::from_str::<UiState>(&s).ok())
+            .unwrap_or_default()
+            .clamped()
+    }
+
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        std::fs::write(path, json)
+    }
+
+    /// Replace values no real window could have.
+    pub fn clamped(mut self) -> Self {
+        self.width = clamp_extent(self.width, MIN_WIDTH, DEFAULT_WIDTH);
+        self.height = clamp_extent(self.height, MIN_HEIGHT, DEFAULT_HEIGHT);
+        let limit = MAX_EXTENT as i32;
+        if let Some((x, y)) = self.position {
+            if x.abs() > limit || y.abs() > limit {
+                self.position = None;
+            }
+        }
+        if !self.scroll_offset.is_finite() || self.scroll_offset < 0.0 {
+            self.scroll_offset = 0.0;
+        }
+        self.tile_size = if self.tile_size.is_finite() {
+            self.tile_size.clamp(MIN_TILE_SIZE, MAX_TILE_SIZE)
+        } else {
+            DEFAULT_TILE_SIZE
+        };
+        self.recent_searches.truncate(RECENT_LIMIT);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_roundtrip() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join(FILE_NAME);
+        let state = UiState {
+            width: 1280.0,
+            height: 900.0,
+            position: Some((40, 60)),
+            album: Some("a1".into()),
+            search_mode: SearchMode::Favoriten,
+            scroll_offset: 512.0,
+            tile_size: 240.0,
+            recent_searches: vec![SavedSearch { query: "beach".into(), ..Default::default() }],
+            saved_searches: vec![SavedSearch {
+                name: Some("Cameras".into()),
+                camera_make: Some("Canon".into()),
+                favorites: true,
+                ..Default::default()
+            }],
+            last_account: Some("work".into()),
+        };
+        state.save(&path).unwrap();
+        assert_eq!(UiState::load(&path), state);
+    }
+
+    #[test]
+    fn test_missing_or_corrupt_file_uses_defaults() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join(FILE_NAME);
+        assert_eq!(UiState::load(&path), UiState::default());
+        std::fs::write(&path, "{ not json").unwrap();
+        assert_eq!(UiState::load(&path), UiState::default());
+    }
+
+    #[test]
+    fn test_absurd_values_are_clamped() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join(FILE_NAME);
+        std::fs::write(
+            &path,
+            r#"{"width": -200, "height": 1e9, "position": [5, 99999999], "scroll_offset": -3, "tile_size": 4000}"#,
+        )
+        .unwrap();
+        let state = UiState::load(&path);
+        assert_eq!(state.width, DEFAULT_WIDTH);
+        assert_eq!(state.height, MAX_EXTENT);
+        assert_eq!(state.position, None);
+        assert_eq!(state.scroll_offset, 0.0);
+        assert_eq!(state.tile_size, MAX_TILE_SIZE);
+        assert_eq!(UiState { width: 10.0, ..state }.clamped().width, MIN_WIDTH);
+    }
+}
@@ -0,0 +1,224 @@
+//! Uploading files dropped onto the window.
+//!
+//! Files are uploaded one after another; large ones use the resumable
+//! protocol. Each file keeps its own status so failures can be retried alone.
+
+use std::path::{Path, PathBuf};
+
+use api_client::{ApiClient, MediaItem};
+use iced::widget::{button, column, container, row, scrollable, text};
+use iced::{Element, Length};
+
+use crate::album_dialogs::AlbumOption;
+use crate::i18n::{tr, trf};
+use crate::style::{self, Palette};
+use crate::{Icon, MaterialSymbol, Message};
+
+/// The upload list scrolls beyond this height.
+const LIST_HEIGHT: f32 = 160.0;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum UploadStatus {
+    Queued,
+    Uploading,
+    /// Uploaded as the media item with this id
+    Done(String),
+    Failed(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct UploadEntry {
+    pub path: PathBuf,
+    pub status: UploadStatus,
+}
+
+impl UploadEntry {
+    pub fn file_name(&self) -> String {
+        self.path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| self.path.display().to_string())
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Uploads {
+    pub entries: Vec<UploadEntry>,
+    /// Album open when the files were dropped, offered as their destination
+    pub album: Option<AlbumOption>,
+}
+
+impl Uploads {
+    /// Queue `path` unless it is already queued, uploading or uploaded.
+    pub fn queue(&mut self, path: PathBuf) -> bool {
+        if let Some(entry) = self.entries.iter_mut().find(|e| e.path == path) {
+            if !matches!(entry.status, UploadStatus::Failed(_)) {
+                return false;
+            }
+            entry.status = UploadStatus::Queued;
+            return true;
+        }
+        self.entries.push(UploadEntry { path, status: UploadStatus::Queued });
+        true
+    }
+
+    /// Mark the next queued file as uploading, unless an upload is running.
+    pub fn next(&mut self) -> Option<PathBuf> {
+        if self.entries.iter().any(|e| e.status == UploadStatus::Uploading) {
+            return None;
+        }
+        let entry = self.entries.iter_mut().find(|e| e.status == UploadStatus::Queued)?;
+        entry.status = UploadStatus::Uploading;
+        Some(entry.path.clone())
+    }
+
+    pub fn finish(&mut self, path: &Path, status: UploadStatus) {
+        if let Some(entry) = self.entries.iter_mut().find(|e| e.path == path) {
+            entry.status = status;
+        }
+    }
+
+    /// Ids of the media items uploaded so far.
+    pub fn uploaded_ids(&self) -> Vec<String> {
+        self.entries
+            .iter()
+            .filter_map(|e| match &e.status {
+                UploadStatus::Done(id) => Some(id.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub fn is_busy(&self) -> bool {
+        self.entries
+            .iter()
+            .any(|e| matches!(e.status, UploadStatus::Queued | UploadStatus::Uploading))
+    }
+}
+
+/// Whether Google Photos accepts the file, judged by its extension.
+pub fn supported(path: &Path) -> bool {
+    api_client::mime_type_for_path(path).is_some()
+}
+
+/// Upload one file and return the media item created for it.
+pub async fn upload_file(client: &ApiClient, path: &Path) -> Result<MediaItem, String> {
+    let file_name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "upload".to_string());
+    let mime = api_client::mime_type_for_path(path).unwrap_or("application/octet-stream");
+    let size = tokio::fs::metadata(path).await.map_err(|e| e.to_string())?.len();
+    if size > api_client::RESUMABLE_UPLOAD_THRESHOLD {
+        client
+            .upload_media_item_resumable(path, &file_name, "", mime)
+            .await
+            .map_err(|e| e.to_string())
+    } else {
+        let data = tokio::fs::read(path).await.map_err(|e| e.to_string())?;
+        client
+            .upload_media_item(&data, &file_name, "")
+            .await
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// Per-file upload status, shown while there are uploads to report.
+pub fn view<'a>(ui: &crate::GooglePiczUI) -> Option<Element<'a, Message>> {
+    let uploads = &ui.uploads;
+    if uploads.entries.is_empty() {
+        return None;
+    }
+    let done = uploads
+        .entries
+        .iter()
+        .filter(|e| matches!(e.status, UploadStatus::Done(_)))
+        .count();
+    let mut header = row![text(trf("upload.progress", &[&done, &uploads.entries.len()]))
+        .size(16)
+        .width(Length::Fill)]
+    .spacing(Palette::SPACING / 2)
+    .align_items(iced::Alignment::Center);
+    if let Some(album) = &uploads.album {
+        let ids = uploads.uploaded_ids();
+        if !ids.is_empty() {
+            header = header.push(
+                button(text(trf("upload.add_to_album", &[&ids.len(), &album.title])))
+                    .style(style::button_primary())
+                    .on_press(Message::AddUploadsToAlbum),
+            );
+        }
+    }
+    header = header.push(
+        button(Icon::new(MaterialSymbol::Close).size(16))
+            .style(style::button_secondary())
+            .on_press_maybe((!uploads.is_busy()).then_some(Message::DismissUploads)),
+    );
+
+    let mut list = column![].spacing(4);
+    for entry in &uploads.entries {
+        let (icon, status) = match &entry.status {
+            UploadStatus::Queued => (MaterialSymbol::Schedule, tr("upload.queued").to_string()),
+            UploadStatus::Uploading => (MaterialSymbol::CloudUpload, tr("upload.uploading").to_string()),
+            UploadStatus::Done(_) => (MaterialSymbol::CheckCircle, tr("upload.done").to_string()),
+            UploadStatus::Failed(err) => (MaterialSymbol::Error, trf("upload.failed", &[err])),
+        };
+        let retry = matches!(entry.status, UploadStatus::Failed(_)).then(|| {
+            button(Icon::new(MaterialSymbol::Refresh).size(16))
+                .style(style::button_secondary())
+                .on_press(Message::RetryUpload(entry.path.clone()))
+        });
+        list = list.push(
+            row![
+                Icon::new(icon).size(16),
+                text(entry.file_name()).size(14).width(Length::FillPortion(2)),
+                text(status).size(14).width(Length::FillPortion(3)),
+            ]
+            .push_maybe(retry)
+            .spacing(Palette::SPACING / 2)
+            .align_items(iced::Alignment::Center),
+        );
+    }
+    Some(
+        container(column![header, scrollable(list).height(Length::Shrink)].spacing(Palette::SPACING / 2))
+            .style(style::card())
+            .padding(Palette::SPACING / 2)
+            .width(Length::Fill)
+            .max_height(LIST_HEIGHT)
+            .into(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_queue_skips_duplicates_and_requeues_failures() {
+        let mut uploads = Uploads::default();
+        assert!(uploads.queue("a.jpg".into()));
+        assert!(uploads.queue("b.jpg".into()));
+        assert!(!uploads.queue("a.jpg".into()));
+
+        assert_eq!(uploads.next(), Some(PathBuf::from("a.jpg")));
+        // One upload at a time
+        assert_eq!(uploads.next(), None);
+        uploads.finish(Path::new("a.jpg"), UploadStatus::Failed("offline".into()));
+        assert_eq!(uploads.next(), Some(PathBuf::from("b.jpg")));
+        uploads.finish(Path::new("b.jpg"), UploadStatus::Done("m1".into()));
+
+        assert!(uploads.queue("a.jpg".into()));
+        assert!(!uploads.queue("b.jpg".into()));
+        assert_eq!(uploads.next(), Some(PathBuf::from("a.jpg")));
+        assert_eq!(uploads.uploaded_ids(), vec!["m1".to_string()]);
+        assert!(uploads.is_busy());
+    }
+
+    #[test]
+    fn test_supported_extensions() {
+        assert!(supported(Path::new("/tmp/IMG_0001.JPG")));
+        assert!(supported(Path::new("clip.mov")));
+        assert!(!supported(Path::new("notes.txt")));
+        assert!(!supported(Path::new("folder")));
+    }
+}
@@ -0,0 +1,184 @@
+//! Videos kept on disk between plays.
+//!
+//! Finished downloads live in `<variant>/<media id>.mp4` below the video
+//! cache directory, under a size cap that evicts the least recently played
+//! video first. Unfinished downloads wait in `partial/` to be resumed and do
+//! not count towards the cap.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use tokio::sync::mpsc;
+
+use crate::disk_cache::{DiskCache, ReadGuard};
+use crate::load_scheduler::CancelToken;
+use crate::video_downloader::{self, VideoDownloadError, VideoDownloader, VideoProgress};
+
+pub const INDEX_FILE: &str = "video_cache.json";
+/// Quality variant requested from the API; `dv` is the playable video
+pub const VARIANT: &str = "dv";
+const EXTENSION: &str = ".mp4";
+const PARTIAL_DIR: &str = "partial";
+
+/// A cached video, protected from eviction while this or a clone is alive.
+#[derive(Debug, Clone)]
+pub struct CachedVideo {
+    path: PathBuf,
+    _guard: Arc<ReadGuard>,
+}
+
+impl CachedVideo {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct VideoCache {
+    root: PathBuf,
+    disk: Arc<DiskCache>,
+    downloader: VideoDownloader,
+}
+
+impl VideoCache {
+    /// Open the cache in `root`, capped at `max_mb` megabytes; 0 keeps everything.
+    pub fn open(root: PathBuf, max_mb: u64) -> Self {
+        let disk = Arc::new(DiskCache::open_folders(&root, &[VARIANT], INDEX_FILE, None));
+        let cache = Self { root, disk, downloader: VideoDownloader::new() };
+        cache.set_limit(max_mb);
+        cache
+    }
+
+    /// Change the cap, evicting right away if the cache is already over it.
+    pub fn set_limit(&self, max_mb: u64) {
+        let max_bytes = (max_mb > 0).then(|| max_mb.saturating_mul(1024 * 1024));
+        self.disk.set_max_bytes(max_bytes);
+    }
+
+    /// Where the finished video of `media_id` is stored.
+    pub fn path(&self, media_id: &str) -> PathBuf {
+        self.root.join(VARIANT).join(format!("{}{}", media_id, EXTENSION))
+    }
+
+    /// The file an unfinished download of `media_id` is written to, e.g. to
+    /// start playing it early.
+    pub fn partial_path(&self, media_id: &str) -> PathBuf {
+        video_downloader::partial_path(&self.root.join(PARTIAL_DIR), media_id, EXTENSION)
+    }
+
+    /// The cached video of `media_id`, if it was downloaded before.
+    pub fn get(&self, media_id: &str) -> Option<CachedVideo> {
+        let path = self.path(media_id);
+        // Taken before the check so the file cannot be evicted in between
+        let guard = self.disk.read(&path);
+        path.exists().then(|| CachedVideo { path, _guard: Arc::new(guard) })
+    }
+
+    /// The cached video of `media_id`, downloading it from `url` first if
+    /// needed. Progress and cancellation work as in
+    /// `VideoDownloader::download_resumable`.
+    pub async fn get_or_download(
+        &self,
+        media_id: &str,
+        url: &str,
+        progress: Option<mpsc::UnboundedSender<VideoProgress>>,
+        cancel: &CancelToken,
+    ) -> Result<CachedVideo, VideoDownloadError> {
+        if let Some(video) = self.get(media_id) {
+            return Ok(video);
+        }
+        let io = |e: std::io::Error| VideoDownloadError::Io(e.to_string());
+        let partial = self.root.join(PARTIAL_DIR);
+        let temp = self
+            .downloader
+            .download_resumable(media_id, url, &partial, EXTENSION, progress, cancel)
+            .await?;
+        let path = self.path(media_id);
+        tokio::fs::create_dir_all(self.root.join(VARIANT)).await.map_err(io)?;
+        let size = tokio::fs::metadata(&temp).await.map_err(io)?.len();
+        temp.persist(&path).map_err(|e| io(e.error))?;
+        let guard = self.disk.read(&path);
+        self.disk.insert(&path, size);
+        Ok(CachedVideo { path, _guard: Arc::new(guard) })
+    }
+
+    /// Bytes and number of videos cached.
+    pub fn usage(&self) -> (u64, usize) {
+        self.disk.usage()
+    }
+
+    /// Delete every cached video not playing right now, and all partial downloads.
+    pub async fn clear(&self) -> Result<(), VideoDownloadError> {
+        let disk = self.disk.clone();
+        let partial = self.root.join(PARTIAL_DIR);
+        tokio::task::spawn_blocking(move || {
+            disk.clear()?;
+            match std::fs::remove_dir_all(partial) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+                _ => Ok(()),
+            }
+        })
+        .await
+        .map_err(|e| VideoDownloadError::Io(e.to_string()))?
+        .map_err(|e| VideoDownloadError::Io(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use httpmock::prelude::*;
+    use tempfile::tempdir;
+
+    const MB: usize = 1024 * 1024;
+
+    #[tokio::test]
+    async fn test_second_play_comes_from_disk() {
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(GET).path("/a");
+            then.status(200).body("video-data");
+        });
+        let dir = tempdir().unwrap();
+        let cache = VideoCache::open(dir.path().to_path_buf(), 0);
+        let url = server.url("/a");
+        let first = cache.get_or_download("a", &url, None, &CancelToken::default()).await.unwrap();
+        assert_eq!(std::fs::read(first.path()).unwrap(), b"video-data");
+        drop(first);
+
+        // Still there after reopening, without another request
+        let cache = VideoCache::open(dir.path().to_path_buf(), 0);
+        let again = cache.get_or_download("a", &url, None, &CancelToken::default()).await.unwrap();
+        assert_eq!(again.path(), cache.path("a"));
+        assert_eq!(cache.usage(), (10, 1));
+        mock.assert_hits(1);
+
+        drop(again);
+        cache.clear().await.unwrap();
+        assert_eq!(cache.usage(), (0, 0));
+        assert!(cache.get("a").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_least_recently_played_video_is_evicted() {
+        let server = MockServer::start();
+        for name in ["a", "b", "c"] {
+            server.mock(|when, then| {
+                when.method(GET).path(format!("/{}", name));
+                then.status(200).body(vec![0u8; MB]);
+            });
+        }
+        let dir = tempdir().unwrap();
+        let cache = VideoCache::open(dir.path().to_path_buf(), 2);
+        let cancel = CancelToken::default();
+        for name in ["a", "b"] {
+            cache.get_or_download(name, &server.url(format!("/{}", name)), None, &cancel).await.unwrap();
+        }
+        // `a` is playing, so `b` makes room for `c`
+        let playing = cache.get("a").unwrap();
+        cache.get_or_download("c", &server.url("/c"), None, &cancel).await.unwrap();
+        assert!(playing.path().exists());
+        assert!(cache.get("b").is_none());
+        assert_eq!(cache.usage(), (2 * MB as u64, 2));
+    }
+}
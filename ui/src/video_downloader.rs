@@ -1,27 +1,93 @@
-use futures_util::StreamExt;
+use crate::load_scheduler::CancelToken;
 use reqwest;
-use std::path::Path;
+use reqwest::header::{CONTENT_RANGE, ETAG, RANGE};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::io::SeekFrom;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 use tempfile::{Builder, TempPath};
 use thiserror::Error;
-use tokio::fs::File;
-use tokio::io::AsyncWriteExt;
+use tokio::fs::{File, OpenOptions};
+use tokio::io::{AsyncSeekExt, AsyncWriteExt};
+use tokio::sync::mpsc;
 
-#[derive(Debug, Error, PartialEq)]
+/// Bytes between two progress reports, so large videos do not flood the UI.
+const PROGRESS_STEP: u64 = 512 * 1024;
+
+/// Requests made by `download_resumable` before giving up for now.
+const RESUME_ATTEMPTS: u32 = 3;
+/// Pause before resuming, multiplied by the attempt number.
+const RESUME_DELAY: Duration = Duration::from_millis(250);
+
+/// What is known about a partly downloaded video, kept next to the data.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+struct PartialMeta {
+    /// Bytes in the partial file that were written completely
+    downloaded: u64,
+    total: Option<u64>,
+    etag: Option<String>,
+}
+
+/// `(start, total)` from a `Content-Range: bytes start-end/total` header.
+fn parse_content_range(value: &str) -> Option<(u64, Option<u64>)> {
+    let (range, total) = value.strip_prefix("bytes ")?.split_once('/')?;
+    let start = range.split_once('-')?.0.trim().parse().ok()?;
+    Some((start, total.trim().parse().ok()))
+}
+
+/// Where `download_resumable` keeps the unfinished download of `media_id`.
+pub fn partial_path(dir: &Path, media_id: &str, extension: &str) -> PathBuf {
+    dir.join(format!("{}{}.part", media_id, extension))
+}
+
+fn etag(resp: &reqwest::Response) -> Option<String> {
+    resp.headers().get(ETAG)?.to_str().ok().map(str::to_string)
+}
+
+#[derive(Debug, Error, Clone, PartialEq)]
 pub enum VideoDownloadError {
     #[error("network error: {0}")]
     Network(String),
     #[error("io error: {0}")]
     Io(String),
+    #[error("download cancelled")]
+    Cancelled,
+}
+
+/// How far a download has got.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct VideoProgress {
+    pub downloaded: u64,
+    /// Size announced by the server, if any
+    pub total: Option<u64>,
+}
+
+impl VideoProgress {
+    /// Share downloaded between 0 and 1, if the size is known.
+    pub fn fraction(&self) -> Option<f32> {
+        self.total
+            .filter(|total| *total > 0)
+            .map(|total| (self.downloaded as f64 / total as f64).min(1.0) as f32)
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct VideoDownloader {
     client: reqwest::Client,
+    /// Where temporary files go; the system default if `None`
+    temp_dir: Option<PathBuf>,
 }
 
 impl VideoDownloader {
     pub fn new() -> Self {
-        Self { client: reqwest::Client::new() }
+        Self { client: reqwest::Client::new(), temp_dir: None }
+    }
+
+    /// Put temporary files in `dir` instead of the system temp directory.
+    pub fn with_temp_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.temp_dir = Some(dir.into());
+        self
     }
 
     /// Download a video incrementally and write to `path`.
@@ -30,19 +96,22 @@ impl VideoDownloader {
         url: &str,
         path: P,
     ) -> Result<(), VideoDownloadError> {
-        let resp = self
+        let mut resp = self
             .client
             .get(url)
             .send()
             .await
+            .and_then(|r| r.error_for_status())
             .map_err(|e| VideoDownloadError::Network(e.to_string()))?;
 
         let mut file = File::create(path.as_ref())
             .await
             .map_err(|e| VideoDownloadError::Io(e.to_string()))?;
-        let mut stream = resp.bytes_stream();
-        while let Some(chunk) = stream.next().await {
-            let bytes = chunk.map_err(|e| VideoDownloadError::Network(e.to_string()))?;
+        while let Some(bytes) = resp
+            .chunk()
+            .await
+            .map_err(|e| VideoDownloadError::Network(e.to_string()))?
+        {
             file
                 .write_all(&bytes)
                 .await
@@ -52,52 +121,231 @@ impl VideoDownloader {
     }
 
     /// Download a video to a temporary file which is deleted when dropped.
+    ///
+    /// The body is written chunk by chunk; progress goes to `progress` as it
+    /// arrives. Cancelling `cancel` aborts the request and deletes the
+    /// partial file.
     pub async fn download_to_tempfile(
         &self,
         url: &str,
         extension: &str,
+        progress: Option<mpsc::UnboundedSender<VideoProgress>>,
+        cancel: &CancelToken,
     ) -> Result<TempPath, VideoDownloadError> {
-        self
-            .download_to_tempfile_with_progress(url, extension, None::<fn(u64, Option<u64>)>)
-            .await
+        let io = |e: std::io::Error| VideoDownloadError::Io(e.to_string());
+        let mut builder = Builder::new();
+        builder.suffix(extension);
+        // Dropped on every early return, which removes the partial file
+        let file = match &self.temp_dir {
+            Some(dir) => builder.tempfile_in(dir),
+            None => builder.tempfile(),
+        }
+        .map_err(io)?;
+        let mut out = File::from_std(file.as_file().try_clone().map_err(io)?);
+
+        let request = self.client.get(url).send();
+        let mut resp = tokio::select! {
+            resp = request => resp
+                .and_then(|r| r.error_for_status())
+                .map_err(|e| VideoDownloadError::Network(e.to_string()))?,
+            _ = cancel.cancelled() => return Err(VideoDownloadError::Cancelled),
+        };
+        let mut state = VideoProgress { downloaded: 0, total: resp.content_length() };
+        let report = |state: VideoProgress| {
+            if let Some(tx) = &progress {
+                // The receiver going away only means nobody is watching
+                let _ = tx.send(state);
+            }
+        };
+        report(state);
+        let mut reported = 0;
+        loop {
+            let chunk = tokio::select! {
+                chunk = resp.chunk() => chunk.map_err(|e| VideoDownloadError::Network(e.to_string()))?,
+                _ = cancel.cancelled() => return Err(VideoDownloadError::Cancelled),
+            };
+            let Some(bytes) = chunk else { break };
+            out.write_all(&bytes).await.map_err(io)?;
+            state.downloaded += bytes.len() as u64;
+            if state.downloaded - reported >= PROGRESS_STEP {
+                reported = state.downloaded;
+                report(state);
+            }
+        }
+        out.flush().await.map_err(io)?;
+        if reported != state.downloaded {
+            report(state);
+        }
+        Ok(file.into_temp_path())
     }
 
-    /// Download a video to a temporary file with optional progress callback.
-    pub async fn download_to_tempfile_with_progress<F>(
+    /// Download a video into `dir`, resuming from what an earlier attempt left.
+    ///
+    /// Unfinished data is kept as `<media_id><extension>.part` together with
+    /// a small JSON record, so a failed download continues with a `Range`
+    /// request the next time, provided `Content-Range` and the ETag show the
+    /// remote file is unchanged. Servers without range support start over.
+    /// The finished file is deleted once the returned path is dropped;
+    /// cancelling deletes the partial file as well.
+    pub async fn download_resumable(
         &self,
+        media_id: &str,
         url: &str,
+        dir: &Path,
         extension: &str,
-        mut progress: Option<F>,
-    ) -> Result<TempPath, VideoDownloadError>
-    where
-        F: FnMut(u64, Option<u64>),
-    {
-        let mut file = Builder::new()
-            .suffix(extension)
-            .tempfile()
-            .map_err(|e| VideoDownloadError::Io(e.to_string()))?;
+        progress: Option<mpsc::UnboundedSender<VideoProgress>>,
+        cancel: &CancelToken,
+    ) -> Result<TempPath, VideoDownloadError> {
+        let io = |e: std::io::Error| VideoDownloadError::Io(e.to_string());
+        tokio::fs::create_dir_all(dir).await.map_err(io)?;
+        let part = partial_path(dir, media_id, extension);
+        let meta_path = dir.join(format!("{}{}.part.json", media_id, extension));
+        let mut meta: PartialMeta = tokio::fs::read(&meta_path)
+            .await
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default();
 
-        let resp = self
-            .client
-            .get(url)
-            .send()
+        let mut attempt = 1;
+        loop {
+            let result = self.fetch_part(url, &part, &mut meta, progress.as_ref(), cancel).await;
+            match result {
+                Ok(()) => break,
+                Err(VideoDownloadError::Cancelled) => {
+                    let _ = tokio::fs::remove_file(&part).await;
+                    let _ = tokio::fs::remove_file(&meta_path).await;
+                    return Err(VideoDownloadError::Cancelled);
+                }
+                Err(e) => {
+                    let json = serde_json::to_vec(&meta).map_err(|e| VideoDownloadError::Io(e.to_string()))?;
+                    tokio::fs::write(&meta_path, json).await.map_err(io)?;
+                    if attempt >= RESUME_ATTEMPTS || !matches!(e, VideoDownloadError::Network(_)) {
+                        return Err(e);
+                    }
+                    tracing::warn!("Video download of {} interrupted at {} bytes: {}", media_id, meta.downloaded, e);
+                    tokio::select! {
+                        _ = tokio::time::sleep(RESUME_DELAY * attempt) => {}
+                        _ = cancel.cancelled() => {}
+                    }
+                    attempt += 1;
+                }
+            }
+        }
+        let _ = tokio::fs::remove_file(&meta_path).await;
+        let done = dir.join(format!("{}{}", media_id, extension));
+        tokio::fs::rename(&part, &done).await.map_err(io)?;
+        Ok(TempPath::from_path(done))
+    }
+
+    /// One request for the rest of `part`, appending to it and keeping
+    /// `meta` up to date.
+    async fn fetch_part(
+        &self,
+        url: &str,
+        part: &Path,
+        meta: &mut PartialMeta,
+        progress: Option<&mpsc::UnboundedSender<VideoProgress>>,
+        cancel: &CancelToken,
+    ) -> Result<(), VideoDownloadError> {
+        let io = |e: std::io::Error| VideoDownloadError::Io(e.to_string());
+        let network = |e: reqwest::Error| VideoDownloadError::Network(e.to_string());
+        let on_disk = tokio::fs::metadata(part).await.map(|m| m.len()).unwrap_or(0);
+        // Bytes past the recorded length may be from an interrupted write
+        let mut offset = meta.downloaded.min(on_disk);
+
+        let mut resp = loop {
+            let mut request = self.client.get(url);
+            if offset > 0 {
+                request = request.header(RANGE, format!("bytes={}-", offset));
+            }
+            let resp = tokio::select! {
+                resp = request.send() => resp.map_err(network)?,
+                _ = cancel.cancelled() => return Err(VideoDownloadError::Cancelled),
+            };
+            if offset > 0 && resp.status() == StatusCode::PARTIAL_CONTENT {
+                let range = resp
+                    .headers()
+                    .get(CONTENT_RANGE)
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(parse_content_range);
+                let same_etag = match (&meta.etag, etag(&resp)) {
+                    (Some(old), Some(new)) => *old == new,
+                    _ => true,
+                };
+                if let Some((start, total)) = range {
+                    let same_size = meta.total.is_none() || total.is_none() || meta.total == total;
+                    if start == offset && same_size && same_etag {
+                        meta.total = total.or(meta.total);
+                        break resp;
+                    }
+                }
+                tracing::info!("Remote video changed since the partial download of {}, starting over", url);
+                offset = 0;
+                continue;
+            }
+            if offset > 0 && resp.status() == StatusCode::RANGE_NOT_SATISFIABLE {
+                offset = 0;
+                continue;
+            }
+            let resp = resp.error_for_status().map_err(network)?;
+            // Either a fresh download or the server ignored the range
+            offset = 0;
+            meta.total = resp.content_length();
+            meta.etag = etag(&resp);
+            break resp;
+        };
+
+        let mut out = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(false)
+            .open(part)
             .await
-            .map_err(|e| VideoDownloadError::Network(e.to_string()))?;
-        let total = resp.content_length();
-        let mut downloaded = 0u64;
-        let mut stream = resp.bytes_stream();
-        while let Some(chunk) = stream.next().await {
-            let bytes = chunk.map_err(|e| VideoDownloadError::Network(e.to_string()))?;
-            downloaded += bytes.len() as u64;
-            file.as_file_mut()
-                .write_all(&bytes)
-                .await
-                .map_err(|e| VideoDownloadError::Io(e.to_string()))?;
-            if let Some(cb) = progress.as_mut() {
-                cb(downloaded, total);
+            .map_err(io)?;
+        out.set_len(offset).await.map_err(io)?;
+        out.seek(SeekFrom::Start(offset)).await.map_err(io)?;
+        meta.downloaded = offset;
+
+        let report = |state: VideoProgress| {
+            if let Some(tx) = progress {
+                let _ = tx.send(state);
+            }
+        };
+        let mut state = VideoProgress { downloaded: offset, total: meta.total };
+        report(state);
+        let mut reported = offset;
+        loop {
+            let chunk = tokio::select! {
+                chunk = resp.chunk() => chunk,
+                _ = cancel.cancelled() => return Err(VideoDownloadError::Cancelled),
+            };
+            let bytes = match chunk {
+                Ok(Some(bytes)) => bytes,
+                Ok(None) => break,
+                Err(e) => {
+                    out.flush().await.map_err(io)?;
+                    return Err(network(e));
+                }
+            };
+            out.write_all(&bytes).await.map_err(io)?;
+            meta.downloaded += bytes.len() as u64;
+            state.downloaded = meta.downloaded;
+            if state.downloaded - reported >= PROGRESS_STEP {
+                reported = state.downloaded;
+                report(state);
             }
         }
-        Ok(file.into_temp_path())
+        out.flush().await.map_err(io)?;
+        if reported != state.downloaded {
+            report(state);
+        }
+        match meta.total {
+            Some(total) if meta.downloaded < total => Err(VideoDownloadError::Network(format!(
+                "connection closed after {} of {} bytes",
+                meta.downloaded, total
+            ))),
+            _ => Ok(()),
+        }
     }
 }
 
@@ -105,7 +353,38 @@ impl VideoDownloader {
 mod tests {
     use super::*;
     use httpmock::prelude::*;
+    use std::sync::{Arc, Mutex};
     use tempfile::tempdir;
+    use tokio::io::AsyncReadExt;
+    use tokio::net::TcpListener;
+
+    /// Serves one raw response per connection, in order, closing each
+    /// connection after writing it, and records the request heads.
+    async fn scripted_server(responses: Vec<&'static str>) -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/video.mp4", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let seen = requests.clone();
+        tokio::spawn(async move {
+            for response in responses {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut head = Vec::new();
+                let mut buf = [0u8; 1024];
+                while !head.ends_with(b"\r\n\r\n") {
+                    let n = socket.read(&mut buf).await.unwrap();
+                    head.extend_from_slice(&buf[..n]);
+                }
+                seen.lock().unwrap().push(String::from_utf8_lossy(&head).to_lowercase());
+                socket.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+        (url, requests)
+    }
+
+    fn write_partial(dir: &Path, data: &str, meta: PartialMeta) {
+        std::fs::write(dir.join("id.mp4.part"), data).unwrap();
+        std::fs::write(dir.join("id.mp4.part.json"), serde_json::to_vec(&meta).unwrap()).unwrap();
+    }
 
     #[tokio::test]
     async fn test_download_progressive() {
@@ -134,7 +413,7 @@ mod tests {
         });
         let dl = VideoDownloader::new();
         let temp = dl
-            .download_to_tempfile(&format!("{}/video.mp4", server.url("")), ".mp4")
+            .download_to_tempfile(&format!("{}/video.mp4", server.url("")), ".mp4", None, &CancelToken::default())
             .await
             .unwrap();
         let content = tokio::fs::read(&temp).await.unwrap();
@@ -147,23 +426,134 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_download_progress_callback() {
+    async fn test_download_reports_progress() {
         let server = MockServer::start();
         let mock = server.mock(|when, then| {
             when.method(GET).path("/video.mp4");
             then.status(200).body("video-data");
         });
         let dl = VideoDownloader::new();
-        let mut progress_called = 0;
-        let _ = dl
-            .download_to_tempfile_with_progress(
-                &format!("{}/video.mp4", server.url("")),
-                ".mp4",
-                Some(|_d, _t| progress_called += 1),
-            )
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let _temp = dl
+            .download_to_tempfile(&format!("{}/video.mp4", server.url("")), ".mp4", Some(tx), &CancelToken::default())
             .await
             .unwrap();
-        assert!(progress_called > 0);
+        let mut updates = Vec::new();
+        while let Some(p) = rx.recv().await {
+            updates.push(p);
+        }
+        assert_eq!(updates.first(), Some(&VideoProgress { downloaded: 0, total: Some(10) }));
+        assert_eq!(updates.last(), Some(&VideoProgress { downloaded: 10, total: Some(10) }));
+        assert_eq!(updates.last().unwrap().fraction(), Some(1.0));
         mock.assert();
     }
+
+    #[tokio::test]
+    async fn test_cancel_removes_partial_file() {
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(GET).path("/video.mp4");
+            then.status(200).body("video-data").delay(Duration::from_secs(5));
+        });
+        let dir = tempdir().unwrap();
+        let dl = VideoDownloader::new().with_temp_dir(dir.path());
+        let cancel = CancelToken::default();
+        let url = format!("{}/video.mp4", server.url(""));
+        let download = dl.download_to_tempfile(&url, ".mp4", None, &cancel);
+        let abort = async {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            // The partial file exists while the request is running
+            assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+            cancel.cancel();
+        };
+        let (result, ()) = tokio::join!(download, abort);
+        assert_eq!(result.unwrap_err(), VideoDownloadError::Cancelled);
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_resume_after_dropped_connection() {
+        let (url, requests) = scripted_server(vec![
+            // Promises ten bytes, sends five, hangs up
+            "HTTP/1.1 200 OK\r\nContent-Length: 10\r\nETag: \"v1\"\r\n\r\nvideo",
+            "HTTP/1.1 206 Partial Content\r\nContent-Length: 5\r\nContent-Range: bytes 5-9/10\r\nETag: \"v1\"\r\n\r\n-data",
+        ])
+        .await;
+        let dir = tempdir().unwrap();
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let path = VideoDownloader::new()
+            .download_resumable("id", &url, dir.path(), ".mp4", Some(tx), &CancelToken::default())
+            .await
+            .unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), b"video-data");
+        let requests = requests.lock().unwrap();
+        assert!(!requests[0].contains("range:"));
+        assert!(requests[1].contains("range: bytes=5-"));
+        // Progress continues from the resumed offset
+        let mut last = VideoProgress::default();
+        while let Ok(p) = rx.try_recv() {
+            last = p;
+        }
+        assert_eq!(last, VideoProgress { downloaded: 10, total: Some(10) });
+        assert!(!dir.path().join("id.mp4.part").exists());
+        assert!(!dir.path().join("id.mp4.part.json").exists());
+        drop(path);
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_failed_download_keeps_partial_file() {
+        let truncated = "HTTP/1.1 200 OK\r\nContent-Length: 10\r\nETag: \"v1\"\r\n\r\nvideo";
+        let (url, _) = scripted_server(vec![truncated; RESUME_ATTEMPTS as usize]).await;
+        let dir = tempdir().unwrap();
+        let err = VideoDownloader::new()
+            .download_resumable("id", &url, dir.path(), ".mp4", None, &CancelToken::default())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, VideoDownloadError::Network(_)));
+        assert_eq!(std::fs::read(dir.path().join("id.mp4.part")).unwrap(), b"video");
+        let meta: PartialMeta =
+            serde_json::from_slice(&std::fs::read(dir.path().join("id.mp4.part.json")).unwrap()).unwrap();
+        assert_eq!(meta, PartialMeta { downloaded: 5, total: Some(10), etag: Some("\"v1\"".into()) });
+    }
+
+    #[tokio::test]
+    async fn test_restart_when_range_is_ignored() {
+        let (url, requests) = scripted_server(vec![
+            "HTTP/1.1 200 OK\r\nContent-Length: 10\r\n\r\nvideo-data",
+        ])
+        .await;
+        let dir = tempdir().unwrap();
+        write_partial(dir.path(), "video", PartialMeta { downloaded: 5, total: Some(10), etag: None });
+        let path = VideoDownloader::new()
+            .download_resumable("id", &url, dir.path(), ".mp4", None, &CancelToken::default())
+            .await
+            .unwrap();
+        assert!(requests.lock().unwrap()[0].contains("range: bytes=5-"));
+        assert_eq!(std::fs::read(&path).unwrap(), b"video-data");
+    }
+
+    #[tokio::test]
+    async fn test_restart_when_remote_file_changed() {
+        let (url, requests) = scripted_server(vec![
+            "HTTP/1.1 206 Partial Content\r\nContent-Length: 5\r\nContent-Range: bytes 5-9/10\r\nETag: \"v2\"\r\n\r\n-data",
+            "HTTP/1.1 200 OK\r\nContent-Length: 10\r\nETag: \"v2\"\r\n\r\nfresh-data",
+        ])
+        .await;
+        let dir = tempdir().unwrap();
+        write_partial(dir.path(), "video", PartialMeta { downloaded: 5, total: Some(10), etag: Some("\"v1\"".into()) });
+        let path = VideoDownloader::new()
+            .download_resumable("id", &url, dir.path(), ".mp4", None, &CancelToken::default())
+            .await
+            .unwrap();
+        assert!(!requests.lock().unwrap()[1].contains("range:"));
+        assert_eq!(std::fs::read(&path).unwrap(), b"fresh-data");
+    }
+
+    #[test]
+    fn test_parse_content_range() {
+        assert_eq!(parse_content_range("bytes 5-9/10"), Some((5, Some(10))));
+        assert_eq!(parse_content_range("bytes 5-9/*"), Some((5, None)));
+        assert_eq!(parse_content_range("items 5-9/10"), None);
+    }
 }
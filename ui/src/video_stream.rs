@@ -0,0 +1,118 @@
+//! Playing a video while it is still downloading.
+//!
+//! Playback starts from the partial file once `START_BYTES` have arrived.
+//! Bytes map to playback time linearly, which is close enough for the
+//! constant bitrate videos the API serves. When playback gets within
+//! `STALL_MARGIN_SECS` of the downloaded end it pauses, and it resumes once
+//! `RESUME_SECS` more are buffered. Videos smaller than `START_BYTES` just
+//! play when they are complete.
+
+use crate::video_downloader::VideoProgress;
+
+/// Bytes downloaded before playback may start.
+pub const START_BYTES: u64 = 8 * 1024 * 1024;
+/// Pause this far ahead of the downloaded end, so the player never reads past it.
+const STALL_MARGIN_SECS: f64 = 2.0;
+/// Buffered playback needed before a paused stream continues.
+const RESUME_SECS: f64 = 5.0;
+
+/// What the player should do after a buffer update.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BufferAction {
+    Pause,
+    Resume,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StreamBuffer {
+    pub progress: VideoProgress,
+    /// Paused by the buffer rather than by the user
+    stalled: bool,
+}
+
+impl StreamBuffer {
+    /// Whether enough has arrived to play from the partial file.
+    pub fn can_start(&self) -> bool {
+        self.progress.total.is_some_and(|total| total > START_BYTES) && self.progress.downloaded >= START_BYTES
+    }
+
+    pub fn is_stalled(&self) -> bool {
+        self.stalled
+    }
+
+    /// Seconds from the start of a `duration` long video that can be played.
+    pub fn buffered_secs(&self, duration: f64) -> f64 {
+        self.progress.fraction().map_or(0.0, |f| duration * f64::from(f))
+    }
+
+    /// `position` limited to the part that has been downloaded.
+    pub fn clamp_seek(&self, position: f64, duration: f64) -> f64 {
+        position.min((self.buffered_secs(duration) - STALL_MARGIN_SECS).max(0.0))
+    }
+
+    /// Pause when playback at `position` catches up with the download, and
+    /// resume a stalled stream once enough is buffered again.
+    pub fn update(&mut self, position: f64, duration: f64) -> Option<BufferAction> {
+        if duration <= 0.0 {
+            return None;
+        }
+        let buffered = self.buffered_secs(duration);
+        let complete = self.progress.fraction() == Some(1.0);
+        if self.stalled {
+            if complete || buffered >= (position + RESUME_SECS).min(duration) {
+                self.stalled = false;
+                return Some(BufferAction::Resume);
+            }
+        } else if !complete && position >= buffered - STALL_MARGIN_SECS {
+            self.stalled = true;
+            return Some(BufferAction::Pause);
+        }
+        None
+    }
+
+    /// The player ran out of data before the buffer noticed.
+    pub fn stall(&mut self) {
+        self.stalled = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MB: u64 = 1024 * 1024;
+
+    fn buffer(downloaded: u64, total: u64) -> StreamBuffer {
+        StreamBuffer { progress: VideoProgress { downloaded, total: Some(total) }, stalled: false }
+    }
+
+    #[test]
+    fn test_starts_after_threshold_for_large_videos_only() {
+        assert!(!buffer(4 * MB, 100 * MB).can_start());
+        assert!(buffer(START_BYTES, 100 * MB).can_start());
+        // Small clips wait for the whole file
+        assert!(!buffer(START_BYTES, START_BYTES).can_start());
+        let unknown = StreamBuffer { progress: VideoProgress { downloaded: 50 * MB, total: None }, stalled: false };
+        assert!(!unknown.can_start());
+    }
+
+    #[test]
+    fn test_pauses_at_download_end_and_resumes() {
+        // A quarter of a 100 second video
+        let mut b = buffer(25 * MB, 100 * MB);
+        assert_eq!(b.buffered_secs(100.0), 25.0);
+        assert_eq!(b.clamp_seek(80.0, 100.0), 23.0);
+        assert_eq!(b.update(10.0, 100.0), None);
+        assert_eq!(b.update(23.5, 100.0), Some(BufferAction::Pause));
+        assert!(b.is_stalled());
+
+        b.progress.downloaded = 27 * MB;
+        assert_eq!(b.update(23.5, 100.0), None);
+        b.progress.downloaded = 29 * MB;
+        assert_eq!(b.update(23.5, 100.0), Some(BufferAction::Resume));
+
+        // Once complete nothing holds playback back
+        b.progress.downloaded = 100 * MB;
+        assert_eq!(b.update(99.5, 100.0), None);
+    }
+}
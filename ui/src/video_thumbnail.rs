@@ -0,0 +1,87 @@
+//! Poster frame and duration of a downloaded video.
+//!
+//! The API neither reports how long a video runs nor offers a frame from
+//! its middle, so both are taken from the local file the first time a
+//! video is played.
+
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use gstreamer_iced::reexport::url;
+use std::path::Path;
+
+/// Edge length of the square grid thumbnails.
+const THUMBNAIL_SIZE: u32 = 150;
+/// Longest wait for the pipeline to preroll or settle after the seek.
+const STATE_TIMEOUT: gst::ClockTime = gst::ClockTime::from_seconds(10);
+
+#[derive(Debug)]
+pub struct VideoFrame {
+    /// JPEG thumbnail of the frame at the middle of the video
+    pub jpeg: Vec<u8>,
+    pub duration_ms: u64,
+}
+
+/// Decode the frame halfway through the video at `path`. Blocks, so run it
+/// on a blocking thread.
+pub fn grab_midpoint(path: &Path) -> Result<VideoFrame, String> {
+    let uri = url::Url::from_file_path(path).map_err(|_| format!("invalid video path {}", path.display()))?;
+    let playbin = gst::ElementFactory::make("playbin")
+        .property("uri", uri.as_str())
+        .build()
+        .map_err(|e| e.to_string())?;
+    // Nothing is shown or played; the pipeline only has to decode
+    for sink in ["video-sink", "audio-sink"] {
+        let fake = gst::ElementFactory::make("fakesink").build().map_err(|e| e.to_string())?;
+        playbin.set_property(sink, &fake);
+    }
+    let frame = grab(&playbin);
+    let _ = playbin.set_state(gst::State::Null);
+    frame
+}
+
+fn grab(playbin: &gst::Element) -> Result<VideoFrame, String> {
+    playbin.set_state(gst::State::Paused).map_err(|e| e.to_string())?;
+    wait_async_done(playbin)?;
+    let duration = playbin
+        .query_duration::<gst::ClockTime>()
+        .ok_or_else(|| "unknown video duration".to_string())?;
+    playbin
+        .seek_simple(gst::SeekFlags::FLUSH | gst::SeekFlags::KEY_UNIT, duration / 2)
+        .map_err(|e| e.to_string())?;
+    wait_async_done(playbin)?;
+    let caps = gst::Caps::builder("image/jpeg").build();
+    let sample = playbin
+        .emit_by_name::<Option<gst::Sample>>("convert-sample", &[&caps])
+        .ok_or_else(|| "no video frame".to_string())?;
+    let buffer = sample.buffer().ok_or_else(|| "empty video frame".to_string())?;
+    let map = buffer.map_readable().map_err(|e| e.to_string())?;
+    Ok(VideoFrame {
+        jpeg: square_thumbnail(map.as_slice())?,
+        duration_ms: duration.mseconds(),
+    })
+}
+
+fn wait_async_done(playbin: &gst::Element) -> Result<(), String> {
+    let bus = playbin.bus().ok_or_else(|| "pipeline has no bus".to_string())?;
+    match bus.timed_pop_filtered(
+        STATE_TIMEOUT,
+        &[gst::MessageType::AsyncDone, gst::MessageType::Error],
+    ) {
+        Some(msg) => match msg.view() {
+            gst::MessageView::Error(err) => Err(err.error().to_string()),
+            _ => Ok(()),
+        },
+        None => Err("timed out decoding video".into()),
+    }
+}
+
+/// Crop to a centred square like the `-c` thumbnails from the API.
+fn square_thumbnail(jpeg: &[u8]) -> Result<Vec<u8>, String> {
+    let frame = image::load_from_memory(jpeg).map_err(|e| e.to_string())?;
+    let thumb = frame.resize_to_fill(THUMBNAIL_SIZE, THUMBNAIL_SIZE, image::imageops::FilterType::Triangle);
+    let mut out = std::io::Cursor::new(Vec::new());
+    thumb
+        .write_to(&mut out, image::ImageOutputFormat::Jpeg(85))
+        .map_err(|e| e.to_string())?;
+    Ok(out.into_inner())
+}
@@ -0,0 +1,63 @@
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use ui::{translation, Language};
+
+/// Keys passed as literals to `tr(...)` or `trf(...)` anywhere in `src/`.
+fn referenced_keys() -> BTreeSet<String> {
+    let src = Path::new(env!("CARGO_MANIFEST_DIR")).join("src");
+    let mut keys = BTreeSet::new();
+    for entry in std::fs::read_dir(src).unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().and_then(|e| e.to_str()) != Some("rs") {
+            continue;
+        }
+        let code = std::fs::read_to_string(&path).unwrap();
+        for call in ["tr(\"", "trf(\""] {
+            for (idx, _) in code.match_indices(call) {
+                let boundary = code[..idx]
+                    .chars()
+                    .next_back()
+                    .map_or(true, |c| !c.is_alphanumeric() && c != '_');
+                if !boundary {
+                    continue;
+                }
+                let rest = &code[idx + call.len()..];
+                let end = rest.find('"').unwrap();
+                keys.insert(rest[..end].to_string());
+            }
+        }
+    }
+    keys
+}
+
+#[test]
+fn test_referenced_keys_exist_in_all_bundles() {
+    let keys = referenced_keys();
+    assert!(keys.contains("settings.title"));
+    for key in &keys {
+        for lang in Language::ALL {
+            assert!(translation(lang, key).is_some(), "{} missing in {:?}", key, lang);
+        }
+    }
+}
+
+#[test]
+fn test_bundles_share_keys_and_placeholders() {
+    for lang in Language::ALL {
+        for key in ui::i18n_keys(Language::En) {
+            let en = translation(Language::En, key).unwrap();
+            let other = translation(lang, key).unwrap_or_else(|| panic!("{} missing in {:?}", key, lang));
+            assert_eq!(en.matches("{}").count(), other.matches("{}").count(), "{}", key);
+        }
+        assert_eq!(ui::i18n_keys(lang).count(), ui::i18n_keys(Language::En).count());
+    }
+}
+
+#[test]
+fn test_language_codes_roundtrip() {
+    for lang in Language::ALL {
+        assert_eq!(Language::from_code(lang.code()), lang);
+    }
+    assert_eq!(Language::from_code("xx"), Language::En);
+}
@@ -1,4 +1,4 @@
-use ui::{ImageLoader, ImageLoaderError};
+use ui::{CancelToken, FaceCropSummary, ImageKey, ImageLoader, ImageLoaderError, PrefetchProgress, PrefetchSummary, Priority, Variant};
 use httpmock::prelude::*;
 use tempfile::tempdir;
 use std::time::Duration;
@@ -106,3 +106,370 @@ async fn test_network_error() {
         other => panic!("expected network error, got {:?}", other),
     }
 }
+
+#[tokio::test]
+async fn test_face_crop_from_cached_original() {
+    let dir = tempdir().unwrap();
+    std::fs::create_dir_all(dir.path().join("full")).unwrap();
+    image::RgbImage::from_pixel(100, 80, image::Rgb([200, 100, 50]))
+        .save(dir.path().join("full").join("1.jpg"))
+        .unwrap();
+    // The original is cached, so nothing is downloaded
+    let loader = ImageLoader::new(dir.path().to_path_buf(), 4);
+
+    loader.load_face_crop("1", "http://127.0.0.1:9/unused", [80, 60, 50, 50]).await.unwrap();
+    let crop = image::open(dir.path().join("faces").join("1_80_60_50_50.png")).unwrap();
+    assert_eq!(crop.width(), crop.height());
+
+    let err = loader
+        .load_face_crop("1", "http://127.0.0.1:9/unused", [120, 0, 10, 10])
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err, ImageLoaderError::Decode(_)));
+}
+
+#[tokio::test]
+async fn test_full_image_file_downloads_when_missing_on_disk() {
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method(GET).path("/img.jpg=d");
+        then.status(200).body("full");
+    });
+    let dir = tempdir().unwrap();
+    let loader = ImageLoader::new(dir.path().to_path_buf(), 4);
+    let url = format!("{}/img.jpg", server.url(""));
+
+    loader.load_full_image("1", &url).await.unwrap();
+    let (path, _guard) = loader.full_image_file("1", &url).await.unwrap();
+    assert_eq!(path, dir.path().join("full").join("1.jpg"));
+    mock.assert_hits(1);
+
+    // Still in memory, but gone from disk
+    std::fs::remove_file(&path).unwrap();
+    let (path, _guard) = loader.full_image_file("1", &url).await.unwrap();
+    assert_eq!(std::fs::read(path).unwrap(), b"full");
+    mock.assert_hits(2);
+}
+
+#[tokio::test]
+async fn test_cache_limit_evicts_oldest_and_clear_empties() {
+    let server = MockServer::start();
+    server.mock(|when, then| {
+        when.method(GET).path_contains("=w150-h150-c");
+        then.status(200).body(vec![0u8; 600 * 1024]);
+    });
+
+    let dir = tempdir().unwrap();
+    let loader = ImageLoader::new(dir.path().to_path_buf(), 4).with_cache_limit(1);
+    for id in ["1", "2"] {
+        let url = format!("{}/{}.jpg", server.url(""), id);
+        loader.load_thumbnail(id, &url).await.unwrap();
+    }
+    // Two thumbnails exceed 1 MB, so the first one made room
+    assert!(!dir.path().join("thumbnails").join("1.jpg").exists());
+    assert!(dir.path().join("thumbnails").join("2.jpg").exists());
+    assert_eq!(loader.cache_usage(), (600 * 1024, 1));
+
+    loader.clear_cache().await.unwrap();
+    assert_eq!(loader.cache_usage(), (0, 0));
+    assert!(!dir.path().join("thumbnails").join("2.jpg").exists());
+}
+
+#[tokio::test]
+async fn test_concurrent_requests_share_one_download() {
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method(GET).path("/img.jpg=w150-h150-c");
+        then.status(200).body("thumb").delay(Duration::from_millis(100));
+    });
+
+    let dir = tempdir().unwrap();
+    let loader = ImageLoader::new(dir.path().to_path_buf(), 4);
+    let url = format!("{}/img.jpg", server.url(""));
+    let loads = (0..50).map(|_| loader.load_thumbnail("1", &url));
+    let results = futures::future::join_all(loads).await;
+    assert!(results.iter().all(Result::is_ok));
+    mock.assert_hits(1);
+
+    // Served from memory, even once the file is gone
+    std::fs::remove_file(dir.path().join("thumbnails").join("1.jpg")).unwrap();
+    loader.load_thumbnail("1", &url).await.unwrap();
+    assert!(loader.get_cached_thumbnail("1").is_some());
+    mock.assert_hits(1);
+}
+
+fn item_with_url(id: &str, base_url: String) -> api_client::MediaItem {
+    api_client::MediaItem {
+        id: id.into(),
+        description: None,
+        product_url: String::new(),
+        base_url,
+        mime_type: "image/jpeg".into(),
+        media_metadata: api_client::MediaMetadata {
+            creation_time: "2023-01-01T00:00:00Z".into(),
+            width: "1".into(),
+            height: "1".into(),
+            video: None,
+        },
+        filename: format!("{}.jpg", id),
+        is_favorite: false,
+    }
+}
+
+#[tokio::test]
+async fn test_export_face_crops_by_person() {
+    let server = MockServer::start();
+    server.mock(|when, then| {
+        when.method(GET).path("/gone=d");
+        then.status(404);
+    });
+    let dir = tempdir().unwrap();
+    std::fs::create_dir_all(dir.path().join("full")).unwrap();
+    image::RgbImage::from_pixel(100, 80, image::Rgb([200, 100, 50]))
+        .save(dir.path().join("full").join("1.jpg"))
+        .unwrap();
+    let cache = cache::CacheManager::new(&dir.path().join("cache.sqlite")).unwrap();
+    cache.insert_media_item(&item_with_url("1", "http://127.0.0.1:9/unused".into())).unwrap();
+    cache.insert_media_item(&item_with_url("2", server.url("/gone"))).unwrap();
+    let face = |bbox, name: Option<&str>, ignored| cache::FaceData { bbox, name: name.map(Into::into), ignored, confidence: None };
+    let faces = vec![
+        face([10, 10, 20, 20], Some("Anna"), false),
+        face([80, 50, 20, 20], Some("Bob"), false),
+        face([0, 0, 5, 5], Some("Anna"), true),
+        face([40, 0, 10, 10], None, false),
+    ];
+    cache.insert_faces("1", &serde_json::to_string(&faces).unwrap()).unwrap();
+    let anna = vec![face([0, 0, 10, 10], Some("Anna"), false)];
+    cache.insert_faces("2", &serde_json::to_string(&anna).unwrap()).unwrap();
+    let loader = ImageLoader::new(dir.path().to_path_buf(), 4);
+
+    let out = dir.path().join("anna");
+    let (tx, mut rx) = tokio::sync::mpsc::channel(8);
+    let summary = loader.export_face_crops(&cache, &out, Some("Anna"), Some(tx)).await.unwrap();
+    assert_eq!(summary, FaceCropSummary { exported: 1, skipped: 1 });
+    let mut last = None;
+    while let Some(p) = rx.recv().await {
+        last = Some(p);
+    }
+    assert_eq!(last, Some(PrefetchProgress { done: 2, total: 2 }));
+    // 20% margin on each side
+    let crop = image::open(out.join("Anna").join("1_0.jpg")).unwrap();
+    assert_eq!((crop.width(), crop.height()), (28, 28));
+    assert!(!out.join("Anna").join("1_2.jpg").exists());
+    assert!(!out.join("Bob").exists());
+
+    let out = dir.path().join("all");
+    let summary = loader.export_face_crops(&cache, &out, None, None).await.unwrap();
+    assert_eq!(summary, FaceCropSummary { exported: 3, skipped: 1 });
+    // Clamped to the image
+    let crop = image::open(out.join("Bob").join("1_1.jpg")).unwrap();
+    assert_eq!((crop.width(), crop.height()), (24, 28));
+    assert!(out.join("unnamed").join("1_3.jpg").exists());
+}
+
+#[tokio::test]
+async fn test_expired_url_is_refreshed_once() {
+    let server = MockServer::start();
+    let expired = server.mock(|when, then| {
+        when.method(GET).path("/old=w150-h150-c");
+        then.status(403);
+    });
+    let fresh = server.mock(|when, then| {
+        when.method(GET).path("/new=w150-h150-c");
+        then.status(200).body("thumb");
+    });
+    let still_expired = server.mock(|when, then| {
+        when.method(GET).path("/stale=w150-h150-c");
+        then.status(403);
+    });
+
+    let refreshes = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let counter = refreshes.clone();
+    let new_url = server.url("/new");
+    let stale_url = server.url("/stale");
+    let refresher = ui::UrlRefresher::new(move |ids: Vec<String>| {
+        counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        let items = ids
+            .iter()
+            .map(|id| item_with_url(id, if id == "1" { new_url.clone() } else { stale_url.clone() }))
+            .collect();
+        async move { Ok(items) }
+    });
+    let dir = tempdir().unwrap();
+    let loader = ImageLoader::new(dir.path().to_path_buf(), 4).with_url_refresher(refresher);
+
+    loader.load_thumbnail("1", &server.url("/old")).await.unwrap();
+    expired.assert_hits(1);
+    fresh.assert_hits(1);
+    assert_eq!(refreshes.load(std::sync::atomic::Ordering::SeqCst), 1);
+
+    // The refreshed URL expiring as well is reported, not retried in a loop
+    let err = loader.load_thumbnail("2", &server.url("/old")).await.unwrap_err();
+    assert_eq!(err, ImageLoaderError::Expired);
+    let err = loader.load_thumbnail("2", &server.url("/old")).await.unwrap_err();
+    assert_eq!(err, ImageLoaderError::Expired);
+    still_expired.assert_hits(2);
+    assert_eq!(refreshes.load(std::sync::atomic::Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_cancelled_request_is_dropped_while_waiting() {
+    let server = MockServer::start();
+    let slow = server.mock(|when, then| {
+        when.method(GET).path("/slow.jpg=w150-h150-c");
+        then.status(200).body("slow").delay(Duration::from_millis(200));
+    });
+    let skipped = server.mock(|when, then| {
+        when.method(GET).path("/far.jpg=w150-h150-c");
+        then.status(200).body("far");
+    });
+
+    let dir = tempdir().unwrap();
+    // One worker, busy with the slow download
+    let loader = ImageLoader::new(dir.path().to_path_buf(), 1);
+    let (slow_url, far_url) = (server.url("/slow.jpg"), server.url("/far.jpg"));
+    let busy = loader.load_thumbnail("slow", &slow_url);
+    let token = CancelToken::default();
+    let far = async {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let load = loader.load_thumbnail_with("far", &far_url, 150, false, Priority::Prefetch, &token);
+        let cancel = async {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            token.cancel();
+        };
+        tokio::join!(load, cancel).0
+    };
+    let (busy, far) = tokio::join!(busy, far);
+    busy.unwrap();
+    assert_eq!(far.unwrap_err(), ImageLoaderError::Cancelled);
+    slow.assert_hits(1);
+    skipped.assert_hits(0);
+}
+
+#[tokio::test]
+async fn test_webp_thumbnail_is_cached_as_jpeg() {
+    let server = MockServer::start();
+    server.mock(|when, then| {
+        when.method(GET).path("/webp=w150-h150-c");
+        then.status(200).body(include_bytes!("fixtures/tiny.webp"));
+    });
+    let dir = tempdir().unwrap();
+    let loader = ImageLoader::new(dir.path().to_path_buf(), 4);
+    loader.load_thumbnail("w", &server.url("/webp")).await.unwrap();
+    let cached = std::fs::read(dir.path().join("thumbnails").join("w.jpg")).unwrap();
+    assert_eq!(image::guess_format(&cached).unwrap(), image::ImageFormat::Jpeg);
+}
+
+#[tokio::test]
+async fn test_undecodable_heic_falls_back_to_transcoded_jpeg() {
+    let server = MockServer::start();
+    let original = server.mock(|when, then| {
+        when.method(GET).path("/heic=d");
+        then.status(200).body(include_bytes!("fixtures/ftyp_only.heic"));
+    });
+    let mut jpeg = std::io::Cursor::new(Vec::new());
+    image::RgbImage::new(4, 3)
+        .write_to(&mut jpeg, image::ImageOutputFormat::Jpeg(90))
+        .unwrap();
+    let transcoded = server.mock(|when, then| {
+        when.method(GET).path("/heic=w4096-h4096");
+        then.status(200).body(jpeg.into_inner());
+    });
+    let dir = tempdir().unwrap();
+    let loader = ImageLoader::new(dir.path().to_path_buf(), 4);
+    loader.load_full_image("h", &server.url("/heic")).await.unwrap();
+    original.assert();
+    transcoded.assert();
+    let cached = image::open(dir.path().join("full").join("h.jpg")).unwrap();
+    assert_eq!((cached.width(), cached.height()), (4, 3));
+}
+
+#[tokio::test]
+async fn test_server_errors_are_retried_but_not_found_is_not() {
+    let server = MockServer::start();
+    let unavailable = server.mock(|when, then| {
+        when.method(GET).path("/busy=w150-h150-c");
+        then.status(503);
+    });
+    let missing = server.mock(|when, then| {
+        when.method(GET).path("/gone=w150-h150-c");
+        then.status(404);
+    });
+    let dir = tempdir().unwrap();
+    let loader = ImageLoader::new(dir.path().to_path_buf(), 4);
+
+    let err = loader.load_thumbnail("1", &server.url("/busy")).await.unwrap_err();
+    assert_eq!(err, ImageLoaderError::Status(503));
+    assert!(err.is_transient() && !err.is_permanent());
+    unavailable.assert_hits(3);
+
+    let err = loader.load_thumbnail("2", &server.url("/gone")).await.unwrap_err();
+    assert!(err.is_permanent());
+    missing.assert_hits(1);
+}
+
+#[tokio::test]
+async fn test_memory_hits_and_invalidate() {
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method(GET).path("/img=w150-h150-c");
+        then.status(200).body("thumb");
+    });
+    let dir = tempdir().unwrap();
+    let loader = ImageLoader::new(dir.path().to_path_buf(), 4);
+    let url = server.url("/img");
+    let handles = loader.handles();
+
+    loader.load_thumbnail("1", &url).await.unwrap();
+    loader.load_thumbnail("1", &url).await.unwrap();
+    mock.assert_hits(1);
+    let stats = handles.stats();
+    assert_eq!((stats.hits, stats.misses, stats.entries), (1, 1, 1));
+    assert!(handles.contains(&ImageKey::thumbnail("1", 150)));
+
+    // Gone from memory and disk, so the next load downloads it again
+    loader.invalidate("1").await.unwrap();
+    assert!(!handles.contains(&ImageKey::thumbnail("1", 150)));
+    assert!(!dir.path().join("thumbnails").join("1.jpg").exists());
+    assert_eq!(loader.cache_usage(), (0, 0));
+    loader.load_thumbnail("1", &url).await.unwrap();
+    mock.assert_hits(2);
+}
+
+#[tokio::test]
+async fn test_prefetch_skips_cached_and_reports_progress() {
+    let server = MockServer::start();
+    let fresh = server.mock(|when, then| {
+        when.method(GET).path("/new=w150-h150-c");
+        then.status(200).body("thumb");
+    });
+    server.mock(|when, then| {
+        when.method(GET).path("/gone=w150-h150-c");
+        then.status(404);
+    });
+    let dir = tempdir().unwrap();
+    std::fs::create_dir_all(dir.path().join("thumbnails")).unwrap();
+    std::fs::write(dir.path().join("thumbnails").join("old.jpg"), b"cached").unwrap();
+    let loader = ImageLoader::new(dir.path().to_path_buf(), 2);
+
+    let items = vec![
+        ("old".to_string(), server.url("/old")),
+        ("new".to_string(), server.url("/new")),
+        ("gone".to_string(), server.url("/gone")),
+    ];
+    let (tx, mut rx) = tokio::sync::mpsc::channel(8);
+    let summary = loader.prefetch(items, Variant::Thumbnail(150), 2, Some(tx)).await;
+    assert_eq!(summary, PrefetchSummary { downloaded: 1, skipped: 1, failed: 1 });
+    fresh.assert();
+    assert!(dir.path().join("thumbnails").join("new.jpg").exists());
+
+    let mut updates = Vec::new();
+    while let Some(update) = rx.recv().await {
+        updates.push(update);
+    }
+    assert_eq!(updates.len(), 3);
+    assert_eq!(updates.last(), Some(&PrefetchProgress { done: 3, total: 3 }));
+    // Warming the disk leaves memory to what is on screen
+    assert!(!loader.handles().contains(&ImageKey::thumbnail("new", 150)));
+}
@@ -0,0 +1,46 @@
+use iced::{Point, Rectangle, Size, Vector};
+use ui::Zoom;
+
+fn view() -> Rectangle {
+    Rectangle::new(Point::ORIGIN, Size::new(100.0, 100.0))
+}
+
+const PHOTO: Size = Size::new(400.0, 200.0);
+
+#[test]
+fn test_fit_centres_photo() {
+    let rect = Zoom::default().image_rect(view(), PHOTO);
+    assert_eq!(rect, Rectangle::new(Point::new(0.0, 25.0), Size::new(100.0, 50.0)));
+}
+
+#[test]
+fn test_zoom_keeps_point_under_cursor() {
+    let cursor = Point::new(75.0, 50.0);
+    let before = Zoom::default().image_rect(view(), PHOTO);
+    let zoom = Zoom::default().zoomed_at(2.0, cursor, view(), PHOTO);
+    let after = zoom.image_rect(view(), PHOTO);
+    assert_eq!(zoom.scale, 2.0);
+    let fraction = |r: Rectangle| (cursor.x - r.x) / r.width;
+    assert!((fraction(before) - fraction(after)).abs() < 1e-4);
+}
+
+#[test]
+fn test_pan_stops_at_photo_edges() {
+    let zoom = Zoom::default().zoomed(4.0).panned(Vector::new(1000.0, 1000.0), view(), PHOTO);
+    let rect = zoom.image_rect(view(), PHOTO);
+    assert_eq!((rect.x, rect.y), (0.0, 0.0));
+    assert!(rect.x + rect.width >= 100.0 && rect.y + rect.height >= 100.0);
+}
+
+#[test]
+fn test_double_click_toggles_actual_size() {
+    let zoom = Zoom::default().toggled_actual(Point::new(50.0, 50.0), view(), PHOTO);
+    assert_eq!(zoom.image_rect(view(), PHOTO).width, PHOTO.width);
+    assert_eq!(zoom.toggled_actual(Point::new(50.0, 50.0), view(), PHOTO), Zoom::default());
+}
+
+#[test]
+fn test_zoom_out_stops_at_fit() {
+    assert_eq!(Zoom::default().zoomed(0.5), Zoom::default());
+    assert!(Zoom::default().zoomed(ui::ZOOM_STEP).scale > 1.0);
+}
@@ -1,7 +1,10 @@
 #[path = "../../app/src/config.rs"]
 mod app_config;
 use app_config::AppConfig;
-use ui::{GooglePiczUI, Message, SearchMode};
+use ui::{
+    CacheStatus, FilterKind, GooglePiczUI, ImageLoaderError, Message, OpenedCache, SearchMode, SettingsField,
+    StatusSnapshot, ThumbnailState, UiFlags, UploadStatus,
+};
 use sync::{SyncTaskError, SyncErrorCode};
 use iced::Application;
 use tempfile::tempdir;
@@ -23,6 +26,7 @@ fn sample_item() -> MediaItem {
             video: None,
         },
         filename: "1.jpg".into(),
+        is_favorite: false,
     }
 }
 
@@ -33,7 +37,7 @@ fn test_initial_state() {
     std::env::set_var("HOME", dir.path());
     std::fs::create_dir_all(dir.path().join(".googlepicz")).unwrap();
 
-    let (ui, _) = GooglePiczUI::new((None, None, None, 0, 4, dir.path().join(".googlepicz")));
+    let (ui, _) = GooglePiczUI::new(UiFlags::new(dir.path().join(".googlepicz")));
     assert_eq!(ui.photo_count(), 0);
     assert_eq!(ui.album_count(), 0);
     assert_eq!(ui.state_debug(), "Grid");
@@ -46,7 +50,7 @@ fn test_select_and_close_photo() {
     std::env::set_var("HOME", dir.path());
     std::fs::create_dir_all(dir.path().join(".googlepicz")).unwrap();
 
-    let (mut ui, _) = GooglePiczUI::new((None, None, None, 0, 4, dir.path().join(".googlepicz")));
+    let (mut ui, _) = GooglePiczUI::new(UiFlags::new(dir.path().join(".googlepicz")));
     let item = sample_item();
 
     let _ = ui.update(Message::SelectPhoto(item.clone()));
@@ -63,7 +67,7 @@ fn test_dismiss_error() {
     std::env::set_var("HOME", dir.path());
     std::fs::create_dir_all(dir.path().join(".googlepicz")).unwrap();
 
-    let (mut ui, _) = GooglePiczUI::new((None, None, None, 0, 4, dir.path().join(".googlepicz")));
+    let (mut ui, _) = GooglePiczUI::new(UiFlags::new(dir.path().join(".googlepicz")));
     let _ = ui.update(Message::SyncError(SyncTaskError::Other {
         code: SyncErrorCode::Other,
         message: "err".into(),
@@ -80,7 +84,7 @@ fn test_sync_error_added() {
     std::env::set_var("HOME", dir.path());
     std::fs::create_dir_all(dir.path().join(".googlepicz")).unwrap();
 
-    let (mut ui, _) = GooglePiczUI::new((None, None, None, 0, 4, dir.path().join(".googlepicz")));
+    let (mut ui, _) = GooglePiczUI::new(UiFlags::new(dir.path().join(".googlepicz")));
     let _ = ui.update(Message::SyncError(SyncTaskError::Other {
         code: SyncErrorCode::Other,
         message: "boom".into(),
@@ -88,6 +92,26 @@ fn test_sync_error_added() {
     assert!(ui.error_count() > 0);
 }
 
+#[test]
+#[serial]
+fn test_revoked_sign_in_prompts_until_signed_in_again() {
+    let dir = tempdir().unwrap();
+    std::env::set_var("HOME", dir.path());
+    std::fs::create_dir_all(dir.path().join(".googlepicz")).unwrap();
+
+    let (mut ui, _) = GooglePiczUI::new(UiFlags::new(dir.path().join(".googlepicz")));
+    let _ = ui.update(Message::SyncError(SyncTaskError::ReauthRequired("Token has been expired or revoked.".into())));
+    assert!(ui.reauth_required());
+    assert_eq!(ui.error_count(), 0);
+
+    let _ = ui.update(Message::Reauthenticated(Err("cancelled".into())));
+    assert!(ui.reauth_required());
+    assert_eq!(ui.error_count(), 1);
+
+    let _ = ui.update(Message::Reauthenticated(Ok(())));
+    assert!(!ui.reauth_required());
+}
+
 #[test]
 #[serial]
 fn test_rename_dialog_state() {
@@ -95,7 +119,7 @@ fn test_rename_dialog_state() {
     std::env::set_var("HOME", dir.path());
     std::fs::create_dir_all(dir.path().join(".googlepicz")).unwrap();
 
-    let (mut ui, _) = GooglePiczUI::new((None, None, None, 0, 4, dir.path().join(".googlepicz")));
+    let (mut ui, _) = GooglePiczUI::new(UiFlags::new(dir.path().join(".googlepicz")));
     let _ = ui.update(Message::ShowRenameAlbumDialog("a1".into(), "Old".into()));
     assert_eq!(ui.renaming_album(), Some("a1".into()));
     assert_eq!(ui.rename_album_title(), "Old");
@@ -110,7 +134,7 @@ fn test_delete_dialog_state() {
     std::env::set_var("HOME", dir.path());
     std::fs::create_dir_all(dir.path().join(".googlepicz")).unwrap();
 
-    let (mut ui, _) = GooglePiczUI::new((None, None, None, 0, 4, dir.path().join(".googlepicz")));
+    let (mut ui, _) = GooglePiczUI::new(UiFlags::new(dir.path().join(".googlepicz")));
     let _ = ui.update(Message::ShowDeleteAlbumDialog("a1".into()));
     assert_eq!(ui.deleting_album(), Some("a1".into()));
     let _ = ui.update(Message::CancelDeleteAlbum);
@@ -124,7 +148,7 @@ fn test_search_input() {
     std::env::set_var("HOME", dir.path());
     std::fs::create_dir_all(dir.path().join(".googlepicz")).unwrap();
 
-    let (mut ui, _) = GooglePiczUI::new((None, None, None, 0, 4, dir.path().join(".googlepicz")));
+    let (mut ui, _) = GooglePiczUI::new(UiFlags::new(dir.path().join(".googlepicz")));
     let _ = ui.update(Message::SearchInputChanged("query".into()));
     assert_eq!(ui.search_query(), "query");
 }
@@ -136,7 +160,7 @@ fn test_search_mode() {
     std::env::set_var("HOME", dir.path());
     std::fs::create_dir_all(dir.path().join(".googlepicz")).unwrap();
 
-    let (mut ui, _) = GooglePiczUI::new((None, None, None, 0, 4, dir.path().join(".googlepicz")));
+    let (mut ui, _) = GooglePiczUI::new(UiFlags::new(dir.path().join(".googlepicz")));
     assert_eq!(ui.search_mode(), SearchMode::Filename);
     let _ = ui.update(Message::SearchModeChanged(SearchMode::Favoriten));
     assert_eq!(ui.search_mode(), SearchMode::Favoriten);
@@ -157,7 +181,7 @@ fn test_settings_dialog() {
     std::env::set_var("HOME", dir.path());
     std::fs::create_dir_all(dir.path().join(".googlepicz")).unwrap();
 
-    let (mut ui, _) = GooglePiczUI::new((None, None, None, 0, 4, dir.path().join(".googlepicz")));
+    let (mut ui, _) = GooglePiczUI::new(UiFlags::new(dir.path().join(".googlepicz")));
     assert!(!ui.settings_open());
     let _ = ui.update(Message::ShowSettings);
     assert!(ui.settings_open());
@@ -180,11 +204,27 @@ fn test_save_settings() {
         sync_interval_minutes: 5,
         debug_console: false,
         trace_spans: false,
+        detect_faces: false,
+        headless: false,
         cache_path: gp_dir.clone(),
+        slideshow_interval_secs: 5,
+        slideshow_play_videos: false,
+        theme: "system".into(),
+        language: "en".into(),
+        image_cache_max_mb: 1024,
+        video_cache_max_mb: 2048,
+        prefetch_after_sync: false,
+        favorites_album_sync: false,
+        metrics_port: 0,
+        unchanged_page_limit: sync::DEFAULT_UNCHANGED_PAGE_LIMIT,
+        min_face_size: cache::DEFAULT_MIN_FACE_SIZE,
+        face_detector: face_recognition::DetectorConfig::default(),
+        profile: None,
+        sources: Default::default(),
     };
     cfg.save_to(Some(gp_dir.join("config"))).unwrap();
 
-    let (mut ui, _) = GooglePiczUI::new((None, None, None, 0, 4, gp_dir.clone()));
+    let (mut ui, _) = GooglePiczUI::new(UiFlags::new(gp_dir.clone()));
     let _ = ui.update(Message::ShowSettings);
     ui.update(Message::SettingsLogLevelChanged("debug".into()));
     let new_cache = gp_dir.join("new_cache");
@@ -193,7 +233,7 @@ fn test_save_settings() {
     ui.update(Message::SettingsDebugConsoleToggled(true));
     let _ = ui.update(Message::SaveSettings);
 
-    let saved = AppConfig::load_from(Some(gp_dir.join("config")));
+    let saved = AppConfig::load_from(Some(gp_dir.join("config")), None);
     assert_eq!(saved.log_level, "debug");
     assert_eq!(saved.cache_path, PathBuf::from(new_cache_str));
     assert!(saved.debug_console);
@@ -207,7 +247,7 @@ fn test_faces_loaded_and_rename() {
     std::env::set_var("HOME", dir.path());
     std::fs::create_dir_all(dir.path().join(".googlepicz")).unwrap();
 
-    let (mut ui, _) = GooglePiczUI::new((None, None, None, 0, 4, dir.path().join(".googlepicz")));
+    let (mut ui, _) = GooglePiczUI::new(UiFlags::new(dir.path().join(".googlepicz")));
     let item = sample_item();
 
     let _ = ui.update(Message::SelectPhoto(item.clone()));
@@ -222,6 +262,56 @@ fn test_faces_loaded_and_rename() {
     assert_eq!(ui.face_name(0), Some("Alice".into()));
 }
 
+#[test]
+#[serial]
+fn test_detected_faces_replace_empty_overlay() {
+    let dir = tempdir().unwrap();
+    std::env::set_var("HOME", dir.path());
+    std::fs::create_dir_all(dir.path().join(".googlepicz")).unwrap();
+
+    let (mut ui, _) = GooglePiczUI::new(UiFlags::new(dir.path().join(".googlepicz")));
+    let item = sample_item();
+    let _ = ui.update(Message::SelectPhoto(item.clone()));
+    assert_eq!(ui.face_count(), 0);
+
+    let _ = ui.update(Message::FacesDetected(item.id.clone(), Ok(Vec::new())));
+    assert_eq!(ui.notifications().last().unwrap().text, "No faces found in this photo");
+    assert!(!ui.is_detecting_faces(&item.id));
+
+    let face = face_recognition::Face { bbox: [1, 2, 30, 30], name: None, ignored: false, confidence: None, rect: (1, 2, 30, 30) };
+    let _ = ui.update(Message::FacesDetected(item.id.clone(), Ok(vec![face.clone()])));
+    assert_eq!(ui.face_count(), 1);
+
+    // Results for a photo no longer open leave the overlay alone
+    let _ = ui.update(Message::FacesDetected("other".into(), Ok(vec![face.clone(), face])));
+    assert_eq!(ui.face_count(), 1);
+}
+
+#[test]
+#[serial]
+fn test_ignore_face_toggles_and_stops_renaming() {
+    let dir = tempdir().unwrap();
+    std::env::set_var("HOME", dir.path());
+    std::fs::create_dir_all(dir.path().join(".googlepicz")).unwrap();
+
+    let (mut ui, _) = GooglePiczUI::new(UiFlags::new(dir.path().join(".googlepicz")));
+    let item = sample_item();
+    let _ = ui.update(Message::SelectPhoto(item.clone()));
+    let face = face_recognition::Face { bbox: [1, 2, 30, 30], name: None, ignored: false, confidence: None, rect: (1, 2, 30, 30) };
+    let small = face_recognition::Face { ignored: true, ..face.clone() };
+    let _ = ui.update(Message::FacesDetected(item.id.clone(), Ok(vec![face, small])));
+    assert!(!ui.face_ignored(0));
+    assert!(ui.face_ignored(1));
+
+    let _ = ui.update(Message::StartRenameFace(0));
+    let _ = ui.update(Message::ToggleFaceIgnored(0));
+    assert!(ui.face_ignored(0));
+    assert_eq!(ui.editing_face(), None);
+
+    let _ = ui.update(Message::ToggleFaceIgnored(1));
+    assert!(!ui.face_ignored(1));
+}
+
 #[test]
 #[serial]
 fn test_load_more_photos() {
@@ -229,7 +319,7 @@ fn test_load_more_photos() {
     std::env::set_var("HOME", dir.path());
     std::fs::create_dir_all(dir.path().join(".googlepicz")).unwrap();
 
-    let (mut ui, _) = GooglePiczUI::new((None, None, None, 0, dir.path().join(".googlepicz")));
+    let (mut ui, _) = GooglePiczUI::new(UiFlags::new(dir.path().join(".googlepicz")));
     ui.update(Message::PhotosLoaded(Ok(vec![sample_item(); 50])));
     assert!(ui.photo_count() == 50);
     let before = ui.photo_count();
@@ -244,7 +334,7 @@ fn test_cache_path_chosen() {
     std::env::set_var("HOME", dir.path());
     std::fs::create_dir_all(dir.path().join(".googlepicz")).unwrap();
 
-    let (mut ui, _) = GooglePiczUI::new((None, None, None, 0, dir.path().join(".googlepicz")));
+    let (mut ui, _) = GooglePiczUI::new(UiFlags::new(dir.path().join(".googlepicz")));
     let path = dir.path().join("other");
     let _ = ui.update(Message::CachePathChosen(Some(path.to_path_buf().to_str().unwrap().into())));
     assert_eq!(ui.settings_cache_path(), path.to_string_lossy());
@@ -257,7 +347,7 @@ fn test_escape_closes_photo() {
     std::env::set_var("HOME", dir.path());
     std::fs::create_dir_all(dir.path().join(".googlepicz")).unwrap();
 
-    let (mut ui, _) = GooglePiczUI::new((None, None, None, 0, dir.path().join(".googlepicz")));
+    let (mut ui, _) = GooglePiczUI::new(UiFlags::new(dir.path().join(".googlepicz")));
     let item = sample_item();
     ui.update(Message::SelectPhoto(item));
     assert!(ui.state_debug().starts_with("SelectedPhoto"));
@@ -272,10 +362,1861 @@ fn test_sync_status_message() {
     std::env::set_var("HOME", dir.path());
     std::fs::create_dir_all(dir.path().join(".googlepicz")).unwrap();
 
-    let (mut ui, _) = GooglePiczUI::new((None, None, None, 0, 4, dir.path().join(".googlepicz")));
+    let (mut ui, _) = GooglePiczUI::new(UiFlags::new(dir.path().join(".googlepicz")));
     let now = chrono::Utc::now();
     let _ = ui.update(Message::SyncStatusUpdated(now, "Sync started".into()));
     assert!(ui.syncing());
     assert_eq!(ui.sync_status(), "Sync started".to_string());
 }
 
+
+#[test]
+#[serial]
+fn test_thumbnail_cache_is_bounded() {
+    let dir = tempdir().unwrap();
+    std::env::set_var("HOME", dir.path());
+    std::fs::create_dir_all(dir.path().join(".googlepicz")).unwrap();
+
+    let (mut ui, _) = GooglePiczUI::new(UiFlags { preload: 8, ..UiFlags::new(dir.path().join(".googlepicz")) });
+    let items: Vec<MediaItem> = (0..500)
+        .map(|i| MediaItem { id: i.to_string(), ..sample_item() })
+        .collect();
+    let _ = ui.update(Message::PhotosLoaded(Ok(items)));
+    for i in 0..500 {
+        let handle = iced::widget::image::Handle::from_memory(Vec::new());
+        let _ = ui.update(Message::ThumbnailLoaded(i.to_string(), Ok(handle)));
+    }
+    assert!(ui.thumbnail_count() <= 400);
+    assert!(ui.thumbnail_count() > 0);
+}
+
+#[test]
+#[serial]
+fn test_selection_toggle_range_and_clear() {
+    let dir = tempdir().unwrap();
+    std::env::set_var("HOME", dir.path());
+    std::fs::create_dir_all(dir.path().join(".googlepicz")).unwrap();
+
+    let (mut ui, _) = GooglePiczUI::new(UiFlags::new(dir.path().join(".googlepicz")));
+    let items: Vec<MediaItem> = (0..6)
+        .map(|i| MediaItem { id: i.to_string(), ..sample_item() })
+        .collect();
+    let _ = ui.update(Message::PhotosLoaded(Ok(items)));
+
+    let _ = ui.update(Message::ToggleSelect("1".into()));
+    let _ = ui.update(Message::SelectRangeTo("4".into()));
+    assert_eq!(ui.selection_count(), 4);
+    let _ = ui.update(Message::ToggleSelect("2".into()));
+    assert_eq!(ui.selection_count(), 3);
+
+    let _ = ui.update(Message::EscapePressed);
+    assert_eq!(ui.selection_count(), 0);
+    assert_eq!(ui.state_debug(), "Grid");
+}
+
+#[test]
+#[serial]
+fn test_toggle_favorite_updates_photo() {
+    let dir = tempdir().unwrap();
+    std::env::set_var("HOME", dir.path());
+    std::fs::create_dir_all(dir.path().join(".googlepicz")).unwrap();
+
+    let (mut ui, _) = GooglePiczUI::new(UiFlags::new(dir.path().join(".googlepicz")));
+    let _ = ui.update(Message::PhotosLoaded(Ok(vec![sample_item()])));
+    assert_eq!(ui.photo_is_favorite("1"), Some(false));
+    let _ = ui.update(Message::ToggleFavorite("1".into()));
+    assert_eq!(ui.photo_is_favorite("1"), Some(true));
+    let _ = ui.update(Message::FavoriteUpdated("1".into(), Err("db".into())));
+    assert_eq!(ui.photo_is_favorite("1"), Some(false));
+}
+
+#[test]
+#[serial]
+fn test_keyboard_navigation() {
+    use iced::keyboard::KeyCode;
+    let dir = tempdir().unwrap();
+    std::env::set_var("HOME", dir.path());
+    std::fs::create_dir_all(dir.path().join(".googlepicz")).unwrap();
+
+    let (mut ui, _) = GooglePiczUI::new(UiFlags::new(dir.path().join(".googlepicz")));
+    let items: Vec<MediaItem> = (0..10)
+        .map(|i| MediaItem { id: i.to_string(), ..sample_item() })
+        .collect();
+    let _ = ui.update(Message::PhotosLoaded(Ok(items)));
+    // Four 150px tiles per row beside the collapsed album sidebar
+    let _ = ui.update(Message::WindowResized(808, 600));
+    assert_eq!(ui.grid_columns(), 4);
+
+    let _ = ui.update(Message::KeyPressed(KeyCode::Right));
+    assert_eq!(ui.focused_index(), Some(0));
+    let _ = ui.update(Message::KeyPressed(KeyCode::Down));
+    let _ = ui.update(Message::KeyPressed(KeyCode::Right));
+    assert_eq!(ui.focused_index(), Some(5));
+
+    let _ = ui.update(Message::KeyPressed(KeyCode::Enter));
+    assert_eq!(ui.selected_photo_id(), Some("5".into()));
+    let _ = ui.update(Message::KeyPressed(KeyCode::Right));
+    assert_eq!(ui.selected_photo_id(), Some("6".into()));
+    let _ = ui.update(Message::KeyPressed(KeyCode::Left));
+    let _ = ui.update(Message::KeyPressed(KeyCode::Left));
+    assert_eq!(ui.selected_photo_id(), Some("4".into()));
+
+    let _ = ui.update(Message::EscapePressed);
+    assert_eq!(ui.state_debug(), "Grid");
+    assert_eq!(ui.focused_index(), Some(4));
+}
+
+#[test]
+#[serial]
+fn test_info_panel_toggle_with_key() {
+    use iced::keyboard::KeyCode;
+    let dir = tempdir().unwrap();
+    std::env::set_var("HOME", dir.path());
+    std::fs::create_dir_all(dir.path().join(".googlepicz")).unwrap();
+
+    let (mut ui, _) = GooglePiczUI::new(UiFlags::new(dir.path().join(".googlepicz")));
+    let _ = ui.update(Message::PhotosLoaded(Ok(vec![sample_item()])));
+    let _ = ui.update(Message::SelectPhoto(sample_item()));
+    assert!(!ui.info_open());
+    let _ = ui.update(Message::KeyPressed(KeyCode::I));
+    assert!(ui.info_open());
+    let _ = ui.update(Message::ToggleInfoPanel);
+    assert!(!ui.info_open());
+}
+
+#[test]
+#[serial]
+fn test_edit_description_updates_grid() {
+    use iced::keyboard::KeyCode;
+    let dir = tempdir().unwrap();
+    std::env::set_var("HOME", dir.path());
+    std::fs::create_dir_all(dir.path().join(".googlepicz")).unwrap();
+
+    let (mut ui, _) = GooglePiczUI::new(UiFlags::new(dir.path().join(".googlepicz")));
+    let _ = ui.update(Message::PhotosLoaded(Ok(vec![sample_item()])));
+    let _ = ui.update(Message::SelectPhoto(sample_item()));
+    let _ = ui.update(Message::StartEditDescription);
+    assert!(ui.editing_description());
+    // Typing must not trigger viewer shortcuts
+    let _ = ui.update(Message::KeyPressed(KeyCode::Space));
+    assert_eq!(ui.photo_is_favorite("1"), Some(false));
+
+    let _ = ui.update(Message::DescriptionChanged("Beach".into()));
+    let _ = ui.update(Message::SaveDescription);
+    assert!(!ui.editing_description());
+    let _ = ui.update(Message::DescriptionSaved("1".into(), Ok(("Beach".into(), true))));
+    assert_eq!(ui.photo_description("1"), Some("Beach".into()));
+
+    let _ = ui.update(Message::StartEditDescription);
+    let _ = ui.update(Message::EscapePressed);
+    assert!(!ui.editing_description());
+    assert_eq!(ui.selected_photo_id(), Some("1".into()));
+}
+
+#[test]
+#[serial]
+fn test_slideshow_advances_pauses_and_exits() {
+    use iced::keyboard::KeyCode;
+    let dir = tempdir().unwrap();
+    std::env::set_var("HOME", dir.path());
+    std::fs::create_dir_all(dir.path().join(".googlepicz")).unwrap();
+
+    let (mut ui, _) = GooglePiczUI::new(UiFlags::new(dir.path().join(".googlepicz")));
+    let mut items: Vec<MediaItem> = (0..3)
+        .map(|i| MediaItem { id: i.to_string(), ..sample_item() })
+        .collect();
+    items[1].mime_type = "video/mp4".into();
+    let _ = ui.update(Message::PhotosLoaded(Ok(items)));
+
+    let _ = ui.update(Message::StartSlideshow);
+    assert_eq!(ui.slideshow_state(), Some((0, true)));
+    // Videos are skipped unless playback is enabled
+    let _ = ui.update(Message::SlideshowTick);
+    assert_eq!(ui.slideshow_state(), Some((2, true)));
+    let _ = ui.update(Message::SlideshowTick);
+    assert_eq!(ui.slideshow_state(), Some((0, true)));
+
+    let _ = ui.update(Message::KeyPressed(KeyCode::Space));
+    assert_eq!(ui.slideshow_state(), Some((0, false)));
+    let _ = ui.update(Message::SlideshowTick);
+    assert_eq!(ui.slideshow_state(), Some((0, false)));
+
+    let _ = ui.update(Message::EscapePressed);
+    assert_eq!(ui.slideshow_state(), None);
+}
+
+#[test]
+#[serial]
+fn test_timeline_sections_and_jump() {
+    use ui::Granularity;
+    let dir = tempdir().unwrap();
+    std::env::set_var("HOME", dir.path());
+    std::fs::create_dir_all(dir.path().join(".googlepicz")).unwrap();
+
+    let (mut ui, _) = GooglePiczUI::new(UiFlags::new(dir.path().join(".googlepicz")));
+    let dates = ["2023-01-05T00:00:00Z", "2024-03-01T00:00:00Z", "2024-03-20T00:00:00Z"];
+    let items: Vec<MediaItem> = dates
+        .iter()
+        .enumerate()
+        .map(|(i, d)| {
+            let mut item = MediaItem { id: i.to_string(), ..sample_item() };
+            item.media_metadata.creation_time = d.to_string();
+            item
+        })
+        .collect();
+    let _ = ui.update(Message::PhotosLoaded(Ok(items)));
+    assert_eq!(ui.section_titles(), vec!["March 2024 (2)", "January 2023 (1)"]);
+
+    let _ = ui.update(Message::TimelineGranularityChanged(Granularity::Year));
+    assert_eq!(ui.section_titles(), vec!["2024 (2)", "2023 (1)"]);
+
+    let _ = ui.update(Message::ToggleSection("2024".into()));
+    let _ = ui.update(Message::JumpDateChanged("2023-06".into()));
+    let _ = ui.update(Message::JumpToDate);
+    // Newest photo of 2023 gets focus
+    assert_eq!(ui.focused_index(), Some(2));
+}
+
+#[test]
+#[serial]
+fn test_theme_applies_immediately_and_persists() {
+    let dir = tempdir().unwrap();
+    std::env::set_var("HOME", dir.path());
+    let gp_dir = dir.path().join(".googlepicz");
+    std::fs::create_dir_all(&gp_dir).unwrap();
+
+    let (mut ui, _) = GooglePiczUI::new(UiFlags::new(gp_dir.clone()));
+    let _ = ui.update(Message::ShowSettings);
+    let _ = ui.update(Message::SettingsThemeChanged("dark".into()));
+    assert_eq!(ui.active_theme(), iced::Theme::Dark);
+    let _ = ui.update(Message::CloseSettings);
+    assert_ne!(AppConfig::load_from(Some(gp_dir.join("config")), None).theme, "dark");
+
+    let _ = ui.update(Message::ShowSettings);
+    let _ = ui.update(Message::SettingsThemeChanged("light".into()));
+    let _ = ui.update(Message::SaveSettings);
+    assert_eq!(ui.active_theme(), iced::Theme::Light);
+    assert_eq!(AppConfig::load_from(Some(gp_dir.join("config")), None).theme, "light");
+}
+
+#[test]
+#[serial]
+fn test_language_setting_switches_strings() {
+    let dir = tempdir().unwrap();
+    std::env::set_var("HOME", dir.path());
+    let gp_dir = dir.path().join(".googlepicz");
+    std::fs::create_dir_all(&gp_dir).unwrap();
+
+    let (mut ui, _) = GooglePiczUI::new(UiFlags::new(gp_dir.clone()));
+    let _ = ui.update(Message::ShowSettings);
+    let _ = ui.update(Message::SettingsLanguageChanged(ui::Language::De));
+    let _ = ui.update(Message::SaveSettings);
+    assert_eq!(AppConfig::load_from(Some(gp_dir.join("config")), None).language, "de");
+    let _ = ui.update(Message::SyncProgress(sync::SyncProgress::Started));
+    assert_eq!(ui.sync_status(), "Synchronisierung gestartet");
+
+    // A restart picks the saved language up before anything is rendered
+    let (mut ui, _) = GooglePiczUI::new(UiFlags::new(gp_dir.clone()));
+    assert_eq!(ui.title(), "GooglePicz - Google Fotos verwalten");
+
+    let _ = ui.update(Message::ShowSettings);
+    let _ = ui.update(Message::SettingsLanguageChanged(ui::Language::En));
+    let _ = ui.update(Message::SaveSettings);
+    let _ = ui.update(Message::SyncProgress(sync::SyncProgress::Started));
+    assert_eq!(ui.sync_status(), "Sync started");
+}
+
+#[test]
+#[serial]
+fn test_notifications_dismiss_individually() {
+    let dir = tempdir().unwrap();
+    std::env::set_var("HOME", dir.path());
+    std::fs::create_dir_all(dir.path().join(".googlepicz")).unwrap();
+
+    let (mut ui, _) = GooglePiczUI::new(UiFlags::new(dir.path().join(".googlepicz")));
+    let album = api_client::Album {
+        id: "a1".into(),
+        title: Some("Vacation".into()),
+        product_url: None,
+        is_writeable: None,
+        media_items_count: None,
+        cover_photo_base_url: None,
+        cover_photo_media_item_id: None,
+    };
+    let _ = ui.update(Message::AlbumCreated(Ok(album)));
+    let _ = ui.update(Message::SyncProgress(sync::SyncProgress::Started));
+    let _ = ui.update(Message::SyncProgress(sync::SyncProgress::Finished(120)));
+    let texts: Vec<String> = ui.notifications().iter().map(|n| n.text.clone()).collect();
+    assert_eq!(texts, vec!["Album \"Vacation\" created", "Sync completed: 120 items"]);
+    assert_eq!(ui.notifications()[0].level, ui::NotificationLevel::Success);
+    assert_eq!(ui.error_count(), 0);
+
+    let first = ui.notifications()[0].id;
+    let _ = ui.update(Message::DismissNotification(first));
+    assert_eq!(ui.notifications().len(), 1);
+    assert_eq!(ui.notifications()[0].text, "Sync completed: 120 items");
+
+    // A closing progress channel reports `Finished` without a running sync
+    let _ = ui.update(Message::SyncProgress(sync::SyncProgress::Finished(0)));
+    assert_eq!(ui.notifications().len(), 1);
+}
+
+#[test]
+#[serial]
+fn test_people_panel_select_and_rename() {
+    let dir = tempdir().unwrap();
+    std::env::set_var("HOME", dir.path());
+    std::fs::create_dir_all(dir.path().join(".googlepicz")).unwrap();
+
+    let (mut ui, _) = GooglePiczUI::new(UiFlags::new(dir.path().join(".googlepicz")));
+    let person = |name: Option<&str>, count| cache::PersonEntry {
+        name: name.map(Into::into),
+        cluster: None,
+        photo_count: count,
+        sample_media_item_id: "1".into(),
+        sample_bbox: [0, 0, 10, 10],
+    };
+    let _ = ui.update(Message::TogglePeoplePanel);
+    let _ = ui.update(Message::PeopleLoaded(Ok(vec![person(Some("Ann"), 2), person(None, 1)])));
+    assert_eq!(ui.people_labels(), vec!["Ann", "Unnamed faces"]);
+
+    let _ = ui.update(Message::SelectPerson(Some("Ann".into())));
+    assert_eq!(ui.selected_person(), Some(Some("Ann".into())));
+
+    let _ = ui.update(Message::StartRenamePerson("Ann".into()));
+    let _ = ui.update(Message::PersonNameChanged("Anna".into()));
+    let _ = ui.update(Message::ConfirmRenamePerson);
+    assert_eq!(ui.selected_person(), Some(Some("Anna".into())));
+
+    let _ = ui.update(Message::PeopleChanged(Ok("Renamed Ann to Anna".into())));
+    assert_eq!(ui.notifications().last().unwrap().text, "Renamed Ann to Anna");
+
+    let _ = ui.update(Message::SelectAlbum(None));
+    assert_eq!(ui.selected_person(), None);
+}
+
+#[test]
+#[serial]
+fn test_people_panel_clusters_and_naming_offer() {
+    let dir = tempdir().unwrap();
+    std::env::set_var("HOME", dir.path());
+    std::fs::create_dir_all(dir.path().join(".googlepicz")).unwrap();
+
+    let (mut ui, _) = GooglePiczUI::new(UiFlags::new(dir.path().join(".googlepicz")));
+    let cluster = |id, count| cache::PersonEntry {
+        name: None,
+        cluster: Some(id),
+        photo_count: count,
+        sample_media_item_id: "1".into(),
+        sample_bbox: [0, 0, 10, 10],
+    };
+    let _ = ui.update(Message::TogglePeoplePanel);
+    let _ = ui.update(Message::PeopleLoaded(Ok(vec![cluster(3, 84)])));
+    assert_eq!(ui.people_labels(), vec!["Unnamed person #3"]);
+
+    let _ = ui.update(Message::SelectCluster(3));
+    assert_eq!(ui.selected_cluster(), Some(3));
+    assert_eq!(ui.selected_person(), None);
+    let _ = ui.update(Message::SelectPerson(None));
+    assert_eq!(ui.selected_cluster(), None);
+
+    // Naming a face of a cluster offers the name for the rest while its photo is open
+    let _ = ui.update(Message::SelectPhoto(sample_item()));
+    let id = ui.selected_photo_id().unwrap();
+    let found = cache::FaceCluster { id: 3, photo_count: 83 };
+    let _ = ui.update(Message::FaceNameSaved(id.clone(), "Ann".into(), Ok(Some(found))));
+    assert_eq!(ui.cluster_offer(), Some((3, "Ann")));
+    let _ = ui.update(Message::DismissClusterOffer);
+    assert_eq!(ui.cluster_offer(), None);
+
+    // Nothing left to name, or a photo that is no longer open
+    let done = cache::FaceCluster { id: 3, photo_count: 0 };
+    let _ = ui.update(Message::FaceNameSaved(id, "Ann".into(), Ok(Some(done))));
+    let _ = ui.update(Message::FaceNameSaved("other".into(), "Ann".into(), Ok(Some(found))));
+    assert_eq!(ui.cluster_offer(), None);
+}
+
+#[test]
+#[serial]
+fn test_drag_selection_onto_album() {
+    let dir = tempdir().unwrap();
+    std::env::set_var("HOME", dir.path());
+    std::fs::create_dir_all(dir.path().join(".googlepicz")).unwrap();
+
+    let (mut ui, _) = GooglePiczUI::new(UiFlags::new(dir.path().join(".googlepicz")));
+    let items: Vec<MediaItem> = (0..4)
+        .map(|i| MediaItem { id: i.to_string(), ..sample_item() })
+        .collect();
+    let _ = ui.update(Message::PhotosLoaded(Ok(items)));
+    let album = api_client::Album {
+        id: "a1".into(),
+        title: Some("Vacation".into()),
+        product_url: None,
+        is_writeable: None,
+        media_items_count: None,
+        cover_photo_base_url: None,
+        cover_photo_media_item_id: None,
+    };
+    let _ = ui.update(Message::AlbumsLoaded(Ok(vec![album])));
+
+    // A press without movement is a plain click
+    let _ = ui.update(Message::TileHovered("3".into()));
+    let _ = ui.update(Message::DragPressed);
+    assert!(ui.dragged_items().is_empty());
+    let _ = ui.update(Message::DragReleased);
+
+    // Unselected tiles travel alone
+    let _ = ui.update(Message::DragPressed);
+    let _ = ui.update(Message::DragMoved);
+    assert_eq!(ui.dragged_items(), ["3"]);
+    let _ = ui.update(Message::DragReleased);
+    assert!(ui.dragged_items().is_empty());
+
+    let _ = ui.update(Message::ToggleSelect("1".into()));
+    let _ = ui.update(Message::ToggleSelect("2".into()));
+    let _ = ui.update(Message::TileUnhovered("3".into()));
+    let _ = ui.update(Message::TileHovered("2".into()));
+    let _ = ui.update(Message::DragPressed);
+    let _ = ui.update(Message::DragMoved);
+    assert_eq!(ui.dragged_items(), ["1", "2"]);
+
+    let _ = ui.update(Message::AlbumHovered("a1".into()));
+    assert_eq!(ui.drop_target(), Some("a1"));
+    let _ = ui.update(Message::DragReleased);
+    assert!(ui.dragged_items().is_empty());
+    assert_eq!(ui.drop_target(), None);
+}
+
+#[test]
+#[serial]
+fn test_zoom_shortcuts_and_reset_on_photo_switch() {
+    use iced::keyboard::KeyCode;
+    let dir = tempdir().unwrap();
+    std::env::set_var("HOME", dir.path());
+    std::fs::create_dir_all(dir.path().join(".googlepicz")).unwrap();
+
+    let (mut ui, _) = GooglePiczUI::new(UiFlags::new(dir.path().join(".googlepicz")));
+    let items: Vec<MediaItem> = (0..2)
+        .map(|i| MediaItem { id: i.to_string(), ..sample_item() })
+        .collect();
+    let _ = ui.update(Message::PhotosLoaded(Ok(items.clone())));
+    let _ = ui.update(Message::SelectPhoto(items[0].clone()));
+    assert_eq!(ui.zoom(), ui::Zoom::default());
+
+    let _ = ui.update(Message::KeyPressed(KeyCode::Plus));
+    let _ = ui.update(Message::KeyPressed(KeyCode::Plus));
+    assert!((ui.zoom().scale - ui::ZOOM_STEP * ui::ZOOM_STEP).abs() < 1e-4);
+    let _ = ui.update(Message::KeyPressed(KeyCode::Minus));
+    assert!((ui.zoom().scale - ui::ZOOM_STEP).abs() < 1e-4);
+    let _ = ui.update(Message::KeyPressed(KeyCode::Key0));
+    assert_eq!(ui.zoom(), ui::Zoom::default());
+
+    let _ = ui.update(Message::KeyPressed(KeyCode::Plus));
+    let _ = ui.update(Message::SelectPhoto(items[1].clone()));
+    assert_eq!(ui.zoom(), ui::Zoom::default());
+}
+
+#[test]
+#[serial]
+fn test_export_progress_and_failure_notifications() {
+    let dir = tempdir().unwrap();
+    std::env::set_var("HOME", dir.path());
+    std::fs::create_dir_all(dir.path().join(".googlepicz")).unwrap();
+
+    let (mut ui, _) = GooglePiczUI::new(UiFlags::new(dir.path().join(".googlepicz")));
+    let items: Vec<MediaItem> = (0..3)
+        .map(|i| MediaItem { id: i.to_string(), filename: format!("{}.jpg", i), ..sample_item() })
+        .collect();
+    let _ = ui.update(Message::PhotosLoaded(Ok(items)));
+    let target = dir.path().join("export");
+
+    let _ = ui.update(Message::ExportTo(vec!["0".into(), "2".into()], Some(target.clone())));
+    assert_eq!(ui.export_progress(), Some((0, 2)));
+
+    let _ = ui.update(Message::ExportProgress("0.jpg".into(), Ok(target.join("0.jpg"))));
+    assert_eq!(ui.export_progress(), Some((1, 2)));
+    assert!(ui.notifications().is_empty());
+
+    let _ = ui.update(Message::ExportProgress("2.jpg".into(), Err("HTTP 404".into())));
+    assert_eq!(ui.export_progress(), None);
+    let texts: Vec<&str> = ui.notifications().iter().map(|n| n.text.as_str()).collect();
+    assert_eq!(texts[0], "Could not export 2.jpg: HTTP 404");
+    assert!(texts[1].starts_with("Exported 1 of 2 photos"));
+    assert_eq!(ui.notifications()[1].level, ui::NotificationLevel::Warning);
+}
+
+#[test]
+#[serial]
+fn test_window_and_view_state_restored() {
+    let dir = tempdir().unwrap();
+    std::env::set_var("HOME", dir.path());
+    let base = dir.path().join(".googlepicz");
+    std::fs::create_dir_all(&base).unwrap();
+
+    let (mut ui, _) = GooglePiczUI::new(UiFlags::new(base.clone()));
+    let _ = ui.update(Message::SelectAlbum(Some("a1".into())));
+    let _ = ui.update(Message::SearchModeChanged(SearchMode::Favoriten));
+    let _ = ui.update(Message::WindowResized(1300, 820));
+    let _ = ui.update(Message::WindowMoved(20, 30));
+    let _ = ui.update(Message::WindowCloseRequested(iced::window::Id::MAIN));
+
+    let (ui, _) = GooglePiczUI::new(UiFlags::new(base.clone()));
+    assert_eq!(ui.selected_album(), Some("a1"));
+    assert_eq!(ui.search_mode(), SearchMode::Favoriten);
+    assert_eq!((ui.ui_state().width, ui.ui_state().height), (1300.0, 820.0));
+    assert_eq!(ui.ui_state().position, Some((20, 30)));
+
+    std::fs::write(base.join("ui_state.json"), "garbage").unwrap();
+    let (ui, _) = GooglePiczUI::new(UiFlags::new(base));
+    assert_eq!(ui.selected_album(), None);
+    assert_eq!(ui.ui_state(), &ui::UiState::default());
+}
+
+#[test]
+#[serial]
+fn test_search_debounce_ignores_stale_results() {
+    let dir = tempdir().unwrap();
+    std::env::set_var("HOME", dir.path());
+    std::fs::create_dir_all(dir.path().join(".googlepicz")).unwrap();
+
+    let (mut ui, _) = GooglePiczUI::new(UiFlags::new(dir.path().join(".googlepicz")));
+    let _ = ui.update(Message::SearchInputChanged("a".into()));
+    let _ = ui.update(Message::SearchInputChanged("ab".into()));
+    let typed = ui.search_generation();
+
+    // The timer of the first keystroke is superseded by the second
+    let _ = ui.update(Message::SearchDebounced(typed - 1));
+    assert_eq!(ui.search_generation(), typed);
+    let _ = ui.update(Message::SearchDebounced(typed));
+    let searching = ui.search_generation();
+    assert!(searching > typed);
+
+    let _ = ui.update(Message::SearchResults(typed, Ok((vec![sample_item(); 5], 0))));
+    assert_eq!(ui.photo_count(), 0);
+    let _ = ui.update(Message::SearchResults(searching, Ok((vec![sample_item(); 2], 7))));
+    assert_eq!(ui.photo_count(), 2);
+    assert_eq!(ui.search_more(), 7);
+
+    let _ = ui.update(Message::LoadPhotos);
+    assert_eq!(ui.search_more(), 0);
+    let _ = ui.update(Message::SearchResults(searching, Ok((vec![sample_item(); 9], 0))));
+    assert_eq!(ui.photo_count(), 2);
+}
+
+#[test]
+#[serial]
+fn test_video_duration_badges() {
+    let dir = tempdir().unwrap();
+    std::env::set_var("HOME", dir.path());
+    std::fs::create_dir_all(dir.path().join(".googlepicz")).unwrap();
+
+    let (mut ui, _) = GooglePiczUI::new(UiFlags::new(dir.path().join(".googlepicz")));
+    assert_eq!(ui.duration_badge("v1"), None);
+    let durations = [("v1".to_string(), 83_400), ("v2".to_string(), 3_725_000)].into_iter().collect();
+    let _ = ui.update(Message::VideoDurationsLoaded(Ok(durations)));
+    assert_eq!(ui.duration_badge("v1").as_deref(), Some("1:23"));
+    assert_eq!(ui.duration_badge("v2").as_deref(), Some("1:02:05"));
+}
+
+#[test]
+fn test_config_validation_clamps_and_reports_issues() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("config");
+    std::fs::write(
+        &path,
+        "oauth_redirect_port = 0\nthumbnails_preload = -5\npreload_threads = 500\ntheme = \"purple\"\ncache_path = \"\"\n\n[face_detector]\nconfidence_threshold = 2.0\n",
+    )
+    .unwrap();
+    let mut cfg = AppConfig::load_from(Some(path), None);
+    // Negative numbers no longer wrap around
+    assert_eq!(cfg.thumbnails_preload, 0);
+    let fields = |issues: &[app_config::ConfigIssue]| issues.iter().map(|i| i.field).collect::<Vec<_>>();
+    assert_eq!(
+        fields(&cfg.validate().unwrap_err()),
+        vec!["oauth_redirect_port", "cache_path", "preload_threads", "theme", "face_detector.confidence_threshold"]
+    );
+
+    let clamped = cfg.clamp_recoverable();
+    assert_eq!(fields(&clamped), vec!["preload_threads", "theme", "face_detector.confidence_threshold"]);
+    assert_eq!(clamped[0].value, "500");
+    assert_eq!(clamped[0].allowed, "1-64");
+    assert_eq!((cfg.preload_threads, cfg.theme.as_str()), (64, "system"));
+    assert_eq!(cfg.face_detector.confidence_threshold, 1.0);
+    let fatal = cfg.validate().unwrap_err();
+    assert!(fatal.iter().all(|i| i.fatal));
+    assert_eq!(fatal[0].to_string(), "oauth_redirect_port = \"0\" (allowed: 1-65535)");
+    assert_eq!(fields(&fatal), vec!["oauth_redirect_port", "cache_path"]);
+
+    cfg.oauth_redirect_port = 8080;
+    cfg.cache_path = dir.path().join("cache");
+    assert_eq!(cfg.validate(), Ok(()));
+}
+
+#[test]
+#[serial]
+fn test_env_overrides_config_file() {
+    use app_config::ConfigSource;
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("config");
+    std::fs::write(&path, "sync_interval_minutes = 10\ntheme = \"dark\"\n\n[face_detector]\nmax_faces = 3\n").unwrap();
+    let vars = [
+        ("GOOGLEPICZ_SYNC_INTERVAL_MINUTES", "15".to_string()),
+        ("GOOGLEPICZ_CACHE_PATH", dir.path().join("data").to_string_lossy().to_string()),
+        ("GOOGLEPICZ_FACE_DETECTOR__BACKEND", "onnx".to_string()),
+        ("GOOGLEPICZ_DETECT_FACES", "1".to_string()),
+    ];
+    for (key, value) in &vars {
+        std::env::set_var(key, value);
+    }
+    let cfg = AppConfig::load_from(Some(path.clone()), None);
+    std::env::set_var("GOOGLEPICZ_PRELOAD_THREADS", "lots");
+    let broken = AppConfig::load_from(Some(path), None);
+    for (key, _) in &vars {
+        std::env::remove_var(key);
+    }
+    std::env::remove_var("GOOGLEPICZ_PRELOAD_THREADS");
+
+    assert_eq!(cfg.sync_interval_minutes, 15);
+    assert_eq!(cfg.cache_path, dir.path().join("data"));
+    assert_eq!(cfg.face_detector.backend, face_recognition::DetectorBackend::Onnx);
+    assert!(cfg.detect_faces);
+    assert_eq!(cfg.validate(), Ok(()));
+    let entries = cfg.entries();
+    let entry = |key: &str| entries.iter().find(|e| e.0 == key).map(|e| (e.1.as_str(), e.2));
+    assert_eq!(entry("sync_interval_minutes"), Some(("15", ConfigSource::Env)));
+    assert_eq!(entry("theme"), Some(("dark", ConfigSource::File)));
+    assert_eq!(entry("face_detector.max_faces"), Some(("3", ConfigSource::File)));
+    assert_eq!(entry("face_detector.backend"), Some(("onnx", ConfigSource::Env)));
+    assert_eq!(entry("language"), Some(("en", ConfigSource::Default)));
+
+    // Values that do not parse keep the default and stop the app
+    assert_eq!(broken.preload_threads, 4);
+    let issues = broken.validate().unwrap_err();
+    assert_eq!(issues.len(), 1);
+    assert_eq!((issues[0].field, issues[0].value.as_str(), issues[0].fatal), ("preload_threads", "lots", true));
+    assert_eq!(issues[0].allowed, "a whole number");
+}
+
+#[test]
+#[serial]
+fn test_profiles_keep_their_own_config_and_cache() {
+    let dir = tempdir().unwrap();
+    std::env::set_var("HOME", dir.path());
+    let base = dir.path().join(".googlepicz");
+
+    let path = app_config::create_profile("work").unwrap();
+    assert_eq!(path, base.join("profiles").join("work").join("config"));
+    assert!(app_config::create_profile("work").is_err());
+    assert!(app_config::create_profile("../escape").is_err());
+    app_config::create_profile("personal").unwrap();
+    assert_eq!(app_config::list_profiles(), vec!["personal", "work"]);
+
+    let mut work = AppConfig::load_from(None, Some("work"));
+    assert_eq!(work.profile.as_deref(), Some("work"));
+    assert_eq!(work.cache_path, base.join("profiles").join("work"));
+    work.sync_interval_minutes = 30;
+    work.save_to(None).unwrap();
+    assert_eq!(AppConfig::load_from(None, Some("work")).sync_interval_minutes, 30);
+    assert_eq!(AppConfig::load_from(None, Some("personal")).sync_interval_minutes, 5);
+    assert_eq!(AppConfig::load_from(None, None).cache_path, base);
+
+    std::env::set_var(app_config::PROFILE_ENV, "personal");
+    assert_eq!(app_config::active_profile(None).as_deref(), Some("personal"));
+    assert_eq!(app_config::active_profile(Some("work")).as_deref(), Some("work"));
+    std::env::set_var(app_config::PROFILE_ENV, " ");
+    assert_eq!(app_config::active_profile(None), None);
+    std::env::remove_var(app_config::PROFILE_ENV);
+
+    let bad = AppConfig::load_from(None, Some("a/b"));
+    let issues = bad.validate().unwrap_err();
+    assert!(issues.iter().any(|i| i.field == "profile" && i.fatal));
+}
+
+#[test]
+#[serial]
+fn test_settings_validation_blocks_save() {
+    let dir = tempdir().unwrap();
+    std::env::set_var("HOME", dir.path());
+    let gp_dir = dir.path().join(".googlepicz");
+    std::fs::create_dir_all(&gp_dir).unwrap();
+
+    let (mut ui, _) = GooglePiczUI::new(UiFlags::new(gp_dir.clone()));
+    let _ = ui.update(Message::ShowSettings);
+    let _ = ui.update(Message::SettingsOauthPortChanged("99999".into()));
+    assert_eq!(
+        ui.settings_error(SettingsField::OauthPort).as_deref(),
+        Some("Enter a whole number from 1 to 65535")
+    );
+    let _ = ui.update(Message::SettingsSyncIntervalChanged("0".into()));
+    assert!(ui.settings_error(SettingsField::SyncInterval).is_some());
+    let file = gp_dir.join("not_a_dir");
+    std::fs::write(&file, "").unwrap();
+    let _ = ui.update(Message::SettingsCachePathChanged(file.join("cache").to_string_lossy().into()));
+    assert!(ui.settings_error(SettingsField::CachePath).is_some());
+
+    let _ = ui.update(Message::SettingsLogLevelChanged("debug".into()));
+    let _ = ui.update(Message::SaveSettings);
+    assert!(ui.settings_open());
+    assert_eq!(ui.errors().last().map(String::as_str), Some("Invalid OAuth port: Enter a whole number from 1 to 65535"));
+    assert_ne!(AppConfig::load_from(Some(gp_dir.join("config")), None).log_level, "debug");
+
+    let _ = ui.update(Message::SettingsOauthPortChanged("8081".into()));
+    let _ = ui.update(Message::SettingsSyncIntervalChanged("10".into()));
+    let _ = ui.update(Message::SettingsCachePathChanged(gp_dir.join("new").to_string_lossy().into()));
+    assert_eq!(ui.settings_error(SettingsField::OauthPort), None);
+    let _ = ui.update(Message::SettingsImageCacheMaxChanged("-1".into()));
+    assert!(ui.settings_error(SettingsField::ImageCacheMaxMb).is_some());
+    let _ = ui.update(Message::SettingsImageCacheMaxChanged("0".into()));
+    assert_eq!(ui.settings_error(SettingsField::ImageCacheMaxMb), None);
+    let _ = ui.update(Message::SettingsVideoCacheMaxChanged("lots".into()));
+    assert!(ui.settings_error(SettingsField::VideoCacheMaxMb).is_some());
+    let _ = ui.update(Message::SettingsVideoCacheMaxChanged("512".into()));
+    assert_eq!(ui.settings_error(SettingsField::VideoCacheMaxMb), None);
+    let _ = ui.update(Message::SaveSettings);
+    assert!(!ui.settings_open());
+    let saved = AppConfig::load_from(Some(gp_dir.join("config")), None);
+    assert_eq!(saved.oauth_redirect_port, 8081);
+    assert_eq!(saved.sync_interval_minutes, 10);
+    assert_eq!(saved.image_cache_max_mb, 0);
+    assert_eq!(saved.video_cache_max_mb, 512);
+
+    let _ = ui.update(Message::ImageCacheCleared(Ok((0, 0))));
+    assert_eq!(ui.image_cache_usage(), Some((0, 0)));
+    assert_eq!(ui.notifications().last().map(|n| n.text.as_str()), Some("Image cache cleared"));
+    let _ = ui.update(Message::VideoCacheCleared(Ok((0, 0))));
+    assert_eq!(ui.video_cache_usage(), Some((0, 0)));
+    assert_eq!(ui.notifications().last().map(|n| n.text.as_str()), Some("Video cache cleared"));
+}
+
+#[test]
+#[serial]
+fn test_error_log_viewer() {
+    let dir = tempdir().unwrap();
+    std::env::set_var("HOME", dir.path());
+    std::fs::create_dir_all(dir.path().join(".googlepicz")).unwrap();
+
+    let (mut ui, _) = GooglePiczUI::new(UiFlags::new(dir.path().join(".googlepicz")));
+    let _ = ui.update(Message::ShowSettings);
+    assert_eq!(ui.error_log(), None);
+    let _ = ui.update(Message::ToggleErrorLog);
+    let _ = ui.update(Message::ErrorLogLoaded(Ok(vec!["first".into(), "second".into()])));
+    assert_eq!(ui.error_log(), Some(&["first".to_string(), "second".to_string()][..]));
+
+    let _ = ui.update(Message::ErrorLogCleared(Ok(())));
+    assert_eq!(ui.error_log(), Some(&[][..]));
+    assert_eq!(ui.notifications().last().unwrap().text, "Error log cleared");
+
+    let _ = ui.update(Message::ToggleErrorLog);
+    let _ = ui.update(Message::ErrorLogLoaded(Ok(vec!["late".into()])));
+    assert_eq!(ui.error_log(), None);
+}
+
+#[test]
+#[serial]
+fn test_tile_size_controls_columns_and_persists() {
+    let dir = tempdir().unwrap();
+    std::env::set_var("HOME", dir.path());
+    let base = dir.path().join(".googlepicz");
+    std::fs::create_dir_all(&base).unwrap();
+
+    let (mut ui, _) = GooglePiczUI::new(UiFlags::new(base.clone()));
+    // The expanded album sidebar takes 256px of the width
+    let _ = ui.update(Message::WindowResized(1656, 900));
+    assert_eq!(ui.grid_columns(), 8);
+    let _ = ui.update(Message::TileSizeChanged(300.0));
+    assert_eq!(ui.grid_columns(), 4);
+    let _ = ui.update(Message::TileSizeChanged(5000.0));
+    assert_eq!(ui.tile_size(), ui::MAX_TILE_SIZE);
+    let _ = ui.update(Message::TileSizeChanged(200.0));
+    let _ = ui.update(Message::TileSizeReleased);
+
+    let (ui, _) = GooglePiczUI::new(UiFlags::new(base));
+    assert_eq!(ui.tile_size(), 200.0);
+}
+
+#[test]
+#[serial]
+fn test_delete_album_undo() {
+    let dir = tempdir().unwrap();
+    std::env::set_var("HOME", dir.path());
+    std::fs::create_dir_all(dir.path().join(".googlepicz")).unwrap();
+
+    let (mut ui, _) = GooglePiczUI::new(UiFlags::new(dir.path().join(".googlepicz")));
+    let album = |id: &str, title: &str| api_client::Album {
+        id: id.into(),
+        title: Some(title.into()),
+        product_url: None,
+        is_writeable: None,
+        media_items_count: None,
+        cover_photo_base_url: None,
+        cover_photo_media_item_id: None,
+    };
+    let both = vec![album("a1", "Vacation"), album("a2", "Family")];
+    let _ = ui.update(Message::AlbumsLoaded(Ok(both.clone())));
+
+    let _ = ui.update(Message::ShowDeleteAlbumDialog("a1".into()));
+    let _ = ui.update(Message::ConfirmDeleteAlbum);
+    assert!(ui.album_delete_pending("a1"));
+    assert_eq!(ui.albums().len(), 1);
+    let _ = ui.update(Message::AlbumDeleteStaged("a1".into(), Ok(vec!["m1".into()])));
+    assert_eq!(ui.notifications().len(), 1);
+    assert_eq!(ui.notifications()[0].text, "Album \"Vacation\" deleted");
+    assert!(matches!(
+        ui.notifications()[0].action.as_ref().map(|a| &a.message),
+        Some(Message::UndoDeleteAlbum(id)) if id == "a1"
+    ));
+
+    // A sync during the undo window still lists the album on the server
+    let _ = ui.update(Message::AlbumsLoaded(Ok(both.clone())));
+    assert_eq!(ui.albums().iter().map(|a| a.id.as_str()).collect::<Vec<_>>(), vec!["a2"]);
+
+    let _ = ui.update(Message::UndoDeleteAlbum("a1".into()));
+    assert!(!ui.album_delete_pending("a1"));
+    assert!(ui.notifications().is_empty());
+    assert_eq!(ui.albums().iter().map(|a| a.id.as_str()).collect::<Vec<_>>(), vec!["a1", "a2"]);
+
+    // Without undo the delete goes out once the toast expires
+    let _ = ui.update(Message::DeleteAlbum("a2".into()));
+    let _ = ui.update(Message::AlbumDeleteStaged("a2".into(), Ok(Vec::new())));
+    let _ = ui.update(Message::AlbumDeleteExpired("a2".into()));
+    assert!(!ui.album_delete_pending("a2"));
+    assert!(ui.notifications().is_empty());
+    let _ = ui.update(Message::UndoDeleteAlbum("a2".into()));
+    assert_eq!(ui.albums().len(), 1);
+}
+
+#[test]
+#[serial]
+fn test_filter_chips_clear_constraints() {
+    let dir = tempdir().unwrap();
+    std::env::set_var("HOME", dir.path());
+    std::fs::create_dir_all(dir.path().join(".googlepicz")).unwrap();
+
+    let (mut ui, _) = GooglePiczUI::new(UiFlags::new(dir.path().join(".googlepicz")));
+    assert!(ui.active_filters().is_empty());
+
+    let _ = ui.update(Message::SearchFavoriteToggled(true));
+    let _ = ui.update(Message::SearchStartChanged("2023-01-01".into()));
+    let _ = ui.update(Message::SearchEndChanged("2023-03-01".into()));
+    let _ = ui.update(Message::SearchCameraMakeChanged(Some("Canon".into())));
+    // Unparsable dates and blank text are ignored by the search, so no chip either
+    let _ = ui.update(Message::SearchCameraChanged("  ".into()));
+    let labels: Vec<_> = ui.active_filters().chips().into_iter().map(|(_, l)| l).collect();
+    assert_eq!(labels, vec!["★ Favorites", "2023-01-01 → 2023-03-01", "Canon"]);
+
+    let _ = ui.update(Message::ClearFilter(FilterKind::DateRange));
+    let filters = ui.active_filters();
+    assert!(filters.start.is_none() && filters.end.is_none());
+    assert!(filters.favorites);
+
+    // Favorites mode counts as the favorite filter and is left when it is cleared
+    let _ = ui.update(Message::SearchModeChanged(SearchMode::Favoriten));
+    let _ = ui.update(Message::SearchFavoriteToggled(false));
+    assert!(ui.active_filters().favorites);
+    let _ = ui.update(Message::ClearFilter(FilterKind::Favorites));
+    assert!(!ui.active_filters().favorites);
+    assert_eq!(ui.search_mode(), SearchMode::Filename);
+
+    let _ = ui.update(Message::SearchInputChanged("beach".into()));
+    assert_eq!(ui.active_filters().query, Some((SearchMode::Filename, "beach".into())));
+    let _ = ui.update(Message::ClearAllFilters);
+    assert!(ui.active_filters().is_empty());
+    assert_eq!(ui.search_query(), "");
+}
+
+#[test]
+#[serial]
+fn test_copy_link_and_image_notices() {
+    let dir = tempdir().unwrap();
+    std::env::set_var("HOME", dir.path());
+    std::fs::create_dir_all(dir.path().join(".googlepicz")).unwrap();
+
+    let (mut ui, _) = GooglePiczUI::new(UiFlags::new(dir.path().join(".googlepicz")));
+    // Nothing to copy outside the detail view
+    let _ = ui.update(Message::CopyLink);
+    assert!(ui.notifications().is_empty());
+
+    let _ = ui.update(Message::SelectPhoto(sample_item()));
+    let _ = ui.update(Message::CopyLink);
+    assert_eq!(ui.notifications().last().unwrap().text, "Link copied");
+
+    #[cfg(feature = "clipboard")]
+    {
+        let _ = ui.update(Message::CopyImage);
+        assert_eq!(ui.notifications().last().unwrap().text, "Image is still loading");
+        let _ = ui.update(Message::ImageCopied(Ok(false)));
+        assert_eq!(
+            ui.notifications().last().unwrap().text,
+            "Full image still loading, copied the thumbnail"
+        );
+    }
+}
+
+#[test]
+#[serial]
+fn test_full_images_bounded_least_recently_used() {
+    let dir = tempdir().unwrap();
+    std::env::set_var("HOME", dir.path());
+    std::fs::create_dir_all(dir.path().join(".googlepicz")).unwrap();
+
+    let (mut ui, _) = GooglePiczUI::new(UiFlags::new(dir.path().join(".googlepicz")));
+    let items: Vec<MediaItem> = (0..30)
+        .map(|i| MediaItem { id: i.to_string(), ..sample_item() })
+        .collect();
+    let _ = ui.update(Message::PhotosLoaded(Ok(items.clone())));
+    let _ = ui.update(Message::SelectPhoto(items[15].clone()));
+    for i in 0..30 {
+        let handle = iced::widget::image::Handle::from_memory(Vec::new());
+        let _ = ui.update(Message::FullImageLoaded(i.to_string(), Ok(handle)));
+    }
+    assert_eq!(ui.full_image_count(), 12);
+    for id in ["18", "29"] {
+        assert!(ui.has_full_image(id));
+    }
+    assert!(!ui.has_full_image("0"));
+    assert!(!ui.has_full_image("17"));
+
+    // A failed neighbour preload stays out of the error banner
+    let _ = ui.update(Message::FullImageLoaded("0".into(), Err("boom".into())));
+    assert_eq!(ui.error_count(), 0);
+
+    // Preloads finishing after the viewer closed are dropped
+    let _ = ui.update(Message::ClosePhoto);
+    let handle = iced::widget::image::Handle::from_memory(Vec::new());
+    let _ = ui.update(Message::FullImageLoaded("0".into(), Ok(handle)));
+    assert!(!ui.has_full_image("0"));
+}
+
+#[test]
+#[serial]
+fn test_album_sidebar_counts_and_favorites() {
+    let dir = tempdir().unwrap();
+    std::env::set_var("HOME", dir.path());
+    std::fs::create_dir_all(dir.path().join(".googlepicz")).unwrap();
+
+    let (mut ui, _) = GooglePiczUI::new(UiFlags::new(dir.path().join(".googlepicz")));
+    let counts = [("a1".to_string(), 3u64)].into_iter().collect();
+    let _ = ui.update(Message::AlbumCountsLoaded(Ok(counts)));
+    assert_eq!(ui.album_item_count("a1"), Some(3));
+    // A failed refresh keeps the previous counts
+    let _ = ui.update(Message::AlbumCountsLoaded(Err("locked".into())));
+    assert_eq!(ui.album_item_count("a1"), Some(3));
+    assert_eq!(ui.error_count(), 0);
+
+    let _ = ui.update(Message::SelectAlbum(Some("a1".into())));
+    let _ = ui.update(Message::SelectFavorites);
+    assert!(ui.favorites_only());
+    let _ = ui.update(Message::SelectAlbum(None));
+    assert!(!ui.favorites_only());
+}
+
+#[test]
+#[serial]
+fn test_grid_position_restored_after_photo_and_search() {
+    let dir = tempdir().unwrap();
+    std::env::set_var("HOME", dir.path());
+    let base = dir.path().join(".googlepicz");
+    std::fs::create_dir_all(&base).unwrap();
+    std::fs::write(base.join("ui_state.json"), r#"{"scroll_offset": 500}"#).unwrap();
+
+    let (mut ui, _) = GooglePiczUI::new(UiFlags::new(base));
+    let items: Vec<MediaItem> = (0..100)
+        .map(|i| MediaItem { id: i.to_string(), ..sample_item() })
+        .collect();
+    let _ = ui.update(Message::PhotosLoaded(Ok(items.clone())));
+    assert_eq!(ui.grid_offset(), 500.0);
+
+    let _ = ui.update(Message::SelectPhoto(items[7].clone()));
+    let _ = ui.update(Message::KeyPressed(iced::keyboard::KeyCode::Right));
+    let _ = ui.update(Message::EscapePressed);
+    assert_eq!(ui.state_debug(), "Grid");
+    assert_eq!(ui.grid_offset(), 500.0);
+    assert_eq!(ui.focused_index(), Some(8));
+
+    let _ = ui.update(Message::SearchInputChanged("9".into()));
+    let _ = ui.update(Message::PerformSearch);
+    let generation = ui.search_generation();
+    let hits = vec![items[9].clone(), items[8].clone()];
+    let _ = ui.update(Message::SearchResults(generation, Ok((hits, 0))));
+    assert_eq!(ui.photo_count(), 2);
+
+    let _ = ui.update(Message::ClearAllFilters);
+    let _ = ui.update(Message::PhotosLoaded(Ok(items)));
+    assert_eq!(ui.grid_offset(), 500.0);
+    assert_eq!(ui.focused_index(), Some(8));
+}
+
+#[test]
+#[serial]
+fn test_sync_panel_tracks_progress_and_controls() {
+    let dir = tempdir().unwrap();
+    std::env::set_var("HOME", dir.path());
+    std::fs::create_dir_all(dir.path().join(".googlepicz")).unwrap();
+
+    let (control_tx, mut control_rx) = tokio::sync::mpsc::unbounded_channel();
+    let (mut ui, _) = GooglePiczUI::new(UiFlags { control: Some(control_tx), ..UiFlags::new(dir.path().join(".googlepicz")) });
+    let _ = ui.update(Message::ToggleSyncPanel);
+    assert!(ui.sync_panel_open());
+
+    let _ = ui.update(Message::SyncProgress(sync::SyncProgress::Started));
+    let _ = ui.update(Message::SyncProgress(sync::SyncProgress::Progress {
+        stage: sync::SyncStage::MediaItems,
+        processed: 200,
+        estimated_total: Some(1000),
+        page: 2,
+    }));
+    assert!(ui.syncing());
+    assert_eq!(ui.sync_page_progress(), (2, 200));
+
+    let report = sync::SyncReport { fetched: 200, inserted: 150, pages: 2, ..Default::default() };
+    let _ = ui.update(Message::SyncProgress(sync::SyncProgress::Report(report.clone())));
+    assert_eq!(ui.sync_report(), Some(report));
+
+    let _ = ui.update(Message::SyncError(SyncTaskError::Other {
+        code: SyncErrorCode::Network,
+        message: "offline".into(),
+    }));
+    let _ = ui.update(Message::SyncProgress(sync::SyncProgress::Retrying(30)));
+    assert_eq!(ui.sync_last_error().as_deref(), Some("offline"));
+    assert!(matches!(ui.sync_retry_remaining(), Some(29..=30)));
+
+    let _ = ui.update(Message::PauseSync);
+    assert!(ui.sync_paused());
+    assert_eq!(control_rx.try_recv().ok(), Some(sync::SyncControl::Pause));
+
+    // Syncing now resumes and skips the pending retry
+    let _ = ui.update(Message::SyncNow);
+    assert!(!ui.sync_paused());
+    assert_eq!(ui.sync_retry_remaining(), None);
+    assert_eq!(control_rx.try_recv().ok(), Some(sync::SyncControl::SyncNow));
+
+    // Once the sync task is gone the controls report an error
+    drop(control_rx);
+    let errors = ui.error_count();
+    let _ = ui.update(Message::SyncNow);
+    assert_eq!(ui.error_count(), errors + 1);
+}
+
+#[test]
+#[serial]
+fn test_config_file_changes_apply_without_restart() {
+    let dir = tempdir().unwrap();
+    std::env::set_var("HOME", dir.path());
+    let base = dir.path().join(".googlepicz");
+    std::fs::create_dir_all(&base).unwrap();
+
+    let (control_tx, mut control_rx) = tokio::sync::mpsc::unbounded_channel();
+    let (mut ui, _) = GooglePiczUI::new(UiFlags { control: Some(control_tx), ..UiFlags::new(base.clone()) });
+    let _ = ui.update(Message::CheckConfig);
+    assert!(control_rx.try_recv().is_err());
+
+    let mut cfg = AppConfig::load_from(Some(base.join("config")), None);
+    cfg.sync_interval_minutes = 15;
+    cfg.thumbnails_preload = 40;
+    cfg.preload_threads = 8;
+    cfg.oauth_redirect_port = 9000;
+    cfg.save_to(Some(base.join("config"))).unwrap();
+    let _ = ui.update(Message::CheckConfig);
+    assert_eq!(ui.preload_count(), 40);
+    assert_eq!(ui.preload_threads(), 8);
+    assert_eq!(
+        control_rx.try_recv().ok(),
+        Some(sync::SyncControl::SetInterval(std::time::Duration::from_secs(15 * 60)))
+    );
+    assert_eq!(ui.restart_required(), ["oauth_redirect_port"]);
+
+    // Seen already, nothing is sent again
+    let _ = ui.update(Message::CheckConfig);
+    assert!(control_rx.try_recv().is_err());
+}
+
+#[test]
+#[serial]
+fn test_face_scan_progress_in_status() {
+    let dir = tempdir().unwrap();
+    std::env::set_var("HOME", dir.path());
+    std::fs::create_dir_all(dir.path().join(".googlepicz")).unwrap();
+
+    let (mut ui, _) = GooglePiczUI::new(UiFlags::new(dir.path().join(".googlepicz")));
+    let _ = ui.update(Message::SyncProgress(sync::SyncProgress::FacesScanned { processed: 12340, total: 60000 }));
+    assert_eq!(ui.face_scan_progress(), Some((12340, 60000)));
+    assert_eq!(ui.sync_status(), "Scanning faces: 12340/60000");
+
+    // A sync run keeps its own status while the scan goes on
+    let _ = ui.update(Message::SyncProgress(sync::SyncProgress::Started));
+    let _ = ui.update(Message::SyncProgress(sync::SyncProgress::FacesScanned { processed: 12344, total: 60000 }));
+    assert_eq!(ui.face_scan_progress(), Some((12344, 60000)));
+    assert_ne!(ui.sync_status(), "Scanning faces: 12344/60000");
+    let _ = ui.update(Message::SyncProgress(sync::SyncProgress::Finished(3)));
+
+    let _ = ui.update(Message::SyncProgress(sync::SyncProgress::FacesScanned { processed: 60000, total: 60000 }));
+    assert_eq!(ui.face_scan_progress(), None);
+    assert_eq!(ui.sync_status(), "Face scan completed: 60000 photos");
+}
+
+#[test]
+#[serial]
+fn test_context_menu_runs_action_and_closes() {
+    let dir = tempdir().unwrap();
+    std::env::set_var("HOME", dir.path());
+    std::fs::create_dir_all(dir.path().join(".googlepicz")).unwrap();
+
+    let (mut ui, _) = GooglePiczUI::new(UiFlags::new(dir.path().join(".googlepicz")));
+    let _ = ui.update(Message::PhotosLoaded(Ok(vec![sample_item()])));
+    let _ = ui.update(Message::OpenContextMenu("1".into()));
+    assert_eq!(ui.context_menu_target().as_deref(), Some("1"));
+    let _ = ui.update(Message::ContextMenuAlbums(true));
+    let _ = ui.update(Message::EscapePressed);
+    assert_eq!(ui.context_menu_target(), None);
+
+    let _ = ui.update(Message::OpenContextMenu("1".into()));
+    let _ = ui.update(Message::ContextMenuPicked(Box::new(Message::CopyPhotoLink("1".into()))));
+    assert_eq!(ui.context_menu_target(), None);
+    assert_eq!(ui.notifications().last().unwrap().text, "Link copied");
+
+    let _ = ui.update(Message::OpenContextMenu("1".into()));
+    let _ = ui.update(Message::ContextMenuPicked(Box::new(Message::SelectPhoto(sample_item()))));
+    assert!(ui.state_debug().starts_with("SelectedPhoto"));
+    assert_eq!(ui.context_menu_target(), None);
+}
+
+#[test]
+#[serial]
+fn test_failed_thumbnails_aggregate_and_retry() {
+    let dir = tempdir().unwrap();
+    std::env::set_var("HOME", dir.path());
+    std::fs::create_dir_all(dir.path().join(".googlepicz")).unwrap();
+
+    let (mut ui, _) = GooglePiczUI::new(UiFlags { preload: 8, ..UiFlags::new(dir.path().join(".googlepicz")) });
+    let items: Vec<MediaItem> = (1..=3)
+        .map(|i| MediaItem { id: i.to_string(), ..sample_item() })
+        .collect();
+    let _ = ui.update(Message::PhotosLoaded(Ok(items)));
+    assert_eq!(ui.thumbnail_state("1"), ThumbnailState::Loading);
+
+    let _ = ui.update(Message::ThumbnailLoaded("1".into(), Err(ImageLoaderError::Timeout)));
+    let _ = ui.update(Message::ThumbnailLoaded("2".into(), Err(ImageLoaderError::Expired)));
+    assert_eq!(ui.thumbnail_state("1"), ThumbnailState::Failed(ImageLoaderError::Timeout));
+    // Both failures share one banner entry
+    assert_eq!(ui.error_count(), 1);
+    assert!(ui.errors()[0].ends_with('2'));
+
+    let _ = ui.update(Message::RetryThumbnail("1".into()));
+    assert_eq!(ui.thumbnail_state("1"), ThumbnailState::Loading);
+    let handle = iced::widget::image::Handle::from_memory(Vec::new());
+    let _ = ui.update(Message::ThumbnailLoaded("1".into(), Ok(handle.clone())));
+    assert_eq!(ui.thumbnail_state("1"), ThumbnailState::Loaded(handle));
+
+    // Expired URLs are refreshed before the thumbnail is fetched again
+    let _ = ui.update(Message::RetryThumbnail("2".into()));
+    assert_eq!(ui.thumbnail_state("2"), ThumbnailState::Loading);
+    let _ = ui.update(Message::BaseUrlsRefreshed(vec!["2".into()], Err("offline".into())));
+    assert_eq!(ui.thumbnail_state("2"), ThumbnailState::Failed(ImageLoaderError::Network("offline".into())));
+    assert_eq!(ui.error_count(), 1);
+}
+
+#[test]
+#[serial]
+fn test_fullscreen_controls_and_escape_order() {
+    let dir = tempdir().unwrap();
+    std::env::set_var("HOME", dir.path());
+    std::fs::create_dir_all(dir.path().join(".googlepicz")).unwrap();
+
+    let (mut ui, _) = GooglePiczUI::new(UiFlags::new(dir.path().join(".googlepicz")));
+    let _ = ui.update(Message::PhotosLoaded(Ok(vec![sample_item()])));
+    // Only the viewer and the slideshow go fullscreen
+    let _ = ui.update(Message::ToggleFullscreen);
+    assert!(!ui.is_fullscreen());
+
+    let _ = ui.update(Message::SelectPhoto(sample_item()));
+    let _ = ui.update(Message::KeyPressed(iced::keyboard::KeyCode::F11));
+    assert!(ui.is_fullscreen());
+    assert_eq!(ui.fullscreen_controls_visible(), Some(true));
+
+    // A hide timer from before the latest activity is ignored
+    let _ = ui.update(Message::FullscreenActivity);
+    let _ = ui.update(Message::HideFullscreenControls(1));
+    assert_eq!(ui.fullscreen_controls_visible(), Some(true));
+    let _ = ui.update(Message::HideFullscreenControls(2));
+    assert_eq!(ui.fullscreen_controls_visible(), Some(false));
+
+    let _ = ui.update(Message::EscapePressed);
+    assert!(!ui.is_fullscreen());
+    assert!(ui.state_debug().starts_with("SelectedPhoto"));
+    let _ = ui.update(Message::EscapePressed);
+    assert_eq!(ui.state_debug(), "Grid");
+
+    let _ = ui.update(Message::StartFullscreenSlideshow);
+    assert!(ui.is_fullscreen());
+    assert_eq!(ui.slideshow_state(), Some((0, true)));
+    let _ = ui.update(Message::ClosePhoto);
+    assert!(!ui.is_fullscreen());
+    assert_eq!(ui.state_debug(), "Grid");
+}
+
+#[test]
+#[serial]
+fn test_recent_and_saved_searches() {
+    let dir = tempdir().unwrap();
+    std::env::set_var("HOME", dir.path());
+    std::fs::create_dir_all(dir.path().join(".googlepicz")).unwrap();
+
+    let (mut ui, _) = GooglePiczUI::new(UiFlags::new(dir.path().join(".googlepicz")));
+    let _ = ui.update(Message::SearchInputChanged("cat".into()));
+    let _ = ui.update(Message::PerformSearch);
+    let _ = ui.update(Message::PerformSearch);
+    assert_eq!(ui.ui_state().recent_searches.len(), 1);
+
+    // Searches run while typing are not remembered
+    let _ = ui.update(Message::SearchInputChanged("dog".into()));
+    let _ = ui.update(Message::SearchDebounced(ui.search_generation()));
+    assert_eq!(ui.ui_state().recent_searches.len(), 1);
+    let _ = ui.update(Message::PerformSearch);
+    assert_eq!(ui.ui_state().recent_searches[0].query, "dog");
+
+    let _ = ui.update(Message::PinSearch(1));
+    let _ = ui.update(Message::PinNameChanged("Cats".into()));
+    let _ = ui.update(Message::ConfirmPinSearch);
+    let saved = ui.ui_state().saved_searches.clone();
+    assert_eq!(saved.len(), 1);
+    assert_eq!(saved[0].name.as_deref(), Some("Cats"));
+
+    let _ = ui.update(Message::ClearAllFilters);
+    let _ = ui.update(Message::SelectSavedSearch(saved[0].clone()));
+    assert_eq!(ui.search_query(), "cat");
+    let queries: Vec<_> = ui.ui_state().recent_searches.iter().map(|s| s.query.clone()).collect();
+    assert_eq!(queries, ["cat", "dog"]);
+
+    for i in 0..12 {
+        let _ = ui.update(Message::SearchInputChanged(format!("q{}", i)));
+        let _ = ui.update(Message::PerformSearch);
+    }
+    assert_eq!(ui.ui_state().recent_searches.len(), 10);
+
+    // Both lists survive a restart
+    let (mut ui, _) = GooglePiczUI::new(UiFlags::new(dir.path().join(".googlepicz")));
+    assert_eq!(ui.ui_state().recent_searches.len(), 10);
+    assert_eq!(ui.ui_state().saved_searches, saved);
+    let _ = ui.update(Message::DeleteSavedSearch(0));
+    assert!(ui.ui_state().saved_searches.is_empty());
+}
+
+#[test]
+#[serial]
+fn test_dropped_files_upload_and_retry() {
+    let dir = tempdir().unwrap();
+    std::env::set_var("HOME", dir.path());
+    std::fs::create_dir_all(dir.path().join(".googlepicz")).unwrap();
+
+    let (mut ui, _) = GooglePiczUI::new(UiFlags::new(dir.path().join(".googlepicz")));
+    let photo = dir.path().join("beach.jpg");
+    let other = dir.path().join("city.png");
+    let _ = ui.update(Message::FileDropped(photo.clone()));
+    let _ = ui.update(Message::FileDropped(other.clone()));
+    let _ = ui.update(Message::FileDropped(dir.path().join("notes.txt")));
+    assert_eq!(ui.uploads().len(), 2);
+    assert_eq!(ui.uploads()[0].status, UploadStatus::Uploading);
+    assert_eq!(ui.uploads()[1].status, UploadStatus::Queued);
+    assert_eq!(ui.notifications().len(), 1);
+
+    let uploaded = MediaItem { id: "new".into(), ..sample_item() };
+    let _ = ui.update(Message::UploadFinished(photo, Ok(uploaded)));
+    assert_eq!(ui.photo_count(), 1);
+    assert_eq!(ui.uploads()[1].status, UploadStatus::Uploading);
+
+    let _ = ui.update(Message::UploadFinished(other.clone(), Err("offline".into())));
+    assert_eq!(ui.uploads()[1].status, UploadStatus::Failed("offline".into()));
+    let _ = ui.update(Message::RetryUpload(other));
+    assert_eq!(ui.uploads()[1].status, UploadStatus::Uploading);
+
+    // The list stays until every upload has finished
+    let _ = ui.update(Message::DismissUploads);
+    assert_eq!(ui.uploads().len(), 2);
+}
+
+#[test]
+#[serial]
+fn test_thumbnail_preload_is_chunked_and_cancellable() {
+    let dir = tempdir().unwrap();
+    std::env::set_var("HOME", dir.path());
+    std::fs::create_dir_all(dir.path().join(".googlepicz")).unwrap();
+
+    // One loader thread allows two thumbnails in flight
+    let (mut ui, _) = GooglePiczUI::new(UiFlags { preload: 200, preload_threads: 1, ..UiFlags::new(dir.path().join(".googlepicz")) });
+    let items: Vec<MediaItem> = (1..=200)
+        .map(|i| MediaItem { id: i.to_string(), ..sample_item() })
+        .collect();
+    let _ = ui.update(Message::PhotosLoaded(Ok(items)));
+    assert_eq!(ui.thumbnail_progress(), Some((0, 200)));
+
+    let handle = iced::widget::image::Handle::from_memory(Vec::new());
+    let _ = ui.update(Message::ThumbnailLoaded("1".into(), Ok(handle)));
+    let _ = ui.update(Message::ThumbnailLoaded("2".into(), Err(ImageLoaderError::Timeout)));
+    assert_eq!(ui.thumbnail_progress(), Some((2, 200)));
+
+    let _ = ui.update(Message::CancelThumbnailPreload);
+    assert_eq!(ui.thumbnail_state("200"), ThumbnailState::NotLoaded);
+    assert!(ui.thumbnail_progress().map_or(true, |(_, total)| total < 200));
+}
+
+#[test]
+#[serial]
+fn test_remove_from_album_confirms_and_offers_undo() {
+    let dir = tempdir().unwrap();
+    std::env::set_var("HOME", dir.path());
+    std::fs::create_dir_all(dir.path().join(".googlepicz")).unwrap();
+
+    let (mut ui, _) = GooglePiczUI::new(UiFlags::new(dir.path().join(".googlepicz")));
+    let items: Vec<MediaItem> = (1..=3)
+        .map(|i| MediaItem { id: i.to_string(), ..sample_item() })
+        .collect();
+    let _ = ui.update(Message::PhotosLoaded(Ok(items)));
+    // Nothing to remove from outside an album
+    let _ = ui.update(Message::ShowRemovePhotoDialog("1".into()));
+    assert_eq!(ui.removing_photos(), None);
+
+    let _ = ui.update(Message::SelectAlbum(Some("a1".into())));
+    let _ = ui.update(Message::ShowRemovePhotoDialog("1".into()));
+    assert_eq!(ui.removing_photos(), Some(vec!["1".to_string()]));
+    let _ = ui.update(Message::CancelRemovePhoto);
+    assert_eq!(ui.removing_photos(), None);
+
+    // A failed request leaves the tiles alone
+    let _ = ui.update(Message::PhotoRemoved("a1".into(), vec!["1".into()], Err("offline".into())));
+    assert_eq!(ui.photo_count(), 3);
+    assert_eq!(ui.error_count(), 1);
+
+    let _ = ui.update(Message::PhotoRemoved("a1".into(), vec!["1".into(), "2".into()], Ok(())));
+    assert_eq!(ui.photo_count(), 1);
+    let toast = ui.notifications().last().unwrap().clone();
+    assert_eq!(toast.text, "Removed 2 photos from album");
+    let undo = toast.action.expect("undo action").message;
+    let _ = ui.update(undo);
+    assert!(ui.notifications().iter().all(|n| n.id != toast.id));
+}
+
+#[test]
+#[serial]
+fn test_offline_mode_uses_cache_and_quiets_repeated_errors() {
+    let dir = tempdir().unwrap();
+    std::env::set_var("HOME", dir.path());
+    std::fs::create_dir_all(dir.path().join(".googlepicz")).unwrap();
+
+    let (mut ui, _) = GooglePiczUI::new(UiFlags::new(dir.path().join(".googlepicz")));
+    let album = api_client::Album {
+        id: "a1".into(),
+        title: Some("Vacation".into()),
+        product_url: None,
+        is_writeable: None,
+        media_items_count: None,
+        cover_photo_base_url: None,
+        cover_photo_media_item_id: None,
+    };
+    let _ = ui.update(Message::AlbumsLoaded(Err("Request Error: error sending request".into())));
+    assert!(ui.offline());
+    assert_eq!(ui.error_count(), 1);
+
+    let network_error = || {
+        Message::SyncError(SyncTaskError::PeriodicSyncFailed {
+            code: SyncErrorCode::Network,
+            message: "no route to host".into(),
+        })
+    };
+    let _ = ui.update(network_error());
+    let _ = ui.update(Message::ClearErrors);
+    let _ = ui.update(network_error());
+    assert_eq!(ui.error_count(), 0);
+
+    let _ = ui.update(Message::CachedAlbumsLoaded(Ok(vec![album.clone()])));
+    assert_eq!(ui.albums().len(), 1);
+    // An empty cache keeps the albums listed before going offline
+    let _ = ui.update(Message::CachedAlbumsLoaded(Ok(Vec::new())));
+    assert_eq!(ui.albums().len(), 1);
+    let _ = ui.update(Message::ShowRenameAlbumDialog("a1".into(), "Vacation".into()));
+    assert_eq!(ui.renaming_album(), None);
+
+    let _ = ui.update(Message::SyncProgress(sync::SyncProgress::Report(Default::default())));
+    assert!(!ui.offline());
+    let _ = ui.update(network_error());
+    assert_eq!(ui.error_count(), 1);
+    let _ = ui.update(Message::BatchCompleted(Err("Google API Error: not found".into())));
+    let _ = ui.update(Message::ShowRenameAlbumDialog("a1".into(), "Vacation".into()));
+    assert_eq!(ui.renaming_album(), Some("a1".into()));
+}
+
+#[test]
+#[serial]
+fn test_status_bar_tracks_connectivity_and_opens_account_settings() {
+    let dir = tempdir().unwrap();
+    std::env::set_var("HOME", dir.path());
+    std::fs::create_dir_all(dir.path().join(".googlepicz")).unwrap();
+
+    let (mut ui, _) = GooglePiczUI::new(UiFlags::new(dir.path().join(".googlepicz")));
+    assert_eq!(ui.online(), None);
+    let _ = ui.update(Message::AlbumsLoaded(Err("Request Error: error sending request".into())));
+    assert_eq!(ui.online(), Some(false));
+    // An error response still means the server was reached
+    let _ = ui.update(Message::BatchCompleted(Err("Google API Error: not found".into())));
+    assert_eq!(ui.online(), Some(true));
+
+    let network = api_client::TransferStats { bytes_down: 2048, requests: 3, ..Default::default() };
+    let snapshot = StatusSnapshot { cached: Some(42), db_size: Some(4096), token: None, network };
+    let _ = ui.update(Message::StatusBarUpdated(snapshot.clone()));
+    assert_eq!(ui.status_snapshot(), &snapshot);
+
+    let _ = ui.update(Message::ShowAccountSettings);
+    assert!(ui.settings_open());
+    assert!(ui.settings_account_open());
+    let _ = ui.update(Message::CloseSettings);
+    assert!(!ui.settings_account_open());
+}
+
+#[test]
+#[serial]
+fn test_switch_account_swaps_cache_and_is_remembered() {
+    let dir = tempdir().unwrap();
+    std::env::set_var("HOME", dir.path());
+    std::env::set_var("MOCK_KEYRING", "1");
+    std::env::remove_var(app_config::PROFILE_ENV);
+    let base = dir.path().join(".googlepicz");
+    std::fs::create_dir_all(&base).unwrap();
+    app_config::create_profile("work").unwrap();
+
+    let (control_tx, mut control_rx) = tokio::sync::mpsc::unbounded_channel();
+    let (mut ui, _) = GooglePiczUI::new(UiFlags { control: Some(control_tx), ..UiFlags::new(base.clone()) });
+    let _ = ui.update(Message::PhotosLoaded(Ok(vec![sample_item()])));
+    assert_eq!(ui.photo_count(), 1);
+
+    let _ = ui.update(Message::ToggleAccountMenu);
+    assert!(ui.account_menu_open());
+    let _ = ui.update(Message::SwitchAccount(Some("work".into())));
+    assert!(!ui.account_menu_open());
+    assert_eq!(ui.profile(), Some("work"));
+    // Auth gets the profile passed in; the process environment stays as it was
+    assert!(std::env::var(app_config::PROFILE_ENV).is_err());
+    let db = base.join("profiles").join("work").join("cache.sqlite");
+    assert_eq!(ui.cache_db_path(), db.as_path());
+    assert_eq!(ui.photo_count(), 0);
+    assert_eq!(
+        control_rx.try_recv().ok(),
+        Some(sync::SyncControl::SwitchAccount { db_path: db, profile: Some("work".into()) })
+    );
+    assert_eq!(ui::last_account().as_deref(), Some("work"));
+
+    let _ = ui.update(Message::SwitchAccount(None));
+    assert_eq!(ui.profile(), None);
+    assert_eq!(ui.cache_db_path(), base.join("cache.sqlite").as_path());
+    assert_eq!(ui::last_account(), None);
+    std::env::remove_var("MOCK_KEYRING");
+}
+
+#[test]
+#[serial]
+fn test_sign_out_clears_library_and_pauses_sync() {
+    let dir = tempdir().unwrap();
+    std::env::set_var("HOME", dir.path());
+    std::env::set_var("MOCK_KEYRING", "1");
+    let base = dir.path().join(".googlepicz");
+    std::fs::create_dir_all(&base).unwrap();
+
+    let (control_tx, mut control_rx) = tokio::sync::mpsc::unbounded_channel();
+    let (mut ui, _) = GooglePiczUI::new(UiFlags { control: Some(control_tx), ..UiFlags::new(base) });
+    let accounts = vec![ui::Account { profile: None, email: Some("me@example.com".into()) }];
+    let _ = ui.update(Message::AccountsLoaded(accounts.clone()));
+    assert_eq!(ui.accounts(), accounts.as_slice());
+    let _ = ui.update(Message::UserInfoLoaded(
+        0,
+        Ok(auth::UserInfo { email: Some("me@example.com".into()), ..Default::default() }),
+    ));
+    assert_eq!(ui.account_email().as_deref(), Some("me@example.com"));
+    let _ = ui.update(Message::PhotosLoaded(Ok(vec![sample_item()])));
+
+    let _ = ui.update(Message::SignOut);
+    assert_eq!(ui.error_count(), 0);
+    assert_eq!(ui.photo_count(), 0);
+    assert_eq!(ui.account_email(), None);
+    assert!(ui.sync_paused());
+    assert_eq!(control_rx.try_recv().ok(), Some(sync::SyncControl::Pause));
+    // Info requested before signing out is dropped
+    let _ = ui.update(Message::UserInfoLoaded(0, Ok(auth::UserInfo::default())));
+    assert_eq!(ui.account_email(), None);
+    std::env::remove_var("MOCK_KEYRING");
+}
+
+#[test]
+#[serial]
+fn test_trash_restore_and_empty() {
+    let dir = tempdir().unwrap();
+    std::env::set_var("HOME", dir.path());
+    std::fs::create_dir_all(dir.path().join(".googlepicz")).unwrap();
+
+    let (mut ui, _) = GooglePiczUI::new(UiFlags::new(dir.path().join(".googlepicz")));
+    let items: Vec<MediaItem> = (1..=3)
+        .map(|i| MediaItem { id: i.to_string(), ..sample_item() })
+        .collect();
+    let _ = ui.update(Message::PhotosLoaded(Ok(items.clone())));
+
+    let _ = ui.update(Message::PhotosTrashed(vec!["1".into(), "2".into()], Ok(())));
+    assert_eq!(ui.photo_count(), 1);
+    let toast = ui.notifications().last().unwrap().clone();
+    assert_eq!(toast.text, "Moved 2 photos to trash");
+    assert!(matches!(toast.action.map(|a| a.message), Some(Message::RestoreFromTrash(_))));
+
+    let trashed: Vec<cache::TrashedMediaItem> = items[..2]
+        .iter()
+        .map(|item| cache::TrashedMediaItem { item: item.clone(), deleted_at: chrono::Utc::now() })
+        .collect();
+    let _ = ui.update(Message::TrashLoaded(Ok(trashed)));
+    // Album contents from the API still list trashed items
+    let _ = ui.update(Message::PhotosLoaded(Ok(items)));
+    assert_eq!(ui.photo_count(), 1);
+
+    let _ = ui.update(Message::SelectTrash);
+    assert!(ui.trash_open());
+    let _ = ui.update(Message::ToggleTrashSelection("2".into()));
+    assert_eq!(ui.trash_selection(), vec!["2".to_string()]);
+
+    let _ = ui.update(Message::TrashRestored(vec!["1".into()], Ok(())));
+    assert_eq!(ui.trashed_ids(), vec!["2".to_string()]);
+    assert_eq!(ui.trash_selection(), vec!["2".to_string()]);
+
+    let _ = ui.update(Message::ShowEmptyTrashDialog);
+    assert!(ui.empty_trash_confirm_open());
+    let _ = ui.update(Message::EscapePressed);
+    assert!(!ui.empty_trash_confirm_open());
+    let _ = ui.update(Message::TrashPurged(Err("locked".into())));
+    assert_eq!(ui.error_count(), 1);
+
+    let _ = ui.update(Message::SelectAlbum(None));
+    assert!(!ui.trash_open());
+}
+
+#[test]
+#[serial]
+fn test_person_filter_chip_combines_with_other_filters() {
+    let dir = tempdir().unwrap();
+    std::env::set_var("HOME", dir.path());
+    std::fs::create_dir_all(dir.path().join(".googlepicz")).unwrap();
+
+    let (mut ui, _) = GooglePiczUI::new(UiFlags::new(dir.path().join(".googlepicz")));
+    let people = vec![cache::PersonSummary { name: "Anna".into(), photo_count: 2 }];
+    let _ = ui.update(Message::PersonOptionsLoaded(Ok(people)));
+
+    let _ = ui.update(Message::SelectAlbum(Some("vacation".into())));
+    let _ = ui.update(Message::SearchFavoriteToggled(true));
+    let _ = ui.update(Message::SearchPersonSelected("Anna".into()));
+    assert_eq!(ui.search_person().as_deref(), Some("Anna"));
+    let labels: Vec<_> = ui.active_filters().chips().into_iter().map(|(_, l)| l).collect();
+    assert_eq!(labels, vec!["In album: Untitled", "Person: Anna", "★ Favorites"]);
+    // Picking a person runs the search and remembers it
+    assert_eq!(ui.ui_state().recent_searches[0].person.as_deref(), Some("Anna"));
+
+    let _ = ui.update(Message::ClearFilter(FilterKind::Person));
+    assert_eq!(ui.search_person(), None);
+    assert!(ui.active_filters().favorites);
+
+    let saved = ui.ui_state().recent_searches[0].clone();
+    let _ = ui.update(Message::ClearAllFilters);
+    let _ = ui.update(Message::SelectSavedSearch(saved));
+    assert_eq!(ui.search_person().as_deref(), Some("Anna"));
+}
+
+#[test]
+#[serial]
+fn test_album_scope_chip_widens_search() {
+    let dir = tempdir().unwrap();
+    std::env::set_var("HOME", dir.path());
+    std::fs::create_dir_all(dir.path().join(".googlepicz")).unwrap();
+
+    let (mut ui, _) = GooglePiczUI::new(UiFlags::new(dir.path().join(".googlepicz")));
+    let album = api_client::Album {
+        id: "a1".into(),
+        title: Some("Vacation".into()),
+        product_url: None,
+        is_writeable: None,
+        media_items_count: None,
+        cover_photo_base_url: None,
+        cover_photo_media_item_id: None,
+    };
+    let _ = ui.update(Message::AlbumsLoaded(Ok(vec![album])));
+    let _ = ui.update(Message::SelectAlbum(Some("a1".into())));
+    // The album view alone is not a search
+    assert!(ui.active_filters().chips().is_empty());
+
+    let _ = ui.update(Message::SearchInputChanged("beach".into()));
+    let _ = ui.update(Message::PerformSearch);
+    assert_eq!(ui.search_album(), Some("a1"));
+    let chips = ui.active_filters().chips();
+    assert_eq!(chips[0], (FilterKind::Album, "In album: Vacation".to_string()));
+
+    // Removing the chip searches the whole library, still from the album view
+    let _ = ui.update(Message::ClearFilter(FilterKind::Album));
+    assert_eq!(ui.search_album(), None);
+    assert_eq!(ui.selected_album(), Some("a1"));
+    let _ = ui.update(Message::PerformSearch);
+    assert_eq!(ui.search_album(), None);
+
+    // A new search from the album starts inside it again
+    let _ = ui.update(Message::ClearAllFilters);
+    let _ = ui.update(Message::SearchInputChanged("beach".into()));
+    let _ = ui.update(Message::PerformSearch);
+    assert_eq!(ui.search_album(), Some("a1"));
+}
+
+#[test]
+#[serial]
+fn test_cache_opens_in_background_with_migration_progress() {
+    use chrono::TimeZone;
+    let dir = tempdir().unwrap();
+    std::env::set_var("HOME", dir.path());
+    std::fs::create_dir_all(dir.path().join(".googlepicz")).unwrap();
+
+    let (mut ui, _) = GooglePiczUI::new(UiFlags::new(dir.path().join(".googlepicz")));
+    assert_eq!(ui.cache_status(), CacheStatus::Opening);
+    assert_eq!(ui.sync_status(), "Initializing…");
+
+    let _ = ui.update(Message::CacheMigrating(2, 5));
+    assert_eq!(ui.cache_status(), CacheStatus::Migrating { step: 2, steps: 5 });
+    // A cache opened for another account is dropped
+    let _ = ui.update(Message::CacheReady(dir.path().join("other.sqlite"), Err("locked".into())));
+    assert_eq!(ui.cache_status(), CacheStatus::Migrating { step: 2, steps: 5 });
+    assert_eq!(ui.error_count(), 0);
+
+    let db_path = ui.cache_db_path().to_path_buf();
+    let cache = cache::CacheManager::new(&db_path).unwrap();
+    let ts = chrono::Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+    let opened = OpenedCache { cache, last_synced: Ok(ts) };
+    let _ = ui.update(Message::CacheReady(db_path.clone(), Ok(opened)));
+    assert_eq!(ui.cache_status(), CacheStatus::Ready);
+    assert_eq!(ui.sync_status(), format!("Last synced {}", ts.to_rfc3339()));
+    assert_eq!(ui.error_count(), 0);
+    // Progress queued behind the result does not bring the banner back
+    let _ = ui.update(Message::CacheMigrating(5, 5));
+    assert_eq!(ui.cache_status(), CacheStatus::Ready);
+
+    let (mut ui, _) = GooglePiczUI::new(UiFlags::new(dir.path().join(".googlepicz")));
+    let _ = ui.update(Message::CacheReady(db_path, Err("database is locked".into())));
+    assert_eq!(ui.cache_status(), CacheStatus::Ready);
+    assert_eq!(ui.sync_status(), "Never synced");
+    assert_eq!(ui.error_count(), 1);
+}
+
+#[test]
+#[serial]
+fn test_every_search_mode_runs_one_cache_query() {
+    let dir = tempdir().unwrap();
+    std::env::set_var("HOME", dir.path());
+    std::fs::create_dir_all(dir.path().join(".googlepicz")).unwrap();
+
+    let (mut ui, _) = GooglePiczUI::new(UiFlags::new(dir.path().join(".googlepicz")));
+    let db_path = ui.cache_db_path().to_path_buf();
+    let cache = cache::CacheManager::new(&db_path).unwrap();
+    let item = |id: &str, filename: &str, description: &str, mime: &str, created: &str| MediaItem {
+        id: id.into(),
+        description: Some(description.into()),
+        filename: filename.into(),
+        mime_type: mime.into(),
+        media_metadata: MediaMetadata { creation_time: created.into(), ..sample_item().media_metadata },
+        ..sample_item()
+    };
+    let beach = MediaItem {
+        is_favorite: true,
+        ..item("1", "beach.jpg", "Sunset at the sea", "image/jpeg", "2023-01-05T10:00:00Z")
+    };
+    let mut alps = item("2", "mountain.png", "Alps", "image/png", "2023-06-01T10:00:00Z");
+    alps.media_metadata.video = Some(api_client::VideoMetadata {
+        camera_make: Some("Canon".into()),
+        camera_model: Some("EOS R".into()),
+        fps: None,
+        status: None,
+    });
+    let party = item("3", "clip.mp4", "Beach party", "video/mp4", "2024-02-01T20:00:00Z");
+    for media in [&beach, &alps, &party] {
+        cache.insert_media_item(media).unwrap();
+    }
+    cache.insert_faces("1", r#"[{"bbox":[0,0,1,1],"name":null}]"#).unwrap();
+    let opened = OpenedCache { cache: cache.clone(), last_synced: Err("never".into()) };
+    let _ = ui.update(Message::CacheReady(db_path, Ok(opened)));
+
+    let cases = [
+        (SearchMode::Filename, "beach", 1),
+        (SearchMode::Description, "alps", 1),
+        (SearchMode::Text, "beach", 2),
+        (SearchMode::Favoriten, "", 1),
+        (SearchMode::DateRange, "2023-01-01..2023-12-31", 2),
+        (SearchMode::MimeType, "video/mp4", 1),
+        (SearchMode::CameraModel, "EOS R", 1),
+        (SearchMode::CameraMake, "Canon", 1),
+        (SearchMode::Faces, "", 1),
+    ];
+    assert_eq!(cases.len(), SearchMode::ALL.len());
+    for (mode, text, expected) in cases {
+        let _ = ui.update(Message::SearchModeChanged(mode));
+        let _ = ui.update(Message::SearchInputChanged(text.into()));
+        let generation = ui.search_generation();
+        let _ = ui.update(Message::PerformSearch);
+        assert_eq!(ui.search_generation(), generation + 1, "{:?}", mode);
+        let found = cache.query(&ui.active_filters().to_query()).unwrap();
+        assert_eq!(found.len(), expected, "{:?} {:?}", mode, text);
+        let _ = ui.update(Message::ClearAllFilters);
+    }
+
+    // An unparsable range is no filter at all, like unparsable date inputs
+    let _ = ui.update(Message::SearchModeChanged(SearchMode::DateRange));
+    let _ = ui.update(Message::SearchInputChanged("last summer".into()));
+    assert!(ui.active_filters().is_empty());
+    // The query text wins over the input for the same field
+    let _ = ui.update(Message::SearchModeChanged(SearchMode::MimeType));
+    let _ = ui.update(Message::SearchInputChanged("image/png".into()));
+    let _ = ui.update(Message::SearchMimeChanged(Some("video/mp4".into())));
+    assert_eq!(cache.query(&ui.active_filters().to_query()).unwrap()[0].id, "2");
+}
+
+#[test]
+#[serial]
+fn test_reset_local_cache_asks_first_and_empties_the_library() {
+    let dir = tempdir().unwrap();
+    std::env::set_var("HOME", dir.path());
+    std::fs::create_dir_all(dir.path().join(".googlepicz")).unwrap();
+
+    let (mut ui, _) = GooglePiczUI::new(UiFlags::new(dir.path().join(".googlepicz")));
+    // Nothing to reset before the cache is open
+    let _ = ui.update(Message::ShowResetCacheDialog);
+    assert!(!ui.reset_cache_confirm_open());
+
+    let db_path = ui.cache_db_path().to_path_buf();
+    let cache = cache::CacheManager::new(&db_path).unwrap();
+    let opened = OpenedCache { cache, last_synced: Err("never".into()) };
+    let _ = ui.update(Message::CacheReady(db_path, Ok(opened)));
+    let items: Vec<MediaItem> = (1..=3)
+        .map(|i| MediaItem { id: i.to_string(), ..sample_item() })
+        .collect();
+    let _ = ui.update(Message::PhotosLoaded(Ok(items)));
+
+    let _ = ui.update(Message::ShowResetCacheDialog);
+    assert!(ui.reset_cache_confirm_open());
+    let _ = ui.update(Message::EscapePressed);
+    assert!(!ui.reset_cache_confirm_open());
+    assert_eq!(ui.photo_count(), 3);
+
+    let _ = ui.update(Message::CacheReset(Err("database is locked".into())));
+    assert_eq!(ui.photo_count(), 3);
+    assert_eq!(ui.error_count(), 1);
+
+    let _ = ui.update(Message::CacheReset(Ok(())));
+    assert_eq!(ui.photo_count(), 0);
+    assert_eq!(ui.notifications().last().unwrap().text, "Local cache reset, syncing everything again");
+}
+
+#[test]
+#[serial]
+fn test_album_shows_cached_items_when_refresh_fails() {
+    use chrono::TimeZone;
+    let dir = tempdir().unwrap();
+    std::env::set_var("HOME", dir.path());
+    std::fs::create_dir_all(dir.path().join(".googlepicz")).unwrap();
+    std::env::set_var("MOCK_KEYRING", "1");
+    std::env::set_var("MOCK_API_CLIENT", "1");
+    std::env::set_var("MOCK_ACCESS_TOKEN", "token");
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    rt.block_on(auth::authenticate(0)).unwrap();
+
+    let (mut ui, _) = GooglePiczUI::new(UiFlags::new(dir.path().join(".googlepicz")));
+    let db_path = ui.cache_db_path().to_path_buf();
+    let cache = cache::CacheManager::new(&db_path).unwrap();
+    cache.insert_media_item(&sample_item()).unwrap();
+    cache
+        .insert_album(&api_client::Album {
+            id: "a1".into(),
+            title: Some("Trip".into()),
+            product_url: None,
+            is_writeable: None,
+            media_items_count: None,
+            cover_photo_base_url: None,
+            cover_photo_media_item_id: None,
+        })
+        .unwrap();
+    cache.associate_media_item_with_album("1", "a1").unwrap();
+    let ts = chrono::Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+    let _ = ui.update(Message::CacheReady(db_path, Ok(OpenedCache { cache: cache.clone(), last_synced: Ok(ts) })));
+
+    let _ = ui.update(Message::SelectAlbum(Some("a1".into())));
+    let _ = ui.update(Message::LoadPhotos);
+    let cached = rt.block_on(cache.get_media_items_by_album_async("a1".into())).map_err(|e| e.to_string());
+    let _ = ui.update(Message::PhotosLoaded(cached));
+    assert_eq!(ui.photo_count(), 1);
+
+    // The API refresh fails: the cached photos stay and only a toast says so
+    std::env::set_var("MOCK_API_FAIL", "1");
+    let refreshed = rt.block_on(ui::refresh_album_contents(cache.clone(), "a1".into(), None));
+    assert!(refreshed.is_err());
+    let _ = ui.update(Message::AlbumRefreshed("a1".into(), refreshed));
+    assert_eq!(ui.photo_count(), 1);
+    assert_eq!(ui.error_count(), 0);
+    assert_eq!(ui.notifications().len(), 1);
+    assert_eq!(ui.notifications()[0].level, ui::NotificationLevel::Warning);
+
+    // A successful refresh merges new items and records them in the album;
+    // "1" was removed from the album upstream and leaves grid and cache
+    std::env::remove_var("MOCK_API_FAIL");
+    let refreshed = rt.block_on(ui::refresh_album_contents(cache.clone(), "a1".into(), None));
+    let _ = ui.update(Message::AlbumRefreshed("a1".into(), refreshed));
+    assert_eq!(ui.photo_count(), 1);
+    assert_eq!(ui.photo_is_favorite("3"), Some(false));
+    assert_eq!(ui.photo_is_favorite("1"), None);
+    let in_album = cache.get_media_items_by_album("a1").unwrap();
+    assert_eq!(in_album.len(), 1);
+    assert_eq!(in_album[0].id, "3");
+    assert!(cache.get_media_item("1").unwrap().is_some());
+
+    // A refresh arriving after leaving the album is dropped
+    let _ = ui.update(Message::SelectAlbum(None));
+    let _ = ui.update(Message::PhotosLoaded(Ok(vec![sample_item()])));
+    let refreshed = rt.block_on(ui::refresh_album_contents(cache, "a1".into(), None));
+    let _ = ui.update(Message::AlbumRefreshed("a1".into(), refreshed));
+    assert_eq!(ui.photo_count(), 1);
+
+    std::env::remove_var("MOCK_ACCESS_TOKEN");
+    std::env::remove_var("MOCK_API_CLIENT");
+    std::env::remove_var("MOCK_KEYRING");
+}
+
+#[test]
+#[serial]
+fn test_all_albums_menu_filters_and_closes() {
+    let dir = tempdir().unwrap();
+    std::env::set_var("HOME", dir.path());
+    std::fs::create_dir_all(dir.path().join(".googlepicz")).unwrap();
+
+    let (mut ui, _) = GooglePiczUI::new(UiFlags::new(dir.path().join(".googlepicz")));
+    let album = |id: &str, title: &str| api_client::Album {
+        id: id.into(),
+        title: Some(title.into()),
+        product_url: None,
+        is_writeable: None,
+        media_items_count: None,
+        cover_photo_base_url: None,
+        cover_photo_media_item_id: None,
+    };
+    let albums = vec![album("a1", "Vacation"), album("a2", "family"), album("a3", "Birthday")];
+    let _ = ui.update(Message::AlbumsLoaded(Ok(albums)));
+    assert_eq!(ui.all_albums_menu(), None);
+
+    let _ = ui.update(Message::ShowAllAlbums);
+    assert_eq!(
+        ui.all_albums_menu(),
+        Some(vec!["Birthday".to_string(), "family".to_string(), "Vacation".to_string()])
+    );
+    let _ = ui.update(Message::AllAlbumsFilterChanged("FAM".into()));
+    assert_eq!(ui.all_albums_menu(), Some(vec!["family".to_string()]));
+    let _ = ui.update(Message::AllAlbumsFilterChanged("zzz".into()));
+    assert_eq!(ui.all_albums_menu(), Some(Vec::new()));
+
+    let _ = ui.update(Message::EscapePressed);
+    assert_eq!(ui.all_albums_menu(), None);
+
+    // Picking an album from the menu opens it and closes the menu
+    let _ = ui.update(Message::ShowAllAlbums);
+    let _ = ui.update(Message::SelectAlbum(Some("a3".into())));
+    assert_eq!(ui.all_albums_menu(), None);
+}